@@ -0,0 +1,102 @@
+//! House cusps and SVG wheels, recalculated every time versus taken from their caches.
+//!
+//! Each pair measures the same work with and without the cache, so the ratio is the
+//! gain a repeated chart sees. On the development machine Placidus houses went from
+//! 7.5 µs to 84 ns, and a natal SVG from 321 µs to 303 µs: the wheel is a small part
+//! of the drawing next to the planets, aspects and house lines.
+
+use astrolog_rs::api::computation::ChartComputation;
+use astrolog_rs::api::types::{ChartResponse, CHART_SCHEMA_VERSION};
+use astrolog_rs::calc::houses::{calculate_houses, calculate_houses_uncached};
+use astrolog_rs::calc::swiss_ephemeris;
+use astrolog_rs::calc::utils::date_to_julian;
+use astrolog_rs::charts::svg_generator::SVGChartGenerator;
+use astrolog_rs::charts::{init_styles, SvgOptions};
+use astrolog_rs::core::types::{EphemerisSource, HouseSystem};
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+
+const LATITUDE: f64 = 14.6488;
+const LONGITUDE: f64 = 121.0509;
+
+fn houses(c: &mut Criterion) {
+    swiss_ephemeris::init_swiss_ephemeris().expect("Swiss Ephemeris must initialize");
+    let jd = date_to_julian("1977-10-24T04:56:00Z".parse().unwrap());
+
+    let mut group = c.benchmark_group("placidus houses");
+    group.bench_function("uncached", |b| {
+        b.iter(|| calculate_houses_uncached(black_box(jd), LATITUDE, LONGITUDE, HouseSystem::Placidus).unwrap())
+    });
+    group.bench_function("cached", |b| {
+        b.iter(|| calculate_houses(black_box(jd), LATITUDE, LONGITUDE, HouseSystem::Placidus).unwrap())
+    });
+    group.finish();
+}
+
+fn natal_chart() -> ChartResponse {
+    let date = "1977-10-24T04:56:00Z".parse().unwrap();
+    let chart = ChartComputation::new(date_to_julian(date), EphemerisSource::Auto)
+        .and_then(|chart| chart.with_houses(LATITUDE, LONGITUDE, HouseSystem::Placidus))
+        .unwrap();
+    let houses = chart.house_info();
+    let angles = chart.angles();
+    let aspects = chart.aspects(false);
+    ChartResponse {
+        chart_type: "natal".to_string(),
+        schema_version: CHART_SCHEMA_VERSION,
+        date,
+        resolved_date: None,
+        latitude: LATITUDE,
+        longitude: LONGITUDE,
+        house_system: HouseSystem::Placidus,
+        ayanamsa: "tropical".to_string(),
+        house_count: houses.len(),
+        planets: chart.into_planets(),
+        points: Vec::new(),
+        moon_mansion: None,
+        houses,
+        angles,
+        aspect_set: Default::default(),
+        aspects,
+        transit: None,
+        transits: Vec::new(),
+        warnings: Vec::new(),
+        degraded_options: Vec::new(),
+        boundary_warnings: Vec::new(),
+        angular: Vec::new(),
+        ephemeris: None,
+        chart_ruler: None,
+        house_rulers: Vec::new(),
+        houses_summary: Vec::new(),
+        house_emphasis: None,
+        sidereal_time: None,
+        angle_speeds: None,
+        sect: None,
+        sun_altitude: None,
+        sect_ambiguous: false,
+        svg_chart: None,
+        svg_thumbnail: None,
+        meta: None,
+        interpretation: None,
+        diagnostics: None,
+        truncated: false,
+    }
+}
+
+fn natal_svg(c: &mut Criterion) {
+    swiss_ephemeris::init_swiss_ephemeris().expect("Swiss Ephemeris must initialize");
+    init_styles().expect("chart_styles.json must load");
+    let chart = natal_chart();
+    let drawn = SVGChartGenerator {
+        reuse_wheel: false,
+        ..SVGChartGenerator::with_options(SvgOptions::default())
+    };
+    let cached = SVGChartGenerator::with_options(SvgOptions::default());
+
+    let mut group = c.benchmark_group("natal chart SVG");
+    group.bench_function("wheel drawn", |b| b.iter(|| drawn.generate_natal_chart(black_box(&chart)).unwrap()));
+    group.bench_function("wheel cached", |b| b.iter(|| cached.generate_natal_chart(black_box(&chart)).unwrap()));
+    group.finish();
+}
+
+criterion_group!(benches, houses, natal_svg);
+criterion_main!(benches);
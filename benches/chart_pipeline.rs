@@ -0,0 +1,42 @@
+//! Natal chart with transits, without SVG rendering: the calculation half of `/api/chart`.
+//!
+//! Ephemeris lookups dominate the run time, so the gain from sharing positions shows
+//! up in allocations rather than here; see `tests/chart_computation_alloc_test.rs`.
+
+use astrolog_rs::api::computation::ChartComputation;
+use astrolog_rs::calc::swiss_ephemeris;
+use astrolog_rs::calc::utils::date_to_julian;
+use astrolog_rs::core::types::{EphemerisSource, HouseSystem};
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+
+fn natal_with_transits(c: &mut Criterion) {
+    swiss_ephemeris::init_swiss_ephemeris().expect("Swiss Ephemeris must initialize");
+    let natal_jd = date_to_julian("1977-10-24T04:56:00Z".parse().unwrap());
+    let transit_jd = date_to_julian("2024-01-02T03:00:00Z".parse().unwrap());
+
+    c.bench_function("natal chart with transits, no SVG", |b| {
+        b.iter(|| {
+            let natal = ChartComputation::new(black_box(natal_jd), EphemerisSource::Auto)
+                .and_then(|chart| chart.with_houses(14.6488, 121.0509, HouseSystem::Placidus))
+                .unwrap();
+            let transit = ChartComputation::new(black_box(transit_jd), EphemerisSource::Auto).unwrap();
+            let rulers = natal.rulers(false);
+            let houses = natal.house_info();
+            let aspects = natal.aspects(false);
+            let transit_aspects = transit.transit_aspects(false);
+            let cross_aspects = natal.cross_aspects(&transit, false);
+            black_box((
+                natal.into_planets(),
+                transit.into_planets(),
+                rulers,
+                houses,
+                aspects,
+                transit_aspects,
+                cross_aspects,
+            ))
+        })
+    });
+}
+
+criterion_group!(benches, natal_with_transits);
+criterion_main!(benches);
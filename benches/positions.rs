@@ -0,0 +1,78 @@
+//! `GET /api/positions` end to end, for a new second each time and for a
+//! second already in the cache.
+//!
+//! After the Criterion runs, the median of uncached requests is checked against
+//! `P50_TARGET`, the latency the endpoint documents, and the bench fails when it
+//! is exceeded. On the development machine an uncached request for five sidereal
+//! bodies took about 480 µs on average and 380 µs at the median, and a cached
+//! one 17 µs.
+
+use actix_web::{test, App};
+use astrolog_rs::api::server::config;
+use astrolog_rs::calc::swiss_ephemeris;
+use chrono::DateTime;
+use criterion::{criterion_group, Criterion};
+use std::time::{Duration, Instant};
+
+/// Documented median latency of an uncached request
+const P50_TARGET: Duration = Duration::from_millis(1);
+
+const SAMPLES: usize = 2000;
+
+/// A request for a second no earlier request asked for: the nth after the start of 2020
+fn uncached_uri(n: i64) -> String {
+    let date = DateTime::from_timestamp(1_577_836_800 + n, 0).unwrap();
+    format!(
+        "/api/positions?date={}&bodies=sun,moon,mercury,venus,mars&zodiac=sidereal",
+        date.format("%Y-%m-%dT%H:%M:%SZ")
+    )
+}
+
+fn positions(c: &mut Criterion) {
+    swiss_ephemeris::init_swiss_ephemeris().expect("Swiss Ephemeris must initialize");
+    let runtime = actix_rt::Runtime::new().unwrap();
+    let app = runtime.block_on(test::init_service(App::new().configure(config)));
+    let mut group = c.benchmark_group("positions");
+    let mut n = 0;
+    group.bench_function("uncached", |b| {
+        b.iter(|| {
+            n += 1;
+            let request = test::TestRequest::get().uri(&uncached_uri(n)).to_request();
+            runtime.block_on(test::call_service(&app, request))
+        })
+    });
+    group.bench_function("cached", |b| {
+        b.iter(|| {
+            let request = test::TestRequest::get().uri(&uncached_uri(0)).to_request();
+            runtime.block_on(test::call_service(&app, request))
+        })
+    });
+    group.finish();
+}
+
+/// Fails the bench when the median uncached request is slower than documented
+fn check_p50() {
+    let runtime = actix_rt::Runtime::new().unwrap();
+    let app = runtime.block_on(test::init_service(App::new().configure(config)));
+    let mut samples: Vec<Duration> = (0..SAMPLES as i64)
+        .map(|n| {
+            let request = test::TestRequest::get().uri(&uncached_uri(50_000 + n)).to_request();
+            let start = Instant::now();
+            let resp = runtime.block_on(test::call_service(&app, request));
+            assert!(resp.status().is_success());
+            start.elapsed()
+        })
+        .collect();
+    samples.sort();
+    let p50 = samples[samples.len() / 2];
+    println!("positions p50: {:?} (target {:?})", p50, P50_TARGET);
+    assert!(p50 <= P50_TARGET, "positions p50 {:?} is over the {:?} target", p50, P50_TARGET);
+}
+
+criterion_group!(benches, positions);
+
+fn main() {
+    benches();
+    Criterion::default().configure_from_args().final_summary();
+    check_p50();
+}
@@ -3,6 +3,11 @@ use std::fs;
 use std::path::Path;
 
 fn main() {
+    // Nothing to stage for pure-Rust builds without the Swiss Ephemeris
+    if env::var_os("CARGO_FEATURE_SWISS").is_none() {
+        return;
+    }
+
     // Get the home directory
     let home = env::var("HOME").expect("HOME environment variable not set");
     
@@ -13,7 +18,7 @@ fn main() {
     
     // Create directories if they don't exist
     for dir in [&swisseph_lib, &swisseph_include, &swisseph_ephe].iter() {
-        fs::create_dir_all(dir).expect(&format!("Failed to create directory: {}", dir));
+        fs::create_dir_all(dir).unwrap_or_else(|_| panic!("Failed to create directory: {}", dir));
     }
     
     // Copy Swiss Ephemeris files
@@ -31,10 +36,10 @@ fn main() {
     for entry in fs::read_dir(external_dir).expect("Failed to read external/swisseph directory") {
         let entry = entry.expect("Failed to read directory entry");
         let path = entry.path();
-        if path.extension().map_or(false, |ext| ext == "h") {
+        if path.extension().is_some_and(|ext| ext == "h") {
             let filename = path.file_name().expect("Failed to get filename");
             fs::copy(&path, Path::new(&swisseph_include).join(filename))
-                .expect(&format!("Failed to copy header file: {:?}", path));
+                .unwrap_or_else(|_| panic!("Failed to copy header file: {:?}", path));
             header_files_found = true;
         }
     }
@@ -48,10 +53,10 @@ fn main() {
     for entry in fs::read_dir(external_dir).expect("Failed to read external/swisseph directory") {
         let entry = entry.expect("Failed to read directory entry");
         let path = entry.path();
-        if path.extension().map_or(false, |ext| ext == "a") {
+        if path.extension().is_some_and(|ext| ext == "a") {
             let filename = path.file_name().expect("Failed to get filename");
             fs::copy(&path, Path::new(&swisseph_lib).join(filename))
-                .expect(&format!("Failed to copy library file: {:?}", path));
+                .unwrap_or_else(|_| panic!("Failed to copy library file: {:?}", path));
             lib_files_found = true;
         }
     }
@@ -70,7 +75,7 @@ fn main() {
             if path.is_file() {
                 let filename = path.file_name().expect("Failed to get filename");
                 fs::copy(&path, Path::new(&swisseph_ephe).join(filename))
-                    .expect(&format!("Failed to copy ephemeris file: {:?}", path));
+                    .unwrap_or_else(|_| panic!("Failed to copy ephemeris file: {:?}", path));
                 ephe_files_found = true;
             }
         }
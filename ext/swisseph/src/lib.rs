@@ -1,3 +1,9 @@
+use std::cell::Cell;
+use std::ffi::{CStr, CString};
+use std::marker::PhantomData;
+use std::os::raw::c_char;
+use std::path::Path;
+
 #[link(name = "swe", kind = "static")]
 extern "C" {
     pub fn swe_version(ver: *mut ::std::os::raw::c_char);
@@ -7,6 +13,10 @@ extern "C" {
     pub fn swe_set_topo(geolon: f64, geolat: f64, geoalt: f64);
     pub fn swe_close();
     pub fn swe_julday(year: i32, month: i32, day: i32, hour: f64, gregflag: i32) -> f64;
+    pub fn swe_deltat_ex(tjd: f64, iflag: i32, serr: *mut ::std::os::raw::c_char) -> f64;
+    pub fn swe_set_sid_mode(sid_mode: i32, t0: f64, ayan_t0: f64);
+    pub fn swe_get_ayanamsa_ut(tjd_ut: f64) -> f64;
+    pub fn swe_get_ayanamsa_ex_ut(tjd_ut: f64, iflag: i32, daya: *mut f64, serr: *mut ::std::os::raw::c_char) -> i32;
 }
 
 // Planet numbers
@@ -25,11 +35,17 @@ pub const SE_TRUE_NODE: i32 = 11;
 pub const SE_MEAN_APOG: i32 = 12;
 pub const SE_OSCU_APOG: i32 = 13;
 pub const SE_EARTH: i32 = 14;
+pub const SE_CHIRON: i32 = 15;
+pub const SE_PHOLUS: i32 = 16;
+pub const SE_CERES: i32 = 17;
+pub const SE_PALLAS: i32 = 18;
+pub const SE_JUNO: i32 = 19;
+pub const SE_VESTA: i32 = 20;
 
 // Calculation flags
+pub const SEFLG_JPLEPH: i32 = 1;
 pub const SEFLG_SWIEPH: i32 = 2;
-pub const SEFLG_JPLEPH: i32 = 4;
-pub const SEFLG_MOSEPH: i32 = 8;
+pub const SEFLG_MOSEPH: i32 = 4;
 pub const SEFLG_HELCTR: i32 = 0x0008;
 pub const SEFLG_TRUEPOS: i32 = 0x0010;
 pub const SEFLG_J2000: i32 = 0x0020;
@@ -38,17 +54,24 @@ pub const SEFLG_SPEED3: i32 = 0x0080;
 pub const SEFLG_SPEED: i32 = 0x0100;
 pub const SEFLG_NOGDEFL: i32 = 0x0200;
 pub const SEFLG_NOABERR: i32 = 0x0400;
-pub const SEFLG_AST_OFFSET: i32 = 0x0800;
-pub const SEFLG_EQUATORIAL: i32 = 0x1000;
-pub const SEFLG_XYZ: i32 = 0x2000;
-pub const SEFLG_RADIANS: i32 = 0x4000;
-pub const SEFLG_BARYCTR: i32 = 0x8000;
-pub const SEFLG_TOPOCTR: i32 = 0x10000;
-pub const SEFLG_SIDEREAL: i32 = 0x20000;
-pub const SEFLG_ICRS: i32 = 0x40000;
-pub const SEFLG_DPSIDEPS_1980: i32 = 0x80000;
-pub const SEFLG_JPLHOR: i32 = 0x100000;
-pub const SEFLG_JPLHOR_APPROX: i32 = 0x200000;
+pub const SEFLG_EQUATORIAL: i32 = 0x0800;
+pub const SEFLG_XYZ: i32 = 0x1000;
+pub const SEFLG_RADIANS: i32 = 0x2000;
+pub const SEFLG_BARYCTR: i32 = 0x4000;
+pub const SEFLG_TOPOCTR: i32 = 0x8000;
+pub const SEFLG_SIDEREAL: i32 = 0x10000;
+pub const SEFLG_ICRS: i32 = 0x20000;
+pub const SEFLG_DPSIDEPS_1980: i32 = 0x40000;
+pub const SEFLG_JPLHOR: i32 = SEFLG_DPSIDEPS_1980;
+pub const SEFLG_JPLHOR_APPROX: i32 = 0x80000;
+
+// Sidereal modes
+pub const SE_SIDM_FAGAN_BRADLEY: i32 = 0;
+pub const SE_SIDM_LAHIRI: i32 = 1;
+pub const SE_SIDM_DELUCE: i32 = 2;
+pub const SE_SIDM_RAMAN: i32 = 3;
+pub const SE_SIDM_USHASHASHI: i32 = 4;
+pub const SE_SIDM_KRISHNAMURTI: i32 = 5;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Planet {
@@ -67,6 +90,11 @@ pub enum Planet {
     MeanApogee = SE_MEAN_APOG as isize,
     OscuApogee = SE_OSCU_APOG as isize,
     Earth = SE_EARTH as isize,
+    Chiron = SE_CHIRON as isize,
+    Ceres = SE_CERES as isize,
+    Pallas = SE_PALLAS as isize,
+    Juno = SE_JUNO as isize,
+    Vesta = SE_VESTA as isize,
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -117,6 +145,88 @@ impl Flags {
         self.0 |= SEFLG_RADIANS;
         self
     }
+
+    /// Use the built-in Moshier theory instead of `.se1` files.
+    pub fn with_moshier(mut self) -> Self {
+        self.0 = (self.0 & !(SEFLG_SWIEPH | SEFLG_JPLEPH)) | SEFLG_MOSEPH;
+        self
+    }
+}
+
+/// Errors reported by the safe wrapper.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SwissError {
+    /// A string argument contained an interior NUL byte and cannot be passed to C.
+    NulByte(String),
+    /// The ephemeris path (or JPL file) does not exist on disk.
+    PathNotFound(String),
+    /// The numeric id does not correspond to a known body.
+    InvalidPlanet(i32),
+    /// The C library returned an error; the payload is its error string.
+    Calculation(String),
+}
+
+impl std::fmt::Display for SwissError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SwissError::NulByte(s) => write!(f, "string contains an interior NUL byte: {:?}", s),
+            SwissError::PathNotFound(p) => write!(f, "path does not exist: {}", p),
+            SwissError::InvalidPlanet(id) => write!(f, "invalid planet id: {}", id),
+            SwissError::Calculation(msg) => write!(f, "Swiss Ephemeris error: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for SwissError {}
+
+impl TryFrom<i32> for Planet {
+    type Error = SwissError;
+
+    fn try_from(id: i32) -> Result<Self, Self::Error> {
+        Ok(match id {
+            SE_SUN => Planet::Sun,
+            SE_MOON => Planet::Moon,
+            SE_MERCURY => Planet::Mercury,
+            SE_VENUS => Planet::Venus,
+            SE_MARS => Planet::Mars,
+            SE_JUPITER => Planet::Jupiter,
+            SE_SATURN => Planet::Saturn,
+            SE_URANUS => Planet::Uranus,
+            SE_NEPTUNE => Planet::Neptune,
+            SE_PLUTO => Planet::Pluto,
+            SE_MEAN_NODE => Planet::MeanNode,
+            SE_TRUE_NODE => Planet::TrueNode,
+            SE_MEAN_APOG => Planet::MeanApogee,
+            SE_OSCU_APOG => Planet::OscuApogee,
+            SE_EARTH => Planet::Earth,
+            SE_CHIRON => Planet::Chiron,
+            SE_CERES => Planet::Ceres,
+            SE_PALLAS => Planet::Pallas,
+            SE_JUNO => Planet::Juno,
+            SE_VESTA => Planet::Vesta,
+            _ => return Err(SwissError::InvalidPlanet(id)),
+        })
+    }
+}
+
+/// Size of the buffers handed to the C library for error and version strings.
+///
+/// The library documents AS_MAXCH (256) as the maximum, but a few code paths
+/// concatenate file names into the message, so leave generous headroom.
+const SERR_LEN: usize = 1024;
+
+/// Reads a C string out of a buffer filled by the library, forcing NUL termination
+/// so an overlong message can never run past the end of the buffer.
+fn buf_to_string(buf: &mut [c_char; SERR_LEN]) -> String {
+    buf[SERR_LEN - 1] = 0;
+    // SAFETY: the buffer is zero-initialized and its last byte is NUL.
+    unsafe { CStr::from_ptr(buf.as_ptr()) }
+        .to_string_lossy()
+        .into_owned()
+}
+
+fn to_cstring(s: &str) -> Result<CString, SwissError> {
+    CString::new(s).map_err(|_| SwissError::NulByte(s.to_string()))
 }
 
 #[derive(Debug, Clone)]
@@ -128,28 +238,59 @@ impl From<&str> for EphePath {
     }
 }
 
+/// Handle to the Swiss Ephemeris library.
+///
+/// The C library keeps its configuration (ephemeris path, open file handles,
+/// topocentric position, caches) in process-wide globals, so a handle may be
+/// moved to another thread (`Send`) but must not be used from two threads at
+/// once (`!Sync`). Share it behind a `Mutex`.
 pub struct Swisseph {
     initialized: bool,
+    _not_sync: PhantomData<Cell<()>>,
+}
+
+// SAFETY: the handle owns no thread-local state; all library state is global
+// and is only touched through `&mut self` or while the caller holds exclusive
+// access (the type is `!Sync`), so moving it between threads is sound.
+unsafe impl Send for Swisseph {}
+
+impl Default for Swisseph {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Swisseph {
     pub fn new() -> Self {
-        Self { initialized: false }
+        Self {
+            initialized: false,
+            _not_sync: PhantomData,
+        }
     }
 
-    pub fn set_ephe_path(&mut self, path: EphePath) {
-        let path = std::ffi::CString::new(path.0).unwrap();
+    /// Points the library at a directory of `.se1` files.
+    ///
+    /// Fails without touching library state if the path contains a NUL byte
+    /// or does not exist.
+    pub fn set_ephe_path(&mut self, path: EphePath) -> Result<(), SwissError> {
+        let cpath = to_cstring(&path.0)?;
+        if !Path::new(&path.0).exists() {
+            return Err(SwissError::PathNotFound(path.0));
+        }
         unsafe {
-            swe_set_ephe_path(path.as_ptr());
+            swe_set_ephe_path(cpath.as_ptr());
         }
         self.initialized = true;
+        Ok(())
     }
 
-    pub fn set_jpl_file(&mut self, fname: &str) {
-        let fname = std::ffi::CString::new(fname).unwrap();
+    /// Selects a JPL ephemeris file, relative to the ephemeris path.
+    pub fn set_jpl_file(&mut self, fname: &str) -> Result<(), SwissError> {
+        let fname = to_cstring(fname)?;
         unsafe {
             swe_set_jpl_file(fname.as_ptr());
         }
+        Ok(())
     }
 
     pub fn set_topo(&mut self, geolon: f64, geolat: f64, geoalt: f64) {
@@ -158,16 +299,60 @@ impl Swisseph {
         }
     }
 
+    /// Selects the ayanamsa `SEFLG_SIDEREAL` positions are measured with, one
+    /// of the `SE_SIDM_*` modes. The library starts on Fagan/Bradley, and
+    /// `swe_close` puts it back.
+    pub fn set_sid_mode(&self, sid_mode: i32) {
+        unsafe {
+            swe_set_sid_mode(sid_mode, 0.0, 0.0);
+        }
+    }
+
+    /// The ayanamsa of the selected sidereal mode in degrees, at a UT Julian day.
+    pub fn get_ayanamsa_ut(&self, tjd_ut: f64) -> f64 {
+        unsafe { swe_get_ayanamsa_ut(tjd_ut) }
+    }
+
+    /// Like `get_ayanamsa_ut`, but with nutation unless `flags` has
+    /// `SEFLG_NONUT`, so that a tropical position less it is the position
+    /// `SEFLG_SIDEREAL` gives. The ephemeris flag chooses the nutation model.
+    pub fn get_ayanamsa_ex_ut(&self, tjd_ut: f64, flags: Flags) -> Result<f64, String> {
+        let mut daya = 0.0f64;
+        let mut serr: [c_char; SERR_LEN] = [0; SERR_LEN];
+        let ret = unsafe { swe_get_ayanamsa_ex_ut(tjd_ut, flags.0, &mut daya, serr.as_mut_ptr()) };
+        if ret < 0 {
+            Err(buf_to_string(&mut serr))
+        } else {
+            Ok(daya)
+        }
+    }
+
     pub fn julday(&self, year: i32, month: i32, day: i32, hour: f64, gregflag: bool) -> f64 {
         unsafe {
             swe_julday(year, month, day, hour, gregflag as i32)
         }
     }
 
+    /// Delta T (TT - UT) in days for a UT Julian day, as the ephemeris selected by
+    /// `flags` applies it.
+    pub fn deltat(&self, tjd_ut: f64, flags: Flags) -> f64 {
+        // Only warnings about the tidal acceleration are reported here
+        let mut serr: [c_char; SERR_LEN] = [0; SERR_LEN];
+        unsafe { swe_deltat_ex(tjd_ut, flags.0, serr.as_mut_ptr()) }
+    }
+
     pub fn calc_ut(&self, tjd_ut: f64, planet: Planet, flags: Flags) -> Result<[f64; 6], String> {
+        self.calc_ut_flags(tjd_ut, planet, flags).map(|(xx, _)| xx)
+    }
+
+    /// Like `calc_ut`, but also returns the flags the library actually used.
+    ///
+    /// When `.se1` files are missing the library silently drops `SEFLG_SWIEPH`
+    /// and computes with `SEFLG_MOSEPH` instead; the returned flags reveal that.
+    pub fn calc_ut_flags(&self, tjd_ut: f64, planet: Planet, flags: Flags) -> Result<([f64; 6], Flags), String> {
         let mut xx = [0.0f64; 6];
-        let mut serr = [0i8; 256];
-        
+        let mut serr: [c_char; SERR_LEN] = [0; SERR_LEN];
+
         let ret = unsafe {
             swe_calc_ut(
                 tjd_ut,
@@ -179,14 +364,9 @@ impl Swisseph {
         };
 
         if ret < 0 {
-            let error = unsafe {
-                std::ffi::CStr::from_ptr(serr.as_ptr())
-                    .to_string_lossy()
-                    .into_owned()
-            };
-            Err(error)
+            Err(buf_to_string(&mut serr))
         } else {
-            Ok(xx)
+            Ok((xx, Flags(ret)))
         }
     }
 }
@@ -202,10 +382,67 @@ impl Drop for Swisseph {
 }
 
 pub fn get_version() -> String {
-    let mut buf = [0i8; 256];
+    let mut buf: [c_char; SERR_LEN] = [0; SERR_LEN];
     unsafe {
         swe_version(buf.as_mut_ptr());
-        let cstr = std::ffi::CStr::from_ptr(buf.as_ptr());
-        cstr.to_string_lossy().into_owned()
     }
-} 
\ No newline at end of file
+    buf_to_string(&mut buf)
+}
+
+// None of these reach the C library, so they also run under `cargo miri test`.
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_nonexistent_ephe_path() {
+        let mut swe = Swisseph::new();
+        let err = swe
+            .set_ephe_path(EphePath::from("/definitely/not/a/real/ephe/dir"))
+            .unwrap_err();
+        assert!(matches!(err, SwissError::PathNotFound(_)));
+        assert!(!swe.initialized);
+    }
+
+    #[test]
+    fn test_ephe_path_with_nul_byte() {
+        let mut swe = Swisseph::new();
+        let err = swe.set_ephe_path(EphePath::from("ephe\0dir")).unwrap_err();
+        assert!(matches!(err, SwissError::NulByte(_)));
+        assert!(!swe.initialized);
+
+        let err = swe.set_jpl_file("de431\0.eph").unwrap_err();
+        assert!(matches!(err, SwissError::NulByte(_)));
+    }
+
+    #[test]
+    fn test_invalid_planet_id() {
+        assert_eq!(Planet::try_from(SE_MARS), Ok(Planet::Mars));
+        assert_eq!(Planet::try_from(SE_CHIRON), Ok(Planet::Chiron));
+        assert_eq!(Planet::try_from(SE_VESTA), Ok(Planet::Vesta));
+        assert_eq!(Planet::try_from(SE_PHOLUS), Err(SwissError::InvalidPlanet(SE_PHOLUS)));
+        assert_eq!(Planet::try_from(-1), Err(SwissError::InvalidPlanet(-1)));
+        assert_eq!(Planet::try_from(9999), Err(SwissError::InvalidPlanet(9999)));
+    }
+
+    #[test]
+    fn test_buf_to_string_forces_termination() {
+        let mut buf: [c_char; SERR_LEN] = [b'x' as c_char; SERR_LEN];
+        let s = buf_to_string(&mut buf);
+        assert_eq!(s.len(), SERR_LEN - 1);
+    }
+
+    #[test]
+    fn test_moshier_flag_replaces_file_ephemeris() {
+        let flags = Flags::default().with_moshier();
+        assert_eq!(flags.0 & SEFLG_SWIEPH, 0);
+        assert_ne!(flags.0 & SEFLG_MOSEPH, 0);
+        assert_ne!(flags.0 & SEFLG_SPEED, 0);
+    }
+
+    #[test]
+    fn test_swisseph_is_send() {
+        fn assert_send<T: Send>() {}
+        assert_send::<Swisseph>();
+    }
+}
@@ -0,0 +1,460 @@
+//! Audit log of chart calculations, for working out why a chart changed.
+//!
+//! With `audit_log` set, every natal chart the server calculates appends one
+//! JSON line to the file: when it was calculated, a hash of the request, the
+//! request itself with its date resolved, the response's `meta` block and a
+//! digest of the positions and cusps. Lines go through a channel to a writer
+//! thread, so a calculation never waits for the disk; a record that finds the
+//! channel full is dropped and counted. Once the file reaches
+//! `audit_log_max_bytes` it is renamed to `<file>.1`, the older files move up
+//! one, and the oldest beyond `audit_log_files` is deleted.
+//!
+//! `astrolog-rs replay <file>` calculates each recorded request again with the
+//! current build and ephemeris files and reports the numbers that moved.
+
+use crate::api::server::calculate_natal_chart;
+use crate::api::types::{CalculationMeta, ChartRequest, ChartResponse, DateExpr};
+use crate::calc::angles::signed_delta;
+use crate::utils::fnv1a;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc::{self, Receiver, SyncSender};
+use std::sync::OnceLock;
+use std::thread;
+
+/// Size at which the audit file is rotated
+pub const DEFAULT_AUDIT_MAX_BYTES: u64 = 64 * 1024 * 1024;
+
+/// Rotated audit files kept besides the current one
+pub const DEFAULT_AUDIT_FILES: usize = 5;
+
+/// Largest difference `replay` lets pass, in arc seconds (or arc seconds a day for speeds)
+pub const DEFAULT_DRIFT_ARCSEC: f64 = 1.0;
+
+/// Records waiting for the writer thread before new ones are dropped
+const AUDIT_QUEUE_LENGTH: usize = 4096;
+
+/// One body's numbers in a `ResultDigest`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BodyDigest {
+    pub name: String,
+    pub longitude: f64,
+    pub latitude: f64,
+    pub speed: f64,
+}
+
+/// The numbers of a chart that a new build or new ephemeris files could move
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ResultDigest {
+    pub bodies: Vec<BodyDigest>,
+    /// House cusp longitudes, from the first house
+    pub cusps: Vec<f64>,
+    /// Hash of every number to six decimals, equal when nothing moved
+    pub checksum: String,
+}
+
+impl ResultDigest {
+    pub fn of(chart: &ChartResponse) -> Self {
+        let bodies: Vec<BodyDigest> = chart
+            .planets
+            .iter()
+            .map(|planet| BodyDigest {
+                name: planet.name.clone(),
+                longitude: planet.longitude,
+                latitude: planet.latitude,
+                speed: planet.speed,
+            })
+            .collect();
+        let cusps: Vec<f64> = chart.houses.iter().map(|house| house.longitude).collect();
+        let mut text = String::new();
+        for body in &bodies {
+            text.push_str(&format!("{}:{:.6},{:.6},{:.6};", body.name, body.longitude, body.latitude, body.speed));
+        }
+        for cusp in &cusps {
+            text.push_str(&format!("{:.6};", cusp));
+        }
+        Self {
+            bodies,
+            cusps,
+            checksum: format!("{:016x}", fnv1a(&text)),
+        }
+    }
+
+    /// The numbers of `replayed` more than `tolerance` degrees (or degrees a
+    /// day) from these, and bodies or cusps only one of the two has
+    pub fn drift(&self, replayed: &ResultDigest, tolerance: f64) -> Vec<Drift> {
+        let mut drift = Vec::new();
+        let mut compare = |field: String, recorded: Option<f64>, now: Option<f64>, difference: fn(f64, f64) -> f64| {
+            let difference = recorded.zip(now).map(|(recorded, now)| difference(now, recorded));
+            if difference.is_none_or(|difference| difference.abs() > tolerance) {
+                drift.push(Drift {
+                    field,
+                    recorded,
+                    replayed: now,
+                    difference,
+                });
+            }
+        };
+        let plain = |a: f64, b: f64| a - b;
+        for body in &self.bodies {
+            let now = replayed.bodies.iter().find(|other| other.name == body.name);
+            compare(format!("{} longitude", body.name), Some(body.longitude), now.map(|b| b.longitude), signed_delta);
+            compare(format!("{} latitude", body.name), Some(body.latitude), now.map(|b| b.latitude), plain);
+            compare(format!("{} speed", body.name), Some(body.speed), now.map(|b| b.speed), plain);
+        }
+        for body in replayed.bodies.iter().filter(|body| !self.bodies.iter().any(|b| b.name == body.name)) {
+            compare(format!("{} longitude", body.name), None, Some(body.longitude), signed_delta);
+        }
+        for i in 0..self.cusps.len().max(replayed.cusps.len()) {
+            compare(format!("cusp {}", i + 1), self.cusps.get(i).copied(), replayed.cusps.get(i).copied(), signed_delta);
+        }
+        drift
+    }
+}
+
+/// A number that came out differently on replay; `None` where the recorded or
+/// replayed chart doesn't have it
+#[derive(Debug, Clone, PartialEq)]
+pub struct Drift {
+    /// e.g. "Moon longitude" or "cusp 10"
+    pub field: String,
+    pub recorded: Option<f64>,
+    pub replayed: Option<f64>,
+    /// `replayed` less `recorded`, across 0° for longitudes
+    pub difference: Option<f64>,
+}
+
+/// One line of the audit log
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuditRecord {
+    pub timestamp: DateTime<Utc>,
+    /// FNV-1a of `request` as JSON, the same for the same request on any build
+    pub request_hash: String,
+    /// Label of the handler, e.g. "natal"
+    pub endpoint: String,
+    /// The request as received, with its date resolved to the moment calculated
+    pub request: ChartRequest,
+    pub meta: Option<CalculationMeta>,
+    pub digest: ResultDigest,
+}
+
+impl AuditRecord {
+    /// Records `chart`, calculated for `request` at `date`
+    pub fn new(endpoint: &str, request: &ChartRequest, date: DateTime<Utc>, chart: &ChartResponse) -> Self {
+        let request = ChartRequest {
+            date: DateExpr::At(date),
+            ..request.clone()
+        };
+        let canonical = serde_json::to_string(&request).unwrap_or_default();
+        Self {
+            timestamp: Utc::now(),
+            request_hash: format!("{:016x}", fnv1a(&canonical)),
+            endpoint: endpoint.to_string(),
+            request,
+            meta: chart.meta.clone(),
+            digest: ResultDigest::of(chart),
+        }
+    }
+
+    /// Calculates the request again and compares the result with the record,
+    /// letting differences up to `tolerance_arcsec` pass
+    pub fn replay(&self, tolerance_arcsec: f64) -> Result<Vec<Drift>, String> {
+        let chart = calculate_natal_chart(self.request.clone())?;
+        Ok(self.digest.drift(&ResultDigest::of(&chart), tolerance_arcsec / 3600.0))
+    }
+}
+
+/// The records in the text of an audit file, each with its line number;
+/// blank lines are skipped
+pub fn parse_records(text: &str) -> Vec<(usize, Result<AuditRecord, String>)> {
+    text.lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(i, line)| (i + 1, serde_json::from_str(line).map_err(|e| e.to_string())))
+        .collect()
+}
+
+/// `path` with `.n` appended, e.g. "audit.jsonl.2"
+pub fn rotated_path(path: &Path, n: usize) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(format!(".{}", n));
+    PathBuf::from(name)
+}
+
+/// An append-only file that is rotated before a line would take it past
+/// `max_bytes`, keeping `files` rotated copies
+pub struct RotatingFile {
+    path: PathBuf,
+    max_bytes: u64,
+    files: usize,
+    file: BufWriter<File>,
+    written: u64,
+}
+
+impl RotatingFile {
+    /// Opens `path` for appending, counting what it already holds
+    pub fn open(path: impl Into<PathBuf>, max_bytes: u64, files: usize) -> Result<Self, String> {
+        let path = path.into();
+        let file = open_append(&path).map_err(|e| format!("{}: {}", path.display(), e))?;
+        let written = file.metadata().map(|m| m.len()).unwrap_or(0);
+        Ok(Self {
+            path,
+            max_bytes: max_bytes.max(1),
+            files: files.max(1),
+            file: BufWriter::new(file),
+            written,
+        })
+    }
+
+    /// Appends `line` and a newline. A line longer than `max_bytes` still gets
+    /// a file of its own rather than being split.
+    pub fn write_line(&mut self, line: &str) -> io::Result<()> {
+        let size = line.len() as u64 + 1;
+        if self.written > 0 && self.written + size > self.max_bytes {
+            self.rotate()?;
+        }
+        self.file.write_all(line.as_bytes())?;
+        self.file.write_all(b"\n")?;
+        self.written += size;
+        Ok(())
+    }
+
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.file.flush()
+    }
+
+    fn rotate(&mut self) -> io::Result<()> {
+        self.file.flush()?;
+        let oldest = rotated_path(&self.path, self.files);
+        if oldest.exists() {
+            fs::remove_file(&oldest)?;
+        }
+        for n in (1..self.files).rev() {
+            let from = rotated_path(&self.path, n);
+            if from.exists() {
+                fs::rename(&from, rotated_path(&self.path, n + 1))?;
+            }
+        }
+        fs::rename(&self.path, rotated_path(&self.path, 1))?;
+        self.file = BufWriter::new(open_append(&self.path)?);
+        self.written = 0;
+        Ok(())
+    }
+}
+
+fn open_append(path: &Path) -> io::Result<File> {
+    OpenOptions::new().create(true).append(true).open(path)
+}
+
+enum AuditMessage {
+    Line(String),
+    /// Flush, then answer
+    Flush(mpsc::Sender<()>),
+}
+
+/// Hands records to a writer thread that owns the file
+pub struct AuditLog {
+    sender: SyncSender<AuditMessage>,
+    dropped: AtomicU64,
+}
+
+impl AuditLog {
+    /// Opens the file and starts its writer thread
+    pub fn open(path: impl Into<PathBuf>, max_bytes: u64, files: usize) -> Result<Self, String> {
+        let mut file = RotatingFile::open(path, max_bytes, files)?;
+        let (sender, receiver) = mpsc::sync_channel(AUDIT_QUEUE_LENGTH);
+        thread::Builder::new()
+            .name("audit-log".to_string())
+            .spawn(move || write_records(&mut file, receiver))
+            .map_err(|e| format!("Can't start the audit log writer: {}", e))?;
+        Ok(Self {
+            sender,
+            dropped: AtomicU64::new(0),
+        })
+    }
+
+    /// Queues the record without waiting; it is dropped if the queue is full
+    pub fn append(&self, record: &AuditRecord) {
+        let queued = serde_json::to_string(record)
+            .ok()
+            .is_some_and(|line| self.sender.try_send(AuditMessage::Line(line)).is_ok());
+        if !queued {
+            self.dropped.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    /// Records dropped because the writer was behind
+    pub fn dropped(&self) -> u64 {
+        self.dropped.load(Ordering::Relaxed)
+    }
+
+    /// Waits until the records appended so far are in the file
+    pub fn flush(&self) {
+        let (done, wait) = mpsc::channel();
+        if self.sender.send(AuditMessage::Flush(done)).is_ok() {
+            let _ = wait.recv();
+        }
+    }
+}
+
+/// Writes lines as they come, flushing whenever the queue runs empty
+fn write_records(file: &mut RotatingFile, receiver: Receiver<AuditMessage>) {
+    while let Ok(message) = receiver.recv() {
+        let mut next = Some(message);
+        while let Some(message) = next {
+            match message {
+                AuditMessage::Line(line) => {
+                    if let Err(e) = file.write_line(&line) {
+                        log::warn!("Audit log write failed: {}", e);
+                    }
+                }
+                AuditMessage::Flush(done) => {
+                    if let Err(e) = file.flush() {
+                        log::warn!("Audit log flush failed: {}", e);
+                    }
+                    let _ = done.send(());
+                }
+            }
+            next = receiver.try_recv().ok();
+        }
+        if let Err(e) = file.flush() {
+            log::warn!("Audit log flush failed: {}", e);
+        }
+    }
+}
+
+static AUDIT_LOG: OnceLock<AuditLog> = OnceLock::new();
+
+/// Starts recording calculations to `path`; later calls keep the first log
+pub fn init_audit_log(path: &str, max_bytes: u64, files: usize) -> Result<(), String> {
+    if AUDIT_LOG.get().is_none() {
+        let _ = AUDIT_LOG.set(AuditLog::open(path, max_bytes, files)?);
+    }
+    Ok(())
+}
+
+/// The server's audit log, when `init_audit_log` has been called
+pub fn audit_log() -> Option<&'static AuditLog> {
+    AUDIT_LOG.get()
+}
+
+/// Appends a record of the chart to the audit log, if there is one
+pub fn record_calculation(endpoint: &str, request: &ChartRequest, date: DateTime<Utc>, chart: &ChartResponse) {
+    if let Some(log) = audit_log() {
+        log.append(&AuditRecord::new(endpoint, request, date, chart));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::calc::swiss_ephemeris;
+
+    fn scratch_path(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("astrolog-audit-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir.join("audit.jsonl")
+    }
+
+    #[test]
+    fn test_file_rotates_at_its_size_and_keeps_the_newest() {
+        let path = scratch_path("rotation");
+        let line = "x".repeat(99);
+        // Three 100-byte lines to a file, two rotated files kept
+        let log = AuditLog::open(&path, 300, 2).unwrap();
+        for i in 0..10 {
+            log.sender.send(AuditMessage::Line(format!("{}{}", i, &line[1..]))).unwrap();
+        }
+        log.flush();
+
+        let read = |path: &Path| fs::read_to_string(path).unwrap();
+        let first_digits = |text: String| text.lines().map(|l| l[..1].to_string()).collect::<Vec<_>>().join("");
+        assert_eq!(first_digits(read(&path)), "9");
+        assert_eq!(first_digits(read(&rotated_path(&path, 1))), "678");
+        assert_eq!(first_digits(read(&rotated_path(&path, 2))), "345");
+        assert!(!rotated_path(&path, 3).exists());
+        assert!(read(&rotated_path(&path, 1)).len() as u64 <= 300);
+
+        // Reopening appends after what the file already holds
+        drop(log);
+        let mut file = RotatingFile::open(&path, 300, 2).unwrap();
+        file.write_line(&line).unwrap();
+        file.write_line(&line).unwrap();
+        file.flush().unwrap();
+        assert_eq!(read(&path).lines().count(), 3);
+        assert_eq!(dropped_by_a_stopped_writer(), 1);
+    }
+
+    /// A log whose writer thread has stopped counts what it couldn't queue
+    fn dropped_by_a_stopped_writer() -> u64 {
+        let (sender, receiver) = mpsc::sync_channel(1);
+        drop(receiver);
+        let log = AuditLog {
+            sender,
+            dropped: AtomicU64::new(0),
+        };
+        let record = AuditRecord {
+            timestamp: Utc::now(),
+            request_hash: String::new(),
+            endpoint: "natal".to_string(),
+            request: ChartRequest::default(),
+            meta: None,
+            digest: ResultDigest {
+                bodies: Vec::new(),
+                cusps: Vec::new(),
+                checksum: String::new(),
+            },
+        };
+        log.append(&record);
+        log.dropped()
+    }
+
+    #[test]
+    fn test_replay_finds_injected_drift() {
+        swiss_ephemeris::init_moshier_ephemeris().unwrap();
+        let request: ChartRequest = serde_json::from_value(serde_json::json!({
+            "date": "1990-05-15T14:30:00Z",
+            "latitude": 40.7128,
+            "longitude": -74.0060,
+            "house_system": "placidus",
+            "ayanamsa": "tropical",
+            "ephemeris": "moshier"
+        }))
+        .unwrap();
+        let chart = calculate_natal_chart(request.clone()).unwrap();
+        let date = chart.date;
+        let record = AuditRecord::new("natal", &request, date, &chart);
+        assert_eq!(record.request.date, DateExpr::At(date));
+        assert_eq!(record.digest.cusps.len(), 12);
+
+        // Through the file and back, unchanged, nothing has moved
+        let path = scratch_path("replay");
+        let log = AuditLog::open(&path, DEFAULT_AUDIT_MAX_BYTES, DEFAULT_AUDIT_FILES).unwrap();
+        log.append(&record);
+        let mut drifted = record.clone();
+        let moon = drifted.digest.bodies.iter_mut().find(|b| b.name == "Moon").unwrap();
+        moon.longitude += 0.01;
+        drifted.digest.cusps[9] -= 0.5 / 3600.0;
+        log.append(&drifted);
+        log.flush();
+        let records = parse_records(&fs::read_to_string(&path).unwrap());
+        assert_eq!(records.len(), 2);
+        let (_, recorded) = &records[0];
+        let recorded = recorded.as_ref().unwrap();
+        assert_eq!(recorded.request_hash, record.request_hash);
+        assert_eq!(recorded.replay(DEFAULT_DRIFT_ARCSEC).unwrap(), Vec::new());
+
+        // The Moon 36" out is reported; the Midheaven half an arc second out isn't
+        let (_, drifted) = &records[1];
+        let drift = drifted.as_ref().unwrap().replay(DEFAULT_DRIFT_ARCSEC).unwrap();
+        assert_eq!(drift.len(), 1, "{:?}", drift);
+        assert_eq!(drift[0].field, "Moon longitude");
+        assert!((drift[0].difference.unwrap() + 0.01).abs() < 1e-9, "{:?}", drift[0]);
+        assert_eq!(drifted.as_ref().unwrap().replay(0.1).unwrap().len(), 2);
+    }
+}
@@ -0,0 +1,104 @@
+//! The `GET /api/capabilities` document, built from the same tables the request
+//! validators read, so a new house system or body shows up here without listing
+//! it twice
+
+use crate::api::types::{
+    AspectTypeInfo, CapabilitiesResponse, CapabilityInfo, FeaturesInfo, HouseSystemInfo, LimitsInfo, MotionRangeInfo,
+    ZodiacInfo, MAX_CYCLE_RANGE_DAYS, MAX_TRANSIT_SERIES, RENDER_FORMATS,
+};
+use crate::calc::aspects::{get_aspect_types, MAX_CUSTOM_ASPECTS, MAX_CUSTOM_ORB};
+use crate::calc::ephemeris::Ephemeris;
+use crate::calc::planets::{Planet, DAILY_MOTION_RANGES};
+use crate::calc::positions::{body_positions, Center, Zodiac, POSITION_BODIES, UNSUPPORTED_ZODIACS};
+use crate::core::types::HouseSystem;
+
+/// What this deployment supports. Each house system, zodiac and body is tried
+/// on the ephemeris at `jd`, houses at the equator where every system is
+/// defined, so one that needs missing files or the Swiss library is listed as
+/// unavailable with the error a chart would get.
+pub fn capabilities(ephemeris: &dyn Ephemeris, jd: f64) -> CapabilitiesResponse {
+    let source = ephemeris.default_source();
+    let backend = ephemeris.capabilities();
+    let major = get_aspect_types(false);
+
+    CapabilitiesResponse {
+        house_systems: HouseSystem::ALL
+            .into_iter()
+            .map(|system| HouseSystemInfo {
+                capability: CapabilityInfo::checked(
+                    system.key().to_string(),
+                    ephemeris.houses(jd, 0.0, 0.0, system).map(|_| ()),
+                ),
+                code: system.code(),
+            })
+            .collect(),
+        zodiacs: Zodiac::ALL
+            .into_iter()
+            .map(|zodiac| ZodiacInfo {
+                capability: CapabilityInfo::checked(
+                    zodiac.key().to_string(),
+                    body_positions(jd, &[Planet::Mars], zodiac, Center::default(), source).map(|_| ()),
+                ),
+                ayanamsa: zodiac.ayanamsa().map(str::to_string),
+                center: zodiac.center().map(|center| center.key().to_string()),
+            })
+            .chain(UNSUPPORTED_ZODIACS.into_iter().map(|(name, reason)| ZodiacInfo {
+                capability: CapabilityInfo::checked(name.to_string(), Err(reason)),
+                ayanamsa: None,
+                center: None,
+            }))
+            .collect(),
+        bodies: POSITION_BODIES
+            .into_iter()
+            .map(|planet| CapabilityInfo::checked(planet, ephemeris.position(jd, planet, source).map(|_| ())))
+            .collect(),
+        aspects: get_aspect_types(true)
+            .into_iter()
+            .map(|aspect_type| AspectTypeInfo {
+                name: aspect_type.to_string(),
+                angle: aspect_type.angle(),
+                orb: aspect_type.orb(),
+                transit_orb: aspect_type.transit_orb(),
+                minor: !major.contains(&aspect_type),
+            })
+            .collect(),
+        motion_ranges: DAILY_MOTION_RANGES
+            .into_iter()
+            .map(|(body, min_speed, max_speed)| MotionRangeInfo {
+                body,
+                min_speed,
+                max_speed,
+            })
+            .collect(),
+        render_formats: RENDER_FORMATS
+            .into_iter()
+            .map(|(name, reason)| CapabilityInfo::checked(name.to_string(), reason.map_or(Ok(()), Err)))
+            .collect(),
+        limits: LimitsInfo {
+            max_transit_series: MAX_TRANSIT_SERIES,
+            max_chart_bodies: POSITION_BODIES.len(),
+            max_custom_aspects: MAX_CUSTOM_ASPECTS,
+            max_custom_orb: MAX_CUSTOM_ORB,
+            max_cycle_range_days: MAX_CYCLE_RANGE_DAYS,
+        },
+        features: FeaturesInfo {
+            swiss_ephemeris: cfg!(feature = "swiss"),
+            ephemeris_files: ephemeris_files(),
+            svg: cfg!(feature = "svg"),
+            default_ephemeris: source,
+            ephemeris_sources: backend.sources,
+            topocentric: backend.topocentric,
+            sidereal: backend.sidereal,
+        },
+    }
+}
+
+#[cfg(feature = "swiss")]
+fn ephemeris_files() -> bool {
+    crate::calc::swiss_ephemeris::ephemeris_files_available()
+}
+
+#[cfg(not(feature = "swiss"))]
+fn ephemeris_files() -> bool {
+    false
+}
@@ -0,0 +1,1068 @@
+//! Shared calculation pipeline behind the chart handlers
+
+use crate::api::types::{
+    default_extra_bodies, unsupported_options_message, AngleSpeedInfo, AnglePointInfo, AnglesInfo, AspectInfo,
+    BodyWarningInfo, BoundaryWarningInfo, CalculationFlags, CalculationMeta, ChartRequest, ChartResponse, ChartRulerInfo,
+    CHART_SCHEMA_VERSION, HouseEmphasisInfo, HouseInfo, HouseRulerInfo, HouseSummaryInfo, LunarNodesInfo, MansionInfo, PlanetInfo,
+    SynastryAspectInfo, TransitHouseInfo,
+};
+use crate::calc::angles::{normalize, signed_delta};
+use crate::calc::aspects::{
+    aspect_defs, body_points, calculate_body_aspects_with_defs, calculate_cross_body_aspects_with_defs,
+    calculate_point_aspects_with_defs, days_to_exact, synastry_aspects_with_defs, Aspect, AspectDef,
+    AspectFilter, AspectSet, ChartPoint, OrbConfig,
+};
+use crate::calc::boundaries::boundary_warnings;
+use crate::calc::coordinates::{equatorial_of_date, local_sidereal_time, CoordinateSystem, EquatorialPosition};
+use crate::calc::ephemeris::{default_ephemeris, Ephemeris};
+use crate::calc::house_summary::{house_emphasis, house_occupancy};
+use crate::calc::houses::{house_of, Houses};
+use crate::calc::nodes::{lunar_nodes_from, south_node};
+use crate::calc::planets::{calculate_body_positions_from, check_daily_motion, BodyFailure, Planet, PlanetPosition};
+use crate::calc::pluto;
+use crate::calc::points::{angular_conjunctions, house_angle_points, point_defs, AnglePoint};
+use crate::calc::precession::precess_longitude;
+use crate::calc::rulers::{chart_ruler, house_rulers, solar_condition, Sign};
+use crate::calc::sect::{chart_sect, ChartSect};
+use crate::calc::subdivisions::{decan, mansion, MansionSystem};
+use crate::calc::transit_houses::transit_house;
+#[cfg(feature = "swiss")]
+use crate::calc::swiss_ephemeris;
+use crate::calc::utils::julian_to_date;
+use crate::core::types::{EphemerisSource, HouseSystem, NodeType};
+use crate::core::AstrologError;
+use crate::data::i18n::Lang;
+use crate::io::astrolog_config::defaults as astrolog_defaults;
+use chrono::{DateTime, Utc};
+use std::cell::OnceCell;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+/// Bodies included in every chart, in response order
+pub const CHART_PLANETS: [Planet; 10] = [
+    Planet::Sun,
+    Planet::Moon,
+    Planet::Mercury,
+    Planet::Venus,
+    Planet::Mars,
+    Planet::Jupiter,
+    Planet::Saturn,
+    Planet::Uranus,
+    Planet::Neptune,
+    Planet::Pluto,
+];
+
+/// Bodies without which there's no chart to draw
+pub const REQUIRED_PLANETS: [Planet; 2] = [Planet::Sun, Planet::Moon];
+
+/// Seconds either side of the chart's moment the angles are calculated at to
+/// find their speed
+const ANGLE_SPEED_STEP_SECONDS: f64 = 30.0;
+
+static NEXT_CALCULATION_ID: AtomicU64 = AtomicU64::new(1);
+
+/// Positions calculated with a speed outside the body's plausible range
+static MOTION_ANOMALIES: AtomicU64 = AtomicU64::new(0);
+
+/// How many positions have had an implausible speed since the server started
+pub fn motion_anomaly_count() -> u64 {
+    MOTION_ANOMALIES.load(Ordering::Relaxed)
+}
+
+/// Describes how a chart was calculated. Every handler builds its `meta` here.
+pub fn calculation_meta(jd: f64, source: EphemerisSource, house_system: Option<HouseSystem>) -> CalculationMeta {
+    let (swiss_ephemeris_version, delta_t_seconds) = swiss_details(jd, source);
+    CalculationMeta {
+        calculation_id: NEXT_CALCULATION_ID.fetch_add(1, Ordering::Relaxed),
+        crate_version: env!("CARGO_PKG_VERSION").to_string(),
+        swiss_ephemeris_version,
+        ephemeris: source.to_string(),
+        precision_arcsec: source.precision_arcsec(),
+        // Charts are tropical and geocentric; `ChartComputation::meta` sets the
+        // node choice, the zodiac and whether the positions are topocentric
+        flags: CalculationFlags {
+            sidereal: false,
+            topocentric: false,
+            true_node: false,
+        },
+        delta_t_seconds,
+        house_system_used: house_system,
+        house_system_code: house_system.map(|system| system.code()),
+        warnings: precision_warnings(jd, source),
+        lunar_nodes: None,
+        svg_bytes: None,
+        locale: Lang::En.to_string(),
+    }
+}
+
+/// Bodies whose analytic theory doesn't cover `jd`
+fn precision_warnings(jd: f64, source: EphemerisSource) -> Vec<String> {
+    match source {
+        EphemerisSource::Analytic => pluto::precision_warning(jd).into_iter().collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Bodies moving faster or slower than they ever plausibly do
+fn motion_warnings(bodies: &[Planet], positions: &[PlanetPosition]) -> Vec<String> {
+    bodies
+        .iter()
+        .zip(positions)
+        .filter_map(|(&planet, position)| check_daily_motion(planet, position).err())
+        .collect()
+}
+
+/// Library version and the Delta T it applies at `jd`
+#[cfg(feature = "swiss")]
+fn swiss_details(jd: f64, source: EphemerisSource) -> (Option<String>, Option<f64>) {
+    (
+        Some(swiss_ephemeris::library_version().to_string()),
+        swiss_ephemeris::delta_t_seconds(jd, source).ok(),
+    )
+}
+
+/// The analytic theory takes UT as TT, so no Delta T is applied
+#[cfg(not(feature = "swiss"))]
+fn swiss_details(_jd: f64, _source: EphemerisSource) -> (Option<String>, Option<f64>) {
+    (None, None)
+}
+
+/// Positions, and optionally houses, for one moment.
+///
+/// Everything a response needs is derived from the same position slice: the aspect
+/// routines borrow it, and the `PlanetInfo` view is built on first use. Bodies
+/// whose positions fail are left out and listed in `failures`, so the slice can
+/// be shorter than the bodies asked for.
+pub struct ChartComputation {
+    pub jd: f64,
+    /// The bodies calculated, parallel to `positions`
+    pub bodies: Vec<Planet>,
+    pub positions: Vec<PlanetPosition>,
+    /// Bodies left out because their positions couldn't be calculated
+    pub failures: Vec<BodyFailure>,
+    /// Ephemeris that actually produced the positions
+    pub source: EphemerisSource,
+    /// Where the positions and houses come from
+    ephemeris: Arc<dyn Ephemeris>,
+    houses: Option<Houses>,
+    house_system: Option<HouseSystem>,
+    /// Request-defined aspects looked for alongside the built-in ones
+    pub custom_aspects: Vec<AspectDef>,
+    /// Orbs of the built-in aspects at natal orbs
+    orbs: OrbConfig,
+    /// Angle points reported and aspected alongside the planets, once there are houses
+    points: Vec<AnglePoint>,
+    /// Whether the Ascendant and Midheaven are aspected like the angle points
+    angle_aspects: bool,
+    /// Whether each planet is given its decan
+    decans: bool,
+    /// Mansions each planet is placed in, if any
+    mansions: Option<MansionSystem>,
+    /// Whether each planet is given its right ascension and declination
+    coordinates: CoordinateSystem,
+    /// Language of each planet's formatted position
+    lang: Lang,
+    planets: OnceCell<Vec<PlanetInfo>>,
+}
+
+impl ChartComputation {
+    /// Calculates the chart planets for a Julian date, failing only if the Sun
+    /// or Moon can't be calculated
+    pub fn new(jd: f64, source: EphemerisSource) -> Result<Self, AstrologError> {
+        Self::for_bodies(jd, source, &CHART_PLANETS, &REQUIRED_PLANETS)
+    }
+
+    /// Calculates `bodies` for a Julian date, failing only if one of `required`
+    /// can't be calculated
+    pub fn for_bodies(
+        jd: f64,
+        source: EphemerisSource,
+        bodies: &[Planet],
+        required: &[Planet],
+    ) -> Result<Self, AstrologError> {
+        Self::from_ephemeris(default_ephemeris(), jd, source, bodies, required)
+    }
+
+    /// Calculates `bodies` like `for_bodies`, from a given ephemeris. Its houses
+    /// and lunar nodes come from the same ephemeris.
+    pub fn from_ephemeris(
+        ephemeris: Arc<dyn Ephemeris>,
+        jd: f64,
+        source: EphemerisSource,
+        bodies: &[Planet],
+        required: &[Planet],
+    ) -> Result<Self, AstrologError> {
+        let calculated = calculate_body_positions_from(&*ephemeris, jd, bodies, required, source)?;
+        let anomalies = motion_warnings(&calculated.bodies, &calculated.positions);
+        for warning in &anomalies {
+            log::warn!("Implausible position at JD {}: {}", jd, warning);
+        }
+        MOTION_ANOMALIES.fetch_add(anomalies.len() as u64, Ordering::Relaxed);
+        Ok(Self {
+            jd,
+            bodies: calculated.bodies,
+            positions: calculated.positions,
+            failures: calculated.failures,
+            source: calculated.source,
+            ephemeris,
+            houses: None,
+            house_system: None,
+            custom_aspects: Vec::new(),
+            orbs: OrbConfig::natal(),
+            points: Vec::new(),
+            angle_aspects: false,
+            decans: false,
+            mansions: None,
+            coordinates: CoordinateSystem::Ecliptic,
+            lang: Lang::En,
+            planets: OnceCell::new(),
+        })
+    }
+
+    /// Also looks for these aspects, at the orbs they define, in every aspect list
+    /// this chart calculates
+    pub fn with_custom_aspects(mut self, custom_aspects: Vec<AspectDef>) -> Self {
+        self.custom_aspects = custom_aspects;
+        self
+    }
+
+    /// Looks for the built-in aspects at these orbs wherever natal orbs are used
+    pub fn with_orbs(mut self, orbs: OrbConfig) -> Self {
+        self.orbs = orbs;
+        self
+    }
+
+    /// Also reports these angle points and includes them, at orbs of at most
+    /// `POINT_ORB`, in every aspect list. They need houses to be calculated.
+    pub fn with_points(mut self, points: Vec<AnglePoint>) -> Self {
+        self.points = points;
+        self
+    }
+
+    /// Also aspects the Ascendant and Midheaven, as the angle points are, when
+    /// `angle_aspects` is set. They need houses to be calculated.
+    pub fn with_angle_aspects(mut self, angle_aspects: bool) -> Self {
+        self.angle_aspects = angle_aspects;
+        self
+    }
+
+    /// Also gives each planet its decan, and its lunar mansion in `mansions`
+    pub fn with_subdivisions(mut self, decans: bool, mansions: Option<MansionSystem>) -> Self {
+        self.decans = decans;
+        self.mansions = mansions;
+        self.planets = OnceCell::new();
+        self
+    }
+
+    /// Also gives each planet its right ascension and declination, unless
+    /// `coordinates` is ecliptic only
+    pub fn with_coordinates(mut self, coordinates: CoordinateSystem) -> Self {
+        self.coordinates = coordinates;
+        self.planets = OnceCell::new();
+        self
+    }
+
+    /// Formats each planet's position, and reports the locale in `meta`, in `lang`
+    pub fn with_lang(mut self, lang: Lang) -> Self {
+        self.lang = lang;
+        self.planets = OnceCell::new();
+        self
+    }
+
+    /// Calculates houses for a place, placing each planet in its house
+    pub fn with_houses(
+        mut self,
+        latitude: f64,
+        longitude: f64,
+        house_system: HouseSystem,
+    ) -> Result<Self, AstrologError> {
+        self.houses = Some(self.ephemeris.houses(self.jd, latitude, longitude, house_system)?);
+        self.house_system = Some(house_system);
+        self.planets = OnceCell::new();
+        Ok(self)
+    }
+
+    /// This chart's positions carried forward by the general precession to the
+    /// equinox of `jd`, for comparing with positions on that date. The copy has
+    /// no houses, and so no angle points. A sidereal chart's positions are
+    /// measured from the stars already and are copied as they are.
+    pub fn precessed_to(&self, jd: f64) -> ChartComputation {
+        let sidereal = self.ephemeris.sidereal_ayanamsa().is_some();
+        let positions = self
+            .positions
+            .iter()
+            .map(|position| PlanetPosition {
+                longitude: if sidereal {
+                    position.longitude
+                } else {
+                    precess_longitude(position.longitude, self.jd, jd)
+                },
+                ..*position
+            })
+            .collect();
+        Self {
+            jd: self.jd,
+            bodies: self.bodies.clone(),
+            positions,
+            failures: self.failures.clone(),
+            source: self.source,
+            ephemeris: Arc::clone(&self.ephemeris),
+            houses: None,
+            house_system: None,
+            custom_aspects: self.custom_aspects.clone(),
+            orbs: self.orbs.clone(),
+            points: Vec::new(),
+            angle_aspects: false,
+            decans: false,
+            mansions: None,
+            coordinates: CoordinateSystem::Ecliptic,
+            lang: self.lang,
+            planets: OnceCell::new(),
+        }
+    }
+
+    /// Language the positions are formatted in
+    pub fn lang(&self) -> Lang {
+        self.lang
+    }
+
+    /// The ephemeris the chart was calculated from
+    pub fn ephemeris(&self) -> Arc<dyn Ephemeris> {
+        Arc::clone(&self.ephemeris)
+    }
+
+    pub fn houses(&self) -> Option<&Houses> {
+        self.houses.as_ref()
+    }
+
+    /// Position of one body, if it was calculated
+    pub fn position_of(&self, planet: Planet) -> Option<&PlanetPosition> {
+        let index = self.bodies.iter().position(|&body| body == planet)?;
+        self.positions.get(index)
+    }
+
+    /// The chart's sect at the given place, from the Sun's position, if it was calculated
+    pub fn sect(&self, latitude: f64, longitude: f64, twilight: f64) -> Option<ChartSect> {
+        let sun = self.position_of(Planet::Sun)?;
+        let sun_longitude = self.tropical(sun.longitude).ok()?;
+        Some(chart_sect(self.jd, latitude, longitude, sun_longitude, sun.latitude, twilight))
+    }
+
+    /// A longitude of this chart measured from the equinox, as the sky's
+    /// coordinates on the equator and horizon are found from
+    fn tropical(&self, longitude: f64) -> Result<f64, String> {
+        match self.ephemeris.sidereal_ayanamsa() {
+            Some(ayanamsa) => Ok(normalize(longitude + self.ephemeris.ayanamsa(self.jd, ayanamsa, self.source)?)),
+            None => Ok(longitude),
+        }
+    }
+
+    /// How fast the Ascendant and Midheaven move at the given place, from the
+    /// angles half a minute either side of the chart's moment; `None` without houses
+    pub fn angle_speeds(&self, latitude: f64, longitude: f64) -> Result<Option<AngleSpeedInfo>, AstrologError> {
+        let Some(system) = self.house_system else {
+            return Ok(None);
+        };
+        let step = ANGLE_SPEED_STEP_SECONDS / 86400.0;
+        let before = self.ephemeris.houses(self.jd - step, latitude, longitude, system)?;
+        let after = self.ephemeris.houses(self.jd + step, latitude, longitude, system)?;
+        let minutes = 2.0 * ANGLE_SPEED_STEP_SECONDS / 60.0;
+        let ascendant = signed_delta(after.ascendant, before.ascendant) / minutes;
+        Ok(Some(AngleSpeedInfo {
+            ascendant,
+            midheaven: signed_delta(after.midheaven, before.midheaven) / minutes,
+            ascendant_minutes_per_degree: 1.0 / ascendant.abs(),
+        }))
+    }
+
+    /// The bodies as named chart points
+    pub fn body_points(&self) -> Vec<ChartPoint> {
+        body_points(&self.bodies, &self.positions)
+    }
+
+    /// The bodies left out, for the response's `warnings`
+    pub fn warnings(&self) -> Vec<BodyWarningInfo> {
+        self.failures.iter().map(BodyWarningInfo::from).collect()
+    }
+
+    /// How this chart was calculated, under a new calculation id, with the lunar
+    /// nodes of the chosen type
+    pub fn meta(&self, node_type: NodeType) -> Result<CalculationMeta, AstrologError> {
+        let mut meta = calculation_meta(self.jd, self.source, self.house_system);
+        meta.warnings.extend(motion_warnings(&self.bodies, &self.positions));
+        let nodes = lunar_nodes_from(&*self.ephemeris, self.jd, self.source).map_err(|message| AstrologError::CalculationError { message })?;
+        let north_node = nodes.north_node(node_type);
+        meta.flags.true_node = node_type == NodeType::True;
+        meta.flags.topocentric = self.ephemeris.observer().is_some();
+        meta.flags.sidereal = self.ephemeris.sidereal_ayanamsa().is_some();
+        meta.locale = self.lang.to_string();
+        meta.lunar_nodes = Some(LunarNodesInfo {
+            node_type,
+            north_node,
+            south_node: south_node(north_node),
+            true_node: nodes.true_node,
+            mean_node: nodes.mean_node,
+        });
+        Ok(meta)
+    }
+
+    /// Named planets with their house placements, built on first use
+    pub fn planets(&self) -> &[PlanetInfo] {
+        self.planets.get_or_init(|| self.build_planets())
+    }
+
+    /// Named planets with their house placements, reusing the cached view if built
+    pub fn into_planets(mut self) -> Vec<PlanetInfo> {
+        match self.planets.take() {
+            Some(planets) => planets,
+            None => self.build_planets(),
+        }
+    }
+
+    /// Named planets placed in another chart's houses, as transits are read
+    /// against the natal houses. Without twelve cusps in that chart, as
+    /// `into_planets` gives them.
+    pub fn into_planets_in(self, natal: &ChartComputation) -> Vec<PlanetInfo> {
+        let mut planets = self.into_planets();
+        if let Some(cusps) = natal.houses.as_ref().and_then(Houses::cusp_longitudes) {
+            for planet in &mut planets {
+                let placement = house_of(planet.longitude, &cusps);
+                planet.house = Some(placement.house);
+                planet.house_fraction = Some(placement.fraction);
+            }
+        }
+        planets
+    }
+
+    fn build_planets(&self) -> Vec<PlanetInfo> {
+        let cusps = self.houses.as_ref().and_then(Houses::cusp_longitudes);
+        self.bodies
+            .iter()
+            .zip(&self.positions)
+            .map(|(planet, pos)| {
+                let placement = cusps.as_ref().map(|cusps| house_of(pos.longitude, cusps));
+                PlanetInfo {
+                    name: planet.to_string(),
+                    longitude: pos.longitude,
+                    latitude: pos.latitude,
+                    speed: pos.speed,
+                    is_retrograde: pos.is_retrograde,
+                    motion: pos.motion,
+                    formatted_position: self.lang.format_position(pos.longitude),
+                    house: placement.map(|p| p.house).or(pos.house),
+                    house_fraction: placement.map(|p| p.fraction),
+                    decan: self.decans.then(|| decan(pos.longitude).into()),
+                    mansion: self.mansions.map(|system| mansion(pos.longitude, system).into()),
+                    equatorial: self.coordinates.includes_equatorial().then(|| self.equatorial_of(*planet, pos).into()),
+                }
+            })
+            .collect()
+    }
+
+    // A body's right ascension and declination from the ephemeris, or from its
+    // ecliptic position when the ephemeris can't give them
+    fn equatorial_of(&self, planet: Planet, position: &PlanetPosition) -> EquatorialPosition {
+        self.ephemeris
+            .equatorial(self.jd, planet, self.source)
+            .unwrap_or_else(|_| {
+                let longitude = self.tropical(position.longitude).unwrap_or(position.longitude);
+                equatorial_of_date(self.jd, longitude, position.latitude)
+            })
+    }
+
+    /// The Moon's lunar mansion, when mansions were asked for
+    pub fn moon_mansion(&self) -> Option<MansionInfo> {
+        let system = self.mansions?;
+        let moon = self.position_of(Planet::Moon)?;
+        Some(mansion(moon.longitude, system).into())
+    }
+
+    /// The angle points as named chart points, after the Ascendant and
+    /// Midheaven when they are aspected too; empty when houses weren't calculated
+    pub fn angle_points(&self) -> Vec<ChartPoint> {
+        let Some(houses) = &self.houses else {
+            return Vec::new();
+        };
+        let mut points = Vec::with_capacity(self.points.len() + 2);
+        if self.angle_aspects {
+            points.extend(
+                house_angle_points(houses)
+                    .into_iter()
+                    .filter(|point| matches!(point.name.as_str(), "Ascendant" | "Midheaven")),
+            );
+        }
+        points.extend(self.points.iter().map(|point| point.chart_point(houses)));
+        points
+    }
+
+    /// The chart's angles for the response, `None` when houses weren't calculated
+    pub fn angles(&self) -> Option<AnglesInfo> {
+        self.houses.as_ref().map(AnglesInfo::from)
+    }
+
+    /// The angle points for the response, placed in their houses
+    pub fn point_info(&self) -> Vec<AnglePointInfo> {
+        let Some(houses) = &self.houses else {
+            return Vec::new();
+        };
+        let cusps = houses.cusp_longitudes();
+        self.points
+            .iter()
+            .map(|&point| {
+                let longitude = point.longitude(houses);
+                AnglePointInfo {
+                    name: point.to_string(),
+                    longitude,
+                    label: point.label().to_string(),
+                    house: cusps.as_ref().map(|cusps| house_of(longitude, cusps).house),
+                }
+            })
+            .collect()
+    }
+
+    /// House cusps for the response, empty when houses weren't calculated
+    pub fn house_info(&self) -> Vec<HouseInfo> {
+        self.houses
+            .iter()
+            .flat_map(|houses| &houses.cusps)
+            .map(|h| HouseInfo {
+                number: h.number,
+                longitude: h.longitude,
+                latitude: h.latitude,
+            })
+            .collect()
+    }
+
+    /// Identifies the chart ruler and the ruler of each house
+    pub fn rulers(&self, modern: bool) -> (Option<ChartRulerInfo>, Vec<HouseRulerInfo>) {
+        let Some((houses, cusps)) = self
+            .houses
+            .as_ref()
+            .and_then(|houses| houses.cusp_longitudes().map(|cusps| (houses, cusps)))
+        else {
+            return (None, Vec::new());
+        };
+        let planets = self.planets();
+        let find = |planet: Planet| {
+            self.bodies
+                .iter()
+                .position(|&p| p == planet)
+                .and_then(|i| planets.get(i))
+        };
+
+        let bodies: Vec<(Planet, f64)> = self
+            .bodies
+            .iter()
+            .zip(planets)
+            .map(|(&planet, info)| (planet, info.longitude))
+            .collect();
+        let house_ruler_info = house_rulers(&cusps, &bodies, modern)
+            .into_iter()
+            .map(|r| HouseRulerInfo {
+                house: r.house,
+                sign: r.sign.to_string(),
+                ruler: r.ruler.to_string(),
+                ruler_house: r.ruler_house,
+            })
+            .collect();
+
+        let ruler = chart_ruler(houses.ascendant, modern);
+        let sun = find(Planet::Sun);
+        let chart_ruler_info = find(ruler).map(|info| ChartRulerInfo {
+            sign: Sign::from_longitude(houses.ascendant).to_string(),
+            planet: info.clone(),
+            house: info.house,
+            is_retrograde: info.is_retrograde,
+            solar_condition: match (ruler, sun) {
+                (Planet::Sun, _) | (_, None) => None,
+                (_, Some(sun)) => Some(solar_condition(info.longitude, sun.longitude)),
+            },
+        });
+
+        (chart_ruler_info, house_ruler_info)
+    }
+
+    /// Lists the planets in each house and counts them by kind of house
+    pub fn houses_summary(&self) -> (Vec<HouseSummaryInfo>, Option<HouseEmphasisInfo>) {
+        let Some(cusps) = self.houses.as_ref().and_then(Houses::cusp_longitudes) else {
+            return (Vec::new(), None);
+        };
+        let points = self.body_points();
+        let occupancy = house_occupancy(&points, &cusps);
+        let emphasis = house_emphasis(&occupancy);
+        (
+            occupancy.into_iter().map(HouseSummaryInfo::from).collect(),
+            Some(emphasis.into()),
+        )
+    }
+
+    /// Bodies within `sign_orb` of a sign boundary, and within `house_orb` of a
+    /// house cusp when the chart has twelve cusps
+    pub fn boundary_warnings(&self, sign_orb: f64, house_orb: f64) -> Vec<BoundaryWarningInfo> {
+        let cusps = self.houses.as_ref().and_then(Houses::cusp_longitudes);
+        boundary_warnings(&self.body_points(), cusps.as_ref(), sign_orb, house_orb)
+            .into_iter()
+            .map(BoundaryWarningInfo::from)
+            .collect()
+    }
+
+    /// Bodies within `ANGULAR_ORB` of the Ascendant, Descendant, Midheaven or
+    /// IC, tightest first; empty without houses
+    pub fn angular(&self) -> Vec<AspectInfo> {
+        let Some(houses) = &self.houses else {
+            return Vec::new();
+        };
+        angular_conjunctions(&self.body_points(), houses)
+            .into_iter()
+            .map(AspectInfo::from)
+            .collect()
+    }
+
+    /// Built-in aspects at this chart's natal orbs or at transit orbs, then its
+    /// custom aspects
+    fn aspect_defs(&self, include_minor_aspects: bool, use_transit_orbs: bool) -> Vec<AspectDef> {
+        let mut defs = aspect_defs(include_minor_aspects, use_transit_orbs, &self.custom_aspects);
+        if !use_transit_orbs {
+            for def in &mut defs {
+                if let Some(aspect_type) = def.aspect_type {
+                    def.orb = self.orbs.orb(aspect_type);
+                }
+            }
+        }
+        defs
+    }
+
+    /// Aspects from the planets to the angle points, at orbs capped at `POINT_ORB`
+    fn point_aspects(&self, include_minor_aspects: bool, use_transit_orbs: bool) -> Vec<AspectInfo> {
+        let points = self.angle_points();
+        if points.is_empty() {
+            return Vec::new();
+        }
+        calculate_point_aspects_with_defs(
+            &self.body_points(),
+            &points,
+            &point_defs(&self.aspect_defs(include_minor_aspects, use_transit_orbs)),
+        )
+        .into_iter()
+        .map(AspectInfo::from)
+        .collect()
+    }
+
+    /// Aspects within this chart at natal orbs, the angle points' last
+    pub fn aspects(&self, include_minor_aspects: bool) -> Vec<AspectInfo> {
+        calculate_body_aspects_with_defs(&self.bodies, &self.positions, &self.aspect_defs(include_minor_aspects, false))
+            .into_iter()
+            .map(AspectInfo::from)
+            .chain(self.point_aspects(include_minor_aspects, false))
+            .collect()
+    }
+
+    /// Aspects within this chart at natal orbs, with `exact_in_days` filled in for
+    /// the applying ones
+    pub fn aspects_with_exact_times(&self, include_minor_aspects: bool) -> Result<Vec<AspectInfo>, AstrologError> {
+        let planets = self.planets();
+        let body = |name: &str| {
+            let index = planets.iter().position(|p| p.name == name)?;
+            Some((self.bodies[index], &self.positions[index]))
+        };
+        let defs = self.aspect_defs(include_minor_aspects, false);
+        calculate_body_aspects_with_defs(&self.bodies, &self.positions, &defs)
+            .into_iter()
+            .map(|aspect| {
+                let angle = defs.iter().find(|def| def.name == aspect.name).map(|def| def.angle);
+                let exact_in_days = match (body(&aspect.planet1), body(&aspect.planet2), angle) {
+                    (Some((body1, position1)), Some((body2, position2)), Some(angle)) => days_to_exact(
+                        self.jd,
+                        body1,
+                        position1,
+                        body2,
+                        position2,
+                        angle,
+                        self.source,
+                    )
+                    .map_err(|message| AstrologError::CalculationError { message })?,
+                    _ => None,
+                };
+                Ok(AspectInfo {
+                    exact_in_days,
+                    ..aspect.into()
+                })
+            })
+            // The points turn with the sky, so they have no exact time to report
+            .chain(self.point_aspects(include_minor_aspects, false).into_iter().map(Ok))
+            .collect()
+    }
+
+    /// Aspects within this chart at the tighter transit orbs
+    pub fn transit_aspects(&self, include_minor_aspects: bool) -> Vec<AspectInfo> {
+        calculate_body_aspects_with_defs(&self.bodies, &self.positions, &self.aspect_defs(include_minor_aspects, true))
+            .into_iter()
+            .map(AspectInfo::from)
+            .chain(self.point_aspects(include_minor_aspects, true))
+            .collect()
+    }
+
+    /// Aspects from this (natal) chart's planets, then its angle points, to a
+    /// transit chart's planets
+    pub fn cross_aspects(&self, transit: &ChartComputation, include_minor_aspects: bool) -> Vec<AspectInfo> {
+        let defs = self.aspect_defs(include_minor_aspects, true);
+        let mut aspects = calculate_cross_body_aspects_with_defs(
+            &self.bodies,
+            &self.positions,
+            &transit.bodies,
+            &transit.positions,
+            &defs,
+        );
+        let points = self.angle_points();
+        if !points.is_empty() {
+            let tagged = |tag: &str, points: Vec<ChartPoint>| -> Vec<ChartPoint> {
+                points
+                    .into_iter()
+                    .map(|point| ChartPoint {
+                        name: format!("{} {}", tag, point.name),
+                        ..point
+                    })
+                    .collect()
+            };
+            aspects.extend(calculate_point_aspects_with_defs(
+                &tagged("Natal", points),
+                &tagged("Transit", transit.body_points()),
+                &point_defs(&defs),
+            ));
+        }
+        aspects.into_iter().map(|aspect| transit.natal_aspect_info(aspect)).collect()
+    }
+
+    /// A transit-to-natal aspect, from this transit chart to a natal chart,
+    /// with `exact_at` estimated from this chart's moment
+    fn natal_aspect_info(&self, aspect: Aspect) -> AspectInfo {
+        let exact_at = aspect.exact_in.and_then(|days| julian_to_date(self.jd + days));
+        AspectInfo {
+            exact_at,
+            ..aspect.into()
+        }
+    }
+
+    /// Aspects from this (natal) chart's angles and house cusps to a transit
+    /// chart's planets, at the transit orbs capped at `POINT_ORB`. Empty when
+    /// houses weren't calculated.
+    pub fn angle_aspects(&self, transit: &ChartComputation, include_minor_aspects: bool) -> Vec<AspectInfo> {
+        let Some(houses) = &self.houses else {
+            return Vec::new();
+        };
+        let tagged = |tag: &str, points: Vec<ChartPoint>| -> Vec<ChartPoint> {
+            points
+                .into_iter()
+                .map(|point| ChartPoint {
+                    name: format!("{} {}", tag, point.name),
+                    ..point
+                })
+                .collect()
+        };
+        calculate_point_aspects_with_defs(
+            &tagged("Natal", house_angle_points(houses)),
+            &tagged("Transit", transit.body_points()),
+            &point_defs(&self.aspect_defs(include_minor_aspects, true)),
+        )
+        .into_iter()
+        .map(|aspect| transit.natal_aspect_info(aspect))
+        .collect()
+    }
+
+    /// The natal house each of a transit chart's planets is in, and when it
+    /// entered it. Needs this chart's twelve house cusps.
+    pub fn transit_houses(&self, transit: &ChartComputation) -> Result<Vec<TransitHouseInfo>, String> {
+        let cusps = self
+            .houses
+            .as_ref()
+            .and_then(Houses::cusp_longitudes)
+            .ok_or("Transit houses need a natal chart with twelve houses")?;
+        transit
+            .bodies
+            .iter()
+            .map(|&planet| {
+                let placed = transit_house(planet, transit.jd, &cusps, transit.source)?;
+                let entered = placed
+                    .entered
+                    .map(|jd| julian_to_date(jd).ok_or_else(|| format!("Date out of range: JD {}", jd)))
+                    .transpose()?;
+                Ok(TransitHouseInfo {
+                    planet: planet.to_string(),
+                    house: placed.house,
+                    entered,
+                    retrograde: placed.retrograde,
+                })
+            })
+            .collect()
+    }
+
+    /// Aspects from this chart's planets (person1) to another chart's (person2)
+    /// at natal orbs, looking for this chart's custom aspects. Contacts with
+    /// either chart's angle points use orbs capped at `POINT_ORB`; the list is
+    /// sorted by orb either way.
+    pub fn synastry_aspects(&self, other: &ChartComputation, include_minor_aspects: bool) -> Vec<SynastryAspectInfo> {
+        self.synastry_side(include_minor_aspects).aspects_to(other)
+    }
+
+    /// What `synastry_aspects` takes from this chart, to compare it with many
+    /// others without recalculating it
+    pub fn synastry_side(&self, include_minor_aspects: bool) -> SynastrySide {
+        SynastrySide {
+            defs: self.aspect_defs(include_minor_aspects, false),
+            bodies: self.body_points(),
+            points: self.angle_points(),
+        }
+    }
+}
+
+/// The person1 chart of a synastry: its aspect definitions, bodies and angle points
+#[derive(Debug, Clone)]
+pub struct SynastrySide {
+    defs: Vec<AspectDef>,
+    bodies: Vec<ChartPoint>,
+    points: Vec<ChartPoint>,
+}
+
+impl SynastrySide {
+    /// As `ChartComputation::synastry_aspects` from this side's chart
+    pub fn aspects_to(&self, other: &ChartComputation) -> Vec<SynastryAspectInfo> {
+        let bodies2 = other.body_points();
+        let mut aspects = synastry_aspects_with_defs(&self.bodies, &bodies2, &self.defs);
+        let points2 = other.angle_points();
+        if !self.points.is_empty() || !points2.is_empty() {
+            let defs = point_defs(&self.defs);
+            let all1: Vec<ChartPoint> = self.bodies.iter().chain(&self.points).cloned().collect();
+            aspects.extend(synastry_aspects_with_defs(&all1, &points2, &defs));
+            aspects.extend(synastry_aspects_with_defs(&self.points, &bodies2, &defs));
+            aspects.sort_by(|a, b| a.orb.total_cmp(&b.orb));
+        }
+        aspects.into_iter().map(SynastryAspectInfo::from).collect()
+    }
+}
+
+/// What a natal chart is calculated for and what it includes, other than its
+/// moment. `from_request` reads these off a `ChartRequest`.
+#[derive(Debug, Clone)]
+pub struct NatalChartOptions {
+    pub latitude: f64,
+    pub longitude: f64,
+    pub house_system: HouseSystem,
+    /// Reported as the chart's zodiac; the ephemeris decides the longitudes
+    pub ayanamsa: String,
+    pub source: EphemerisSource,
+    pub bodies: Vec<Planet>,
+    /// Bodies whose failure fails the chart rather than leaving them out
+    pub required_bodies: Vec<Planet>,
+    pub orbs: OrbConfig,
+    pub custom_aspects: Vec<AspectDef>,
+    pub points: Vec<AnglePoint>,
+    pub include_minor_aspects: bool,
+    pub include_angle_aspects: bool,
+    pub include_exact_times: bool,
+    pub aspect_filter: AspectFilter,
+    pub include_decans: bool,
+    pub mansion_system: Option<MansionSystem>,
+    pub coordinates: CoordinateSystem,
+    pub lang: Lang,
+    pub modern_rulers: bool,
+    pub sign_boundary_orb: f64,
+    pub house_cusp_orb: f64,
+    pub sect_twilight: f64,
+    pub include_angle_speeds: bool,
+    pub node_type: NodeType,
+}
+
+impl NatalChartOptions {
+    /// The options of a chart request, positions coming from `source` and
+    /// formatted in `lang`. Natal aspects take the `astrolog.as` defaults' orbs
+    /// if there are any.
+    pub fn from_request(req: &ChartRequest, source: EphemerisSource, lang: Lang) -> Result<Self, String> {
+        // Checked in the order the endpoints always have, so a request with more
+        // than one mistake is told about the same one
+        let node_type = req.node_type.as_deref().map(str::parse).transpose()?.unwrap_or_default();
+        let aspect_filter = req.aspect_filter()?;
+        let custom_aspects = req.custom_aspects()?;
+        let points = req.aspect_points()?;
+        let mansion_system = req.mansion_system()?;
+        let coordinates = req.coordinate_system()?;
+        let (bodies, required_bodies) = req.bodies()?;
+        let (sign_boundary_orb, house_cusp_orb) = req.boundary_orbs()?;
+        let sect_twilight = req.sect_twilight()?;
+        Ok(Self {
+            latitude: req.latitude,
+            longitude: req.longitude,
+            house_system: req.house_system,
+            ayanamsa: req.ayanamsa.clone(),
+            source,
+            bodies,
+            required_bodies,
+            orbs: astrolog_defaults().map(|defaults| defaults.orbs.clone()).unwrap_or_default(),
+            custom_aspects,
+            points,
+            include_minor_aspects: req.include_minor_aspects(),
+            include_angle_aspects: req.include_angle_aspects,
+            include_exact_times: req.include_exact_times,
+            aspect_filter,
+            include_decans: req.include_decans,
+            mansion_system,
+            coordinates,
+            lang,
+            modern_rulers: req.modern_rulers,
+            sign_boundary_orb,
+            house_cusp_orb,
+            sect_twilight,
+            include_angle_speeds: req.include_angle_speeds,
+            node_type,
+        })
+    }
+}
+
+/// A natal chart at Julian date `jd` (UT), which is `date`, from `ephemeris`:
+/// the positions with each planet placed in its house, the houses and angles,
+/// and the aspects, along with everything else `options` asks for but the SVG.
+/// `stage` is called with "positions", "houses" and "aspects" as each is done.
+pub fn natal_chart(
+    ephemeris: Arc<dyn Ephemeris>,
+    jd: f64,
+    date: DateTime<Utc>,
+    options: &NatalChartOptions,
+    mut stage: impl FnMut(&'static str),
+) -> Result<ChartResponse, AstrologError> {
+    let chart = natal_computation(ephemeris, jd, options, &mut stage)?;
+    natal_response(chart, date, options, stage)
+}
+
+/// The positions and houses of the natal chart `natal_chart` answers, for
+/// handlers that calculate more from them first. `stage` is called with
+/// "positions" and "houses".
+pub fn natal_computation(
+    ephemeris: Arc<dyn Ephemeris>,
+    jd: f64,
+    options: &NatalChartOptions,
+    mut stage: impl FnMut(&'static str),
+) -> Result<ChartComputation, AstrologError> {
+    let chart = ChartComputation::from_ephemeris(ephemeris, jd, options.source, &options.bodies, &options.required_bodies)?
+        .with_custom_aspects(options.custom_aspects.clone())
+        .with_orbs(options.orbs.clone())
+        .with_points(options.points.clone())
+        .with_angle_aspects(options.include_angle_aspects)
+        .with_subdivisions(options.include_decans, options.mansion_system)
+        .with_coordinates(options.coordinates)
+        .with_lang(options.lang);
+    stage("positions");
+    let chart = chart.with_houses(options.latitude, options.longitude, options.house_system)?;
+    stage("houses");
+    Ok(chart)
+}
+
+/// The natal chart `natal_chart` answers, from its `natal_computation`.
+/// `stage` is called with "aspects".
+pub fn natal_response(
+    chart: ChartComputation,
+    date: DateTime<Utc>,
+    options: &NatalChartOptions,
+    mut stage: impl FnMut(&'static str),
+) -> Result<ChartResponse, AstrologError> {
+    let (chart_ruler, house_rulers) = chart.rulers(options.modern_rulers);
+    let (houses_summary, house_emphasis) = chart.houses_summary();
+    let boundary_warnings = chart.boundary_warnings(options.sign_boundary_orb, options.house_cusp_orb);
+    let angular = chart.angular();
+    let sect = chart.sect(options.latitude, options.longitude, options.sect_twilight);
+    let angle_speeds = if options.include_angle_speeds {
+        chart.angle_speeds(options.latitude, options.longitude)?
+    } else {
+        None
+    };
+    let houses = chart.house_info();
+    let angles = chart.angles();
+
+    let aspects = if options.include_exact_times {
+        chart.aspects_with_exact_times(options.include_minor_aspects)?
+    } else {
+        chart.aspects(options.include_minor_aspects)
+    };
+    let (aspects, truncated) = options.aspect_filter.apply(aspects);
+    stage("aspects");
+
+    let meta = chart.meta(options.node_type)?;
+    Ok(ChartResponse {
+        chart_type: "natal".to_string(),
+        schema_version: CHART_SCHEMA_VERSION,
+        date,
+        resolved_date: None,
+        latitude: options.latitude,
+        longitude: options.longitude,
+        house_system: options.house_system,
+        ayanamsa: options.ayanamsa.clone(),
+        warnings: chart.warnings(),
+        degraded_options: Vec::new(),
+        boundary_warnings,
+        angular,
+        points: chart.point_info(),
+        moon_mansion: chart.moon_mansion(),
+        ephemeris: Some(chart.source.to_string()),
+        sidereal_time: Some(local_sidereal_time(chart.jd, options.longitude) / 15.0),
+        planets: chart.into_planets(),
+        house_count: houses.len(),
+        houses,
+        angles,
+        aspect_set: AspectSet::new(options.include_minor_aspects),
+        aspects,
+        transit: None,
+        transits: Vec::new(),
+        chart_ruler,
+        house_rulers,
+        houses_summary,
+        house_emphasis,
+        angle_speeds,
+        sect: sect.map(|s| s.sect),
+        sun_altitude: sect.map(|s| s.sun_altitude),
+        sect_ambiguous: sect.is_some_and(|s| s.ambiguous),
+        svg_chart: None,
+        svg_thumbnail: None,
+        diagnostics: None,
+        meta: Some(meta),
+        interpretation: None,
+        truncated,
+    })
+}
+
+/// Calculates a natal chart at a Julian date (UT) on the default ephemeris, as
+/// `/api/chart/natal` would answer a tropical request with no other options,
+/// without the SVG: positions, houses and aspects, each planet placed in its house. The moment
+/// isn't converted to a calendar date first, so a date before the Gregorian
+/// reform means the same here as anywhere else the crate takes Julian dates.
+/// A moment or place the endpoint would refuse fails with the same message.
+pub fn calculate_natal_chart_jd(
+    jd: f64,
+    latitude: f64,
+    longitude: f64,
+    house_system: HouseSystem,
+    include_minor_aspects: bool,
+) -> Result<ChartResponse, AstrologError> {
+    let date = julian_to_date(jd).ok_or_else(|| AstrologError::DateTimeError {
+        message: format!("Date out of range: Julian date {}", jd),
+        date: None,
+    })?;
+    let invalid = |message: String| AstrologError::InvalidInput {
+        message,
+        parameter: "request".to_string(),
+    };
+    let mut req = ChartRequest {
+        date: date.into(),
+        latitude,
+        longitude,
+        house_system,
+        ayanamsa: "tropical".to_string(),
+        include_minor_aspects: Some(include_minor_aspects),
+        extra_bodies: default_extra_bodies(),
+        ..ChartRequest::default()
+    };
+    let ephemeris = default_ephemeris();
+    let degraded = req.degrade(&ephemeris.capabilities(), ephemeris.default_source());
+    if !degraded.is_empty() {
+        return Err(invalid(unsupported_options_message(&degraded)));
+    }
+    let options = NatalChartOptions::from_request(&req, ephemeris.default_source(), Lang::default()).map_err(invalid)?;
+    natal_chart(ephemeris, jd, date, &options, |_| {})
+}
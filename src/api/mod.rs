@@ -1,6 +1,34 @@
+#[cfg(feature = "server")]
+pub mod audit;
+pub mod capabilities;
+pub mod computation;
+#[cfg(feature = "server")]
+pub mod moon;
+#[cfg(feature = "server")]
+pub mod payload;
+#[cfg(feature = "server")]
+pub mod pool;
+#[cfg(feature = "server")]
 pub mod server;
+#[cfg(feature = "server")]
 pub mod queue;
+#[cfg(feature = "server")]
+pub mod render;
+#[cfg(feature = "server")]
+pub mod settings;
+pub mod positions;
+#[cfg(feature = "server")]
+pub mod prefetch;
+pub mod rounding;
+pub mod search;
+#[cfg(feature = "svg")]
+pub mod symbols;
 pub mod types;
+#[cfg(feature = "server")]
+pub mod versioning;
+pub mod weather;
 
+#[cfg(feature = "server")]
 pub use server::*;
+#[cfg(feature = "server")]
 pub use queue::*;
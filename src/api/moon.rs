@@ -0,0 +1,208 @@
+//! The `GET /api/moon` table: the Moon's sign, phase, void of course, mansion
+//! and distance at every step of a date range, for lunar calendars that need
+//! nothing else of the chart.
+//!
+//! Each step takes only the Sun's and Moon's positions. The void of course
+//! stretches are found once for the whole range, and rows are cached per
+//! moment, so overlapping tables share their steps.
+
+use crate::api::types::{MoonHeaderInfo, MoonResponse, MoonStepInfo};
+use crate::calc::angles::normalize;
+use crate::calc::aspects::{next_exact_with_source, AspectType};
+use crate::calc::ephemeris::default_ephemeris;
+use crate::calc::planets::{planet_longitude_at, Planet};
+use crate::calc::rulers::Sign;
+use crate::calc::subdivisions::{mansion, MansionSystem};
+use crate::calc::utils::{date_to_julian, julian_to_date};
+use crate::calc::weather::{illumination, moon_distance, void_periods, MoonPhase, VoidOfCourse};
+use crate::core::types::EphemerisSource;
+use crate::io::precompute::{parse_step, DateRange};
+use chrono::{DateTime, TimeDelta, Utc};
+use std::collections::HashMap;
+use std::sync::{Mutex, OnceLock};
+
+/// Most rows one table may have: a 31-day month at 15-minute steps
+pub const MAX_MOON_ROWS: usize = 31 * 24 * 4 + 1;
+
+/// Shortest step between rows, in minutes
+pub const MIN_MOON_STEP_MINUTES: i64 = 15;
+
+/// Most rows kept in memory at once
+pub const MOON_CACHE_CAPACITY: usize = 8192;
+
+/// Rows for the same second and options are shared
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct MoonKey {
+    second: i64,
+    mansion_system: MansionSystem,
+    source: EphemerisSource,
+}
+
+/// Least recently used rows, evicting the oldest once full
+struct MoonCache {
+    entries: HashMap<MoonKey, (MoonStepInfo, u64)>,
+    clock: u64,
+}
+
+impl MoonCache {
+    fn get(&mut self, key: &MoonKey) -> Option<MoonStepInfo> {
+        self.clock += 1;
+        let (row, used) = self.entries.get_mut(key)?;
+        *used = self.clock;
+        Some(row.clone())
+    }
+
+    fn insert(&mut self, key: MoonKey, row: MoonStepInfo) {
+        self.clock += 1;
+        if self.entries.len() >= MOON_CACHE_CAPACITY && !self.entries.contains_key(&key) {
+            if let Some(oldest) = self.entries.iter().min_by_key(|(_, (_, used))| *used).map(|(k, _)| *k) {
+                self.entries.remove(&oldest);
+            }
+        }
+        self.entries.insert(key, (row, self.clock));
+    }
+}
+
+fn moon_cache() -> &'static Mutex<MoonCache> {
+    static MOON_CACHE: OnceLock<Mutex<MoonCache>> = OnceLock::new();
+    MOON_CACHE.get_or_init(|| {
+        Mutex::new(MoonCache {
+            entries: HashMap::with_capacity(MOON_CACHE_CAPACITY),
+            clock: 0,
+        })
+    })
+}
+
+/// Parses a table step such as "15m", "1h" or "1d", refusing steps under
+/// `MIN_MOON_STEP_MINUTES`
+pub fn parse_moon_step(step: &str) -> Result<TimeDelta, String> {
+    let delta = parse_step(step)?;
+    if delta < TimeDelta::minutes(MIN_MOON_STEP_MINUTES) {
+        return Err(format!("The step must be at least {} minutes", MIN_MOON_STEP_MINUTES));
+    }
+    Ok(delta)
+}
+
+/// The moments of a table, refusing more than `MAX_MOON_ROWS`
+pub fn moon_range(from: DateTime<Utc>, to: DateTime<Utc>, step: TimeDelta) -> Result<DateRange, String> {
+    let range = DateRange::new(from, to, step)?;
+    if range.moments() > MAX_MOON_ROWS {
+        return Err(format!(
+            "The table would have {} rows; at most {} are returned, so use a longer step or a shorter range",
+            range.moments(),
+            MAX_MOON_ROWS
+        ));
+    }
+    Ok(range)
+}
+
+/// The table for a range checked by `moon_range`. `step` is echoed in the header
+/// as the request gave it.
+pub fn moon_table(
+    range: &DateRange,
+    step: &str,
+    mansion_system: MansionSystem,
+    source: EphemerisSource,
+) -> Result<MoonResponse, String> {
+    let jd_from = date_to_julian(range.from);
+    let date = |jd: f64| julian_to_date(jd).ok_or_else(|| format!("Date out of range: Julian date {}", jd));
+    let lunation = |aspect: AspectType| -> Result<DateTime<Utc>, String> {
+        let jd = next_exact_with_source(jd_from, Planet::Moon, Planet::Sun, aspect, 30.0, source)?
+            .into_iter()
+            .next()
+            .ok_or_else(|| "No lunation within 30 days".to_string())?;
+        date(jd)
+    };
+    let (_, _, used) = default_ephemeris().position(jd_from, Planet::Moon, source)?;
+
+    let keys: Vec<MoonKey> = (0..range.moments())
+        .map(|index| MoonKey {
+            second: range.moment(index).timestamp(),
+            mansion_system,
+            source,
+        })
+        .collect();
+    let cached: Vec<Option<MoonStepInfo>> = {
+        let mut cache = moon_cache().lock().unwrap_or_else(|e| e.into_inner());
+        keys.iter().map(|key| cache.get(key)).collect()
+    };
+    // The void of course stretches are only looked for when a row is missing
+    let voids = if cached.iter().any(Option::is_none) {
+        void_periods(jd_from, date_to_julian(range.moment(range.moments() - 1)), source)?
+    } else {
+        Vec::new()
+    };
+    let rows = keys
+        .into_iter()
+        .zip(cached)
+        .enumerate()
+        .map(|(index, (key, cached))| match cached {
+            Some(row) => Ok(row),
+            None => {
+                let row = moon_step(range.moment(index), mansion_system, source, &voids)?;
+                moon_cache().lock().unwrap_or_else(|e| e.into_inner()).insert(key, row.clone());
+                Ok(row)
+            }
+        })
+        .collect::<Result<_, String>>()?;
+
+    Ok(MoonResponse {
+        header: MoonHeaderInfo {
+            from: range.from,
+            to: range.to,
+            step: step.to_string(),
+            mansion_system,
+            ephemeris: used.to_string(),
+            next_new_moon: lunation(AspectType::Conjunction)?,
+            next_full_moon: lunation(AspectType::Opposition)?,
+        },
+        rows,
+    })
+}
+
+/// One row, from the Sun's and Moon's positions and the range's void stretches
+fn moon_step(
+    date: DateTime<Utc>,
+    mansion_system: MansionSystem,
+    source: EphemerisSource,
+    voids: &[VoidOfCourse],
+) -> Result<MoonStepInfo, String> {
+    let jd = date_to_julian(date);
+    let moon = planet_longitude_at(Planet::Moon, jd, source)?;
+    let sun = planet_longitude_at(Planet::Sun, jd, source)?;
+    let elongation = normalize(moon - sun);
+    Ok(MoonStepInfo {
+        date,
+        longitude: moon,
+        sign: Sign::from_longitude(moon),
+        phase: MoonPhase::from_elongation(elongation),
+        illumination: illumination(elongation),
+        void_of_course: voids.iter().any(|void| void.since <= jd && jd < void.until),
+        mansion: mansion(moon, mansion_system).name.to_string(),
+        distance_km: moon_distance(jd).round(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_steps_of_at_least_a_quarter_hour() {
+        assert_eq!(parse_moon_step("15m"), Ok(TimeDelta::minutes(15)));
+        assert_eq!(parse_moon_step("1h"), Ok(TimeDelta::hours(1)));
+        assert_eq!(parse_moon_step("1d"), Ok(TimeDelta::days(1)));
+        assert!(parse_moon_step("1s").is_err());
+        assert!(parse_moon_step("14m").is_err());
+        assert!(parse_moon_step("hourly").is_err());
+    }
+
+    #[test]
+    fn test_row_cap() {
+        let from = "2025-01-01T00:00:00Z".parse().unwrap();
+        let month = "2025-02-01T00:00:00Z".parse().unwrap();
+        assert_eq!(moon_range(from, month, TimeDelta::minutes(15)).unwrap().moments(), MAX_MOON_ROWS);
+        assert!(moon_range(from, month + TimeDelta::minutes(15), TimeDelta::minutes(15)).is_err());
+        assert!(moon_range(month, from, TimeDelta::hours(1)).is_err());
+    }
+}
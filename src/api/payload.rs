@@ -0,0 +1,135 @@
+//! Size limits on JSON request bodies, and the errors for bodies that can't be read.
+//!
+//! POST handlers take a `JsonBody<T>` rather than `web::Json<T>`. It reads at
+//! most the route's `BodyLimit` and deserializes with the path of the failing
+//! field kept, so a wrong-typed field is reported as, say, `transit.date: ...`
+//! in the `{"error", "message"}` body the API documents for a bad request.
+//! Nesting deeper than serde_json's recursion limit of 128 fails the same way.
+
+use actix_web::dev::Payload;
+use actix_web::http::StatusCode;
+use actix_web::{web, FromRequest, HttpMessage, HttpRequest, HttpResponse, ResponseError};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::future::Future;
+use std::pin::Pin;
+
+/// Environment variable for the body limit of single-chart endpoints, in bytes
+pub const MAX_BODY_SIZE_ENV: &str = "MAX_BODY_SIZE";
+
+/// Environment variable for the body limit of endpoints taking several moments or charts, in bytes
+pub const MAX_BATCH_BODY_SIZE_ENV: &str = "MAX_BATCH_BODY_SIZE";
+
+/// Body limit of single-chart endpoints when `MAX_BODY_SIZE` isn't set
+pub const DEFAULT_BODY_SIZE: usize = 64 * 1024;
+
+/// Body limit of batch endpoints when `MAX_BATCH_BODY_SIZE` isn't set
+pub const DEFAULT_BATCH_BODY_SIZE: usize = 1024 * 1024;
+
+/// Largest body, in bytes, a route reads. Routes set it with `app_data` from the
+/// `AppConfig`; those that don't get the default single-chart limit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BodyLimit(pub usize);
+
+impl BodyLimit {
+    /// The default limit for endpoints that calculate one chart
+    pub fn single() -> Self {
+        Self(DEFAULT_BODY_SIZE)
+    }
+
+    /// The default limit for endpoints that take several moments or charts at once
+    pub fn batch() -> Self {
+        Self(DEFAULT_BATCH_BODY_SIZE)
+    }
+}
+
+/// A request body that was refused before it reached the handler
+#[derive(Debug, Clone, PartialEq)]
+pub struct PayloadError {
+    status: StatusCode,
+    error: &'static str,
+    message: String,
+}
+
+impl PayloadError {
+    fn invalid(message: impl Into<String>) -> Self {
+        Self {
+            status: StatusCode::BAD_REQUEST,
+            error: "Invalid request format",
+            message: message.into(),
+        }
+    }
+}
+
+impl std::fmt::Display for PayloadError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.error, self.message)
+    }
+}
+
+#[derive(Serialize)]
+struct ErrorBody<'a> {
+    error: &'a str,
+    message: &'a str,
+}
+
+impl ResponseError for PayloadError {
+    fn status_code(&self) -> StatusCode {
+        self.status
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        HttpResponse::build(self.status).json(ErrorBody {
+            error: self.error,
+            message: &self.message,
+        })
+    }
+}
+
+/// A JSON request body of type `T`, read up to the route's `BodyLimit`
+#[derive(Debug)]
+pub struct JsonBody<T>(pub T);
+
+impl<T> JsonBody<T> {
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T: DeserializeOwned + 'static> FromRequest for JsonBody<T> {
+    type Error = PayloadError;
+    type Future = Pin<Box<dyn Future<Output = Result<Self, PayloadError>>>>;
+
+    fn from_request(req: &HttpRequest, payload: &mut Payload) -> Self::Future {
+        let limit = req.app_data::<BodyLimit>().copied().unwrap_or_else(BodyLimit::single).0;
+        let is_json = match req.mime_type() {
+            Ok(Some(mime)) => mime.subtype() == "json" || mime.suffix().is_some_and(|suffix| suffix == "json"),
+            _ => false,
+        };
+        let body = web::Payload::from_request(req, payload);
+        Box::pin(async move {
+            if !is_json {
+                return Err(PayloadError {
+                    status: StatusCode::UNSUPPORTED_MEDIA_TYPE,
+                    error: "Unsupported media type",
+                    message: "Request bodies must be sent as application/json".to_string(),
+                });
+            }
+            let body = body.await.map_err(|e| PayloadError::invalid(e.to_string()))?;
+            let bytes = match body.to_bytes_limited(limit).await {
+                Ok(read) => read.map_err(|e| PayloadError::invalid(e.to_string()))?,
+                Err(_) => {
+                    return Err(PayloadError {
+                        status: StatusCode::PAYLOAD_TOO_LARGE,
+                        error: "Payload too large",
+                        message: format!("Request body is over the {} byte limit", limit),
+                    })
+                }
+            };
+            let mut deserializer = serde_json::Deserializer::from_slice(&bytes);
+            let value = serde_path_to_error::deserialize(&mut deserializer).map_err(|e| PayloadError::invalid(e.to_string()))?;
+            deserializer.end().map_err(|e| PayloadError::invalid(e.to_string()))?;
+            Ok(JsonBody(value))
+        })
+    }
+}
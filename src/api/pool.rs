@@ -0,0 +1,140 @@
+//! Dedicated threads for the CPU-bound half of a request.
+//!
+//! Ephemeris lookups and SVG rendering can take tens of milliseconds, long enough
+//! to stall an HTTP worker's event loop. Handlers hand that work to the
+//! calculation pool and await the result, so cheap requests such as `/health`
+//! keep being served while charts are calculated.
+
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::mpsc::{self, Sender};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::thread;
+use tokio::sync::{oneshot, Semaphore};
+
+/// Calculations admitted to the pool at once, running or waiting for a thread
+pub const DEFAULT_MAX_CONCURRENT: usize = 500;
+
+type Job = Box<dyn FnOnce() + Send>;
+
+/// A fixed set of calculation threads, sized independently of the HTTP workers
+pub struct CalculationPool {
+    jobs: Mutex<Sender<Job>>,
+    permits: Arc<Semaphore>,
+    threads: usize,
+}
+
+impl CalculationPool {
+    /// Starts `threads` calculation threads that accept up to `max_concurrent`
+    /// calculations at a time; further callers wait for a permit
+    pub fn new(threads: usize, max_concurrent: usize) -> Self {
+        let threads = threads.max(1);
+        let (sender, receiver) = mpsc::channel::<Job>();
+        let receiver = Arc::new(Mutex::new(receiver));
+        for i in 0..threads {
+            let receiver = Arc::clone(&receiver);
+            thread::Builder::new()
+                .name(format!("calculation-{}", i))
+                .spawn(move || loop {
+                    let job = match receiver.lock() {
+                        Ok(receiver) => receiver.recv(),
+                        Err(_) => return,
+                    };
+                    match job {
+                        Ok(job) => job(),
+                        Err(_) => return,
+                    }
+                })
+                .expect("failed to start calculation thread");
+        }
+        Self {
+            jobs: Mutex::new(sender),
+            permits: Arc::new(Semaphore::new(max_concurrent.max(1))),
+            threads,
+        }
+    }
+
+    pub fn threads(&self) -> usize {
+        self.threads
+    }
+
+    /// Permits not held by a running or queued calculation
+    pub fn available_permits(&self) -> usize {
+        self.permits.available_permits()
+    }
+
+    /// Runs `calculation` on a pool thread once a permit is free.
+    ///
+    /// # Returns
+    ///
+    /// The calculation's result, or an error if it panicked or the pool has shut down
+    pub async fn run<T, F>(&self, calculation: F) -> Result<T, String>
+    where
+        F: FnOnce() -> T + Send + 'static,
+        T: Send + 'static,
+    {
+        let _permit = self
+            .permits
+            .acquire()
+            .await
+            .map_err(|_| "Calculation pool is closed".to_string())?;
+        let (sender, receiver) = oneshot::channel();
+        let job: Job = Box::new(move || {
+            let result = panic::catch_unwind(AssertUnwindSafe(calculation));
+            let _ = sender.send(result);
+        });
+        self.jobs
+            .lock()
+            .map_err(|_| "Failed to lock calculation pool".to_string())?
+            .send(job)
+            .map_err(|_| "Calculation pool is closed".to_string())?;
+        match receiver.await {
+            Ok(Ok(value)) => Ok(value),
+            Ok(Err(_)) => Err("Calculation panicked".to_string()),
+            Err(_) => Err("Calculation was dropped".to_string()),
+        }
+    }
+}
+
+static CALCULATION_POOL: OnceLock<CalculationPool> = OnceLock::new();
+
+/// Starts the shared calculation pool. Only the first call has any effect.
+pub fn init_calculation_pool(threads: usize, max_concurrent: usize) {
+    CALCULATION_POOL.get_or_init(|| CalculationPool::new(threads, max_concurrent));
+}
+
+/// The shared calculation pool, started with one thread per CPU if not configured
+pub fn calculation_pool() -> &'static CalculationPool {
+    CALCULATION_POOL.get_or_init(|| CalculationPool::new(num_cpus::get(), DEFAULT_MAX_CONCURRENT))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[actix_rt::test]
+    async fn test_permits_bound_the_pool() {
+        let pool = Arc::new(CalculationPool::new(2, 3));
+        let (release, wait) = std::sync::mpsc::channel::<()>();
+        let wait = Arc::new(Mutex::new(wait));
+        let mut running = Vec::new();
+        for _ in 0..3 {
+            let (pool, wait) = (Arc::clone(&pool), Arc::clone(&wait));
+            running.push(actix_rt::spawn(async move {
+                pool.run(move || wait.lock().unwrap().recv().unwrap()).await
+            }));
+        }
+        while pool.available_permits() > 0 {
+            actix_rt::task::yield_now().await;
+        }
+        for _ in 0..3 {
+            release.send(()).unwrap();
+        }
+        for task in running {
+            task.await.unwrap().unwrap();
+        }
+        assert_eq!(pool.available_permits(), 3);
+        assert_eq!(pool.run(|| 6 * 7).await, Ok(42));
+        assert!(pool.run(|| panic!("boom")).await.is_err());
+        assert_eq!(pool.run(|| "still running").await, Ok("still running"));
+    }
+}
@@ -0,0 +1,97 @@
+//! The `GET /api/positions` array, straight from the ephemeris and cached per
+//! second, so a client polling positions doesn't recalculate them on every request
+
+use crate::api::types::BodyPositionInfo;
+use crate::calc::planets::Planet;
+use crate::calc::positions::{body_positions, Center, Zodiac};
+use crate::calc::utils::date_to_julian;
+use crate::core::types::EphemerisSource;
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, OnceLock};
+
+/// Most position lists kept in memory at once
+pub const POSITIONS_CACHE_CAPACITY: usize = 1024;
+
+/// Requests for the same second and bodies share their positions
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct PositionsKey {
+    second: i64,
+    bodies: Vec<Planet>,
+    zodiac: Zodiac,
+    center: Center,
+    source: EphemerisSource,
+}
+
+/// Least recently used position lists, evicting the oldest once full
+struct PositionsCache {
+    entries: HashMap<PositionsKey, (Arc<[BodyPositionInfo]>, u64)>,
+    clock: u64,
+}
+
+impl PositionsCache {
+    fn get(&mut self, key: &PositionsKey) -> Option<Arc<[BodyPositionInfo]>> {
+        self.clock += 1;
+        let (positions, used) = self.entries.get_mut(key)?;
+        *used = self.clock;
+        Some(Arc::clone(positions))
+    }
+
+    fn insert(&mut self, key: PositionsKey, positions: Arc<[BodyPositionInfo]>) {
+        self.clock += 1;
+        if self.entries.len() >= POSITIONS_CACHE_CAPACITY && !self.entries.contains_key(&key) {
+            if let Some(oldest) = self
+                .entries
+                .iter()
+                .min_by_key(|(_, (_, used))| *used)
+                .map(|(k, _)| k.clone())
+            {
+                self.entries.remove(&oldest);
+            }
+        }
+        self.entries.insert(key, (positions, self.clock));
+    }
+}
+
+fn positions_cache() -> &'static Mutex<PositionsCache> {
+    static POSITIONS_CACHE: OnceLock<Mutex<PositionsCache>> = OnceLock::new();
+    POSITIONS_CACHE.get_or_init(|| {
+        Mutex::new(PositionsCache {
+            entries: HashMap::with_capacity(POSITIONS_CACHE_CAPACITY),
+            clock: 0,
+        })
+    })
+}
+
+/// Positions of the bodies at the whole second containing `date`, from the
+/// cache when the same second and bodies were asked for before
+pub fn positions_at(
+    date: DateTime<Utc>,
+    bodies: Vec<Planet>,
+    zodiac: Zodiac,
+    center: Center,
+    source: EphemerisSource,
+) -> Result<Arc<[BodyPositionInfo]>, String> {
+    let second = date.timestamp();
+    let key = PositionsKey {
+        second,
+        bodies,
+        zodiac,
+        center,
+        source,
+    };
+    if let Some(positions) = positions_cache().lock().unwrap_or_else(|e| e.into_inner()).get(&key) {
+        return Ok(positions);
+    }
+    let moment = DateTime::from_timestamp(second, 0).ok_or_else(|| format!("Date out of range: {}", date))?;
+    let positions: Arc<[BodyPositionInfo]> =
+        body_positions(date_to_julian(moment), &key.bodies, zodiac, center, source)?
+            .into_iter()
+            .map(BodyPositionInfo::from)
+            .collect();
+    positions_cache()
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .insert(key, Arc::clone(&positions));
+    Ok(positions)
+}
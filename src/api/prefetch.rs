@@ -0,0 +1,190 @@
+//! The current sky, prefetched so `GET /api/now` and `GET /api/positions`
+//! without a date don't wait on the ephemeris.
+//!
+//! A `Prefetcher` holds one snapshot: every position body and the Moon's phase
+//! and void of course status at the start of a UT minute. The server starts its
+//! background task, which calculates the snapshot before the first request and
+//! then checks every `prefetch_interval` seconds whether the minute has turned.
+//! `GET /api/now` in a minute the task hasn't reached yet calculates the
+//! snapshot itself and leaves it for the rest of the minute. The void of course
+//! search, like the weather report's, samples the default ephemeris.
+
+use crate::api::computation::REQUIRED_PLANETS;
+use crate::api::types::{BodyPositionInfo, MoonWeatherInfo, NowResponse};
+use crate::calc::angles::normalize;
+use crate::calc::ephemeris::{default_ephemeris, Ephemeris};
+use crate::calc::planets::{calculate_body_positions_from, Planet};
+use crate::calc::positions::{BodyPosition, POSITION_BODIES};
+use crate::calc::rulers::Sign;
+use crate::calc::utils::{date_to_julian, julian_to_date};
+use crate::calc::weather::{illumination, void_of_course, MoonPhase};
+use crate::core::types::EphemerisSource;
+use chrono::{DateTime, Utc};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::oneshot;
+use tokio::task::JoinHandle;
+use tokio::time::MissedTickBehavior;
+
+/// Seconds between the background task's checks, unless the settings say otherwise
+pub const DEFAULT_PREFETCH_INTERVAL: u64 = 60;
+
+struct Snapshot {
+    minute: i64,
+    fetched: Instant,
+    now: Arc<NowResponse>,
+}
+
+/// The latest snapshot of the sky from one ephemeris
+pub struct Prefetcher {
+    ephemeris: Arc<dyn Ephemeris>,
+    latest: Mutex<Option<Snapshot>>,
+}
+
+impl Prefetcher {
+    pub fn new(ephemeris: Arc<dyn Ephemeris>) -> Self {
+        Self {
+            ephemeris,
+            latest: Mutex::new(None),
+        }
+    }
+
+    /// The snapshot for the minute containing `date`, if it has been calculated
+    pub fn current(&self, date: DateTime<Utc>) -> Option<Arc<NowResponse>> {
+        let minute = date.timestamp().div_euclid(60);
+        let latest = self.latest.lock().unwrap_or_else(|e| e.into_inner());
+        latest
+            .as_ref()
+            .filter(|snapshot| snapshot.minute == minute)
+            .map(|snapshot| Arc::clone(&snapshot.now))
+    }
+
+    /// The snapshot for the minute containing `date`, calculated and kept if
+    /// the one held is for another minute
+    pub fn now_at(&self, date: DateTime<Utc>) -> Result<Arc<NowResponse>, String> {
+        if let Some(now) = self.current(date) {
+            return Ok(now);
+        }
+        let minute = date.timestamp().div_euclid(60);
+        let start = DateTime::from_timestamp(minute * 60, 0).ok_or_else(|| format!("Date out of range: {}", date))?;
+        let now = Arc::new(snapshot(&*self.ephemeris, start)?);
+        *self.latest.lock().unwrap_or_else(|e| e.into_inner()) = Some(Snapshot {
+            minute,
+            fetched: Instant::now(),
+            now: Arc::clone(&now),
+        });
+        Ok(now)
+    }
+
+    /// The current positions of `bodies`, in that order, if the snapshot for
+    /// this minute is held, and was calculated from `source` when one is asked for
+    pub fn positions(&self, bodies: &[Planet], source: Option<EphemerisSource>) -> Option<Arc<[BodyPositionInfo]>> {
+        if source.is_some_and(|source| source != self.ephemeris.default_source()) {
+            return None;
+        }
+        let now = self.current(Utc::now())?;
+        bodies
+            .iter()
+            .map(|&body| now.positions.iter().find(|position| position.body == body).copied())
+            .collect()
+    }
+
+    /// Seconds since the snapshot held was calculated, or `None` before the first
+    pub fn age_seconds(&self) -> Option<f64> {
+        let latest = self.latest.lock().unwrap_or_else(|e| e.into_inner());
+        latest.as_ref().map(|snapshot| snapshot.fetched.elapsed().as_secs_f64())
+    }
+
+    // Calculates this minute's snapshot off the async threads, unless it is held
+    async fn refresh(self: &Arc<Self>) {
+        let prefetcher = Arc::clone(self);
+        match tokio::task::spawn_blocking(move || prefetcher.now_at(Utc::now()).map(|_| ())).await {
+            Ok(Ok(())) => {}
+            Ok(Err(e)) => log::warn!("Prefetching the current positions failed: {}", e),
+            Err(e) => log::warn!("Prefetching the current positions panicked: {}", e),
+        }
+    }
+
+    /// Calculates the snapshot for this minute, then starts the task that keeps
+    /// it current, checking every `interval`
+    pub async fn start(self: &Arc<Self>, interval: Duration) -> PrefetchTask {
+        self.refresh().await;
+        let (stop, mut stopped) = oneshot::channel();
+        let prefetcher = Arc::clone(self);
+        let handle = tokio::spawn(async move {
+            let mut ticks = tokio::time::interval_at(tokio::time::Instant::now() + interval, interval);
+            ticks.set_missed_tick_behavior(MissedTickBehavior::Delay);
+            loop {
+                tokio::select! {
+                    _ = &mut stopped => break,
+                    _ = ticks.tick() => prefetcher.refresh().await,
+                }
+            }
+        });
+        PrefetchTask { stop, handle }
+    }
+}
+
+/// The running background task of a `Prefetcher`
+pub struct PrefetchTask {
+    stop: oneshot::Sender<()>,
+    handle: JoinHandle<()>,
+}
+
+impl PrefetchTask {
+    /// Stops the task, waiting for a refresh in progress to finish
+    pub async fn stop(self) {
+        let _ = self.stop.send(());
+        if let Err(e) = self.handle.await {
+            log::warn!("The prefetch task ended abnormally: {}", e);
+        }
+    }
+}
+
+// Every position body the ephemeris has and the Moon at `date`
+fn snapshot(ephemeris: &dyn Ephemeris, date: DateTime<Utc>) -> Result<NowResponse, String> {
+    let jd = date_to_julian(date);
+    let calculated = calculate_body_positions_from(ephemeris, jd, &POSITION_BODIES, &REQUIRED_PLANETS, ephemeris.default_source())
+        .map_err(|e| e.to_string())?;
+    let positions: Vec<BodyPositionInfo> = calculated
+        .bodies
+        .iter()
+        .zip(&calculated.positions)
+        .map(|(&planet, position)| {
+            BodyPositionInfo::from(BodyPosition {
+                planet,
+                longitude: position.longitude,
+                latitude: position.latitude,
+                speed: position.speed,
+            })
+        })
+        .collect();
+    let longitude_of = |body: Planet| {
+        positions
+            .iter()
+            .find(|position| position.body == body)
+            .map(|position| position.longitude)
+            .ok_or_else(|| format!("The {:?} was not calculated", body))
+    };
+    let moon_longitude = longitude_of(Planet::Moon)?;
+    let elongation = normalize(moon_longitude - longitude_of(Planet::Sun)?);
+
+    let void = void_of_course(jd, default_ephemeris().default_source())?;
+    let void_date = |jd: f64| julian_to_date(jd).ok_or_else(|| format!("Date out of range: Julian date {}", jd));
+    let moon = MoonWeatherInfo {
+        sign: Sign::from_longitude(moon_longitude).to_string(),
+        phase: MoonPhase::from_elongation(elongation),
+        elongation,
+        illumination: illumination(elongation),
+        void_of_course: void.is_some(),
+        void_since: void.map(|v| void_date(v.since)).transpose()?,
+        void_until: void.map(|v| void_date(v.until)).transpose()?,
+    };
+
+    Ok(NowResponse {
+        date,
+        ephemeris: calculated.source.to_string(),
+        positions,
+        moon,
+    })
+}
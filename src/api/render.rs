@@ -0,0 +1,183 @@
+//! Background render jobs for `POST /api/render`.
+//!
+//! A job renders on the calculation pool like any chart, but the request returns
+//! at once with the job's id. Finished artifacts wait in a bounded in-memory
+//! store until fetched from `GET /api/render/{id}` or until they expire. A
+//! separate permit count keeps slow renders from taking every pool thread.
+
+use std::collections::HashMap;
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+use tokio::sync::Semaphore;
+
+/// Renders running or waiting for a pool thread at once
+pub const DEFAULT_MAX_CONCURRENT_RENDERS: usize = 4;
+
+/// Most jobs kept at once, pending or finished
+pub const RENDER_STORE_CAPACITY: usize = 128;
+
+/// How long a finished job's artifact is kept
+pub const RENDER_TTL: Duration = Duration::from_secs(600);
+
+/// What a job produced
+#[derive(Debug, Clone, PartialEq)]
+pub enum RenderOutcome {
+    /// The rendered SVG document
+    Svg(String),
+    /// Why the render failed, with the HTTP status a synchronous request would get
+    Failed { status: u16, message: String },
+}
+
+/// The state of a job as seen by `GET /api/render/{id}`
+#[derive(Debug, Clone, PartialEq)]
+pub enum RenderJobState {
+    Pending,
+    Finished(RenderOutcome),
+    /// Finished longer ago than the TTL, or evicted to make room
+    Expired,
+    /// Never issued
+    Unknown,
+}
+
+struct RenderJob {
+    outcome: Option<RenderOutcome>,
+    finished: Option<Instant>,
+}
+
+/// Jobs by id. Ids count up from 1, so a missing id below the next one to be
+/// issued belonged to a job that has gone.
+pub struct RenderStore {
+    jobs: HashMap<u64, RenderJob>,
+    next_id: u64,
+    capacity: usize,
+    ttl: Duration,
+}
+
+impl RenderStore {
+    pub fn new(capacity: usize, ttl: Duration) -> Self {
+        Self {
+            jobs: HashMap::new(),
+            next_id: 1,
+            capacity: capacity.max(1),
+            ttl,
+        }
+    }
+
+    /// Registers a pending job, dropping expired jobs and then the oldest
+    /// finished one if the store is full.
+    ///
+    /// # Returns
+    ///
+    /// The new job's id, or `None` when every slot holds a pending job
+    pub fn create(&mut self) -> Option<u64> {
+        self.evict_expired(Instant::now());
+        if self.jobs.len() >= self.capacity {
+            let oldest = self
+                .jobs
+                .iter()
+                .filter_map(|(id, job)| job.finished.map(|finished| (finished, *id)))
+                .min()
+                .map(|(_, id)| id)?;
+            self.jobs.remove(&oldest);
+        }
+        let id = self.next_id;
+        self.next_id += 1;
+        self.jobs.insert(
+            id,
+            RenderJob {
+                outcome: None,
+                finished: None,
+            },
+        );
+        Some(id)
+    }
+
+    /// Stores a job's result, starting its TTL
+    pub fn finish(&mut self, id: u64, outcome: RenderOutcome) {
+        if let Some(job) = self.jobs.get_mut(&id) {
+            job.outcome = Some(outcome);
+            job.finished = Some(Instant::now());
+        }
+    }
+
+    pub fn state(&mut self, id: u64) -> RenderJobState {
+        self.evict_expired(Instant::now());
+        match self.jobs.get(&id) {
+            Some(RenderJob { outcome: Some(outcome), .. }) => RenderJobState::Finished(outcome.clone()),
+            Some(_) => RenderJobState::Pending,
+            None if id > 0 && id < self.next_id => RenderJobState::Expired,
+            None => RenderJobState::Unknown,
+        }
+    }
+
+    fn evict_expired(&mut self, now: Instant) {
+        let ttl = self.ttl;
+        self.jobs
+            .retain(|_, job| job.finished.is_none_or(|finished| now.duration_since(finished) < ttl));
+    }
+}
+
+/// The shared job store
+pub fn render_store() -> &'static Mutex<RenderStore> {
+    static RENDER_STORE: OnceLock<Mutex<RenderStore>> = OnceLock::new();
+    RENDER_STORE.get_or_init(|| Mutex::new(RenderStore::new(RENDER_STORE_CAPACITY, RENDER_TTL)))
+}
+
+static RENDER_PERMITS: OnceLock<Semaphore> = OnceLock::new();
+
+/// Sets how many renders may run at once. Only the first call has any effect.
+pub fn init_render_limit(max_concurrent: usize) {
+    RENDER_PERMITS.get_or_init(|| Semaphore::new(max_concurrent.max(1)));
+}
+
+/// Permits a job holds while it renders, separate from the calculation pool's
+pub fn render_permits() -> &'static Semaphore {
+    RENDER_PERMITS.get_or_init(|| Semaphore::new(DEFAULT_MAX_CONCURRENT_RENDERS))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_job_lifecycle() {
+        let mut store = RenderStore::new(4, RENDER_TTL);
+        let id = store.create().unwrap();
+        assert_eq!(store.state(id), RenderJobState::Pending);
+        store.finish(id, RenderOutcome::Svg("<svg/>".to_string()));
+        assert_eq!(store.state(id), RenderJobState::Finished(RenderOutcome::Svg("<svg/>".to_string())));
+        assert_eq!(store.state(id + 1), RenderJobState::Unknown);
+        assert_eq!(store.state(0), RenderJobState::Unknown);
+    }
+
+    #[test]
+    fn test_finished_jobs_expire() {
+        let mut store = RenderStore::new(4, Duration::ZERO);
+        let id = store.create().unwrap();
+        // Pending jobs wait however long the render takes
+        assert_eq!(store.state(id), RenderJobState::Pending);
+        store.finish(
+            id,
+            RenderOutcome::Failed {
+                status: 400,
+                message: "bad".to_string(),
+            },
+        );
+        assert_eq!(store.state(id), RenderJobState::Expired);
+    }
+
+    #[test]
+    fn test_full_store_evicts_the_oldest_finished_job() {
+        let mut store = RenderStore::new(2, RENDER_TTL);
+        let first = store.create().unwrap();
+        let second = store.create().unwrap();
+        assert_eq!(store.create(), None);
+
+        store.finish(second, RenderOutcome::Svg(String::new()));
+        store.finish(first, RenderOutcome::Svg(String::new()));
+        let third = store.create().unwrap();
+        assert_eq!(store.state(second), RenderJobState::Expired);
+        assert!(matches!(store.state(first), RenderJobState::Finished(_)));
+        assert_eq!(store.state(third), RenderJobState::Pending);
+    }
+}
@@ -0,0 +1,75 @@
+//! Serializers that round response numbers to the precision they carry.
+//!
+//! Positions are good to well under an arc second, so longitudes keep six
+//! decimals (0.0036") and orbs three; printing every digit of an `f64` only
+//! makes the responses larger.
+
+use serde::Serializer;
+
+/// Decimals kept for ecliptic longitudes and latitudes
+pub const LONGITUDE_DECIMALS: i32 = 6;
+
+/// Decimals kept for aspect orbs
+pub const ORB_DECIMALS: i32 = 3;
+
+fn round_to(value: f64, decimals: i32) -> f64 {
+    let factor = 10f64.powi(decimals);
+    (value * factor).round() / factor
+}
+
+/// An ecliptic longitude to `LONGITUDE_DECIMALS`, staying below 360°
+pub fn longitude<S: Serializer>(value: &f64, serializer: S) -> Result<S::Ok, S::Error> {
+    let rounded = round_to(*value, LONGITUDE_DECIMALS);
+    serializer.serialize_f64(if rounded >= 360.0 { rounded - 360.0 } else { rounded })
+}
+
+/// An ecliptic latitude to `LONGITUDE_DECIMALS`
+pub fn latitude<S: Serializer>(value: &f64, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_f64(round_to(*value, LONGITUDE_DECIMALS))
+}
+
+/// An aspect orb to `ORB_DECIMALS`
+pub fn orb<S: Serializer>(value: &f64, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_f64(round_to(*value, ORB_DECIMALS))
+}
+
+/// An aspect strength to `ORB_DECIMALS`, as fine as the orb it comes from
+pub fn strength<S: Serializer>(value: &f64, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_f64(round_to(*value, ORB_DECIMALS))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::Serialize;
+
+    #[derive(Serialize)]
+    struct Rounded {
+        #[serde(serialize_with = "longitude")]
+        longitude: f64,
+        #[serde(serialize_with = "latitude")]
+        latitude: f64,
+        #[serde(serialize_with = "orb")]
+        orb: f64,
+    }
+
+    #[test]
+    fn test_numbers_are_rounded() {
+        let json = serde_json::to_string(&Rounded {
+            longitude: 123.456789123456,
+            latitude: -1.23456789,
+            orb: 0.12345678,
+        })
+        .unwrap();
+        assert_eq!(json, r#"{"longitude":123.456789,"latitude":-1.234568,"orb":0.123}"#);
+
+        // Rounding up to 360° wraps to 0°
+        let json = serde_json::to_string(&Rounded {
+            longitude: 359.99999999,
+            latitude: 0.0,
+            orb: 0.0,
+        })
+        .unwrap();
+        assert!(json.starts_with(r#"{"longitude":0.0,"#), "{}", json);
+    }
+}
@@ -0,0 +1,279 @@
+//! Finding charts that match astrological criteria, such as
+//! `sun.sign:scorpio,moon.house:7,aspect:sun-square-saturn`.
+//!
+//! Each criterion is checked against a chart's computed positions and aspects.
+//! `ChartIndex` keeps the charts with an index of sign and house placements,
+//! updated as charts are added and removed, so a search with a placement
+//! criterion only looks at the charts that have that placement.
+
+use crate::api::types::{AspectInfo, ChartResponse, PlanetInfo};
+use crate::calc::aspects::AspectType;
+use crate::calc::planets::Planet;
+use crate::calc::rulers::Sign;
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+/// Most criteria in one search
+pub const MAX_CRITERIA: usize = 16;
+
+/// A condition a chart must meet
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Criterion {
+    /// `sun.sign:scorpio`
+    Sign(Planet, Sign),
+    /// `moon.house:7`
+    House(Planet, u8),
+    /// `mars.retrograde:true`
+    Retrograde(Planet, bool),
+    /// `aspect:sun-square-saturn`, or `aspect:sun-square-saturn:2` within 2° of exact
+    Aspect {
+        first: Planet,
+        aspect: AspectType,
+        second: Planet,
+        max_orb: Option<f64>,
+    },
+}
+
+fn parse_planet(name: &str) -> Result<Planet, String> {
+    name.trim().parse()
+}
+
+fn parse_sign(name: &str) -> Result<Sign, String> {
+    Sign::ALL
+        .into_iter()
+        .find(|sign| sign.to_string().eq_ignore_ascii_case(name.trim()))
+        .ok_or_else(|| format!("Unknown sign '{}'", name))
+}
+
+impl std::str::FromStr for Criterion {
+    type Err = String;
+
+    /// Parses one criterion, e.g. "sun.sign:scorpio" or "aspect:sun-square-saturn:2"
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (key, value) = s
+            .split_once(':')
+            .ok_or_else(|| format!("Invalid criterion '{}' (expected key:value)", s))?;
+
+        if key.trim().eq_ignore_ascii_case("aspect") {
+            let (bodies, max_orb) = match value.split_once(':') {
+                Some((bodies, orb)) => match orb.trim().parse::<f64>() {
+                    Ok(orb) if orb.is_finite() && orb >= 0.0 => (bodies, Some(orb)),
+                    _ => return Err(format!("Invalid orb '{}' in criterion '{}'", orb, s)),
+                },
+                None => (value, None),
+            };
+            let parts: Vec<&str> = bodies.split('-').collect();
+            let [first, aspect, second] = parts[..] else {
+                return Err(format!(
+                    "Invalid aspect criterion '{}' (expected aspect:body-aspect-body, with underscores in body names)",
+                    s
+                ));
+            };
+            return Ok(Criterion::Aspect {
+                first: parse_planet(first)?,
+                aspect: aspect.trim().parse()?,
+                second: parse_planet(second)?,
+                max_orb,
+            });
+        }
+
+        let (body, field) = key
+            .split_once('.')
+            .ok_or_else(|| format!("Invalid criterion '{}' (expected body.field:value or aspect:...)", s))?;
+        let planet = parse_planet(body)?;
+        match field.trim().to_ascii_lowercase().as_str() {
+            "sign" => Ok(Criterion::Sign(planet, parse_sign(value)?)),
+            "house" => match value.trim().parse::<u8>() {
+                Ok(house) if (1..=36).contains(&house) => Ok(Criterion::House(planet, house)),
+                _ => Err(format!("Invalid house '{}' in criterion '{}'", value, s)),
+            },
+            "retrograde" => match value.trim().to_ascii_lowercase().as_str() {
+                "true" | "yes" => Ok(Criterion::Retrograde(planet, true)),
+                "false" | "no" => Ok(Criterion::Retrograde(planet, false)),
+                _ => Err(format!("Invalid retrograde value '{}' in criterion '{}'", value, s)),
+            },
+            _ => Err(format!("Unknown field '{}' in criterion '{}' (expected sign, house or retrograde)", field, s)),
+        }
+    }
+}
+
+/// Parses a comma-separated list of criteria, of which there must be at least one
+pub fn parse_criteria(list: &str) -> Result<Vec<Criterion>, String> {
+    let criteria = list
+        .split(',')
+        .map(str::trim)
+        .filter(|criterion| !criterion.is_empty())
+        .map(str::parse)
+        .collect::<Result<Vec<Criterion>, String>>()?;
+    if criteria.is_empty() {
+        return Err("No search criteria given".to_string());
+    }
+    if criteria.len() > MAX_CRITERIA {
+        return Err(format!("Too many search criteria: {} (at most {})", criteria.len(), MAX_CRITERIA));
+    }
+    Ok(criteria)
+}
+
+// The chart's body `planet`, if it has it
+fn find_planet(chart: &ChartResponse, planet: Planet) -> Option<&PlanetInfo> {
+    chart.planets.iter().find(|info| info.name.parse::<Planet>() == Ok(planet))
+}
+
+// Whether `aspect` is between `first` and `second`, either way round
+fn is_between(aspect: &AspectInfo, first: Planet, second: Planet) -> bool {
+    let (one, other) = (aspect.planet1.parse::<Planet>(), aspect.planet2.parse::<Planet>());
+    (one == Ok(first) && other == Ok(second)) || (one == Ok(second) && other == Ok(first))
+}
+
+impl Criterion {
+    /// How `chart` meets the criterion, e.g. "Sun in Scorpio", or `None` if it doesn't
+    pub fn matches(&self, chart: &ChartResponse) -> Option<String> {
+        match *self {
+            Criterion::Sign(planet, sign) => {
+                let info = find_planet(chart, planet)?;
+                (Sign::from_longitude(info.longitude) == sign).then(|| format!("{} in {}", info.name, sign))
+            }
+            Criterion::House(planet, house) => {
+                let info = find_planet(chart, planet)?;
+                (info.house == Some(house)).then(|| format!("{} in house {}", info.name, house))
+            }
+            Criterion::Retrograde(planet, retrograde) => {
+                let info = find_planet(chart, planet)?;
+                let motion = if retrograde { "retrograde" } else { "direct" };
+                (info.is_retrograde == retrograde).then(|| format!("{} {}", info.name, motion))
+            }
+            Criterion::Aspect {
+                first,
+                aspect,
+                second,
+                max_orb,
+            } => chart
+                .aspects
+                .iter()
+                .filter(|info| is_between(info, first, second) && info.aspect.parse::<AspectType>() == Ok(aspect))
+                .find(|info| max_orb.is_none_or(|max_orb| info.orb.abs() <= max_orb))
+                .map(|info| format!("{} {} {} (orb {:.2}°)", info.planet1, info.aspect, info.planet2, info.orb.abs())),
+        }
+    }
+}
+
+/// A chart that met every criterion, with how it met each
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ChartMatch {
+    pub id: u64,
+    pub name: String,
+    /// One per criterion, in the order given
+    pub factors: Vec<String>,
+}
+
+// Takes `id` out of the entry for `key`, dropping the entry once it is empty
+fn unindex<K: std::hash::Hash + Eq>(index: &mut HashMap<K, BTreeSet<u64>>, key: K, id: u64) {
+    if let Some(ids) = index.get_mut(&key) {
+        ids.remove(&id);
+        if ids.is_empty() {
+            index.remove(&key);
+        }
+    }
+}
+
+struct IndexedChart {
+    name: String,
+    chart: ChartResponse,
+}
+
+/// Charts by id, with the ids of the charts holding each sign and house placement
+#[derive(Default)]
+pub struct ChartIndex {
+    charts: BTreeMap<u64, IndexedChart>,
+    by_sign: HashMap<(Planet, Sign), BTreeSet<u64>>,
+    by_house: HashMap<(Planet, u8), BTreeSet<u64>>,
+}
+
+impl ChartIndex {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Number of charts
+    pub fn len(&self) -> usize {
+        self.charts.len()
+    }
+
+    /// Whether there are no charts
+    pub fn is_empty(&self) -> bool {
+        self.charts.is_empty()
+    }
+
+    // The sign and house placements of a chart's bodies
+    fn placements(chart: &ChartResponse) -> impl Iterator<Item = (Planet, Sign, Option<u8>)> + '_ {
+        chart.planets.iter().filter_map(|info| {
+            let planet = info.name.parse::<Planet>().ok()?;
+            Some((planet, Sign::from_longitude(info.longitude), info.house))
+        })
+    }
+
+    /// Adds a chart, replacing any chart with the same id
+    pub fn insert(&mut self, id: u64, name: impl Into<String>, chart: ChartResponse) {
+        self.remove(id);
+        for (planet, sign, house) in Self::placements(&chart) {
+            self.by_sign.entry((planet, sign)).or_default().insert(id);
+            if let Some(house) = house {
+                self.by_house.entry((planet, house)).or_default().insert(id);
+            }
+        }
+        self.charts.insert(id, IndexedChart { name: name.into(), chart });
+    }
+
+    /// Removes a chart, returning whether there was one
+    pub fn remove(&mut self, id: u64) -> bool {
+        let Some(removed) = self.charts.remove(&id) else {
+            return false;
+        };
+        for (planet, sign, house) in Self::placements(&removed.chart) {
+            unindex(&mut self.by_sign, (planet, sign), id);
+            if let Some(house) = house {
+                unindex(&mut self.by_house, (planet, house), id);
+            }
+        }
+        true
+    }
+
+    /// Ids of the charts with a placement, or `None` for a criterion that isn't indexed
+    fn indexed(&self, criterion: &Criterion) -> Option<BTreeSet<u64>> {
+        let ids = match *criterion {
+            Criterion::Sign(planet, sign) => self.by_sign.get(&(planet, sign)),
+            Criterion::House(planet, house) => self.by_house.get(&(planet, house)),
+            Criterion::Retrograde(..) | Criterion::Aspect { .. } => return None,
+        };
+        Some(ids.cloned().unwrap_or_default())
+    }
+
+    /// Ids of the charts a search for `criteria` checks: those in every
+    /// indexed sign and house placement asked for, or all of them without any
+    pub fn candidates(&self, criteria: &[Criterion]) -> Vec<u64> {
+        criteria
+            .iter()
+            .filter_map(|criterion| self.indexed(criterion))
+            .reduce(|both, ids| both.intersection(&ids).copied().collect())
+            .map_or_else(|| self.charts.keys().copied().collect(), |ids| ids.into_iter().collect())
+    }
+
+    /// The charts meeting every criterion, in id order
+    pub fn search(&self, criteria: &[Criterion]) -> Vec<ChartMatch> {
+        self.candidates(criteria)
+            .into_iter()
+            .filter_map(|id| {
+                let indexed = self.charts.get(&id)?;
+                let factors = criteria
+                    .iter()
+                    .map(|criterion| criterion.matches(&indexed.chart))
+                    .collect::<Option<Vec<String>>>()?;
+                Some(ChartMatch {
+                    id,
+                    name: indexed.name.clone(),
+                    factors,
+                })
+            })
+            .collect()
+    }
+}
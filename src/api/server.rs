@@ -1,27 +1,91 @@
+use crate::api::audit::record_calculation;
+use crate::api::capabilities::capabilities;
+use crate::api::symbols::symbols;
+use crate::api::computation::{
+    self, calculation_meta, motion_anomaly_count, ChartComputation, NatalChartOptions, CHART_PLANETS, REQUIRED_PLANETS,
+};
+use crate::api::payload::{BodyLimit, JsonBody};
+use crate::api::settings::AppConfig;
+use crate::api::pool::calculation_pool;
+use crate::api::render::{render_permits, render_store, RenderJobState, RenderOutcome};
+use crate::api::versioning::{ApiVersion, API_VERSION_HEADER};
 use crate::api::types::{
-    AspectInfo, ChartRequest, ChartResponse, HouseInfo, PlanetInfo, SynastryRequest,
-    SynastryResponse, SynastryAspectInfo, TransitRequest, TransitResponse, TransitData, TransitInfo,
+    AtDegreeRequest, AtDegreeResponse, BodyPositionInfo, DegradedOptionInfo, BodyWarningInfo, CapabilitiesResponse, ChartRequest, ChartResponse, ComparedChart, CompareRequest, CompareResponse, CriticalDegrees, CuspComparisonInfo, CycleEventInfo, DateExpr, CyclesQuery, CyclesResponse, Diagnostics,
+    HighlightsRequest, HouseCompareRequest, HouseCompareResponse, HouseInfo, HouseSystemPairInfo, Location, MundaneRequest, MundaneResponse, NearbyMidpointInfo, NearbyPointInfo, PlanetHousesInfo,
+    MoonQuery, MoonResponse, RenderJobInfo, RenderRequest,
+    ReturnDirection, ReturnOccurrence, ReturnPassInfo, ReturnRequest, ReturnResponse, SabianInfo, SynastryRequest, SynastryBatchItem, SynastryBatchRequest, SynastryBatchResponse,
+    SynastryAspectInfo, SynastryResponse, TransitRequest, TransitResponse, TransitData, TransitInfo,
+    unsupported_options_message, CHART_SCHEMA_VERSION, HouseOverlayInfo, MAX_CYCLE_RANGE_DAYS, MAX_TRANSIT_CALENDAR_DAYS, MAX_TRANSIT_CALENDAR_EVENTS, MAX_ECLIPSE_LOOKBACK_MONTHS, MAX_TRANSIT_SERIES, DEFAULT_CALENDAR_PLANETS, TransitCalendarQuery, NowResponse, PointInfo, PositionsQuery, SymbolsQuery, SymbolsResponse, WeatherQuery, WeatherResponse,
 };
-use crate::calc::aspects::{calculate_aspects_with_options, calculate_transit_aspects_with_options, calculate_cross_aspects_with_options, calculate_synastry_aspects};
-use crate::calc::houses::calculate_houses;
-use crate::calc::planets::calculate_planet_positions;
-use crate::calc::utils::date_to_julian;
-use crate::core::types::HouseSystem;
+use crate::api::positions::positions_at;
+use crate::api::prefetch::Prefetcher;
+use crate::api::weather::weather_report;
+use crate::api::moon::{moon_range, moon_table, parse_moon_step};
+use crate::calc::angles::normalize;
+use crate::calc::aspects::{get_aspect_types, synastry_aspects_between, AspectFilter, AspectSet, ChartPoint};
+use crate::calc::compatibility::compatibility_scores;
+use crate::calc::comparison::{composite_cusps, composite_midpoints, house_overlays, placement_diff, PlacedChart};
+use crate::calc::coordinates::local_sidereal_time;
+use crate::calc::cycles::{has_synodic_cycle, synodic_events_with_source};
+use crate::calc::degree_lookup::degree_lookup;
+use crate::calc::ephemeris::{default_ephemeris, ephemeris_backend, Ephemeris, SiderealEphemeris, TopocentricEphemeris};
+use crate::calc::finite;
+use crate::calc::house_compare::compare_house_systems;
+use crate::calc::houses::house_cache_hits;
+use crate::calc::mundane::{find_event, MundaneEvent};
+use crate::calc::planets::{planet_longitude_at, Planet};
+use crate::calc::positions::{check_ayanamsa, parse_bodies, parse_chart_ayanamsa, Center, Zodiac, DEFAULT_POSITION_BODIES};
+use crate::calc::precession::precess_longitude;
+use crate::calc::returns::{next_return, previous_return, return_period, returns_between_with_source, PlanetReturn};
+use crate::calc::rulers::Sign;
+use crate::calc::sect::DEFAULT_TWILIGHT;
+use crate::calc::utils::{date_to_julian, julian_to_date};
+use crate::core::types::{Ayanamsa, EphemerisSource, HouseSystem, NodeType};
+use crate::core::AstrologError;
+use crate::data::i18n::Lang;
+use crate::interpret::{content_pack, interpret_chart};
+use crate::calc::eclipses::{eclipse_contacts, eclipses_between, DEFAULT_ECLIPSE_LOOKBACK_MONTHS, ECLIPSE_POINT_ORB};
+use crate::calc::transit_search::{exact_transits, parse_transiting, NATAL_PLANETS};
+use crate::io::ical::transit_calendar;
+use crate::io::text::render_listing;
 use crate::utils::logging::log_request_error;
-use crate::charts::{generate_natal_svg, generate_synastry_svg, generate_transit_svg};
+use crate::charts::styles::get_styles;
+use crate::charts::{
+    generate_natal_svg_with_options, generate_synastry_svg_with_options, generate_thumbnail_svg,
+    generate_transit_svg_with_options, DegreeSet, Highlights, LegendPosition, SvgOptions, THUMBNAIL_SIZE,
+};
 use actix_web::{
-    web, HttpResponse, Responder, middleware,
+    web, HttpRequest, HttpResponse, HttpResponseBuilder, Responder, middleware,
     dev::{ServiceRequest, ServiceResponse, Service, Transform},
+    http::header::{Accept, ContentType, Header, HeaderName, HeaderValue, ACCEPT_LANGUAGE, LOCATION},
+    http::StatusCode,
     Error
 };
+use serde::Serialize;
 use serde_json::json;
-use std::cell::RefCell;
+use std::cell::{Cell, RefCell};
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Instant;
+use tracing::Instrument;
 use std::future::{ready, Ready, Future};
 use std::pin::Pin;
 use std::task::{Context, Poll};
 
 thread_local! {
     static CLIENT_IP: RefCell<String> = RefCell::new("unknown".to_string());
+    static REQUEST_ID: RefCell<String> = const { RefCell::new(String::new()) };
+    static API_VERSION: Cell<ApiVersion> = const { Cell::new(ApiVersion::V1) };
+    static PREFERRED_LANG: Cell<Option<Lang>> = const { Cell::new(None) };
+}
+
+static NEXT_REQUEST_ID: AtomicU64 = AtomicU64::new(1);
+
+/// Short unique id tying a response to its log entries
+fn new_request_id() -> String {
+    let n = NEXT_REQUEST_ID.fetch_add(1, Ordering::Relaxed);
+    format!("{:x}-{:04x}", chrono::Utc::now().timestamp_millis(), n & 0xffff)
 }
 
 pub struct IpMiddleware;
@@ -68,15 +132,48 @@ where
             .unwrap_or("unknown")
             .to_string();
         
-        CLIENT_IP.with(|cell| {
-            *cell.borrow_mut() = ip;
-        });
+        let request_id = new_request_id();
+        let endpoint = req.path().to_string();
+        let version = ApiVersion::from_path(&endpoint);
+        let accept_language = req
+            .headers()
+            .get(ACCEPT_LANGUAGE)
+            .and_then(|value| value.to_str().ok())
+            .and_then(Lang::from_accept_language);
+        let span = tracing::info_span!("request", request_id = %request_id, endpoint = %endpoint);
+        let start = Instant::now();
 
-        let fut = self.service.call(req);
-        Box::pin(async move {
-            let res = fut.await?;
-            Ok(res)
-        })
+        let mut fut = Box::pin(self.service.call(req));
+        Box::pin(
+            async move {
+                // Requests interleave on a worker thread, so restore this request's
+                // context every time its handler is polled
+                let res = std::future::poll_fn(|cx| {
+                    CLIENT_IP.with(|cell| cell.borrow_mut().clone_from(&ip));
+                    REQUEST_ID.with(|cell| cell.borrow_mut().clone_from(&request_id));
+                    API_VERSION.with(|cell| cell.set(version));
+                    PREFERRED_LANG.with(|cell| cell.set(accept_language));
+                    fut.as_mut().poll(cx)
+                })
+                .await;
+                let mut res = res?;
+
+                tracing::info!(
+                    request_id = %request_id,
+                    endpoint = %endpoint,
+                    status = res.status().as_u16(),
+                    duration_ms = start.elapsed().as_secs_f64() * 1000.0,
+                    "request completed"
+                );
+                if let Ok(value) = HeaderValue::from_str(&request_id) {
+                    res.headers_mut().insert(HeaderName::from_static("x-request-id"), value);
+                }
+                res.headers_mut()
+                    .insert(HeaderName::from_static(API_VERSION_HEADER), HeaderValue::from(version.number()));
+                Ok(res)
+            }
+            .instrument(span),
+        )
     }
 }
 
@@ -84,770 +181,2267 @@ fn get_client_ip() -> String {
     CLIENT_IP.with(|cell| cell.borrow().clone())
 }
 
-#[allow(dead_code)]
-fn parse_house_system(system: &str) -> HouseSystem {
-    match system.to_lowercase().as_str() {
-        "placidus" => HouseSystem::Placidus,
-        "koch" => HouseSystem::Koch,
-        "equal" => HouseSystem::Equal,
-        "wholesign" => HouseSystem::WholeSign,
-        "campanus" => HouseSystem::Campanus,
-        "regiomontanus" => HouseSystem::Regiomontanus,
-        _ => HouseSystem::Placidus, // Default to Placidus
-    }
+fn get_request_id() -> String {
+    REQUEST_ID.with(|cell| cell.borrow().clone())
 }
 
-async fn generate_chart_with_transits(req: web::Json<ChartRequest>) -> impl Responder {
-    let jd = date_to_julian(req.date);
-    let house_system = parse_house_system(&req.house_system);
-
-    // Calculate natal chart
-    match calculate_planet_positions(jd) {
-        Ok(natal_positions) => {
-            let planets: Vec<PlanetInfo> = natal_positions
-                .iter()
-                .enumerate()
-                .map(|(i, pos)| {
-                    let mut info: PlanetInfo = (*pos).into();
-                    info.name = match i {
-                        0 => "Sun".to_string(),
-                        1 => "Moon".to_string(),
-                        2 => "Mercury".to_string(),
-                        3 => "Venus".to_string(),
-                        4 => "Mars".to_string(),
-                        5 => "Jupiter".to_string(),
-                        6 => "Saturn".to_string(),
-                        7 => "Uranus".to_string(),
-                        8 => "Neptune".to_string(),
-                        9 => "Pluto".to_string(),
-                        _ => format!("Planet {}", i + 1),
-                    };
-                    info
-                })
-                .collect();
-
-            // Calculate houses
-            let houses = match calculate_houses(jd, req.latitude, req.longitude, house_system) {
-                Ok(h) => h,
-                Err(e) => {
-                    log_request_error(
-                        "chart",
-                        &get_client_ip(),
-                        &json!(req.0).to_string(),
-                        &e.to_string(),
-                    );
-                    return HttpResponse::InternalServerError().body(e.to_string());
-                }
-            };
-            let house_info: Vec<HouseInfo> = houses
-                .iter()
-                .map(|h| HouseInfo {
-                    number: h.number,
-                    longitude: h.longitude,
-                    latitude: h.latitude,
-                })
-                .collect();
-
-            // Calculate natal aspects
-            let natal_aspects = calculate_aspects_with_options(&natal_positions, req.include_minor_aspects);
-            let aspect_info: Vec<AspectInfo> = natal_aspects
-                .iter()
-                .map(|a| AspectInfo {
-                    aspect: format!("{:?}", a.aspect_type),
-                    orb: a.orb,
-                    planet1: a.planet1.clone(),
-                    planet2: a.planet2.clone(),
-                })
-                .collect();
-
-            // Handle transit data if provided
-            let transit_data = if let Some(transit_info) = &req.transit {
-                let transit_jd = date_to_julian(transit_info.date);
-                
-                match calculate_planet_positions(transit_jd) {
-                    Ok(transit_positions) => {
-                        let transit_planets: Vec<PlanetInfo> = transit_positions
-                            .iter()
-                            .enumerate()
-                            .map(|(i, pos)| {
-                                let mut info: PlanetInfo = (*pos).into();
-                                info.name = match i {
-                                    0 => "Sun".to_string(),
-                                    1 => "Moon".to_string(),
-                                    2 => "Mercury".to_string(),
-                                    3 => "Venus".to_string(),
-                                    4 => "Mars".to_string(),
-                                    5 => "Jupiter".to_string(),
-                                    6 => "Saturn".to_string(),
-                                    7 => "Uranus".to_string(),
-                                    8 => "Neptune".to_string(),
-                                    9 => "Pluto".to_string(),
-                                    _ => format!("Planet {}", i + 1),
-                                };
-                                info
-                            })
-                            .collect();
-
-                        // Calculate transit aspects
-                        let transit_aspects = calculate_transit_aspects_with_options(&transit_positions, req.include_minor_aspects);
-                        let transit_aspect_info: Vec<AspectInfo> = transit_aspects
-                            .iter()
-                            .map(|a| AspectInfo {
-                                aspect: format!("{:?}", a.aspect_type),
-                                orb: a.orb,
-                                planet1: a.planet1.clone(),
-                                planet2: a.planet2.clone(),
-                            })
-                            .collect();
-
-                        // Calculate transit-to-natal aspects
-                        let cross_aspects = calculate_cross_aspects_with_options(&natal_positions, &transit_positions, req.include_minor_aspects);
-                        let cross_aspect_info: Vec<AspectInfo> = cross_aspects
-                            .iter()
-                            .map(|a| AspectInfo {
-                                aspect: format!("{:?}", a.aspect_type),
-                                orb: a.orb,
-                                planet1: a.planet1.clone(),
-                                planet2: a.planet2.clone(),
-                            })
-                            .collect();
-
-                        Some(TransitData {
-                            date: transit_info.date,
-                            latitude: transit_info.latitude,
-                            longitude: transit_info.longitude,
-                            planets: transit_planets,
-                            aspects: transit_aspect_info,
-                            transit_to_natal_aspects: cross_aspect_info,
-                        })
-                    }
-                    Err(e) => {
-                        log_request_error(
-                            "chart_transit",
-                            &get_client_ip(),
-                            &json!(req.0).to_string(),
-                            &e.to_string(),
-                        );
-                        return HttpResponse::InternalServerError().body(format!("Failed to calculate transit positions: {}", e));
-                    }
-                }
-            } else {
-                // Use default transit values if no transit data provided
-                let default_transit = TransitInfo::default();
-                let transit_jd = date_to_julian(default_transit.date);
-                
-                match calculate_planet_positions(transit_jd) {
-                    Ok(transit_positions) => {
-                        let transit_planets: Vec<PlanetInfo> = transit_positions
-                            .iter()
-                            .enumerate()
-                            .map(|(i, pos)| {
-                                let mut info: PlanetInfo = (*pos).into();
-                                info.name = match i {
-                                    0 => "Sun".to_string(),
-                                    1 => "Moon".to_string(),
-                                    2 => "Mercury".to_string(),
-                                    3 => "Venus".to_string(),
-                                    4 => "Mars".to_string(),
-                                    5 => "Jupiter".to_string(),
-                                    6 => "Saturn".to_string(),
-                                    7 => "Uranus".to_string(),
-                                    8 => "Neptune".to_string(),
-                                    9 => "Pluto".to_string(),
-                                    _ => format!("Planet {}", i + 1),
-                                };
-                                info
-                            })
-                            .collect();
-
-                        // Calculate transit aspects
-                        let transit_aspects = calculate_transit_aspects_with_options(&transit_positions, req.include_minor_aspects);
-                        let transit_aspect_info: Vec<AspectInfo> = transit_aspects
-                            .iter()
-                            .map(|a| AspectInfo {
-                                aspect: format!("{:?}", a.aspect_type),
-                                orb: a.orb,
-                                planet1: a.planet1.clone(),
-                                planet2: a.planet2.clone(),
-                            })
-                            .collect();
-
-                        // Calculate transit-to-natal aspects
-                        let cross_aspects = calculate_cross_aspects_with_options(&natal_positions, &transit_positions, req.include_minor_aspects);
-                        let cross_aspect_info: Vec<AspectInfo> = cross_aspects
-                            .iter()
-                            .map(|a| AspectInfo {
-                                aspect: format!("{:?}", a.aspect_type),
-                                orb: a.orb,
-                                planet1: a.planet1.clone(),
-                                planet2: a.planet2.clone(),
-                            })
-                            .collect();
-
-                        Some(TransitData {
-                            date: default_transit.date,
-                            latitude: default_transit.latitude,
-                            longitude: default_transit.longitude,
-                            planets: transit_planets,
-                            aspects: transit_aspect_info,
-                            transit_to_natal_aspects: cross_aspect_info,
-                        })
-                    }
-                    Err(e) => {
-                        log_request_error(
-                            "chart_default_transit",
-                            &get_client_ip(),
-                            &json!(req.0).to_string(),
-                            &e.to_string(),
-                        );
-                        return HttpResponse::InternalServerError().body(format!("Failed to calculate default transit positions: {}", e));
-                    }
-                }
-            };
+fn get_api_version() -> ApiVersion {
+    API_VERSION.with(Cell::get)
+}
 
-            let response = ChartResponse {
-                chart_type: "natal".to_string(),
-                date: req.date,
-                latitude: req.latitude,
-                longitude: req.longitude,
-                house_system: req.house_system.clone(),
-                ayanamsa: req.ayanamsa.clone(),
-                planets,
-                houses: house_info,
-                aspects: aspect_info,
-                transit: transit_data,
-                svg_chart: None, // Will be set below
-            };
+/// The language the request's `Accept-Language` header prefers, if it's one we have
+fn get_accept_language() -> Option<Lang> {
+    PREFERRED_LANG.with(Cell::get)
+}
 
-            // Generate SVG chart
-            match generate_natal_svg(&response) {
-                Ok(svg_chart) => {
-                    let mut final_response = response;
-                    final_response.svg_chart = Some(svg_chart);
-                    HttpResponse::Ok().json(final_response)
-                }
-                Err(svg_error) => {
-                    log_request_error(
-                        "chart",
-                        &get_client_ip(),
-                        &json!(req.0).to_string(),
-                        &format!("SVG generation failed: {}", svg_error),
-                    );
-                    HttpResponse::InternalServerError().body(format!("SVG generation failed: {}", svg_error))
-                }
-            }
+/// Why a calculation couldn't produce a response. The handler has already logged it.
+#[derive(Debug)]
+struct CalculationFailure {
+    status: StatusCode,
+    message: String,
+}
+
+impl CalculationFailure {
+    fn bad_request(message: impl Into<String>) -> Self {
+        Self {
+            status: StatusCode::BAD_REQUEST,
+            message: message.into(),
+        }
+    }
+
+    fn internal(message: impl Into<String>) -> Self {
+        Self {
+            status: StatusCode::INTERNAL_SERVER_ERROR,
+            message: message.into(),
+        }
+    }
+
+    fn unprocessable(message: impl Into<String>) -> Self {
+        Self {
+            status: StatusCode::UNPROCESSABLE_ENTITY,
+            message: message.into(),
+        }
+    }
+
+    /// A value that came out NaN or infinite fails the request with a 422, as
+    /// the calculation can't be done for these inputs; other errors are internal
+    fn calculation(error: &AstrologError) -> Self {
+        if finite::is_not_finite(error) {
+            Self::unprocessable(error.to_string())
+        } else {
+            Self::internal(error.to_string())
         }
+    }
+}
+
+/// Runs a handler's calculation on the calculation pool and answers with its
+/// JSON, in the shape of the API version the request was made to.
+///
+/// The request's client IP and id are carried over to the pool thread for
+/// logging, along with its preferred language, and the response is serialized there too, so the HTTP worker only
+/// waits.
+async fn respond<T, F>(calculation: F) -> HttpResponse
+where
+    F: FnOnce() -> Result<T, CalculationFailure> + Send + 'static,
+    T: Serialize,
+{
+    let version = get_api_version();
+    respond_as(ContentType::json(), calculation, move |body| {
+        version.to_json(&body).map_err(|e| CalculationFailure::internal(e.to_string()))
+    })
+    .await
+}
+
+/// Like `respond`, with the body rendered by `render` and sent as `content_type`
+async fn respond_as<T, F, R>(content_type: ContentType, calculation: F, render: R) -> HttpResponse
+where
+    F: FnOnce() -> Result<T, CalculationFailure> + Send + 'static,
+    R: FnOnce(T) -> Result<String, CalculationFailure> + Send + 'static,
+{
+    let (ip, request_id, version) = (get_client_ip(), get_request_id(), get_api_version());
+    let accept_language = get_accept_language();
+    let result = calculation_pool()
+        .run(move || {
+            CLIENT_IP.with(|cell| *cell.borrow_mut() = ip);
+            REQUEST_ID.with(|cell| *cell.borrow_mut() = request_id);
+            PREFERRED_LANG.with(|cell| cell.set(accept_language));
+            calculation().and_then(render)
+        })
+        .await;
+    match result {
+        Ok(Ok(body)) => HttpResponse::Ok().content_type(content_type).body(body),
+        Ok(Err(failure)) => failure_response(version, failure.status, &failure.message),
         Err(e) => {
-            log_request_error(
-                "chart",
-                &get_client_ip(),
-                &json!(req.0).to_string(),
-                &e.to_string(),
-            );
-            HttpResponse::InternalServerError().body(e.to_string())
+            log::error!("Request {} failed in the calculation pool: {}", get_request_id(), e);
+            failure_response(version, StatusCode::INTERNAL_SERVER_ERROR, &e)
         }
     }
 }
 
-#[allow(dead_code)]
-async fn generate_natal_chart(req: web::Json<ChartRequest>) -> impl Responder {
-    let jd = date_to_julian(req.date);
-    let house_system = parse_house_system(&req.house_system);
-
-    match calculate_planet_positions(jd) {
-        Ok(positions) => {
-            let planets: Vec<PlanetInfo> = positions
-                .iter()
-                .enumerate()
-                .map(|(i, pos)| {
-                    let mut info: PlanetInfo = (*pos).into();
-                    info.name = match i {
-                        0 => "Sun".to_string(),
-                        1 => "Moon".to_string(),
-                        2 => "Mercury".to_string(),
-                        3 => "Venus".to_string(),
-                        4 => "Mars".to_string(),
-                        5 => "Jupiter".to_string(),
-                        6 => "Saturn".to_string(),
-                        7 => "Uranus".to_string(),
-                        8 => "Neptune".to_string(),
-                        9 => "Pluto".to_string(),
-                        _ => format!("Planet {}", i + 1),
-                    };
-                    info
-                })
-                .collect();
-
-            // Calculate houses
-            let houses = match calculate_houses(jd, req.latitude, req.longitude, house_system) {
-                Ok(h) => h,
-                Err(e) => {
-                    log_request_error(
-                        "natal",
-                        &get_client_ip(),
-                        &json!(req.0).to_string(),
-                        &e.to_string(),
-                    );
-                    return HttpResponse::InternalServerError().body(e.to_string());
-                }
-            };
-            let _house_info: Vec<HouseInfo> = houses
-                .iter()
-                .map(|h| HouseInfo {
-                    number: h.number,
-                    longitude: h.longitude,
-                    latitude: h.latitude,
-                })
-                .collect();
-
-            // Calculate aspects
-            let aspects = calculate_aspects_with_options(&positions, req.include_minor_aspects);
-            let aspect_info: Vec<AspectInfo> = aspects
-                .iter()
-                .map(|a| AspectInfo {
-                    aspect: format!("{:?}", a.aspect_type),
-                    orb: a.orb,
-                    planet1: a.planet1.clone(),
-                    planet2: a.planet2.clone(),
-                })
-                .collect();
-
-            let response = ChartResponse {
-                chart_type: "natal".to_string(),
-                date: req.date,
-                latitude: req.latitude,
-                longitude: req.longitude,
-                house_system: req.house_system.clone(),
-                ayanamsa: req.ayanamsa.clone(),
-                planets,
-                houses: _house_info,
-                aspects: aspect_info,
-                transit: None,
-                svg_chart: None, // Will be set below
-            };
+/// `body` as JSON in the shape of the request's API version
+fn versioned_json<T: Serialize>(mut response: HttpResponseBuilder, body: &T) -> HttpResponse {
+    let version = get_api_version();
+    match version.to_json(body) {
+        Ok(json) => response.content_type(ContentType::json()).body(json),
+        Err(e) => failure_response(version, StatusCode::INTERNAL_SERVER_ERROR, &e.to_string()),
+    }
+}
 
-            // Generate SVG chart
-            match generate_natal_svg(&response) {
-                Ok(svg_chart) => {
-                    let mut final_response = response;
-                    final_response.svg_chart = Some(svg_chart);
-                    HttpResponse::Ok().json(final_response)
-                }
-                Err(svg_error) => {
-                    log_request_error(
-                        "chart",
-                        &get_client_ip(),
-                        &json!(req.0).to_string(),
-                        &format!("SVG generation failed: {}", svg_error),
-                    );
-                    HttpResponse::InternalServerError().body(format!("SVG generation failed: {}", svg_error))
-                }
-            }
+/// A failed request's response, plain text for v1 and JSON for later versions
+fn failure_response(version: ApiVersion, status: StatusCode, message: &str) -> HttpResponse {
+    let body = version.error_body(status.canonical_reason().unwrap_or("Error"), message);
+    let mut response = HttpResponse::build(status);
+    if version.structured_errors() {
+        response.content_type(ContentType::json());
+    }
+    response.body(body)
+}
+
+/// Answers with the chart as JSON, or as the plain-text listing when the
+/// client prefers `text/plain`
+async fn respond_chart<F>(http: &HttpRequest, calculation: F) -> HttpResponse
+where
+    F: FnOnce() -> Result<ChartResponse, CalculationFailure> + Send + 'static,
+{
+    if wants_listing(http) {
+        respond_as(ContentType::plaintext(), calculation, |chart| Ok(render_listing(&chart))).await
+    } else {
+        respond(calculation).await
+    }
+}
+
+/// Whether the `Accept` header ranks `text/plain` first
+fn wants_listing(http: &HttpRequest) -> bool {
+    Accept::parse(http)
+        .ok()
+        .and_then(|accept| accept.ranked().into_iter().next())
+        .is_some_and(|mime| mime.essence_str() == "text/plain")
+}
+
+/// Wall-clock time spent in each stage of a chart calculation
+struct StageTimer {
+    start: Instant,
+    last: Instant,
+    stages: Vec<(&'static str, f64)>,
+    /// `house_cache_hits` when the calculation started
+    house_cache_hits: u64,
+}
+
+impl StageTimer {
+    fn new() -> Self {
+        let now = Instant::now();
+        Self {
+            start: now,
+            last: now,
+            stages: Vec::new(),
+            house_cache_hits: house_cache_hits(),
         }
-        Err(e) => {
-            log_request_error(
-                "natal",
-                &get_client_ip(),
-                &json!(req.0).to_string(),
-                &e.to_string(),
-            );
-            HttpResponse::InternalServerError().body(e.to_string())
+    }
+
+    /// Records the time since the previous stage ended
+    fn lap(&mut self, stage: &'static str) {
+        let now = Instant::now();
+        self.stages.push((stage, (now - self.last).as_secs_f64() * 1000.0));
+        self.last = now;
+    }
+
+    fn total_ms(&self) -> f64 {
+        self.start.elapsed().as_secs_f64() * 1000.0
+    }
+
+    /// Logs the stage timings for the current request
+    fn log(&self, chart_type: &str) {
+        let stages = self
+            .stages
+            .iter()
+            .map(|(stage, ms)| format!("{}={:.2}", stage, ms))
+            .collect::<Vec<_>>()
+            .join(" ");
+        tracing::info!(
+            request_id = %get_request_id(),
+            chart_type,
+            total_ms = self.total_ms(),
+            stages = %stages,
+            "chart calculated"
+        );
+    }
+
+    fn diagnostics(&self, ephemeris: String) -> Diagnostics {
+        let mut timings_ms: BTreeMap<String, f64> = self
+            .stages
+            .iter()
+            .map(|(stage, ms)| (stage.to_string(), *ms))
+            .collect();
+        timings_ms.insert("total".to_string(), self.total_ms());
+        Diagnostics {
+            request_id: get_request_id(),
+            timings_ms,
+            ephemeris,
+            cache_hit: house_cache_hits() > self.house_cache_hits,
         }
     }
 }
 
-#[allow(dead_code)]
-async fn generate_transit_chart(req: web::Json<TransitRequest>) -> impl Responder {
-    let natal_jd = date_to_julian(req.natal_date);
-    let transit_jd = date_to_julian(req.transit_date);
-    let house_system = parse_house_system(&req.house_system);
-
-    match (
-        calculate_planet_positions(natal_jd),
-        calculate_planet_positions(transit_jd),
-    ) {
-        (Ok(natal_positions), Ok(transit_positions)) => {
-            let natal_planets: Vec<PlanetInfo> = natal_positions
-                .iter()
-                .enumerate()
-                .map(|(i, pos)| {
-                    let mut info: PlanetInfo = (*pos).into();
-                    info.name = match i {
-                        0 => "Sun".to_string(),
-                        1 => "Moon".to_string(),
-                        2 => "Mercury".to_string(),
-                        3 => "Venus".to_string(),
-                        4 => "Mars".to_string(),
-                        5 => "Jupiter".to_string(),
-                        6 => "Saturn".to_string(),
-                        7 => "Uranus".to_string(),
-                        8 => "Neptune".to_string(),
-                        9 => "Pluto".to_string(),
-                        _ => format!("Planet {}", i + 1),
-                    };
-                    info
-                })
-                .collect();
-
-            let transit_planets: Vec<PlanetInfo> = transit_positions
-                .iter()
-                .enumerate()
-                .map(|(i, pos)| {
-                    let mut info: PlanetInfo = (*pos).into();
-                    info.name = match i {
-                        0 => "Sun".to_string(),
-                        1 => "Moon".to_string(),
-                        2 => "Mercury".to_string(),
-                        3 => "Venus".to_string(),
-                        4 => "Mars".to_string(),
-                        5 => "Jupiter".to_string(),
-                        6 => "Saturn".to_string(),
-                        7 => "Uranus".to_string(),
-                        8 => "Neptune".to_string(),
-                        9 => "Pluto".to_string(),
-                        _ => format!("Planet {}", i + 1),
-                    };
-                    info
-                })
-                .collect();
-
-            // Calculate houses for the natal chart
-            let houses = match calculate_houses(natal_jd, req.latitude, req.longitude, house_system)
-            {
-                Ok(h) => h,
-                Err(e) => {
-                    log_request_error(
-                        "transit",
-                        &get_client_ip(),
-                        &json!(req.0).to_string(),
-                        &e.to_string(),
-                    );
-                    return HttpResponse::InternalServerError().body(e.to_string());
-                }
-            };
-            let house_info: Vec<HouseInfo> = houses
-                .iter()
-                .map(|h| HouseInfo {
-                    number: h.number,
-                    longitude: h.longitude,
-                    latitude: h.latitude,
-                })
-                .collect();
-
-            // Calculate natal aspects
-            let natal_aspects = calculate_aspects_with_options(&natal_positions, req.include_minor_aspects);
-            let natal_aspect_info: Vec<AspectInfo> = natal_aspects
-                .iter()
-                .map(|a| AspectInfo {
-                    aspect: format!("{:?}", a.aspect_type),
-                    orb: a.orb,
-                    planet1: a.planet1.clone(),
-                    planet2: a.planet2.clone(),
-                })
-                .collect();
-
-            // Calculate transit aspects with tight orbs
-            let transit_aspects = calculate_transit_aspects_with_options(&transit_positions, req.include_minor_aspects);
-            let transit_aspect_info: Vec<AspectInfo> = transit_aspects
-                .iter()
-                .map(|a| AspectInfo {
-                    aspect: format!("{:?}", a.aspect_type),
-                    orb: a.orb,
-                    planet1: a.planet1.clone(),
-                    planet2: a.planet2.clone(),
-                })
-                .collect();
-
-            let response = TransitResponse {
-                chart_type: "transit".to_string(),
-                natal_date: req.natal_date,
-                transit_date: req.transit_date,
-                latitude: req.latitude,
-                longitude: req.longitude,
-                house_system: req.house_system.clone(),
-                ayanamsa: req.ayanamsa.clone(),
-                natal_planets,
-                transit_planets,
-                houses: house_info,
-                natal_aspects: natal_aspect_info,
-                transit_aspects: transit_aspect_info,
-                svg_chart: None, // Will be set below
-            };
+/// Resolves the per-request ephemeris setting, falling back to the ephemeris's default
+fn parse_ephemeris_source(ephemeris: &dyn Ephemeris, value: Option<&str>) -> Result<EphemerisSource, String> {
+    match value {
+        Some(source) => source.parse(),
+        None => Ok(ephemeris.default_source()),
+    }
+}
 
-            // Generate SVG chart
-            match generate_transit_svg(&response) {
-                Ok(svg_chart) => {
-                    let mut final_response = response;
-                    final_response.svg_chart = Some(svg_chart);
-                    HttpResponse::Ok().json(final_response)
-                }
-                Err(svg_error) => {
-                    log_request_error(
-                        "transit",
-                        &get_client_ip(),
-                        &json!(req.0).to_string(),
-                        &format!("SVG generation failed: {}", svg_error),
-                    );
-                    HttpResponse::InternalServerError().body(format!("SVG generation failed: {}", svg_error))
+/// Resolves the per-request lunar node choice, defaulting to the true node
+fn parse_node_type(value: Option<&str>) -> Result<NodeType, String> {
+    Ok(value.map(str::parse).transpose()?.unwrap_or_default())
+}
+
+/// Resolves the per-request SVG glyph mode, language and detail, defaulting to full
+/// detail with text glyphs in the `Accept-Language` header's language, or English.
+/// The legend is drawn only with `include_legend`, on the right unless placed.
+fn parse_svg_options(
+    glyph_mode: Option<&str>,
+    lang: Option<&str>,
+    detail: Option<&str>,
+    wheel_style: Option<&str>,
+    layers: Option<&str>,
+    include_legend: bool,
+    legend_position: Option<&str>,
+) -> Result<SvgOptions, String> {
+    let legend: LegendPosition = legend_position.map(str::parse).transpose()?.unwrap_or_default();
+    Ok(SvgOptions {
+        glyph_mode: glyph_mode.map(str::parse).transpose()?.unwrap_or_default(),
+        lang: lang.map(str::parse).transpose()?.or_else(get_accept_language).unwrap_or_default(),
+        detail: detail.map(str::parse).transpose()?.unwrap_or_default(),
+        wheel_style: wheel_style.map(str::parse).transpose()?.unwrap_or_default(),
+        layers: layers.map(str::parse).transpose()?.unwrap_or_default(),
+        legend: include_legend.then_some(legend),
+        ..SvgOptions::default()
+    })
+}
+
+/// The natal wheel overlays a request asks for, none when it sends no `highlights`
+fn parse_highlights(highlights: Option<&HighlightsRequest>) -> Result<Highlights, String> {
+    let Some(highlights) = highlights else {
+        return Ok(Highlights::default());
+    };
+    let critical_degrees = match &highlights.critical_degrees {
+        None | Some(CriticalDegrees::Classical(false)) => DegreeSet::default(),
+        Some(CriticalDegrees::Classical(true)) => DegreeSet::classical(),
+        Some(CriticalDegrees::Degrees(degrees)) => degrees
+            .iter()
+            .map(|&degree| match degree {
+                d if d.fract() == 0.0 && (0.0..360.0).contains(&d) => Ok(d as u16),
+                d => Err(format!("critical_degrees must be whole degrees from 0 to 359, got {}", d)),
+            })
+            .collect::<Result<_, _>>()?,
+    };
+    Ok(Highlights {
+        critical_degrees,
+        aries_points: highlights.aries_points,
+        angular: highlights.angular,
+    })
+}
+
+/// Transit positions for one moment with their aspects to each other and to the natal
+/// chart, and whether the aspect filter capped any of those lists
+fn transit_data(
+    req: &ChartRequest,
+    natal: &ChartComputation,
+    ephemeris: Arc<dyn Ephemeris>,
+    transit_info: &TransitInfo,
+    date: chrono::DateTime<chrono::Utc>,
+    source: EphemerisSource,
+    aspect_filter: &AspectFilter,
+) -> Result<(TransitData, bool), AstrologError> {
+    let include_minor_aspects = req.include_minor_aspects();
+    let transit = ChartComputation::from_ephemeris(
+        ephemeris,
+        date_to_julian(date),
+        source,
+        &CHART_PLANETS,
+        &REQUIRED_PLANETS,
+    )?
+        .with_custom_aspects(natal.custom_aspects.clone())
+        .with_lang(natal.lang());
+    let precessed = req.precession_correction.then(|| natal.precessed_to(transit.jd));
+    let (aspects, transit_truncated) = aspect_filter.apply(transit.transit_aspects(include_minor_aspects));
+    let (transit_to_natal_aspects, cross_truncated) =
+        aspect_filter.apply(precessed.as_ref().unwrap_or(natal).cross_aspects(&transit, include_minor_aspects));
+    let warnings = transit.warnings();
+    let data = TransitData {
+        date,
+        resolved_date: transit_info.date.is_expression().then_some(date),
+        latitude: transit_info.latitude,
+        longitude: transit_info.longitude,
+        planets: transit.into_planets_in(natal),
+        aspects,
+        transit_to_natal_aspects,
+        precessed_natal_planets: precessed
+            .map(|chart| chart.body_points().into_iter().map(PointInfo::from).collect())
+            .unwrap_or_default(),
+        warnings,
+    };
+    Ok((data, transit_truncated || cross_truncated))
+}
+
+/// Checks a chart request's options against what the ephemeris can calculate.
+/// One it can't honor fails the request with a 422, unless the request sets
+/// `allow_degraded`; then the request is returned with those options replaced
+/// by what will be used, along with the list of them.
+fn degrade_chart_request(
+    label: &str,
+    req: &ChartRequest,
+    ephemeris: &dyn Ephemeris,
+) -> Result<(ChartRequest, Vec<DegradedOptionInfo>), CalculationFailure> {
+    let mut degraded_req = req.clone();
+    let degraded = degraded_req.degrade(&ephemeris.capabilities(), ephemeris.default_source());
+    if !degraded.is_empty() && !req.allow_degraded {
+        let e = unsupported_options_message(&degraded);
+        log_request_error(label, &get_client_ip(), &json!(req).to_string(), &e);
+        return Err(CalculationFailure::unprocessable(e));
+    }
+    Ok((degraded_req, degraded))
+}
+
+/// The ephemeris a chart request's natal positions come from: the server's,
+/// seen from the birthplace when the request is topocentric, in the sidereal
+/// zodiac of `ayanamsa` when there is one
+fn chart_ephemeris(req: &ChartRequest, ephemeris: Arc<dyn Ephemeris>, ayanamsa: Option<Ayanamsa>) -> Arc<dyn Ephemeris> {
+    let ephemeris: Arc<dyn Ephemeris> = match req.observer() {
+        Some(observer) => Arc::new(TopocentricEphemeris::new(ephemeris, observer)),
+        None => ephemeris,
+    };
+    zodiac_ephemeris(ephemeris, ayanamsa)
+}
+
+/// The ephemeris measuring longitudes in the sidereal zodiac of `ayanamsa`, or
+/// the tropical one when there's none
+fn zodiac_ephemeris(ephemeris: Arc<dyn Ephemeris>, ayanamsa: Option<Ayanamsa>) -> Arc<dyn Ephemeris> {
+    match ayanamsa {
+        Some(ayanamsa) => Arc::new(SiderealEphemeris::new(ephemeris, ayanamsa)),
+        None => ephemeris,
+    }
+}
+
+async fn generate_chart_with_transits(
+    http: HttpRequest,
+    req: JsonBody<ChartRequest>,
+    ephemeris: web::Data<dyn Ephemeris>,
+) -> impl Responder {
+    respond_chart(&http, move || chart_with_transits(web::Json(req.into_inner()), ephemeris.into_inner())).await
+}
+
+fn chart_with_transits(req: web::Json<ChartRequest>, ephemeris: Arc<dyn Ephemeris>) -> Result<ChartResponse, CalculationFailure> {
+    let (req, degraded_options) = degrade_chart_request("chart", &req, &*ephemeris)?;
+    let req = web::Json(req);
+    let mut timer = StageTimer::new();
+    let now = chrono::Utc::now();
+    let date = match req.date.resolve("date", now, None) {
+        Ok(d) => d,
+        Err(e) => {
+            log_request_error("chart", &get_client_ip(), &json!(req.0).to_string(), &e);
+            return Err(CalculationFailure::bad_request(e));
+        }
+    };
+    let jd = date_to_julian(date);
+    let ayanamsa = match parse_chart_ayanamsa(&req.ayanamsa) {
+        Ok(parsed) => parsed,
+        Err(e) => {
+            log_request_error("chart", &get_client_ip(), &json!(req.0).to_string(), &e);
+            return Err(CalculationFailure::bad_request(e));
+        }
+    };
+    let source = match parse_ephemeris_source(&*ephemeris, req.ephemeris.as_deref()) {
+        Ok(s) => s,
+        Err(e) => {
+            log_request_error("chart", &get_client_ip(), &json!(req.0).to_string(), &e);
+            return Err(CalculationFailure::bad_request(e));
+        }
+    };
+    let svg_options = match parse_svg_options(
+        req.glyph_mode.as_deref(),
+        req.lang.as_deref(),
+        req.svg_detail.as_deref(),
+        req.wheel_style.as_deref(),
+        req.svg_layers.as_deref(),
+        req.include_legend,
+        req.legend_position.as_deref(),
+    )
+    .and_then(|o| Ok(SvgOptions {
+        highlights: parse_highlights(req.highlights.as_ref())?,
+        ..o
+    })) {
+        Ok(o) => o,
+        Err(e) => {
+            log_request_error("chart", &get_client_ip(), &json!(req.0).to_string(), &e);
+            return Err(CalculationFailure::bad_request(e));
+        }
+    };
+    let options = match NatalChartOptions::from_request(&req, source, svg_options.lang) {
+        Ok(o) => o,
+        Err(e) => {
+            log_request_error("chart", &get_client_ip(), &json!(req.0).to_string(), &e);
+            return Err(CalculationFailure::bad_request(e));
+        }
+    };
+    if req.transits.len() > MAX_TRANSIT_SERIES {
+        let e = format!("At most {} transits can be calculated per chart", MAX_TRANSIT_SERIES);
+        log_request_error("chart", &get_client_ip(), &json!(req.0).to_string(), &e);
+        return Err(CalculationFailure::bad_request(e));
+    }
+
+    // Calculate natal positions and houses; transits stay geocentric, in the natal zodiac
+    let chart_ephemeris = chart_ephemeris(&req, Arc::clone(&ephemeris), ayanamsa);
+    let natal = match computation::natal_computation(chart_ephemeris, jd, &options, |stage| timer.lap(stage)) {
+        Ok(c) => c,
+        Err(e) => {
+            log_request_error("chart", &get_client_ip(), &json!(req.0).to_string(), &e.to_string());
+            return Err(CalculationFailure::calculation(&e));
+        }
+    };
+
+    // Use default transit values if no transit data provided
+    let (transit_infos, error_context, error_label) = match (&req.transit, req.transits.is_empty()) {
+        (Some(transit_info), true) => (vec![transit_info.clone()], "chart_transit", "transit"),
+        (None, true) => (vec![TransitInfo::default()], "chart_default_transit", "default transit"),
+        (None, false) => (req.transits.clone(), "chart_transit", "transit"),
+        (Some(_), false) => {
+            let e = "Send either transit or transits, not both";
+            log_request_error("chart", &get_client_ip(), &json!(req.0).to_string(), e);
+            return Err(CalculationFailure::bad_request(e));
+        }
+    };
+
+    let mut transit_series = Vec::with_capacity(transit_infos.len());
+    let mut transits_truncated = false;
+    for (i, transit_info) in transit_infos.iter().enumerate() {
+        let field = if req.transits.is_empty() { "transit.date".to_string() } else { format!("transits[{}].date", i) };
+        let transit_date = match transit_info.date.resolve(&field, now, Some(date)) {
+            Ok(d) => d,
+            Err(e) => {
+                log_request_error("chart", &get_client_ip(), &json!(req.0).to_string(), &e);
+                return Err(CalculationFailure::bad_request(e));
+            }
+        };
+        match transit_data(&req, &natal, zodiac_ephemeris(Arc::clone(&ephemeris), ayanamsa), transit_info, transit_date, source, &options.aspect_filter) {
+            Ok((data, truncated)) => {
+                transits_truncated |= truncated;
+                transit_series.push(data);
+            }
+            Err(e) => {
+                log_request_error(
+                    error_context,
+                    &get_client_ip(),
+                    &json!(req.0).to_string(),
+                    &e.to_string(),
+                );
+                return Err(CalculationFailure {
+                    message: format!("Failed to calculate {} positions: {}", error_label, e),
+                    ..CalculationFailure::calculation(&e)
+                });
+            }
+        }
+    }
+    // A single transit keeps its original place in the response
+    let (transit_data, transit_series) = if req.transits.is_empty() {
+        (transit_series.pop(), Vec::new())
+    } else {
+        (None, transit_series)
+    };
+
+    timer.lap("transits");
+
+    // Natal aspects and the rest of the chart
+    let mut response = match computation::natal_response(natal, date, &options, |stage| timer.lap(stage)) {
+        Ok(r) => r,
+        Err(e) => {
+            log_request_error("chart", &get_client_ip(), &json!(req.0).to_string(), &e.to_string());
+            return Err(CalculationFailure::calculation(&e));
+        }
+    };
+    response.resolved_date = req.date.is_expression().then_some(date);
+    response.degraded_options = degraded_options;
+    response.truncated |= transits_truncated;
+    response.transit = transit_data;
+    response.transits = transit_series;
+    let used_source = response.ephemeris.clone().unwrap_or_default();
+    if req.include_interpretation {
+        response.interpretation = Some(interpret_chart(&response));
+    }
+
+    // Generate SVG chart
+    match generate_natal_svg_with_options(&response, svg_options) {
+        Ok(svg_chart) => {
+            let mut final_response = response;
+            if let Some(meta) = final_response.meta.as_mut() {
+                meta.svg_bytes = Some(svg_chart.len());
+            }
+            final_response.svg_chart = Some(svg_chart);
+            if req.include_thumbnail {
+                match generate_thumbnail_svg(&final_response, THUMBNAIL_SIZE) {
+                    Ok(thumbnail) => final_response.svg_thumbnail = Some(thumbnail),
+                    Err(e) => {
+                        let message = format!("Thumbnail generation failed: {}", e);
+                        log_request_error("chart", &get_client_ip(), &json!(req.0).to_string(), &message);
+                        return Err(CalculationFailure::internal(message));
+                    }
                 }
             }
+            timer.lap("svg");
+            timer.log(&final_response.chart_type);
+            if req.debug {
+                final_response.diagnostics = Some(timer.diagnostics(used_source));
+            }
+            record_calculation("chart", &req, date, &final_response);
+            Ok(final_response)
         }
-        _ => {
+        Err(svg_error) => {
             log_request_error(
-                "transit",
+                "chart",
                 &get_client_ip(),
                 &json!(req.0).to_string(),
-                "Failed to calculate positions",
+                &format!("SVG generation failed: {}", svg_error),
             );
-            HttpResponse::InternalServerError().body("Failed to calculate positions")
+            Err(CalculationFailure::internal(format!("SVG generation failed: {}", svg_error)))
         }
     }
 }
 
+/// Calculates a natal chart outside the server, on the default ephemeris, as
+/// the `/api/chart/natal` endpoint would answer it
+pub fn calculate_natal_chart(req: ChartRequest) -> Result<ChartResponse, String> {
+    natal_chart(web::Json(req), default_ephemeris()).map_err(|failure| failure.message)
+}
+
 #[allow(dead_code)]
-async fn generate_synastry_chart(req: web::Json<SynastryRequest>) -> impl Responder {
-    let jd1 = date_to_julian(req.chart1.date);
-    let jd2 = date_to_julian(req.chart2.date);
-    let house_system = parse_house_system(&req.chart1.house_system);
-
-    match (
-        calculate_planet_positions(jd1),
-        calculate_planet_positions(jd2),
-    ) {
-        (Ok(positions1), Ok(positions2)) => {
-            let planets1: Vec<PlanetInfo> = positions1
-                .iter()
-                .enumerate()
-                .map(|(i, pos)| {
-                    let mut info: PlanetInfo = (*pos).into();
-                    info.name = match i {
-                        0 => "Sun".to_string(),
-                        1 => "Moon".to_string(),
-                        2 => "Mercury".to_string(),
-                        3 => "Venus".to_string(),
-                        4 => "Mars".to_string(),
-                        5 => "Jupiter".to_string(),
-                        6 => "Saturn".to_string(),
-                        7 => "Uranus".to_string(),
-                        8 => "Neptune".to_string(),
-                        9 => "Pluto".to_string(),
-                        _ => format!("Planet {}", i + 1),
-                    };
-                    info
-                })
-                .collect();
-
-            let planets2: Vec<PlanetInfo> = positions2
-                .iter()
-                .enumerate()
-                .map(|(i, pos)| {
-                    let mut info: PlanetInfo = (*pos).into();
-                    info.name = match i {
-                        0 => "Sun".to_string(),
-                        1 => "Moon".to_string(),
-                        2 => "Mercury".to_string(),
-                        3 => "Venus".to_string(),
-                        4 => "Mars".to_string(),
-                        5 => "Jupiter".to_string(),
-                        6 => "Saturn".to_string(),
-                        7 => "Uranus".to_string(),
-                        8 => "Neptune".to_string(),
-                        9 => "Pluto".to_string(),
-                        _ => format!("Planet {}", i + 1),
-                    };
-                    info
-                })
-                .collect();
-
-            // Calculate houses for both charts
-            let houses1 = match calculate_houses(
-                jd1,
-                req.chart1.latitude,
-                req.chart1.longitude,
-                house_system,
-            ) {
-                Ok(h) => h,
-                Err(e) => {
-                    log_request_error(
-                        "synastry",
-                        &get_client_ip(),
-                        &json!(req.0).to_string(),
-                        &e.to_string(),
-                    );
-                    return HttpResponse::InternalServerError().body(e.to_string());
-                }
-            };
-            let houses2 = match calculate_houses(
-                jd2,
-                req.chart2.latitude,
-                req.chart2.longitude,
-                house_system,
-            ) {
-                Ok(h) => h,
-                Err(e) => {
-                    log_request_error(
-                        "synastry",
-                        &get_client_ip(),
-                        &json!(req.0).to_string(),
-                        &e.to_string(),
-                    );
-                    return HttpResponse::InternalServerError().body(e.to_string());
-                }
-            };
+async fn generate_natal_chart(http: HttpRequest, req: JsonBody<ChartRequest>, ephemeris: web::Data<dyn Ephemeris>) -> impl Responder {
+    respond_chart(&http, move || natal_chart(web::Json(req.into_inner()), ephemeris.into_inner())).await
+}
 
-            let _house_info1: Vec<HouseInfo> = houses1
-                .iter()
-                .map(|h| HouseInfo {
-                    number: h.number,
-                    longitude: h.longitude,
-                    latitude: h.latitude,
-                })
-                .collect();
-            let _house_info2: Vec<HouseInfo> = houses2
-                .iter()
-                .map(|h| HouseInfo {
-                    number: h.number,
-                    longitude: h.longitude,
-                    latitude: h.latitude,
-                })
-                .collect();
-
-            // Calculate aspects for both charts
-            let aspects1 = calculate_aspects_with_options(&positions1, req.chart1.include_minor_aspects);
-            let aspects2 = calculate_aspects_with_options(&positions2, req.chart2.include_minor_aspects);
-            let aspect_info1: Vec<AspectInfo> = aspects1
-                .iter()
-                .map(|a| AspectInfo {
-                    aspect: format!("{:?}", a.aspect_type),
-                    orb: a.orb,
-                    planet1: a.planet1.clone(),
-                    planet2: a.planet2.clone(),
-                })
-                .collect();
-
-            let aspect_info2: Vec<AspectInfo> = aspects2
-                .iter()
-                .map(|a| AspectInfo {
-                    aspect: format!("{:?}", a.aspect_type),
-                    orb: a.orb,
-                    planet1: a.planet1.clone(),
-                    planet2: a.planet2.clone(),
-                })
-                .collect();
-
-            // Calculate synastry aspects
-            let synastry_aspects = calculate_synastry_aspects(&positions1, &positions2, req.chart1.include_minor_aspects);
-            let aspect_info: Vec<SynastryAspectInfo> = synastry_aspects
-                .iter()
-                .map(|a| SynastryAspectInfo {
-                    aspect: format!("{:?}", a.aspect_type),
-                    orb: a.orb,
-                    person1: a.planet1.clone(),
-                    person2: a.planet2.clone(),
-                })
-                .collect();
-
-            let chart1 = ChartResponse {
-                chart_type: "natal".to_string(),
-                date: req.chart1.date,
-                latitude: req.chart1.latitude,
-                longitude: req.chart1.longitude,
-                house_system: req.chart1.house_system.clone(),
-                ayanamsa: req.chart1.ayanamsa.clone(),
-                planets: planets1,
-                houses: _house_info1,
-                aspects: aspect_info1,
-                transit: None,
-                svg_chart: None, // No individual SVG for synastry to reduce response size
-            };
+fn natal_chart(req: web::Json<ChartRequest>, ephemeris: Arc<dyn Ephemeris>) -> Result<ChartResponse, CalculationFailure> {
+    let date = match req.date.resolve("date", chrono::Utc::now(), None) {
+        Ok(d) => d,
+        Err(e) => {
+            log_request_error("natal", &get_client_ip(), &json!(req.0).to_string(), &e);
+            return Err(CalculationFailure::bad_request(e));
+        }
+    };
+    natal_chart_at(req, date, date_to_julian(date), ephemeris)
+}
 
-            let chart2 = ChartResponse {
-                chart_type: "natal".to_string(),
-                date: req.chart2.date,
-                latitude: req.chart2.latitude,
-                longitude: req.chart2.longitude,
-                house_system: req.chart2.house_system.clone(),
-                ayanamsa: req.chart2.ayanamsa.clone(),
-                planets: planets2,
-                houses: _house_info2,
-                aspects: aspect_info2,
-                transit: None,
-                svg_chart: None, // No individual SVG for synastry to reduce response size
-            };
+/// The natal chart for a request whose date has been resolved to `date`, at
+/// Julian date `jd`
+fn natal_chart_at(
+    req: web::Json<ChartRequest>,
+    date: chrono::DateTime<chrono::Utc>,
+    jd: f64,
+    ephemeris: Arc<dyn Ephemeris>,
+) -> Result<ChartResponse, CalculationFailure> {
+    let (req, degraded_options) = degrade_chart_request("natal", &req, &*ephemeris)?;
+    let req = web::Json(req);
+    let mut timer = StageTimer::new();
+    let ayanamsa = match parse_chart_ayanamsa(&req.ayanamsa) {
+        Ok(a) => a,
+        Err(e) => {
+            log_request_error("natal", &get_client_ip(), &json!(req.0).to_string(), &e);
+            return Err(CalculationFailure::bad_request(e));
+        }
+    };
+    let source = match parse_ephemeris_source(&*ephemeris, req.ephemeris.as_deref()) {
+        Ok(s) => s,
+        Err(e) => {
+            log_request_error("natal", &get_client_ip(), &json!(req.0).to_string(), &e);
+            return Err(CalculationFailure::bad_request(e));
+        }
+    };
+    let svg_options = match parse_svg_options(
+        req.glyph_mode.as_deref(),
+        req.lang.as_deref(),
+        req.svg_detail.as_deref(),
+        req.wheel_style.as_deref(),
+        req.svg_layers.as_deref(),
+        req.include_legend,
+        req.legend_position.as_deref(),
+    )
+    .and_then(|o| Ok(SvgOptions {
+        highlights: parse_highlights(req.highlights.as_ref())?,
+        ..o
+    })) {
+        Ok(o) => o,
+        Err(e) => {
+            log_request_error("natal", &get_client_ip(), &json!(req.0).to_string(), &e);
+            return Err(CalculationFailure::bad_request(e));
+        }
+    };
+    let options = match NatalChartOptions::from_request(&req, source, svg_options.lang) {
+        Ok(o) => o,
+        Err(e) => {
+            log_request_error("natal", &get_client_ip(), &json!(req.0).to_string(), &e);
+            return Err(CalculationFailure::bad_request(e));
+        }
+    };
 
-            // Skip individual SVG generation for chart1 and chart2 to reduce response size
-            let response = SynastryResponse {
-                chart_type: "synastry".to_string(),
-                chart1,
-                chart2,
-                synastries: aspect_info,
-                svg_chart: None, // Will be set below
-            };
+    // Positions, houses and aspects, each timed
+    let chart_ephemeris = chart_ephemeris(&req, ephemeris, ayanamsa);
+    let mut response = match computation::natal_chart(chart_ephemeris, jd, date, &options, |stage| timer.lap(stage)) {
+        Ok(r) => r,
+        Err(e) => {
+            log_request_error("natal", &get_client_ip(), &json!(req.0).to_string(), &e.to_string());
+            return Err(CalculationFailure::calculation(&e));
+        }
+    };
+    response.resolved_date = req.date.is_expression().then_some(date);
+    response.degraded_options = degraded_options;
+    let used_source = response.ephemeris.clone().unwrap_or_default();
+    if req.include_interpretation {
+        response.interpretation = Some(interpret_chart(&response));
+    }
 
-            // Generate only the top-level synastry SVG chart
-            match generate_synastry_svg(&response) {
-                Ok(synastry_svg) => {
-                    let mut final_response = response;
-                    final_response.svg_chart = Some(synastry_svg);
-                    HttpResponse::Ok().json(final_response)
-                }
-                Err(svg_error) => {
-                    log_request_error(
-                        "synastry",
-                        &get_client_ip(),
-                        &json!(req.0).to_string(),
-                        &format!("Synastry SVG generation failed: {}", svg_error),
-                    );
-                    HttpResponse::InternalServerError().body(format!("Synastry SVG generation failed: {}", svg_error))
+    // Generate SVG chart
+    match generate_natal_svg_with_options(&response, svg_options) {
+        Ok(svg_chart) => {
+            let mut final_response = response;
+            if let Some(meta) = final_response.meta.as_mut() {
+                meta.svg_bytes = Some(svg_chart.len());
+            }
+            final_response.svg_chart = Some(svg_chart);
+            if req.include_thumbnail {
+                match generate_thumbnail_svg(&final_response, THUMBNAIL_SIZE) {
+                    Ok(thumbnail) => final_response.svg_thumbnail = Some(thumbnail),
+                    Err(e) => {
+                        let message = format!("Thumbnail generation failed: {}", e);
+                        log_request_error("natal", &get_client_ip(), &json!(req.0).to_string(), &message);
+                        return Err(CalculationFailure::internal(message));
+                    }
                 }
             }
+            timer.lap("svg");
+            timer.log(&final_response.chart_type);
+            if req.debug {
+                final_response.diagnostics = Some(timer.diagnostics(used_source));
+            }
+            record_calculation("natal", &req, date, &final_response);
+            Ok(final_response)
         }
-        _ => {
+        Err(svg_error) => {
             log_request_error(
-                "synastry",
+                "chart",
                 &get_client_ip(),
                 &json!(req.0).to_string(),
-                "Failed to calculate positions",
+                &format!("SVG generation failed: {}", svg_error),
             );
-            HttpResponse::InternalServerError().body("Failed to calculate positions")
+            Err(CalculationFailure::internal(format!("SVG generation failed: {}", svg_error)))
         }
     }
 }
 
 #[allow(dead_code)]
-async fn health_check() -> impl Responder {
-    // Check Swiss Ephemeris availability
-    let ephemeris_status = if std::path::Path::new("./ephe").exists() {
-        "available"
-    } else {
-        "unavailable"
-    };
-    
-    HttpResponse::Ok().json(json!({
-        "status": "healthy",
-        "timestamp": chrono::Utc::now().to_rfc3339(),
-        "service": "astrolog-rs",
-        "version": env!("CARGO_PKG_VERSION"),
-        "checks": {
-            "ephemeris": ephemeris_status,
-            "server": "running"
-        }
-    }))
+async fn generate_transit_chart(req: JsonBody<TransitRequest>, ephemeris: web::Data<dyn Ephemeris>) -> impl Responder {
+    respond(move || transit_chart(web::Json(req.into_inner()), ephemeris.into_inner())).await
 }
 
-#[allow(dead_code)]
-pub fn config(cfg: &mut web::ServiceConfig) {
-    // Health endpoint at root level for load balancers/monitoring
-    cfg.route("/health", web::get().to(health_check));
-    
-    // API endpoints under /api scope
-    cfg.service(
-        web::scope("/api")
-            .wrap(middleware::Logger::default())
-            .wrap(IpMiddleware)
-            .route("/chart", web::post().to(generate_chart_with_transits))
-            .route("/chart/natal", web::post().to(generate_natal_chart))
-            .route("/chart/transit", web::post().to(generate_transit_chart))
-            .route("/chart/synastry", web::post().to(generate_synastry_chart)),
-    );
+fn transit_chart(req: web::Json<TransitRequest>, ephemeris: Arc<dyn Ephemeris>) -> Result<TransitResponse, CalculationFailure> {
+    let mut timer = StageTimer::new();
+    let now = chrono::Utc::now();
+    let dates = req.natal_date.resolve("natal_date", now, None).and_then(|natal_date| {
+        Ok((natal_date, req.transit_date.resolve("transit_date", now, Some(natal_date))?))
+    });
+    let (natal_date, transit_date) = match dates {
+        Ok(dates) => dates,
+        Err(e) => {
+            log_request_error("transit", &get_client_ip(), &json!(req.0).to_string(), &e);
+            return Err(CalculationFailure::bad_request(e));
+        }
+    };
+    let natal_jd = date_to_julian(natal_date);
+    let transit_jd = date_to_julian(transit_date);
+    let (ayanamsa, house_system) = match parse_chart_ayanamsa(&req.ayanamsa).map(|ayanamsa| (ayanamsa, req.house_system)) {
+        Ok(parsed) => parsed,
+        Err(e) => {
+            log_request_error("transit", &get_client_ip(), &json!(req.0).to_string(), &e);
+            return Err(CalculationFailure::bad_request(e));
+        }
+    };
+    let source = match parse_ephemeris_source(&*ephemeris, req.ephemeris.as_deref()) {
+        Ok(s) => s,
+        Err(e) => {
+            log_request_error("transit", &get_client_ip(), &json!(req.0).to_string(), &e);
+            return Err(CalculationFailure::bad_request(e));
+        }
+    };
+    let svg_options = match parse_svg_options(
+        req.glyph_mode.as_deref(),
+        req.lang.as_deref(),
+        req.svg_detail.as_deref(),
+        req.wheel_style.as_deref(),
+        req.svg_layers.as_deref(),
+        req.include_legend,
+        req.legend_position.as_deref(),
+    ) {
+        Ok(o) => o,
+        Err(e) => {
+            log_request_error("transit", &get_client_ip(), &json!(req.0).to_string(), &e);
+            return Err(CalculationFailure::bad_request(e));
+        }
+    };
+    let aspect_filter = match req.aspect_filter() {
+        Ok(f) => f,
+        Err(e) => {
+            log_request_error("transit", &get_client_ip(), &json!(req.0).to_string(), &e);
+            return Err(CalculationFailure::bad_request(e));
+        }
+    };
+    let custom_aspects = match req.custom_aspects() {
+        Ok(c) => c,
+        Err(e) => {
+            log_request_error("transit", &get_client_ip(), &json!(req.0).to_string(), &e);
+            return Err(CalculationFailure::bad_request(e));
+        }
+    };
+
+    let ephemeris = zodiac_ephemeris(ephemeris, ayanamsa);
+    let (natal, transit) = match (
+        ChartComputation::from_ephemeris(Arc::clone(&ephemeris), natal_jd, source, &CHART_PLANETS, &REQUIRED_PLANETS),
+        ChartComputation::from_ephemeris(ephemeris, transit_jd, source, &CHART_PLANETS, &REQUIRED_PLANETS),
+    ) {
+        (Ok(natal), Ok(transit)) => (
+            natal
+                .with_custom_aspects(custom_aspects.clone())
+                .with_angle_aspects(req.include_angle_aspects)
+                .with_lang(svg_options.lang),
+            transit.with_custom_aspects(custom_aspects).with_lang(svg_options.lang),
+        ),
+        _ => {
+            log_request_error(
+                "transit",
+                &get_client_ip(),
+                &json!(req.0).to_string(),
+                "Failed to calculate positions",
+            );
+            return Err(CalculationFailure::internal("Failed to calculate positions"));
+        }
+    };
+    let used_source = if transit.source == EphemerisSource::Moshier {
+        transit.source
+    } else {
+        natal.source
+    };
+
+    timer.lap("positions");
+
+    // Calculate houses for the natal chart
+    let natal = match natal.with_houses(req.latitude, req.longitude, house_system) {
+        Ok(c) => c,
+        Err(e) => {
+            log_request_error(
+                "transit",
+                &get_client_ip(),
+                &json!(req.0).to_string(),
+                &e.to_string(),
+            );
+            return Err(CalculationFailure::calculation(&e));
+        }
+    };
+    let house_info = natal.house_info();
+    let angles = natal.angles();
+
+    timer.lap("houses");
+
+    // Calculate natal aspects, and transit aspects with tight orbs
+    let (natal_aspect_info, natal_truncated) = aspect_filter.apply(natal.aspects(req.include_minor_aspects));
+    let (transit_aspect_info, transit_truncated) =
+        aspect_filter.apply(transit.transit_aspects(req.include_minor_aspects));
+    let (natal_angle_aspects, angles_truncated) = if req.include_natal_angles {
+        aspect_filter.apply(natal.angle_aspects(&transit, req.include_minor_aspects))
+    } else {
+        (Vec::new(), false)
+    };
+
+    timer.lap("aspects");
+
+    let transit_house_positions = if req.include_transit_houses {
+        match natal.transit_houses(&transit) {
+            Ok(positions) => positions,
+            Err(e) => {
+                log_request_error("transit", &get_client_ip(), &json!(req.0).to_string(), &e);
+                return Err(CalculationFailure::bad_request(e));
+            }
+        }
+    } else {
+        Vec::new()
+    };
+
+    let mut meta = calculation_meta(natal_jd, used_source, Some(house_system));
+    meta.locale = svg_options.lang.to_string();
+    // Tagged like the bodies in cross aspects, e.g. "Transit Chiron"
+    let warnings = [("Natal", &natal), ("Transit", &transit)]
+        .into_iter()
+        .flat_map(|(tag, chart)| {
+            chart.warnings().into_iter().map(move |warning| BodyWarningInfo {
+                body: format!("{} {}", tag, warning.body),
+                ..warning
+            })
+        })
+        .collect();
+    let response = TransitResponse {
+        chart_type: "transit".to_string(),
+        natal_date,
+        transit_date,
+        resolved_date: req.transit_date.is_expression().then_some(transit_date),
+        latitude: req.latitude,
+        longitude: req.longitude,
+        house_system: req.house_system,
+        ayanamsa: req.ayanamsa.clone(),
+        transit_planets: transit.into_planets_in(&natal),
+        natal_planets: natal.into_planets(),
+        houses: house_info,
+        angles,
+        aspect_set: AspectSet::new(req.include_minor_aspects),
+        natal_aspects: natal_aspect_info,
+        transit_aspects: transit_aspect_info,
+        natal_angle_aspects,
+        transit_house_positions,
+        warnings,
+        ephemeris: Some(used_source.to_string()),
+        svg_chart: None, // Will be set below
+        diagnostics: None,
+        meta: Some(meta),
+        truncated: natal_truncated || transit_truncated || angles_truncated,
+    };
+
+    // Generate SVG chart
+    match generate_transit_svg_with_options(&response, svg_options) {
+        Ok(svg_chart) => {
+            let mut final_response = response;
+            if let Some(meta) = final_response.meta.as_mut() {
+                meta.svg_bytes = Some(svg_chart.len());
+            }
+            final_response.svg_chart = Some(svg_chart);
+            timer.lap("svg");
+            timer.log(&final_response.chart_type);
+            if req.debug {
+                final_response.diagnostics = Some(timer.diagnostics(used_source.to_string()));
+            }
+            Ok(final_response)
+        }
+        Err(svg_error) => {
+            log_request_error(
+                "transit",
+                &get_client_ip(),
+                &json!(req.0).to_string(),
+                &format!("SVG generation failed: {}", svg_error),
+            );
+            Err(CalculationFailure::internal(format!("SVG generation failed: {}", svg_error)))
+        }
+    }
+}
+
+#[allow(dead_code)]
+async fn generate_synastry_chart(req: JsonBody<SynastryRequest>, ephemeris: web::Data<dyn Ephemeris>) -> impl Responder {
+    respond(move || synastry_chart(web::Json(req.into_inner()), ephemeris.into_inner())).await
+}
+
+fn synastry_chart(req: web::Json<SynastryRequest>, ephemeris: Arc<dyn Ephemeris>) -> Result<SynastryResponse, CalculationFailure> {
+    let mut timer = StageTimer::new();
+    let now = chrono::Utc::now();
+    let dates = req.chart1.date.resolve("chart1.date", now, None).and_then(|date1| {
+        Ok((date1, req.chart2.date.resolve("chart2.date", now, None)?))
+    });
+    let (date1, date2) = match dates {
+        Ok(dates) => dates,
+        Err(e) => {
+            log_request_error("synastry", &get_client_ip(), &json!(req.0).to_string(), &e);
+            return Err(CalculationFailure::bad_request(e));
+        }
+    };
+    let jd1 = date_to_julian(date1);
+    let jd2 = date_to_julian(date2);
+    let (ayanamsa1, ayanamsa2, house_system) = match parse_chart_ayanamsa(&req.chart1.ayanamsa)
+        .and_then(|ayanamsa1| Ok((ayanamsa1, parse_chart_ayanamsa(&req.chart2.ayanamsa)?, req.chart1.house_system))) {
+        Ok(parsed) => parsed,
+        Err(e) => {
+            log_request_error("synastry", &get_client_ip(), &json!(req.0).to_string(), &e);
+            return Err(CalculationFailure::bad_request(e));
+        }
+    };
+    let sources = parse_ephemeris_source(&*ephemeris, req.chart1.ephemeris.as_deref())
+        .and_then(|s1| parse_ephemeris_source(&*ephemeris, req.chart2.ephemeris.as_deref()).map(|s2| (s1, s2)));
+    let (source1, source2) = match sources {
+        Ok(s) => s,
+        Err(e) => {
+            log_request_error("synastry", &get_client_ip(), &json!(req.0).to_string(), &e);
+            return Err(CalculationFailure::bad_request(e));
+        }
+    };
+    let node_types = parse_node_type(req.chart1.node_type.as_deref())
+        .and_then(|n1| parse_node_type(req.chart2.node_type.as_deref()).map(|n2| (n1, n2)));
+    let (node_type1, node_type2) = match node_types {
+        Ok(n) => n,
+        Err(e) => {
+            log_request_error("synastry", &get_client_ip(), &json!(req.0).to_string(), &e);
+            return Err(CalculationFailure::bad_request(e));
+        }
+    };
+    // Both charts share one wheel, so the first chart's rendering options apply
+    let svg_options = match parse_svg_options(
+        req.chart1.glyph_mode.as_deref(),
+        req.chart1.lang.as_deref(),
+        req.chart1.svg_detail.as_deref(),
+        req.chart1.wheel_style.as_deref(),
+        req.chart1.svg_layers.as_deref(),
+        req.chart1.include_legend,
+        req.chart1.legend_position.as_deref(),
+    ) {
+        Ok(o) => SvgOptions {
+            show_second_houses: req.chart1.show_second_houses,
+            ..o
+        },
+        Err(e) => {
+            log_request_error("synastry", &get_client_ip(), &json!(req.0).to_string(), &e);
+            return Err(CalculationFailure::bad_request(e));
+        }
+    };
+    // Each chart's own aspects use its filter; the aspects between them use the first chart's
+    let filters = req.chart1.aspect_filter().and_then(|f1| req.chart2.aspect_filter().map(|f2| (f1, f2)));
+    let (filter1, filter2) = match filters {
+        Ok(f) => f,
+        Err(e) => {
+            log_request_error("synastry", &get_client_ip(), &json!(req.0).to_string(), &e);
+            return Err(CalculationFailure::bad_request(e));
+        }
+    };
+    let customs = req.chart1.custom_aspects().and_then(|c1| req.chart2.custom_aspects().map(|c2| (c1, c2)));
+    let (custom_aspects1, custom_aspects2) = match customs {
+        Ok(c) => c,
+        Err(e) => {
+            log_request_error("synastry", &get_client_ip(), &json!(req.0).to_string(), &e);
+            return Err(CalculationFailure::bad_request(e));
+        }
+    };
+    let points = req.chart1.aspect_points().and_then(|p1| req.chart2.aspect_points().map(|p2| (p1, p2)));
+    let (points1, points2) = match points {
+        Ok(p) => p,
+        Err(e) => {
+            log_request_error("synastry", &get_client_ip(), &json!(req.0).to_string(), &e);
+            return Err(CalculationFailure::bad_request(e));
+        }
+    };
+    let twilights = req.chart1.sect_twilight().and_then(|t1| req.chart2.sect_twilight().map(|t2| (t1, t2)));
+    let (twilight1, twilight2) = match twilights {
+        Ok(t) => t,
+        Err(e) => {
+            log_request_error("synastry", &get_client_ip(), &json!(req.0).to_string(), &e);
+            return Err(CalculationFailure::bad_request(e));
+        }
+    };
+    let mansions = req.chart1.mansion_system().and_then(|m1| req.chart2.mansion_system().map(|m2| (m1, m2)));
+    let (mansions1, mansions2) = match mansions {
+        Ok(m) => m,
+        Err(e) => {
+            log_request_error("synastry", &get_client_ip(), &json!(req.0).to_string(), &e);
+            return Err(CalculationFailure::bad_request(e));
+        }
+    };
+    let coordinates = req.chart1.coordinate_system().and_then(|c1| req.chart2.coordinate_system().map(|c2| (c1, c2)));
+    let (coordinates1, coordinates2) = match coordinates {
+        Ok(c) => c,
+        Err(e) => {
+            log_request_error("synastry", &get_client_ip(), &json!(req.0).to_string(), &e);
+            return Err(CalculationFailure::bad_request(e));
+        }
+    };
+    let bodies = req.chart1.bodies().and_then(|b1| req.chart2.bodies().map(|b2| (b1, b2)));
+    let ((bodies1, required1), (bodies2, required2)) = match bodies {
+        Ok(b) => b,
+        Err(e) => {
+            log_request_error("synastry", &get_client_ip(), &json!(req.0).to_string(), &e);
+            return Err(CalculationFailure::bad_request(e));
+        }
+    };
+
+    let (chart1, chart2) = match (
+        ChartComputation::from_ephemeris(zodiac_ephemeris(Arc::clone(&ephemeris), ayanamsa1), jd1, source1, &bodies1, &required1),
+        ChartComputation::from_ephemeris(zodiac_ephemeris(ephemeris, ayanamsa2), jd2, source2, &bodies2, &required2),
+    ) {
+        (Ok(chart1), Ok(chart2)) => (
+            chart1
+                .with_custom_aspects(custom_aspects1)
+                .with_points(points1)
+                .with_angle_aspects(req.chart1.include_angle_aspects)
+                .with_subdivisions(req.chart1.include_decans, mansions1)
+                .with_coordinates(coordinates1)
+                .with_lang(svg_options.lang),
+            chart2
+                .with_custom_aspects(custom_aspects2)
+                .with_points(points2)
+                .with_angle_aspects(req.chart2.include_angle_aspects)
+                .with_subdivisions(req.chart2.include_decans, mansions2)
+                .with_coordinates(coordinates2)
+                .with_lang(svg_options.lang),
+        ),
+        _ => {
+            log_request_error(
+                "synastry",
+                &get_client_ip(),
+                &json!(req.0).to_string(),
+                "Failed to calculate positions",
+            );
+            return Err(CalculationFailure::internal("Failed to calculate positions"));
+        }
+    };
+
+    timer.lap("positions");
+
+    // Calculate houses for both charts
+    let houses = chart1
+        .with_houses(req.chart1.latitude, req.chart1.longitude, house_system)
+        .and_then(|chart1| {
+            chart2
+                .with_houses(req.chart2.latitude, req.chart2.longitude, house_system)
+                .map(|chart2| (chart1, chart2))
+        });
+    let (chart1, chart2) = match houses {
+        Ok(charts) => charts,
+        Err(e) => {
+            log_request_error(
+                "synastry",
+                &get_client_ip(),
+                &json!(req.0).to_string(),
+                &e.to_string(),
+            );
+            return Err(CalculationFailure::calculation(&e));
+        }
+    };
+    let (chart_ruler1, house_rulers1) = chart1.rulers(req.chart1.modern_rulers);
+    let (chart_ruler2, house_rulers2) = chart2.rulers(req.chart2.modern_rulers);
+    let (houses_summary1, house_emphasis1) = chart1.houses_summary();
+    let (houses_summary2, house_emphasis2) = chart2.houses_summary();
+    let house_info1 = chart1.house_info();
+    let house_info2 = chart2.house_info();
+    let (angles1, angles2) = (chart1.angles(), chart2.angles());
+    let sect1 = chart1.sect(req.chart1.latitude, req.chart1.longitude, twilight1);
+    let sect2 = chart2.sect(req.chart2.latitude, req.chart2.longitude, twilight2);
+
+    timer.lap("houses");
+
+    // Calculate aspects for both charts, then between them
+    let (minor1, minor2) = req.chart_minor_aspects();
+    let (aspect_info1, truncated1) = filter1.apply(chart1.aspects(minor1));
+    let (aspect_info2, truncated2) = filter2.apply(chart2.aspects(minor2));
+    let (aspect_info, truncated) = filter1.apply(chart1.synastry_aspects(&chart2, req.include_minor_aspects));
+
+    timer.lap("aspects");
+
+    let used_source1 = chart1.source;
+    let used_source2 = chart2.source;
+    let (meta1, meta2) = match chart1.meta(node_type1).and_then(|m1| chart2.meta(node_type2).map(|m2| (m1, m2))) {
+        Ok(m) => m,
+        Err(e) => {
+            log_request_error("synastry", &get_client_ip(), &json!(req.0).to_string(), &e.to_string());
+            return Err(CalculationFailure::calculation(&e));
+        }
+    };
+    let mut chart1 = ChartResponse {
+        chart_type: "natal".to_string(),
+        schema_version: CHART_SCHEMA_VERSION,
+        date: date1,
+        resolved_date: req.chart1.date.is_expression().then_some(date1),
+        latitude: req.chart1.latitude,
+        longitude: req.chart1.longitude,
+        house_system: req.chart1.house_system,
+        ayanamsa: req.chart1.ayanamsa.clone(),
+        warnings: chart1.warnings(),
+        degraded_options: Vec::new(),
+        boundary_warnings: Vec::new(),
+        angular: Vec::new(),
+        points: chart1.point_info(),
+        moon_mansion: chart1.moon_mansion(),
+        planets: chart1.into_planets(),
+        house_count: house_info1.len(),
+        houses: house_info1,
+        angles: angles1,
+        aspect_set: AspectSet::new(minor1),
+        aspects: aspect_info1,
+        transit: None,
+        transits: Vec::new(),
+        ephemeris: Some(used_source1.to_string()),
+        chart_ruler: chart_ruler1,
+        house_rulers: house_rulers1,
+        houses_summary: houses_summary1,
+        house_emphasis: house_emphasis1,
+        sidereal_time: Some(local_sidereal_time(jd1, req.chart1.longitude) / 15.0),
+        angle_speeds: None,
+        sect: sect1.map(|s| s.sect),
+        sun_altitude: sect1.map(|s| s.sun_altitude),
+        sect_ambiguous: sect1.is_some_and(|s| s.ambiguous),
+        svg_chart: None, // No individual SVG for synastry to reduce response size
+        svg_thumbnail: None,
+        diagnostics: None,
+        meta: Some(meta1),
+        interpretation: None,
+        truncated: truncated1,
+    };
+    if req.chart1.include_interpretation {
+        chart1.interpretation = Some(interpret_chart(&chart1));
+    }
+
+    let mut chart2 = ChartResponse {
+        chart_type: "natal".to_string(),
+        schema_version: CHART_SCHEMA_VERSION,
+        date: date2,
+        resolved_date: req.chart2.date.is_expression().then_some(date2),
+        latitude: req.chart2.latitude,
+        longitude: req.chart2.longitude,
+        house_system: req.chart2.house_system,
+        ayanamsa: req.chart2.ayanamsa.clone(),
+        warnings: chart2.warnings(),
+        degraded_options: Vec::new(),
+        boundary_warnings: Vec::new(),
+        angular: Vec::new(),
+        points: chart2.point_info(),
+        moon_mansion: chart2.moon_mansion(),
+        planets: chart2.into_planets(),
+        house_count: house_info2.len(),
+        houses: house_info2,
+        angles: angles2,
+        aspect_set: AspectSet::new(minor2),
+        aspects: aspect_info2,
+        transit: None,
+        transits: Vec::new(),
+        ephemeris: Some(used_source2.to_string()),
+        chart_ruler: chart_ruler2,
+        house_rulers: house_rulers2,
+        houses_summary: houses_summary2,
+        house_emphasis: house_emphasis2,
+        sidereal_time: Some(local_sidereal_time(jd2, req.chart2.longitude) / 15.0),
+        angle_speeds: None,
+        sect: sect2.map(|s| s.sect),
+        sun_altitude: sect2.map(|s| s.sun_altitude),
+        sect_ambiguous: sect2.is_some_and(|s| s.ambiguous),
+        svg_chart: None, // No individual SVG for synastry to reduce response size
+        svg_thumbnail: None,
+        diagnostics: None,
+        meta: Some(meta2),
+        interpretation: None,
+        truncated: truncated2,
+    };
+    if req.chart2.include_interpretation {
+        chart2.interpretation = Some(interpret_chart(&chart2));
+    }
+
+    let synastry_ephemeris = if used_source1 == used_source2 {
+        used_source1.to_string()
+    } else {
+        format!("{}, {}", used_source1, used_source2)
+    };
+
+    // Skip individual SVG generation for chart1 and chart2 to reduce response size
+    let response = SynastryResponse {
+        chart_type: "synastry".to_string(),
+        chart1,
+        chart2,
+        aspect_set: AspectSet::new(req.include_minor_aspects),
+        compatibility: compatibility_scores(&aspect_info).into(),
+        synastries: aspect_info,
+        svg_chart: None, // Will be set below
+        diagnostics: None,
+        truncated,
+    };
+
+    // Generate only the top-level synastry SVG chart
+    match generate_synastry_svg_with_options(&response, svg_options) {
+        Ok(synastry_svg) => {
+            let mut final_response = response;
+            final_response.svg_chart = Some(synastry_svg);
+            timer.lap("svg");
+            timer.log(&final_response.chart_type);
+            if req.chart1.debug || req.chart2.debug {
+                final_response.diagnostics = Some(timer.diagnostics(synastry_ephemeris));
+            }
+            Ok(final_response)
+        }
+        Err(svg_error) => {
+            log_request_error(
+                "synastry",
+                &get_client_ip(),
+                &json!(req.0).to_string(),
+                &format!("Synastry SVG generation failed: {}", svg_error),
+            );
+            Err(CalculationFailure::internal(format!("Synastry SVG generation failed: {}", svg_error)))
+        }
+    }
+}
+
+/// Compares one chart with many, calculating the base chart once and each
+/// partner on its own pool thread. A partner that fails gets an `error` in its
+/// item instead of failing the batch.
+async fn synastry_batch(
+    req: JsonBody<SynastryBatchRequest>,
+    settings: web::Data<AppConfig>,
+    ephemeris: web::Data<dyn Ephemeris>,
+) -> HttpResponse {
+    let version = get_api_version();
+    let req = req.into_inner();
+    let checked = req
+        .validate(settings.max_synastry_batch)
+        .and_then(|_| Ok((req.base.aspect_filter()?, req.base.house_system)));
+    let (aspect_filter, house_system) = match checked {
+        Ok(checked) => checked,
+        Err(e) => {
+            log_request_error("synastry_batch", &get_client_ip(), &json!(req).to_string(), &e);
+            return failure_response(version, StatusCode::BAD_REQUEST, &e);
+        }
+    };
+
+    let ephemeris = ephemeris.into_inner();
+    let (base_request, base_ephemeris) = (req.base.clone(), Arc::clone(&ephemeris));
+    let base = calculation_pool()
+        .run(in_request_context(move || synastry_batch_chart(&base_request, house_system, base_ephemeris)))
+        .await
+        .map_err(CalculationFailure::internal)
+        .and_then(|result| result);
+    let (include_minor, top_aspects) = (req.include_minor_aspects, req.top_aspects);
+    let base = match base {
+        Ok(base) => Arc::new(base.synastry_side(include_minor)),
+        Err(failure) => {
+            log_request_error("synastry_batch", &get_client_ip(), &json!(req.base).to_string(), &failure.message);
+            return failure_response(version, failure.status, &failure.message);
+        }
+    };
+
+    let partners: Vec<_> = req
+        .others
+        .into_iter()
+        .map(|other| {
+            let (base, ephemeris, aspect_filter) = (Arc::clone(&base), Arc::clone(&ephemeris), aspect_filter.clone());
+            let calculation = in_request_context(move || {
+                let partner = synastry_batch_chart(&other, house_system, ephemeris).map_err(|failure| {
+                    log_request_error("synastry_batch", &get_client_ip(), &json!(other).to_string(), &failure.message);
+                    failure.message
+                })?;
+                let (aspects, _) = aspect_filter.apply(base.aspects_to(&partner));
+                let mut tightest = aspects.clone();
+                tightest.sort_by(|a, b| a.orb.total_cmp(&b.orb));
+                tightest.truncate(top_aspects);
+                Ok::<_, String>((compatibility_scores(&aspects), tightest))
+            });
+            actix_web::rt::spawn(async move { calculation_pool().run(calculation).await })
+        })
+        .collect();
+
+    let mut results = Vec::with_capacity(partners.len());
+    for (index, partner) in partners.into_iter().enumerate() {
+        let outcome = match partner.await {
+            Ok(Ok(outcome)) => outcome,
+            Ok(Err(e)) => Err(e),
+            Err(e) => Err(e.to_string()),
+        };
+        results.push(match outcome {
+            Ok((scores, top_aspects)) => SynastryBatchItem {
+                index,
+                scores: Some(scores.into()),
+                top_aspects,
+                error: None,
+            },
+            Err(e) => SynastryBatchItem {
+                index,
+                scores: None,
+                top_aspects: Vec::new(),
+                error: Some(e),
+            },
+        });
+    }
+
+    versioned_json(
+        HttpResponse::Ok(),
+        &SynastryBatchResponse {
+            chart_type: "synastry_batch".to_string(),
+            aspect_set: AspectSet::new(include_minor),
+            results,
+        },
+    )
+}
+
+/// One chart of a synastry batch, as far as `synastry_chart` takes it before
+/// the aspects between the charts: positions, angle points, custom aspects
+/// and houses in the base chart's house system
+fn synastry_batch_chart(
+    chart: &ChartRequest,
+    house_system: HouseSystem,
+    ephemeris: Arc<dyn Ephemeris>,
+) -> Result<ChartComputation, CalculationFailure> {
+    let parsed = (|| {
+        let date = chart.date.resolve("date", chrono::Utc::now(), None)?;
+        let ayanamsa = parse_chart_ayanamsa(&chart.ayanamsa)?;
+        parse_node_type(chart.node_type.as_deref())?;
+        let source = parse_ephemeris_source(&*ephemeris, chart.ephemeris.as_deref())?;
+        Ok::<_, String>((date, ayanamsa, source, chart.bodies()?, chart.custom_aspects()?, chart.aspect_points()?))
+    })();
+    let (date, ayanamsa, source, (bodies, required), custom_aspects, points) =
+        parsed.map_err(CalculationFailure::bad_request)?;
+    ChartComputation::from_ephemeris(zodiac_ephemeris(ephemeris, ayanamsa), date_to_julian(date), source, &bodies, &required)
+        .and_then(|computation| {
+            computation
+                .with_custom_aspects(custom_aspects)
+                .with_points(points)
+                .with_angle_aspects(chart.include_angle_aspects)
+                .with_houses(chart.latitude, chart.longitude, house_system)
+        })
+        .map_err(|e| CalculationFailure::calculation(&e))
+}
+
+/// Wraps `calculation` to run on a pool thread with this request's client IP,
+/// id and preferred language, as `respond` does
+fn in_request_context<T>(calculation: impl FnOnce() -> T + Send + 'static) -> impl FnOnce() -> T + Send + 'static {
+    let (ip, request_id, accept_language) = (get_client_ip(), get_request_id(), get_accept_language());
+    move || {
+        CLIENT_IP.with(|cell| *cell.borrow_mut() = ip);
+        REQUEST_ID.with(|cell| *cell.borrow_mut() = request_id);
+        PREFERRED_LANG.with(|cell| cell.set(accept_language));
+        calculation()
+    }
+}
+
+async fn compare_charts(req: JsonBody<CompareRequest>) -> impl Responder {
+    respond(move || compare(web::Json(req.into_inner()))).await
+}
+
+/// Compares two charts from their longitudes alone; nothing is recalculated
+fn compare(req: web::Json<CompareRequest>) -> Result<CompareResponse, CalculationFailure> {
+    let (aspect_filter, composite_houses) = match req
+        .chart1
+        .validate("chart1")
+        .and_then(|_| req.chart2.validate("chart2"))
+        .and_then(|_| Ok((req.aspect_filter()?, req.composite_houses()?)))
+    {
+        Ok(parsed) => parsed,
+        Err(e) => {
+            log_request_error("compare", &get_client_ip(), &json!(req.0).to_string(), &e);
+            return Err(CalculationFailure::bad_request(e));
+        }
+    };
+    let (points1, points2) = (req.chart1.points(), req.chart2.points());
+    let (cusps1, cusps2) = (req.chart1.cusps(), req.chart2.cusps());
+
+    let (synastries, _) = aspect_filter.apply(
+        synastry_aspects_between(&points1, &points2, req.include_minor_aspects)
+            .into_iter()
+            .map(SynastryAspectInfo::from)
+            .collect(),
+    );
+    let overlays = |points: &[ChartPoint], other: &ComparedChart| match other.cusps() {
+        Some(cusps) => house_overlays(points, &cusps)
+            .into_iter()
+            .map(HouseOverlayInfo::from)
+            .collect(),
+        None => Vec::new(),
+    };
+
+    Ok(CompareResponse {
+        chart_type: "comparison".to_string(),
+        aspect_set: AspectSet::new(req.include_minor_aspects),
+        synastries,
+        chart1_in_chart2_houses: overlays(&points1, &req.chart2),
+        chart2_in_chart1_houses: overlays(&points2, &req.chart1),
+        composite: composite_midpoints(&points1, &points2)
+            .into_iter()
+            .map(PointInfo::from)
+            .collect(),
+        composite_houses: match (cusps1, cusps2) {
+            (Some(cusps1), Some(cusps2)) => composite_cusps(&cusps1, &cusps2, composite_houses)
+                .into_iter()
+                .zip(1..)
+                .map(|(longitude, number)| HouseInfo {
+                    number,
+                    longitude,
+                    latitude: 0.0,
+                })
+                .collect(),
+            _ => Vec::new(),
+        },
+        chart2_vs_chart1: placement_diff(
+            PlacedChart {
+                points: &points1,
+                cusps: cusps1.as_ref(),
+            },
+            PlacedChart {
+                points: &points2,
+                cusps: cusps2.as_ref(),
+            },
+        )
+        .into(),
+    })
+}
+
+async fn look_up_degree(req: JsonBody<AtDegreeRequest>) -> impl Responder {
+    respond(move || at_degree(web::Json(req.into_inner()))).await
+}
+
+/// What a chart has at one degree, from its longitudes alone
+fn at_degree(req: web::Json<AtDegreeRequest>) -> Result<AtDegreeResponse, CalculationFailure> {
+    let checked = req.chart.validate("chart").and_then(|_| {
+        if req.longitude.is_finite() {
+            Ok(())
+        } else {
+            Err(format!("Invalid longitude: {}", req.longitude))
+        }
+    });
+    if let Err(e) = checked {
+        log_request_error("at_degree", &get_client_ip(), &json!(req.0).to_string(), &e);
+        return Err(CalculationFailure::bad_request(e));
+    }
+
+    let cusps = req.chart.cusps();
+    let lookup = degree_lookup(req.longitude, &req.chart.points(), cusps.as_ref(), req.modern_rulers);
+    let degree_in_sign = (lookup.degree - 1) % 30 + 1;
+    Ok(AtDegreeResponse {
+        longitude: lookup.longitude,
+        sign: lookup.sign.to_string(),
+        sign_ruler: lookup.sign_ruler.to_string(),
+        decan: lookup.decan.into(),
+        term: lookup.term.into(),
+        house: lookup.house.map(|placement| placement.house),
+        house_fraction: lookup.house.map(|placement| placement.fraction),
+        nearby: lookup.nearby.into_iter().map(NearbyPointInfo::from).collect(),
+        midpoints: lookup.midpoints.into_iter().map(NearbyMidpointInfo::from).collect(),
+        sabian: SabianInfo {
+            degree: lookup.degree,
+            label: format!("{} {}", lookup.sign, degree_in_sign),
+            text: content_pack().sabian(lookup.degree).map(str::to_string),
+        },
+    })
+}
+
+async fn generate_house_compare(req: JsonBody<HouseCompareRequest>, ephemeris: web::Data<dyn Ephemeris>) -> impl Responder {
+    respond(move || house_compare(web::Json(req.into_inner()), ephemeris.into_inner())).await
+}
+
+/// Places one set of planetary positions in the houses of each requested system
+fn house_compare(req: web::Json<HouseCompareRequest>, ephemeris: Arc<dyn Ephemeris>) -> Result<HouseCompareResponse, CalculationFailure> {
+    let validated = (|| {
+        if !(-90.0..=90.0).contains(&req.latitude) || !(-180.0..=180.0).contains(&req.longitude) {
+            return Err(format!("Invalid location: latitude {}, longitude {}", req.latitude, req.longitude));
+        }
+        Ok((req.house_systems()?, parse_ephemeris_source(&*ephemeris, req.ephemeris.as_deref())?))
+    })();
+    let (systems, source) = match validated {
+        Ok(v) => v,
+        Err(e) => {
+            log_request_error("house_compare", &get_client_ip(), &json!(req.0).to_string(), &e);
+            return Err(CalculationFailure::bad_request(e));
+        }
+    };
+    let failed = |e: AstrologError| {
+        log_request_error("house_compare", &get_client_ip(), &json!(req.0).to_string(), &e.to_string());
+        CalculationFailure::calculation(&e)
+    };
+
+    let jd = date_to_julian(req.date);
+    let chart = ChartComputation::from_ephemeris(Arc::clone(&ephemeris), jd, source, &CHART_PLANETS, &REQUIRED_PLANETS)
+        .map_err(failed)?;
+    let mut system_cusps = Vec::with_capacity(systems.len());
+    for &system in &systems {
+        let houses = ephemeris.houses(jd, req.latitude, req.longitude, system).map_err(failed)?;
+        let cusps = houses.cusp_longitudes().ok_or_else(|| {
+            failed(AstrologError::CalculationError {
+                message: format!("{} returned {} cusps", system, houses.cusps.len()),
+            })
+        })?;
+        system_cusps.push((system, cusps));
+    }
+    let comparison = compare_house_systems(&chart.body_points(), &system_cusps);
+
+    Ok(HouseCompareResponse {
+        chart_type: "house_compare".to_string(),
+        date: req.date,
+        latitude: req.latitude,
+        longitude: req.longitude,
+        house_systems: systems,
+        planets: comparison.planets.into_iter().map(PlanetHousesInfo::from).collect(),
+        pairs: comparison.pairs.into_iter().map(HouseSystemPairInfo::from).collect(),
+        cusps: (0..12)
+            .map(|i| CuspComparisonInfo {
+                house: i as u8 + 1,
+                longitudes: system_cusps.iter().map(|(_, cusps)| cusps[i]).collect(),
+            })
+            .collect(),
+        meta: calculation_meta(jd, chart.source, None),
+    })
+}
+
+/// Finds the requested return of a body to its natal longitude
+fn find_return(
+    body: Planet,
+    natal_longitude: f64,
+    occurrence: ReturnOccurrence,
+    reference_date: chrono::DateTime<chrono::Utc>,
+    source: EphemerisSource,
+) -> Result<PlanetReturn, String> {
+    match occurrence {
+        ReturnOccurrence::Relative(ReturnDirection::Next) => {
+            next_return(body, natal_longitude, date_to_julian(reference_date), source)
+        }
+        ReturnOccurrence::Relative(ReturnDirection::Previous) => {
+            previous_return(body, natal_longitude, date_to_julian(reference_date), source)
+        }
+        ReturnOccurrence::Year { year } => {
+            let start = |year| {
+                chrono::NaiveDate::from_ymd_opt(year, 1, 1)
+                    .map(|date| date_to_julian(date.and_time(chrono::NaiveTime::MIN).and_utc()))
+                    .ok_or_else(|| format!("Invalid year {}", year))
+            };
+            returns_between_with_source(body, natal_longitude, start(year)?, start(year + 1)?, source)?
+                .into_iter()
+                .next()
+                .ok_or_else(|| format!("No {:?} return in {}", body, year))
+        }
+    }
+}
+
+async fn generate_return_chart(req: JsonBody<ReturnRequest>, ephemeris: web::Data<dyn Ephemeris>) -> impl Responder {
+    respond(move || return_chart(web::Json(req.into_inner()), ephemeris.into_inner())).await
+}
+
+fn return_chart(req: web::Json<ReturnRequest>, ephemeris: Arc<dyn Ephemeris>) -> Result<ReturnResponse, CalculationFailure> {
+    let validated = (|| {
+        let body = req.body;
+        if return_period(body).is_none() {
+            return Err(format!("{} has no return", body));
+        }
+        let source = parse_ephemeris_source(&*ephemeris, req.ephemeris.as_deref())?;
+        let svg_options = parse_svg_options(
+            req.glyph_mode.as_deref(),
+            req.lang.as_deref(),
+            req.svg_detail.as_deref(),
+            req.wheel_style.as_deref(),
+            req.svg_layers.as_deref(),
+            req.include_legend,
+            req.legend_position.as_deref(),
+        )?;
+        let node_type = parse_node_type(req.node_type.as_deref())?;
+        Ok((body, source, svg_options, node_type, req.aspect_filter()?, req.custom_aspects()?))
+    })();
+    let (body, source, svg_options, node_type, aspect_filter, custom_aspects) = match validated {
+        Ok(v) => v,
+        Err(e) => {
+            log_request_error("return", &get_client_ip(), &json!(req.0).to_string(), &e);
+            return Err(CalculationFailure::bad_request(e));
+        }
+    };
+    let (ayanamsa, house_system) = match parse_chart_ayanamsa(&req.ayanamsa).map(|ayanamsa| (ayanamsa, req.house_system)) {
+        Ok(parsed) => parsed,
+        Err(e) => {
+            log_request_error("return", &get_client_ip(), &json!(req.0).to_string(), &e);
+            return Err(CalculationFailure::bad_request(e));
+        }
+    };
+    let location = req.location.unwrap_or(Location {
+        latitude: req.latitude,
+        longitude: req.longitude,
+    });
+
+    let now = chrono::Utc::now();
+    let reference = req.reference_date.unwrap_or(DateExpr::Now);
+    let dates = req.natal_date.resolve("natal_date", now, None).and_then(|natal_date| {
+        Ok((natal_date, reference.resolve("reference_date", now, Some(natal_date))?))
+    });
+    let (natal_date, reference_date) = match dates {
+        Ok(dates) => dates,
+        Err(e) => {
+            log_request_error("return", &get_client_ip(), &json!(req.0).to_string(), &e);
+            return Err(CalculationFailure::bad_request(e));
+        }
+    };
+    let natal_longitude = match planet_longitude_at(body, date_to_julian(natal_date), source) {
+        Ok(longitude) => longitude,
+        Err(e) => {
+            log_request_error("return", &get_client_ip(), &json!(req.0).to_string(), &e);
+            return Err(CalculationFailure::internal(e));
+        }
+    };
+    // A body returns to its sidereal natal place when it has moved past its
+    // tropical one by the precession since birth, so a sidereal return is
+    // found as a precession-corrected one
+    let found = find_return(body, natal_longitude, req.occurrence, reference_date, source).and_then(|planet_return| {
+        if !req.precession_correction && ayanamsa.is_none() {
+            return Ok((planet_return, None));
+        }
+        // The precession moves the return by at most a few days, hardly changing
+        // the precession itself, so aiming at the first estimate's date is enough
+        let precessed =
+            precess_longitude(natal_longitude, date_to_julian(natal_date), planet_return.true_pass.jd);
+        Ok((find_return(body, precessed, req.occurrence, reference_date, source)?, Some(precessed)))
+    });
+    let (planet_return, precessed_natal_longitude) = match found {
+        Ok((planet_return, precessed)) => (planet_return, precessed.filter(|_| req.precession_correction)),
+        Err(e) => {
+            log_request_error("return", &get_client_ip(), &json!(req.0).to_string(), &e);
+            return Err(CalculationFailure::bad_request(e));
+        }
+    };
+
+    // The natal chart the return is measured against, at the birthplace, and
+    // the natal longitude reported in its zodiac
+    let ephemeris = zodiac_ephemeris(ephemeris, ayanamsa);
+    let natal_longitude = match ayanamsa.map(|ayanamsa| ephemeris.ayanamsa(date_to_julian(natal_date), ayanamsa, source)) {
+        Some(Ok(shift)) => normalize(natal_longitude - shift),
+        Some(Err(e)) => {
+            log_request_error("return", &get_client_ip(), &json!(req.0).to_string(), &e);
+            return Err(CalculationFailure::internal(e));
+        }
+        None => natal_longitude,
+    };
+    let natal = match ChartComputation::from_ephemeris(
+        Arc::clone(&ephemeris),
+        date_to_julian(natal_date),
+        source,
+        &CHART_PLANETS,
+        &REQUIRED_PLANETS,
+    )
+    .and_then(|chart| chart.with_houses(req.latitude, req.longitude, house_system))
+    {
+        Ok(c) => c,
+        Err(e) => {
+            log_request_error("return", &get_client_ip(), &json!(req.0).to_string(), &e.to_string());
+            return Err(CalculationFailure::calculation(&e));
+        }
+    };
+
+    let jd = planet_return.true_pass.jd;
+    let chart = match ChartComputation::from_ephemeris(ephemeris, jd, source, &CHART_PLANETS, &REQUIRED_PLANETS)
+        .and_then(|chart| chart.with_houses(location.latitude, location.longitude, house_system))
+    {
+        Ok(c) => c.with_custom_aspects(custom_aspects).with_lang(svg_options.lang),
+        Err(e) => {
+            log_request_error("return", &get_client_ip(), &json!(req.0).to_string(), &e.to_string());
+            return Err(CalculationFailure::calculation(&e));
+        }
+    };
+    let Some(date) = julian_to_date(jd) else {
+        let e = format!("Return date out of range: Julian date {}", jd);
+        log_request_error("return", &get_client_ip(), &json!(req.0).to_string(), &e);
+        return Err(CalculationFailure::internal(e));
+    };
+    let natal_cusps = natal.houses().and_then(|houses| houses.cusp_longitudes());
+    let return_cusps = chart.houses().and_then(|houses| houses.cusp_longitudes());
+    let vs_natal = placement_diff(
+        PlacedChart {
+            points: &natal.body_points(),
+            cusps: natal_cusps.as_ref(),
+        },
+        PlacedChart {
+            points: &chart.body_points(),
+            cusps: return_cusps.as_ref(),
+        },
+    );
+    let (chart_ruler_info, house_ruler_info) = chart.rulers(req.modern_rulers);
+    let (houses_summary, house_emphasis) = chart.houses_summary();
+    let house_info = chart.house_info();
+    let angles = chart.angles();
+    let (aspect_info, truncated) = aspect_filter.apply(chart.aspects(req.include_minor_aspects));
+    let sect = chart.sect(location.latitude, location.longitude, DEFAULT_TWILIGHT);
+    let used_source = chart.source;
+    let meta = match chart.meta(node_type) {
+        Ok(m) => m,
+        Err(e) => {
+            log_request_error("return", &get_client_ip(), &json!(req.0).to_string(), &e.to_string());
+            return Err(CalculationFailure::calculation(&e));
+        }
+    };
+
+    let mut return_chart = ChartResponse {
+        chart_type: "return".to_string(),
+        schema_version: CHART_SCHEMA_VERSION,
+        date,
+        resolved_date: None,
+        latitude: location.latitude,
+        longitude: location.longitude,
+        house_system: req.house_system,
+        ayanamsa: req.ayanamsa.clone(),
+        warnings: chart.warnings(),
+        degraded_options: Vec::new(),
+        boundary_warnings: Vec::new(),
+        angular: Vec::new(),
+        planets: chart.into_planets(),
+        points: Vec::new(),
+        moon_mansion: None,
+        house_count: house_info.len(),
+        houses: house_info,
+        angles,
+        aspect_set: AspectSet::new(req.include_minor_aspects),
+        aspects: aspect_info,
+        transit: None,
+        transits: Vec::new(),
+        ephemeris: Some(used_source.to_string()),
+        chart_ruler: chart_ruler_info,
+        house_rulers: house_ruler_info,
+        houses_summary,
+        house_emphasis,
+        sidereal_time: Some(local_sidereal_time(jd, location.longitude) / 15.0),
+        angle_speeds: None,
+        sect: sect.map(|s| s.sect),
+        sun_altitude: sect.map(|s| s.sun_altitude),
+        sect_ambiguous: sect.is_some_and(|s| s.ambiguous),
+        svg_chart: None, // Will be set below
+        svg_thumbnail: None,
+        diagnostics: None,
+        meta: Some(meta),
+        interpretation: None,
+        truncated,
+    };
+    match generate_natal_svg_with_options(&return_chart, svg_options) {
+        Ok(svg_chart) => {
+            if let Some(meta) = return_chart.meta.as_mut() {
+                meta.svg_bytes = Some(svg_chart.len());
+            }
+            return_chart.svg_chart = Some(svg_chart);
+        }
+        Err(svg_error) => {
+            log_request_error(
+                "return",
+                &get_client_ip(),
+                &json!(req.0).to_string(),
+                &format!("SVG generation failed: {}", svg_error),
+            );
+            return Err(CalculationFailure::internal(format!("SVG generation failed: {}", svg_error)));
+        }
+    }
+
+    let passes = planet_return
+        .passes
+        .iter()
+        .filter_map(|pass| {
+            Some(ReturnPassInfo {
+                pass: pass.pass,
+                date: julian_to_date(pass.jd)?,
+                retrograde: pass.retrograde,
+                is_true_return: *pass == planet_return.true_pass,
+            })
+        })
+        .collect();
+
+    Ok(ReturnResponse {
+        chart_type: "return".to_string(),
+        body,
+        natal_date,
+        resolved_date: (!matches!(req.reference_date, Some(DateExpr::At(_)))).then_some(reference_date),
+        natal_longitude,
+        precessed_natal_longitude,
+        occurrence: req.occurrence,
+        passes,
+        chart: return_chart,
+        vs_natal: vs_natal.into(),
+    })
+}
+
+async fn generate_mundane_chart(req: JsonBody<MundaneRequest>, ephemeris: web::Data<dyn Ephemeris>) -> impl Responder {
+    respond(move || mundane_chart(web::Json(req.into_inner()), ephemeris.into_inner())).await
+}
+
+/// Casts a chart at a location for the moment of a cardinal ingress or lunation
+fn mundane_chart(req: web::Json<MundaneRequest>, ephemeris: Arc<dyn Ephemeris>) -> Result<MundaneResponse, CalculationFailure> {
+    let location = req.location;
+    let found = (|| {
+        let event: MundaneEvent = req.event.parse()?;
+        if !(-90.0..=90.0).contains(&location.latitude) || !(-180.0..=180.0).contains(&location.longitude) {
+            return Err(format!(
+                "Invalid location: latitude {}, longitude {}",
+                location.latitude, location.longitude
+            ));
+        }
+        let source = parse_ephemeris_source(&*ephemeris, req.ephemeris.as_deref())?;
+        let jd = find_event(event, req.year, req.month, source)?;
+        let event_time = julian_to_date(jd).ok_or_else(|| format!("Event date out of range: Julian date {}", jd))?;
+        Ok((event, event_time))
+    })();
+    let (event, event_time) = match found {
+        Ok(f) => f,
+        Err(e) => {
+            log_request_error("mundane", &get_client_ip(), &json!(req.0).to_string(), &e);
+            return Err(CalculationFailure::bad_request(e));
+        }
+    };
+
+    let mut chart = natal_chart(web::Json(ChartRequest {
+        date: event_time.into(),
+        latitude: location.latitude,
+        longitude: location.longitude,
+        house_system: req.house_system.unwrap_or_default(),
+        ayanamsa: "tropical".to_string(),
+        include_minor_aspects: Some(req.include_minor_aspects),
+        ephemeris: req.ephemeris.clone(),
+        modern_rulers: req.modern_rulers,
+        glyph_mode: req.glyph_mode.clone(),
+        aspect_sort: req.aspect_sort.clone(),
+        lang: req.lang.clone(),
+        svg_detail: req.svg_detail.clone(),
+        wheel_style: req.wheel_style.clone(),
+        svg_layers: req.svg_layers.clone(),
+        include_legend: req.include_legend,
+        legend_position: req.legend_position.clone(),
+        node_type: req.node_type.clone(),
+        ..Default::default()
+    }), ephemeris)?;
+    chart.chart_type = "mundane".to_string();
+
+    Ok(MundaneResponse {
+        chart_type: "mundane".to_string(),
+        event,
+        year: req.year,
+        month: req.month.filter(|_| event.is_lunation()),
+        event_time,
+        chart,
+    })
+}
+
+/// Splits a "planet-planet" pair into two bodies that have a synodic cycle
+fn parse_cycle_pair(pair: &str) -> Result<(Planet, Planet), String> {
+    let (first, second) = pair
+        .split_once('-')
+        .ok_or_else(|| format!("Invalid pair '{}', expected e.g. 'jupiter-saturn'", pair))?;
+    let (body1, body2): (Planet, Planet) = (first.parse()?, second.parse()?);
+    for body in [body1, body2] {
+        if !has_synodic_cycle(body) {
+            return Err(format!("{:?} has no synodic cycle", body));
+        }
+    }
+    if body1 == body2 {
+        return Err("A cycle needs two different planets".to_string());
+    }
+    Ok((body1, body2))
+}
+
+async fn get_cycles(query: web::Query<CyclesQuery>) -> impl Responder {
+    respond(move || cycles(query)).await
+}
+
+fn cycles(query: web::Query<CyclesQuery>) -> Result<CyclesResponse, CalculationFailure> {
+    let validated = parse_cycle_pair(&query.pair).and_then(|pair| {
+        let days = (query.to - query.from).num_days();
+        if days <= 0 {
+            return Err("'to' must be after 'from'".to_string());
+        }
+        if days > MAX_CYCLE_RANGE_DAYS {
+            return Err(format!("Range is limited to {} days", MAX_CYCLE_RANGE_DAYS));
+        }
+        let source = parse_ephemeris_source(&*default_ephemeris(), query.ephemeris.as_deref())?;
+        Ok((pair, source))
+    });
+    let ((body1, body2), source) = match validated {
+        Ok(v) => v,
+        Err(e) => {
+            log_request_error("cycles", &get_client_ip(), &json!(query.0).to_string(), &e);
+            return Err(CalculationFailure::bad_request(e));
+        }
+    };
+
+    let jd_from = date_to_julian(query.from.and_time(chrono::NaiveTime::MIN).and_utc());
+    let jd_to = date_to_julian(query.to.and_time(chrono::NaiveTime::MIN).and_utc());
+    let events = match synodic_events_with_source(body1, body2, jd_from, jd_to, source) {
+        Ok(events) => events,
+        Err(e) => {
+            log_request_error("cycles", &get_client_ip(), &json!(query.0).to_string(), &e);
+            return Err(CalculationFailure::internal(e));
+        }
+    };
+
+    let events = events
+        .into_iter()
+        .filter_map(|event| {
+            Some(CycleEventInfo {
+                event: event.kind,
+                date: julian_to_date(event.jd)?,
+                planet: event.planet.to_string(),
+                longitude: event.longitude,
+                sign: Sign::from_longitude(event.longitude).to_string(),
+                degree: event.longitude % 30.0,
+            })
+        })
+        .collect();
+
+    Ok(CyclesResponse {
+        planet1: body1.to_string(),
+        planet2: body2.to_string(),
+        from: query.from,
+        to: query.to,
+        events,
+    })
+}
+
+async fn get_transit_calendar(query: web::Query<TransitCalendarQuery>) -> impl Responder {
+    let calendar = ContentType("text/calendar; charset=utf-8".parse().expect("a valid MIME type"));
+    respond_as(calendar, move || transit_calendar_feed(query), Ok).await
+}
+
+/// The exact transits to the natal planets in the query's range, and the
+/// natal planets on recent eclipse degrees, as an iCalendar feed
+fn transit_calendar_feed(query: web::Query<TransitCalendarQuery>) -> Result<String, CalculationFailure> {
+    let validated = parse_transiting(query.planets.as_deref().unwrap_or(DEFAULT_CALENDAR_PLANETS)).and_then(|planets| {
+        let days = (query.to - query.from).num_days();
+        if days <= 0 {
+            return Err("'to' must be after 'from'".to_string());
+        }
+        if days > MAX_TRANSIT_CALENDAR_DAYS {
+            return Err(format!("Range is limited to {} days", MAX_TRANSIT_CALENDAR_DAYS));
+        }
+        if query.eclipse_points == Some(0) {
+            return Err("eclipse_points must be at least 1".to_string());
+        }
+        let lookback = query.eclipse_lookback_months.unwrap_or(DEFAULT_ECLIPSE_LOOKBACK_MONTHS);
+        if lookback == 0 || lookback > MAX_ECLIPSE_LOOKBACK_MONTHS {
+            return Err(format!("eclipse_lookback_months must be from 1 to {}", MAX_ECLIPSE_LOOKBACK_MONTHS));
+        }
+        let source = parse_ephemeris_source(&*default_ephemeris(), query.ephemeris.as_deref())?;
+        Ok((planets, lookback, source))
+    });
+    let (planets, lookback, source) = match validated {
+        Ok(v) => v,
+        Err(e) => {
+            log_request_error("transit_calendar", &get_client_ip(), &json!(query.0).to_string(), &e);
+            return Err(CalculationFailure::bad_request(e));
+        }
+    };
+
+    let natal_jd = date_to_julian(query.natal);
+    let jd_from = date_to_julian(query.from.and_time(chrono::NaiveTime::MIN).and_utc());
+    let jd_to = date_to_julian(query.to.and_time(chrono::NaiveTime::MIN).and_utc());
+    let found = NATAL_PLANETS
+        .iter()
+        .map(|&planet| Ok((planet.to_string(), planet_longitude_at(planet, natal_jd, source)?)))
+        .collect::<Result<Vec<_>, String>>()
+        .and_then(|natal| {
+            let hits = exact_transits(&natal, &planets, &get_aspect_types(false), jd_from, jd_to, source)?;
+            let Some(count) = query.eclipse_points else {
+                return Ok((hits, Vec::new()));
+            };
+            // A month as a twelfth of the Julian year
+            let eclipses = eclipses_between(jd_to - f64::from(lookback) * 365.25 / 12.0, jd_to, source)?;
+            let recent = &eclipses[eclipses.len().saturating_sub(count)..];
+            Ok((hits, eclipse_contacts(&natal, recent, ECLIPSE_POINT_ORB)))
+        });
+    let (mut hits, eclipses) = match found {
+        Ok(found) => found,
+        Err(e) => {
+            log_request_error("transit_calendar", &get_client_ip(), &json!(query.0).to_string(), &e);
+            return Err(CalculationFailure::internal(e));
+        }
+    };
+    hits.truncate(MAX_TRANSIT_CALENDAR_EVENTS);
+
+    let natal_id = query.natal.to_rfc3339_opts(chrono::SecondsFormat::Secs, true);
+    Ok(transit_calendar(&format!("Transits to {}", natal_id), &natal_id, &hits, &eclipses, chrono::Utc::now()))
+}
+
+async fn get_capabilities(ephemeris: web::Data<dyn Ephemeris>) -> impl Responder {
+    respond(move || -> Result<CapabilitiesResponse, CalculationFailure> {
+        Ok(capabilities(&**ephemeris, date_to_julian(chrono::Utc::now())))
+    })
+    .await
+}
+
+async fn get_symbols(query: web::Query<SymbolsQuery>) -> impl Responder {
+    respond(move || -> Result<SymbolsResponse, CalculationFailure> {
+        let styles = get_styles().ok_or_else(|| CalculationFailure::internal("Chart styles not initialized. chart_styles.json is required."))?;
+        symbols(styles, query.theme.as_deref()).map_err(|e| {
+            log_request_error("symbols", &get_client_ip(), &json!(query.0).to_string(), &e);
+            CalculationFailure::bad_request(e)
+        })
+    })
+    .await
+}
+
+async fn get_weather(query: web::Query<WeatherQuery>) -> impl Responder {
+    respond(move || weather(query)).await
+}
+
+fn weather(query: web::Query<WeatherQuery>) -> Result<WeatherResponse, CalculationFailure> {
+    let validated = (|| {
+        if !(-90.0..=90.0).contains(&query.lat) || !(-180.0..=180.0).contains(&query.lon) {
+            return Err(format!("Invalid location: latitude {}, longitude {}", query.lat, query.lon));
+        }
+        parse_ephemeris_source(&*default_ephemeris(), query.ephemeris.as_deref())
+    })();
+    let source = match validated {
+        Ok(source) => source,
+        Err(e) => {
+            log_request_error("weather", &get_client_ip(), &json!(query.0).to_string(), &e);
+            return Err(CalculationFailure::bad_request(e));
+        }
+    };
+
+    weather_report(query.date, query.lat, query.lon, source).map_err(|e| {
+        log_request_error("weather", &get_client_ip(), &json!(query.0).to_string(), &e.to_string());
+        CalculationFailure::calculation(&e)
+    })
+}
+
+async fn get_moon(query: web::Query<MoonQuery>) -> impl Responder {
+    respond(move || moon(query)).await
+}
+
+fn moon(query: web::Query<MoonQuery>) -> Result<MoonResponse, CalculationFailure> {
+    let step = query.step.as_deref().unwrap_or("1h");
+    let validated = (|| -> Result<_, String> {
+        let range = moon_range(query.from, query.to, parse_moon_step(step)?)?;
+        let mansion_system = query.mansion_system.as_deref().map(str::parse).transpose()?.unwrap_or_default();
+        Ok((range, mansion_system, parse_ephemeris_source(&*default_ephemeris(), query.ephemeris.as_deref())?))
+    })();
+    let (range, mansion_system, source) = match validated {
+        Ok(v) => v,
+        Err(e) => {
+            log_request_error("moon", &get_client_ip(), &json!(query.0).to_string(), &e);
+            return Err(CalculationFailure::bad_request(e));
+        }
+    };
+
+    moon_table(&range, step, mansion_system, source).map_err(|e| {
+        log_request_error("moon", &get_client_ip(), &json!(query.0).to_string(), &e);
+        CalculationFailure::internal(e)
+    })
+}
+
+async fn get_positions(query: web::Query<PositionsQuery>, prefetcher: Option<web::Data<Prefetcher>>) -> impl Responder {
+    respond(move || positions(query, prefetcher)).await
+}
+
+fn positions(
+    query: web::Query<PositionsQuery>,
+    prefetcher: Option<web::Data<Prefetcher>>,
+) -> Result<Arc<[BodyPositionInfo]>, CalculationFailure> {
+    let validated = (|| {
+        let bodies = match query.bodies.as_deref() {
+            Some(list) => parse_bodies(list)?,
+            None => DEFAULT_POSITION_BODIES.to_vec(),
+        };
+        let zodiac: Zodiac = query.zodiac.as_deref().map(str::parse).transpose()?.unwrap_or_default();
+        check_ayanamsa(zodiac, query.ayanamsa.as_deref())?;
+        let asked: Option<Center> = query.center.as_deref().map(str::parse).transpose()?;
+        let center = match (zodiac.center(), asked) {
+            (Some(tied), Some(asked)) if tied != asked => {
+                return Err(format!(
+                    "The {} zodiac is seen from center \"{}\", not \"{}\"",
+                    zodiac.key(),
+                    tied.key(),
+                    asked.key()
+                ))
+            }
+            (tied, asked) => tied.or(asked).unwrap_or_default(),
+        };
+        if let Some(body) = bodies.iter().find(|&&body| !center.supports(body)) {
+            return Err(format!("{:?} has no heliocentric position", body));
+        }
+        Ok((bodies, zodiac, center, parse_ephemeris_source(&*default_ephemeris(), query.ephemeris.as_deref())?))
+    })();
+    let (bodies, zodiac, center, source) = match validated {
+        Ok(v) => v,
+        Err(e) => {
+            log_request_error("positions", &get_client_ip(), &json!(query.0).to_string(), &e);
+            return Err(CalculationFailure::bad_request(e));
+        }
+    };
+
+    // The current tropical, geocentric positions come from the prefetched snapshot when it has them
+    if query.date.is_none() && zodiac == Zodiac::Tropical && center == Center::Geocentric {
+        let asked = query.ephemeris.is_some().then_some(source);
+        if let Some(positions) = prefetcher.and_then(|prefetcher| prefetcher.positions(&bodies, asked)) {
+            return Ok(positions);
+        }
+    }
+
+    positions_at(query.date.unwrap_or_else(chrono::Utc::now), bodies, zodiac, center, source).map_err(|e| {
+        log_request_error("positions", &get_client_ip(), &json!(query.0).to_string(), &e);
+        CalculationFailure::internal(e)
+    })
+}
+
+/// The current positions and Moon, from the registered prefetcher, or calculated
+/// from the ephemeris when there is none
+async fn get_now(ephemeris: web::Data<dyn Ephemeris>, prefetcher: Option<web::Data<Prefetcher>>) -> impl Responder {
+    respond(move || -> Result<Arc<NowResponse>, CalculationFailure> {
+        let now = chrono::Utc::now();
+        let snapshot = match prefetcher {
+            Some(prefetcher) => prefetcher.now_at(now),
+            None => Prefetcher::new(ephemeris.into_inner()).now_at(now),
+        };
+        snapshot.map_err(|e| {
+            log_request_error("now", &get_client_ip(), "{}", &e);
+            CalculationFailure::internal(e)
+        })
+    })
+    .await
+}
+
+/// Queues a chart render and answers 202 with the job to poll
+async fn create_render_job(http: HttpRequest, req: JsonBody<RenderRequest>, ephemeris: web::Data<dyn Ephemeris>) -> HttpResponse {
+    if let Err(e) = req.0.validate_format() {
+        log_request_error("render", &get_client_ip(), &json!(req.0).to_string(), &e);
+        return failure_response(get_api_version(), StatusCode::BAD_REQUEST, &e);
+    }
+    let created = render_store().lock().unwrap_or_else(|e| e.into_inner()).create();
+    let Some(id) = created else {
+        let message = "Too many render jobs are pending; try again later";
+        return failure_response(get_api_version(), StatusCode::SERVICE_UNAVAILABLE, message);
+    };
+
+    let (ip, request_id, accept_language) = (get_client_ip(), get_request_id(), get_accept_language());
+    let chart = req.into_inner().chart;
+    actix_web::rt::spawn(async move {
+        let outcome = match render_permits().acquire().await {
+            Ok(_permit) => calculation_pool()
+                .run(move || {
+                    CLIENT_IP.with(|cell| *cell.borrow_mut() = ip);
+                    REQUEST_ID.with(|cell| *cell.borrow_mut() = request_id);
+                    PREFERRED_LANG.with(|cell| cell.set(accept_language));
+                    chart_with_transits(web::Json(chart), ephemeris.into_inner())
+                })
+                .await
+                .map_err(CalculationFailure::internal)
+                .and_then(|result| result),
+            Err(_) => Err(CalculationFailure::internal("Render limit is closed")),
+        };
+        let outcome = match outcome.map(|chart| chart.svg_chart) {
+            Ok(Some(svg)) => RenderOutcome::Svg(svg),
+            Ok(None) => RenderOutcome::Failed {
+                status: 500,
+                message: "The chart has no SVG".to_string(),
+            },
+            Err(failure) => RenderOutcome::Failed {
+                status: failure.status.as_u16(),
+                message: failure.message,
+            },
+        };
+        render_store().lock().unwrap_or_else(|e| e.into_inner()).finish(id, outcome);
+    });
+
+    let mut accepted = HttpResponse::Accepted();
+    // Under the same scope the job was created in
+    accepted.insert_header((LOCATION, format!("{}/{}", http.path(), id)));
+    versioned_json(
+        accepted,
+        &RenderJobInfo {
+            id,
+            status: "pending".to_string(),
+        },
+    )
+}
+
+/// The finished artifact, 202 while the job runs, or 410 once it has expired
+async fn get_render_job(id: web::Path<u64>) -> HttpResponse {
+    let id = id.into_inner();
+    let state = render_store().lock().unwrap_or_else(|e| e.into_inner()).state(id);
+    match state {
+        RenderJobState::Pending => versioned_json(
+            HttpResponse::Accepted(),
+            &RenderJobInfo {
+                id,
+                status: "pending".to_string(),
+            },
+        ),
+        RenderJobState::Finished(RenderOutcome::Svg(svg)) => {
+            HttpResponse::Ok().content_type("image/svg+xml").body(svg)
+        }
+        RenderJobState::Finished(RenderOutcome::Failed { status, message }) => failure_response(
+            get_api_version(),
+            StatusCode::from_u16(status).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+            &message,
+        ),
+        RenderJobState::Expired => {
+            failure_response(get_api_version(), StatusCode::GONE, &format!("Render job {} has expired", id))
+        }
+        RenderJobState::Unknown => failure_response(get_api_version(), StatusCode::NOT_FOUND, &format!("No render job {}", id)),
+    }
+}
+
+#[allow(dead_code)]
+async fn health_check(prefetcher: Option<web::Data<Prefetcher>>) -> impl Responder {
+    // Check Swiss Ephemeris availability
+    let ephemeris_status = if std::path::Path::new("./ephe").exists() {
+        "available"
+    } else {
+        "unavailable"
+    };
+    
+    HttpResponse::Ok().json(json!({
+        "status": "healthy",
+        "timestamp": chrono::Utc::now().to_rfc3339(),
+        "service": "astrolog-rs",
+        "version": env!("CARGO_PKG_VERSION"),
+        "checks": {
+            "ephemeris": ephemeris_status,
+            "server": "running",
+            "motion_anomalies": motion_anomaly_count(),
+            "prefetch_age_seconds": prefetcher.and_then(|prefetcher| prefetcher.age_seconds())
+        }
+    }))
+}
+
+/// Registers the routes with the settings from the environment and the default
+/// ephemeris. An app can calculate from another ephemeris by registering its own
+/// `web::Data<dyn Ephemeris>` after this, and serve the current positions from
+/// a `web::Data<Prefetcher>` it has started.
+#[allow(dead_code)]
+pub fn config(cfg: &mut web::ServiceConfig) {
+    let settings = AppConfig::from_env().unwrap_or_else(|e| {
+        log::warn!("Using the default settings: {}", e);
+        AppConfig::default()
+    });
+    routes(cfg, &settings);
+    cfg.app_data(web::Data::from(default_ephemeris()));
+}
+
+/// Registers the routes, the settings as `web::Data<AppConfig>` and the
+/// ephemeris backend the settings name
+pub fn configure(cfg: &mut web::ServiceConfig, settings: &AppConfig) {
+    routes(cfg, settings);
+    let ephemeris = ephemeris_backend(&settings.ephemeris_backend).unwrap_or_else(|_| default_ephemeris());
+    cfg.app_data(web::Data::from(ephemeris));
+}
+
+fn routes(cfg: &mut web::ServiceConfig, settings: &AppConfig) {
+    // Health endpoint at root level for load balancers/monitoring
+    cfg.route("/health", web::get().to(health_check));
+
+    cfg.app_data(web::Data::new(settings.clone()));
+    let (single, batch) = (BodyLimit(settings.max_body_size), BodyLimit(settings.max_batch_body_size));
+
+    // API endpoints under each version's scope, then bare /api as an alias of
+    // v1; the versioned scopes come first so /api doesn't take their paths
+    for scope in ApiVersion::ALL.map(ApiVersion::scope).into_iter().chain(["/api"]) {
+        cfg.service(
+            web::scope(scope)
+                .wrap(middleware::Logger::default())
+                .wrap(IpMiddleware)
+                .app_data(single)
+                .configure(|api| api_routes(api, batch)),
+        );
+    }
+}
+
+/// The endpoints every API version serves
+fn api_routes(api: &mut web::ServiceConfig, batch: BodyLimit) {
+    // A chart with a transit series, and whole charts sent back to compare, run larger
+    api.service(
+        web::resource("/chart")
+            .app_data(batch)
+            .route(web::post().to(generate_chart_with_transits)),
+    )
+    .route("/chart/natal", web::post().to(generate_natal_chart))
+    .route("/chart/transit", web::post().to(generate_transit_chart))
+    .route("/chart/synastry", web::post().to(generate_synastry_chart))
+    .route("/chart/return", web::post().to(generate_return_chart))
+    .route("/chart/mundane", web::post().to(generate_mundane_chart))
+    .route("/chart/house_compare", web::post().to(generate_house_compare))
+    .service(web::resource("/chart/at_degree").app_data(batch).route(web::post().to(look_up_degree)))
+    .service(web::resource("/compare").app_data(batch).route(web::post().to(compare_charts)))
+    .service(web::resource("/synastry/batch").app_data(batch).route(web::post().to(synastry_batch)))
+    .route("/capabilities", web::get().to(get_capabilities))
+    .route("/symbols", web::get().to(get_symbols))
+    .route("/cycles", web::get().to(get_cycles))
+    .route("/transits/ical", web::get().to(get_transit_calendar))
+    .route("/weather", web::get().to(get_weather))
+    .route("/moon", web::get().to(get_moon))
+    .route("/positions", web::get().to(get_positions))
+    .route("/now", web::get().to(get_now))
+    .service(web::resource("/render").app_data(batch).route(web::post().to(create_render_job)))
+    .route("/render/{id}", web::get().to(get_render_job));
 }
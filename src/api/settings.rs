@@ -0,0 +1,280 @@
+//! Server settings, read once at startup.
+//!
+//! `AppConfig::load` starts from the defaults, applies the TOML file named by
+//! `ASTROLOG_CONFIG` if there is one, then the environment variables, so a
+//! container can override any file setting with `-e`. Every value is checked
+//! before the server starts, and the error names the variable or key at fault.
+//! The routes get the settings as `web::Data<AppConfig>`.
+
+use crate::api::audit::{DEFAULT_AUDIT_FILES, DEFAULT_AUDIT_MAX_BYTES};
+use crate::api::payload::{DEFAULT_BATCH_BODY_SIZE, DEFAULT_BODY_SIZE, MAX_BATCH_BODY_SIZE_ENV, MAX_BODY_SIZE_ENV};
+use crate::api::prefetch::DEFAULT_PREFETCH_INTERVAL;
+use crate::api::types::DEFAULT_MAX_SYNASTRY_BATCH;
+use crate::api::{pool, render};
+use crate::calc::ephemeris::{ephemeris_backend, BACKEND_ENV};
+use crate::calc::swiss_ephemeris::EPHE_PATH;
+use crate::core::types::EphemerisSource;
+use crate::interpret::CONTENT_PACK_ENV;
+use serde::{Deserialize, Serialize};
+use std::fmt::Display;
+use std::str::FromStr;
+use std::time::Duration;
+
+/// Environment variable naming the TOML settings file
+pub const CONFIG_PATH_ENV: &str = "ASTROLOG_CONFIG";
+
+/// Levels `log_level` can be set to
+const LOG_LEVELS: [&str; 6] = ["off", "error", "warn", "info", "debug", "trace"];
+
+/// Everything the server binary reads at startup. Field names are the TOML
+/// keys; each field's environment variable is listed beside it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct AppConfig {
+    /// Address to listen on (`HOST`); "0.0.0.0" in a container
+    pub host: String,
+    /// Port to listen on (`PORT`)
+    pub port: u16,
+    /// HTTP worker threads (`WORKERS`); one per CPU by default
+    pub workers: usize,
+    /// Threads calculations run on (`CALCULATION_THREADS`); one per CPU by default
+    pub calculation_threads: usize,
+    /// Calculations running or waiting at once before requests are refused (`MAX_CONCURRENT`)
+    pub max_concurrent: usize,
+    /// Background renders running at once (`MAX_CONCURRENT_RENDERS`)
+    pub max_concurrent_renders: usize,
+    /// Requests the queue holds (`MAX_QUEUE_SIZE`)
+    pub max_queue_size: usize,
+    /// Seconds a request waits in the queue (`MAX_WAIT_TIME`)
+    pub max_wait_time: u64,
+    /// Body limit of single-chart endpoints in bytes (`MAX_BODY_SIZE`)
+    pub max_body_size: usize,
+    /// Body limit of endpoints taking several moments or charts, in bytes (`MAX_BATCH_BODY_SIZE`)
+    pub max_batch_body_size: usize,
+    /// Partners one `/api/synastry/batch` request may compare the base chart with (`MAX_SYNASTRY_BATCH`)
+    pub max_synastry_batch: usize,
+    /// Ephemeris source when a request doesn't name one (`EPHEMERIS`)
+    pub ephemeris: EphemerisSource,
+    /// Directory of the Swiss Ephemeris `.se1` files (`EPHE_PATH`)
+    pub ephemeris_path: String,
+    /// "swiss" or "analytic" (`EPHEMERIS_BACKEND`)
+    pub ephemeris_backend: String,
+    /// Chart styles file (`CHART_STYLES`); the usual locations are searched when unset
+    pub styles_path: Option<String>,
+    /// Interpretation content pack (`INTERPRETATION_PACK`); the starter pack when unset
+    pub content_pack: Option<String>,
+    /// Log level when `RUST_LOG` isn't set (`LOG_LEVEL`)
+    pub log_level: String,
+    /// Seconds between checks that the prefetched current positions are for this minute (`PREFETCH_INTERVAL`)
+    pub prefetch_interval: u64,
+    /// File every natal chart calculated is recorded to (`AUDIT_LOG`); no audit log when unset
+    pub audit_log: Option<String>,
+    /// Size in bytes at which the audit log is rotated (`AUDIT_LOG_MAX_BYTES`)
+    pub audit_log_max_bytes: u64,
+    /// Rotated audit logs kept (`AUDIT_LOG_FILES`)
+    pub audit_log_files: usize,
+    /// Astrolog `astrolog.as` file whose defaults chart requests start from (`ASTROLOG_DEFAULTS`)
+    pub astrolog_defaults_path: Option<String>,
+}
+
+impl Default for AppConfig {
+    fn default() -> Self {
+        Self {
+            host: "127.0.0.1".to_string(),
+            port: 4008,
+            workers: num_cpus::get(),
+            calculation_threads: num_cpus::get(),
+            max_concurrent: pool::DEFAULT_MAX_CONCURRENT,
+            max_concurrent_renders: render::DEFAULT_MAX_CONCURRENT_RENDERS,
+            max_queue_size: 10000,
+            max_wait_time: 30,
+            max_body_size: DEFAULT_BODY_SIZE,
+            max_batch_body_size: DEFAULT_BATCH_BODY_SIZE,
+            max_synastry_batch: DEFAULT_MAX_SYNASTRY_BATCH,
+            ephemeris: EphemerisSource::Auto,
+            ephemeris_path: EPHE_PATH.to_string(),
+            ephemeris_backend: "swiss".to_string(),
+            styles_path: None,
+            content_pack: None,
+            log_level: "info".to_string(),
+            prefetch_interval: DEFAULT_PREFETCH_INTERVAL,
+            audit_log: None,
+            audit_log_max_bytes: DEFAULT_AUDIT_MAX_BYTES,
+            audit_log_files: DEFAULT_AUDIT_FILES,
+            astrolog_defaults_path: None,
+        }
+    }
+}
+
+impl AppConfig {
+    /// The settings from `ASTROLOG_CONFIG` and the environment
+    pub fn load() -> Result<Self, String> {
+        let file = match std::env::var(CONFIG_PATH_ENV) {
+            Ok(path) => {
+                let text = std::fs::read_to_string(&path).map_err(|e| format!("Can't read {}: {}", path, e))?;
+                Some((path, text))
+            }
+            Err(_) => None,
+        };
+        Self::from_sources(
+            file.as_ref().map(|(path, text)| (path.as_str(), text.as_str())),
+            |name| std::env::var(name).ok(),
+        )
+    }
+
+    /// The defaults overridden by the environment alone
+    pub fn from_env() -> Result<Self, String> {
+        Self::from_sources(None, |name| std::env::var(name).ok())
+    }
+
+    /// The defaults, overridden by a TOML file's text (with its path for
+    /// errors), then by the variables `env` returns, then validated
+    pub fn from_sources(file: Option<(&str, &str)>, env: impl Fn(&str) -> Option<String>) -> Result<Self, String> {
+        let mut config = match file {
+            Some((path, text)) => toml::from_str(text).map_err(|e| format!("Invalid settings in {}: {}", path, e))?,
+            None => Self::default(),
+        };
+        config.apply_env(env)?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    fn apply_env(&mut self, env: impl Fn(&str) -> Option<String>) -> Result<(), String> {
+        override_with(&mut self.host, "HOST", &env)?;
+        override_with(&mut self.port, "PORT", &env)?;
+        override_with(&mut self.workers, "WORKERS", &env)?;
+        override_with(&mut self.calculation_threads, "CALCULATION_THREADS", &env)?;
+        override_with(&mut self.max_concurrent, "MAX_CONCURRENT", &env)?;
+        override_with(&mut self.max_concurrent_renders, "MAX_CONCURRENT_RENDERS", &env)?;
+        override_with(&mut self.max_queue_size, "MAX_QUEUE_SIZE", &env)?;
+        override_with(&mut self.max_wait_time, "MAX_WAIT_TIME", &env)?;
+        override_with(&mut self.max_body_size, MAX_BODY_SIZE_ENV, &env)?;
+        override_with(&mut self.max_batch_body_size, MAX_BATCH_BODY_SIZE_ENV, &env)?;
+        override_with(&mut self.max_synastry_batch, "MAX_SYNASTRY_BATCH", &env)?;
+        override_with(&mut self.ephemeris, "EPHEMERIS", &env)?;
+        override_with(&mut self.ephemeris_path, "EPHE_PATH", &env)?;
+        override_with(&mut self.ephemeris_backend, BACKEND_ENV, &env)?;
+        if let Some(path) = env("CHART_STYLES") {
+            self.styles_path = Some(path);
+        }
+        if let Some(path) = env(CONTENT_PACK_ENV) {
+            self.content_pack = Some(path);
+        }
+        override_with(&mut self.prefetch_interval, "PREFETCH_INTERVAL", &env)?;
+        if let Some(path) = env("AUDIT_LOG") {
+            self.audit_log = Some(path);
+        }
+        override_with(&mut self.audit_log_max_bytes, "AUDIT_LOG_MAX_BYTES", &env)?;
+        override_with(&mut self.audit_log_files, "AUDIT_LOG_FILES", &env)?;
+        if let Some(path) = env("ASTROLOG_DEFAULTS") {
+            self.astrolog_defaults_path = Some(path);
+        }
+        override_with(&mut self.log_level, "LOG_LEVEL", &env)
+    }
+
+    /// Refuses settings the server can't run with
+    pub fn validate(&self) -> Result<(), String> {
+        if self.host.trim().is_empty() {
+            return Err("host must not be empty".to_string());
+        }
+        let counts = [
+            ("workers", self.workers),
+            ("calculation_threads", self.calculation_threads),
+            ("max_concurrent", self.max_concurrent),
+            ("max_concurrent_renders", self.max_concurrent_renders),
+            ("max_queue_size", self.max_queue_size),
+            ("max_wait_time", self.max_wait_time as usize),
+            ("max_body_size", self.max_body_size),
+            ("max_batch_body_size", self.max_batch_body_size),
+            ("max_synastry_batch", self.max_synastry_batch),
+            ("prefetch_interval", self.prefetch_interval as usize),
+            ("audit_log_max_bytes", self.audit_log_max_bytes as usize),
+            ("audit_log_files", self.audit_log_files),
+        ];
+        if let Some((name, _)) = counts.iter().find(|(_, value)| *value == 0) {
+            return Err(format!("{} must be at least 1", name));
+        }
+        ephemeris_backend(&self.ephemeris_backend)?;
+        if !LOG_LEVELS.contains(&self.log_level.to_lowercase().as_str()) {
+            return Err(format!("Invalid log_level: {} (expected one of {})", self.log_level, LOG_LEVELS.join(", ")));
+        }
+        Ok(())
+    }
+
+    /// Address and port to bind
+    pub fn bind_address(&self) -> (&str, u16) {
+        (self.host.as_str(), self.port)
+    }
+
+    /// How long a request may wait in the queue
+    pub fn max_wait(&self) -> Duration {
+        Duration::from_secs(self.max_wait_time)
+    }
+
+    /// How often the prefetch task checks the current positions
+    pub fn prefetch_interval(&self) -> Duration {
+        Duration::from_secs(self.prefetch_interval)
+    }
+}
+
+/// Replaces `field` with the variable's value when it is set, naming the
+/// variable if the value doesn't parse
+fn override_with<T>(field: &mut T, name: &str, env: &impl Fn(&str) -> Option<String>) -> Result<(), String>
+where
+    T: FromStr,
+    T::Err: Display,
+{
+    if let Some(value) = env(name) {
+        *field = value.trim().parse().map_err(|e| format!("Invalid {}={:?}: {}", name, value, e))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn env(vars: &[(&str, &str)]) -> impl Fn(&str) -> Option<String> {
+        let vars: HashMap<String, String> = vars.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect();
+        move |name| vars.get(name).cloned()
+    }
+
+    #[test]
+    fn test_env_overrides_file_overrides_defaults() {
+        let file = r#"
+            host = "0.0.0.0"
+            port = 8080
+            workers = 2
+            ephemeris = "moshier"
+        "#;
+        let config = AppConfig::from_sources(Some(("astrolog.toml", file)), env(&[("PORT", "9000")])).unwrap();
+        // From the environment, the file, then the defaults
+        assert_eq!(config.bind_address(), ("0.0.0.0", 9000));
+        assert_eq!(config.workers, 2);
+        assert_eq!(config.ephemeris, EphemerisSource::Moshier);
+        assert_eq!(config.max_queue_size, 10000);
+        assert_eq!(config.ephemeris_path, EPHE_PATH);
+
+        let config = AppConfig::from_sources(None, env(&[])).unwrap();
+        assert_eq!(config, AppConfig::default());
+        assert_eq!(config.bind_address(), ("127.0.0.1", 4008));
+    }
+
+    #[test]
+    fn test_rejected_settings_say_why() {
+        let error = AppConfig::from_sources(None, env(&[("WORKERS", "0")])).unwrap_err();
+        assert_eq!(error, "workers must be at least 1");
+        let error = AppConfig::from_sources(Some(("astrolog.toml", "workers = 0")), env(&[])).unwrap_err();
+        assert_eq!(error, "workers must be at least 1");
+
+        let error = AppConfig::from_sources(None, env(&[("PORT", "eighty")])).unwrap_err();
+        assert!(error.starts_with("Invalid PORT=\"eighty\""), "{}", error);
+        let error = AppConfig::from_sources(Some(("astrolog.toml", "wokers = 2")), env(&[])).unwrap_err();
+        assert!(error.contains("astrolog.toml") && error.contains("wokers"), "{}", error);
+        assert!(AppConfig::from_sources(None, env(&[("EPHEMERIS_BACKEND", "jpl")])).is_err());
+        assert!(AppConfig::from_sources(None, env(&[("LOG_LEVEL", "loud")])).is_err());
+        let error = AppConfig::from_sources(None, env(&[("AUDIT_LOG_FILES", "0")])).unwrap_err();
+        assert_eq!(error, "audit_log_files must be at least 1");
+    }
+}
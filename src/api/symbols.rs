@@ -0,0 +1,59 @@
+//! The `GET /api/symbols` legend, built from the symbol tables in
+//! `data::symbols` and the chart styles the SVG renderer draws with
+
+use crate::api::types::{SymbolInfo, SymbolsResponse};
+use crate::charts::styles::ChartStyles;
+use crate::data::symbols::{codepoint, Symbol, ASPECT_SYMBOLS, BODY_SYMBOLS, SIGN_SYMBOLS};
+use std::collections::HashMap;
+
+// The styles' glyph for `name`, if they override the built-in one
+fn glyph_override<'a>(overrides: &'a HashMap<String, String>, name: &str) -> Option<&'a str> {
+    overrides.get(name).map(String::as_str)
+}
+
+fn symbol_info(symbol: &Symbol, chart_name: String, glyph: Option<&str>, color: Option<&String>) -> SymbolInfo {
+    let glyph = glyph.or(symbol.glyph);
+    SymbolInfo {
+        key: symbol.key.to_string(),
+        chart_name,
+        display_name: symbol.name.to_string(),
+        glyph: glyph.map(str::to_string),
+        codepoint: glyph.and_then(codepoint),
+        color: color.cloned(),
+        category: symbol.category.to_string(),
+    }
+}
+
+/// Every body, sign and aspect with its glyph and its color in `theme`, or in
+/// the base theme without one. An unknown theme is an error naming the known ones.
+pub fn symbols(styles: &ChartStyles, theme: Option<&str>) -> Result<SymbolsResponse, String> {
+    let themed = styles.themed(theme.unwrap_or(&styles.theme))?;
+    Ok(SymbolsResponse {
+        themes: styles.theme_names().into_iter().map(str::to_string).collect(),
+        bodies: BODY_SYMBOLS
+            .iter()
+            .map(|(planet, symbol)| {
+                let name = planet.to_string();
+                let (glyph, color) = (glyph_override(&themed.glyphs.planets, &name), themed.planet_colors.get(&name));
+                symbol_info(symbol, name, glyph, color)
+            })
+            .collect(),
+        signs: SIGN_SYMBOLS
+            .iter()
+            .map(|(sign, symbol)| {
+                let name = sign.to_string();
+                let glyph = glyph_override(&themed.glyphs.signs, &name);
+                symbol_info(symbol, name, glyph, None)
+            })
+            .collect(),
+        aspects: ASPECT_SYMBOLS
+            .iter()
+            .map(|(aspect, symbol)| {
+                let name = aspect.to_string();
+                let (glyph, color) = (glyph_override(&themed.glyphs.aspects, &name), themed.aspect_line_colors.default_colors.get(&name));
+                symbol_info(symbol, name, glyph, color)
+            })
+            .collect(),
+        theme: themed.theme,
+    })
+}
@@ -1,152 +1,2818 @@
-use crate::calc::planets::PlanetPosition;
-use chrono::{DateTime, Utc};
+use crate::api::computation::{CHART_PLANETS, REQUIRED_PLANETS};
+use crate::api::rounding;
+use crate::calc::angles::normalize;
+use crate::calc::aspects::{
+    aspect_strength, is_exact, validate_custom_aspects, Aspect, AspectDef, AspectFilter, AspectRecord, AspectSet,
+    AspectSort, AspectType, ChartPoint, SynastryAspect, MAX_CUSTOM_ASPECTS,
+};
+use crate::calc::boundaries::{boundary_orb, Adjacent, BoundaryKind, BoundaryWarning};
+use crate::calc::comparison::{Change, CompositeHouses, HouseOverlay, PlacementChange, PlacementDiff};
+use crate::calc::compatibility::CompatibilityScores;
+use crate::calc::coordinates::{format_right_ascension, CoordinateSystem, EquatorialPosition, Observer};
+use crate::calc::cycles::CycleEventKind;
+use crate::calc::degree_lookup::{NearbyMidpoint, NearbyPoint};
+use crate::calc::houses::Houses;
+use crate::calc::ephemeris::EphemerisCapabilities;
+use crate::calc::house_compare::{PlanetHouses, SystemPairChanges};
+use crate::calc::house_summary::{HouseEmphasis, HouseKind, HouseOccupancy};
+use crate::calc::mundane::MundaneEvent;
+use crate::calc::nodes::node_planet;
+use crate::calc::planets::{BodyFailure, Motion, Planet, PlanetPosition};
+use crate::calc::positions::{parse_chart_ayanamsa, BodyPosition, Zodiac, ASTEROID_BODIES, POSITION_BODIES};
+use crate::calc::points::AnglePoint;
+use crate::calc::rulers::{Sign, SolarCondition};
+use crate::calc::sect::{self, Sect};
+use crate::calc::subdivisions::{Decan, Mansion, MansionSystem, Term};
+use crate::calc::weather::MoonPhase;
+use crate::core::types::{EphemerisSource, HouseSystem, NodeType};
+use crate::interpret::Interpretation;
+use crate::io::astrolog_config::defaults as astrolog_defaults;
+use crate::data::i18n::Lang;
+use chrono::{DateTime, Months, NaiveDate, NaiveTime, SecondsFormat, Utc};
+use serde::de::{self, Deserializer, SeqAccess, Visitor};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::marker::PhantomData;
+
+/// How far a natal-relative date is from the natal date
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DateOffset {
+    /// Calendar months, twelve to a year; a day past the end of the month it
+    /// lands in becomes the month's last day
+    Months(i32),
+    Days(i32),
+}
+
+/// A date as a request gives it: an RFC 3339 date, or an expression resolved
+/// in UTC when the request is served.
+///
+/// The expressions are `"now"`, `"today"` (midnight UTC), `"today T12:00"`,
+/// and `"natal+30y"`, `"natal-3m"` or `"natal+10d"` counted from the request's
+/// natal date, which only fields with a natal date to count from accept.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub enum DateExpr {
+    At(DateTime<Utc>),
+    Now,
+    /// Today's date at a UTC time of day
+    Today(NaiveTime),
+    Natal(DateOffset),
+}
+
+impl DateExpr {
+    /// Whether the date is worked out when the request is served rather than given
+    pub fn is_expression(&self) -> bool {
+        !matches!(self, DateExpr::At(_))
+    }
+
+    /// The moment the date stands for, with "now" at `now` and natal-relative
+    /// dates counted from `natal`. `field` names the date in errors.
+    pub fn resolve(&self, field: &str, now: DateTime<Utc>, natal: Option<DateTime<Utc>>) -> Result<DateTime<Utc>, String> {
+        match *self {
+            DateExpr::At(date) => Ok(date),
+            DateExpr::Now => Ok(now),
+            DateExpr::Today(time) => Ok(now.date_naive().and_time(time).and_utc()),
+            DateExpr::Natal(offset) => {
+                let natal = natal.ok_or_else(|| format!("{}: natal-relative dates need a natal date to count from", field))?;
+                let moved = match offset {
+                    DateOffset::Months(months) if months >= 0 => natal.checked_add_months(Months::new(months.unsigned_abs())),
+                    DateOffset::Months(months) => natal.checked_sub_months(Months::new(months.unsigned_abs())),
+                    DateOffset::Days(days) => natal.checked_add_signed(chrono::Duration::days(days.into())),
+                };
+                moved.ok_or_else(|| format!("{}: {} is out of range", field, self))
+            }
+        }
+    }
+}
+
+impl Default for DateExpr {
+    fn default() -> Self {
+        DateExpr::At(DateTime::default())
+    }
+}
+
+impl From<DateTime<Utc>> for DateExpr {
+    fn from(date: DateTime<Utc>) -> Self {
+        DateExpr::At(date)
+    }
+}
+
+impl std::str::FromStr for DateExpr {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let expression = s.trim().to_lowercase();
+        let unknown = || {
+            format!(
+                "Unknown date '{}': expected an RFC 3339 date, \"now\", \"today\", \"today T12:00\" or \"natal+30y\"",
+                s
+            )
+        };
+        if expression == "now" {
+            return Ok(DateExpr::Now);
+        }
+        if let Some(rest) = expression.strip_prefix("today") {
+            let time = rest.trim_start().trim_start_matches('t');
+            if time.is_empty() {
+                return Ok(DateExpr::Today(NaiveTime::MIN));
+            }
+            return NaiveTime::parse_from_str(time, "%H:%M")
+                .or_else(|_| NaiveTime::parse_from_str(time, "%H:%M:%S"))
+                .map(DateExpr::Today)
+                .map_err(|_| unknown());
+        }
+        if let Some(rest) = expression.strip_prefix("natal") {
+            let (sign, rest) = match rest.chars().next() {
+                Some('+') => (1, &rest[1..]),
+                Some('-') => (-1, &rest[1..]),
+                _ => return Err(unknown()),
+            };
+            let unit = rest.chars().last().ok_or_else(unknown)?;
+            let amount: i32 = rest[..rest.len() - unit.len_utf8()]
+                .parse()
+                .ok()
+                .filter(|amount: &i32| *amount >= 0)
+                .ok_or_else(unknown)?;
+            return match unit {
+                'y' => amount.checked_mul(12).map(|months| DateOffset::Months(sign * months)),
+                'm' => Some(DateOffset::Months(sign * amount)),
+                'd' => Some(DateOffset::Days(sign * amount)),
+                _ => None,
+            }
+            .map(DateExpr::Natal)
+            .ok_or_else(unknown);
+        }
+        s.trim().parse().map(DateExpr::At).map_err(|_| unknown())
+    }
+}
+
+impl TryFrom<String> for DateExpr {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+impl std::fmt::Display for DateExpr {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match *self {
+            DateExpr::At(date) => write!(f, "{}", date.to_rfc3339_opts(SecondsFormat::AutoSi, true)),
+            DateExpr::Now => write!(f, "now"),
+            DateExpr::Today(time) if time == NaiveTime::MIN => write!(f, "today"),
+            DateExpr::Today(time) => write!(f, "today T{}", time.format("%H:%M:%S")),
+            DateExpr::Natal(DateOffset::Months(months)) if months % 12 == 0 => write!(f, "natal{:+}y", months / 12),
+            DateExpr::Natal(DateOffset::Months(months)) => write!(f, "natal{:+}m", months),
+            DateExpr::Natal(DateOffset::Days(days)) => write!(f, "natal{:+}d", days),
+        }
+    }
+}
+
+impl From<DateExpr> for String {
+    fn from(date: DateExpr) -> Self {
+        date.to_string()
+    }
+}
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct TransitInfo {
-    pub date: DateTime<Utc>,
+    /// May be natal-relative, e.g. "natal+30y"
+    pub date: DateExpr,
+    #[serde(default = "default_transit_latitude")]
     pub latitude: f64,
+    #[serde(default = "default_transit_longitude")]
     pub longitude: f64,
 }
 
+// London coordinates as default
+fn default_transit_latitude() -> f64 {
+    51.45
+}
+
+fn default_transit_longitude() -> f64 {
+    0.05
+}
+
 impl Default for TransitInfo {
     fn default() -> Self {
         Self {
-            date: Utc::now(),
-            latitude: 51.45,  // London coordinates as default
-            longitude: 0.05,
+            date: DateExpr::Now,
+            latitude: default_transit_latitude(),
+            longitude: default_transit_longitude(),
         }
     }
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+/// Most transit moments one chart request may ask for
+pub const MAX_TRANSIT_SERIES: usize = 31;
+
+/// Most names or points any other list in a request may hold
+pub const MAX_REQUEST_LIST: usize = 64;
+
+/// Deserializes a list of at most `N` items, failing at the first one over
+/// rather than reading the whole array
+fn at_most<'de, D, T, const N: usize>(deserializer: D) -> Result<Vec<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    struct BoundedVisitor<T, const N: usize>(PhantomData<T>);
+
+    impl<'de, T: Deserialize<'de>, const N: usize> Visitor<'de> for BoundedVisitor<T, N> {
+        type Value = Vec<T>;
+
+        fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+            write!(f, "a list of at most {} items", N)
+        }
+
+        fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Vec<T>, A::Error> {
+            let mut items = Vec::with_capacity(seq.size_hint().unwrap_or(0).min(N));
+            while let Some(item) = seq.next_element()? {
+                if items.len() == N {
+                    return Err(de::Error::invalid_length(N + 1, &self));
+                }
+                items.push(item);
+            }
+            Ok(items)
+        }
+    }
+
+    deserializer.deserialize_seq(BoundedVisitor::<T, N>(PhantomData))
+}
+
+/// Longest range `/api/cycles` will search, in days (about twenty years)
+pub const MAX_CYCLE_RANGE_DAYS: i64 = 7305;
+
+/// Longest range `/api/transits/ical` will search, in days (about ten years)
+pub const MAX_TRANSIT_CALENDAR_DAYS: i64 = 3653;
+
+/// Most events one `/api/transits/ical` feed lists; later hits are left out
+pub const MAX_TRANSIT_CALENDAR_EVENTS: usize = 500;
+
+/// Longest `eclipse_lookback_months` of `/api/transits/ical` (ten years)
+pub const MAX_ECLIPSE_LOOKBACK_MONTHS: u32 = 120;
+
+/// Transiting bodies `/api/transits/ical` follows unless asked for others
+pub const DEFAULT_CALENDAR_PLANETS: &str = "jupiter,saturn,uranus,neptune,pluto";
+
+/// House system of a chart request that doesn't name one: the `astrolog.as`
+/// defaults' if they set one, else Placidus
+pub fn default_house_system() -> HouseSystem {
+    astrolog_defaults()
+        .and_then(|defaults| defaults.house_system)
+        .unwrap_or(HouseSystem::Placidus)
+}
+
+/// Extra bodies of a chart request that doesn't list any: those the
+/// `astrolog.as` defaults leave unrestricted
+pub fn default_extra_bodies() -> Vec<Planet> {
+    astrolog_defaults()
+        .map(|defaults| defaults.extra_bodies.clone())
+        .unwrap_or_default()
+}
+
+/// Layout version of `ChartResponse`, bumped whenever a field `/api/compare`
+/// reads changes meaning
+pub const CHART_SCHEMA_VERSION: u32 = 1;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
 pub struct ChartRequest {
-    pub date: DateTime<Utc>,
+    /// An RFC 3339 date, "now" or "today"
+    pub date: DateExpr,
     pub latitude: f64,
     pub longitude: f64,
-    pub house_system: String,
+    /// The `astrolog.as` defaults' house system when absent, else "placidus"
+    #[serde(default = "default_house_system")]
+    pub house_system: HouseSystem,
     pub ayanamsa: String,
     #[serde(default)]
     pub transit: Option<TransitInfo>,
+    /// Several transit moments for the same natal chart, returned in order as
+    /// `transits`; used instead of `transit`, up to `MAX_TRANSIT_SERIES`
+    #[serde(default, deserialize_with = "at_most::<_, _, MAX_TRANSIT_SERIES>")]
+    pub transits: Vec<TransitInfo>,
+    /// Look for the minor and harmonic aspects too; when absent, as the
+    /// `astrolog.as` defaults say, else false. In a synastry request this
+    /// chart's setting overrides the top-level one for its own aspects
     #[serde(default)]
-    pub include_minor_aspects: bool,
+    pub include_minor_aspects: Option<bool>,
+    /// Ephemeris source: "swiss", "moshier" or "auto"; defaults to the server setting
+    #[serde(default)]
+    pub ephemeris: Option<String>,
+    /// Use modern rulers (Pluto, Uranus, Neptune) for Scorpio, Aquarius and Pisces
+    #[serde(default)]
+    pub modern_rulers: bool,
+    /// SVG symbol rendering: "text" (default) or "paths" for viewers without astrological fonts
+    #[serde(default)]
+    pub glyph_mode: Option<String>,
+    /// Language for formatted positions, SVG labels and tooltips: "en", "es", "de", "fr"
+    /// or "pt"; the `Accept-Language` header's choice when absent, else "en"
+    #[serde(default)]
+    pub lang: Option<String>,
+    /// SVG detail: "full" (default) or "compact", which drops the degree labels
+    /// and rounds coordinates to one decimal for a smaller payload
+    #[serde(default)]
+    pub svg_detail: Option<String>,
+    /// SVG wheel: "degree" (default), or "whole_sign" to start the wheel at the
+    /// rising sign with each sign drawn as a house
+    #[serde(default)]
+    pub wheel_style: Option<String>,
+    /// SVG layers: "all" (default), or "dynamic" for only the `planets` and
+    /// `aspects` groups, to swap into a chart already shown
+    #[serde(default)]
+    pub svg_layers: Option<String>,
+    /// Draw a legend beside the SVG wheel listing each body's position, house
+    /// and motion, and the angles
+    #[serde(default)]
+    pub include_legend: bool,
+    /// Where the legend goes: "right" (default) or "bottom"
+    #[serde(default)]
+    pub legend_position: Option<String>,
+    /// In a synastry request, also draw the second chart's house cusps as short
+    /// ticks outside the zodiac ring; only the first chart's setting is read
+    #[serde(default)]
+    pub show_second_houses: bool,
+    /// Overlays on the natal wheel: critical degrees, the Aries point axes and
+    /// rings around bodies on an angle
+    #[serde(default)]
+    pub highlights: Option<HighlightsRequest>,
+    /// Also return `svg_thumbnail`, a 120-pixel mini-wheel for list views
+    #[serde(default)]
+    pub include_thumbnail: bool,
+    /// Return a `diagnostics` block with timings in the response
+    #[serde(default)]
+    pub debug: bool,
+    /// Report `exact_in_days` on applying natal aspects; each one costs extra ephemeris lookups
+    #[serde(default)]
+    pub include_exact_times: bool,
+    /// Narrow the aspect lists to particular planets, aspect types or orbs
+    #[serde(default)]
+    pub aspect_filter: Option<AspectFilterRequest>,
+    /// Keep at most this many aspects per list, the tightest orbs first; sets `truncated`
+    #[serde(default)]
+    pub max_aspects: Option<usize>,
+    /// Order of the aspect lists: "strength" (default), strongest first, or
+    /// "planets" for the order the bodies are listed in
+    #[serde(default)]
+    pub aspect_sort: Option<String>,
+    /// Extra aspects to look for, named in the aspect lists as given
+    #[serde(default, deserialize_with = "at_most::<_, _, MAX_CUSTOM_ASPECTS>")]
+    pub custom_aspects: Vec<CustomAspectRequest>,
+    /// Add an `interpretation` section with text for the chart's factors
+    #[serde(default)]
+    pub include_interpretation: bool,
+    /// Lunar node to use: "true" (default) or "mean"
+    #[serde(default)]
+    pub node_type: Option<String>,
+    /// Carry the natal planets forward by the precession to each transit date
+    /// before finding the transit-to-natal aspects
+    #[serde(default)]
+    pub precession_correction: bool,
+    /// Points derived from the angles to add as `points` and to the aspect lists:
+    /// "vertex", "anti_vertex" and "east_point". Houses must be calculable.
+    #[serde(default, deserialize_with = "at_most::<_, _, MAX_REQUEST_LIST>")]
+    pub aspect_points: Vec<String>,
+    /// Aspect the Ascendant and Midheaven too, at the `aspect_points` orbs.
+    /// Their opposites would only repeat the same aspects.
+    #[serde(default)]
+    pub include_angle_aspects: bool,
+    /// Add each planet's decan and its Chaldean ruler
+    #[serde(default)]
+    pub include_decans: bool,
+    /// Add the lunar mansion of each planet, and `moon_mansion`
+    #[serde(default)]
+    pub include_mansions: bool,
+    /// Add `angle_speeds`, how fast the Ascendant and Midheaven move at the
+    /// birthplace, for rectification
+    #[serde(default)]
+    pub include_angle_speeds: bool,
+    /// Mansions to use: "arabic" (28, default) or "nakshatra" (27)
+    #[serde(default)]
+    pub mansion_system: Option<String>,
+    /// "ecliptic" (default), or "equatorial" or "both" to add each planet's right
+    /// ascension and declination of date
+    #[serde(default)]
+    pub coordinates: Option<String>,
+    /// Calculate the natal positions as seen from the birthplace, at sea level,
+    /// rather than the Earth's centre; false when absent. Needs the Swiss Ephemeris.
+    #[serde(default)]
+    pub topocentric: bool,
+    /// Bodies to add after Pluto: "mean_node", "true_node", "mean_lilith",
+    /// "true_lilith", "chiron", "ceres", "pallas", "juno" or "vesta". One whose
+    /// position can't be calculated is left out and listed in `warnings`. The
+    /// bodies the `astrolog.as` defaults leave unrestricted when absent.
+    #[serde(default = "default_extra_bodies", deserialize_with = "at_most::<_, _, MAX_REQUEST_LIST>")]
+    pub extra_bodies: Vec<Planet>,
+    /// Add Chiron, Ceres, Pallas, Juno and Vesta after `extra_bodies`, those not
+    /// already there; false when absent. Like the extra bodies, one whose
+    /// ephemeris file is missing is listed in `warnings` instead.
+    #[serde(default)]
+    pub include_asteroids: bool,
+    /// Add the North Node of `node_type` and Mean Lilith after `extra_bodies`,
+    /// those not already there; true when absent
+    #[serde(default)]
+    pub include_nodes: Option<bool>,
+    /// Bodies the chart fails without, besides the Sun and Moon; each must be one
+    /// of the ten planets or in `extra_bodies`
+    #[serde(default, deserialize_with = "at_most::<_, _, MAX_REQUEST_LIST>")]
+    pub required_bodies: Vec<Planet>,
+    /// Degrees from a sign boundary within which a body is listed in
+    /// `boundary_warnings`; 0.5 by default, at most 5
+    #[serde(default)]
+    pub sign_boundary_orb: Option<f64>,
+    /// Degrees from a house cusp within which a body is listed in
+    /// `boundary_warnings`; 0.5 by default, at most 5
+    #[serde(default)]
+    pub house_cusp_orb: Option<f64>,
+    /// Degrees below the horizon the Sun can be for a day chart; 0 by default, at most 18
+    #[serde(default)]
+    pub sect_twilight: Option<f64>,
+    /// Calculate without the options the ephemeris can't honor, listing them in
+    /// `degraded_options`, rather than failing with a 422
+    #[serde(default)]
+    pub allow_degraded: bool,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct TransitRequest {
-    pub natal_date: DateTime<Utc>,
-    pub transit_date: DateTime<Utc>,
+    /// An RFC 3339 date, "now" or "today"
+    pub natal_date: DateExpr,
+    /// May be natal-relative, e.g. "natal+30y"
+    pub transit_date: DateExpr,
     pub latitude: f64,
     pub longitude: f64,
-    pub house_system: String,
+    pub house_system: HouseSystem,
     pub ayanamsa: String,
+    /// Look for the minor and harmonic aspects too; false when absent
     #[serde(default)]
     pub include_minor_aspects: bool,
+    /// Ephemeris source: "swiss", "moshier" or "auto"; defaults to the server setting
+    #[serde(default)]
+    pub ephemeris: Option<String>,
+    /// SVG symbol rendering: "text" (default) or "paths" for viewers without astrological fonts
+    #[serde(default)]
+    pub glyph_mode: Option<String>,
+    /// Language for formatted positions, SVG labels and tooltips: "en", "es", "de", "fr"
+    /// or "pt"; the `Accept-Language` header's choice when absent, else "en"
+    #[serde(default)]
+    pub lang: Option<String>,
+    /// SVG detail: "full" (default) or "compact", which drops the degree labels
+    /// and rounds coordinates to one decimal for a smaller payload
+    #[serde(default)]
+    pub svg_detail: Option<String>,
+    /// SVG wheel: "degree" (default), or "whole_sign" to start the wheel at the
+    /// rising sign with each sign drawn as a house
+    #[serde(default)]
+    pub wheel_style: Option<String>,
+    /// SVG layers: "all" (default), or "dynamic" for only the `planets` and
+    /// `aspects` groups, to swap into a chart already shown
+    #[serde(default)]
+    pub svg_layers: Option<String>,
+    /// Draw a legend beside the SVG wheel listing each body's position, house
+    /// and motion, and the angles
+    #[serde(default)]
+    pub include_legend: bool,
+    /// Where the legend goes: "right" (default) or "bottom"
+    #[serde(default)]
+    pub legend_position: Option<String>,
+    /// Return a `diagnostics` block with timings in the response
+    #[serde(default)]
+    pub debug: bool,
+    /// Narrow the aspect lists to particular planets, aspect types or orbs
+    #[serde(default)]
+    pub aspect_filter: Option<AspectFilterRequest>,
+    /// Keep at most this many aspects per list, the tightest orbs first; sets `truncated`
+    #[serde(default)]
+    pub max_aspects: Option<usize>,
+    /// Order of the aspect lists: "strength" (default), strongest first, or
+    /// "planets" for the order the bodies are listed in
+    #[serde(default)]
+    pub aspect_sort: Option<String>,
+    /// Extra aspects to look for, named in the aspect lists as given
+    #[serde(default, deserialize_with = "at_most::<_, _, MAX_CUSTOM_ASPECTS>")]
+    pub custom_aspects: Vec<CustomAspectRequest>,
+    /// Add `natal_angle_aspects`, from the natal angles and house cusps to the
+    /// transiting planets
+    #[serde(default)]
+    pub include_natal_angles: bool,
+    /// Aspect the natal Ascendant and Midheaven to the natal planets, as for a
+    /// natal chart
+    #[serde(default)]
+    pub include_angle_aspects: bool,
+    /// Add `transit_house_positions`, the natal house of each transiting planet
+    /// and when it entered it
+    #[serde(default)]
+    pub include_transit_houses: bool,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct SynastryRequest {
     pub chart1: ChartRequest,
     pub chart2: ChartRequest,
+    /// Look for the minor and harmonic aspects too, between the charts and within
+    /// each chart that doesn't set its own `include_minor_aspects`; false when absent
+    #[serde(default)]
+    pub include_minor_aspects: bool,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
-pub struct PlanetInfo {
+impl SynastryRequest {
+    /// Whether each chart's own aspects include the minor ones
+    pub fn chart_minor_aspects(&self) -> (bool, bool) {
+        (
+            self.chart1.include_minor_aspects.unwrap_or(self.include_minor_aspects),
+            self.chart2.include_minor_aspects.unwrap_or(self.include_minor_aspects),
+        )
+    }
+}
+
+/// A body in a chart sent back to `/api/compare`; any other `PlanetInfo` fields are ignored
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct PointInfo {
     pub name: String,
+    #[serde(serialize_with = "rounding::longitude")]
     pub longitude: f64,
-    pub latitude: f64,
+    #[serde(default)]
     pub speed: f64,
-    pub is_retrograde: bool,
-    pub house: Option<u8>,
 }
 
+impl From<&PointInfo> for ChartPoint {
+    fn from(point: &PointInfo) -> Self {
+        ChartPoint {
+            name: point.name.clone(),
+            longitude: point.longitude,
+            speed: point.speed,
+        }
+    }
+}
+
+impl From<ChartPoint> for PointInfo {
+    fn from(point: ChartPoint) -> Self {
+        PointInfo {
+            name: point.name,
+            longitude: point.longitude,
+            speed: point.speed,
+        }
+    }
+}
+
+/// A previously returned `ChartResponse`, or just its `schema_version`, `planets`
+/// and optionally `houses`
 #[derive(Debug, Serialize, Deserialize, Clone)]
-pub struct HouseInfo {
-    pub number: u8,
-    pub longitude: f64,
-    pub latitude: f64,
+pub struct ComparedChart {
+    /// Must match `CHART_SCHEMA_VERSION`
+    #[serde(default)]
+    pub schema_version: u32,
+    #[serde(deserialize_with = "at_most::<_, _, MAX_REQUEST_LIST>")]
+    pub planets: Vec<PointInfo>,
+    #[serde(default, deserialize_with = "at_most::<_, _, MAX_REQUEST_LIST>")]
+    pub houses: Vec<HouseInfo>,
+}
+
+impl ComparedChart {
+    /// Checks the chart came from a compatible version of this API and can be compared
+    pub fn validate(&self, label: &str) -> Result<(), String> {
+        match self.schema_version {
+            0 => return Err(format!("{} has no schema_version; send a chart returned by this API", label)),
+            CHART_SCHEMA_VERSION => {}
+            version => {
+                return Err(format!(
+                    "{} has schema_version {}, expected {}",
+                    label, version, CHART_SCHEMA_VERSION
+                ))
+            }
+        }
+        if self.planets.is_empty() {
+            return Err(format!("{} has no planets", label));
+        }
+        if let Some(point) = self.planets.iter().find(|p| !p.longitude.is_finite()) {
+            return Err(format!("{} has an invalid longitude for {}", label, point.name));
+        }
+        if !self.houses.is_empty() && self.houses.len() != 12 {
+            return Err(format!("{} must have 12 houses or none, not {}", label, self.houses.len()));
+        }
+        Ok(())
+    }
+
+    pub fn points(&self) -> Vec<ChartPoint> {
+        self.planets.iter().map(ChartPoint::from).collect()
+    }
+
+    /// Cusp longitudes in house order, when the chart has houses
+    pub fn cusps(&self) -> Option<[f64; 12]> {
+        let mut houses: Vec<&HouseInfo> = self.houses.iter().collect();
+        houses.sort_by_key(|h| h.number);
+        let cusps: Vec<f64> = houses.iter().map(|h| h.longitude).collect();
+        cusps.try_into().ok()
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
-pub struct AspectInfo {
-    pub planet1: String,
-    pub planet2: String,
-    pub aspect: String,
-    pub orb: f64,
+pub struct CompareRequest {
+    pub chart1: ComparedChart,
+    pub chart2: ComparedChart,
+    /// Look for the minor and harmonic aspects too; false when absent
+    #[serde(default)]
+    pub include_minor_aspects: bool,
+    /// Order of the synastry aspects: "strength" (default) or "planets"
+    #[serde(default)]
+    pub aspect_sort: Option<String>,
+    /// How the composite's cusps are derived: "porphyry" (default) or "derive_placidus"
+    #[serde(default)]
+    pub composite_houses: Option<String>,
 }
 
+/// A planet of one chart placed in the other chart's houses
 #[derive(Debug, Serialize, Deserialize, Clone)]
-pub struct SynastryAspectInfo {
-    pub person1: String,
-    pub person2: String,
-    pub aspect: String,
-    pub orb: f64,
+pub struct HouseOverlayInfo {
+    pub planet: String,
+    pub house: u8,
+    /// How far through the house the planet sits, from 0.0 at its cusp
+    pub house_fraction: f64,
+}
+
+impl From<HouseOverlay> for HouseOverlayInfo {
+    fn from(overlay: HouseOverlay) -> Self {
+        Self {
+            planet: overlay.name,
+            house: overlay.placement.house,
+            house_fraction: overlay.placement.fraction,
+        }
+    }
+}
+
+/// A placement in the first chart and in the second
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct ChangeInfo<T> {
+    pub from: T,
+    pub to: T,
+    pub changed: bool,
+}
+
+impl<T: PartialEq> From<Change<T>> for ChangeInfo<T> {
+    fn from(change: Change<T>) -> Self {
+        Self {
+            changed: change.changed(),
+            from: change.from,
+            to: change.to,
+        }
+    }
 }
 
+/// How one body's placement differs between two charts
 #[derive(Debug, Serialize, Deserialize, Clone)]
-pub struct ChartResponse {
+pub struct PlacementChangeInfo {
+    pub body: String,
+    pub sign: ChangeInfo<Sign>,
+    /// Both positions in the first chart's houses; null when it has none
+    pub house: Option<ChangeInfo<u8>>,
+    /// Each position in its own chart's houses; null unless both have houses
+    pub own_house: Option<ChangeInfo<u8>>,
+    pub retrograde: ChangeInfo<bool>,
+}
+
+impl From<PlacementChange> for PlacementChangeInfo {
+    fn from(change: PlacementChange) -> Self {
+        Self {
+            body: change.name,
+            sign: change.sign.into(),
+            house: change.house.map(ChangeInfo::from),
+            own_house: change.own_house.map(ChangeInfo::from),
+            retrograde: change.retrograde.into(),
+        }
+    }
+}
+
+/// Sign, house and direction changes of the bodies found in both of two charts
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PlacementDiffInfo {
+    /// In the first chart's order
+    pub bodies: Vec<PlacementChangeInfo>,
+    pub sign_changes: usize,
+    pub house_changes: usize,
+    pub own_house_changes: usize,
+    pub retrograde_changes: usize,
+}
+
+impl From<PlacementDiff> for PlacementDiffInfo {
+    fn from(diff: PlacementDiff) -> Self {
+        Self {
+            bodies: diff.bodies.into_iter().map(PlacementChangeInfo::from).collect(),
+            sign_changes: diff.sign_changes,
+            house_changes: diff.house_changes,
+            own_house_changes: diff.own_house_changes,
+            retrograde_changes: diff.retrograde_changes,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CompareResponse {
     pub chart_type: String,
+    /// Built-in aspects looked for between the charts: "major", or "all" with the minor ones
+    #[serde(default)]
+    pub aspect_set: AspectSet,
+    /// Aspects from chart1's planets to chart2's, as `/api/chart/synastry` returns them
+    pub synastries: Vec<SynastryAspectInfo>,
+    /// chart1's planets in chart2's houses; empty when chart2 has no houses
+    pub chart1_in_chart2_houses: Vec<HouseOverlayInfo>,
+    /// chart2's planets in chart1's houses; empty when chart1 has no houses
+    pub chart2_in_chart1_houses: Vec<HouseOverlayInfo>,
+    /// Midpoint of each planet found in both charts, in chart1's order
+    pub composite: Vec<PointInfo>,
+    /// Cusps of the composite chart; empty unless both charts have houses
+    pub composite_houses: Vec<HouseInfo>,
+    /// How each planet found in both charts moved from chart1 to chart2
+    pub chart2_vs_chart1: PlacementDiffInfo,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct HouseCompareRequest {
     pub date: DateTime<Utc>,
     pub latitude: f64,
     pub longitude: f64,
-    pub house_system: String,
-    pub ayanamsa: String,
-    pub planets: Vec<PlanetInfo>,
-    pub houses: Vec<HouseInfo>,
-    pub aspects: Vec<AspectInfo>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub transit: Option<TransitData>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub svg_chart: Option<String>,
+    /// House systems to compare, e.g. ["placidus", "equal", "whole_sign"]
+    pub house_systems: Vec<HouseSystem>,
+    /// Ephemeris source: "swiss", "moshier" or "auto"; defaults to the server setting
+    #[serde(default)]
+    pub ephemeris: Option<String>,
+}
+
+impl HouseCompareRequest {
+    /// The requested systems in order: at least one, none repeated, and each
+    /// dividing the chart into 12 houses
+    pub fn house_systems(&self) -> Result<Vec<HouseSystem>, String> {
+        if self.house_systems.is_empty() {
+            return Err("house_systems must name at least one house system".to_string());
+        }
+        let mut systems = Vec::with_capacity(self.house_systems.len());
+        for &system in &self.house_systems {
+            if system.cusp_count() != 12 {
+                return Err(format!("{} doesn't divide the chart into 12 houses", system));
+            }
+            if systems.contains(&system) {
+                return Err(format!("{} is listed more than once", system));
+            }
+            systems.push(system);
+        }
+        Ok(systems)
+    }
 }
 
+/// A planet's house under each compared system
 #[derive(Debug, Serialize, Deserialize, Clone)]
-pub struct TransitData {
-    pub date: DateTime<Utc>,
-    pub latitude: f64,
+pub struct PlanetHousesInfo {
+    pub planet: String,
+    #[serde(serialize_with = "rounding::longitude")]
     pub longitude: f64,
-    pub planets: Vec<PlanetInfo>,
-    pub aspects: Vec<AspectInfo>,
-    pub transit_to_natal_aspects: Vec<AspectInfo>,
+    /// House numbers, in `house_systems` order
+    pub houses: Vec<u8>,
+    /// Whether the systems disagree on the planet's house
+    pub changes: bool,
+}
+
+impl From<PlanetHouses> for PlanetHousesInfo {
+    fn from(planet: PlanetHouses) -> Self {
+        Self {
+            planet: planet.name,
+            longitude: planet.longitude,
+            houses: planet.houses,
+            changes: planet.changes,
+        }
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
-pub struct TransitResponse {
+pub struct HouseSystemPairInfo {
+    pub system1: HouseSystem,
+    pub system2: HouseSystem,
+    /// Planets in a different house under the two systems
+    pub planets_moved: usize,
+}
+
+impl From<SystemPairChanges> for HouseSystemPairInfo {
+    fn from(pair: SystemPairChanges) -> Self {
+        Self {
+            system1: pair.system1,
+            system2: pair.system2,
+            planets_moved: pair.planets_moved,
+        }
+    }
+}
+
+/// One house's cusp under each compared system
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CuspComparisonInfo {
+    pub house: u8,
+    /// Cusp longitudes, in `house_systems` order
+    pub longitudes: Vec<f64>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct HouseCompareResponse {
     pub chart_type: String,
-    pub natal_date: DateTime<Utc>,
-    pub transit_date: DateTime<Utc>,
+    pub date: DateTime<Utc>,
     pub latitude: f64,
     pub longitude: f64,
-    pub house_system: String,
-    pub ayanamsa: String,
-    pub natal_planets: Vec<PlanetInfo>,
-    pub transit_planets: Vec<PlanetInfo>,
-    pub houses: Vec<HouseInfo>,
-    pub natal_aspects: Vec<AspectInfo>,
-    pub transit_aspects: Vec<AspectInfo>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub svg_chart: Option<String>,
+    /// The compared systems; every per-system list follows this order
+    pub house_systems: Vec<HouseSystem>,
+    pub planets: Vec<PlanetHousesInfo>,
+    /// Every pair of systems once, the earlier listed system first
+    pub pairs: Vec<HouseSystemPairInfo>,
+    pub cusps: Vec<CuspComparisonInfo>,
+    /// How the positions were calculated
+    pub meta: CalculationMeta,
 }
 
+/// A degree to look up in a chart, for `POST /api/chart/at_degree`
 #[derive(Debug, Serialize, Deserialize, Clone)]
-pub struct SynastryResponse {
-    pub chart_type: String,
-    pub chart1: ChartResponse,
-    pub chart2: ChartResponse,
-    pub synastries: Vec<SynastryAspectInfo>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub svg_chart: Option<String>,
+pub struct AtDegreeRequest {
+    /// A chart returned by this API, as `POST /api/compare` takes it
+    pub chart: ComparedChart,
+    /// Ecliptic longitude to look up, in degrees
+    pub longitude: f64,
+    /// Give Scorpio, Aquarius and Pisces their modern rulers; false when absent
+    #[serde(default)]
+    pub modern_rulers: bool,
 }
 
-impl From<PlanetPosition> for PlanetInfo {
-    fn from(position: PlanetPosition) -> Self {
-        Self {
+/// The Egyptian term a degree is in
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct TermInfo {
+    pub ruler: String,
+    /// Longitudes where the term begins and ends
+    pub start: f64,
+    pub end: f64,
+}
+
+impl From<Term> for TermInfo {
+    fn from(term: Term) -> Self {
+        Self {
+            ruler: term.ruler.to_string(),
+            start: term.start,
+            end: term.end,
+        }
+    }
+}
+
+/// A chart point within 5° of the looked-up degree
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct NearbyPointInfo {
+    pub name: String,
+    #[serde(serialize_with = "rounding::longitude")]
+    pub longitude: f64,
+    /// Degrees from the looked-up longitude, positive when the point is later in the zodiac
+    #[serde(serialize_with = "rounding::orb")]
+    pub distance: f64,
+}
+
+impl From<NearbyPoint> for NearbyPointInfo {
+    fn from(point: NearbyPoint) -> Self {
+        Self {
+            name: point.name,
+            longitude: point.longitude,
+            distance: point.distance,
+        }
+    }
+}
+
+/// A midpoint of two chart points within 1° of the looked-up degree
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct NearbyMidpointInfo {
+    pub point1: String,
+    pub point2: String,
+    #[serde(serialize_with = "rounding::longitude")]
+    pub longitude: f64,
+    #[serde(serialize_with = "rounding::orb")]
+    pub orb: f64,
+}
+
+impl From<NearbyMidpoint> for NearbyMidpointInfo {
+    fn from(midpoint: NearbyMidpoint) -> Self {
+        Self {
+            point1: midpoint.point1,
+            point2: midpoint.point2,
+            longitude: midpoint.longitude,
+            orb: midpoint.orb,
+        }
+    }
+}
+
+/// The Sabian symbol of a degree
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SabianInfo {
+    /// The zodiacal degree 1-360
+    pub degree: u16,
+    /// The degree within its sign, e.g. "Aries 1" for 0°00'-0°59' Aries
+    pub label: String,
+    /// The symbol from the content pack, under the key `sabian_{degree}`;
+    /// absent when the pack has none
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub text: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct AtDegreeResponse {
+    #[serde(serialize_with = "rounding::longitude")]
+    pub longitude: f64,
+    pub sign: String,
+    pub sign_ruler: String,
+    pub decan: DecanInfo,
+    pub term: TermInfo,
+    /// Absent when the chart has no houses
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub house: Option<u8>,
+    /// How far through the house the degree is, from 0.0 at its cusp
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub house_fraction: Option<f64>,
+    /// Closest first
+    pub nearby: Vec<NearbyPointInfo>,
+    /// Closest first
+    pub midpoints: Vec<NearbyMidpointInfo>,
+    pub sabian: SabianInfo,
+}
+
+/// A chart to render in the background, for `POST /api/render`
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RenderRequest {
+    /// The chart, as sent to `POST /api/chart`
+    pub chart: ChartRequest,
+    /// Output format; only "svg" (the default) is available
+    #[serde(default)]
+    pub format: Option<String>,
+}
+
+/// Formats `POST /api/render` knows, each with the reason it can't be rendered
+/// here, if it can't
+pub const RENDER_FORMATS: [(&str, Option<&str>); 2] = [
+    ("svg", None),
+    ("png", Some("PNG rendering is not available; request \"svg\"")),
+];
+
+impl RenderRequest {
+    /// Checks the output format is one this server can render
+    pub fn validate_format(&self) -> Result<(), String> {
+        let format = self.format.as_deref().unwrap_or("svg").to_lowercase();
+        match RENDER_FORMATS.iter().find(|(name, _)| *name == format) {
+            Some((_, None)) => Ok(()),
+            Some((_, Some(reason))) => Err(reason.to_string()),
+            None => Err(format!("Invalid render format: {}", format)),
+        }
+    }
+}
+
+/// A render job that hasn't finished yet
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RenderJobInfo {
+    pub id: u64,
+    /// Always "pending"; a finished job returns its artifact instead
+    pub status: String,
+}
+
+/// Which return `POST /api/chart/return` looks for: "next", "previous" or `{"year": 2027}`
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(untagged)]
+pub enum ReturnOccurrence {
+    Relative(ReturnDirection),
+    /// The first return whose true pass falls in this calendar year (UT)
+    Year { year: i32 },
+}
+
+impl Default for ReturnOccurrence {
+    fn default() -> Self {
+        ReturnOccurrence::Relative(ReturnDirection::Next)
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum ReturnDirection {
+    Next,
+    Previous,
+}
+
+/// A place to cast a chart for
+#[derive(Debug, Serialize, Deserialize, Clone, Copy)]
+pub struct Location {
+    pub latitude: f64,
+    pub longitude: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ReturnRequest {
+    /// An RFC 3339 date, "now" or "today"
+    pub natal_date: DateExpr,
+    pub latitude: f64,
+    pub longitude: f64,
+    pub house_system: HouseSystem,
+    pub ayanamsa: String,
+    /// The returning body, e.g. "sun", "moon" or "saturn"
+    pub body: Planet,
+    #[serde(default)]
+    pub occurrence: ReturnOccurrence,
+    /// Moment "next" and "previous" count from, natal-relative ones included;
+    /// defaults to now
+    #[serde(default)]
+    pub reference_date: Option<DateExpr>,
+    /// Where to cast the return chart; defaults to the birthplace
+    #[serde(default)]
+    pub location: Option<Location>,
+    /// Look for the minor and harmonic aspects too; false when absent
+    #[serde(default)]
+    pub include_minor_aspects: bool,
+    /// Ephemeris source: "swiss", "moshier" or "auto"; defaults to the server setting
+    #[serde(default)]
+    pub ephemeris: Option<String>,
+    /// Use modern rulers (Pluto, Uranus, Neptune) for Scorpio, Aquarius and Pisces
+    #[serde(default)]
+    pub modern_rulers: bool,
+    /// SVG symbol rendering: "text" (default) or "paths" for viewers without astrological fonts
+    #[serde(default)]
+    pub glyph_mode: Option<String>,
+    /// Language for formatted positions, SVG labels and tooltips: "en", "es", "de", "fr"
+    /// or "pt"; the `Accept-Language` header's choice when absent, else "en"
+    #[serde(default)]
+    pub lang: Option<String>,
+    /// SVG detail: "full" (default) or "compact", which drops the degree labels
+    /// and rounds coordinates to one decimal for a smaller payload
+    #[serde(default)]
+    pub svg_detail: Option<String>,
+    /// SVG wheel: "degree" (default), or "whole_sign" to start the wheel at the
+    /// rising sign with each sign drawn as a house
+    #[serde(default)]
+    pub wheel_style: Option<String>,
+    /// SVG layers: "all" (default), or "dynamic" for only the `planets` and
+    /// `aspects` groups, to swap into a chart already shown
+    #[serde(default)]
+    pub svg_layers: Option<String>,
+    /// Draw a legend beside the SVG wheel listing each body's position, house
+    /// and motion, and the angles
+    #[serde(default)]
+    pub include_legend: bool,
+    /// Where the legend goes: "right" (default) or "bottom"
+    #[serde(default)]
+    pub legend_position: Option<String>,
+    /// Narrow the aspect lists to particular planets, aspect types or orbs
+    #[serde(default)]
+    pub aspect_filter: Option<AspectFilterRequest>,
+    /// Keep at most this many aspects per list, the tightest orbs first; sets `truncated`
+    #[serde(default)]
+    pub max_aspects: Option<usize>,
+    /// Order of the aspect lists: "strength" (default), strongest first, or
+    /// "planets" for the order the bodies are listed in
+    #[serde(default)]
+    pub aspect_sort: Option<String>,
+    /// Extra aspects to look for, named in the aspect lists as given
+    #[serde(default, deserialize_with = "at_most::<_, _, MAX_CUSTOM_ASPECTS>")]
+    pub custom_aspects: Vec<CustomAspectRequest>,
+    /// Lunar node to use: "true" (default) or "mean"
+    #[serde(default)]
+    pub node_type: Option<String>,
+    /// Look for the return to the natal longitude carried forward by the
+    /// precession to the return date
+    #[serde(default)]
+    pub precession_correction: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct MundaneRequest {
+    /// "aries_ingress", "cancer_ingress", "libra_ingress", "capricorn_ingress",
+    /// "new_moon" or "full_moon"
+    pub event: String,
+    pub year: i32,
+    /// Month (1-12) to find a lunation in; required for lunations
+    #[serde(default)]
+    pub month: Option<u32>,
+    /// Where to cast the chart, usually a capital city
+    pub location: Location,
+    /// House system; Placidus when absent
+    #[serde(default)]
+    pub house_system: Option<HouseSystem>,
+    /// Look for the minor and harmonic aspects too; false when absent
+    #[serde(default)]
+    pub include_minor_aspects: bool,
+    /// Ephemeris source: "swiss", "moshier" or "auto"; defaults to the server setting
+    #[serde(default)]
+    pub ephemeris: Option<String>,
+    /// Use modern rulers (Pluto, Uranus, Neptune) for Scorpio, Aquarius and Pisces
+    #[serde(default)]
+    pub modern_rulers: bool,
+    /// SVG symbol rendering: "text" (default) or "paths" for viewers without astrological fonts
+    #[serde(default)]
+    pub glyph_mode: Option<String>,
+    /// Language for formatted positions, SVG labels and tooltips: "en", "es", "de", "fr"
+    /// or "pt"; the `Accept-Language` header's choice when absent, else "en"
+    #[serde(default)]
+    pub lang: Option<String>,
+    /// SVG detail: "full" (default) or "compact"
+    #[serde(default)]
+    pub svg_detail: Option<String>,
+    /// SVG wheel: "degree" (default) or "whole_sign"
+    #[serde(default)]
+    pub wheel_style: Option<String>,
+    /// SVG layers: "all" (default) or "dynamic"
+    #[serde(default)]
+    pub svg_layers: Option<String>,
+    /// Draw a legend beside the SVG wheel
+    #[serde(default)]
+    pub include_legend: bool,
+    /// Where the legend goes: "right" (default) or "bottom"
+    #[serde(default)]
+    pub legend_position: Option<String>,
+    /// Order of the aspect list: "strength" (default) or "planets"
+    #[serde(default)]
+    pub aspect_sort: Option<String>,
+    /// Lunar node to use: "true" (default) or "mean"
+    #[serde(default)]
+    pub node_type: Option<String>,
+}
+
+/// Aspect filter as sent by clients; see `AspectFilter`
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct AspectFilterRequest {
+    /// Keep aspects involving any of these planets, e.g. ["Sun", "Moon"]
+    #[serde(default, deserialize_with = "at_most::<_, _, MAX_REQUEST_LIST>")]
+    pub planets: Vec<String>,
+    /// Keep only these aspect types, e.g. ["Conjunction", "Square"]
+    #[serde(default, deserialize_with = "at_most::<_, _, MAX_REQUEST_LIST>")]
+    pub aspects: Vec<AspectType>,
+    /// Drop aspects with a wider orb, in degrees
+    #[serde(default)]
+    pub max_orb: Option<f64>,
+    /// Keep aspects whose other planet is one of these
+    #[serde(default, deserialize_with = "at_most::<_, _, MAX_REQUEST_LIST>")]
+    pub to_points: Vec<String>,
+}
+
+/// Natal wheel overlays as sent by clients; see `Highlights`
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct HighlightsRequest {
+    /// Degrees to mark with a tick into the zodiac ring
+    #[serde(default)]
+    pub critical_degrees: Option<CriticalDegrees>,
+    /// Mark 0° Aries, Cancer, Libra and Capricorn across the zodiac ring
+    #[serde(default)]
+    pub aries_points: bool,
+    /// Ring the bodies listed in `angular`
+    #[serde(default)]
+    pub angular: bool,
+}
+
+/// `true` for the classical critical degrees, or the zodiac longitudes to
+/// mark in whole degrees, 0 to 359
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(untagged)]
+pub enum CriticalDegrees {
+    Classical(bool),
+    Degrees(Vec<f64>),
+}
+
+/// Builds the aspect filter for a request, rejecting unknown aspect names
+fn aspect_filter(
+    filter: Option<&AspectFilterRequest>,
+    max_aspects: Option<usize>,
+    sort: Option<&str>,
+) -> Result<AspectFilter, String> {
+    let sort: AspectSort = sort.map(str::parse).transpose()?.unwrap_or_default();
+    let Some(filter) = filter else {
+        return Ok(AspectFilter {
+            max_aspects,
+            sort,
+            ..Default::default()
+        });
+    };
+    Ok(AspectFilter {
+        planets: filter.planets.clone(),
+        aspects: filter.aspects.clone(),
+        max_orb: filter.max_orb,
+        to_points: filter.to_points.clone(),
+        max_aspects,
+        sort,
+    })
+}
+
+/// A client-defined aspect; see `AspectDef::custom` for the accepted ranges
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CustomAspectRequest {
+    /// Name reported in the aspect lists, e.g. "Decile"
+    pub name: String,
+    /// Exact angle in degrees, between 0 and 180
+    pub angle: f64,
+    /// Allowed deviation from the exact angle, in degrees
+    pub orb: f64,
+}
+
+/// Builds and validates a request's custom aspects
+fn custom_aspects(custom: &[CustomAspectRequest]) -> Result<Vec<AspectDef>, String> {
+    let defs = custom
+        .iter()
+        .map(|aspect| AspectDef::custom(&aspect.name, aspect.angle, aspect.orb))
+        .collect::<Result<Vec<_>, String>>()?;
+    validate_custom_aspects(&defs)?;
+    Ok(defs)
+}
+
+impl ChartRequest {
+    /// The aspect filter and cap requested for this chart
+    pub fn aspect_filter(&self) -> Result<AspectFilter, String> {
+        aspect_filter(self.aspect_filter.as_ref(), self.max_aspects, self.aspect_sort.as_deref())
+    }
+
+    /// The custom aspects requested for this chart
+    pub fn custom_aspects(&self) -> Result<Vec<AspectDef>, String> {
+        custom_aspects(&self.custom_aspects)
+    }
+
+    /// Whether to look for the minor aspects: as asked for, else as the
+    /// `astrolog.as` defaults say, else false
+    pub fn include_minor_aspects(&self) -> bool {
+        self.include_minor_aspects
+            .or_else(|| astrolog_defaults().and_then(|defaults| defaults.include_minor_aspects))
+            .unwrap_or(false)
+    }
+
+    /// The requested angle points in request order, each at most once
+    pub fn aspect_points(&self) -> Result<Vec<AnglePoint>, String> {
+        let mut points = Vec::with_capacity(self.aspect_points.len());
+        for name in &self.aspect_points {
+            let point: AnglePoint = name.parse()?;
+            if points.contains(&point) {
+                return Err(format!("Point '{}' is listed more than once", name));
+            }
+            points.push(point);
+        }
+        Ok(points)
+    }
+
+    /// The birthplace, when the positions are to be seen from it
+    pub fn observer(&self) -> Option<Observer> {
+        self.topocentric.then_some(Observer {
+            longitude: self.longitude,
+            latitude: self.latitude,
+            altitude: 0.0,
+        })
+    }
+
+    /// The mansion system to report, or `None` without `include_mansions`
+    pub fn mansion_system(&self) -> Result<Option<MansionSystem>, String> {
+        let system = match self.mansion_system.as_deref() {
+            Some(name) => name.parse()?,
+            None => MansionSystem::default(),
+        };
+        Ok(self.include_mansions.then_some(system))
+    }
+
+    /// The coordinates to give each planet
+    pub fn coordinate_system(&self) -> Result<CoordinateSystem, String> {
+        self.coordinates.as_deref().map_or(Ok(CoordinateSystem::default()), str::parse)
+    }
+
+    /// The bodies to calculate, the ten planets then `extra_bodies` and any node,
+    /// Lilith and asteroids `include_nodes` and `include_asteroids` add, and the
+    /// ones the chart can't do without
+    pub fn bodies(&self) -> Result<(Vec<Planet>, Vec<Planet>), String> {
+        let mut bodies = CHART_PLANETS.to_vec();
+        for &planet in &self.extra_bodies {
+            if CHART_PLANETS.contains(&planet) || !POSITION_BODIES.contains(&planet) {
+                return Err(format!("'{}' can't be added to a chart", planet));
+            }
+            if bodies.contains(&planet) {
+                return Err(format!("Body '{}' is listed more than once", planet));
+            }
+            bodies.push(planet);
+        }
+        if self.include_nodes.unwrap_or(true) {
+            let node_type: NodeType = self.node_type.as_deref().map(str::parse).transpose()?.unwrap_or_default();
+            for planet in [node_planet(node_type), Planet::MeanLilith] {
+                if !bodies.contains(&planet) {
+                    bodies.push(planet);
+                }
+            }
+        }
+        if self.include_asteroids {
+            for &planet in ASTEROID_BODIES {
+                if !bodies.contains(&planet) {
+                    bodies.push(planet);
+                }
+            }
+        }
+        let mut required = REQUIRED_PLANETS.to_vec();
+        for &planet in &self.required_bodies {
+            if !bodies.contains(&planet) {
+                return Err(format!("Required body '{}' isn't in the chart; add it to extra_bodies", planet));
+            }
+            if !required.contains(&planet) {
+                required.push(planet);
+            }
+        }
+        Ok((bodies, required))
+    }
+
+    /// The sign boundary and house cusp orbs for `boundary_warnings`
+    pub fn boundary_orbs(&self) -> Result<(f64, f64), String> {
+        Ok((
+            boundary_orb(self.sign_boundary_orb, "sign_boundary_orb")?,
+            boundary_orb(self.house_cusp_orb, "house_cusp_orb")?,
+        ))
+    }
+
+    /// The twilight allowance for the chart's sect
+    pub fn sect_twilight(&self) -> Result<f64, String> {
+        sect::twilight(self.sect_twilight)
+    }
+
+    /// Replaces each option `capabilities` can't honor with what the chart will
+    /// be calculated with instead, and lists them. Options that don't parse are
+    /// left for their own checks to reject.
+    pub fn degrade(&mut self, capabilities: &EphemerisCapabilities, default_source: EphemerisSource) -> Vec<DegradedOptionInfo> {
+        let mut degraded = Vec::new();
+        if let Some(Ok(source)) = self.ephemeris.as_deref().map(str::parse::<EphemerisSource>) {
+            if !capabilities.sources.contains(&source) {
+                degraded.push(DegradedOptionInfo {
+                    option: "ephemeris".to_string(),
+                    requested: source.to_string(),
+                    used: default_source.to_string(),
+                    reason: format!("The {} ephemeris isn't available", source),
+                });
+                self.ephemeris = None;
+            }
+        }
+        if self.topocentric && !capabilities.topocentric {
+            degraded.push(DegradedOptionInfo {
+                option: "topocentric".to_string(),
+                requested: "topocentric".to_string(),
+                used: "geocentric".to_string(),
+                reason: "The ephemeris has no topocentric positions".to_string(),
+            });
+            self.topocentric = false;
+        }
+        if matches!(parse_chart_ayanamsa(&self.ayanamsa), Ok(Some(_))) && !capabilities.sidereal {
+            degraded.push(DegradedOptionInfo {
+                option: "ayanamsa".to_string(),
+                requested: self.ayanamsa.clone(),
+                used: Zodiac::Tropical.key().to_string(),
+                reason: "The ephemeris has no sidereal zodiac".to_string(),
+            });
+            self.ayanamsa = Zodiac::Tropical.key().to_string();
+        }
+        degraded
+    }
+}
+
+impl TransitRequest {
+    /// The aspect filter and cap requested for this chart
+    pub fn aspect_filter(&self) -> Result<AspectFilter, String> {
+        aspect_filter(self.aspect_filter.as_ref(), self.max_aspects, self.aspect_sort.as_deref())
+    }
+
+    /// The custom aspects requested for this chart
+    pub fn custom_aspects(&self) -> Result<Vec<AspectDef>, String> {
+        custom_aspects(&self.custom_aspects)
+    }
+}
+
+impl CompareRequest {
+    /// Orders the synastry aspects; comparisons don't filter them
+    pub fn aspect_filter(&self) -> Result<AspectFilter, String> {
+        aspect_filter(None, None, self.aspect_sort.as_deref())
+    }
+
+    /// How to derive the composite's cusps, Porphyry when not given
+    pub fn composite_houses(&self) -> Result<CompositeHouses, String> {
+        self.composite_houses.as_deref().map(str::parse).transpose().map(Option::unwrap_or_default)
+    }
+}
+
+impl ReturnRequest {
+    /// The aspect filter and cap requested for the return chart
+    pub fn aspect_filter(&self) -> Result<AspectFilter, String> {
+        aspect_filter(self.aspect_filter.as_ref(), self.max_aspects, self.aspect_sort.as_deref())
+    }
+
+    /// The custom aspects requested for the return chart
+    pub fn custom_aspects(&self) -> Result<Vec<AspectDef>, String> {
+        custom_aspects(&self.custom_aspects)
+    }
+}
+
+/// Query string for `GET /api/cycles`
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CyclesQuery {
+    /// Two bodies joined by a hyphen, e.g. "jupiter-saturn"
+    pub pair: String,
+    /// First day of the range (inclusive, from 00:00 UT)
+    pub from: NaiveDate,
+    /// Last day of the range (exclusive, up to 00:00 UT)
+    pub to: NaiveDate,
+    /// Ephemeris source: "swiss", "moshier" or "auto"; defaults to the server setting
+    #[serde(default)]
+    pub ephemeris: Option<String>,
+}
+
+/// Query string for `GET /api/transits/ical`
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct TransitCalendarQuery {
+    /// Birth moment of the natal chart; its Sun through Pluto are the transited points
+    pub natal: DateTime<Utc>,
+    /// First day of the range (inclusive, from 00:00 UT)
+    pub from: NaiveDate,
+    /// Last day of the range (exclusive, up to 00:00 UT)
+    pub to: NaiveDate,
+    /// Comma-separated transiting bodies, including "north_node" and
+    /// "south_node"; defaults to `DEFAULT_CALENDAR_PLANETS`
+    #[serde(default)]
+    pub planets: Option<String>,
+    /// Ephemeris source: "swiss", "moshier" or "auto"; defaults to the server setting
+    #[serde(default)]
+    pub ephemeris: Option<String>,
+    /// Add the natal planets within 1° of the degrees of the last this many
+    /// eclipses before `to`
+    #[serde(default)]
+    pub eclipse_points: Option<usize>,
+    /// Months before `to` the eclipses are taken from; `DEFAULT_ECLIPSE_LOOKBACK_MONTHS` by default
+    #[serde(default)]
+    pub eclipse_lookback_months: Option<u32>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PlanetInfo {
+    pub name: String,
+    #[serde(serialize_with = "rounding::longitude")]
+    pub longitude: f64,
+    #[serde(serialize_with = "rounding::latitude")]
+    pub latitude: f64,
+    pub speed: f64,
+    pub is_retrograde: bool,
+    /// "direct", "retrograde" or "stationary"
+    #[serde(default)]
+    pub motion: Motion,
+    /// Degrees and minutes in the sign, e.g. "12°05' Scorpio", in the request's `lang`
+    #[serde(default)]
+    pub formatted_position: String,
+    pub house: Option<u8>,
+    /// How far through its house the planet sits (0.0 at the cusp, approaching 1.0 at the next)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub house_fraction: Option<f64>,
+    /// Present with `include_decans`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub decan: Option<DecanInfo>,
+    /// Present with `include_mansions`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mansion: Option<MansionInfo>,
+    /// Right ascension and declination, with `coordinates` "equatorial" or "both"
+    #[serde(flatten)]
+    pub equatorial: Option<EquatorialInfo>,
+}
+
+/// A body's place on the celestial equator, of date
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct EquatorialInfo {
+    /// Degrees, 0-360
+    #[serde(serialize_with = "rounding::longitude")]
+    pub right_ascension: f64,
+    /// The same in hours, 0-24
+    #[serde(serialize_with = "rounding::latitude")]
+    pub right_ascension_hours: f64,
+    #[serde(serialize_with = "rounding::latitude")]
+    pub declination: f64,
+    /// The right ascension as "HHh MMm SSs"
+    pub formatted_ra: String,
+}
+
+impl From<EquatorialPosition> for EquatorialInfo {
+    fn from(position: EquatorialPosition) -> Self {
+        Self {
+            right_ascension: position.right_ascension,
+            right_ascension_hours: position.right_ascension_hours(),
+            declination: position.declination,
+            formatted_ra: format_right_ascension(position.right_ascension),
+        }
+    }
+}
+
+/// The decan a body is in
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct DecanInfo {
+    /// 1, 2 or 3 within the sign
+    pub index: u8,
+    /// Chaldean ruler, e.g. "Mars" for the first decan of Aries
+    pub ruler: String,
+}
+
+impl From<Decan> for DecanInfo {
+    fn from(decan: Decan) -> Self {
+        Self {
+            index: decan.index,
+            ruler: decan.ruler.to_string(),
+        }
+    }
+}
+
+/// Rates of the Ascendant and Midheaven at the chart's moment and place, in
+/// degrees of longitude per minute of clock time
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub struct AngleSpeedInfo {
+    pub ascendant: f64,
+    /// About 0.25°/min everywhere, faster near 0° Aries and Libra
+    pub midheaven: f64,
+    /// Minutes of clock time the Ascendant takes to move 1° at this rate
+    pub ascendant_minutes_per_degree: f64,
+}
+
+/// The lunar mansion a body is in
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct MansionInfo {
+    pub system: MansionSystem,
+    /// 1-based position from 0° Aries
+    pub index: u8,
+    pub name: String,
+    /// Longitude where the mansion begins
+    #[serde(serialize_with = "rounding::longitude")]
+    pub start: f64,
+}
+
+impl From<Mansion> for MansionInfo {
+    fn from(mansion: Mansion) -> Self {
+        Self {
+            system: mansion.system,
+            index: mansion.index,
+            name: mansion.name.to_string(),
+            start: mansion.start,
+        }
+    }
+}
+
+/// A point derived from the chart angles, such as the Vertex
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct AnglePointInfo {
+    /// "Vertex", "AntiVertex" or "EastPoint"
+    pub name: String,
+    #[serde(serialize_with = "rounding::longitude")]
+    pub longitude: f64,
+    /// Short label drawn on the wheel, e.g. "Vx"
+    pub label: String,
+    pub house: Option<u8>,
+}
+
+/// The chart's angles as read off its houses
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub struct AnglesInfo {
+    #[serde(serialize_with = "rounding::longitude")]
+    pub ascendant: f64,
+    #[serde(serialize_with = "rounding::longitude")]
+    pub descendant: f64,
+    #[serde(serialize_with = "rounding::longitude")]
+    pub midheaven: f64,
+    #[serde(serialize_with = "rounding::longitude")]
+    pub ic: f64,
+    #[serde(serialize_with = "rounding::longitude")]
+    pub vertex: f64,
+    /// The equatorial Ascendant
+    #[serde(serialize_with = "rounding::longitude")]
+    pub east_point: f64,
+    /// Right ascension of the Midheaven, in degrees; unlike the others it
+    /// isn't a longitude
+    #[serde(serialize_with = "rounding::longitude")]
+    pub armc: f64,
+}
+
+impl From<&Houses> for AnglesInfo {
+    fn from(houses: &Houses) -> Self {
+        Self {
+            ascendant: houses.ascendant,
+            descendant: normalize(houses.ascendant + 180.0),
+            midheaven: houses.midheaven,
+            ic: normalize(houses.midheaven + 180.0),
+            vertex: houses.vertex,
+            east_point: houses.east_point,
+            armc: houses.armc,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct HouseInfo {
+    pub number: u8,
+    #[serde(serialize_with = "rounding::longitude")]
+    pub longitude: f64,
+    #[serde(serialize_with = "rounding::latitude")]
+    pub latitude: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct AspectInfo {
+    pub planet1: String,
+    pub planet2: String,
+    pub aspect: String,
+    #[serde(serialize_with = "rounding::orb")]
+    pub orb: f64,
+    /// 1 when exact, falling to 0 at the widest orb the aspect was looked for at
+    #[serde(default, serialize_with = "rounding::strength")]
+    pub strength: f64,
+    /// Whether the orb is under `EXACT_ORB` (0.1°)
+    #[serde(default)]
+    pub exact: bool,
+    /// Whether the bodies are moving towards the exact aspect, rather than away from it
+    #[serde(default)]
+    pub applying: bool,
+    /// Days until an applying aspect is exact; absent for separating aspects or unless requested
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exact_in_days: Option<f64>,
+    /// When an applying transit-to-natal aspect is exact, estimated from the
+    /// transiting body's current speed; absent otherwise
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exact_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ChartRulerInfo {
+    /// Sign on the Ascendant
+    pub sign: String,
+    pub planet: PlanetInfo,
+    pub house: Option<u8>,
+    pub is_retrograde: bool,
+    /// "cazimi", "combust", "under_beams" or "free"; absent when the Sun rules the chart
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub solar_condition: Option<SolarCondition>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct HouseRulerInfo {
+    pub house: u8,
+    /// Sign on the house cusp
+    pub sign: String,
+    pub ruler: String,
+    /// House the ruler occupies
+    pub ruler_house: Option<u8>,
+}
+
+/// A house with the planets inside it
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct HouseSummaryInfo {
+    pub house: u8,
+    pub cusp: f64,
+    /// Sign on the house cusp
+    pub sign: String,
+    pub kind: HouseKind,
+    /// Planets in the house, in zodiacal order from the cusp
+    pub planets: Vec<String>,
+}
+
+impl From<HouseOccupancy> for HouseSummaryInfo {
+    fn from(occupancy: HouseOccupancy) -> Self {
+        Self {
+            house: occupancy.house,
+            cusp: occupancy.cusp,
+            sign: occupancy.sign.to_string(),
+            kind: occupancy.kind,
+            planets: occupancy.planets,
+        }
+    }
+}
+
+/// Planets per kind of house
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct HouseEmphasisInfo {
+    pub angular: usize,
+    pub succedent: usize,
+    pub cadent: usize,
+    /// The house with the most planets, the lowest numbered on a tie
+    pub most_occupied: Option<u8>,
+}
+
+impl From<HouseEmphasis> for HouseEmphasisInfo {
+    fn from(emphasis: HouseEmphasis) -> Self {
+        Self {
+            angular: emphasis.angular,
+            succedent: emphasis.succedent,
+            cadent: emphasis.cadent,
+            most_occupied: emphasis.most_occupied,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SynastryAspectInfo {
+    /// Planet from chart1
+    pub person1_planet: String,
+    /// Planet from chart2
+    pub person2_planet: String,
+    pub aspect: String,
+    #[serde(serialize_with = "rounding::orb")]
+    pub orb: f64,
+    /// 1 when exact, falling to 0 at the widest orb the aspect was looked for at
+    #[serde(default, serialize_with = "rounding::strength")]
+    pub strength: f64,
+    /// Whether the orb is under `EXACT_ORB` (0.1°)
+    #[serde(default)]
+    pub exact: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ChartResponse {
+    pub chart_type: String,
+    /// `CHART_SCHEMA_VERSION` of the API that returned the chart; 0 when missing
+    #[serde(default)]
+    pub schema_version: u32,
+    pub date: DateTime<Utc>,
+    /// The date the request's date expression resolved to; only when it was one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub resolved_date: Option<DateTime<Utc>>,
+    pub latitude: f64,
+    pub longitude: f64,
+    pub house_system: HouseSystem,
+    pub ayanamsa: String,
+    pub planets: Vec<PlanetInfo>,
+    /// The `aspect_points` asked for, in request order
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub points: Vec<AnglePointInfo>,
+    /// The Moon's lunar mansion, with `include_mansions`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub moon_mansion: Option<MansionInfo>,
+    /// Number of entries in `houses`: 12, or 36 for Gauquelin sectors
+    #[serde(default)]
+    pub house_count: usize,
+    pub houses: Vec<HouseInfo>,
+    /// The Ascendant, Midheaven, their opposites, the Vertex and the East
+    /// Point; absent without houses
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub angles: Option<AnglesInfo>,
+    /// Built-in aspects looked for: "major", or "all" with the minor ones
+    #[serde(default)]
+    pub aspect_set: AspectSet,
+    pub aspects: Vec<AspectInfo>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub transit: Option<TransitData>,
+    /// One entry per requested `transits` moment, in request order
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub transits: Vec<TransitData>,
+    /// Ephemeris source the positions were calculated from
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ephemeris: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub chart_ruler: Option<ChartRulerInfo>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub house_rulers: Vec<HouseRulerInfo>,
+    /// The twelve houses with the planets in each; empty without twelve house cusps
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub houses_summary: Vec<HouseSummaryInfo>,
+    /// Planets in angular, succedent and cadent houses
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub house_emphasis: Option<HouseEmphasisInfo>,
+    /// Local apparent sidereal time at birth in hours (0-24)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sidereal_time: Option<f64>,
+    /// How fast the angles were moving, with `include_angle_speeds`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub angle_speeds: Option<AngleSpeedInfo>,
+    /// "day" when the Sun is above the horizon, less any `sect_twilight`, else "night"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sect: Option<Sect>,
+    /// The Sun's altitude in degrees that decides `sect`, negative below the horizon
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sun_altitude: Option<f64>,
+    /// Set when the Sun is within a quarter degree of the day/night line, so
+    /// the sect turns on the birth minute
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub sect_ambiguous: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub svg_chart: Option<String>,
+    /// Mini-wheel with no text, present when the request set `include_thumbnail`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub svg_thumbnail: Option<String>,
+    /// Bodies left out because their positions couldn't be calculated
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub warnings: Vec<BodyWarningInfo>,
+    /// Requested options the ephemeris couldn't honor, calculated without
+    /// because the request set `allow_degraded`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub degraded_options: Vec<DegradedOptionInfo>,
+    /// Bodies close enough to a sign boundary or house cusp that a small
+    /// error in the birth time could move them across it
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub boundary_warnings: Vec<BoundaryWarningInfo>,
+    /// Bodies within 1° of the Ascendant, Descendant, Midheaven or IC, as
+    /// conjunctions to the angle, tightest first
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub angular: Vec<AspectInfo>,
+    /// How the chart was calculated
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub meta: Option<CalculationMeta>,
+    /// Text for the chart's factors, when `include_interpretation` is set
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub interpretation: Option<Interpretation>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub diagnostics: Option<Diagnostics>,
+    /// Whether `max_aspects` dropped any aspects from this response
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub truncated: bool,
+}
+
+/// How a chart was calculated, for reproducing it later
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CalculationMeta {
+    /// Increases by one with every chart calculated since the server started
+    pub calculation_id: u64,
+    pub crate_version: String,
+    /// Version of the linked Swiss Ephemeris library; absent without the `swiss` feature
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub swiss_ephemeris_version: Option<String>,
+    /// Ephemeris source the positions were calculated from
+    pub ephemeris: String,
+    /// Rough worst-case error of the planet longitudes for that source, in arc seconds
+    pub precision_arcsec: f64,
+    pub flags: CalculationFlags,
+    /// Delta T (TT - UT) applied to the chart time; absent when the ephemeris takes UT as TT
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub delta_t_seconds: Option<f64>,
+    /// House system the cusps were calculated with, after defaults were applied
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub house_system_used: Option<HouseSystem>,
+    /// Swiss Ephemeris letter of `house_system_used`, e.g. "P" for Placidus
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub house_system_code: Option<char>,
+    /// Reasons some positions are less precise than `precision_arcsec` suggests
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub warnings: Vec<String>,
+    /// Lunar nodes at the chart time; absent where the chart has no node choice
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub lunar_nodes: Option<LunarNodesInfo>,
+    /// Size of `svg_chart` in bytes; absent when the response carries no SVG
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub svg_bytes: Option<usize>,
+    /// Language of the formatted positions and chart labels, from `lang` or `Accept-Language`
+    #[serde(default)]
+    pub locale: String,
+}
+
+/// The chosen lunar nodes, with both North Node variants so clients can show the difference
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub struct LunarNodesInfo {
+    pub node_type: NodeType,
+    #[serde(serialize_with = "rounding::longitude")]
+    pub north_node: f64,
+    /// Always exactly opposite `north_node`
+    #[serde(serialize_with = "rounding::longitude")]
+    pub south_node: f64,
+    #[serde(serialize_with = "rounding::longitude")]
+    pub true_node: f64,
+    #[serde(serialize_with = "rounding::longitude")]
+    pub mean_node: f64,
+}
+
+/// Options the positions were calculated with
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub struct CalculationFlags {
+    /// Sidereal zodiac, measured with the chart's `ayanamsa`
+    pub sidereal: bool,
+    /// Positions seen from the birthplace rather than the Earth's centre
+    pub topocentric: bool,
+    /// True rather than mean lunar nodes
+    pub true_node: bool,
+}
+
+/// How a request was served, returned when the request sets `debug: true`
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Diagnostics {
+    /// Matches the `x-request-id` response header and the server log entries
+    pub request_id: String,
+    /// Milliseconds spent in each calculation stage, plus the total
+    pub timings_ms: BTreeMap<String, f64>,
+    /// Ephemeris source the positions were calculated from
+    pub ephemeris: String,
+    /// Whether the houses came from the house cache, having been calculated
+    /// for the same moment, place and system by an earlier request
+    pub cache_hit: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct TransitData {
+    pub date: DateTime<Utc>,
+    /// The date the transit's date expression resolved to; only when it was one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub resolved_date: Option<DateTime<Utc>>,
+    pub latitude: f64,
+    pub longitude: f64,
+    pub planets: Vec<PlanetInfo>,
+    pub aspects: Vec<AspectInfo>,
+    pub transit_to_natal_aspects: Vec<AspectInfo>,
+    /// With `precession_correction`, the natal planets carried forward to this
+    /// date's equinox, which `transit_to_natal_aspects` were found against
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub precessed_natal_planets: Vec<PointInfo>,
+    /// Transit bodies left out because their positions couldn't be calculated
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub warnings: Vec<BodyWarningInfo>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct TransitResponse {
+    pub chart_type: String,
+    pub natal_date: DateTime<Utc>,
+    pub transit_date: DateTime<Utc>,
+    /// The date the transit date expression resolved to; only when it was one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub resolved_date: Option<DateTime<Utc>>,
+    pub latitude: f64,
+    pub longitude: f64,
+    pub house_system: HouseSystem,
+    pub ayanamsa: String,
+    pub natal_planets: Vec<PlanetInfo>,
+    pub transit_planets: Vec<PlanetInfo>,
+    pub houses: Vec<HouseInfo>,
+    /// The natal angles, as for a natal chart
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub angles: Option<AnglesInfo>,
+    /// Built-in aspects looked for: "major", or "all" with the minor ones
+    #[serde(default)]
+    pub aspect_set: AspectSet,
+    pub natal_aspects: Vec<AspectInfo>,
+    pub transit_aspects: Vec<AspectInfo>,
+    /// With `include_natal_angles`, aspects from the natal angles and house
+    /// cusps to the transiting planets, e.g. "Natal Midheaven" to "Transit Saturn"
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub natal_angle_aspects: Vec<AspectInfo>,
+    /// With `include_transit_houses`, the natal house each transiting planet is in
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub transit_house_positions: Vec<TransitHouseInfo>,
+    /// Ephemeris source the positions were calculated from
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ephemeris: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub svg_chart: Option<String>,
+    /// Natal and transit bodies left out because their positions couldn't be calculated
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub warnings: Vec<BodyWarningInfo>,
+    /// How the chart was calculated
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub meta: Option<CalculationMeta>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub diagnostics: Option<Diagnostics>,
+    /// Whether `max_aspects` dropped any aspects from this response
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub truncated: bool,
+}
+
+/// A transiting planet's place among the natal houses
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct TransitHouseInfo {
+    pub planet: String,
+    pub house: u8,
+    /// When the planet entered the house; absent if that was over a century ago
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub entered: Option<DateTime<Utc>>,
+    /// Whether it backed into the house while retrograde
+    pub retrograde: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SynastryResponse {
+    pub chart_type: String,
+    pub chart1: ChartResponse,
+    pub chart2: ChartResponse,
+    /// Built-in aspects looked for between the charts: "major", or "all" with the minor ones
+    #[serde(default)]
+    pub aspect_set: AspectSet,
+    pub synastries: Vec<SynastryAspectInfo>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub svg_chart: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub diagnostics: Option<Diagnostics>,
+    /// Whether `max_aspects` dropped any aspects from this response
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub truncated: bool,
+    /// Scores of the `synastries` listed
+    #[serde(default)]
+    pub compatibility: CompatibilityInfo,
+}
+
+/// Summed strengths of the aspects between two charts, by the aspects' nature
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+pub struct CompatibilityInfo {
+    pub aspect_count: usize,
+    /// Trines, sextiles, quintiles and noviles
+    #[serde(serialize_with = "rounding::strength")]
+    pub harmony: f64,
+    /// Squares, oppositions, semisquares, sesquisquares and quincunxes
+    #[serde(serialize_with = "rounding::strength")]
+    pub tension: f64,
+    #[serde(serialize_with = "rounding::strength")]
+    pub conjunction: f64,
+    /// Every aspect, semisextiles, septiles and custom aspects included
+    #[serde(serialize_with = "rounding::strength")]
+    pub total: f64,
+}
+
+impl From<CompatibilityScores> for CompatibilityInfo {
+    fn from(scores: CompatibilityScores) -> Self {
+        Self {
+            aspect_count: scores.aspect_count,
+            harmony: scores.harmony,
+            tension: scores.tension,
+            conjunction: scores.conjunction,
+            total: scores.total,
+        }
+    }
+}
+
+/// Partners one `/api/synastry/batch` request may hold unless the server sets
+/// `max_synastry_batch`
+pub const DEFAULT_MAX_SYNASTRY_BATCH: usize = 500;
+
+/// Tightest aspects listed for each partner when a batch doesn't say
+pub const DEFAULT_TOP_ASPECTS: usize = 5;
+
+/// Most tightest aspects a batch may ask for per partner
+pub const MAX_TOP_ASPECTS: usize = 50;
+
+fn default_top_aspects() -> usize {
+    DEFAULT_TOP_ASPECTS
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SynastryBatchRequest {
+    /// The chart every partner is compared with, as `chart1` of `/api/chart/synastry`
+    pub base: ChartRequest,
+    /// Each partner, as `chart2` of `/api/chart/synastry`
+    pub others: Vec<ChartRequest>,
+    /// Look for the minor and harmonic aspects too; false when absent
+    #[serde(default)]
+    pub include_minor_aspects: bool,
+    /// Tightest aspects listed per partner
+    #[serde(default = "default_top_aspects")]
+    pub top_aspects: usize,
+}
+
+impl SynastryBatchRequest {
+    /// Checks the batch holds between one and `max_others` partners and asks
+    /// for at most `MAX_TOP_ASPECTS` aspects each
+    pub fn validate(&self, max_others: usize) -> Result<(), String> {
+        if self.others.is_empty() {
+            return Err("others must hold at least one chart".to_string());
+        }
+        if self.others.len() > max_others {
+            return Err(format!("At most {} charts can be compared in one batch, not {}", max_others, self.others.len()));
+        }
+        if self.top_aspects > MAX_TOP_ASPECTS {
+            return Err(format!("top_aspects must be at most {}", MAX_TOP_ASPECTS));
+        }
+        Ok(())
+    }
+}
+
+/// The base chart against one partner
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SynastryBatchItem {
+    /// Position of the partner in `others`
+    pub index: usize,
+    /// As `compatibility` of `/api/chart/synastry`; absent when the partner failed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scores: Option<CompatibilityInfo>,
+    /// The tightest aspects, tightest first
+    #[serde(default)]
+    pub top_aspects: Vec<SynastryAspectInfo>,
+    /// Why the partner's chart couldn't be calculated
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SynastryBatchResponse {
+    pub chart_type: String,
+    #[serde(default)]
+    pub aspect_set: AspectSet,
+    /// One item per partner, in `others` order
+    pub results: Vec<SynastryBatchItem>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct MundaneResponse {
+    pub chart_type: String,
+    pub event: MundaneEvent,
+    pub year: i32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub month: Option<u32>,
+    /// Moment of the event, to the second
+    pub event_time: DateTime<Utc>,
+    /// The chart for that moment at the requested location
+    pub chart: ChartResponse,
+}
+
+/// One crossing of the natal longitude
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ReturnPassInfo {
+    /// 1, or 1 to 3 when a retrograde loop crosses the natal longitude again
+    pub pass: usize,
+    pub date: DateTime<Utc>,
+    pub retrograde: bool,
+    /// Whether this is the final direct pass, which the chart is cast for
+    pub is_true_return: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ReturnResponse {
+    pub chart_type: String,
+    pub body: Planet,
+    pub natal_date: DateTime<Utc>,
+    /// The moment the search counted from, when the reference date was an
+    /// expression or left to default to now
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub resolved_date: Option<DateTime<Utc>>,
+    /// Longitude of the body in the natal chart, in the request's zodiac
+    #[serde(serialize_with = "rounding::longitude")]
+    pub natal_longitude: f64,
+    /// With `precession_correction`, the tropical natal longitude carried
+    /// forward to the return date, which the return was found for
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub precessed_natal_longitude: Option<f64>,
+    pub occurrence: ReturnOccurrence,
+    pub passes: Vec<ReturnPassInfo>,
+    /// The chart for the true return, at the requested location
+    pub chart: ChartResponse,
+    /// How each body moved from the natal chart, cast at the birthplace, to the return chart
+    pub vs_natal: PlacementDiffInfo,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CycleEventInfo {
+    /// "conjunction", "inferior_conjunction", "superior_conjunction", "opposition",
+    /// "station_retrograde" or "station_direct"
+    pub event: CycleEventKind,
+    pub date: DateTime<Utc>,
+    /// The stationing planet for stations, otherwise the first planet of the pair
+    pub planet: String,
+    #[serde(serialize_with = "rounding::longitude")]
+    pub longitude: f64,
+    pub sign: String,
+    /// Degrees into the sign (0-30)
+    pub degree: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CyclesResponse {
+    pub planet1: String,
+    pub planet2: String,
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+    pub events: Vec<CycleEventInfo>,
+}
+
+/// Query string for `GET /api/weather`
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct WeatherQuery {
+    /// Moment to report on; the report covers the whole UT hour it falls in
+    pub date: DateTime<Utc>,
+    pub lat: f64,
+    pub lon: f64,
+    /// Ephemeris source: "swiss", "moshier" or "auto"; defaults to the server setting
+    #[serde(default)]
+    pub ephemeris: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct WeatherPlanetInfo {
+    pub name: String,
+    #[serde(serialize_with = "rounding::longitude")]
+    pub longitude: f64,
+    pub sign: String,
+    /// Degrees into the sign (0-30)
+    pub degree: f64,
+    pub speed: f64,
+    pub is_retrograde: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct MoonWeatherInfo {
+    pub sign: String,
+    pub phase: MoonPhase,
+    /// The Moon's longitude minus the Sun's (0-360)
+    pub elongation: f64,
+    /// Fraction of the disc lit (0-1)
+    pub illumination: f64,
+    pub void_of_course: bool,
+    /// When the void began: the Moon's last Ptolemaic aspect, or its ingress
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub void_since: Option<DateTime<Utc>>,
+    /// When the void ends, with the Moon entering the next sign
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub void_until: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ExactAspectInfo {
+    pub planet1: String,
+    pub planet2: String,
+    pub aspect: String,
+    pub date: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct IngressInfo {
+    pub planet: String,
+    /// The sign entered
+    pub sign: String,
+    pub date: DateTime<Utc>,
+    /// Whether the planet backs into the sign while retrograde
+    pub retrograde: bool,
+}
+
+/// The sky for one UT hour, for everyone rather than one birth chart
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct WeatherResponse {
+    /// Start of the hour the report is for
+    pub date: DateTime<Utc>,
+    pub latitude: f64,
+    pub longitude: f64,
+    /// Ephemeris source the positions were calculated from
+    pub ephemeris: String,
+    /// Sun to Pluto, in that order, leaving out any whose position failed
+    pub planets: Vec<WeatherPlanetInfo>,
+    pub moon: MoonWeatherInfo,
+    /// Ascendant at the location
+    pub ascendant: f64,
+    /// Midheaven at the location
+    pub midheaven: f64,
+    /// Ptolemaic aspects between the planets exact within 24 hours either side, earliest first
+    pub exact_aspects: Vec<ExactAspectInfo>,
+    /// Planets that are retrograde, in `planets` order
+    pub retrograde: Vec<String>,
+    /// Planets changing sign during the UT day, earliest first
+    pub ingresses: Vec<IngressInfo>,
+}
+
+/// Query string for `GET /api/moon`
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct MoonQuery {
+    /// First step of the table
+    pub from: DateTime<Utc>,
+    /// Last moment the table may reach; the steps stop at or before it
+    pub to: DateTime<Utc>,
+    /// Time between rows, e.g. "15m", "1h" or "1d"; defaults to "1h"
+    #[serde(default)]
+    pub step: Option<String>,
+    /// "arabic" (default) or "nakshatra"
+    #[serde(default)]
+    pub mansion_system: Option<String>,
+    /// Ephemeris source: "swiss", "moshier" or "auto"; defaults to the server setting
+    #[serde(default)]
+    pub ephemeris: Option<String>,
+}
+
+/// The Moon at one step of `GET /api/moon`
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct MoonStepInfo {
+    pub date: DateTime<Utc>,
+    #[serde(serialize_with = "rounding::longitude")]
+    pub longitude: f64,
+    pub sign: Sign,
+    pub phase: MoonPhase,
+    /// Fraction of the disc lit (0-1)
+    #[serde(serialize_with = "rounding::orb")]
+    pub illumination: f64,
+    pub void_of_course: bool,
+    /// Name of the lunar mansion, in the header's `mansion_system`
+    pub mansion: String,
+    /// From the Earth's centre, to the kilometre
+    pub distance_km: f64,
+}
+
+/// What the rows of `GET /api/moon` cover, and the lunations after `from`
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct MoonHeaderInfo {
+    pub from: DateTime<Utc>,
+    pub to: DateTime<Utc>,
+    pub step: String,
+    pub mansion_system: MansionSystem,
+    /// Ephemeris source the positions were calculated from
+    pub ephemeris: String,
+    pub next_new_moon: DateTime<Utc>,
+    pub next_full_moon: DateTime<Utc>,
+}
+
+/// The Moon at every step of a date range, without the rest of the chart
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct MoonResponse {
+    pub header: MoonHeaderInfo,
+    pub rows: Vec<MoonStepInfo>,
+}
+
+/// A body left out of a chart, and why
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct BodyWarningInfo {
+    pub body: String,
+    pub reason: String,
+}
+
+impl From<&BodyFailure> for BodyWarningInfo {
+    fn from(failure: &BodyFailure) -> Self {
+        Self {
+            body: failure.planet.to_string(),
+            reason: failure.reason.clone(),
+        }
+    }
+}
+
+/// A requested option the ephemeris couldn't honor, and what was used instead
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct DegradedOptionInfo {
+    /// The request field, e.g. "topocentric"
+    pub option: String,
+    pub requested: String,
+    pub used: String,
+    pub reason: String,
+}
+
+/// The error for a request with options the ephemeris can't honor, naming each
+pub fn unsupported_options_message(degraded: &[DegradedOptionInfo]) -> String {
+    let options: Vec<String> = degraded
+        .iter()
+        .map(|option| format!("{} '{}' ({})", option.option, option.requested, option.reason))
+        .collect();
+    format!(
+        "Unsupported by this ephemeris: {}. Set allow_degraded to calculate with {} instead",
+        options.join("; "),
+        degraded.iter().map(|option| format!("{} '{}'", option.option, option.used)).collect::<Vec<_>>().join(", ")
+    )
+}
+
+/// A body near a sign boundary or house cusp, and what lies across it
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct BoundaryWarningInfo {
+    pub body: String,
+    pub kind: BoundaryKind,
+    /// Longitude of the boundary or cusp
+    pub boundary: f64,
+    /// Degrees between the body and the boundary
+    pub distance: f64,
+    /// The sign across a sign boundary
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub adjacent_sign: Option<String>,
+    /// The house across a house cusp
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub adjacent_house: Option<u8>,
+}
+
+impl From<BoundaryWarning> for BoundaryWarningInfo {
+    fn from(warning: BoundaryWarning) -> Self {
+        let (adjacent_sign, adjacent_house) = match warning.adjacent {
+            Adjacent::Sign(sign) => (Some(sign.to_string()), None),
+            Adjacent::House(house) => (None, Some(house)),
+        };
+        Self {
+            body: warning.name,
+            kind: warning.kind,
+            boundary: warning.boundary,
+            distance: warning.distance,
+            adjacent_sign,
+            adjacent_house,
+        }
+    }
+}
+
+/// Query string for `GET /api/positions`
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PositionsQuery {
+    /// Moment of the positions; defaults to now
+    #[serde(default)]
+    pub date: Option<DateTime<Utc>>,
+    /// Comma-separated bodies, e.g. "sun,moon,true_node"; defaults to Sun through Pluto
+    /// and both nodes
+    #[serde(default)]
+    pub bodies: Option<String>,
+    /// "tropical" (default), "sidereal" or "heliocentric-sidereal"
+    #[serde(default)]
+    pub zodiac: Option<String>,
+    /// Only for a sidereal zodiac: "lahiri", the default
+    #[serde(default)]
+    pub ayanamsa: Option<String>,
+    /// "geo" (default) or "helio"; "heliocentric-sidereal" is always "helio"
+    #[serde(default)]
+    pub center: Option<String>,
+    /// Ephemeris source: "swiss", "moshier" or "auto"; defaults to the server setting
+    #[serde(default)]
+    pub ephemeris: Option<String>,
+}
+
+/// One body in the `GET /api/positions` array
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub struct BodyPositionInfo {
+    pub body: Planet,
+    #[serde(serialize_with = "rounding::longitude")]
+    pub longitude: f64,
+    #[serde(serialize_with = "rounding::latitude")]
+    pub latitude: f64,
+    /// Degrees per day
+    pub speed: f64,
+    pub retrograde: bool,
+    pub sign: Sign,
+}
+
+impl From<BodyPosition> for BodyPositionInfo {
+    fn from(position: BodyPosition) -> Self {
+        Self {
+            body: position.planet,
+            longitude: position.longitude,
+            latitude: position.latitude,
+            speed: position.speed,
+            retrograde: position.speed < 0.0,
+            sign: position.sign(),
+        }
+    }
+}
+
+/// `GET /api/now`: the current positions and Moon, prefetched once a minute
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct NowResponse {
+    /// Start of the minute the positions are for
+    pub date: DateTime<Utc>,
+    /// Ephemeris source the positions were calculated from
+    pub ephemeris: String,
+    /// Every position body the ephemeris has, in `GET /api/positions` order
+    pub positions: Vec<BodyPositionInfo>,
+    pub moon: MoonWeatherInfo,
+}
+
+/// Whether this deployment can serve an option
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum CapabilityStatus {
+    Available,
+    Unavailable,
+}
+
+/// One house system, body or format in `GET /api/capabilities`
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct CapabilityInfo<T> {
+    pub name: T,
+    pub status: CapabilityStatus,
+    /// Why it's unavailable
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
+
+impl<T> CapabilityInfo<T> {
+    /// Available when `check` succeeds, unavailable with its error otherwise
+    pub fn checked<E: std::fmt::Display>(name: T, check: Result<(), E>) -> Self {
+        match check {
+            Ok(()) => Self {
+                name,
+                status: CapabilityStatus::Available,
+                reason: None,
+            },
+            Err(e) => Self {
+                name,
+                status: CapabilityStatus::Unavailable,
+                reason: Some(e.to_string()),
+            },
+        }
+    }
+}
+
+/// A house system in `GET /api/capabilities`, with its Swiss Ephemeris letter
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct HouseSystemInfo {
+    #[serde(flatten)]
+    pub capability: CapabilityInfo<String>,
+    pub code: char,
+}
+
+/// A zodiac `GET /api/positions` can measure longitudes in, or one that is
+/// asked for but not calculated, listed as unavailable with the reason
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct ZodiacInfo {
+    #[serde(flatten)]
+    pub capability: CapabilityInfo<String>,
+    /// The ayanamsa a sidereal zodiac is measured with
+    pub ayanamsa: Option<String>,
+    /// The center the zodiac is always seen from, if it is tied to one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub center: Option<String>,
+}
+
+/// A built-in aspect and its default orbs
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct AspectTypeInfo {
+    pub name: String,
+    pub angle: f64,
+    /// Orb in natal charts, in degrees
+    pub orb: f64,
+    /// Orb between transiting and natal bodies, in degrees
+    pub transit_orb: f64,
+    /// Only looked for with `include_minor_aspects`
+    pub minor: bool,
+}
+
+/// A body's plausible daily motion. A chart whose body moves outside it has a
+/// warning in its `meta`.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct MotionRangeInfo {
+    pub body: Planet,
+    /// Slowest motion in degrees per day, negative when retrograde
+    pub min_speed: f64,
+    /// Fastest motion in degrees per day
+    pub max_speed: f64,
+}
+
+/// Request limits
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct LimitsInfo {
+    /// Transit moments per `POST /api/chart`
+    pub max_transit_series: usize,
+    /// Bodies in one chart, the ten planets and every extra body
+    pub max_chart_bodies: usize,
+    pub max_custom_aspects: usize,
+    /// Widest orb a custom aspect may have, in degrees
+    pub max_custom_orb: f64,
+    /// Longest range `GET /api/cycles` searches, in days
+    pub max_cycle_range_days: i64,
+}
+
+/// Optional features and files this deployment has
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct FeaturesInfo {
+    /// Built with the Swiss Ephemeris library
+    pub swiss_ephemeris: bool,
+    /// The Swiss Ephemeris `.se1` files were found at startup
+    pub ephemeris_files: bool,
+    /// Charts can include an SVG wheel
+    pub svg: bool,
+    /// Source used when a request doesn't ask for one
+    pub default_ephemeris: EphemerisSource,
+    /// `ephemeris` values a chart can ask for without `allow_degraded`
+    pub ephemeris_sources: Vec<EphemerisSource>,
+    /// Charts can be calculated topocentric
+    pub topocentric: bool,
+    /// Charts can be calculated in a sidereal zodiac
+    pub sidereal: bool,
+}
+
+/// Response from `GET /api/capabilities`: what requests to this deployment can ask for
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CapabilitiesResponse {
+    pub house_systems: Vec<HouseSystemInfo>,
+    pub zodiacs: Vec<ZodiacInfo>,
+    pub bodies: Vec<CapabilityInfo<Planet>>,
+    pub aspects: Vec<AspectTypeInfo>,
+    /// Daily motion each body's calculated speed is checked against
+    pub motion_ranges: Vec<MotionRangeInfo>,
+    /// Formats `POST /api/render` can produce
+    pub render_formats: Vec<CapabilityInfo<String>>,
+    pub limits: LimitsInfo,
+    pub features: FeaturesInfo,
+}
+
+/// Query string for `GET /api/symbols`
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SymbolsQuery {
+    /// Color theme, e.g. "light"; defaults to the styles' base theme
+    #[serde(default)]
+    pub theme: Option<String>,
+}
+
+/// A body, sign or aspect in `GET /api/symbols`
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct SymbolInfo {
+    /// Key as requests spell it, e.g. "true_node"
+    pub key: String,
+    /// Name as charts and their styles give it, e.g. "TrueNode"
+    pub chart_name: String,
+    /// Name for people, e.g. "True Node"
+    pub display_name: String,
+    /// Symbol the charts draw, after the styles' overrides
+    pub glyph: Option<String>,
+    /// Code point of a one-character glyph, e.g. "U+2609"
+    pub codepoint: Option<String>,
+    /// Color in the theme, where the styles give one
+    pub color: Option<String>,
+    pub category: String,
+}
+
+/// Response from `GET /api/symbols`: how the charts name, draw and color
+/// everything they show, for legends
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SymbolsResponse {
+    pub theme: String,
+    /// Every theme the styles have
+    pub themes: Vec<String>,
+    pub bodies: Vec<SymbolInfo>,
+    pub signs: Vec<SymbolInfo>,
+    pub aspects: Vec<SymbolInfo>,
+}
+
+impl From<PlanetPosition> for PlanetInfo {
+    fn from(position: PlanetPosition) -> Self {
+        Self {
             name: "Unknown".to_string(), // This will be set by the caller
             longitude: position.longitude,
             latitude: position.latitude,
             speed: position.speed,
             is_retrograde: position.is_retrograde,
+            motion: position.motion,
+            formatted_position: Lang::En.format_position(position.longitude),
             house: position.house,
+            house_fraction: None,
+            decan: None,
+            mansion: None,
+            equatorial: None,
+        }
+    }
+}
+
+impl AspectRecord for AspectInfo {
+    fn bodies(&self) -> (&str, &str) {
+        (&self.planet1, &self.planet2)
+    }
+
+    fn aspect_type(&self) -> Option<AspectType> {
+        self.aspect.parse().ok()
+    }
+
+    fn orb(&self) -> f64 {
+        self.orb
+    }
+
+    fn strength(&self) -> f64 {
+        self.strength
+    }
+}
+
+impl AspectRecord for SynastryAspectInfo {
+    fn bodies(&self) -> (&str, &str) {
+        (&self.person1_planet, &self.person2_planet)
+    }
+
+    fn aspect_type(&self) -> Option<AspectType> {
+        self.aspect.parse().ok()
+    }
+
+    fn orb(&self) -> f64 {
+        self.orb
+    }
+
+    fn strength(&self) -> f64 {
+        self.strength
+    }
+}
+
+impl From<Aspect> for AspectInfo {
+    fn from(aspect: Aspect) -> Self {
+        Self {
+            planet1: aspect.planet1,
+            planet2: aspect.planet2,
+            aspect: aspect.name,
+            orb: aspect.orb,
+            strength: aspect_strength(aspect.orb, aspect.orb_allowed),
+            exact: is_exact(aspect.orb),
+            applying: aspect.applying,
+            exact_in_days: None,
+            exact_at: None,
+        }
+    }
+}
+
+impl From<SynastryAspect> for SynastryAspectInfo {
+    fn from(aspect: SynastryAspect) -> Self {
+        Self {
+            person1_planet: aspect.person1_planet,
+            person2_planet: aspect.person2_planet,
+            aspect: aspect.name,
+            orb: aspect.orb,
+            strength: aspect_strength(aspect.orb, aspect.orb_allowed),
+            exact: is_exact(aspect.orb),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn utc(date: &str) -> DateTime<Utc> {
+        date.parse().unwrap()
+    }
+
+    fn resolve(expression: &str, natal: &str) -> DateTime<Utc> {
+        let date: DateExpr = expression.parse().unwrap();
+        date.resolve("date", utc("2026-10-16T15:42:07Z"), Some(utc(natal))).unwrap()
+    }
+
+    #[test]
+    fn test_date_expressions_parse() {
+        let cases = [
+            ("now", DateExpr::Now),
+            (" NOW ", DateExpr::Now),
+            ("today", DateExpr::Today(NaiveTime::MIN)),
+            ("today T12:00", DateExpr::Today(NaiveTime::from_hms_opt(12, 0, 0).unwrap())),
+            ("todayT06:30:15", DateExpr::Today(NaiveTime::from_hms_opt(6, 30, 15).unwrap())),
+            ("natal+30y", DateExpr::Natal(DateOffset::Months(360))),
+            ("natal-3m", DateExpr::Natal(DateOffset::Months(-3))),
+            ("Natal+10d", DateExpr::Natal(DateOffset::Days(10))),
+            ("1990-05-15T14:30:00Z", DateExpr::At(utc("1990-05-15T14:30:00Z"))),
+            ("1990-05-15T16:30:00+02:00", DateExpr::At(utc("1990-05-15T14:30:00Z"))),
+        ];
+        for (expression, expected) in cases {
+            assert_eq!(expression.parse::<DateExpr>(), Ok(expected), "{}", expression);
+        }
+        // Each form reads back as itself
+        for expression in ["now", "today", "today T12:00:00", "natal+30y", "natal-3m", "natal+10d", "1990-05-15T14:30:00Z"] {
+            assert_eq!(expression.parse::<DateExpr>().unwrap().to_string(), expression);
         }
     }
+
+    #[test]
+    fn test_unknown_date_expressions_are_rejected() {
+        for expression in ["", "yesterday", "now+1d", "today T25:00", "todays", "natal", "natal+", "natal+y", "natal30y", "natal+3w", "natal+-3y", "natal+1.5y", "1990-05-15"] {
+            let error = expression.parse::<DateExpr>().unwrap_err();
+            assert!(error.starts_with("Unknown date '"), "{}: {}", expression, error);
+        }
+        assert!(serde_json::from_str::<DateExpr>("\"bogus\"").is_err());
+        assert_eq!(serde_json::from_str::<DateExpr>("\"natal+1y\"").unwrap(), DateExpr::Natal(DateOffset::Months(12)));
+    }
+
+    #[test]
+    fn test_date_expressions_resolve_in_utc() {
+        let now = utc("2026-10-16T15:42:07Z");
+        assert_eq!(DateExpr::Now.resolve("date", now, None), Ok(now));
+        let today: DateExpr = "today".parse().unwrap();
+        assert_eq!(today.resolve("date", now, None), Ok(utc("2026-10-16T00:00:00Z")));
+        let noon: DateExpr = "today T12:00".parse().unwrap();
+        assert_eq!(noon.resolve("date", now, None), Ok(utc("2026-10-16T12:00:00Z")));
+        let given = DateExpr::from(utc("1990-05-15T14:30:00Z"));
+        assert!(!given.is_expression());
+        assert_eq!(given.resolve("date", now, None), Ok(utc("1990-05-15T14:30:00Z")));
+    }
+
+    #[test]
+    fn test_natal_relative_dates_keep_the_time_of_birth() {
+        assert_eq!(resolve("natal+30y", "1990-05-15T14:30:00Z"), utc("2020-05-15T14:30:00Z"));
+        assert_eq!(resolve("natal-3m", "1990-05-15T14:30:00Z"), utc("1990-02-15T14:30:00Z"));
+        assert_eq!(resolve("natal+10d", "1990-12-25T08:00:00Z"), utc("1991-01-04T08:00:00Z"));
+        assert_eq!(resolve("natal-1d", "1990-03-01T08:00:00Z"), utc("1990-02-28T08:00:00Z"));
+        assert_eq!(resolve("natal+0y", "1990-05-15T14:30:00Z"), utc("1990-05-15T14:30:00Z"));
+    }
+
+    #[test]
+    fn test_natal_relative_dates_clamp_to_the_end_of_the_month() {
+        // No February 29th in 2025, nor a 31st in February or April
+        assert_eq!(resolve("natal+1y", "2024-02-29T12:00:00Z"), utc("2025-02-28T12:00:00Z"));
+        assert_eq!(resolve("natal+4y", "2024-02-29T12:00:00Z"), utc("2028-02-29T12:00:00Z"));
+        assert_eq!(resolve("natal-1y", "2024-02-29T12:00:00Z"), utc("2023-02-28T12:00:00Z"));
+        assert_eq!(resolve("natal+1m", "2023-01-31T12:00:00Z"), utc("2023-02-28T12:00:00Z"));
+        assert_eq!(resolve("natal+1m", "2024-01-31T12:00:00Z"), utc("2024-02-29T12:00:00Z"));
+        assert_eq!(resolve("natal+3m", "2024-01-31T12:00:00Z"), utc("2024-04-30T12:00:00Z"));
+        assert_eq!(resolve("natal-1m", "2024-03-31T12:00:00Z"), utc("2024-02-29T12:00:00Z"));
+    }
+
+    #[test]
+    fn test_natal_relative_dates_need_a_natal_date() {
+        let date: DateExpr = "natal+30y".parse().unwrap();
+        let error = date.resolve("transit.date", utc("2026-10-16T15:42:07Z"), None).unwrap_err();
+        assert_eq!(error, "transit.date: natal-relative dates need a natal date to count from");
+        let far: DateExpr = "natal+2000000000d".parse().unwrap();
+        let error = far.resolve("reference_date", utc("2026-10-16T15:42:07Z"), Some(utc("1990-05-15T14:30:00Z"))).unwrap_err();
+        assert_eq!(error, "reference_date: natal+2000000000d is out of range");
+    }
+
+    #[test]
+    fn test_include_asteroids_adds_those_not_listed() {
+        let request = ChartRequest {
+            extra_bodies: vec![Planet::TrueNode, Planet::Juno],
+            include_asteroids: true,
+            include_nodes: Some(false),
+            ..Default::default()
+        };
+        let (bodies, required) = request.bodies().unwrap();
+        assert_eq!(
+            bodies[CHART_PLANETS.len()..],
+            [Planet::TrueNode, Planet::Juno, Planet::Chiron, Planet::Ceres, Planet::Pallas, Planet::Vesta]
+        );
+        assert_eq!(required, REQUIRED_PLANETS);
+
+        let request = ChartRequest {
+            include_asteroids: false,
+            ..request
+        };
+        assert_eq!(request.bodies().unwrap().0.len(), CHART_PLANETS.len() + 2);
+    }
+
+    #[test]
+    fn test_include_nodes_by_default() {
+        let bodies = |request: ChartRequest| request.bodies().unwrap().0[CHART_PLANETS.len()..].to_vec();
+        assert_eq!(bodies(ChartRequest::default()), [Planet::TrueNode, Planet::MeanLilith]);
+        let mean = ChartRequest {
+            node_type: Some("mean".to_string()),
+            extra_bodies: vec![Planet::MeanLilith, Planet::TrueNode],
+            ..Default::default()
+        };
+        assert_eq!(bodies(mean), [Planet::MeanLilith, Planet::TrueNode, Planet::MeanNode]);
+        let without = ChartRequest {
+            include_nodes: Some(false),
+            ..Default::default()
+        };
+        assert!(bodies(without).is_empty());
+        let unknown = ChartRequest {
+            node_type: Some("osculating".to_string()),
+            ..Default::default()
+        };
+        assert!(unknown.bodies().is_err());
+    }
 }
@@ -0,0 +1,168 @@
+//! API versions and the response shapes that differ between them.
+//!
+//! The routes are served under `/api/v1` and `/api/v2`, with bare `/api` kept
+//! as an alias of v1. Handlers build the same response structs whatever the
+//! version. v1 serializes them as they are, which is the JSON the API has
+//! always returned. v2 adapts that JSON:
+//!
+//! - a chart's single `transit` block is folded into `transits`, so transits are always a list
+//! - each aspect gains the aspect's exact `angle` and its `category`, "major" or "minor"
+//! - the top-level `meta` object carries `api_version`, and is added where a response has none
+//! - failures are `{"error", "message"}` JSON bodies, like refused request bodies, rather than plain text
+
+use crate::calc::aspects::AspectType;
+use crate::data::symbols::aspect_symbol;
+use serde::Serialize;
+use serde_json::{json, Map, Value};
+
+/// Response header naming the version a response was shaped for
+pub const API_VERSION_HEADER: &str = "x-api-version";
+
+/// A version of the API's response shapes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum ApiVersion {
+    /// The original shapes, also served under bare `/api`
+    #[default]
+    V1,
+    V2,
+}
+
+impl ApiVersion {
+    /// Every version, oldest first
+    pub const ALL: [ApiVersion; 2] = [ApiVersion::V1, ApiVersion::V2];
+
+    /// The version a request path asks for: v2 under `/api/v2`, else v1
+    pub fn from_path(path: &str) -> Self {
+        match path.strip_prefix("/api/v2") {
+            Some(rest) if rest.is_empty() || rest.starts_with('/') => ApiVersion::V2,
+            _ => ApiVersion::V1,
+        }
+    }
+
+    pub fn number(self) -> u32 {
+        match self {
+            ApiVersion::V1 => 1,
+            ApiVersion::V2 => 2,
+        }
+    }
+
+    /// Scope the version's routes are mounted at
+    pub fn scope(self) -> &'static str {
+        match self {
+            ApiVersion::V1 => "/api/v1",
+            ApiVersion::V2 => "/api/v2",
+        }
+    }
+
+    /// A response body as this version's JSON
+    pub fn to_json<T: Serialize>(self, body: &T) -> Result<String, serde_json::Error> {
+        match self {
+            ApiVersion::V1 => serde_json::to_string(body),
+            ApiVersion::V2 => {
+                let mut value = serde_json::to_value(body)?;
+                adapt_v2(&mut value);
+                serde_json::to_string(&value)
+            }
+        }
+    }
+
+    /// The body of a failed request: the message itself for v1, JSON for v2
+    pub fn error_body(self, error: &str, message: &str) -> String {
+        match self {
+            ApiVersion::V1 => message.to_string(),
+            ApiVersion::V2 => json!({ "error": error, "message": message }).to_string(),
+        }
+    }
+
+    /// Whether `error_body` is JSON
+    pub fn structured_errors(self) -> bool {
+        self != ApiVersion::V1
+    }
+}
+
+impl std::fmt::Display for ApiVersion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "v{}", self.number())
+    }
+}
+
+// Turns a v1 response into its v2 shape
+fn adapt_v2(value: &mut Value) {
+    add_aspect_fields(value);
+    let Value::Object(response) = value else {
+        return;
+    };
+    fold_transit(response);
+    let meta = response.entry("meta").or_insert_with(|| Value::Object(Map::new()));
+    if let Value::Object(meta) = meta {
+        meta.insert("api_version".to_string(), ApiVersion::V2.number().into());
+    }
+}
+
+// Adds `angle` and `category` to every aspect, at any depth. An aspect is an
+// object with an `aspect` name and an `orb`; custom aspects' names aren't
+// built-in aspect types and are left as they are.
+fn add_aspect_fields(value: &mut Value) {
+    match value {
+        Value::Object(object) => {
+            let aspect = match (object.get("aspect"), object.get("orb")) {
+                (Some(Value::String(name)), Some(Value::Number(_))) => name.parse::<AspectType>().ok(),
+                _ => None,
+            };
+            if let Some(aspect) = aspect {
+                object.insert("angle".to_string(), aspect.angle().into());
+                object.insert("category".to_string(), aspect_symbol(aspect).category.into());
+            }
+            object.values_mut().for_each(add_aspect_fields);
+        }
+        Value::Array(items) => items.iter_mut().for_each(add_aspect_fields),
+        _ => {}
+    }
+}
+
+// Moves a chart's single `transit` block into the `transits` list
+fn fold_transit(response: &mut Map<String, Value>) {
+    let Some(transit) = response.remove("transit") else {
+        return;
+    };
+    match response.entry("transits").or_insert_with(|| Value::Array(Vec::new())) {
+        Value::Array(transits) if transits.is_empty() => transits.push(transit),
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_version_from_path() {
+        assert_eq!(ApiVersion::from_path("/api/v2/chart"), ApiVersion::V2);
+        assert_eq!(ApiVersion::from_path("/api/v2"), ApiVersion::V2);
+        assert_eq!(ApiVersion::from_path("/api/v1/chart"), ApiVersion::V1);
+        assert_eq!(ApiVersion::from_path("/api/chart"), ApiVersion::V1);
+        assert_eq!(ApiVersion::from_path("/api/v20/chart"), ApiVersion::V1);
+    }
+
+    #[test]
+    fn test_v2_shape() {
+        let v1 = json!({
+            "transit": { "aspects": [{ "aspect": "Trine", "orb": 1.5 }] },
+            "aspects": [{ "aspect": "Septile", "orb": 0.2 }, { "aspect": "Golden", "orb": 0.5 }],
+            "meta": { "calculation_id": 7 }
+        });
+        assert_eq!(ApiVersion::V1.to_json(&v1).unwrap(), v1.to_string());
+
+        let v2: Value = serde_json::from_str(&ApiVersion::V2.to_json(&v1).unwrap()).unwrap();
+        assert!(v2.get("transit").is_none());
+        assert_eq!(v2["transits"][0]["aspects"][0]["angle"], 120.0);
+        assert_eq!(v2["transits"][0]["aspects"][0]["category"], "major");
+        assert_eq!(v2["aspects"][0]["category"], "minor");
+        assert!(v2["aspects"][1].get("angle").is_none());
+        assert_eq!(v2["meta"], json!({ "calculation_id": 7, "api_version": 2 }));
+
+        let error: Value = serde_json::from_str(&ApiVersion::V2.error_body("Bad Request", "No such sign")).unwrap();
+        assert_eq!(error, json!({ "error": "Bad Request", "message": "No such sign" }));
+        assert_eq!(ApiVersion::V1.error_body("Bad Request", "No such sign"), "No such sign");
+    }
+}
@@ -0,0 +1,190 @@
+//! The `GET /api/weather` report, put together from the chart pipeline and the
+//! weather calculations and cached per UT hour
+
+use crate::api::computation::{ChartComputation, CHART_PLANETS};
+use crate::api::types::{ExactAspectInfo, IngressInfo, MoonWeatherInfo, WeatherPlanetInfo, WeatherResponse};
+use crate::calc::planets::Planet;
+use crate::calc::rulers::Sign;
+use crate::calc::utils::{date_to_julian, julian_to_date};
+use crate::calc::weather::{exact_aspects_between, illumination, ingresses, moon_elongation, void_of_course, MoonPhase};
+use crate::core::types::{EphemerisSource, HouseSystem};
+use crate::core::AstrologError;
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+use std::sync::{Mutex, OnceLock};
+
+/// Most reports kept in memory at once
+pub const WEATHER_CACHE_CAPACITY: usize = 256;
+
+/// Reports for the same hour and place are shared; the coordinates are rounded
+/// to four decimals like the house cache
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct WeatherKey {
+    hour: i64,
+    latitude: i64,
+    longitude: i64,
+    source: EphemerisSource,
+}
+
+/// Least recently used reports, evicting the oldest once full
+struct WeatherCache {
+    entries: HashMap<WeatherKey, (WeatherResponse, u64)>,
+    clock: u64,
+}
+
+impl WeatherCache {
+    fn get(&mut self, key: &WeatherKey) -> Option<WeatherResponse> {
+        self.clock += 1;
+        let (report, used) = self.entries.get_mut(key)?;
+        *used = self.clock;
+        Some(report.clone())
+    }
+
+    fn insert(&mut self, key: WeatherKey, report: WeatherResponse) {
+        self.clock += 1;
+        if self.entries.len() >= WEATHER_CACHE_CAPACITY && !self.entries.contains_key(&key) {
+            if let Some(oldest) = self.entries.iter().min_by_key(|(_, (_, used))| *used).map(|(k, _)| *k) {
+                self.entries.remove(&oldest);
+            }
+        }
+        self.entries.insert(key, (report, self.clock));
+    }
+}
+
+fn weather_cache() -> &'static Mutex<WeatherCache> {
+    static WEATHER_CACHE: OnceLock<Mutex<WeatherCache>> = OnceLock::new();
+    WEATHER_CACHE.get_or_init(|| {
+        Mutex::new(WeatherCache {
+            entries: HashMap::with_capacity(WEATHER_CACHE_CAPACITY),
+            clock: 0,
+        })
+    })
+}
+
+/// The weather report for the UT hour containing `date`, from the cache when
+/// the same hour and place were asked for before
+pub fn weather_report(
+    date: DateTime<Utc>,
+    latitude: f64,
+    longitude: f64,
+    source: EphemerisSource,
+) -> Result<WeatherResponse, AstrologError> {
+    let hour_number = date.timestamp().div_euclid(3600);
+    let hour = DateTime::from_timestamp(hour_number * 3600, 0).ok_or_else(|| AstrologError::CalculationError {
+        message: format!("Date out of range: {}", date),
+    })?;
+    let key = WeatherKey {
+        hour: hour_number,
+        latitude: (latitude * 1e4).round() as i64,
+        longitude: (longitude * 1e4).round() as i64,
+        source,
+    };
+    if let Some(report) = weather_cache().lock().unwrap_or_else(|e| e.into_inner()).get(&key) {
+        return Ok(report);
+    }
+    let report = calculate_weather(hour, latitude, longitude, source)?;
+    weather_cache()
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .insert(key, report.clone());
+    Ok(report)
+}
+
+fn calculate_weather(
+    hour: DateTime<Utc>,
+    latitude: f64,
+    longitude: f64,
+    source: EphemerisSource,
+) -> Result<WeatherResponse, AstrologError> {
+    let calculation = |message: String| AstrologError::CalculationError { message };
+    let date = |jd: f64| {
+        julian_to_date(jd).ok_or_else(|| calculation(format!("Date out of range: Julian date {}", jd)))
+    };
+    let jd = date_to_julian(hour);
+    let sky = ChartComputation::new(jd, source)?.with_houses(latitude, longitude, HouseSystem::Equal)?;
+    let source = sky.source;
+
+    let planets: Vec<WeatherPlanetInfo> = sky
+        .bodies
+        .iter()
+        .zip(&sky.positions)
+        .map(|(planet, position)| WeatherPlanetInfo {
+            name: planet.to_string(),
+            longitude: position.longitude,
+            sign: Sign::from_longitude(position.longitude).to_string(),
+            degree: position.longitude % 30.0,
+            speed: position.speed,
+            is_retrograde: position.is_retrograde,
+        })
+        .collect();
+    let retrograde = planets
+        .iter()
+        .filter(|planet| planet.is_retrograde)
+        .map(|planet| planet.name.clone())
+        .collect();
+
+    let elongation = moon_elongation(jd, source).map_err(calculation)?;
+    let void = void_of_course(jd, source).map_err(calculation)?;
+    let moon_longitude = sky
+        .position_of(Planet::Moon)
+        .ok_or_else(|| calculation("The Moon was not calculated".to_string()))?
+        .longitude;
+    let moon = MoonWeatherInfo {
+        sign: Sign::from_longitude(moon_longitude).to_string(),
+        phase: MoonPhase::from_elongation(elongation),
+        elongation,
+        illumination: illumination(elongation),
+        void_of_course: void.is_some(),
+        void_since: void.map(|v| date(v.since)).transpose()?,
+        void_until: void.map(|v| date(v.until)).transpose()?,
+    };
+
+    let exact_aspects = exact_aspects_between(jd - 1.0, jd + 1.0, source)
+        .map_err(calculation)?
+        .into_iter()
+        .map(|aspect| {
+            Ok(ExactAspectInfo {
+                planet1: aspect.planet1.to_string(),
+                planet2: aspect.planet2.to_string(),
+                aspect: aspect.aspect_type.to_string(),
+                date: date(aspect.jd)?,
+            })
+        })
+        .collect::<Result<_, AstrologError>>()?;
+
+    let day_start = (jd - 0.5).floor() + 0.5;
+    let mut day_ingresses = Vec::new();
+    for planet in CHART_PLANETS {
+        day_ingresses.extend(ingresses(planet, day_start, day_start + 1.0, source).map_err(calculation)?);
+    }
+    day_ingresses.sort_by(|a, b| a.jd.total_cmp(&b.jd));
+    let ingresses = day_ingresses
+        .into_iter()
+        .map(|ingress| {
+            Ok(IngressInfo {
+                planet: ingress.planet.to_string(),
+                sign: ingress.sign.to_string(),
+                date: date(ingress.jd)?,
+                retrograde: ingress.retrograde,
+            })
+        })
+        .collect::<Result<_, AstrologError>>()?;
+
+    let houses = sky
+        .houses()
+        .ok_or_else(|| calculation("Houses were not calculated".to_string()))?;
+    Ok(WeatherResponse {
+        date: hour,
+        latitude,
+        longitude,
+        ephemeris: source.to_string(),
+        planets,
+        moon,
+        ascendant: houses.ascendant,
+        midheaven: houses.midheaven,
+        exact_aspects,
+        retrograde,
+        ingresses,
+    })
+}
+
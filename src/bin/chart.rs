@@ -0,0 +1,134 @@
+//! Calculates one natal chart from the command line and prints it as JSON or
+//! as the plain-text listing.
+//!
+//! ```text
+//! astrolog-chart --date 1977-10-24T04:56:00Z --latitude 14.6488 --longitude 121.0509 --format text
+//! ```
+//!
+//! With `--astrolog-config`, the house system, aspects, orbs, extra bodies and
+//! place not given on the command line come from an Astrolog `astrolog.as` file.
+
+use astrolog_rs::api::server::calculate_natal_chart;
+use astrolog_rs::api::settings::AppConfig;
+use astrolog_rs::api::types::{default_extra_bodies, default_house_system, ChartRequest};
+use astrolog_rs::calc::swiss_ephemeris;
+use astrolog_rs::charts;
+use astrolog_rs::core::types::HouseSystem;
+use astrolog_rs::io::astrolog_config::{self, load_astrolog_config};
+use astrolog_rs::io::text::{render_listing_with, ListingOptions};
+use chrono::{DateTime, Utc};
+use clap::{Parser, ValueEnum};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum Format {
+    Json,
+    Text,
+}
+
+/// Calculate a natal chart
+#[derive(Debug, Parser)]
+#[command(name = "astrolog-chart", version)]
+struct Args {
+    /// Moment of birth, RFC 3339 (e.g. 1977-10-24T04:56:00Z)
+    #[arg(long)]
+    date: DateTime<Utc>,
+    /// Degrees north, negative for south; the `-zl` latitude of `--astrolog-config` when absent
+    #[arg(long, allow_hyphen_values = true)]
+    latitude: Option<f64>,
+    /// Degrees east, negative for west; the `-zl` longitude of `--astrolog-config` when absent
+    #[arg(long, allow_hyphen_values = true)]
+    longitude: Option<f64>,
+    /// The `-c` house system of `--astrolog-config` when absent, else placidus
+    #[arg(long)]
+    house_system: Option<HouseSystem>,
+    #[arg(long, default_value = "tropical")]
+    ayanamsa: String,
+    /// "auto", "swiss", "moshier" or "analytic"
+    #[arg(long)]
+    ephemeris: Option<String>,
+    #[arg(long, value_enum, default_value_t = Format::Json)]
+    format: Format,
+    /// Sign symbols instead of three-letter names in the text listing
+    #[arg(long)]
+    glyphs: bool,
+    /// Astrolog `astrolog.as` file to take the chart defaults from
+    #[arg(long)]
+    astrolog_config: Option<String>,
+}
+
+fn main() {
+    let args = Args::parse();
+
+    let settings = match AppConfig::load() {
+        Ok(settings) => settings,
+        Err(e) => {
+            eprintln!("Invalid configuration: {}", e);
+            std::process::exit(1);
+        }
+    };
+    if let Err(e) = swiss_ephemeris::init_swiss_ephemeris_with(&settings.ephemeris_path, settings.ephemeris) {
+        eprintln!("Failed to initialize Swiss Ephemeris: {}", e);
+        std::process::exit(1);
+    }
+    // The chart comes with its wheel, which needs the styles
+    let styles = match settings.styles_path.as_deref() {
+        Some(path) => charts::init_styles_from(path),
+        None => charts::init_styles(),
+    };
+    if let Err(e) = styles {
+        eprintln!("Failed to initialize chart styles: {}", e);
+        std::process::exit(1);
+    }
+
+    if let Some(path) = args.astrolog_config.as_deref() {
+        match load_astrolog_config(path) {
+            Ok(defaults) => {
+                for warning in &defaults.warnings {
+                    eprintln!("{}: {}", path, warning);
+                }
+                astrolog_config::init_defaults(defaults);
+            }
+            Err(e) => {
+                eprintln!("{}", e);
+                std::process::exit(1);
+            }
+        }
+    }
+    let location = astrolog_config::defaults().and_then(|defaults| defaults.location);
+    let (Some(latitude), Some(longitude)) = (
+        args.latitude.or(location.map(|(latitude, _)| latitude)),
+        args.longitude.or(location.map(|(_, longitude)| longitude)),
+    ) else {
+        eprintln!("--latitude and --longitude are needed unless --astrolog-config sets a location");
+        std::process::exit(1);
+    };
+
+    let request = ChartRequest {
+        date: args.date.into(),
+        latitude,
+        longitude,
+        house_system: args.house_system.unwrap_or_else(default_house_system),
+        ayanamsa: args.ayanamsa,
+        ephemeris: args.ephemeris,
+        extra_bodies: default_extra_bodies(),
+        ..ChartRequest::default()
+    };
+    let chart = match calculate_natal_chart(request) {
+        Ok(chart) => chart,
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+    };
+
+    match args.format {
+        Format::Text => print!("{}", render_listing_with(&chart, ListingOptions { sign_glyphs: args.glyphs })),
+        Format::Json => match serde_json::to_string_pretty(&chart) {
+            Ok(json) => println!("{}", json),
+            Err(e) => {
+                eprintln!("{}", e);
+                std::process::exit(1);
+            }
+        },
+    }
+}
@@ -0,0 +1,227 @@
+//! How far the analytic ephemeris is from a reference one.
+//!
+//! `compare_ephemerides` calculates every body at each moment of a date grid
+//! with both ephemerides and keeps, per body, the largest and the mean absolute
+//! difference in longitude. `ANALYTIC_TOLERANCES` are the largest differences
+//! from the Swiss Ephemeris files the analytic theories are allowed over
+//! 1950–2050, sampled every 30 days. The accuracy test fails when a body goes
+//! over its tolerance, and `astrolog-rs accuracy-report` writes the same
+//! figures as a Markdown table or CSV.
+
+use crate::calc::angles::shortest_distance;
+use crate::calc::ephemeris::Ephemeris;
+use crate::calc::planets::Planet;
+use crate::calc::utils::julian_to_date;
+use crate::core::types::EphemerisSource;
+use std::fmt::Write;
+
+/// Every body the analytic ephemeris has a theory for
+pub const ANALYTIC_BODIES: [Planet; 13] = [
+    Planet::Sun,
+    Planet::Moon,
+    Planet::Mercury,
+    Planet::Venus,
+    Planet::Mars,
+    Planet::Jupiter,
+    Planet::Saturn,
+    Planet::Uranus,
+    Planet::Neptune,
+    Planet::Pluto,
+    Planet::MeanNode,
+    Planet::TrueNode,
+    Planet::MeanLilith,
+];
+
+/// Largest longitude difference in degrees from the Swiss Ephemeris files each
+/// analytic body is allowed over 1950–2050: the measured errors with a small
+/// margin, to be tightened as the theories improve. The Sun and the planets
+/// through Neptune are on the J2000 equinox rather than the equinox of date, so
+/// they drift about 50" a year from 2000 and are 0.7° out at the ends of the
+/// range; the Moon's few-term theory is off by up to 4°. Mean Lilith is Meeus'
+/// mean perigee turned around, which the Swiss Ephemeris corrects by up to 0.12°.
+pub const ANALYTIC_TOLERANCES: [(Planet, f64); 13] = [
+    (Planet::Sun, 0.75),
+    (Planet::Moon, 4.5),
+    (Planet::Mercury, 0.75),
+    (Planet::Venus, 0.75),
+    (Planet::Mars, 0.75),
+    (Planet::Jupiter, 0.7),
+    (Planet::Saturn, 1.5),
+    (Planet::Uranus, 0.9),
+    (Planet::Neptune, 0.8),
+    (Planet::Pluto, 0.02),
+    (Planet::MeanNode, 0.01),
+    (Planet::TrueNode, 0.3),
+    (Planet::MeanLilith, 0.13),
+];
+
+/// The tolerance in `ANALYTIC_TOLERANCES` for a body, if it has one
+pub fn analytic_tolerance(planet: Planet) -> Option<f64> {
+    ANALYTIC_TOLERANCES
+        .iter()
+        .find(|(body, _)| *body == planet)
+        .map(|&(_, tolerance)| tolerance)
+}
+
+/// How far one body's longitudes are from the reference's
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BodyAccuracy {
+    pub body: Planet,
+    pub samples: usize,
+    /// Largest absolute difference in degrees
+    pub max_error: f64,
+    pub mean_error: f64,
+    /// Julian date (UT) of the largest difference
+    pub worst_jd: f64,
+}
+
+impl BodyAccuracy {
+    /// Whether the largest difference is within the body's tolerance; a body
+    /// without one always is
+    pub fn within_tolerance(&self) -> bool {
+        analytic_tolerance(self.body).is_none_or(|tolerance| self.max_error <= tolerance)
+    }
+}
+
+/// The differences of every body compared
+#[derive(Debug, Clone, PartialEq)]
+pub struct AccuracyReport {
+    /// Source the reference positions came from
+    pub reference: EphemerisSource,
+    pub candidate: EphemerisSource,
+    pub bodies: Vec<BodyAccuracy>,
+}
+
+impl AccuracyReport {
+    /// The bodies over their tolerance
+    pub fn failures(&self) -> Vec<&BodyAccuracy> {
+        self.bodies.iter().filter(|body| !body.within_tolerance()).collect()
+    }
+
+    /// A Markdown table with a row per body
+    pub fn to_markdown(&self) -> String {
+        let mut out = format!("# {} compared with {}\n\n", self.candidate, self.reference);
+        out.push_str("| Body | Samples | Mean error (°) | Max error (°) | Worst date | Tolerance (°) |\n");
+        out.push_str("|---|---:|---:|---:|---|---:|\n");
+        for body in &self.bodies {
+            let _ = writeln!(
+                out,
+                "| {:?} | {} | {:.4} | {:.4} | {} | {} |",
+                body.body,
+                body.samples,
+                body.mean_error,
+                body.max_error,
+                worst_date(body.worst_jd),
+                tolerance(body.body)
+            );
+        }
+        out
+    }
+
+    /// The same figures as `to_markdown`, one CSV row per body
+    pub fn to_csv(&self) -> String {
+        let mut out = String::from("body,samples,mean_error,max_error,worst_date,tolerance\n");
+        for body in &self.bodies {
+            let _ = writeln!(
+                out,
+                "{:?},{},{:.6},{:.6},{},{}",
+                body.body,
+                body.samples,
+                body.mean_error,
+                body.max_error,
+                worst_date(body.worst_jd),
+                tolerance(body.body)
+            );
+        }
+        out
+    }
+}
+
+fn worst_date(jd: f64) -> String {
+    julian_to_date(jd).map(|date| date.format("%Y-%m-%d").to_string()).unwrap_or_default()
+}
+
+fn tolerance(body: Planet) -> String {
+    analytic_tolerance(body).map(|tolerance| tolerance.to_string()).unwrap_or_default()
+}
+
+/// The Julian dates from `start` to `end` inclusive, `step` days apart
+pub fn date_grid(start: f64, end: f64, step: f64) -> Result<Vec<f64>, String> {
+    if !(start.is_finite() && end.is_finite() && step.is_finite() && step > 0.0) {
+        return Err(format!("Invalid date grid: {} to {} every {} days", start, end, step));
+    }
+    if end < start {
+        return Err("The end of the date grid is before its start".to_string());
+    }
+    let steps = ((end - start) / step + 1e-9).floor() as usize;
+    Ok((0..=steps).map(|i| start + i as f64 * step).collect())
+}
+
+/// The longitude differences of `bodies` between `candidate` and `reference`
+/// at each of `jds`
+pub fn compare_ephemerides(
+    reference: (&dyn Ephemeris, EphemerisSource),
+    candidate: (&dyn Ephemeris, EphemerisSource),
+    bodies: &[Planet],
+    jds: &[f64],
+) -> Result<AccuracyReport, String> {
+    if jds.is_empty() {
+        return Err("The date grid is empty".to_string());
+    }
+    let (mut reference_used, mut candidate_used) = (reference.1, candidate.1);
+    let mut accuracies = Vec::with_capacity(bodies.len());
+    for &body in bodies {
+        let mut accuracy = BodyAccuracy { body, samples: 0, max_error: 0.0, mean_error: 0.0, worst_jd: jds[0] };
+        let mut total = 0.0;
+        for &jd in jds {
+            let (expected, _, used) = reference.0.position(jd, body, reference.1)?;
+            reference_used = used;
+            let (actual, _, used) = candidate.0.position(jd, body, candidate.1)?;
+            candidate_used = used;
+            let error = shortest_distance(actual, expected);
+            total += error;
+            accuracy.samples += 1;
+            if error > accuracy.max_error {
+                accuracy.max_error = error;
+                accuracy.worst_jd = jd;
+            }
+        }
+        accuracy.mean_error = total / accuracy.samples as f64;
+        accuracies.push(accuracy);
+    }
+    Ok(AccuracyReport {
+        reference: reference_used,
+        candidate: candidate_used,
+        bodies: accuracies,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::calc::ephemeris::MockEphemeris;
+
+    #[test]
+    fn test_differences_wrap_around_aries() {
+        let reference = MockEphemeris::new(2451545.0).with_body(Planet::Sun, 359.5, 0.0, 1.0).with_body(Planet::Moon, 10.0, 0.0, 13.0);
+        let candidate = MockEphemeris::new(2451545.0).with_body(Planet::Sun, 0.5, 0.0, 1.0).with_body(Planet::Moon, 10.0, 0.0, 13.2);
+        let jds = date_grid(2451545.0, 2451555.0, 5.0).unwrap();
+        assert_eq!(jds, [2451545.0, 2451550.0, 2451555.0]);
+
+        let source = EphemerisSource::Auto;
+        let report = compare_ephemerides((&reference, source), (&candidate, source), &[Planet::Sun, Planet::Moon], &jds).unwrap();
+        let sun = report.bodies[0];
+        assert_eq!(sun.samples, 3);
+        assert!((sun.max_error - 1.0).abs() < 1e-9 && (sun.mean_error - 1.0).abs() < 1e-9);
+        // The Moon drifts 0.2° a day apart, so it is furthest out at the end
+        let moon = report.bodies[1];
+        assert!((moon.max_error - 2.0).abs() < 1e-9 && (moon.mean_error - 1.0).abs() < 1e-9);
+        assert_eq!(moon.worst_jd, 2451555.0);
+        assert!(moon.within_tolerance());
+        assert_eq!(report.failures().len(), 1);
+
+        assert!(report.to_markdown().contains("| Moon | 3 | 1.0000 | 2.0000 | 2000-01-11 | 4.5 |"));
+        assert_eq!(report.to_csv().lines().nth(1), Some("Sun,3,1.000000,1.000000,2000-01-01,0.75"));
+        assert!(date_grid(2451545.0, 2451544.0, 1.0).is_err());
+    }
+}
@@ -1,4 +1,119 @@
-use crate::calc::utils::{degrees_to_radians, normalize_angle, radians_to_degrees};
+use crate::calc::utils::{degrees_to_radians, radians_to_degrees};
+
+/// Wraps an angle into [0°, 360°).
+///
+/// `rem_euclid` alone returns exactly 360 for a tiny negative angle, whose
+/// distance below 360 is lost to rounding, and keeps the sign of -0; both
+/// come back as 0 here.
+///
+/// # Examples
+///
+/// ```
+/// use astrolog_rs::calc::angles::normalize;
+///
+/// assert_eq!(normalize(370.0), 10.0);
+/// assert_eq!(normalize(-720.0), 0.0);
+/// assert_eq!(normalize(-1e-20), 0.0);
+/// ```
+pub fn normalize(deg: f64) -> f64 {
+    debug_assert!(deg.is_finite(), "normalize got {}", deg);
+    let wrapped = deg.rem_euclid(360.0);
+    if wrapped >= 360.0 || wrapped == 0.0 {
+        0.0
+    } else {
+        wrapped
+    }
+}
+
+/// The angle from `b` to `a`, in (-180°, 180°]: positive when `a` is ahead of
+/// `b` in the zodiac. Both are wrapped before subtracting, so large inputs
+/// don't lose precision, and `signed_delta(a, b) == -signed_delta(b, a)`
+/// exactly except at 180°, which is always positive.
+///
+/// ```
+/// use astrolog_rs::calc::angles::signed_delta;
+///
+/// assert_eq!(signed_delta(10.0, 350.0), 20.0);
+/// assert_eq!(signed_delta(350.0, 10.0), -20.0);
+/// assert_eq!(signed_delta(0.0, 180.0), 180.0);
+/// ```
+pub fn signed_delta(a: f64, b: f64) -> f64 {
+    let delta = normalize(a) - normalize(b);
+    if delta > 180.0 {
+        delta - 360.0
+    } else if delta <= -180.0 {
+        delta + 360.0
+    } else {
+        delta
+    }
+}
+
+/// Length of the shorter arc between two angles, in [0°, 180°]
+pub fn shortest_distance(a: f64, b: f64) -> f64 {
+    signed_delta(a, b).abs()
+}
+
+/// Midpoint of the shorter arc between two angles, in [0°, 360°). When they
+/// are exactly opposite, the midpoint 90° ahead of `a` is taken.
+///
+/// ```
+/// use astrolog_rs::calc::angles::midpoint_shortest;
+///
+/// assert_eq!(midpoint_shortest(350.0, 20.0), 5.0);
+/// assert_eq!(midpoint_shortest(0.0, 180.0), 90.0);
+/// ```
+pub fn midpoint_shortest(a: f64, b: f64) -> f64 {
+    normalize(a + signed_delta(b, a) / 2.0)
+}
+
+/// Centre of a cluster of angles, in [0°, 360°): each is taken on the
+/// shorter arc from the first, so a cluster around 0° Aries centres there
+/// rather than opposite it. Meant for angles within 180° of each other;
+/// `None` when there are none.
+///
+/// ```
+/// use astrolog_rs::calc::angles::cluster_center;
+///
+/// assert_eq!(cluster_center(&[359.0, 1.0, 3.0]), Some(1.0));
+/// assert_eq!(cluster_center(&[]), None);
+/// ```
+pub fn cluster_center(angles: &[f64]) -> Option<f64> {
+    let (&first, _) = angles.split_first()?;
+    let offset = angles.iter().map(|&angle| signed_delta(angle, first)).sum::<f64>() / angles.len() as f64;
+    Some(normalize(first + offset))
+}
+
+/// Groups angles lying within `threshold` of their neighbour in zodiac order,
+/// returning the indices of each group in that order. A group running past
+/// 0° Aries is one group, starting before 0°.
+///
+/// ```
+/// use astrolog_rs::calc::angles::proximity_groups;
+///
+/// assert_eq!(proximity_groups(&[0.5, 120.0, 359.5], 2.0), vec![vec![2, 0], vec![1]]);
+/// ```
+pub fn proximity_groups(angles: &[f64], threshold: f64) -> Vec<Vec<usize>> {
+    let mut order: Vec<usize> = (0..angles.len()).collect();
+    order.sort_by(|&a, &b| normalize(angles[a]).total_cmp(&normalize(angles[b])));
+
+    let mut groups: Vec<Vec<usize>> = Vec::new();
+    for index in order {
+        match groups.last_mut() {
+            Some(group) if normalize(angles[index]) - normalize(angles[*group.last().unwrap()]) <= threshold => group.push(index),
+            _ => groups.push(vec![index]),
+        }
+    }
+
+    if groups.len() > 1 {
+        let last = *groups[groups.len() - 1].last().unwrap();
+        if shortest_distance(angles[last], angles[groups[0][0]]) <= threshold {
+            let mut wrapped = groups.pop().unwrap();
+            wrapped.append(&mut groups[0]);
+            groups[0] = wrapped;
+        }
+    }
+    groups
+}
 
 /// Calculates the Ascendant (rising sign) and Midheaven (MC) angles for a given time and location.
 ///
@@ -38,13 +153,13 @@ pub fn calculate_angles(sidereal_time: f64, latitude: f64, obliquity: f64) -> (f
     let obl_rad = degrees_to_radians(obliquity);
 
     // Calculate MC (Midheaven)
-    let mc_longitude = normalize_angle(sidereal_time);
+    let mc_longitude = normalize(sidereal_time);
 
     // Calculate ASC (Ascendant)
     let y = (st_rad.sin() * obl_rad.cos()).atan2(st_rad.cos());
     let x = (lat_rad.cos() * st_rad.sin() - lat_rad.sin() * obl_rad.cos() * st_rad.cos())
         / (lat_rad.sin() * st_rad.sin() + lat_rad.cos() * obl_rad.cos() * st_rad.cos());
-    let asc_longitude = normalize_angle(radians_to_degrees(y.atan2(x)));
+    let asc_longitude = normalize(radians_to_degrees(y.atan2(x)));
 
     (mc_longitude, asc_longitude)
 }
@@ -111,5 +226,100 @@ pub fn calculate_sidereal_time(t: f64, longitude: f64) -> f64 {
         280.46061837 + 360.98564736629 * (t * 36525.0) + t * t * (0.000387933 - t / 38710000.0);
 
     // Add longitude and normalize
-    normalize_angle(mst + longitude)
+    normalize(mst + longitude)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    #[test]
+    fn test_normalize_edges() {
+        assert_eq!(normalize(0.0), 0.0);
+        assert!(normalize(-0.0).is_sign_positive());
+        assert_eq!(normalize(360.0), 0.0);
+        assert_eq!(normalize(-360.0), 0.0);
+        assert_eq!(normalize(180.0), 180.0);
+        assert_eq!(normalize(-180.0), 180.0);
+        assert!((normalize(-720.0001) - 359.9999).abs() < 1e-9);
+        assert!((normalize(720.0001) - 0.0001).abs() < 1e-9);
+        // Close enough to 0 that 360 minus it rounds to 360
+        assert_eq!(normalize(-1e-15), 0.0);
+        assert!(normalize(-1e-12) < 360.0);
+    }
+
+    #[test]
+    fn test_delta_and_distance_edges() {
+        // Opposition is +180 from either side
+        assert_eq!(signed_delta(180.0, 0.0), 180.0);
+        assert_eq!(signed_delta(0.0, 180.0), 180.0);
+        assert_eq!(signed_delta(-90.0, 90.0), 180.0);
+        assert_eq!(shortest_distance(0.0, 180.0), 180.0);
+        assert_eq!(shortest_distance(10.0, 10.0), 0.0);
+        assert_eq!(shortest_distance(-720.0001, 0.0), shortest_distance(0.0, -720.0001));
+        assert!((signed_delta(-720.0001, 0.0) + 0.0001).abs() < 1e-9);
+        assert!((signed_delta(359.9, 3600.1) + 0.2).abs() < 1e-9);
+        assert_eq!(midpoint_shortest(350.0, 10.0), 0.0);
+        assert_eq!(midpoint_shortest(10.0, 350.0), 0.0);
+        assert_eq!(midpoint_shortest(180.0, 0.0), 270.0);
+    }
+
+    #[test]
+    fn test_clusters_across_zero_aries() {
+        assert!((cluster_center(&[359.5, 0.5]).unwrap()).abs() < 1e-9);
+        assert!((cluster_center(&[0.5, 359.5]).unwrap()).abs() < 1e-9);
+        assert!((cluster_center(&[358.0, 359.0]).unwrap() - 358.5).abs() < 1e-9);
+        assert_eq!(cluster_center(&[42.0]), Some(42.0));
+
+        assert_eq!(proximity_groups(&[0.5, 359.5], 1.0), vec![vec![1, 0]]);
+        assert_eq!(proximity_groups(&[10.0, 359.5, 0.5, 200.0], 1.0), vec![vec![1, 2], vec![0], vec![3]]);
+        assert_eq!(proximity_groups(&[0.5, 359.0], 1.0), vec![vec![0], vec![1]]);
+        // A chain all the way round stays one group
+        let ring: Vec<f64> = (0..12).map(|i| i as f64 * 30.0).collect();
+        assert_eq!(proximity_groups(&ring, 30.0).len(), 1);
+        assert!(proximity_groups(&[], 1.0).is_empty());
+    }
+
+    proptest! {
+        #[test]
+        fn normalize_is_idempotent_and_in_range(deg in -1e6..1e6f64) {
+            let once = normalize(deg);
+            prop_assert!((0.0..360.0).contains(&once));
+            prop_assert_eq!(normalize(once), once);
+        }
+
+        #[test]
+        fn delta_is_antisymmetric(a in -1e4..1e4f64, b in -1e4..1e4f64) {
+            let delta = signed_delta(a, b);
+            prop_assert!(delta > -180.0 && delta <= 180.0);
+            if delta == 180.0 {
+                prop_assert_eq!(signed_delta(b, a), 180.0);
+            } else {
+                prop_assert_eq!(signed_delta(b, a), -delta);
+            }
+        }
+
+        #[test]
+        fn distance_is_symmetric(a in -1e4..1e4f64, b in -1e4..1e4f64) {
+            let distance = shortest_distance(a, b);
+            prop_assert!((0.0..=180.0).contains(&distance));
+            prop_assert_eq!(distance, shortest_distance(b, a));
+        }
+
+        #[test]
+        fn midpoint_lies_within_the_shorter_arc(a in -1e4..1e4f64, b in -1e4..1e4f64) {
+            let midpoint = midpoint_shortest(a, b);
+            let (to_a, to_b) = (shortest_distance(midpoint, a), shortest_distance(midpoint, b));
+            prop_assert!((to_a - to_b).abs() < 1e-9);
+            prop_assert!((to_a + to_b - shortest_distance(a, b)).abs() < 1e-9);
+        }
+
+        #[test]
+        fn groups_hold_each_angle_once(angles in prop::collection::vec(0.0..360.0f64, 0..12), threshold in 0.0..20.0f64) {
+            let mut indices: Vec<usize> = proximity_groups(&angles, threshold).concat();
+            indices.sort_unstable();
+            prop_assert_eq!(indices, (0..angles.len()).collect::<Vec<_>>());
+        }
+    }
 }
@@ -1,7 +1,11 @@
-// use crate::calc::utils::normalize_angle;
+use crate::calc::angles::{shortest_distance, signed_delta};
+use crate::calc::planets::{default_source, max_daily_motion, planet_longitude_at, Planet};
 use crate::calc::PlanetPosition;
+use crate::core::types::EphemerisSource;
+use serde::{Deserialize, Serialize};
 
-/// Aspect types
+/// Aspect types. An aspect serializes as its `name`, the form responses have
+/// always used, e.g. "SemiSextile", and reads back from any name `FromStr` accepts.
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum AspectType {
     Conjunction,  // 0°
@@ -25,27 +29,98 @@ pub enum AspectType {
 
 /// Aspect configuration
 #[allow(dead_code)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub struct AspectConfig {
     pub orb: f64,
     pub applying: bool,
 }
 
-/// Calculate aspects between two positions
+/// Orb allowances per aspect type
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct OrbConfig {
+    /// Orb for aspect types without an explicit entry; `None` uses `AspectType::orb`
+    pub default_orb: Option<f64>,
+    /// Explicit orbs for individual aspect types
+    pub orbs: Vec<(AspectType, f64)>,
+}
+
+impl OrbConfig {
+    /// Standard natal orbs (`AspectType::orb`)
+    pub fn natal() -> Self {
+        Self::default()
+    }
+
+    /// Tight transit orbs (`AspectType::transit_orb`)
+    pub fn transit() -> Self {
+        Self {
+            default_orb: None,
+            orbs: get_aspect_types(true)
+                .into_iter()
+                .map(|aspect_type| (aspect_type, aspect_type.transit_orb()))
+                .collect(),
+        }
+    }
+
+    /// The same orb for every aspect type
+    pub fn uniform(orb: f64) -> Self {
+        Self {
+            default_orb: Some(orb),
+            orbs: Vec::new(),
+        }
+    }
+
+    /// Sets the orb for one aspect type, replacing any previous entry
+    pub fn with_orb(mut self, aspect_type: AspectType, orb: f64) -> Self {
+        self.orbs.retain(|(existing, _)| *existing != aspect_type);
+        self.orbs.push((aspect_type, orb));
+        self
+    }
+
+    /// Orb allowed for the given aspect type
+    pub fn orb(&self, aspect_type: AspectType) -> f64 {
+        self.orbs
+            .iter()
+            .find(|(existing, _)| *existing == aspect_type)
+            .map(|(_, orb)| *orb)
+            .or(self.default_orb)
+            .unwrap_or_else(|| aspect_type.orb())
+    }
+}
+
+/// An aspect found between two entries of a position slice
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct IndexedAspect {
+    /// Index of the first position
+    pub index1: usize,
+    /// Index of the second position
+    pub index2: usize,
+    pub aspect_type: AspectType,
+    /// Actual angular separation between the two positions (0-180°)
+    pub angle: f64,
+    pub config: AspectConfig,
+}
+
+/// Calculate aspects between two positions moving at the given speeds, in
+/// degrees a day
 #[allow(dead_code)]
 pub fn calculate_aspect(
     pos1: f64,
+    vel1: f64,
     pos2: f64,
+    vel2: f64,
     aspect_type: AspectType,
     orb: f64,
 ) -> Option<AspectConfig> {
+    // Positions are checked when they're calculated; a NaN here would never be within orb
+    debug_assert!(pos1.is_finite() && pos2.is_finite(), "aspect between {} and {}", pos1, pos2);
     let _aspect_angle = get_aspect_angle(aspect_type);
-    let diff = (pos1 - pos2).abs() % 360.0;
+    let diff = shortest_distance(pos1, pos2);
     let aspect_diff = (diff - _aspect_angle).abs();
 
     if aspect_diff <= orb {
         Some(AspectConfig {
             orb: aspect_diff,
-            applying: is_aspect_applying(pos1, pos2, aspect_type),
+            applying: is_aspect_applying(pos1, vel1, pos2, vel2, _aspect_angle),
         })
     } else {
         None
@@ -76,52 +151,64 @@ fn get_aspect_angle(aspect_type: AspectType) -> f64 {
     }
 }
 
-/// Check if an aspect is applying (planets moving towards exact aspect)
-#[allow(dead_code)]
-fn is_aspect_applying(pos1: f64, pos2: f64, aspect_type: AspectType) -> bool {
-    let _aspect_angle = get_aspect_angle(aspect_type);
-    let diff = (pos1 - pos2) % 360.0;
+/// How fast the separation of two bodies closes on `angle` degrees, in degrees
+/// a day: positive while the aspect applies, negative while it separates.
+/// Only the relative motion counts, so either body may be the faster or
+/// retrograde, and the shorter arc may run past 0° Aries.
+pub fn closing_speed(pos1: f64, vel1: f64, pos2: f64, vel2: f64, angle: f64) -> f64 {
+    let difference = signed_delta(pos1, pos2);
+    let deviation = difference.abs() - angle;
+    -deviation.signum() * difference.signum() * (vel1 - vel2)
+}
 
-    match aspect_type {
-        AspectType::Conjunction => diff > 0.0 && diff < 180.0,
-        AspectType::Opposition => diff > 0.0 && diff < 180.0,
-        AspectType::Trine => diff > 0.0 && diff < 180.0,
-        AspectType::Square => diff > 0.0 && diff < 180.0,
-        AspectType::Sextile => diff > 0.0 && diff < 180.0,
-        AspectType::Quincunx => diff > 0.0 && diff < 180.0,
-        AspectType::SemiSextile => diff > 0.0 && diff < 180.0,
-        AspectType::SemiSquare => diff > 0.0 && diff < 180.0,
-        AspectType::Sesquisquare => diff > 0.0 && diff < 180.0,
-        AspectType::Quintile => diff > 0.0 && diff < 180.0,
-        AspectType::BiQuintile => diff > 0.0 && diff < 180.0,
-        AspectType::Septile => diff > 0.0 && diff < 180.0,
-        AspectType::BiSeptile => diff > 0.0 && diff < 180.0,
-        AspectType::TriSeptile => diff > 0.0 && diff < 180.0,
-        AspectType::Novile => diff > 0.0 && diff < 180.0,
-        AspectType::BiNovile => diff > 0.0 && diff < 180.0,
-        AspectType::QuadNovile => diff > 0.0 && diff < 180.0,
+/// Check if an aspect of `angle` degrees is applying (the bodies moving towards
+/// the exact aspect). An exact aspect is neither applying nor separating.
+pub fn is_aspect_applying(pos1: f64, vel1: f64, pos2: f64, vel2: f64, angle: f64) -> bool {
+    let deviation = shortest_distance(pos1, pos2) - angle;
+    deviation != 0.0 && closing_speed(pos1, vel1, pos2, vel2, angle) > 0.0
+}
+
+/// Days until an aspect of `angle` degrees is exact if both bodies kept their
+/// current speeds; `None` while it separates or the bodies move together
+pub fn linear_days_to_exact(pos1: f64, vel1: f64, pos2: f64, vel2: f64, angle: f64) -> Option<f64> {
+    let deviation = (shortest_distance(pos1, pos2) - angle).abs();
+    if deviation == 0.0 {
+        return Some(0.0);
     }
+    let closing = closing_speed(pos1, vel1, pos2, vel2, angle);
+    (closing > 0.0).then(|| deviation / closing)
 }
 
 /// Calculate all aspects between a set of positions
+///
+/// Orbs are looked up by aspect type in `orbs`, so `aspect_types` may be any
+/// subset in any order.
 #[allow(dead_code)]
 pub fn calculate_all_aspects(
-    positions: &[f64],
-    orbs: &[f64],
+    positions: &[PlanetPosition],
+    orbs: &OrbConfig,
     aspect_types: &[AspectType],
-) -> Vec<(usize, usize, AspectType, AspectConfig)> {
+) -> Vec<IndexedAspect> {
     let mut aspects = Vec::new();
 
-    for i in 0..positions.len() {
-        for j in (i + 1)..positions.len() {
+    for (i, pos1) in positions.iter().enumerate() {
+        for (j, pos2) in positions.iter().enumerate().skip(i + 1) {
             for &aspect_type in aspect_types {
                 if let Some(config) = calculate_aspect(
-                    positions[i],
-                    positions[j],
+                    pos1.longitude,
+                    pos1.speed,
+                    pos2.longitude,
+                    pos2.speed,
                     aspect_type,
-                    orbs[aspect_type as usize],
+                    orbs.orb(aspect_type),
                 ) {
-                    aspects.push((i, j, aspect_type, config));
+                    aspects.push(IndexedAspect {
+                        index1: i,
+                        index2: j,
+                        aspect_type,
+                        angle: shortest_distance(pos1.longitude, pos2.longitude),
+                        config,
+                    });
                 }
             }
         }
@@ -130,7 +217,11 @@ pub fn calculate_all_aspects(
     aspects
 }
 
-/// Calculate the exact time of an aspect
+/// Estimate the time of an aspect assuming constant velocities: the days until
+/// the separation on the shorter arc, which may run past 0° Aries, closes on
+/// the aspect's angle either way round. `None` when the bodies are separating
+/// from it or move together.
+/// See `next_exact` for a solver that follows the bodies' actual motion.
 #[allow(dead_code)]
 pub fn calculate_aspect_time(
     pos1: f64,
@@ -139,31 +230,351 @@ pub fn calculate_aspect_time(
     vel2: f64,
     aspect_type: AspectType,
 ) -> Option<f64> {
-    let _aspect_angle = get_aspect_angle(aspect_type);
-    let diff = (pos1 - pos2) % 360.0;
+    let aspect_angle = get_aspect_angle(aspect_type);
+    let separation = signed_delta(pos1, pos2);
     let vel_diff = vel1 - vel2;
 
     if vel_diff == 0.0 {
         return None;
     }
 
-    let time = (diff - _aspect_angle) / vel_diff;
-    if time >= 0.0 {
-        Some(time)
-    } else {
-        None
+    [aspect_angle, -aspect_angle]
+        .into_iter()
+        .map(|target| signed_delta(target, separation) / vel_diff)
+        .filter(|&time| time >= 0.0)
+        .min_by(f64::total_cmp)
+}
+
+/// Longest search for an applying aspect's perfection, in days
+pub const EXACT_HORIZON_DAYS: f64 = 365.0;
+
+/// Largest relative motion, in degrees, allowed between two samples of the solver
+const MAX_SAMPLE_MOTION: f64 = 5.0;
+
+/// Longest step between two samples of the solver, in days
+const MAX_SAMPLE_STEP: f64 = 5.0;
+
+/// Precision of the exact times, in days (about 0.1 s)
+const EXACT_TOLERANCE: f64 = 1e-6;
+
+/// Find every time an aspect between two moving bodies is exact.
+///
+/// Samples both bodies from the ephemeris often enough that neither can move
+/// more than a few degrees between samples, then refines each crossing.
+/// Retrograde loops can perfect an aspect several times, so all of them are
+/// returned. A pair that only touches the exact angle and turns back without
+/// crossing it is not reported.
+///
+/// # Arguments
+///
+/// * `jd_start` - Julian date (UT) to search from
+/// * `body1`, `body2` - The two bodies
+/// * `aspect` - The aspect to perfect
+/// * `horizon_days` - How far ahead of `jd_start` to search
+///
+/// # Returns
+///
+/// Julian dates of the exact aspects within the horizon, earliest first
+pub fn next_exact(
+    jd_start: f64,
+    body1: Planet,
+    body2: Planet,
+    aspect: AspectType,
+    horizon_days: f64,
+) -> Result<Vec<f64>, String> {
+    next_exact_with_source(jd_start, body1, body2, aspect, horizon_days, default_source())
+}
+
+/// Find every time an aspect is exact, sampling a specific ephemeris source.
+/// See `next_exact`.
+pub fn next_exact_with_source(
+    jd_start: f64,
+    body1: Planet,
+    body2: Planet,
+    aspect: AspectType,
+    horizon_days: f64,
+    source: EphemerisSource,
+) -> Result<Vec<f64>, String> {
+    next_exact_at_angle(jd_start, body1, body2, aspect.angle(), horizon_days, source)
+}
+
+/// Find every time two bodies are `angle` degrees apart (0-180°), sampling a
+/// specific ephemeris source. See `next_exact`.
+pub fn next_exact_at_angle(
+    jd_start: f64,
+    body1: Planet,
+    body2: Planet,
+    angle: f64,
+    horizon_days: f64,
+    source: EphemerisSource,
+) -> Result<Vec<f64>, String> {
+    // Separation equals the aspect angle when body1 leads body2 by +angle or -angle
+    let mut targets = vec![angle];
+    if angle > 0.0 && angle < 180.0 {
+        targets.push(-angle);
+    }
+
+    let difference = |jd: f64| -> Result<f64, String> {
+        Ok(planet_longitude_at(body1, jd, source)? - planet_longitude_at(body2, jd, source)?)
+    };
+    let relative_motion = max_daily_motion(body1) + max_daily_motion(body2);
+    let samples = sample_motion(jd_start, horizon_days, relative_motion, difference)?;
+
+    let mut exact = Vec::new();
+    for &target in &targets {
+        exact.extend(crossings(&samples, target, difference)?);
+    }
+    exact.sort_by(f64::total_cmp);
+    exact.dedup_by(|a, b| (*a - *b).abs() < EXACT_TOLERANCE * 10.0);
+    Ok(exact)
+}
+
+/// Samples an angle that moves at most `daily_motion` degrees a day often enough
+/// that it can't move more than a few degrees between samples
+pub(crate) fn sample_motion(
+    jd_start: f64,
+    horizon_days: f64,
+    daily_motion: f64,
+    value: impl Fn(f64) -> Result<f64, String>,
+) -> Result<Vec<(f64, f64)>, String> {
+    let steps = (horizon_days / (MAX_SAMPLE_MOTION / daily_motion).min(MAX_SAMPLE_STEP))
+        .ceil()
+        .max(1.0) as usize;
+    let step = horizon_days / steps as f64;
+    (0..=steps)
+        .map(|k| {
+            let jd = jd_start + k as f64 * step;
+            value(jd).map(|angle| (jd, angle))
+        })
+        .collect()
+}
+
+/// Finds every time a sampled angle passes `target`, refining each crossing
+/// with `value`. Crossings in either direction are reported, earliest first.
+pub(crate) fn crossings(
+    samples: &[(f64, f64)],
+    target: f64,
+    value: impl Fn(f64) -> Result<f64, String>,
+) -> Result<Vec<f64>, String> {
+    let offset = |angle: f64| signed_delta(angle, target);
+    let mut exact = Vec::new();
+    for pair in samples.windows(2) {
+        let (jd0, g0) = (pair[0].0, offset(pair[0].1));
+        let (jd1, g1) = (pair[1].0, offset(pair[1].1));
+        if g0 == 0.0 {
+            exact.push(jd0);
+        } else if g1 != 0.0 && g0.signum() != g1.signum() && (g1 - g0).abs() < 180.0 {
+            // A sign change across ±180° is the offset wrapping, not a crossing
+            exact.push(refine_exact(jd0, g0, jd1, g1, |jd| value(jd).map(offset))?);
+        }
+    }
+    if let Some(&(jd, angle)) = samples.last() {
+        if offset(angle) == 0.0 {
+            exact.push(jd);
+        }
+    }
+    Ok(exact)
+}
+
+/// Narrows a bracketed crossing of `offset` to `EXACT_TOLERANCE` by regula falsi
+/// with the Illinois modification
+pub(crate) fn refine_exact(
+    mut jd0: f64,
+    mut g0: f64,
+    mut jd1: f64,
+    mut g1: f64,
+    offset: impl Fn(f64) -> Result<f64, String>,
+) -> Result<f64, String> {
+    let mut side = 0;
+    for _ in 0..100 {
+        let jd = jd1 - g1 * (jd1 - jd0) / (g1 - g0);
+        let g = offset(jd)?;
+        if g == 0.0 || (jd1 - jd0).abs() < EXACT_TOLERANCE {
+            return Ok(jd);
+        }
+        if g.signum() == g1.signum() {
+            jd1 = jd;
+            g1 = g;
+            if side == -1 {
+                g0 /= 2.0;
+            }
+            side = -1;
+        } else {
+            jd0 = jd;
+            g0 = g;
+            if side == 1 {
+                g1 /= 2.0;
+            }
+            side = 1;
+        }
+    }
+    Ok(jd1 - g1 * (jd1 - jd0) / (g1 - g0))
+}
+
+/// Days until an applying aspect of `angle` degrees between two bodies is exact.
+///
+/// # Returns
+///
+/// `None` when the aspect is separating, or doesn't perfect within
+/// `EXACT_HORIZON_DAYS`
+pub fn days_to_exact(
+    jd: f64,
+    body1: Planet,
+    position1: &PlanetPosition,
+    body2: Planet,
+    position2: &PlanetPosition,
+    angle: f64,
+    source: EphemerisSource,
+) -> Result<Option<f64>, String> {
+    let deviation = shortest_distance(position1.longitude, position2.longitude) - angle;
+    let closing_speed = closing_speed(position1.longitude, position1.speed, position2.longitude, position2.speed, angle);
+    if deviation == 0.0 {
+        return Ok(Some(0.0));
+    }
+    if closing_speed <= 0.0 {
+        return Ok(None);
+    }
+
+    // Allow for the bodies slowing down before they reach the exact angle
+    let horizon = (3.0 * deviation.abs() / closing_speed + 1.0).min(EXACT_HORIZON_DAYS);
+    Ok(next_exact_at_angle(jd, body1, body2, angle, horizon, source)?
+        .first()
+        .map(|exact| exact - jd))
+}
+
+/// Orbs tighter than this count as exact, in degrees
+pub const EXACT_ORB: f64 = 0.1;
+
+/// How near an aspect is to exact: 1 with no orb, falling to 0 at the widest
+/// orb it was looked for at
+pub fn aspect_strength(orb: f64, orb_allowed: f64) -> f64 {
+    if orb_allowed <= 0.0 {
+        return 1.0;
     }
+    (1.0 - orb / orb_allowed).clamp(0.0, 1.0)
+}
+
+/// Whether an orb is tight enough to call the aspect exact
+pub fn is_exact(orb: f64) -> bool {
+    orb < EXACT_ORB
 }
 
 #[derive(Debug, Clone)]
 pub struct Aspect {
     pub planet1: String,
     pub planet2: String,
-    pub aspect_type: AspectType,
+    /// `None` for a custom aspect
+    pub aspect_type: Option<AspectType>,
+    /// The aspect type's name, or the custom aspect's
+    pub name: String,
     pub orb: f64,
+    /// The widest orb the aspect was looked for at
+    pub orb_allowed: f64,
+    /// Whether the bodies are moving towards the exact aspect
+    pub applying: bool,
+    /// Days until the aspect is exact if the bodies kept their current speeds;
+    /// `None` while it separates
+    pub exact_in: Option<f64>,
+}
+
+impl std::str::FromStr for AspectType {
+    type Err = String;
+
+    /// Parses an aspect by name, ignoring case and separators, e.g. "trine",
+    /// "SemiSextile" or "semi_sextile"
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let name: String = s
+            .chars()
+            .filter(|c| !matches!(c, '_' | '-' | ' '))
+            .collect::<String>()
+            .to_ascii_lowercase();
+        match name.as_str() {
+            "conjunction" => Ok(AspectType::Conjunction),
+            "semisextile" => Ok(AspectType::SemiSextile),
+            "semisquare" => Ok(AspectType::SemiSquare),
+            "sextile" => Ok(AspectType::Sextile),
+            "quintile" => Ok(AspectType::Quintile),
+            "square" => Ok(AspectType::Square),
+            "biquintile" => Ok(AspectType::BiQuintile),
+            "trine" => Ok(AspectType::Trine),
+            "sesquisquare" => Ok(AspectType::Sesquisquare),
+            "quincunx" => Ok(AspectType::Quincunx),
+            "opposition" => Ok(AspectType::Opposition),
+            "septile" => Ok(AspectType::Septile),
+            "biseptile" => Ok(AspectType::BiSeptile),
+            "triseptile" => Ok(AspectType::TriSeptile),
+            "novile" => Ok(AspectType::Novile),
+            "binovile" => Ok(AspectType::BiNovile),
+            "quadnovile" => Ok(AspectType::QuadNovile),
+            _ => Err(format!("Unknown aspect '{}'", s)),
+        }
+    }
+}
+
+impl std::fmt::Display for AspectType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl Serialize for AspectType {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.name())
+    }
+}
+
+impl<'de> Deserialize<'de> for AspectType {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let name = String::deserialize(deserializer)?;
+        name.parse().map_err(serde::de::Error::custom)
+    }
 }
 
 impl AspectType {
+    /// Every aspect type, in the order they are looked for
+    pub const ALL: [AspectType; 17] = [
+        AspectType::Conjunction,
+        AspectType::SemiSextile,
+        AspectType::SemiSquare,
+        AspectType::Sextile,
+        AspectType::Quintile,
+        AspectType::Square,
+        AspectType::BiQuintile,
+        AspectType::Trine,
+        AspectType::Sesquisquare,
+        AspectType::Quincunx,
+        AspectType::Opposition,
+        AspectType::Septile,
+        AspectType::BiSeptile,
+        AspectType::TriSeptile,
+        AspectType::Novile,
+        AspectType::BiNovile,
+        AspectType::QuadNovile,
+    ];
+
+    /// The aspect's name in responses, e.g. "SemiSextile"
+    pub fn name(&self) -> &'static str {
+        match self {
+            AspectType::Conjunction => "Conjunction",
+            AspectType::SemiSextile => "SemiSextile",
+            AspectType::SemiSquare => "SemiSquare",
+            AspectType::Sextile => "Sextile",
+            AspectType::Quintile => "Quintile",
+            AspectType::Square => "Square",
+            AspectType::BiQuintile => "BiQuintile",
+            AspectType::Trine => "Trine",
+            AspectType::Sesquisquare => "Sesquisquare",
+            AspectType::Quincunx => "Quincunx",
+            AspectType::Opposition => "Opposition",
+            AspectType::Septile => "Septile",
+            AspectType::BiSeptile => "BiSeptile",
+            AspectType::TriSeptile => "TriSeptile",
+            AspectType::Novile => "Novile",
+            AspectType::BiNovile => "BiNovile",
+            AspectType::QuadNovile => "QuadNovile",
+        }
+    }
+
     pub fn angle(&self) -> f64 {
         match self {
             AspectType::Conjunction => 0.0,
@@ -245,28 +656,138 @@ impl AspectType {
     }
 }
 
+/// Largest orb a custom aspect may allow, in degrees
+pub const MAX_CUSTOM_ORB: f64 = 10.0;
+
+/// Most custom aspects one request may define
+pub const MAX_CUSTOM_ASPECTS: usize = 24;
+
+/// An aspect to look for: its name, exact angle and the orb allowed around it.
+///
+/// The built-in `AspectType`s convert into definitions, and requests can add
+/// their own, such as a 36° decile.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AspectDef {
+    pub name: String,
+    /// Exact angle in degrees (0-180)
+    pub angle: f64,
+    pub orb: f64,
+    /// The built-in aspect this was made from; `None` for a custom aspect
+    pub aspect_type: Option<AspectType>,
+}
+
+impl AspectDef {
+    /// A custom aspect, checked for a name, an angle strictly between 0° and 180°
+    /// and an orb above 0° and at most `MAX_CUSTOM_ORB`
+    pub fn custom(name: &str, angle: f64, orb: f64) -> Result<Self, String> {
+        let name = name.trim();
+        if name.is_empty() {
+            return Err("Custom aspects need a name".to_string());
+        }
+        if !(angle > 0.0 && angle < 180.0) {
+            return Err(format!("Custom aspect '{}' needs an angle between 0 and 180 degrees", name));
+        }
+        if !(orb > 0.0 && orb <= MAX_CUSTOM_ORB) {
+            return Err(format!(
+                "Custom aspect '{}' needs an orb above 0 and at most {} degrees",
+                name, MAX_CUSTOM_ORB
+            ));
+        }
+        Ok(Self {
+            name: name.to_string(),
+            angle,
+            orb,
+            aspect_type: None,
+        })
+    }
+}
+
+impl From<AspectType> for AspectDef {
+    /// The aspect at its standard natal orb
+    fn from(aspect_type: AspectType) -> Self {
+        Self {
+            name: aspect_type.to_string(),
+            angle: aspect_type.angle(),
+            orb: aspect_type.orb(),
+            aspect_type: Some(aspect_type),
+        }
+    }
+}
+
+/// Checks that a request's custom aspects fit in `MAX_CUSTOM_ASPECTS` and that
+/// their names, ignoring case, differ from each other and from the built-in aspects
+pub fn validate_custom_aspects(custom: &[AspectDef]) -> Result<(), String> {
+    if custom.len() > MAX_CUSTOM_ASPECTS {
+        return Err(format!("At most {} custom aspects can be defined", MAX_CUSTOM_ASPECTS));
+    }
+    for (i, aspect) in custom.iter().enumerate() {
+        if aspect.name.parse::<AspectType>().is_ok() {
+            return Err(format!("Custom aspect '{}' has the name of a built-in aspect", aspect.name));
+        }
+        if custom[..i].iter().any(|other| other.name.eq_ignore_ascii_case(&aspect.name)) {
+            return Err(format!("Custom aspect '{}' is defined more than once", aspect.name));
+        }
+    }
+    Ok(())
+}
+
+/// The aspects to look for: the built-in set at natal or transit orbs, then `custom`
+/// at the orbs they were defined with
+pub fn aspect_defs(include_minor: bool, use_transit_orbs: bool, custom: &[AspectDef]) -> Vec<AspectDef> {
+    get_aspect_types(include_minor)
+        .into_iter()
+        .map(|aspect_type| AspectDef {
+            orb: if use_transit_orbs {
+                aspect_type.transit_orb()
+            } else {
+                aspect_type.orb()
+            },
+            ..aspect_type.into()
+        })
+        .chain(custom.iter().cloned())
+        .collect()
+}
+
+/// The definition closest to exact between two longitudes, with the deviation
+/// from it, if any is within its orb. The first wins a tie.
+fn closest_aspect(longitude1: f64, longitude2: f64, defs: &[AspectDef]) -> Option<(&AspectDef, f64)> {
+    let separation = shortest_distance(longitude1, longitude2);
+    let mut closest: Option<(&AspectDef, f64)> = None;
+    for def in defs {
+        let deviation = (separation - def.angle).abs();
+        if deviation <= def.orb && closest.is_none_or(|(_, best)| deviation < best) {
+            closest = Some((def, deviation));
+        }
+    }
+    closest
+}
+
+/// Which built-in aspects were looked for, as reported in responses
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AspectSet {
+    /// Conjunction, sextile, square, trine and opposition
+    #[default]
+    Major,
+    /// The major aspects along with the minor and harmonic ones
+    All,
+}
+
+impl AspectSet {
+    /// The set `get_aspect_types` returns for an `include_minor` flag
+    pub fn new(include_minor: bool) -> Self {
+        if include_minor {
+            AspectSet::All
+        } else {
+            AspectSet::Major
+        }
+    }
+}
+
 /// Get the list of aspect types to check based on whether to include minor aspects
 pub fn get_aspect_types(include_minor: bool) -> Vec<AspectType> {
     if include_minor {
-        vec![
-            AspectType::Conjunction,
-            AspectType::SemiSextile,
-            AspectType::SemiSquare,
-            AspectType::Sextile,
-            AspectType::Quintile,
-            AspectType::Square,
-            AspectType::BiQuintile,
-            AspectType::Trine,
-            AspectType::Sesquisquare,
-            AspectType::Quincunx,
-            AspectType::Opposition,
-            AspectType::Septile,
-            AspectType::BiSeptile,
-            AspectType::TriSeptile,
-            AspectType::Novile,
-            AspectType::BiNovile,
-            AspectType::QuadNovile,
-        ]
+        AspectType::ALL.to_vec()
     } else {
         vec![
             AspectType::Conjunction,
@@ -295,78 +816,40 @@ pub fn calculate_transit_aspects_with_options(positions: &[PlanetPosition], incl
 
 /// Internal function to calculate aspects with different orb types
 fn calculate_aspects_with_orb_type(positions: &[PlanetPosition], include_minor_aspects: bool, use_transit_orbs: bool) -> Vec<Aspect> {
+    calculate_aspects_with_defs(positions, &aspect_defs(include_minor_aspects, use_transit_orbs, &[]))
+}
+
+/// Calculate aspects between planets, looking for the given aspect definitions.
+///
+/// Each pair gets at most one aspect, the one closest to exact.
+pub fn calculate_aspects_with_defs(positions: &[PlanetPosition], defs: &[AspectDef]) -> Vec<Aspect> {
+    aspects_between(positions, defs, planet_name)
+}
+
+/// Like `calculate_aspects_with_defs`, for positions of the given bodies rather
+/// than the ten in `calculate_planet_positions` order
+pub fn calculate_body_aspects_with_defs(bodies: &[Planet], positions: &[PlanetPosition], defs: &[AspectDef]) -> Vec<Aspect> {
+    aspects_between(positions, defs, |i| bodies[i].to_string())
+}
+
+fn aspects_between(positions: &[PlanetPosition], defs: &[AspectDef], name: impl Fn(usize) -> String) -> Vec<Aspect> {
     let mut aspects = Vec::new();
-    let aspect_types = get_aspect_types(include_minor_aspects);
 
     for i in 0..positions.len() {
         for j in (i + 1)..positions.len() {
             let pos1 = &positions[i];
             let pos2 = &positions[j];
 
-            // Skip if either planet is retrograde
-            if pos1.is_retrograde || pos2.is_retrograde {
-                continue;
-            }
-
-            let diff = (pos1.longitude - pos2.longitude).abs() % 360.0;
-            let min_diff = diff.min(360.0 - diff);
-
-            // Find the closest aspect within orb (to avoid multiple aspects for the same planet pair)
-            let mut closest_aspect: Option<(AspectType, f64)> = None;
-
-            // Check each aspect type to find the closest one
-            for aspect_type in aspect_types.iter() {
-                let aspect_angle = aspect_type.angle();
-                let orb = if use_transit_orbs {
-                    aspect_type.transit_orb()
-                } else {
-                    aspect_type.orb()
-                };
-                let aspect_diff = (min_diff - aspect_angle).abs();
-                
-                if aspect_diff <= orb {
-                    match closest_aspect {
-                        None => closest_aspect = Some((*aspect_type, aspect_diff)),
-                        Some((_, current_diff)) => {
-                            if aspect_diff < current_diff {
-                                closest_aspect = Some((*aspect_type, aspect_diff));
-                            }
-                        }
-                    }
-                }
-            }
-
-            // Add only the closest aspect if one was found
-            if let Some((aspect_type, orb_diff)) = closest_aspect {
+            if let Some((def, orb)) = closest_aspect(pos1.longitude, pos2.longitude, defs) {
                 aspects.push(Aspect {
-                    planet1: match i {
-                        0 => "Sun".to_string(),
-                        1 => "Moon".to_string(),
-                        2 => "Mercury".to_string(),
-                        3 => "Venus".to_string(),
-                        4 => "Mars".to_string(),
-                        5 => "Jupiter".to_string(),
-                        6 => "Saturn".to_string(),
-                        7 => "Uranus".to_string(),
-                        8 => "Neptune".to_string(),
-                        9 => "Pluto".to_string(),
-                        _ => format!("Planet{}", i + 1),
-                    },
-                    planet2: match j {
-                        0 => "Sun".to_string(),
-                        1 => "Moon".to_string(),
-                        2 => "Mercury".to_string(),
-                        3 => "Venus".to_string(),
-                        4 => "Mars".to_string(),
-                        5 => "Jupiter".to_string(),
-                        6 => "Saturn".to_string(),
-                        7 => "Uranus".to_string(),
-                        8 => "Neptune".to_string(),
-                        9 => "Pluto".to_string(),
-                        _ => format!("Planet{}", j + 1),
-                    },
-                    aspect_type,
-                    orb: orb_diff,
+                    planet1: name(i),
+                    planet2: name(j),
+                    aspect_type: def.aspect_type,
+                    name: def.name.clone(),
+                    orb,
+                    orb_allowed: def.orb,
+                    applying: is_aspect_applying(pos1.longitude, pos1.speed, pos2.longitude, pos2.speed, def.angle),
+                    exact_in: linear_days_to_exact(pos1.longitude, pos1.speed, pos2.longitude, pos2.speed, def.angle),
                 });
             }
         }
@@ -382,69 +865,62 @@ pub fn calculate_cross_aspects(natal_positions: &[PlanetPosition], transit_posit
 
 /// Calculate aspects between two sets of planets with option to include minor aspects
 pub fn calculate_cross_aspects_with_options(natal_positions: &[PlanetPosition], transit_positions: &[PlanetPosition], include_minor_aspects: bool) -> Vec<Aspect> {
+    // Transits use tight orbs
+    calculate_cross_aspects_with_defs(natal_positions, transit_positions, &aspect_defs(include_minor_aspects, true, &[]))
+}
+
+/// Calculate aspects from natal to transit planets, looking for the given aspect definitions
+pub fn calculate_cross_aspects_with_defs(natal_positions: &[PlanetPosition], transit_positions: &[PlanetPosition], defs: &[AspectDef]) -> Vec<Aspect> {
+    cross_aspects_between(
+        natal_positions,
+        transit_positions,
+        defs,
+        |i| format!("Natal {}", planet_name(i)),
+        |j| format!("Transit {}", planet_name(j)),
+    )
+}
+
+/// Like `calculate_cross_aspects_with_defs`, for positions of the given bodies
+/// rather than the ten in `calculate_planet_positions` order
+pub fn calculate_cross_body_aspects_with_defs(
+    natal_bodies: &[Planet],
+    natal_positions: &[PlanetPosition],
+    transit_bodies: &[Planet],
+    transit_positions: &[PlanetPosition],
+    defs: &[AspectDef],
+) -> Vec<Aspect> {
+    cross_aspects_between(
+        natal_positions,
+        transit_positions,
+        defs,
+        |i| format!("Natal {}", natal_bodies[i]),
+        |j| format!("Transit {}", transit_bodies[j]),
+    )
+}
+
+// `natal_label` and `transit_label` give the tagged names, e.g. "Natal Sun"
+fn cross_aspects_between(
+    natal_positions: &[PlanetPosition],
+    transit_positions: &[PlanetPosition],
+    defs: &[AspectDef],
+    natal_label: impl Fn(usize) -> String,
+    transit_label: impl Fn(usize) -> String,
+) -> Vec<Aspect> {
     let mut aspects = Vec::new();
-    let aspect_types = get_aspect_types(include_minor_aspects);
-
-    for i in 0..natal_positions.len() {
-        for j in 0..transit_positions.len() {
-            let natal_pos = &natal_positions[i];
-            let transit_pos = &transit_positions[j];
-
-            let diff = (natal_pos.longitude - transit_pos.longitude).abs() % 360.0;
-            let min_diff = diff.min(360.0 - diff);
-
-            // Find the closest aspect within orb (to avoid multiple aspects for the same planet pair)
-            let mut closest_aspect: Option<(AspectType, f64)> = None;
-
-            // Check each aspect type to find the closest one
-            for aspect_type in aspect_types.iter() {
-                let aspect_angle = aspect_type.angle();
-                let orb = aspect_type.transit_orb(); // Use tight transit orbs
-                let aspect_diff = (min_diff - aspect_angle).abs();
-                
-                if aspect_diff <= orb {
-                    match closest_aspect {
-                        None => closest_aspect = Some((*aspect_type, aspect_diff)),
-                        Some((_, current_diff)) => {
-                            if aspect_diff < current_diff {
-                                closest_aspect = Some((*aspect_type, aspect_diff));
-                            }
-                        }
-                    }
-                }
-            }
 
-            // Add only the closest aspect if one was found
-            if let Some((aspect_type, orb_diff)) = closest_aspect {
+    for (i, natal_pos) in natal_positions.iter().enumerate() {
+        for (j, transit_pos) in transit_positions.iter().enumerate() {
+            if let Some((def, orb)) = closest_aspect(natal_pos.longitude, transit_pos.longitude, defs) {
+                // The natal positions stand still; only the transiting body moves
                 aspects.push(Aspect {
-                    planet1: format!("Natal {}", match i {
-                        0 => "Sun".to_string(),
-                        1 => "Moon".to_string(),
-                        2 => "Mercury".to_string(),
-                        3 => "Venus".to_string(),
-                        4 => "Mars".to_string(),
-                        5 => "Jupiter".to_string(),
-                        6 => "Saturn".to_string(),
-                        7 => "Uranus".to_string(),
-                        8 => "Neptune".to_string(),
-                        9 => "Pluto".to_string(),
-                        _ => format!("Planet{}", i + 1),
-                    }),
-                    planet2: format!("Transit {}", match j {
-                        0 => "Sun".to_string(),
-                        1 => "Moon".to_string(),
-                        2 => "Mercury".to_string(),
-                        3 => "Venus".to_string(),
-                        4 => "Mars".to_string(),
-                        5 => "Jupiter".to_string(),
-                        6 => "Saturn".to_string(),
-                        7 => "Uranus".to_string(),
-                        8 => "Neptune".to_string(),
-                        9 => "Pluto".to_string(),
-                        _ => format!("Planet{}", j + 1),
-                    }),
-                    aspect_type,
-                    orb: orb_diff,
+                    planet1: natal_label(i),
+                    planet2: transit_label(j),
+                    aspect_type: def.aspect_type,
+                    name: def.name.clone(),
+                    orb,
+                    orb_allowed: def.orb,
+                    applying: is_aspect_applying(natal_pos.longitude, 0.0, transit_pos.longitude, transit_pos.speed, def.angle),
+                    exact_in: linear_days_to_exact(natal_pos.longitude, 0.0, transit_pos.longitude, transit_pos.speed, def.angle),
                 });
             }
         }
@@ -453,71 +929,23 @@ pub fn calculate_cross_aspects_with_options(natal_positions: &[PlanetPosition],
     aspects
 }
 
-/// Calculate synastry aspects between two natal charts (person1 vs person2)
-pub fn calculate_synastry_aspects(chart1_positions: &[PlanetPosition], chart2_positions: &[PlanetPosition], include_minor_aspects: bool) -> Vec<Aspect> {
+/// Aspects from each of one set of named points to each of another, named as
+/// the points are. Used for points, like the Vertex, that aren't in a position slice.
+pub fn calculate_point_aspects_with_defs(points1: &[ChartPoint], points2: &[ChartPoint], defs: &[AspectDef]) -> Vec<Aspect> {
     let mut aspects = Vec::new();
-    let aspect_types = get_aspect_types(include_minor_aspects);
-
-    for i in 0..chart1_positions.len() {
-        for j in 0..chart2_positions.len() {
-            let pos1 = &chart1_positions[i];
-            let pos2 = &chart2_positions[j];
-
-            let diff = (pos1.longitude - pos2.longitude).abs() % 360.0;
-            let min_diff = diff.min(360.0 - diff);
-
-            // Find the closest aspect within orb (to avoid multiple aspects for the same planet pair)
-            let mut closest_aspect: Option<(AspectType, f64)> = None;
-
-            // Check each aspect type to find the closest one
-            for aspect_type in aspect_types.iter() {
-                let aspect_angle = aspect_type.angle();
-                let orb = aspect_type.orb(); // Use standard natal orbs for synastry
-                let aspect_diff = (min_diff - aspect_angle).abs();
-                
-                if aspect_diff <= orb {
-                    match closest_aspect {
-                        None => closest_aspect = Some((*aspect_type, aspect_diff)),
-                        Some((_, current_diff)) => {
-                            if aspect_diff < current_diff {
-                                closest_aspect = Some((*aspect_type, aspect_diff));
-                            }
-                        }
-                    }
-                }
-            }
 
-            // Add only the closest aspect if one was found
-            if let Some((aspect_type, orb_diff)) = closest_aspect {
+    for point1 in points1 {
+        for point2 in points2 {
+            if let Some((def, orb)) = closest_aspect(point1.longitude, point2.longitude, defs) {
                 aspects.push(Aspect {
-                    planet1: match i {
-                        0 => "Sun".to_string(),
-                        1 => "Moon".to_string(),
-                        2 => "Mercury".to_string(),
-                        3 => "Venus".to_string(),
-                        4 => "Mars".to_string(),
-                        5 => "Jupiter".to_string(),
-                        6 => "Saturn".to_string(),
-                        7 => "Uranus".to_string(),
-                        8 => "Neptune".to_string(),
-                        9 => "Pluto".to_string(),
-                        _ => format!("Planet{}", i + 1),
-                    },
-                    planet2: match j {
-                        0 => "Sun".to_string(),
-                        1 => "Moon".to_string(),
-                        2 => "Mercury".to_string(),
-                        3 => "Venus".to_string(),
-                        4 => "Mars".to_string(),
-                        5 => "Jupiter".to_string(),
-                        6 => "Saturn".to_string(),
-                        7 => "Uranus".to_string(),
-                        8 => "Neptune".to_string(),
-                        9 => "Pluto".to_string(),
-                        _ => format!("Planet{}", j + 1),
-                    },
-                    aspect_type,
-                    orb: orb_diff,
+                    planet1: point1.name.clone(),
+                    planet2: point2.name.clone(),
+                    aspect_type: def.aspect_type,
+                    name: def.name.clone(),
+                    orb,
+                    orb_allowed: def.orb,
+                    applying: is_aspect_applying(point1.longitude, point1.speed, point2.longitude, point2.speed, def.angle),
+                    exact_in: linear_days_to_exact(point1.longitude, point1.speed, point2.longitude, point2.speed, def.angle),
                 });
             }
         }
@@ -526,9 +954,364 @@ pub fn calculate_synastry_aspects(chart1_positions: &[PlanetPosition], chart2_po
     aspects
 }
 
+/// An aspect from one person's planet to another's, in that direction
+#[derive(Debug, Clone)]
+pub struct SynastryAspect {
+    pub person1_planet: String,
+    pub person2_planet: String,
+    /// `None` for a custom aspect
+    pub aspect_type: Option<AspectType>,
+    /// The aspect type's name, or the custom aspect's
+    pub name: String,
+    pub orb: f64,
+    /// The widest orb the aspect was looked for at
+    pub orb_allowed: f64,
+}
+
+/// A named point on the ecliptic, whether calculated here or read back from a
+/// chart a client sent
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChartPoint {
+    pub name: String,
+    pub longitude: f64,
+    /// Degrees per day, negative when retrograde
+    pub speed: f64,
+}
+
+impl ChartPoint {
+    pub fn new(name: impl Into<String>, longitude: f64, speed: f64) -> Self {
+        ChartPoint {
+            name: name.into(),
+            longitude,
+            speed,
+        }
+    }
+}
+
+/// Names positions in `calculate_planet_positions` order
+pub fn chart_points(positions: &[PlanetPosition]) -> Vec<ChartPoint> {
+    named_points(positions, planet_name)
+}
+
+/// Names positions after their bodies, `bodies[i]` for `positions[i]`
+pub fn body_points(bodies: &[Planet], positions: &[PlanetPosition]) -> Vec<ChartPoint> {
+    named_points(positions, |i| bodies[i].to_string())
+}
+
+fn named_points(positions: &[PlanetPosition], name: impl Fn(usize) -> String) -> Vec<ChartPoint> {
+    positions
+        .iter()
+        .enumerate()
+        .map(|(i, position)| ChartPoint {
+            name: name(i),
+            longitude: position.longitude,
+            speed: position.speed,
+        })
+        .collect()
+}
+
+/// Calculate synastry aspects between two natal charts (person1 vs person2).
+///
+/// Each pair of planets appears once, always with person1's planet first, and the
+/// result is sorted by orb, tightest first, with ties kept in chart order.
+pub fn calculate_synastry_aspects(chart1_positions: &[PlanetPosition], chart2_positions: &[PlanetPosition], include_minor_aspects: bool) -> Vec<SynastryAspect> {
+    synastry_aspects_between(&chart_points(chart1_positions), &chart_points(chart2_positions), include_minor_aspects)
+}
+
+/// Synastry aspects between two sets of named points, ordered as
+/// `calculate_synastry_aspects` orders them
+pub fn synastry_aspects_between(chart1: &[ChartPoint], chart2: &[ChartPoint], include_minor_aspects: bool) -> Vec<SynastryAspect> {
+    // Standard natal orbs for synastry
+    synastry_aspects_with_defs(chart1, chart2, &aspect_defs(include_minor_aspects, false, &[]))
+}
+
+/// Synastry aspects between two sets of named points, looking for the given
+/// aspect definitions
+pub fn synastry_aspects_with_defs(chart1: &[ChartPoint], chart2: &[ChartPoint], defs: &[AspectDef]) -> Vec<SynastryAspect> {
+    let mut aspects = Vec::new();
+
+    for (i, pos1) in chart1.iter().enumerate() {
+        for (j, pos2) in chart2.iter().enumerate() {
+            if let Some((def, orb)) = closest_aspect(pos1.longitude, pos2.longitude, defs) {
+                aspects.push(((i, j), SynastryAspect {
+                    person1_planet: pos1.name.clone(),
+                    person2_planet: pos2.name.clone(),
+                    aspect_type: def.aspect_type,
+                    name: def.name.clone(),
+                    orb,
+                    orb_allowed: def.orb,
+                }));
+            }
+        }
+    }
+
+    aspects.sort_by(|(a_index, a), (b_index, b)| {
+        a.orb.total_cmp(&b.orb).then(a_index.cmp(b_index))
+    });
+    aspects.into_iter().map(|(_, aspect)| aspect).collect()
+}
+
+/// The fields of an aspect that an `AspectFilter` looks at, for each aspect representation
+pub trait AspectRecord {
+    /// Names of the two bodies, possibly tagged with their chart, e.g. "Natal Sun"
+    fn bodies(&self) -> (&str, &str);
+    /// `None` when the aspect is stored under a name that doesn't parse
+    fn aspect_type(&self) -> Option<AspectType>;
+    fn orb(&self) -> f64;
+    /// 0 to 1, as `aspect_strength` gives it
+    fn strength(&self) -> f64;
+}
+
+impl AspectRecord for Aspect {
+    fn bodies(&self) -> (&str, &str) {
+        (&self.planet1, &self.planet2)
+    }
+
+    fn aspect_type(&self) -> Option<AspectType> {
+        self.aspect_type
+    }
+
+    fn orb(&self) -> f64 {
+        self.orb
+    }
+
+    fn strength(&self) -> f64 {
+        aspect_strength(self.orb, self.orb_allowed)
+    }
+}
+
+impl AspectRecord for SynastryAspect {
+    fn bodies(&self) -> (&str, &str) {
+        (&self.person1_planet, &self.person2_planet)
+    }
+
+    fn aspect_type(&self) -> Option<AspectType> {
+        self.aspect_type
+    }
+
+    fn orb(&self) -> f64 {
+        self.orb
+    }
+
+    fn strength(&self) -> f64 {
+        aspect_strength(self.orb, self.orb_allowed)
+    }
+}
+
+/// The order aspects are listed in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum AspectSort {
+    /// Strongest first, ties kept in the order they were found
+    #[default]
+    Strength,
+    /// The order they were found in: chart order for the bodies, or tightest
+    /// orb first for synastry
+    Planets,
+}
+
+impl std::str::FromStr for AspectSort {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "strength" => Ok(AspectSort::Strength),
+            "planets" => Ok(AspectSort::Planets),
+            _ => Err(format!("Invalid aspect sort: {} (expected \"strength\" or \"planets\")", s)),
+        }
+    }
+}
+
+/// Narrows a list of aspects to the ones a client asked for.
+///
+/// Body names are compared without their chart tag and ignoring case, so "sun"
+/// matches both "Sun" and "Natal Sun". Empty lists don't filter anything.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct AspectFilter {
+    /// Keep aspects with one of these bodies on either side
+    pub planets: Vec<String>,
+    /// Keep only these aspect types
+    pub aspects: Vec<AspectType>,
+    /// Drop aspects wider than this orb
+    pub max_orb: Option<f64>,
+    /// Keep aspects whose other side is one of these points
+    pub to_points: Vec<String>,
+    /// Keep at most this many aspects, preferring the tightest orbs
+    pub max_aspects: Option<usize>,
+    /// Order of the aspects kept
+    pub sort: AspectSort,
+}
+
+impl AspectFilter {
+    /// Whether an aspect passes the planet, aspect type and orb filters
+    pub fn matches<A: AspectRecord>(&self, aspect: &A) -> bool {
+        if self.max_orb.is_some_and(|max_orb| aspect.orb() > max_orb) {
+            return false;
+        }
+        if !self.aspects.is_empty()
+            && !aspect.aspect_type().is_some_and(|aspect_type| self.aspects.contains(&aspect_type))
+        {
+            return false;
+        }
+        let listed = |names: &[String], body: &str| {
+            let body = body.rsplit(' ').next().unwrap_or(body);
+            names.is_empty() || names.iter().any(|name| name.eq_ignore_ascii_case(body))
+        };
+        let (body1, body2) = aspect.bodies();
+        (listed(&self.planets, body1) && listed(&self.to_points, body2))
+            || (listed(&self.planets, body2) && listed(&self.to_points, body1))
+    }
+
+    /// Keeps the matching aspects, capped at `max_aspects`, and puts them in
+    /// `sort` order.
+    ///
+    /// Returns whether the cap dropped any.
+    pub fn apply<A: AspectRecord>(&self, aspects: Vec<A>) -> (Vec<A>, bool) {
+        let (mut aspects, truncated) = self.cap(aspects);
+        if self.sort == AspectSort::Strength {
+            aspects.sort_by(|a, b| b.strength().total_cmp(&a.strength()));
+        }
+        (aspects, truncated)
+    }
+
+    /// Keeps the matching aspects in their original order, capped at `max_aspects`
+    fn cap<A: AspectRecord>(&self, mut aspects: Vec<A>) -> (Vec<A>, bool) {
+        aspects.retain(|aspect| self.matches(aspect));
+        let Some(limit) = self.max_aspects.filter(|&limit| aspects.len() > limit) else {
+            return (aspects, false);
+        };
+
+        let mut by_orb: Vec<usize> = (0..aspects.len()).collect();
+        by_orb.sort_by(|&a, &b| aspects[a].orb().total_cmp(&aspects[b].orb()));
+        let mut keep = vec![false; aspects.len()];
+        for &index in &by_orb[..limit] {
+            keep[index] = true;
+        }
+        let mut keep = keep.into_iter();
+        aspects.retain(|_| keep.next().unwrap_or(false));
+        (aspects, true)
+    }
+}
+
+/// Names of the bodies in the order `calculate_planet_positions` returns them
+const PLANET_NAMES: [&str; 10] = [
+    "Sun", "Moon", "Mercury", "Venus", "Mars", "Jupiter", "Saturn", "Uranus", "Neptune", "Pluto",
+];
+
+fn planet_name(index: usize) -> String {
+    match PLANET_NAMES.get(index) {
+        Some(name) => name.to_string(),
+        None => format!("Planet{}", index + 1),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::calc::planets::Motion;
+
+    #[test]
+    fn test_aspect_type_round_trips() {
+        for aspect in AspectType::ALL {
+            let json = serde_json::to_string(&aspect).unwrap();
+            assert_eq!(json, format!("\"{}\"", aspect.name()));
+            assert_eq!(serde_json::from_str::<AspectType>(&json).unwrap(), aspect);
+            assert_eq!(aspect.to_string(), format!("{:?}", aspect));
+            assert_eq!(aspect.name().parse::<AspectType>(), Ok(aspect));
+            assert_eq!(aspect.name().to_lowercase().parse::<AspectType>(), Ok(aspect));
+        }
+        assert_eq!(serde_json::from_str::<AspectType>("\"semi_sextile\"").unwrap(), AspectType::SemiSextile);
+        assert_eq!("Bi-Quintile".parse::<AspectType>(), Ok(AspectType::BiQuintile));
+        assert!(serde_json::from_str::<AspectType>("\"decile\"").is_err());
+    }
+
+    /// Positions at the given longitudes, none of them moving
+    fn still(longitudes: &[f64]) -> Vec<PlanetPosition> {
+        longitudes.iter().map(|&longitude| PlanetPosition::new(longitude, 0.0, 0.0, false)).collect()
+    }
+
+    #[test]
+    fn test_moon_applying_to_a_conjunction() {
+        // The Moon 3° behind the Sun and gaining 12° a day on it
+        let sun = PlanetPosition::new(100.0, 0.0, 1.0, false);
+        let moon = PlanetPosition::new(97.0, 0.0, 13.0, false);
+        let aspects = calculate_aspects_with_defs(&[sun, moon], &aspect_defs(false, false, &[]));
+        assert_eq!(aspects.len(), 1);
+        assert_eq!(aspects[0].aspect_type, Some(AspectType::Conjunction));
+        assert!(aspects[0].applying);
+        let days = aspects[0].exact_in.unwrap();
+        assert!((days - 0.25).abs() < 1e-9, "{}", days);
+
+        // Judged the same with the Moon listed first, and across 0° Aries
+        assert!(is_aspect_applying(97.0, 13.0, 100.0, 1.0, 0.0));
+        assert!(is_aspect_applying(358.5, 13.0, 1.5, 1.0, 0.0));
+        let config = calculate_aspect(358.5, 13.0, 1.5, 1.0, AspectType::Conjunction, 10.0).unwrap();
+        assert!(config.applying);
+    }
+
+    #[test]
+    fn test_moon_separating_from_a_conjunction() {
+        // The Moon 3° past the Sun and pulling away
+        let sun = PlanetPosition::new(100.0, 0.0, 1.0, false);
+        let moon = PlanetPosition::new(103.0, 0.0, 13.0, false);
+        let aspects = calculate_aspects_with_defs(&[sun, moon], &aspect_defs(false, false, &[]));
+        assert_eq!(aspects.len(), 1);
+        assert!(!aspects[0].applying);
+        assert_eq!(aspects[0].exact_in, None);
+
+        assert!(!is_aspect_applying(1.5, 13.0, 358.5, 1.0, 0.0));
+        // Though a faster body coming up behind the Moon closes on it
+        assert!(is_aspect_applying(103.0, 13.0, 100.0, 15.0, 0.0));
+        // Exact is neither applying nor separating
+        assert!(!is_aspect_applying(100.0, 13.0, 100.0, 1.0, 0.0));
+        assert_eq!(linear_days_to_exact(100.0, 13.0, 100.0, 1.0, 0.0), Some(0.0));
+    }
+
+    #[test]
+    fn test_applying_to_a_square_from_either_side() {
+        // Closing on 90° from inside and from outside it
+        assert!(is_aspect_applying(85.0, 1.0, 0.0, 0.0, 90.0));
+        assert!(!is_aspect_applying(95.0, 1.0, 0.0, 0.0, 90.0));
+        assert!(is_aspect_applying(95.0, -1.0, 0.0, 0.0, 90.0));
+        // The waning square, 90° behind
+        assert!(is_aspect_applying(275.0, -1.0, 0.0, 0.0, 90.0));
+        assert!(!is_aspect_applying(275.0, 1.0, 0.0, 0.0, 90.0));
+        let days = linear_days_to_exact(0.0, 0.0, 85.0, 2.0, 90.0).unwrap();
+        assert!((days - 2.5).abs() < 1e-9, "{}", days);
+    }
+
+    #[test]
+    fn test_all_aspects_orbs_by_type() {
+        // Two aspect types and two orbs used to index past the end of the slice
+        let orbs = OrbConfig::uniform(5.0).with_orb(AspectType::Trine, 2.0);
+        let aspect_types = [AspectType::Opposition, AspectType::Trine];
+        let aspects = calculate_all_aspects(&still(&[0.0, 177.0, 123.0]), &orbs, &aspect_types);
+
+        assert_eq!(aspects.len(), 1);
+        assert_eq!(aspects[0].index1, 0);
+        assert_eq!(aspects[0].index2, 1);
+        assert_eq!(aspects[0].aspect_type, AspectType::Opposition);
+        assert!((aspects[0].angle - 177.0).abs() < 1e-9);
+        assert!((aspects[0].config.orb - 3.0).abs() < 1e-9);
+
+        // The 3° trine between 0° and 123° is outside its 2° orb
+        let wider = orbs.with_orb(AspectType::Trine, 4.0);
+        let aspects = calculate_all_aspects(&still(&[0.0, 177.0, 123.0]), &wider, &aspect_types);
+        assert!(aspects.iter().any(|a| a.aspect_type == AspectType::Trine && a.index2 == 2));
+    }
+
+    #[test]
+    fn test_all_aspects_across_zero_aries() {
+        let aspects = calculate_all_aspects(&still(&[358.0, 2.0]), &OrbConfig::natal(), &[AspectType::Conjunction]);
+        assert_eq!(aspects.len(), 1);
+        assert!((aspects[0].angle - 4.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_orb_config_lookup() {
+        assert_eq!(OrbConfig::natal().orb(AspectType::Sextile), AspectType::Sextile.orb());
+        assert_eq!(OrbConfig::transit().orb(AspectType::Trine), AspectType::Trine.transit_orb());
+        assert_eq!(OrbConfig::uniform(1.5).orb(AspectType::Septile), 1.5);
+    }
 
     #[test]
     fn test_aspect_calculations() {
@@ -538,6 +1321,7 @@ mod tests {
                 latitude: 0.0,
                 speed: 0.0,
                 is_retrograde: false,
+                motion: Motion::Direct,
                 house: Some(1),
             },
             PlanetPosition {
@@ -545,6 +1329,7 @@ mod tests {
                 latitude: 0.0,
                 speed: 1.0,
                 is_retrograde: false,
+                motion: Motion::Direct,
                 house: Some(2),
             },
         ];
@@ -555,7 +1340,7 @@ mod tests {
         // Should find a sextile aspect
         let sextile = aspects
             .iter()
-            .find(|a| a.aspect_type == AspectType::Sextile);
+            .find(|a| a.aspect_type == Some(AspectType::Sextile));
         assert!(sextile.is_some());
         if let Some(sextile) = sextile {
             assert_eq!(sextile.planet1, "Sun");
@@ -572,6 +1357,7 @@ mod tests {
                 latitude: 0.0,
                 speed: 0.0,
                 is_retrograde: false,
+                motion: Motion::Direct,
                 house: Some(1),
             },
             PlanetPosition {
@@ -579,6 +1365,7 @@ mod tests {
                 latitude: 0.0,
                 speed: 1.0,
                 is_retrograde: false,
+                motion: Motion::Direct,
                 house: Some(2),
             },
         ];
@@ -587,7 +1374,7 @@ mod tests {
         // Should find a conjunction aspect
         let conjunction = aspects
             .iter()
-            .find(|a| a.aspect_type == AspectType::Conjunction);
+            .find(|a| a.aspect_type == Some(AspectType::Conjunction));
         assert!(conjunction.is_some());
         if let Some(conjunction) = conjunction {
             assert_eq!(conjunction.planet1, "Sun");
@@ -604,6 +1391,7 @@ mod tests {
                 latitude: 0.0,
                 speed: 0.0,
                 is_retrograde: false,
+                motion: Motion::Direct,
                 house: Some(1),
             },
             PlanetPosition {
@@ -611,11 +1399,40 @@ mod tests {
                 latitude: 0.0,
                 speed: 1.0,
                 is_retrograde: true,
+                motion: Motion::Retrograde,
                 house: Some(2),
             },
         ];
+        // Retrograde planets aspect like any other
         let aspects = calculate_aspects_with_options(&positions, false); // Major aspects only
-        assert!(aspects.is_empty());
+        assert_eq!(aspects.len(), 1);
+        assert_eq!(aspects[0].aspect_type, Some(AspectType::Sextile));
+    }
+
+    #[test]
+    fn test_retrograde_saturn_trine_sun() {
+        let sun = PlanetPosition::new(10.0, 0.0, 0.98, false);
+        let saturn = PlanetPosition::new(128.0, 0.0, -0.05, true);
+        let bodies = [Planet::Sun, Planet::Saturn];
+        let positions = [sun, saturn];
+        let defs = aspect_defs(false, false, &[]);
+
+        let aspects = calculate_body_aspects_with_defs(&bodies, &positions, &defs);
+        assert_eq!(aspects.len(), 1);
+        assert_eq!((aspects[0].planet1.as_str(), aspects[0].planet2.as_str()), ("Sun", "Saturn"));
+        assert_eq!(aspects[0].aspect_type, Some(AspectType::Trine));
+        assert!((aspects[0].orb - 2.0).abs() < 1e-9);
+
+        // Transit-to-natal and synastry aspects find the same trine
+        let cross = calculate_cross_body_aspects_with_defs(&bodies[..1], &positions[..1], &bodies[1..], &positions[1..], &defs);
+        assert_eq!(cross.len(), 1);
+        assert_eq!(cross[0].planet2, "Transit Saturn");
+        assert_eq!(cross[0].aspect_type, Some(AspectType::Trine));
+        let synastry =
+            synastry_aspects_between(&body_points(&bodies[..1], &positions[..1]), &body_points(&bodies[1..], &positions[1..]), false);
+        assert_eq!(synastry.len(), 1);
+        assert_eq!(synastry[0].person2_planet, "Saturn");
+        assert_eq!(synastry[0].aspect_type, Some(AspectType::Trine));
     }
 
     #[test]
@@ -626,6 +1443,7 @@ mod tests {
                 latitude: 0.0,
                 speed: 0.0,
                 is_retrograde: false,
+                motion: Motion::Direct,
                 house: Some(1),
             },
             PlanetPosition {
@@ -633,6 +1451,7 @@ mod tests {
                 latitude: 0.0,
                 speed: 1.0,
                 is_retrograde: false,
+                motion: Motion::Direct,
                 house: Some(2),
             },
         ];
@@ -642,7 +1461,7 @@ mod tests {
         // Should find a quintile aspect
         let quintile = aspects
             .iter()
-            .find(|a| a.aspect_type == AspectType::Quintile);
+            .find(|a| a.aspect_type == Some(AspectType::Quintile));
         assert!(quintile.is_some());
         if let Some(quintile) = quintile {
             assert_eq!(quintile.planet1, "Sun");
@@ -659,6 +1478,7 @@ mod tests {
                 latitude: 0.0,
                 speed: 0.0,
                 is_retrograde: false,
+                motion: Motion::Direct,
                 house: Some(1),
             },
             PlanetPosition {
@@ -666,6 +1486,7 @@ mod tests {
                 latitude: 0.0,
                 speed: 1.0,
                 is_retrograde: false,
+                motion: Motion::Direct,
                 house: Some(2),
             },
         ];
@@ -675,7 +1496,7 @@ mod tests {
         // Should find a septile aspect
         let septile = aspects
             .iter()
-            .find(|a| a.aspect_type == AspectType::Septile);
+            .find(|a| a.aspect_type == Some(AspectType::Septile));
         assert!(septile.is_some());
         if let Some(septile) = septile {
             assert_eq!(septile.planet1, "Sun");
@@ -692,6 +1513,7 @@ mod tests {
                 latitude: 0.0,
                 speed: 0.0,
                 is_retrograde: false,
+                motion: Motion::Direct,
                 house: Some(1),
             },
             PlanetPosition {
@@ -699,6 +1521,7 @@ mod tests {
                 latitude: 0.0,
                 speed: 1.0,
                 is_retrograde: false,
+                motion: Motion::Direct,
                 house: Some(2),
             },
         ];
@@ -706,7 +1529,7 @@ mod tests {
         println!("test_novile_aspects: aspects = {:#?}", aspects);
         assert!(!aspects.is_empty());
         // Should find a novile aspect
-        let novile = aspects.iter().find(|a| a.aspect_type == AspectType::Novile);
+        let novile = aspects.iter().find(|a| a.aspect_type == Some(AspectType::Novile));
         assert!(novile.is_some());
         if let Some(novile) = novile {
             assert_eq!(novile.planet1, "Sun");
@@ -714,4 +1537,234 @@ mod tests {
             assert!(novile.orb <= 2.0); // Novile orb is 2°
         }
     }
+
+    #[test]
+    fn test_synastry_aspect_direction() {
+        let chart1 = vec![
+            PlanetPosition::new(0.0, 0.0, 1.0, false),
+            PlanetPosition::new(300.0, 0.0, 13.0, false),
+        ];
+        let chart2 = vec![
+            PlanetPosition::new(90.0, 0.0, 1.0, false),
+            PlanetPosition::new(121.0, 0.0, 13.0, false),
+        ];
+        let aspects = calculate_synastry_aspects(&chart1, &chart2, false);
+        let trines: Vec<_> = aspects
+            .iter()
+            .filter(|a| a.aspect_type == Some(AspectType::Trine))
+            .collect();
+        // Person1's Sun trine person2's Moon, reported once and in that direction
+        assert_eq!(trines.len(), 1);
+        assert_eq!(trines[0].person1_planet, "Sun");
+        assert_eq!(trines[0].person2_planet, "Moon");
+        // Tightest orb first
+        assert!(aspects.windows(2).all(|w| w[0].orb <= w[1].orb));
+    }
+
+    #[test]
+    fn test_custom_aspect_is_found_and_named() {
+        let positions = vec![
+            PlanetPosition::new(10.0, 0.0, 1.0, false),
+            PlanetPosition::new(46.0, 0.0, 1.0, false),
+            PlanetPosition::new(250.0, 0.0, 1.0, false),
+        ];
+        let decile = AspectDef::custom("Decile", 36.0, 1.0).unwrap();
+        let aspects = calculate_aspects_with_defs(&positions, &aspect_defs(false, false, &[decile]));
+        assert_eq!(aspects.len(), 2);
+        let decile = aspects.iter().find(|a| a.planet2 == "Moon").unwrap();
+        assert_eq!((decile.name.as_str(), decile.aspect_type), ("Decile", None));
+        assert!(decile.orb.abs() < 1e-9);
+        // Built-in aspects keep their names alongside custom ones
+        let trine = aspects.iter().find(|a| a.planet2 == "Mercury").unwrap();
+        assert_eq!((trine.name.as_str(), trine.aspect_type), ("Trine", Some(AspectType::Trine)));
+    }
+
+    #[test]
+    fn test_aspects_named_after_their_bodies() {
+        // Mercury is missing, so Venus is the second position
+        let bodies = [Planet::Sun, Planet::Venus, Planet::Chiron];
+        let positions = vec![
+            PlanetPosition::new(10.0, 0.0, 1.0, false),
+            PlanetPosition::new(70.0, 0.0, 1.2, false),
+            PlanetPosition::new(190.0, 0.0, 0.05, false),
+        ];
+        let defs = aspect_defs(false, false, &[]);
+        let names: Vec<(String, String)> = calculate_body_aspects_with_defs(&bodies, &positions, &defs)
+            .into_iter()
+            .map(|a| (a.planet1, a.planet2))
+            .collect();
+        assert!(names.contains(&("Sun".to_string(), "Venus".to_string())));
+        assert!(names.contains(&("Sun".to_string(), "Chiron".to_string())));
+
+        let cross = calculate_cross_body_aspects_with_defs(&bodies, &positions, &bodies[1..], &positions[1..], &defs);
+        assert!(cross.iter().any(|a| a.planet1 == "Natal Sun" && a.planet2 == "Transit Venus"));
+        assert_eq!(body_points(&bodies, &positions)[2].name, "Chiron");
+    }
+
+    #[test]
+    fn test_custom_aspect_validation() {
+        assert!(AspectDef::custom("Decile", 36.0, 1.0).is_ok());
+        assert!(AspectDef::custom(" ", 36.0, 1.0).is_err());
+        assert!(AspectDef::custom("Whole", 180.0, 1.0).is_err());
+        assert!(AspectDef::custom("Nothing", 0.0, 1.0).is_err());
+        assert!(AspectDef::custom("Wide", 36.0, MAX_CUSTOM_ORB + 0.1).is_err());
+        assert!(AspectDef::custom("Exact", 36.0, 0.0).is_err());
+
+        let decile = AspectDef::custom("Decile", 36.0, 1.0).unwrap();
+        let again = AspectDef::custom("decile", 108.0, 1.0).unwrap();
+        assert!(validate_custom_aspects(&[decile.clone(), again]).is_err());
+        let trine = AspectDef::custom("trine", 120.0, 1.0).unwrap();
+        assert!(validate_custom_aspects(&[decile.clone(), trine]).is_err());
+        assert!(validate_custom_aspects(&vec![decile; MAX_CUSTOM_ASPECTS + 1]).is_err());
+    }
+
+    fn aspect(planet1: &str, planet2: &str, aspect_type: AspectType, orb: f64) -> Aspect {
+        Aspect {
+            planet1: planet1.to_string(),
+            planet2: planet2.to_string(),
+            aspect_type: Some(aspect_type),
+            name: aspect_type.to_string(),
+            orb,
+            orb_allowed: 5.0,
+            applying: false,
+            exact_in: None,
+        }
+    }
+
+    #[test]
+    fn test_filter_by_planet_and_orb() {
+        let aspects = vec![
+            aspect("Sun", "Moon", AspectType::Square, 1.0),
+            aspect("Venus", "Mars", AspectType::Trine, 4.0),
+            aspect("Mars", "Saturn", AspectType::Sextile, 0.5),
+        ];
+
+        let by_planet = AspectFilter {
+            planets: vec!["mars".to_string()],
+            ..Default::default()
+        };
+        let (kept, truncated) = by_planet.apply(aspects.clone());
+        assert!(!truncated);
+        assert_eq!(kept.len(), 2);
+        assert!(kept.iter().all(|a| a.planet1 == "Mars" || a.planet2 == "Mars"));
+
+        // A 4° trine is too wide for a 3° limit
+        let by_orb = AspectFilter {
+            max_orb: Some(3.0),
+            ..Default::default()
+        };
+        let (kept, _) = by_orb.apply(aspects.clone());
+        assert!(kept.iter().all(|a| a.aspect_type != Some(AspectType::Trine)));
+        assert_eq!(kept.len(), 2);
+
+        // Transit Mars to natal Saturn, ignoring the chart tags
+        let to_point = AspectFilter {
+            planets: vec!["Mars".to_string()],
+            to_points: vec!["Saturn".to_string()],
+            ..Default::default()
+        };
+        let cross = vec![
+            aspect("Natal Saturn", "Transit Mars", AspectType::Square, 2.0),
+            aspect("Natal Venus", "Transit Mars", AspectType::Trine, 1.0),
+        ];
+        let (kept, _) = to_point.apply(cross);
+        assert_eq!(kept.len(), 1);
+        assert_eq!(kept[0].planet1, "Natal Saturn");
+    }
+
+    #[test]
+    fn test_filter_caps_to_tightest_orbs() {
+        let aspects = vec![
+            aspect("Sun", "Moon", AspectType::Square, 3.0),
+            aspect("Sun", "Mars", AspectType::Trine, 1.0),
+            aspect("Moon", "Mars", AspectType::Sextile, 2.0),
+        ];
+        let filter = AspectFilter {
+            max_aspects: Some(2),
+            ..Default::default()
+        };
+        let (kept, truncated) = filter.apply(aspects);
+        assert!(truncated);
+        // The widest is dropped and chart order is kept
+        let orbs: Vec<f64> = kept.iter().map(|a| a.orb).collect();
+        assert_eq!(orbs, vec![1.0, 2.0]);
+
+        assert_eq!("quadnovile".parse::<AspectType>(), Ok(AspectType::QuadNovile));
+        assert!("trien".parse::<AspectType>().is_err());
+    }
+
+    #[test]
+    fn test_aspect_strength() {
+        assert_eq!(aspect_strength(0.0, 8.0), 1.0);
+        assert_eq!(aspect_strength(8.0, 8.0), 0.0);
+        assert_eq!(aspect_strength(2.0, 8.0), 0.75);
+        assert!(is_exact(0.05));
+        assert!(!is_exact(EXACT_ORB));
+
+        // Found at its widest orb or exact, whatever the aspect's own orb is
+        let defs = [AspectDef::from(AspectType::Square)];
+        let a = [ChartPoint::new("A", 10.0, 1.0)];
+        let exact = calculate_point_aspects_with_defs(&a, &[ChartPoint::new("B", 100.0, 1.0)], &defs);
+        assert_eq!(exact[0].strength(), 1.0);
+        let edge = 10.0 + 90.0 + defs[0].orb;
+        let widest = calculate_point_aspects_with_defs(&a, &[ChartPoint::new("B", edge, 1.0)], &defs);
+        assert!(widest[0].strength() < 1e-9, "{}", widest[0].strength());
+    }
+
+    #[test]
+    fn test_aspects_sorted_by_strength_or_planets() {
+        let mut wide_trine = aspect("Sun", "Moon", AspectType::Trine, 3.0);
+        wide_trine.orb_allowed = 4.0;
+        let aspects = vec![
+            wide_trine,
+            aspect("Sun", "Mars", AspectType::Square, 2.0),
+            aspect("Moon", "Mars", AspectType::Sextile, 0.5),
+        ];
+        // Strongest first: a 3° trine of 4° is weaker than a 2° square of 5°
+        let (kept, _) = AspectFilter::default().apply(aspects.clone());
+        let order: Vec<&str> = kept.iter().map(|a| a.planet2.as_str()).collect();
+        assert_eq!(order, vec!["Mars", "Mars", "Moon"]);
+        assert_eq!(kept[0].planet1, "Moon");
+
+        let by_planets = AspectFilter {
+            sort: AspectSort::Planets,
+            ..Default::default()
+        };
+        let (kept, _) = by_planets.apply(aspects);
+        let orbs: Vec<f64> = kept.iter().map(|a| a.orb).collect();
+        assert_eq!(orbs, vec![3.0, 2.0, 0.5]);
+
+        assert_eq!("Planets".parse(), Ok(AspectSort::Planets));
+        assert!("orb".parse::<AspectSort>().is_err());
+    }
+
+    #[cfg(feature = "swiss")]
+    #[test]
+    fn test_next_exact_sun_mars_conjunction() {
+        crate::calc::swiss_ephemeris::init_moshier_ephemeris().unwrap();
+        // Mars was conjunct the Sun on 18 November 2023 at about 05:42 UT
+        let jd_start = 2460249.5; // 1 November 2023
+        let exact = next_exact(jd_start, Planet::Sun, Planet::Mars, AspectType::Conjunction, 60.0).unwrap();
+        assert_eq!(exact.len(), 1);
+        let (year, month, day, hour) = crate::calc::utils::julian_to_calendar(exact[0]);
+        assert_eq!((year, month, day), (2023, 11, 18));
+        assert!((hour - 5.7).abs() < 0.5, "hour = {}", hour);
+    }
+
+    #[cfg(feature = "swiss")]
+    #[test]
+    fn test_next_exact_reports_every_pass() {
+        crate::calc::swiss_ephemeris::init_moshier_ephemeris().unwrap();
+        // Retrograde Mercury met Mars on 28 December 2023, then again direct on 27 January 2024
+        let jd_start = 2460249.5;
+        let exact = next_exact(jd_start, Planet::Mercury, Planet::Mars, AspectType::Conjunction, 150.0).unwrap();
+        let days: Vec<_> = exact
+            .iter()
+            .map(|jd| {
+                let (year, month, day, _) = crate::calc::utils::julian_to_calendar(*jd);
+                (year, month, day)
+            })
+            .collect();
+        assert_eq!(days, vec![(2023, 12, 28), (2024, 1, 27)]);
+    }
 }
@@ -0,0 +1,178 @@
+//! Bodies sitting close to a sign boundary or a house cusp, whose sign or
+//! house a small error in the birth time could change.
+//!
+//! Like `house_summary`, this works from named longitudes and the twelve cusps
+//! alone, after the chart has been calculated.
+
+use crate::calc::angles::normalize;
+use crate::calc::aspects::ChartPoint;
+use crate::calc::houses::house_of;
+use crate::calc::rulers::Sign;
+use serde::{Deserialize, Serialize};
+
+/// Orb used for sign boundaries and house cusps when a request doesn't set one
+pub const DEFAULT_BOUNDARY_ORB: f64 = 0.5;
+
+/// Largest orb a request may ask for; wider ones would flag most of a sign
+pub const MAX_BOUNDARY_ORB: f64 = 5.0;
+
+/// Which kind of boundary a body is close to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BoundaryKind {
+    Sign,
+    House,
+}
+
+/// What a body would be placed in on the other side of the boundary
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Adjacent {
+    Sign(Sign),
+    House(u8),
+}
+
+/// A body within the orb of a boundary
+#[derive(Debug, Clone, PartialEq)]
+pub struct BoundaryWarning {
+    pub name: String,
+    pub kind: BoundaryKind,
+    /// Longitude of the sign boundary or house cusp
+    pub boundary: f64,
+    /// Degrees between the body and the boundary
+    pub distance: f64,
+    /// The sign or house across the boundary
+    pub adjacent: Adjacent,
+}
+
+/// Checks a requested orb, `None` meaning `DEFAULT_BOUNDARY_ORB`
+pub fn boundary_orb(orb: Option<f64>, field: &str) -> Result<f64, String> {
+    match orb {
+        None => Ok(DEFAULT_BOUNDARY_ORB),
+        Some(orb) if orb.is_finite() && (0.0..=MAX_BOUNDARY_ORB).contains(&orb) => Ok(orb),
+        Some(orb) => Err(format!("{} must be between 0 and {}°, got {}", field, MAX_BOUNDARY_ORB, orb)),
+    }
+}
+
+/// Lists the points within `sign_orb` of a sign boundary, then those within
+/// `house_orb` of a house cusp when there are cusps.
+///
+/// A point near both sides of a narrow house is listed against the nearer
+/// cusp. An orb of 0 only flags points exactly on a boundary.
+pub fn boundary_warnings(points: &[ChartPoint], cusps: Option<&[f64; 12]>, sign_orb: f64, house_orb: f64) -> Vec<BoundaryWarning> {
+    let mut warnings: Vec<BoundaryWarning> = points
+        .iter()
+        .filter_map(|point| sign_warning(point, sign_orb))
+        .collect();
+    if let Some(cusps) = cusps {
+        warnings.extend(points.iter().filter_map(|point| house_warning(point, cusps, house_orb)));
+    }
+    warnings
+}
+
+fn sign_warning(point: &ChartPoint, orb: f64) -> Option<BoundaryWarning> {
+    let longitude = normalize(point.longitude);
+    let sign = Sign::from_longitude(longitude);
+    let index = Sign::ALL.iter().position(|s| *s == sign).unwrap_or(0);
+    let start = index as f64 * 30.0;
+    let past = longitude - start;
+    let before = start + 30.0 - longitude;
+    let (boundary, distance, adjacent) = if before <= past {
+        (normalize(start + 30.0), before, Sign::ALL[(index + 1) % 12])
+    } else {
+        (start, past, Sign::ALL[(index + 11) % 12])
+    };
+    (distance <= orb).then(|| BoundaryWarning {
+        name: point.name.clone(),
+        kind: BoundaryKind::Sign,
+        boundary,
+        distance,
+        adjacent: Adjacent::Sign(adjacent),
+    })
+}
+
+fn house_warning(point: &ChartPoint, cusps: &[f64; 12], orb: f64) -> Option<BoundaryWarning> {
+    let house = house_of(point.longitude, cusps).house as usize;
+    let start = cusps[house - 1];
+    let end = cusps[house % 12];
+    let past = normalize(point.longitude - start);
+    let before = normalize(end - point.longitude);
+    let (boundary, distance, adjacent) = if before < past {
+        (end, before, house % 12 + 1)
+    } else {
+        (start, past, (house + 10) % 12 + 1)
+    };
+    (distance <= orb).then(|| BoundaryWarning {
+        name: point.name.clone(),
+        kind: BoundaryKind::House,
+        boundary: normalize(boundary),
+        distance,
+        adjacent: Adjacent::House(adjacent as u8),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CUSPS: [f64; 12] = [350.0, 20.0, 50.0, 80.0, 110.0, 140.0, 170.0, 200.0, 230.0, 260.0, 290.0, 320.0];
+
+    #[test]
+    fn test_late_pisces_names_aries() {
+        let venus = [ChartPoint::new("Venus", 359.7, 1.0)];
+        let warnings = boundary_warnings(&venus, None, DEFAULT_BOUNDARY_ORB, DEFAULT_BOUNDARY_ORB);
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].kind, BoundaryKind::Sign);
+        assert_eq!(warnings[0].adjacent, Adjacent::Sign(Sign::Aries));
+        assert_eq!(warnings[0].boundary, 0.0);
+        assert!((warnings[0].distance - 0.3).abs() < 1e-9);
+
+        // Just into Aries looks back to Pisces; mid-sign is quiet
+        let points = [ChartPoint::new("Mars", 0.2, 1.0), ChartPoint::new("Sun", 15.0, 1.0)];
+        let warnings = boundary_warnings(&points, None, 0.5, 0.5);
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].adjacent, Adjacent::Sign(Sign::Pisces));
+        assert!(boundary_warnings(&[ChartPoint::new("Venus", 359.7, 1.0)], None, 0.2, 0.5).is_empty());
+    }
+
+    #[test]
+    fn test_house_cusps_wrap_past_aries() {
+        // House 1 runs from 350° over 0° Aries to 20°
+        let points = [ChartPoint::new("Moon", 349.8, 1.0), ChartPoint::new("Sun", 19.9, 1.0)];
+        let warnings = boundary_warnings(&points, Some(&CUSPS), 0.0, 0.5);
+        let adjacent: Vec<_> = warnings.iter().map(|w| (w.name.as_str(), w.adjacent, w.boundary)).collect();
+        assert_eq!(adjacent, [("Moon", Adjacent::House(1), 350.0), ("Sun", Adjacent::House(2), 20.0)]);
+        assert!(warnings.iter().all(|w| w.kind == BoundaryKind::House));
+
+        // Just past the first cusp looks back to the twelfth house
+        let warnings = boundary_warnings(&[ChartPoint::new("Mars", 350.3, 1.0)], Some(&CUSPS), 0.0, 0.5);
+        assert_eq!(warnings[0].adjacent, Adjacent::House(12));
+    }
+
+    #[cfg(feature = "swiss")]
+    #[test]
+    fn test_just_past_a_placidus_cusp_names_the_previous_house() {
+        use crate::calc::houses::calculate_houses;
+        use crate::calc::swiss_ephemeris;
+        use crate::core::types::HouseSystem;
+
+        swiss_ephemeris::init_moshier_ephemeris().unwrap();
+        let houses = calculate_houses(2451545.0, 51.5, -0.1, HouseSystem::Placidus).unwrap();
+        let cusps = houses.cusp_longitudes().unwrap();
+        // 0.2° past the fifth cusp, with sign boundaries left out
+        let longitude = cusps[4] + 0.2;
+        let warnings = boundary_warnings(&[ChartPoint::new("Jupiter", longitude, 1.0)], Some(&cusps), 0.0, 0.5);
+        let house = warnings.iter().find(|w| w.kind == BoundaryKind::House).unwrap();
+        assert_eq!(house.adjacent, Adjacent::House(4));
+        assert!((house.distance - 0.2).abs() < 1e-9);
+        assert!((house.boundary - normalize(cusps[4])).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_orbs_are_checked() {
+        assert_eq!(boundary_orb(None, "sign_boundary_orb"), Ok(DEFAULT_BOUNDARY_ORB));
+        assert_eq!(boundary_orb(Some(1.0), "sign_boundary_orb"), Ok(1.0));
+        assert!(boundary_orb(Some(-0.1), "sign_boundary_orb").is_err());
+        assert!(boundary_orb(Some(f64::NAN), "house_cusp_orb").is_err());
+        assert!(boundary_orb(Some(30.0), "house_cusp_orb").is_err());
+    }
+}
@@ -0,0 +1,271 @@
+//! House overlays, composite midpoints and placement changes between two charts.
+//!
+//! Everything works from named longitudes, so charts that were calculated
+//! earlier, possibly by another server, can be compared without their birth data.
+//! For the same reason a composite's houses can't come from rerunning a house
+//! system: there's no moment or place to run it for. They are derived from the
+//! two charts' cusps instead, by default with Porphyry from the composite angles.
+
+use crate::calc::angles::midpoint_shortest;
+use crate::calc::aspects::ChartPoint;
+use crate::calc::houses::{house_of, porphyry_from_angles, HousePlacement};
+use crate::calc::rulers::Sign;
+
+/// A point of one chart placed in another chart's houses
+#[derive(Debug, Clone, PartialEq)]
+pub struct HouseOverlay {
+    pub name: String,
+    pub placement: HousePlacement,
+}
+
+/// Places each point in the houses given by another chart's cusps
+pub fn house_overlays(points: &[ChartPoint], cusps: &[f64; 12]) -> Vec<HouseOverlay> {
+    points
+        .iter()
+        .map(|point| HouseOverlay {
+            name: point.name.clone(),
+            placement: house_of(point.longitude, cusps),
+        })
+        .collect()
+}
+
+/// Midpoint of the shorter arc between two longitudes
+pub fn midpoint(longitude1: f64, longitude2: f64) -> f64 {
+    midpoint_shortest(longitude1, longitude2)
+}
+
+/// Composite chart points: for each name found in both charts, the midpoint of
+/// the two longitudes and the mean of the two speeds, in `chart1` order
+pub fn composite_midpoints(chart1: &[ChartPoint], chart2: &[ChartPoint]) -> Vec<ChartPoint> {
+    chart1
+        .iter()
+        .filter_map(|point1| {
+            let point2 = chart2.iter().find(|p| p.name.eq_ignore_ascii_case(&point1.name))?;
+            Some(ChartPoint {
+                name: point1.name.clone(),
+                longitude: midpoint(point1.longitude, point2.longitude),
+                speed: (point1.speed + point2.speed) / 2.0,
+            })
+        })
+        .collect()
+}
+
+/// How a composite chart's house cusps are derived from the two charts' cusps
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CompositeHouses {
+    /// The composite Ascendant and MC, the midpoints of the two charts' first
+    /// and tenth cusps, with the quadrants between them trisected
+    #[default]
+    Porphyry,
+    /// The midpoint of each pair of cusps, keeping the charts' own quadrant
+    /// system (Placidus unless asked otherwise) at the cost of houses that
+    /// needn't pair up across the chart
+    DerivePlacidus,
+}
+
+impl std::str::FromStr for CompositeHouses {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "porphyry" => Ok(CompositeHouses::Porphyry),
+            "derive_placidus" => Ok(CompositeHouses::DerivePlacidus),
+            _ => Err(format!(
+                "Invalid composite houses: {} (expected \"porphyry\" or \"derive_placidus\")",
+                s
+            )),
+        }
+    }
+}
+
+/// House cusps of the composite of two charts, house 1 first
+pub fn composite_cusps(cusps1: &[f64; 12], cusps2: &[f64; 12], method: CompositeHouses) -> [f64; 12] {
+    match method {
+        CompositeHouses::Porphyry => porphyry_from_angles(midpoint(cusps1[0], cusps2[0]), midpoint(cusps1[9], cusps2[9])),
+        CompositeHouses::DerivePlacidus => std::array::from_fn(|i| midpoint(cusps1[i], cusps2[i])),
+    }
+}
+
+/// One side of a placement diff: a chart's points and, when it has 12
+/// houses, its cusps
+#[derive(Debug, Clone, Copy)]
+pub struct PlacedChart<'a> {
+    pub points: &'a [ChartPoint],
+    pub cusps: Option<&'a [f64; 12]>,
+}
+
+/// A value in the first chart and in the second
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Change<T> {
+    pub from: T,
+    pub to: T,
+}
+
+impl<T: PartialEq> Change<T> {
+    pub fn changed(&self) -> bool {
+        self.from != self.to
+    }
+}
+
+/// How one body's placement differs between two charts
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlacementChange {
+    pub name: String,
+    pub sign: Change<Sign>,
+    /// House in the first chart's houses, so a body that moved across them
+    /// changes; `None` when the first chart has no houses
+    pub house: Option<Change<u8>>,
+    /// House in each chart's own houses; `None` unless both have houses
+    pub own_house: Option<Change<u8>>,
+    pub retrograde: Change<bool>,
+}
+
+/// The placements of the bodies found in both of two charts, with how many
+/// of each kind changed
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct PlacementDiff {
+    /// In `chart_a` order
+    pub bodies: Vec<PlacementChange>,
+    pub sign_changes: usize,
+    pub house_changes: usize,
+    pub own_house_changes: usize,
+    pub retrograde_changes: usize,
+}
+
+/// Compares the sign, house and direction of each body found in both charts,
+/// from `chart_a` to `chart_b`. Names match as `composite_midpoints` matches them.
+pub fn placement_diff(chart_a: PlacedChart, chart_b: PlacedChart) -> PlacementDiff {
+    let mut diff = PlacementDiff::default();
+    for a in chart_a.points {
+        let Some(b) = chart_b.points.iter().find(|p| p.name.eq_ignore_ascii_case(&a.name)) else {
+            continue;
+        };
+        let house = |point: &ChartPoint, cusps: &[f64; 12]| house_of(point.longitude, cusps).house;
+        let change = PlacementChange {
+            name: a.name.clone(),
+            sign: Change {
+                from: Sign::from_longitude(a.longitude),
+                to: Sign::from_longitude(b.longitude),
+            },
+            house: chart_a.cusps.map(|cusps| Change {
+                from: house(a, cusps),
+                to: house(b, cusps),
+            }),
+            own_house: chart_a.cusps.zip(chart_b.cusps).map(|(cusps_a, cusps_b)| Change {
+                from: house(a, cusps_a),
+                to: house(b, cusps_b),
+            }),
+            retrograde: Change {
+                from: a.speed < 0.0,
+                to: b.speed < 0.0,
+            },
+        };
+        diff.sign_changes += change.sign.changed() as usize;
+        diff.house_changes += change.house.is_some_and(|h| h.changed()) as usize;
+        diff.own_house_changes += change.own_house.is_some_and(|h| h.changed()) as usize;
+        diff.retrograde_changes += change.retrograde.changed() as usize;
+        diff.bodies.push(change);
+    }
+    diff
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_composite_takes_the_shorter_arc() {
+        assert!((midpoint(350.0, 20.0) - 5.0).abs() < 1e-9);
+        assert!((midpoint(20.0, 350.0) - 5.0).abs() < 1e-9);
+        assert!((midpoint(100.0, 160.0) - 130.0).abs() < 1e-9);
+
+        let composite = composite_midpoints(
+            &[
+                ChartPoint::new("Sun", 350.0, 1.0),
+                ChartPoint::new("Chiron", 10.0, 1.0),
+                ChartPoint::new("Moon", 90.0, 1.0),
+            ],
+            &[ChartPoint::new("Moon", 120.0, 1.0), ChartPoint::new("sun", 20.0, 1.0)],
+        );
+        assert_eq!(composite.len(), 2);
+        assert_eq!((composite[0].name.as_str(), composite[1].name.as_str()), ("Sun", "Moon"));
+        assert!((composite[1].longitude - 105.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_composite_cusps() {
+        // Equal houses from 0° and from 20° Aries, MC on the tenth cusp
+        let cusps1: [f64; 12] = std::array::from_fn(|i| 30.0 * i as f64);
+        let cusps2: [f64; 12] = std::array::from_fn(|i| 20.0 + 30.0 * i as f64);
+        let porphyry = composite_cusps(&cusps1, &cusps2, CompositeHouses::Porphyry);
+        assert_eq!(porphyry, porphyry_from_angles(10.0, 280.0));
+        let derived = composite_cusps(&cusps1, &cusps2, CompositeHouses::DerivePlacidus);
+        assert!(derived.iter().zip(&cusps1).all(|(derived, cusp)| (derived - cusp - 10.0).abs() < 1e-9));
+        assert_eq!("Derive_Placidus".parse(), Ok(CompositeHouses::DerivePlacidus));
+        assert!("koch".parse::<CompositeHouses>().is_err());
+    }
+
+    #[test]
+    fn test_overlays_use_the_other_charts_cusps() {
+        // Equal houses from 15° Aries
+        let cusps: [f64; 12] = std::array::from_fn(|i| 15.0 + 30.0 * i as f64);
+        let points = [ChartPoint::new("Sun", 10.0, 1.0), ChartPoint::new("Moon", 200.0, 1.0)];
+        let overlays = house_overlays(&points, &cusps);
+        assert_eq!(overlays[0].placement.house, 12);
+        assert_eq!(overlays[1].placement.house, 7);
+        assert!((overlays[1].placement.fraction - 5.0 / 30.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_placement_diff_tells_sign_and_house_changes_apart() {
+        // Equal houses from 15° of each sign in the first chart, from 0° Aries in the second
+        let cusps_a: [f64; 12] = std::array::from_fn(|i| 15.0 + 30.0 * i as f64);
+        let cusps_b: [f64; 12] = std::array::from_fn(|i| 30.0 * i as f64);
+        let chart_a = [
+            ChartPoint::new("Sun", 25.0, 1.0),
+            ChartPoint::new("Moon", 40.0, 1.0),
+            ChartPoint::new("Mars", 100.0, 1.0),
+        ];
+        // The Sun crosses into Taurus inside the first house; the Moon stays in
+        // Taurus but crosses the second cusp at 45°; Venus is only in one chart
+        let chart_b = [
+            ChartPoint::new("Sun", 35.0, 1.0),
+            ChartPoint::new("Moon", 50.0, 1.0),
+            ChartPoint::new("Venus", 10.0, 1.0),
+            ChartPoint::new("mars", 100.5, -0.2),
+        ];
+
+        let diff = placement_diff(
+            PlacedChart { points: &chart_a, cusps: Some(&cusps_a) },
+            PlacedChart { points: &chart_b, cusps: Some(&cusps_b) },
+        );
+        let names: Vec<&str> = diff.bodies.iter().map(|body| body.name.as_str()).collect();
+        assert_eq!(names, ["Sun", "Moon", "Mars"]);
+        let (sun, moon, mars) = (&diff.bodies[0], &diff.bodies[1], &diff.bodies[2]);
+
+        assert_eq!(sun.sign, Change { from: Sign::Aries, to: Sign::Taurus });
+        assert_eq!(sun.house, Some(Change { from: 1, to: 1 }));
+        assert!(sun.sign.changed() && !sun.house.unwrap().changed());
+
+        assert_eq!(moon.sign, Change { from: Sign::Taurus, to: Sign::Taurus });
+        assert_eq!(moon.house, Some(Change { from: 1, to: 2 }));
+        // In its own houses the second chart has the Moon in the second house too
+        assert_eq!(moon.own_house, Some(Change { from: 1, to: 2 }));
+        assert_eq!(sun.own_house, Some(Change { from: 1, to: 2 }));
+
+        assert_eq!(mars.retrograde, Change { from: false, to: true });
+        assert!(!mars.sign.changed() && !mars.house.unwrap().changed());
+        assert_eq!(
+            (diff.sign_changes, diff.house_changes, diff.own_house_changes, diff.retrograde_changes),
+            (1, 1, 3, 1)
+        );
+
+        // Without the first chart's houses there's nothing to place either body in
+        let diff = placement_diff(
+            PlacedChart { points: &chart_a, cusps: None },
+            PlacedChart { points: &chart_b, cusps: Some(&cusps_b) },
+        );
+        assert!(diff.bodies.iter().all(|body| body.house.is_none() && body.own_house.is_none()));
+        assert_eq!((diff.sign_changes, diff.house_changes), (1, 0));
+    }
+}
@@ -0,0 +1,111 @@
+//! Numeric compatibility scores from the aspects between two charts.
+//!
+//! Each aspect counts for its strength, 1 when exact falling to 0 at its
+//! widest orb, so a handful of tight contacts outweighs many wide ones. The
+//! strengths are summed by the aspect's nature: trines, sextiles and the
+//! quintile and novile families for harmony; squares, oppositions, semisquares,
+//! sesquisquares and quincunxes for tension; conjunctions on their own, as
+//! they take the nature of the bodies that meet. Semisextiles, septiles and
+//! custom aspects count only towards the total.
+
+use crate::calc::aspects::{AspectRecord, AspectType};
+
+/// How an aspect is scored
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AspectNature {
+    Harmonious,
+    Tense,
+    Conjunction,
+    Neutral,
+}
+
+impl AspectNature {
+    /// The nature of a built-in aspect; custom aspects (`None`) are neutral
+    pub fn of(aspect_type: Option<AspectType>) -> AspectNature {
+        match aspect_type {
+            Some(AspectType::Conjunction) => AspectNature::Conjunction,
+            Some(
+                AspectType::Trine
+                | AspectType::Sextile
+                | AspectType::Quintile
+                | AspectType::BiQuintile
+                | AspectType::Novile
+                | AspectType::BiNovile
+                | AspectType::QuadNovile,
+            ) => AspectNature::Harmonious,
+            Some(
+                AspectType::Square
+                | AspectType::Opposition
+                | AspectType::SemiSquare
+                | AspectType::Sesquisquare
+                | AspectType::Quincunx,
+            ) => AspectNature::Tense,
+            _ => AspectNature::Neutral,
+        }
+    }
+}
+
+/// Summed aspect strengths between two charts
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct CompatibilityScores {
+    pub aspect_count: usize,
+    pub harmony: f64,
+    pub tension: f64,
+    pub conjunction: f64,
+    /// Every aspect's strength, the neutral ones included
+    pub total: f64,
+}
+
+/// Scores a list of aspects between two charts
+pub fn compatibility_scores<A: AspectRecord>(aspects: &[A]) -> CompatibilityScores {
+    let mut scores = CompatibilityScores {
+        aspect_count: aspects.len(),
+        ..Default::default()
+    };
+    for aspect in aspects {
+        let strength = aspect.strength();
+        match AspectNature::of(aspect.aspect_type()) {
+            AspectNature::Harmonious => scores.harmony += strength,
+            AspectNature::Tense => scores.tension += strength,
+            AspectNature::Conjunction => scores.conjunction += strength,
+            AspectNature::Neutral => {}
+        }
+        scores.total += strength;
+    }
+    scores
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::calc::aspects::SynastryAspect;
+
+    fn aspect(aspect_type: Option<AspectType>, orb: f64) -> SynastryAspect {
+        SynastryAspect {
+            person1_planet: "Sun".to_string(),
+            person2_planet: "Moon".to_string(),
+            aspect_type,
+            name: aspect_type.map_or("Decile".to_string(), |t| t.to_string()),
+            orb,
+            orb_allowed: 8.0,
+        }
+    }
+
+    #[test]
+    fn test_scores_sum_strengths_by_nature() {
+        let aspects = [
+            aspect(Some(AspectType::Trine), 0.0),
+            aspect(Some(AspectType::Square), 4.0),
+            aspect(Some(AspectType::Conjunction), 2.0),
+            aspect(Some(AspectType::Septile), 0.0),
+            aspect(None, 6.0),
+        ];
+        let scores = compatibility_scores(&aspects);
+        assert_eq!(scores.aspect_count, 5);
+        assert!((scores.harmony - 1.0).abs() < 1e-9);
+        assert!((scores.tension - 0.5).abs() < 1e-9);
+        assert!((scores.conjunction - 0.75).abs() < 1e-9);
+        assert!((scores.total - 3.5).abs() < 1e-9);
+        assert_eq!(compatibility_scores::<SynastryAspect>(&[]), CompatibilityScores::default());
+    }
+}
@@ -1,4 +1,5 @@
-use crate::calc::utils::{degrees_to_radians, radians_to_degrees};
+use crate::calc::angles::{calculate_obliquity, normalize};
+use crate::calc::utils::{degrees_to_radians, julian_centuries, radians_to_degrees};
 use crate::core::AstrologError;
 
 /// Convert ecliptic coordinates to equatorial coordinates
@@ -8,11 +9,7 @@ pub fn ecliptic_to_equatorial(
     latitude: f64,
     obliquity: f64,
 ) -> Result<(f64, f64), AstrologError> {
-    // Normalize longitude to 0-360 range
-    let mut _longitude = longitude % 360.0;
-    if _longitude < 0.0 {
-        _longitude += 360.0;
-    }
+    let _longitude = normalize(longitude);
 
     // Handle edge cases for latitude
     if latitude.abs() >= 90.0 {
@@ -37,10 +34,7 @@ pub fn ecliptic_to_equatorial(
     let ra_deg = radians_to_degrees(ra);
     let dec_deg = radians_to_degrees(dec);
 
-    // Normalize right ascension to 0-360 range
-    let ra_normalized = if ra_deg < 0.0 { ra_deg + 360.0 } else { ra_deg };
-
-    Ok((ra_normalized, dec_deg))
+    Ok((normalize(ra_deg), dec_deg))
 }
 
 /// Convert equatorial coordinates to ecliptic coordinates
@@ -101,21 +95,169 @@ pub fn equatorial_to_horizontal(
     (az.to_degrees(), alt.to_degrees())
 }
 
-/// Calculate the sidereal time for a given Julian date and longitude
+/// Calculate the local apparent sidereal time for a given Julian date and longitude.
+///
+/// Equivalent to `local_sidereal_time`; kept for existing callers.
 #[allow(dead_code)]
 pub fn calculate_sidereal_time(julian_date: f64, longitude: f64) -> f64 {
-    let t = (julian_date - 2451545.0) / 36525.0;
+    local_sidereal_time(julian_date, longitude)
+}
+
+/// Calculates nutation in longitude and obliquity.
+///
+/// Uses the abbreviated IAU 1980 series (Meeus, *Astronomical Algorithms*, ch. 22),
+/// good to about 0.5" in longitude and 0.1" in obliquity.
+///
+/// # Arguments
+///
+/// * `julian_date` - The Julian date
+///
+/// # Returns
+///
+/// A tuple of (nutation in longitude, nutation in obliquity) in degrees
+pub fn nutation(julian_date: f64) -> (f64, f64) {
+    let t = julian_centuries(julian_date);
+
+    // Longitude of the Moon's ascending node, mean longitudes of the Sun and Moon
+    let omega = degrees_to_radians(
+        125.04452 - 1934.136261 * t + 0.0020708 * t * t + t * t * t / 450000.0,
+    );
+    let l_sun = degrees_to_radians(280.4665 + 36000.7698 * t);
+    let l_moon = degrees_to_radians(218.3165 + 481267.8813 * t);
+
+    let delta_psi = -17.20 * omega.sin() - 1.32 * (2.0 * l_sun).sin() - 0.23 * (2.0 * l_moon).sin()
+        + 0.21 * (2.0 * omega).sin();
+    let delta_epsilon = 9.20 * omega.cos() + 0.57 * (2.0 * l_sun).cos() + 0.10 * (2.0 * l_moon).cos()
+        - 0.09 * (2.0 * omega).cos();
+
+    (delta_psi / 3600.0, delta_epsilon / 3600.0)
+}
 
-    // Calculate Greenwich Mean Sidereal Time
+/// Calculates Greenwich apparent sidereal time.
+///
+/// Greenwich mean sidereal time (Meeus 12.4) plus the equation of the equinoxes,
+/// the nutation in longitude projected onto the equator.
+///
+/// # Arguments
+///
+/// * `julian_date` - The Julian date (UT)
+///
+/// # Returns
+///
+/// The apparent sidereal time at Greenwich in degrees (0-360)
+pub fn greenwich_sidereal_time(julian_date: f64) -> f64 {
+    let t = julian_centuries(julian_date);
     let gmst = 280.46061837
         + 360.98564736629 * (julian_date - 2451545.0)
         + t * t * (0.000387933 - t / 38710000.0);
 
-    // Add longitude to get Local Sidereal Time
-    let lst = gmst + longitude;
+    let (delta_psi, delta_epsilon) = nutation(julian_date);
+    let true_obliquity = calculate_obliquity(t) + delta_epsilon;
+    let equation_of_equinoxes = delta_psi * degrees_to_radians(true_obliquity).cos();
+
+    normalize(gmst + equation_of_equinoxes)
+}
+
+/// Calculates local apparent sidereal time, which is also the ARMC (right ascension
+/// of the Midheaven) used by house calculations.
+///
+/// # Arguments
+///
+/// * `julian_date` - The Julian date (UT)
+/// * `longitude` - The geographical longitude in degrees, east positive
+///
+/// # Returns
+///
+/// The local sidereal time in degrees (0-360); divide by 15 for hours
+///
+/// # Examples
+///
+/// ```
+/// use astrolog_rs::calc::coordinates::local_sidereal_time;
+///
+/// let lst = local_sidereal_time(2451545.0, -74.0);
+/// assert!((0.0..360.0).contains(&lst));
+/// ```
+pub fn local_sidereal_time(julian_date: f64, longitude: f64) -> f64 {
+    normalize(greenwich_sidereal_time(julian_date) + longitude)
+}
+
+/// The true obliquity of the ecliptic at a Julian date: the mean obliquity
+/// plus the nutation in obliquity, in degrees
+pub fn true_obliquity(julian_date: f64) -> f64 {
+    let (_, delta_epsilon) = nutation(julian_date);
+    calculate_obliquity(julian_centuries(julian_date)) + delta_epsilon
+}
+
+/// A place on the Earth's surface that topocentric positions are seen from
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Observer {
+    /// Geographical longitude in degrees, east positive
+    pub longitude: f64,
+    pub latitude: f64,
+    /// Metres above sea level
+    pub altitude: f64,
+}
+
+/// Right ascension and declination, both in degrees
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EquatorialPosition {
+    pub right_ascension: f64,
+    pub declination: f64,
+}
+
+impl EquatorialPosition {
+    /// Right ascension in hours, 0-24
+    pub fn right_ascension_hours(&self) -> f64 {
+        self.right_ascension / 15.0
+    }
+}
 
-    // Normalize to 0-360 degrees
-    lst % 360.0
+/// The equatorial position of an ecliptic longitude and latitude of date, with
+/// the true obliquity at `julian_date`. Anything that needs a body's
+/// declination, such as parallels or rising times, should start here.
+pub fn equatorial_of_date(julian_date: f64, longitude: f64, latitude: f64) -> EquatorialPosition {
+    // The latitude is never ±90°, which is the only input the conversion refuses
+    let (right_ascension, declination) =
+        ecliptic_to_equatorial(longitude, latitude, true_obliquity(julian_date)).unwrap_or((longitude, latitude));
+    EquatorialPosition { right_ascension, declination }
+}
+
+/// A right ascension in degrees as hours, minutes and seconds, e.g. "05h 34m 32s"
+pub fn format_right_ascension(right_ascension: f64) -> String {
+    let seconds = (normalize(right_ascension) / 15.0 * 3600.0).round() as u32 % (24 * 3600);
+    format!("{:02}h {:02}m {:02}s", seconds / 3600, seconds / 60 % 60, seconds % 60)
+}
+
+/// Which coordinates a response gives each body
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum CoordinateSystem {
+    /// Longitude and latitude on the ecliptic only
+    #[default]
+    Ecliptic,
+    /// Right ascension and declination alongside them
+    Equatorial,
+    Both,
+}
+
+impl CoordinateSystem {
+    /// Whether bodies are given their right ascension and declination
+    pub fn includes_equatorial(self) -> bool {
+        self != CoordinateSystem::Ecliptic
+    }
+}
+
+impl std::str::FromStr for CoordinateSystem {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "ecliptic" => Ok(CoordinateSystem::Ecliptic),
+            "equatorial" => Ok(CoordinateSystem::Equatorial),
+            "both" => Ok(CoordinateSystem::Both),
+            _ => Err(format!("Invalid coordinates: {} (expected \"ecliptic\", \"equatorial\" or \"both\")", s)),
+        }
+    }
 }
 
 /// Calculate the Julian date for a given date and time
@@ -151,11 +293,7 @@ pub fn calculate_julian_date(
 
 #[allow(dead_code)]
 pub fn normalize_coordinates(longitude: f64, latitude: f64) -> (f64, f64) {
-    // Normalize longitude to 0-360 range
-    let mut normalized_longitude = longitude % 360.0;
-    if normalized_longitude < 0.0 {
-        normalized_longitude += 360.0;
-    }
+    let normalized_longitude = normalize(longitude);
 
     // Handle edge cases for latitude
     let normalized_latitude = if latitude.abs() >= 90.0 {
@@ -226,6 +364,23 @@ mod tests {
 
     const OBLIQUITY: f64 = 23.4367; // Current obliquity of the ecliptic
 
+    #[test]
+    fn test_equatorial_of_date() {
+        // Meeus example 22.a: 1987 April 10, true obliquity 23°26'36.850"
+        let jd = 2446895.5;
+        assert!((true_obliquity(jd) - 23.443569).abs() < 1e-4, "{}", true_obliquity(jd));
+        // Pollux (Meeus example 13.a), converted with the J2000 obliquity there
+        // and with the 0.004° larger obliquity of date here
+        let pollux = equatorial_of_date(jd, 113.215630, 6.684170);
+        let (ra, dec) = ecliptic_to_equatorial(113.215630, 6.684170, 23.4392911).unwrap();
+        assert!((ra - 116.328942).abs() < 1e-5 && (dec - 28.026183).abs() < 1e-5);
+        assert!((pollux.right_ascension - ra).abs() < 0.01 && (pollux.declination - dec).abs() < 0.01);
+        assert_eq!(format_right_ascension(pollux.right_ascension), "07h 45m 19s");
+        assert_eq!(format_right_ascension(359.9999), "00h 00m 00s");
+        assert_eq!("Both".parse::<CoordinateSystem>(), Ok(CoordinateSystem::Both));
+        assert!("galactic".parse::<CoordinateSystem>().is_err());
+    }
+
     #[test]
     fn test_ecliptic_to_equatorial_0_0() {
         let (ra, dec) = ecliptic_to_equatorial(0.0, 0.0, OBLIQUITY).unwrap();
@@ -332,4 +487,36 @@ mod tests {
         assert_relative_eq!(y, -0.6123724356957945, epsilon = 1e-10);
         assert_relative_eq!(z, -0.5, epsilon = 1e-10);
     }
+
+    #[test]
+    fn test_apparent_sidereal_time_meeus_example() {
+        // Meeus example 12.a: 1987 April 10, 0h UT
+        let jd = 2446895.5;
+        let (delta_psi, delta_epsilon) = nutation(jd);
+        assert_relative_eq!(delta_psi * 3600.0, -3.788, epsilon = 0.5);
+        assert_relative_eq!(delta_epsilon * 3600.0, 9.443, epsilon = 0.1);
+        // 13h10m46.1351s
+        let expected = (13.0 + 10.0 / 60.0 + 46.1351 / 3600.0) * 15.0;
+        assert_relative_eq!(greenwich_sidereal_time(jd), expected, epsilon = 1e-4);
+        assert_relative_eq!(local_sidereal_time(jd, -200.0), normalize(expected - 200.0), epsilon = 1e-4);
+    }
+
+    #[cfg(feature = "swiss")]
+    #[test]
+    fn test_local_sidereal_time_matches_swiss_armc() {
+        use crate::calc::angles::signed_delta;
+        use crate::calc::swiss_ephemeris::calculate_house_cusps_swiss;
+        use crate::core::types::HouseSystem;
+
+        for year in (1900..=2100).step_by(25) {
+            let jd = 2415020.5 + (year - 1900) as f64 * 365.25 + 0.37;
+            for longitude in [-179.5, -122.4, -74.0, 0.0, 2.35, 77.2, 139.7, 180.0] {
+                let (_, ascmc) =
+                    calculate_house_cusps_swiss(jd, 40.0, longitude, HouseSystem::Equal).unwrap();
+                let lst = local_sidereal_time(jd, longitude);
+                let diff = signed_delta(lst, ascmc[2]);
+                assert!(diff.abs() < 0.01, "{} {}: {} vs {}", year, longitude, lst, ascmc[2]);
+            }
+        }
+    }
 }
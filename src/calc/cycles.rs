@@ -0,0 +1,220 @@
+use crate::calc::angles::signed_delta;
+use crate::calc::aspects::{next_exact_with_source, refine_exact, AspectType};
+use crate::calc::planets::{can_retrograde, default_source, max_daily_motion, planet_longitude_at, Planet};
+use crate::core::types::EphemerisSource;
+use serde::{Deserialize, Serialize};
+
+/// Half-width of the window used to sample a body's speed, in days
+const SPEED_WINDOW: f64 = 0.05;
+
+/// Bodies with a synodic cycle: everything the ephemeris tracks as a moving body
+const CYCLE_BODIES: [Planet; 11] = [
+    Planet::Sun,
+    Planet::Moon,
+    Planet::Mercury,
+    Planet::Venus,
+    Planet::Mars,
+    Planet::Jupiter,
+    Planet::Saturn,
+    Planet::Uranus,
+    Planet::Neptune,
+    Planet::Pluto,
+    Planet::Chiron,
+];
+
+/// A turning point in the synodic cycle of two bodies
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CycleEventKind {
+    /// The two bodies at the same longitude
+    Conjunction,
+    /// Mercury or Venus passing between the Earth and the Sun, retrograde
+    InferiorConjunction,
+    /// Mercury or Venus passing behind the Sun, direct
+    SuperiorConjunction,
+    /// The two bodies 180° apart
+    Opposition,
+    /// One of the bodies turning retrograde
+    StationRetrograde,
+    /// One of the bodies turning direct
+    StationDirect,
+}
+
+/// One event of a synodic cycle
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CycleEvent {
+    pub kind: CycleEventKind,
+    /// Julian date (UT) of the event
+    pub jd: f64,
+    /// The stationing body for stations, otherwise the first body of the pair
+    pub planet: Planet,
+    /// Longitude of `planet` at the event
+    pub longitude: f64,
+}
+
+/// Whether a body moves enough to have a synodic cycle with another
+pub fn has_synodic_cycle(planet: Planet) -> bool {
+    CYCLE_BODIES.contains(&planet)
+}
+
+fn is_inner(planet: Planet) -> bool {
+    matches!(planet, Planet::Mercury | Planet::Venus)
+}
+
+/// Apparent daily motion of a body, from a central difference
+pub(crate) fn speed_at(planet: Planet, jd: f64, source: EphemerisSource) -> Result<f64, String> {
+    let before = planet_longitude_at(planet, jd - SPEED_WINDOW, source)?;
+    let after = planet_longitude_at(planet, jd + SPEED_WINDOW, source)?;
+    Ok(signed_delta(after, before) / (2.0 * SPEED_WINDOW))
+}
+
+/// Finds the events of the synodic cycle between two bodies.
+///
+/// Reports their conjunctions and oppositions, and the stations of either
+/// body. The Sun never gets far from Mercury or Venus, so that pair only has
+/// conjunctions, told apart as inferior (retrograde) or superior (direct).
+///
+/// # Arguments
+///
+/// * `body1`, `body2` - The pair, e.g. Jupiter and Saturn
+/// * `jd_from`, `jd_to` - Julian dates (UT) bounding the search
+///
+/// # Returns
+///
+/// The events in the range, earliest first
+pub fn synodic_events(body1: Planet, body2: Planet, jd_from: f64, jd_to: f64) -> Result<Vec<CycleEvent>, String> {
+    synodic_events_with_source(body1, body2, jd_from, jd_to, default_source())
+}
+
+/// Finds the events of the synodic cycle between two bodies from a specific
+/// ephemeris source. See `synodic_events`.
+pub fn synodic_events_with_source(
+    body1: Planet,
+    body2: Planet,
+    jd_from: f64,
+    jd_to: f64,
+    source: EphemerisSource,
+) -> Result<Vec<CycleEvent>, String> {
+    for body in [body1, body2] {
+        if !has_synodic_cycle(body) {
+            return Err(format!("{:?} has no synodic cycle", body));
+        }
+    }
+    if body1 == body2 {
+        return Err("A synodic cycle needs two different bodies".to_string());
+    }
+    if jd_to <= jd_from {
+        return Err("The end of the range must be after its start".to_string());
+    }
+    let horizon = jd_to - jd_from;
+    let sun_and_inner = matches!(
+        (body1, body2),
+        (Planet::Sun, other) | (other, Planet::Sun) if is_inner(other)
+    );
+
+    let mut events = Vec::new();
+    for jd in next_exact_with_source(jd_from, body1, body2, AspectType::Conjunction, horizon, source)? {
+        let kind = if sun_and_inner {
+            let inner = if body1 == Planet::Sun { body2 } else { body1 };
+            if speed_at(inner, jd, source)? < 0.0 {
+                CycleEventKind::InferiorConjunction
+            } else {
+                CycleEventKind::SuperiorConjunction
+            }
+        } else {
+            CycleEventKind::Conjunction
+        };
+        events.push(CycleEvent {
+            kind,
+            jd,
+            planet: body1,
+            longitude: planet_longitude_at(body1, jd, source)?,
+        });
+    }
+    // Mercury and Venus are never more than 47° from the Sun
+    if !sun_and_inner {
+        for jd in next_exact_with_source(jd_from, body1, body2, AspectType::Opposition, horizon, source)? {
+            events.push(CycleEvent {
+                kind: CycleEventKind::Opposition,
+                jd,
+                planet: body1,
+                longitude: planet_longitude_at(body1, jd, source)?,
+            });
+        }
+    }
+    for body in [body1, body2] {
+        if can_retrograde(body) {
+            events.extend(stations(body, jd_from, jd_to, source)?);
+        }
+    }
+
+    events.sort_by(|a, b| a.jd.total_cmp(&b.jd));
+    Ok(events)
+}
+
+/// Finds the times a body turns retrograde or direct
+fn stations(planet: Planet, jd_from: f64, jd_to: f64, source: EphemerisSource) -> Result<Vec<CycleEvent>, String> {
+    // Stations are weeks apart even for Mercury, so a day or two between samples is plenty
+    let step_limit = (2.0 / max_daily_motion(planet)).clamp(0.5, 5.0);
+    let steps = ((jd_to - jd_from) / step_limit).ceil().max(1.0) as usize;
+    let step = (jd_to - jd_from) / steps as f64;
+
+    let mut events = Vec::new();
+    let mut previous = (jd_from, speed_at(planet, jd_from, source)?);
+    for k in 1..=steps {
+        let jd = jd_from + k as f64 * step;
+        let speed = speed_at(planet, jd, source)?;
+        let (jd0, speed0) = previous;
+        if speed0 != 0.0 && speed0.signum() != speed.signum() {
+            let station = refine_exact(jd0, speed0, jd, speed, |jd| speed_at(planet, jd, source))?;
+            events.push(CycleEvent {
+                kind: if speed0 > 0.0 {
+                    CycleEventKind::StationRetrograde
+                } else {
+                    CycleEventKind::StationDirect
+                },
+                jd: station,
+                planet,
+                longitude: planet_longitude_at(planet, station, source)?,
+            });
+        }
+        previous = (jd, speed);
+    }
+    Ok(events)
+}
+
+#[cfg(all(test, feature = "swiss"))]
+mod tests {
+    use super::*;
+    use crate::calc::swiss_ephemeris;
+    use crate::calc::utils::julian_to_calendar;
+
+    #[test]
+    fn test_jupiter_saturn_great_conjunction_2020() {
+        swiss_ephemeris::init_moshier_ephemeris().unwrap();
+        // 1 December 2020 to 1 January 2021
+        let events = synodic_events(Planet::Jupiter, Planet::Saturn, 2459184.5, 2459215.5).unwrap();
+        let conjunctions: Vec<_> = events
+            .iter()
+            .filter(|e| e.kind == CycleEventKind::Conjunction)
+            .collect();
+        assert_eq!(conjunctions.len(), 1);
+        let (year, month, day, _) = julian_to_calendar(conjunctions[0].jd);
+        assert_eq!((year, month, day), (2020, 12, 21));
+        // 0°29' Aquarius
+        assert!((conjunctions[0].longitude - (300.0 + 29.0 / 60.0)).abs() < 0.05);
+    }
+
+    #[test]
+    fn test_sun_mercury_has_only_conjunctions_and_stations() {
+        swiss_ephemeris::init_moshier_ephemeris().unwrap();
+        // 2025: Mercury has three retrograde periods, so three inferior conjunctions
+        let events = synodic_events(Planet::Sun, Planet::Mercury, 2460676.5, 2461041.5).unwrap();
+        let count = |kind| events.iter().filter(|e| e.kind == kind).count();
+        assert_eq!(count(CycleEventKind::Opposition), 0);
+        assert_eq!(count(CycleEventKind::Conjunction), 0);
+        assert_eq!(count(CycleEventKind::InferiorConjunction), 3);
+        assert_eq!(count(CycleEventKind::StationRetrograde), 3);
+        assert_eq!(count(CycleEventKind::StationDirect), 3);
+    }
+}
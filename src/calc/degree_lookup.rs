@@ -0,0 +1,161 @@
+//! Everything a chart has at one degree of the zodiac.
+//!
+//! Given a longitude, `degree_lookup` gives its sign, decan and term rulers,
+//! the house it falls in, the chart's points within `NEARBY_ORB` of it and the
+//! midpoints of pairs of points within `MIDPOINT_ORB`. Like the house overlays
+//! in `comparison` it works from named longitudes and cusps alone, so a chart
+//! returned earlier can be looked up without recalculating it.
+
+use crate::calc::angles::{normalize, shortest_distance, signed_delta};
+use crate::calc::aspects::ChartPoint;
+use crate::calc::comparison::midpoint;
+use crate::calc::houses::{house_of, HousePlacement};
+use crate::calc::planets::Planet;
+use crate::calc::rulers::{sign_ruler, Sign};
+use crate::calc::subdivisions::{decan, term, Decan, Term};
+
+/// Degrees either side of the longitude a point must be within to be reported
+pub const NEARBY_ORB: f64 = 5.0;
+
+/// Degrees either side of the longitude a midpoint must be within to be reported
+pub const MIDPOINT_ORB: f64 = 1.0;
+
+/// A chart point near the looked-up degree
+#[derive(Debug, Clone, PartialEq)]
+pub struct NearbyPoint {
+    pub name: String,
+    pub longitude: f64,
+    /// Degrees from the looked-up longitude, positive when the point is later in the zodiac
+    pub distance: f64,
+}
+
+/// The midpoint of two chart points near the looked-up degree
+#[derive(Debug, Clone, PartialEq)]
+pub struct NearbyMidpoint {
+    pub point1: String,
+    pub point2: String,
+    pub longitude: f64,
+    pub orb: f64,
+}
+
+/// A degree of the zodiac and what a chart has there
+#[derive(Debug, Clone, PartialEq)]
+pub struct DegreeLookup {
+    pub longitude: f64,
+    pub sign: Sign,
+    pub sign_ruler: Planet,
+    /// The zodiacal degree 1-360, counting 0°00'-0°59' Aries as the first
+    pub degree: u16,
+    pub decan: Decan,
+    pub term: Term,
+    /// `None` when the chart has no houses
+    pub house: Option<HousePlacement>,
+    /// Closest first
+    pub nearby: Vec<NearbyPoint>,
+    /// Closest first
+    pub midpoints: Vec<NearbyMidpoint>,
+}
+
+/// Looks up `longitude` in a chart given by its points and, optionally, its cusps
+pub fn degree_lookup(longitude: f64, points: &[ChartPoint], cusps: Option<&[f64; 12]>, modern_rulers: bool) -> DegreeLookup {
+    let longitude = normalize(longitude);
+    let sign = Sign::from_longitude(longitude);
+
+    let mut nearby: Vec<NearbyPoint> = points
+        .iter()
+        .map(|point| NearbyPoint {
+            name: point.name.clone(),
+            longitude: normalize(point.longitude),
+            distance: signed_delta(point.longitude, longitude),
+        })
+        .filter(|point| point.distance.abs() <= NEARBY_ORB)
+        .collect();
+    nearby.sort_by(|a, b| a.distance.abs().total_cmp(&b.distance.abs()));
+
+    let mut midpoints = Vec::new();
+    for (i, point1) in points.iter().enumerate() {
+        for point2 in &points[i + 1..] {
+            let at = normalize(midpoint(point1.longitude, point2.longitude));
+            let orb = shortest_distance(at, longitude);
+            if orb <= MIDPOINT_ORB {
+                midpoints.push(NearbyMidpoint {
+                    point1: point1.name.clone(),
+                    point2: point2.name.clone(),
+                    longitude: at,
+                    orb,
+                });
+            }
+        }
+    }
+    midpoints.sort_by(|a, b| a.orb.total_cmp(&b.orb));
+
+    DegreeLookup {
+        longitude,
+        sign,
+        sign_ruler: sign_ruler(sign, modern_rulers),
+        degree: (longitude.floor() as u16).min(359) + 1,
+        decan: decan(longitude),
+        term: term(longitude),
+        house: cusps.map(|cusps| house_of(longitude, cusps)),
+        nearby,
+        midpoints,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CUSPS: [f64; 12] = [15.0, 45.0, 75.0, 105.0, 135.0, 165.0, 195.0, 225.0, 255.0, 285.0, 315.0, 345.0];
+
+    #[test]
+    fn test_degree_on_a_house_cusp() {
+        let points = [
+            ChartPoint::new("Sun", 103.0, 1.0),
+            ChartPoint::new("Moon", 108.5, 1.0),
+            ChartPoint::new("Mars", 45.0, 1.0),
+            ChartPoint::new("Venus", 165.0, 1.0),
+        ];
+        let lookup = degree_lookup(105.0, &points, Some(&CUSPS), false);
+
+        assert_eq!(lookup.sign, Sign::Cancer);
+        assert_eq!(lookup.sign_ruler, Planet::Moon);
+        assert_eq!(lookup.degree, 106);
+        assert_eq!((lookup.decan.index, lookup.decan.ruler), (2, Planet::Mercury));
+        assert_eq!((lookup.term.ruler, lookup.term.start, lookup.term.end), (Planet::Mercury, 103.0, 109.0));
+        // A cusp begins its house
+        let house = lookup.house.unwrap();
+        assert_eq!(house.house, 4);
+        assert!(house.fraction.abs() < 1e-9);
+
+        let nearby: Vec<(&str, f64)> = lookup.nearby.iter().map(|p| (p.name.as_str(), p.distance)).collect();
+        assert_eq!(nearby, [("Sun", -2.0), ("Moon", 3.5)]);
+        // Mars and Venus are 120° apart and meet at exactly 105°
+        assert_eq!(lookup.midpoints.len(), 2);
+        assert_eq!((lookup.midpoints[0].point1.as_str(), lookup.midpoints[0].point2.as_str()), ("Mars", "Venus"));
+        assert!(lookup.midpoints[0].orb < 1e-9);
+        assert_eq!((lookup.midpoints[1].point1.as_str(), lookup.midpoints[1].point2.as_str()), ("Sun", "Moon"));
+    }
+
+    #[test]
+    fn test_degree_in_an_empty_house() {
+        let points = [
+            ChartPoint::new("Sun", 103.0, 1.0),
+            ChartPoint::new("Moon", 200.0, 1.0),
+            ChartPoint::new("Saturn", 350.0, 1.0),
+        ];
+        let lookup = degree_lookup(240.0, &points, Some(&CUSPS), true);
+
+        assert_eq!(lookup.sign, Sign::Sagittarius);
+        assert_eq!(lookup.sign_ruler, Planet::Jupiter);
+        assert_eq!(lookup.degree, 241);
+        let house = lookup.house.unwrap();
+        assert_eq!(house.house, 8);
+        assert!((house.fraction - 0.5).abs() < 1e-9);
+        assert!(lookup.nearby.is_empty());
+        assert!(lookup.midpoints.is_empty());
+
+        assert_eq!(degree_lookup(240.0, &points, None, false).house, None);
+        assert_eq!(degree_lookup(-0.25, &points, None, false).degree, 360);
+    }
+}
@@ -0,0 +1,147 @@
+//! Eclipses and the natal points on their degrees.
+//!
+//! An eclipse is a New or Full Moon close enough to a lunar node that the Moon
+//! is within `ECLIPSE_LATITUDE_LIMIT` of the ecliptic: a solar eclipse at New
+//! Moon, a lunar one at Full Moon. The limit sits between the latitudes where
+//! an eclipse is certain and where one is impossible, so the faintest partial
+//! and penumbral eclipses, which depend on the Moon's distance, can be missed
+//! or added. The eclipse's degree is the Moon's longitude.
+
+use crate::calc::angles::signed_delta;
+use crate::calc::cycles::{synodic_events_with_source, CycleEventKind};
+use crate::calc::ephemeris::default_ephemeris;
+use crate::calc::planets::Planet;
+use crate::core::types::EphemerisSource;
+use serde::{Deserialize, Serialize};
+
+/// Largest latitude of the Moon at a syzygy that makes an eclipse, in degrees
+pub const ECLIPSE_LATITUDE_LIMIT: f64 = 1.5;
+
+/// Degrees from an eclipse's degree a natal point is reported within
+pub const ECLIPSE_POINT_ORB: f64 = 1.0;
+
+/// Months before the end of a search that eclipse points are taken from
+pub const DEFAULT_ECLIPSE_LOOKBACK_MONTHS: u32 = 18;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EclipseKind {
+    /// At New Moon
+    Solar,
+    /// At Full Moon
+    Lunar,
+}
+
+impl std::fmt::Display for EclipseKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            EclipseKind::Solar => "Solar",
+            EclipseKind::Lunar => "Lunar",
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Eclipse {
+    pub kind: EclipseKind,
+    /// Julian date (UT) of the New or Full Moon
+    pub jd: f64,
+    /// The Moon's longitude at that moment
+    pub longitude: f64,
+    /// The Moon's latitude at that moment, smaller for more central eclipses
+    pub moon_latitude: f64,
+}
+
+/// A natal point on an eclipse's degree
+#[derive(Debug, Clone, PartialEq)]
+pub struct EclipseContact {
+    pub eclipse: Eclipse,
+    /// Name of the natal point
+    pub natal: String,
+    /// Degrees from the eclipse's degree to the natal point, signed
+    pub orb: f64,
+}
+
+/// The solar and lunar eclipses between `jd_from` and `jd_to`, earliest first
+pub fn eclipses_between(jd_from: f64, jd_to: f64, source: EphemerisSource) -> Result<Vec<Eclipse>, String> {
+    let ephemeris = default_ephemeris();
+    let mut eclipses = Vec::new();
+    for event in synodic_events_with_source(Planet::Moon, Planet::Sun, jd_from, jd_to, source)? {
+        let kind = match event.kind {
+            CycleEventKind::Conjunction => EclipseKind::Solar,
+            CycleEventKind::Opposition => EclipseKind::Lunar,
+            _ => continue,
+        };
+        let (_, moon_latitude, _) = ephemeris.position(event.jd, Planet::Moon, source)?;
+        if moon_latitude.abs() < ECLIPSE_LATITUDE_LIMIT {
+            eclipses.push(Eclipse {
+                kind,
+                jd: event.jd,
+                longitude: event.longitude,
+                moon_latitude,
+            });
+        }
+    }
+    Ok(eclipses)
+}
+
+/// The `natal` points (name and longitude) within `orb` of each eclipse's
+/// degree, in eclipse order
+pub fn eclipse_contacts(natal: &[(String, f64)], eclipses: &[Eclipse], orb: f64) -> Vec<EclipseContact> {
+    eclipses
+        .iter()
+        .flat_map(|eclipse| {
+            natal.iter().filter_map(move |(name, longitude)| {
+                let distance = signed_delta(*longitude, eclipse.longitude);
+                (distance.abs() <= orb).then(|| EclipseContact {
+                    eclipse: *eclipse,
+                    natal: name.clone(),
+                    orb: distance,
+                })
+            })
+        })
+        .collect()
+}
+
+#[cfg(all(test, feature = "swiss"))]
+mod tests {
+    use super::*;
+    use crate::calc::swiss_ephemeris;
+    use crate::calc::utils::{date_to_julian, julian_to_date};
+
+    #[test]
+    fn test_eclipses_of_2023_to_2025() {
+        swiss_ephemeris::init_moshier_ephemeris().unwrap();
+        let jd = |date: &str| date_to_julian(format!("{}T00:00:00Z", date).parse().unwrap());
+        let eclipses = eclipses_between(jd("2023-01-01"), jd("2026-01-01"), EphemerisSource::Moshier).unwrap();
+        let found: Vec<(EclipseKind, String)> = eclipses
+            .iter()
+            .map(|e| (e.kind, julian_to_date(e.jd).unwrap().format("%Y-%m-%d").to_string()))
+            .collect();
+        let expected = [
+            (EclipseKind::Solar, "2023-04-20"),
+            (EclipseKind::Lunar, "2023-05-05"),
+            (EclipseKind::Solar, "2023-10-14"),
+            (EclipseKind::Lunar, "2023-10-28"),
+            (EclipseKind::Lunar, "2024-03-25"),
+            (EclipseKind::Solar, "2024-04-08"),
+            (EclipseKind::Lunar, "2024-09-18"),
+            (EclipseKind::Solar, "2024-10-02"),
+            (EclipseKind::Lunar, "2025-03-14"),
+            (EclipseKind::Solar, "2025-03-29"),
+            (EclipseKind::Lunar, "2025-09-07"),
+            (EclipseKind::Solar, "2025-09-21"),
+        ];
+        assert_eq!(found, expected.map(|(kind, date)| (kind, date.to_string())));
+
+        // The total eclipse of 8 April 2024 was at 19°24' Aries
+        let april = eclipses[5];
+        assert!((april.longitude - 19.4).abs() < 0.05, "{}", april.longitude);
+        let natal = [("Sun".to_string(), 19.0), ("Moon".to_string(), 100.0), ("Venus".to_string(), 21.0)];
+        let contacts = eclipse_contacts(&natal, &eclipses, ECLIPSE_POINT_ORB);
+        assert_eq!(contacts.len(), 1, "{:?}", contacts);
+        assert_eq!(contacts[0].natal, "Sun");
+        assert_eq!(contacts[0].eclipse, april);
+        assert!(contacts[0].orb < 0.0);
+    }
+}
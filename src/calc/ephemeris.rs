@@ -0,0 +1,508 @@
+//! The ephemeris a calculation reads body positions and house cusps from.
+//!
+//! Charts take an `Arc<dyn Ephemeris>` rather than calling the Swiss Ephemeris
+//! directly, so the same pipeline runs on the Swiss library, on the analytic
+//! orbital elements, or on fixed positions in tests. The server shares one
+//! through `app_data`; code with no ephemeris of its own uses `default_ephemeris`.
+
+use crate::calc::angles::normalize;
+use crate::calc::coordinates::{equatorial_of_date, EquatorialPosition, Observer};
+use crate::calc::houses::Houses;
+use crate::calc::planets::Planet;
+use crate::calc::positions::ayanamsa_at;
+use crate::core::types::{AstrologError, Ayanamsa, EphemerisSource, HouseSystem};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, OnceLock};
+
+/// Environment variable choosing the default backend: "swiss" or "analytic"
+pub const BACKEND_ENV: &str = "EPHEMERIS_BACKEND";
+
+/// What an ephemeris can calculate, which requests are checked against before
+/// the calculation so that an option it can't honor isn't silently ignored
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EphemerisCapabilities {
+    /// Sources a request may ask for; `Auto` is always among them
+    pub sources: Vec<EphemerisSource>,
+    /// Positions seen from a place on the Earth's surface
+    pub topocentric: bool,
+    /// Positions and houses in a sidereal zodiac
+    pub sidereal: bool,
+}
+
+/// A source of body positions and house cusps.
+///
+/// One ephemeris serves every request at once, from many threads, so a result
+/// may depend only on the arguments of the call that asked for it. Options a
+/// request chooses, such as the observer of topocentric positions, are passed
+/// with each call or fixed when a wrapper like `TopocentricEphemeris` is built
+/// for the request; an implementation never keeps them from one call for the
+/// next. A backend over a library with global settings, like the Swiss
+/// Ephemeris with `swe_set_topo` and `swe_set_sid_mode`, sets them from the
+/// call's arguments under the same lock as the calculation that reads them.
+/// The zodiac is chosen the same way, with a `SiderealEphemeris`.
+pub trait Ephemeris: Send + Sync {
+    /// Ecliptic longitude and latitude of a body at a Julian date (UT), and the
+    /// source that actually produced them
+    fn position(&self, jd: f64, planet: Planet, source: EphemerisSource) -> Result<(f64, f64, EphemerisSource), String>;
+
+    /// House cusps and angles for a moment and place
+    fn houses(&self, jd: f64, latitude: f64, longitude: f64, system: HouseSystem) -> Result<Houses, AstrologError>;
+
+    /// Source used when a request doesn't ask for one
+    fn default_source(&self) -> EphemerisSource;
+
+    /// Right ascension and declination of a body at a Julian date (UT). Unless
+    /// the ephemeris has its own, the ecliptic position is turned onto the
+    /// equator with the true obliquity of date.
+    fn equatorial(&self, jd: f64, planet: Planet, source: EphemerisSource) -> Result<EquatorialPosition, String> {
+        let (longitude, latitude, _) = self.position(jd, planet, source)?;
+        Ok(equatorial_of_date(jd, longitude, latitude))
+    }
+
+    /// Ecliptic longitude and latitude of a body as seen from `observer` rather
+    /// than the Earth's centre. Only ephemerides that model parallax have them.
+    fn topocentric_position(
+        &self,
+        _jd: f64,
+        _planet: Planet,
+        _source: EphemerisSource,
+        _observer: Observer,
+    ) -> Result<(f64, f64, EphemerisSource), String> {
+        Err("Topocentric positions require the Swiss Ephemeris".to_string())
+    }
+
+    /// Place the positions are seen from, or `None` for the Earth's centre
+    fn observer(&self) -> Option<Observer> {
+        None
+    }
+
+    /// How far the zero point of `ayanamsa` is from the equinox at a Julian
+    /// date (UT), in degrees. Unless the ephemeris has its own, `ayanamsa_at`.
+    fn ayanamsa(&self, jd: f64, ayanamsa: Ayanamsa, _source: EphemerisSource) -> Result<f64, String> {
+        Ok(ayanamsa_at(ayanamsa, jd))
+    }
+
+    /// The ayanamsa longitudes are measured from, or `None` for the equinox
+    fn sidereal_ayanamsa(&self) -> Option<Ayanamsa> {
+        None
+    }
+
+    /// The options this ephemeris can honor. Unless it says otherwise, only its
+    /// default source, geocentric and tropical.
+    fn capabilities(&self) -> EphemerisCapabilities {
+        let mut sources = vec![EphemerisSource::Auto];
+        if self.default_source() != EphemerisSource::Auto {
+            sources.push(self.default_source());
+        }
+        EphemerisCapabilities {
+            sources,
+            topocentric: false,
+            sidereal: false,
+        }
+    }
+}
+
+/// Another ephemeris's positions as seen from one place, for a request that
+/// asks for topocentric positions. Houses are the inner ephemeris's.
+pub struct TopocentricEphemeris {
+    ephemeris: Arc<dyn Ephemeris>,
+    observer: Observer,
+}
+
+impl TopocentricEphemeris {
+    pub fn new(ephemeris: Arc<dyn Ephemeris>, observer: Observer) -> Self {
+        Self { ephemeris, observer }
+    }
+}
+
+impl Ephemeris for TopocentricEphemeris {
+    fn position(&self, jd: f64, planet: Planet, source: EphemerisSource) -> Result<(f64, f64, EphemerisSource), String> {
+        self.ephemeris.topocentric_position(jd, planet, source, self.observer)
+    }
+
+    fn houses(&self, jd: f64, latitude: f64, longitude: f64, system: HouseSystem) -> Result<Houses, AstrologError> {
+        self.ephemeris.houses(jd, latitude, longitude, system)
+    }
+
+    fn default_source(&self) -> EphemerisSource {
+        self.ephemeris.default_source()
+    }
+
+    fn topocentric_position(
+        &self,
+        jd: f64,
+        planet: Planet,
+        source: EphemerisSource,
+        observer: Observer,
+    ) -> Result<(f64, f64, EphemerisSource), String> {
+        self.ephemeris.topocentric_position(jd, planet, source, observer)
+    }
+
+    fn observer(&self) -> Option<Observer> {
+        Some(self.observer)
+    }
+
+    fn ayanamsa(&self, jd: f64, ayanamsa: Ayanamsa, source: EphemerisSource) -> Result<f64, String> {
+        self.ephemeris.ayanamsa(jd, ayanamsa, source)
+    }
+
+    fn sidereal_ayanamsa(&self) -> Option<Ayanamsa> {
+        self.ephemeris.sidereal_ayanamsa()
+    }
+
+    fn capabilities(&self) -> EphemerisCapabilities {
+        self.ephemeris.capabilities()
+    }
+}
+
+/// Another ephemeris's positions and houses measured from the zero point of an
+/// ayanamsa rather than the equinox, for a request in a sidereal zodiac. Each
+/// longitude is the tropical one less the ayanamsa of its moment; right
+/// ascensions and declinations don't depend on the zodiac and are unchanged.
+pub struct SiderealEphemeris {
+    ephemeris: Arc<dyn Ephemeris>,
+    ayanamsa: Ayanamsa,
+}
+
+impl SiderealEphemeris {
+    pub fn new(ephemeris: Arc<dyn Ephemeris>, ayanamsa: Ayanamsa) -> Self {
+        Self { ephemeris, ayanamsa }
+    }
+
+    fn shift(&self, jd: f64, position: (f64, f64, EphemerisSource)) -> Result<(f64, f64, EphemerisSource), String> {
+        let (longitude, latitude, used) = position;
+        let ayanamsa = self.ephemeris.ayanamsa(jd, self.ayanamsa, used)?;
+        Ok((normalize(longitude - ayanamsa), latitude, used))
+    }
+}
+
+impl Ephemeris for SiderealEphemeris {
+    fn position(&self, jd: f64, planet: Planet, source: EphemerisSource) -> Result<(f64, f64, EphemerisSource), String> {
+        self.shift(jd, self.ephemeris.position(jd, planet, source)?)
+    }
+
+    fn houses(&self, jd: f64, latitude: f64, longitude: f64, system: HouseSystem) -> Result<Houses, AstrologError> {
+        let houses = self.ephemeris.houses(jd, latitude, longitude, system)?;
+        let ayanamsa = self
+            .ephemeris
+            .ayanamsa(jd, self.ayanamsa, self.default_source())
+            .map_err(|message| AstrologError::CalculationError { message })?;
+        Ok(houses.sidereal(ayanamsa))
+    }
+
+    fn default_source(&self) -> EphemerisSource {
+        self.ephemeris.default_source()
+    }
+
+    fn equatorial(&self, jd: f64, planet: Planet, source: EphemerisSource) -> Result<EquatorialPosition, String> {
+        self.ephemeris.equatorial(jd, planet, source)
+    }
+
+    fn topocentric_position(
+        &self,
+        jd: f64,
+        planet: Planet,
+        source: EphemerisSource,
+        observer: Observer,
+    ) -> Result<(f64, f64, EphemerisSource), String> {
+        self.shift(jd, self.ephemeris.topocentric_position(jd, planet, source, observer)?)
+    }
+
+    fn observer(&self) -> Option<Observer> {
+        self.ephemeris.observer()
+    }
+
+    fn ayanamsa(&self, jd: f64, ayanamsa: Ayanamsa, source: EphemerisSource) -> Result<f64, String> {
+        self.ephemeris.ayanamsa(jd, ayanamsa, source)
+    }
+
+    fn sidereal_ayanamsa(&self) -> Option<Ayanamsa> {
+        Some(self.ayanamsa)
+    }
+
+    fn capabilities(&self) -> EphemerisCapabilities {
+        self.ephemeris.capabilities()
+    }
+}
+
+/// The Swiss Ephemeris library, from its files or its built-in Moshier theory.
+/// It must be initialized through `swiss_ephemeris` first.
+#[cfg(feature = "swiss")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SwissEphemeris;
+
+#[cfg(feature = "swiss")]
+impl Ephemeris for SwissEphemeris {
+    fn position(&self, jd: f64, planet: Planet, source: EphemerisSource) -> Result<(f64, f64, EphemerisSource), String> {
+        use crate::calc::swiss_ephemeris::{calculate_planet_position_at, map_planet_to_swe};
+
+        let swe_planet = map_planet_to_swe(planet).ok_or_else(|| "Invalid planet".to_string())?;
+        let ((longitude, latitude, _distance, _speed), used) =
+            calculate_planet_position_at(swe_planet, jd, source).map_err(|e| e.to_string())?;
+        Ok((longitude, latitude, used))
+    }
+
+    fn houses(&self, jd: f64, latitude: f64, longitude: f64, system: HouseSystem) -> Result<Houses, AstrologError> {
+        crate::calc::houses::calculate_houses(jd, latitude, longitude, system)
+    }
+
+    fn default_source(&self) -> EphemerisSource {
+        crate::calc::swiss_ephemeris::default_ephemeris_source()
+    }
+
+    fn equatorial(&self, jd: f64, planet: Planet, source: EphemerisSource) -> Result<EquatorialPosition, String> {
+        use crate::calc::swiss_ephemeris::{calculate_equatorial_position_at, map_planet_to_swe};
+
+        let swe_planet = map_planet_to_swe(planet).ok_or_else(|| "Invalid planet".to_string())?;
+        let ((right_ascension, declination, _distance, _speed), _) =
+            calculate_equatorial_position_at(swe_planet, jd, source).map_err(|e| e.to_string())?;
+        Ok(EquatorialPosition { right_ascension, declination })
+    }
+
+    fn topocentric_position(
+        &self,
+        jd: f64,
+        planet: Planet,
+        source: EphemerisSource,
+        observer: Observer,
+    ) -> Result<(f64, f64, EphemerisSource), String> {
+        use crate::calc::swiss_ephemeris::{calculate_topocentric_position_at, map_planet_to_swe};
+
+        let swe_planet = map_planet_to_swe(planet).ok_or_else(|| "Invalid planet".to_string())?;
+        let ((longitude, latitude, _distance, _speed), used) =
+            calculate_topocentric_position_at(swe_planet, jd, source, observer).map_err(|e| e.to_string())?;
+        Ok((longitude, latitude, used))
+    }
+
+    /// The library's own ayanamsa, with nutation, as `SEFLG_SIDEREAL` applies it
+    fn ayanamsa(&self, jd: f64, ayanamsa: Ayanamsa, source: EphemerisSource) -> Result<f64, String> {
+        crate::calc::swiss_ephemeris::ayanamsa_ut(jd, ayanamsa, source).map_err(|e| e.to_string())
+    }
+
+    /// The file ephemeris only when its files were found; Moshier always
+    fn capabilities(&self) -> EphemerisCapabilities {
+        let mut sources = vec![EphemerisSource::Auto, EphemerisSource::Moshier];
+        if crate::calc::swiss_ephemeris::ephemeris_files_available() {
+            sources.push(EphemerisSource::Swiss);
+        }
+        EphemerisCapabilities {
+            sources,
+            topocentric: true,
+            sidereal: true,
+        }
+    }
+}
+
+/// Keplerian orbital elements with the Meeus lunar and Pluto theories. Needs no
+/// files or initialization, but only has the Equal, Whole Sign, Porphyry,
+/// Campanus, Vedic and Null houses.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct AnalyticEphemeris;
+
+impl Ephemeris for AnalyticEphemeris {
+    fn position(&self, jd: f64, planet: Planet, source: EphemerisSource) -> Result<(f64, f64, EphemerisSource), String> {
+        if !matches!(source, EphemerisSource::Auto | EphemerisSource::Analytic) {
+            return Err(format!("The {} ephemeris requires the `swiss` feature", source));
+        }
+        let (longitude, latitude) = crate::calc::planets::analytic_position(planet, jd)?;
+        Ok((longitude, latitude, EphemerisSource::Analytic))
+    }
+
+    fn houses(&self, jd: f64, latitude: f64, longitude: f64, system: HouseSystem) -> Result<Houses, AstrologError> {
+        crate::calc::houses::analytic_houses(jd, latitude, longitude, system)
+    }
+
+    fn default_source(&self) -> EphemerisSource {
+        EphemerisSource::Analytic
+    }
+
+    fn capabilities(&self) -> EphemerisCapabilities {
+        EphemerisCapabilities {
+            sources: vec![EphemerisSource::Auto, EphemerisSource::Analytic],
+            topocentric: false,
+            sidereal: true,
+        }
+    }
+}
+
+/// One body's fixture: where it is at the epoch and how fast it moves
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MockBody {
+    pub longitude: f64,
+    pub latitude: f64,
+    /// Degrees per day, negative when retrograde
+    pub speed: f64,
+}
+
+/// Fixed positions for tests. Each body moves uniformly from its place at the
+/// epoch; bodies without a fixture fail, as they would on a real ephemeris that
+/// doesn't cover them. Every position and house request is counted, so a test
+/// can tell whether a calculation reached the ephemeris; clones share the count.
+#[derive(Debug, Clone, Default)]
+pub struct MockEphemeris {
+    epoch: f64,
+    bodies: HashMap<Planet, MockBody>,
+    houses: Option<Houses>,
+    calls: Arc<AtomicU64>,
+}
+
+impl MockEphemeris {
+    /// An ephemeris with no bodies, whose fixtures are for `epoch`
+    pub fn new(epoch: f64) -> Self {
+        Self {
+            epoch,
+            ..Self::default()
+        }
+    }
+
+    /// Puts a body at `longitude` at the epoch, moving `speed` degrees a day
+    pub fn with_body(mut self, planet: Planet, longitude: f64, latitude: f64, speed: f64) -> Self {
+        self.bodies.insert(planet, MockBody { longitude, latitude, speed });
+        self
+    }
+
+    /// Answers every house request with these houses
+    pub fn with_houses(mut self, houses: Houses) -> Self {
+        self.houses = Some(houses);
+        self
+    }
+
+    /// Position and house requests answered so far, by this mock and its clones
+    pub fn calls(&self) -> u64 {
+        self.calls.load(Ordering::Relaxed)
+    }
+}
+
+impl Ephemeris for MockEphemeris {
+    fn position(&self, jd: f64, planet: Planet, source: EphemerisSource) -> Result<(f64, f64, EphemerisSource), String> {
+        self.calls.fetch_add(1, Ordering::Relaxed);
+        let body = self
+            .bodies
+            .get(&planet)
+            .ok_or_else(|| format!("{:?} is not in the fixtures", planet))?;
+        let longitude = body.longitude + body.speed * (jd - self.epoch);
+        // A NaN or infinite fixture comes back as it is, as from a failing ephemeris
+        let longitude = if longitude.is_finite() { normalize(longitude) } else { longitude };
+        Ok((longitude, body.latitude, source))
+    }
+
+    fn houses(&self, _jd: f64, _latitude: f64, _longitude: f64, system: HouseSystem) -> Result<Houses, AstrologError> {
+        self.calls.fetch_add(1, Ordering::Relaxed);
+        self.houses.clone().ok_or_else(|| AstrologError::NotImplemented {
+            message: format!("The fixtures have no {} houses", system),
+        })
+    }
+
+    fn default_source(&self) -> EphemerisSource {
+        EphemerisSource::Auto
+    }
+
+    /// Any source, as every one is answered from the fixtures
+    fn capabilities(&self) -> EphemerisCapabilities {
+        EphemerisCapabilities {
+            sources: vec![
+                EphemerisSource::Auto,
+                EphemerisSource::Swiss,
+                EphemerisSource::Moshier,
+                EphemerisSource::Analytic,
+            ],
+            topocentric: false,
+            sidereal: false,
+        }
+    }
+}
+
+/// The backend called `name`: "swiss", which needs the `swiss` feature, or "analytic"
+pub fn ephemeris_backend(name: &str) -> Result<Arc<dyn Ephemeris>, String> {
+    match name.to_lowercase().as_str() {
+        "analytic" => Ok(Arc::new(AnalyticEphemeris)),
+        #[cfg(feature = "swiss")]
+        "swiss" => Ok(Arc::new(SwissEphemeris)),
+        #[cfg(not(feature = "swiss"))]
+        "swiss" => Err("The swiss backend requires the `swiss` feature".to_string()),
+        _ => Err(format!("Unknown ephemeris backend: {} (expected \"swiss\" or \"analytic\")", name)),
+    }
+}
+
+/// The backend named by `EPHEMERIS_BACKEND`, chosen on first use: the Swiss
+/// Ephemeris when built with the `swiss` feature, the analytic one otherwise
+pub fn default_ephemeris() -> Arc<dyn Ephemeris> {
+    static DEFAULT: OnceLock<Arc<dyn Ephemeris>> = OnceLock::new();
+    Arc::clone(DEFAULT.get_or_init(|| {
+        let backend = std::env::var(BACKEND_ENV).unwrap_or_default();
+        let fallback = if cfg!(feature = "swiss") { "swiss" } else { "analytic" };
+        ephemeris_backend(&backend)
+            .or_else(|_| ephemeris_backend(fallback))
+            .expect("the fallback backend is always built")
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::calc::houses::HousePosition;
+
+    #[test]
+    fn test_mock_bodies_move_from_the_epoch() {
+        let mock = MockEphemeris::new(2451545.0).with_body(Planet::Moon, 350.0, 5.0, 13.0);
+        assert_eq!(mock.position(2451545.0, Planet::Moon, EphemerisSource::Auto), Ok((350.0, 5.0, EphemerisSource::Auto)));
+        let (longitude, _, _) = mock.position(2451546.0, Planet::Moon, EphemerisSource::Auto).unwrap();
+        assert!((longitude - 3.0).abs() < 1e-9);
+        assert!(mock.position(2451545.0, Planet::Sun, EphemerisSource::Auto).is_err());
+        assert!(mock.houses(2451545.0, 0.0, 0.0, HouseSystem::Placidus).is_err());
+        assert_eq!(mock.clone().calls(), 4);
+    }
+
+    #[test]
+    fn test_analytic_only_serves_analytic() {
+        let (longitude, _, source) = AnalyticEphemeris.position(2451545.0, Planet::Sun, EphemerisSource::Auto).unwrap();
+        // The Sun at J2000 was near 280.4°
+        assert!((longitude - 280.4).abs() < 0.1, "{}", longitude);
+        assert_eq!(source, EphemerisSource::Analytic);
+        assert!(AnalyticEphemeris.position(2451545.0, Planet::Sun, EphemerisSource::Moshier).is_err());
+        assert!(AnalyticEphemeris.houses(2451545.0, 40.0, -74.0, HouseSystem::Equal).is_ok());
+        assert!(AnalyticEphemeris.houses(2451545.0, 40.0, -74.0, HouseSystem::Placidus).is_err());
+        assert_eq!(
+            AnalyticEphemeris.capabilities(),
+            EphemerisCapabilities {
+                sources: vec![EphemerisSource::Auto, EphemerisSource::Analytic],
+                topocentric: false,
+                sidereal: true,
+            }
+        );
+    }
+
+    #[test]
+    fn test_sidereal_positions_and_houses() {
+        let houses = Houses {
+            cusps: (0..12)
+                .map(|i| HousePosition {
+                    number: i as u8 + 1,
+                    longitude: i as f64 * 30.0,
+                    latitude: 0.0,
+                })
+                .collect(),
+            ascendant: 15.0,
+            midheaven: 285.0,
+            armc: 287.0,
+            vertex: 195.0,
+            east_point: 15.0,
+            system: HouseSystem::WholeSign,
+        };
+        let mock = MockEphemeris::new(2451545.0)
+            .with_body(Planet::Sun, 10.0, 0.0, 1.0)
+            .with_houses(houses);
+        let ayanamsa = ayanamsa_at(Ayanamsa::Lahiri, 2451545.0);
+        let sidereal = SiderealEphemeris::new(Arc::new(mock), Ayanamsa::Lahiri);
+        assert_eq!(sidereal.sidereal_ayanamsa(), Some(Ayanamsa::Lahiri));
+        let (longitude, _, _) = sidereal.position(2451545.0, Planet::Sun, EphemerisSource::Auto).unwrap();
+        assert!((longitude - normalize(10.0 - ayanamsa)).abs() < 1e-9, "{}", longitude);
+
+        // The Ascendant falls back into Pisces, and whole sign houses with it
+        let houses = sidereal.houses(2451545.0, 40.0, -74.0, HouseSystem::WholeSign).unwrap();
+        assert!((houses.ascendant - normalize(15.0 - ayanamsa)).abs() < 1e-9);
+        assert_eq!(houses.cusps[0].longitude, 330.0);
+        assert_eq!(houses.armc, 287.0);
+    }
+}
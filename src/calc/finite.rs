@@ -0,0 +1,95 @@
+//! Checks that calculated values are real numbers.
+//!
+//! A failed ephemeris lookup or a division by a vanishing denominator produces
+//! NaN or infinity, which serde_json writes as `null` and the SVG generator
+//! draws at meaningless coordinates. Each calculation step checks its output
+//! here and fails with an error naming the offending value instead.
+
+use crate::calc::houses::Houses;
+use crate::calc::planets::{Planet, PlanetPosition};
+use crate::core::AstrologError;
+use std::fmt;
+
+/// How every error from `finite` ends
+const NOT_FINITE: &str = "rather than a finite number";
+
+/// The value if it's finite, otherwise an error naming `what`
+pub fn finite(value: f64, what: impl fmt::Display) -> Result<f64, AstrologError> {
+    if value.is_finite() {
+        Ok(value)
+    } else {
+        Err(AstrologError::CalculationError {
+            message: format!("{} is {} {}", what, value, NOT_FINITE),
+        })
+    }
+}
+
+/// Whether an error is one of `finite`'s, for a value that came out NaN or infinite
+pub fn is_not_finite(error: &AstrologError) -> bool {
+    matches!(error, AstrologError::CalculationError { message } if message.ends_with(NOT_FINITE))
+}
+
+/// Fails if a body's longitude, latitude or speed isn't finite
+pub fn check_position(planet: Planet, position: &PlanetPosition) -> Result<(), AstrologError> {
+    finite(position.longitude, format_args!("{:?} longitude", planet))?;
+    finite(position.latitude, format_args!("{:?} latitude", planet))?;
+    finite(position.speed, format_args!("{:?} speed", planet))?;
+    Ok(())
+}
+
+/// Fails if any cusp or angle isn't finite
+pub fn check_houses(houses: &Houses) -> Result<(), AstrologError> {
+    for cusp in &houses.cusps {
+        finite(cusp.longitude, format_args!("House {} cusp", cusp.number))?;
+    }
+    finite(houses.ascendant, "Ascendant")?;
+    finite(houses.midheaven, "Midheaven")?;
+    finite(houses.armc, "ARMC")?;
+    finite(houses.vertex, "Vertex")?;
+    finite(houses.east_point, "East Point")?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::calc::houses::HousePosition;
+    use crate::core::types::HouseSystem;
+
+    #[test]
+    fn test_non_finite_values_are_named() {
+        let mut position = PlanetPosition::new(123.0, 1.0, 0.5, false);
+        assert!(check_position(Planet::Mars, &position).is_ok());
+        position.speed = f64::NAN;
+        match check_position(Planet::Mars, &position) {
+            Err(AstrologError::CalculationError { message }) => {
+                assert_eq!(message, "Mars speed is NaN rather than a finite number")
+            }
+            other => panic!("expected a calculation error, got {:?}", other),
+        }
+        assert!(is_not_finite(&check_position(Planet::Mars, &position).unwrap_err()));
+        assert!(!is_not_finite(&AstrologError::CalculationError {
+            message: "Mars is not in this ephemeris".to_string(),
+        }));
+
+        let mut houses = Houses {
+            cusps: (1..=12)
+                .map(|number| HousePosition {
+                    number,
+                    longitude: 30.0 * (number - 1) as f64,
+                    latitude: 0.0,
+                })
+                .collect(),
+            ascendant: 0.0,
+            midheaven: 270.0,
+            armc: 270.0,
+            vertex: 180.0,
+            east_point: 0.0,
+            system: HouseSystem::Equal,
+        };
+        assert!(check_houses(&houses).is_ok());
+        houses.cusps[3].longitude = f64::INFINITY;
+        let error = check_houses(&houses).unwrap_err().to_string();
+        assert!(error.contains("House 4 cusp is inf"), "{}", error);
+    }
+}
@@ -0,0 +1,143 @@
+//! How a chart's house placements change from one house system to another.
+//!
+//! The planets are placed once per system from the same longitudes, so the
+//! only differences come from the cusps.
+
+use crate::calc::aspects::ChartPoint;
+use crate::calc::houses::house_of;
+use crate::core::types::HouseSystem;
+
+/// One planet's house under each compared system
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlanetHouses {
+    pub name: String,
+    pub longitude: f64,
+    /// House numbers, in the order the systems were given
+    pub houses: Vec<u8>,
+    /// Whether any two systems put the planet in different houses
+    pub changes: bool,
+}
+
+/// How many planets change house between two of the systems
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SystemPairChanges {
+    pub system1: HouseSystem,
+    pub system2: HouseSystem,
+    pub planets_moved: usize,
+}
+
+/// Placements of the same points under several house systems
+#[derive(Debug, Clone, PartialEq)]
+pub struct HouseSystemComparison {
+    pub planets: Vec<PlanetHouses>,
+    /// Every pair of systems once, the earlier system in the list first
+    pub pairs: Vec<SystemPairChanges>,
+}
+
+/// Places each point under every system's cusps and counts the moves between
+/// each pair of systems
+pub fn compare_house_systems(points: &[ChartPoint], systems: &[(HouseSystem, [f64; 12])]) -> HouseSystemComparison {
+    let planets: Vec<PlanetHouses> = points
+        .iter()
+        .map(|point| {
+            let houses: Vec<u8> = systems
+                .iter()
+                .map(|(_, cusps)| house_of(point.longitude, cusps).house)
+                .collect();
+            PlanetHouses {
+                name: point.name.clone(),
+                longitude: point.longitude,
+                changes: houses.windows(2).any(|pair| pair[0] != pair[1]),
+                houses,
+            }
+        })
+        .collect();
+
+    let mut pairs = Vec::new();
+    for i in 0..systems.len() {
+        for j in i + 1..systems.len() {
+            pairs.push(SystemPairChanges {
+                system1: systems[i].0,
+                system2: systems[j].0,
+                planets_moved: planets.iter().filter(|p| p.houses[i] != p.houses[j]).count(),
+            });
+        }
+    }
+
+    HouseSystemComparison { planets, pairs }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::calc::houses::{calculate_equal_houses, calculate_whole_sign_houses};
+
+    fn points() -> Vec<ChartPoint> {
+        (0..24)
+            .map(|i| ChartPoint {
+                name: format!("P{}", i),
+                longitude: i as f64 * 15.0 + 7.0,
+                speed: 1.0,
+            })
+            .collect()
+    }
+
+    fn equal_and_whole_sign(ascendant: f64) -> HouseSystemComparison {
+        let cusps = |houses: Vec<f64>| -> [f64; 12] { houses.try_into().unwrap() };
+        compare_house_systems(
+            &points(),
+            &[
+                (HouseSystem::Equal, cusps(calculate_equal_houses(ascendant))),
+                (HouseSystem::WholeSign, cusps(calculate_whole_sign_houses(ascendant))),
+            ],
+        )
+    }
+
+    #[test]
+    fn test_equal_and_whole_sign_agree_with_the_ascendant_at_0_degrees() {
+        let comparison = equal_and_whole_sign(120.0);
+        assert!(comparison.planets.iter().all(|p| !p.changes));
+        assert_eq!(comparison.pairs.len(), 1);
+        assert_eq!(comparison.pairs[0].planets_moved, 0);
+    }
+
+    #[test]
+    fn test_equal_and_whole_sign_differ_otherwise() {
+        // With the Ascendant at 10° Leo, points in the first 10° of a sign fall
+        // a house earlier in Equal houses
+        let comparison = equal_and_whole_sign(130.0);
+        let moved: Vec<&PlanetHouses> = comparison.planets.iter().filter(|p| p.changes).collect();
+        assert_eq!(moved.len(), 12);
+        assert!(moved.iter().all(|p| p.longitude % 30.0 < 10.0));
+        assert!(moved.iter().all(|p| p.houses[0] % 12 + 1 == p.houses[1]));
+        assert_eq!(comparison.pairs[0].planets_moved, 12);
+    }
+
+    #[test]
+    fn test_pairs_in_list_order() {
+        // Only the cusps matter, so the third system can reuse the equal ones
+        let equal = calculate_equal_houses(130.0).try_into().unwrap();
+        let whole_sign = calculate_whole_sign_houses(130.0).try_into().unwrap();
+        let comparison = compare_house_systems(
+            &points(),
+            &[
+                (HouseSystem::Equal, equal),
+                (HouseSystem::WholeSign, whole_sign),
+                (HouseSystem::Porphyrius, equal),
+            ],
+        );
+        let pairs: Vec<(HouseSystem, HouseSystem, usize)> = comparison
+            .pairs
+            .iter()
+            .map(|p| (p.system1, p.system2, p.planets_moved))
+            .collect();
+        assert_eq!(
+            pairs,
+            vec![
+                (HouseSystem::Equal, HouseSystem::WholeSign, 12),
+                (HouseSystem::Equal, HouseSystem::Porphyrius, 0),
+                (HouseSystem::WholeSign, HouseSystem::Porphyrius, 12),
+            ]
+        );
+    }
+}
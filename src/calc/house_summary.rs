@@ -0,0 +1,159 @@
+//! Which planets occupy each house, and how they spread over angular,
+//! succedent and cadent houses.
+//!
+//! Works from named longitudes and the twelve cusps alone, like the house
+//! overlays in `comparison`, so it needs nothing else from the chart.
+
+use crate::calc::aspects::ChartPoint;
+use crate::calc::houses::house_of;
+use crate::calc::rulers::Sign;
+use serde::{Deserialize, Serialize};
+
+/// A house's position in its quadrant
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HouseKind {
+    /// Houses 1, 4, 7 and 10, which begin at the angles
+    Angular,
+    /// Houses 2, 5, 8 and 11
+    Succedent,
+    /// Houses 3, 6, 9 and 12
+    Cadent,
+}
+
+impl HouseKind {
+    /// The kind of a house numbered 1-12
+    pub fn of(house: u8) -> HouseKind {
+        match house % 3 {
+            1 => HouseKind::Angular,
+            2 => HouseKind::Succedent,
+            _ => HouseKind::Cadent,
+        }
+    }
+}
+
+/// One house and the points inside it
+#[derive(Debug, Clone, PartialEq)]
+pub struct HouseOccupancy {
+    pub house: u8,
+    pub cusp: f64,
+    pub sign: Sign,
+    pub kind: HouseKind,
+    /// Names of the points in the house, in zodiacal order from its cusp
+    pub planets: Vec<String>,
+}
+
+/// How many points fall in each kind of house
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HouseEmphasis {
+    pub angular: usize,
+    pub succedent: usize,
+    pub cadent: usize,
+    /// The house holding the most points, the lowest numbered on a tie; `None`
+    /// when there are no points
+    pub most_occupied: Option<u8>,
+}
+
+/// Lists the points in each of the twelve houses.
+///
+/// A point exactly on a cusp belongs to the house that cusp begins.
+pub fn house_occupancy(points: &[ChartPoint], cusps: &[f64; 12]) -> Vec<HouseOccupancy> {
+    let mut placed: Vec<(u8, f64, &str)> = points
+        .iter()
+        .map(|point| {
+            let placement = house_of(point.longitude, cusps);
+            (placement.house, placement.fraction, point.name.as_str())
+        })
+        .collect();
+    placed.sort_by(|a, b| a.1.total_cmp(&b.1));
+
+    (1..=12u8)
+        .map(|house| {
+            let cusp = cusps[house as usize - 1];
+            HouseOccupancy {
+                house,
+                cusp,
+                sign: Sign::from_longitude(cusp),
+                kind: HouseKind::of(house),
+                planets: placed
+                    .iter()
+                    .filter(|(h, _, _)| *h == house)
+                    .map(|(_, _, name)| name.to_string())
+                    .collect(),
+            }
+        })
+        .collect()
+}
+
+/// Counts the points in angular, succedent and cadent houses
+pub fn house_emphasis(occupancy: &[HouseOccupancy]) -> HouseEmphasis {
+    let count = |kind: HouseKind| {
+        occupancy
+            .iter()
+            .filter(|h| h.kind == kind)
+            .map(|h| h.planets.len())
+            .sum()
+    };
+    let most_occupied = occupancy
+        .iter()
+        .filter(|h| !h.planets.is_empty())
+        .fold(None::<&HouseOccupancy>, |best, h| match best {
+            Some(best) if best.planets.len() >= h.planets.len() => Some(best),
+            _ => Some(h),
+        })
+        .map(|h| h.house);
+    HouseEmphasis {
+        angular: count(HouseKind::Angular),
+        succedent: count(HouseKind::Succedent),
+        cadent: count(HouseKind::Cadent),
+        most_occupied,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Equal houses from 15° Aries
+    fn cusps() -> [f64; 12] {
+        std::array::from_fn(|i| 15.0 + 30.0 * i as f64)
+    }
+
+    #[test]
+    fn test_point_on_a_cusp_is_in_the_house_it_enters() {
+        let points = [ChartPoint::new("Sun", 45.0, 1.0), ChartPoint::new("Moon", 44.999, 1.0)];
+        let occupancy = house_occupancy(&points, &cusps());
+        assert_eq!(occupancy[1].planets, vec!["Sun"]);
+        assert_eq!(occupancy[0].planets, vec!["Moon"]);
+        assert_eq!(occupancy[1].sign, Sign::Taurus);
+        assert_eq!(occupancy[1].kind, HouseKind::Succedent);
+    }
+
+    #[test]
+    fn test_planets_in_order_from_the_cusp() {
+        // The twelfth house runs from 345° over 0° Aries to 15°
+        let occupancy = house_occupancy(
+            &[
+                ChartPoint::new("Mars", 10.0, 1.0),
+                ChartPoint::new("Venus", 350.0, 1.0),
+                ChartPoint::new("Sun", 200.0, 1.0),
+            ],
+            &cusps(),
+        );
+        assert_eq!(occupancy[11].planets, vec!["Venus", "Mars"]);
+        let emphasis = house_emphasis(&occupancy);
+        assert_eq!((emphasis.angular, emphasis.succedent, emphasis.cadent), (1, 0, 2));
+        assert_eq!(emphasis.most_occupied, Some(12));
+    }
+
+    #[test]
+    fn test_empty_houses() {
+        let occupancy = house_occupancy(&[ChartPoint::new("Sun", 110.0, 1.0)], &cusps());
+        assert_eq!(occupancy.iter().filter(|h| h.planets.is_empty()).count(), 11);
+        assert_eq!(occupancy[3].planets, vec!["Sun"]);
+
+        let emphasis = house_emphasis(&house_occupancy(&[], &cusps()));
+        assert_eq!((emphasis.angular, emphasis.succedent, emphasis.cadent), (0, 0, 0));
+        assert_eq!(emphasis.most_occupied, None);
+    }
+}
@@ -1,14 +1,20 @@
+use crate::calc::angles::normalize;
+use crate::calc::finite::check_houses;
+#[cfg(feature = "swiss")]
 use crate::calc::swiss_ephemeris::calculate_house_cusps_swiss;
-use crate::calc::utils::{degrees_to_radians, normalize_angle, radians_to_degrees};
+use crate::calc::utils::{degrees_to_radians, radians_to_degrees};
 use crate::core::types::HouseSystem;
 use crate::core::AstrologError;
 use approx::{AbsDiffEq, RelativeEq};
+use std::cell::Cell;
+use std::collections::HashMap;
+use std::sync::{Mutex, OnceLock};
 
 /// Represents a house cusp position in the astrological chart.
 /// Houses are divisions of the zodiac that represent different areas of life.
 #[derive(Debug, Clone, PartialEq)]
 pub struct HousePosition {
-    /// House number (1-12) in the chart
+    /// House number (1-12, or 1-36 for Gauquelin sectors) in the chart
     pub number: u8,
     /// Longitude in degrees (0-360) where the house begins
     pub longitude: f64,
@@ -51,7 +57,78 @@ impl RelativeEq for HousePosition {
     }
 }
 
-/// Calculates house cusps for a given date, time, and location using the specified house system.
+/// House cusps together with the chart angles they were derived from.
+///
+/// In Equal and WholeSign charts the Ascendant and MC are generally not cusps, so
+/// they are carried separately rather than read off the first and tenth houses.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Houses {
+    /// The house cusps, house 1 first: 12, or 36 Gauquelin sectors
+    pub cusps: Vec<HousePosition>,
+    /// Longitude of the Ascendant in degrees (0-360)
+    pub ascendant: f64,
+    /// Longitude of the Midheaven in degrees (0-360)
+    pub midheaven: f64,
+    /// Right ascension of the Midheaven (local sidereal time) in degrees (0-360)
+    pub armc: f64,
+    /// Longitude of the Vertex in degrees (0-360)
+    pub vertex: f64,
+    /// Longitude of the East Point (equatorial Ascendant) in degrees (0-360)
+    pub east_point: f64,
+    /// The system the cusps were drawn in, which for Vedic houses from the
+    /// Swiss Ephemeris is Whole Sign
+    pub system: HouseSystem,
+}
+
+impl Houses {
+    /// Cusp longitudes for houses 1-12, or None if there aren't exactly 12 cusps
+    pub fn cusp_longitudes(&self) -> Option<[f64; 12]> {
+        if self.cusps.len() != 12 {
+            return None;
+        }
+        let mut cusps = [0.0; 12];
+        for (cusp, house) in cusps.iter_mut().zip(&self.cusps) {
+            *cusp = house.longitude;
+        }
+        Some(cusps)
+    }
+
+    /// These houses in a sidereal zodiac whose zero point is `ayanamsa` degrees
+    /// past the equinox. The angles and cusps move back by it, but cusps drawn
+    /// on sign boundaries stay on them: Null houses keep 0° of each sign, and
+    /// Whole Sign houses begin again at the sign of the sidereal Ascendant.
+    /// Vedic houses drawn as Vehlow's, from 15° before the Ascendant, move
+    /// with it. The ARMC is a right ascension and doesn't move.
+    pub fn sidereal(&self, ayanamsa: f64) -> Houses {
+        let ascendant = normalize(self.ascendant - ayanamsa);
+        let longitudes: Vec<f64> = match self.system {
+            HouseSystem::Null => self.cusps.iter().map(|cusp| cusp.longitude).collect(),
+            HouseSystem::WholeSign => calculate_whole_sign_houses(ascendant),
+            _ => self.cusps.iter().map(|cusp| normalize(cusp.longitude - ayanamsa)).collect(),
+        };
+        Houses {
+            cusps: self
+                .cusps
+                .iter()
+                .zip(longitudes)
+                .map(|(cusp, longitude)| HousePosition { longitude, ..*cusp })
+                .collect(),
+            ascendant,
+            midheaven: normalize(self.midheaven - ayanamsa),
+            armc: self.armc,
+            vertex: normalize(self.vertex - ayanamsa),
+            east_point: normalize(self.east_point - ayanamsa),
+            system: self.system,
+        }
+    }
+}
+
+/// Calculates house cusps and angles for a given date, time, and location using the
+/// specified house system.
+///
+/// The most recent results are cached, so charts for the same moment and place
+/// (to the second and to four decimals of a degree) reuse their houses; use
+/// `calculate_houses_uncached` to always recalculate.
 ///
 /// # Arguments
 ///
@@ -62,8 +139,8 @@ impl RelativeEq for HousePosition {
 ///
 /// # Returns
 ///
-/// A Result containing a vector of HousePosition structs representing the house cusps,
-/// or an AstrologError if the calculation fails.
+/// A Result containing a Houses struct with the house cusps, Ascendant, MC, ARMC,
+/// Vertex and East Point, or an AstrologError if the calculation fails.
 ///
 /// # Examples
 ///
@@ -79,8 +156,8 @@ impl RelativeEq for HousePosition {
 ///
 /// match calculate_houses(julian_date, latitude, longitude, house_system) {
 ///     Ok(houses) => {
-///         assert_eq!(houses.len(), 12);
-///         // Process house positions...
+///         assert_eq!(houses.cusps.len(), 12);
+///         println!("Ascendant: {}°, MC: {}°", houses.ascendant, houses.midheaven);
 ///     },
 ///     Err(e) => println!("Error calculating houses: {}", e),
 /// }
@@ -91,22 +168,134 @@ pub fn calculate_houses(
     latitude: f64,
     longitude: f64,
     house_system: HouseSystem,
-) -> Result<Vec<HousePosition>, AstrologError> {
-    // Special case for Null house system - each house starts at 0° of its sign
-    if house_system == HouseSystem::Null {
-        return Ok((0..12)
-            .map(|i| HousePosition {
-                number: (i + 1) as u8,
-                longitude: (i * 30) as f64,
-                latitude: 0.0,
-            })
-            .collect());
+) -> Result<Houses, AstrologError> {
+    let key = HouseCacheKey::new(julian_date, latitude, longitude, house_system);
+    if let Some(houses) = house_cache().lock().ok().and_then(|mut cache| cache.get(&key)) {
+        HOUSE_CACHE_HITS.with(|hits| hits.set(hits.get() + 1));
+        return Ok(houses);
+    }
+    let houses = calculate_houses_uncached(julian_date, latitude, longitude, house_system)?;
+    if let Ok(mut cache) = house_cache().lock() {
+        cache.insert(key, houses.clone());
+    }
+    Ok(houses)
+}
+
+/// Number of recent house calculations kept by `calculate_houses`
+pub const HOUSE_CACHE_CAPACITY: usize = 1024;
+
+/// Charts for the same moment and place share their houses: the Julian date is
+/// rounded to the second and the coordinates to four decimals (about 11 metres)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct HouseCacheKey {
+    second: i64,
+    latitude: i64,
+    longitude: i64,
+    house_system: HouseSystem,
+}
+
+impl HouseCacheKey {
+    fn new(julian_date: f64, latitude: f64, longitude: f64, house_system: HouseSystem) -> Self {
+        Self {
+            second: (julian_date * 86400.0).round() as i64,
+            latitude: (latitude * 1e4).round() as i64,
+            longitude: (longitude * 1e4).round() as i64,
+            house_system,
+        }
+    }
+}
+
+/// Least recently used house calculations, evicting the oldest once full
+struct HouseCache {
+    entries: HashMap<HouseCacheKey, (Houses, u64)>,
+    clock: u64,
+}
+
+impl HouseCache {
+    fn get(&mut self, key: &HouseCacheKey) -> Option<Houses> {
+        self.clock += 1;
+        let (houses, used) = self.entries.get_mut(key)?;
+        *used = self.clock;
+        Some(houses.clone())
+    }
+
+    fn insert(&mut self, key: HouseCacheKey, houses: Houses) {
+        self.clock += 1;
+        if self.entries.len() >= HOUSE_CACHE_CAPACITY && !self.entries.contains_key(&key) {
+            if let Some(oldest) = self.entries.iter().min_by_key(|(_, (_, used))| *used).map(|(k, _)| *k) {
+                self.entries.remove(&oldest);
+            }
+        }
+        self.entries.insert(key, (houses, self.clock));
     }
+}
+
+thread_local! {
+    // Lookups this thread answered from the house cache
+    static HOUSE_CACHE_HITS: Cell<u64> = const { Cell::new(0) };
+}
+
+/// How many of this thread's `calculate_houses` calls the cache has answered.
+/// A calculation that runs on one thread can compare the count before and
+/// after to tell whether its houses were cached.
+pub fn house_cache_hits() -> u64 {
+    HOUSE_CACHE_HITS.with(Cell::get)
+}
+
+fn house_cache() -> &'static Mutex<HouseCache> {
+    static HOUSE_CACHE: OnceLock<Mutex<HouseCache>> = OnceLock::new();
+    HOUSE_CACHE.get_or_init(|| {
+        Mutex::new(HouseCache {
+            entries: HashMap::with_capacity(HOUSE_CACHE_CAPACITY),
+            clock: 0,
+        })
+    })
+}
+
+/// Calculates houses like `calculate_houses`, bypassing the cache
+pub fn calculate_houses_uncached(
+    julian_date: f64,
+    latitude: f64,
+    longitude: f64,
+    house_system: HouseSystem,
+) -> Result<Houses, AstrologError> {
+    #[cfg(feature = "swiss")]
+    let cusps_and_angles = swiss_cusps_and_angles;
+    #[cfg(not(feature = "swiss"))]
+    let cusps_and_angles = analytic_cusps_and_angles;
+    houses_with(julian_date, latitude, longitude, house_system, cusps_and_angles)
+}
+
+/// Calculates houses from the analytic angles whatever the build, uncached. Only
+/// the Equal, Whole Sign, Porphyry, Campanus, Vedic and Null systems are available
+/// this way; the others are `UnsupportedInThisBuild`.
+pub fn analytic_houses(
+    julian_date: f64,
+    latitude: f64,
+    longitude: f64,
+    house_system: HouseSystem,
+) -> Result<Houses, AstrologError> {
+    houses_with(julian_date, latitude, longitude, house_system, analytic_cusps_and_angles)
+}
 
+/// Cusp longitudes, the angles [ASC, MC, ARMC, Vertex, East Point] and the
+/// system the cusps were drawn in, for a moment, place and system
+type CuspsAndAngles = fn(f64, f64, f64, HouseSystem) -> Result<(Vec<f64>, [f64; 5], HouseSystem), AstrologError>;
+
+/// Houses from the cusps and angles one ephemeris gives, with the latitude
+/// limits and Null houses applied the same way for every ephemeris
+fn houses_with(
+    julian_date: f64,
+    latitude: f64,
+    longitude: f64,
+    house_system: HouseSystem,
+    cusps_and_angles: CuspsAndAngles,
+) -> Result<Houses, AstrologError> {
     // Check for extreme latitudes
     if latitude.abs() > 66.0
         && house_system != HouseSystem::Equal
         && house_system != HouseSystem::WholeSign
+        && house_system != HouseSystem::Null
     {
         return Err(AstrologError::InvalidLatitude(format!(
             "The {} system of houses is not defined at extreme latitudes.",
@@ -114,24 +303,67 @@ pub fn calculate_houses(
         )));
     }
 
-    // Handle polar regions
+    // Handle polar regions, where the angles themselves are undefined
     if latitude.abs() >= 89.9 {
-        return Ok(vec![
-            HousePosition {
-                number: 1,
-                longitude: 0.0,
-                latitude: 0.0,
-            };
-            12
-        ]);
+        let cusps = if house_system == HouseSystem::Null {
+            calculate_null_houses(0.0, 0.0, 0.0, latitude)
+        } else {
+            vec![0.0; house_system.cusp_count()]
+        };
+        return Ok(Houses {
+            cusps: to_house_positions(&cusps),
+            ascendant: 0.0,
+            midheaven: 0.0,
+            armc: 0.0,
+            vertex: 0.0,
+            east_point: 0.0,
+            system: house_system,
+        });
     }
 
-    // Use Swiss Ephemeris for more accurate calculations
-    let (cusps, _ascmc) =
-        calculate_house_cusps_swiss(julian_date, latitude, longitude, house_system)?;
+    let (mut cusps, [ascendant, midheaven, armc, vertex, east_point], system) =
+        cusps_and_angles(julian_date, latitude, longitude, house_system)?;
+
+    // Null houses start at 0° of each sign whatever the angles are
+    if house_system == HouseSystem::Null {
+        cusps = calculate_null_houses(midheaven, ascendant, 0.0, latitude);
+    }
+
+    let houses = Houses {
+        cusps: to_house_positions(&cusps),
+        ascendant,
+        midheaven,
+        armc,
+        vertex,
+        east_point,
+        system,
+    };
+    check_houses(&houses)?;
+    Ok(houses)
+}
+
+/// Calculates house cusps only.
+///
+/// Kept for callers written before `calculate_houses` returned the chart angles.
+///
+/// # Returns
+///
+/// A Result containing a vector of HousePosition structs representing the house cusps,
+/// or an AstrologError if the calculation fails.
+#[deprecated(since = "0.2.0", note = "use `calculate_houses(..)?.cusps` instead")]
+#[allow(dead_code)]
+pub fn calculate_house_cusps(
+    julian_date: f64,
+    latitude: f64,
+    longitude: f64,
+    house_system: HouseSystem,
+) -> Result<Vec<HousePosition>, AstrologError> {
+    calculate_houses(julian_date, latitude, longitude, house_system).map(|houses| houses.cusps)
+}
 
-    // Convert house cusps to HousePosition structs
-    Ok(cusps[1..13]
+/// Converts cusp longitudes, house 1 first, into HousePosition structs
+fn to_house_positions(cusps: &[f64]) -> Vec<HousePosition> {
+    cusps
         .iter()
         .enumerate()
         .map(|(i, &longitude)| HousePosition {
@@ -139,7 +371,88 @@ pub fn calculate_houses(
             longitude,
             latitude: 0.0, // House cusps are always on the ecliptic
         })
-        .collect())
+        .collect()
+}
+
+/// Cusp longitudes, 12 or 36 of them, and the angles [ASC, MC, ARMC, Vertex, East
+/// Point] from the Swiss Ephemeris
+#[cfg(feature = "swiss")]
+fn swiss_cusps_and_angles(
+    julian_date: f64,
+    latitude: f64,
+    longitude: f64,
+    house_system: HouseSystem,
+) -> Result<(Vec<f64>, [f64; 5], HouseSystem), AstrologError> {
+    let (cusps, ascmc) =
+        calculate_house_cusps_swiss(julian_date, latitude, longitude, house_system)?;
+    // The library is asked for Whole Sign houses in place of Vedic ones
+    let system = match house_system {
+        HouseSystem::Vedic => HouseSystem::WholeSign,
+        other => other,
+    };
+    Ok((cusps.as_slice().to_vec(), [ascmc[0], ascmc[1], ascmc[2], ascmc[3], ascmc[4]], system))
+}
+
+/// Cusp longitudes for houses 1-12 and the angles [ASC, MC, ARMC, Vertex, East Point] from the
+/// apparent sidereal time and true obliquity, used by the analytic ephemeris and by
+/// builds without the Swiss Ephemeris. The equal divisions, Porphyry and Campanus
+/// are available this way; the time-based quadrant systems need the Swiss Ephemeris.
+fn analytic_cusps_and_angles(
+    julian_date: f64,
+    latitude: f64,
+    longitude: f64,
+    house_system: HouseSystem,
+) -> Result<(Vec<f64>, [f64; 5], HouseSystem), AstrologError> {
+    use crate::calc::coordinates::{local_sidereal_time, true_obliquity};
+
+    let armc = local_sidereal_time(julian_date, longitude);
+    let obliquity = true_obliquity(julian_date);
+    let asc = ascendant_of(armc, latitude, obliquity);
+    let right_ascension = degrees_to_radians(armc);
+    let mc = normalize(radians_to_degrees(
+        right_ascension
+            .sin()
+            .atan2(right_ascension.cos() * degrees_to_radians(obliquity).cos()),
+    ));
+    // The Vertex is the Ascendant of the opposite meridian at the co-latitude
+    let colatitude = if latitude >= 0.0 {
+        90.0 - latitude
+    } else {
+        -90.0 - latitude
+    };
+    let vertex = ascendant_of(normalize(armc + 180.0), colatitude, obliquity);
+    // The East Point is where the ecliptic meets the equator's eastern horizon,
+    // at right ascension ARMC + 90°
+    let east_point = ascendant_of(armc, 0.0, obliquity);
+
+    let cusps = match house_system {
+        HouseSystem::Equal => calculate_equal_houses(asc),
+        HouseSystem::WholeSign => calculate_whole_sign_houses(asc),
+        HouseSystem::Porphyrius => porphyry_from_angles(asc, mc).to_vec(),
+        HouseSystem::Campanus => calculate_campanus_houses(mc, asc, armc, latitude, obliquity),
+        HouseSystem::Vedic => calculate_vedic_houses(mc, asc, obliquity, latitude),
+        HouseSystem::Null => calculate_null_houses(mc, asc, obliquity, latitude),
+        _ => {
+            return Err(AstrologError::UnsupportedInThisBuild {
+                message: format!(
+                    "The {} house system requires the Swiss Ephemeris (`swiss` feature)",
+                    house_system
+                ),
+            })
+        }
+    };
+
+    Ok((cusps, [asc, mc, armc, vertex, east_point], house_system))
+}
+
+/// Longitude where the ecliptic rises through the eastern horizon of a place at
+/// `pole` degrees of latitude whose meridian has right ascension `armc`
+fn ascendant_of(armc: f64, pole: f64, obliquity: f64) -> f64 {
+    let armc = degrees_to_radians(armc);
+    let obliquity = degrees_to_radians(obliquity);
+    normalize(radians_to_degrees(armc.cos().atan2(
+        -(armc.sin() * obliquity.cos() + degrees_to_radians(pole).tan() * obliquity.sin()),
+    )))
 }
 
 #[allow(dead_code)]
@@ -158,7 +471,7 @@ fn calculate_placidus_houses(
     houses[0] = asc_longitude; // ASC (1st house)
 
     // Calculate intermediate house cusps using Placidus system
-    for i in 1..9 {
+    for (i, house) in houses.iter_mut().enumerate().take(9).skip(1) {
         let angle = (i as f64 * 30.0).to_radians();
         let x = (angle.cos() * lat_rad.cos() * obl_rad.sin())
             / (lat_rad.sin() * obl_rad.cos() - angle.sin() * lat_rad.cos() * obl_rad.sin());
@@ -166,14 +479,14 @@ fn calculate_placidus_houses(
             / (lat_rad.sin() * obl_rad.cos() - angle.sin() * lat_rad.cos() * obl_rad.sin());
 
         let cusp = (y.atan2(x) + mc_longitude.to_radians()).to_degrees();
-        houses[i] = normalize_angle(cusp);
+        *house = normalize(cusp);
     }
 
     // Calculate remaining houses
-    houses[3] = normalize_angle(mc_longitude + 180.0); // IC (4th house)
-    houses[6] = normalize_angle(asc_longitude + 180.0); // DESC (7th house)
-    houses[10] = normalize_angle(houses[4] + 180.0); // 11th house
-    houses[11] = normalize_angle(houses[5] + 180.0); // 12th house
+    houses[3] = normalize(mc_longitude + 180.0); // IC (4th house)
+    houses[6] = normalize(asc_longitude + 180.0); // DESC (7th house)
+    houses[10] = normalize(houses[4] + 180.0); // 11th house
+    houses[11] = normalize(houses[5] + 180.0); // 12th house
 
     houses
 }
@@ -194,7 +507,7 @@ fn calculate_koch_houses(
     houses[0] = asc_longitude; // ASC (1st house)
 
     // Calculate intermediate houses using Koch system
-    for i in 1..9 {
+    for (i, house) in houses.iter_mut().enumerate().take(9).skip(1) {
         let angle = (i as f64) * 30.0;
         let angle_rad = degrees_to_radians(angle);
 
@@ -205,18 +518,18 @@ fn calculate_koch_houses(
 
         // Apply Koch-specific correction
         let correction = (angle / 90.0) * (obliquity / 3.0);
-        houses[i] = normalize_angle(radians_to_degrees(y.atan2(x)) + asc_longitude + correction);
+        *house = normalize(radians_to_degrees(y.atan2(x)) + asc_longitude + correction);
     }
 
     // Calculate remaining houses to ensure 180° oppositions
-    houses[3] = normalize_angle(mc_longitude + 180.0); // IC (4th house)
-    houses[6] = normalize_angle(asc_longitude + 180.0); // DESC (7th house)
+    houses[3] = normalize(mc_longitude + 180.0); // IC (4th house)
+    houses[6] = normalize(asc_longitude + 180.0); // DESC (7th house)
 
     // Ensure opposite houses are exactly 180° apart
     for i in 0..6 {
         if i != 3 {
             // Skip IC since it's already set
-            houses[i + 6] = normalize_angle(houses[i] + 180.0);
+            houses[i + 6] = normalize(houses[i] + 180.0);
         }
     }
 
@@ -224,61 +537,50 @@ fn calculate_koch_houses(
 }
 
 #[allow(dead_code)]
-fn calculate_equal_houses(asc_longitude: f64) -> Vec<f64> {
+pub(crate) fn calculate_equal_houses(asc_longitude: f64) -> Vec<f64> {
     (0..12)
-        .map(|i| normalize_angle(asc_longitude + (i as f64) * 30.0))
+        .map(|i| normalize(asc_longitude + (i as f64) * 30.0))
         .collect()
 }
 
 #[allow(dead_code)]
-fn calculate_whole_sign_houses(asc_longitude: f64) -> Vec<f64> {
+pub(crate) fn calculate_whole_sign_houses(asc_longitude: f64) -> Vec<f64> {
     // In whole sign houses, each house starts at the beginning of a sign
     let asc_sign = (asc_longitude / 30.0).floor() * 30.0;
     (0..12)
-        .map(|i| normalize_angle(asc_sign + (i as f64) * 30.0))
+        .map(|i| normalize(asc_sign + (i as f64) * 30.0))
         .collect()
 }
 
-#[allow(dead_code)]
+/// Campanus cusps: the prime vertical is divided into 30° arcs from the
+/// meridian, and each cusp is where the great circle through the north and south
+/// points of the horizon and that division meets the ecliptic. The circle acts as
+/// the horizon of a place at its pole height, rotated round the equator.
 fn calculate_campanus_houses(
     mc_longitude: f64,
     asc_longitude: f64,
+    armc: f64,
     latitude: f64,
     obliquity: f64,
 ) -> Vec<f64> {
-    let mut houses = vec![0.0; 12];
     let lat_rad = degrees_to_radians(latitude);
-    let obl_rad = degrees_to_radians(obliquity);
+    let cusp = |division: f64| {
+        let division = degrees_to_radians(division);
+        let pole = radians_to_degrees((lat_rad.sin() * division.sin()).asin());
+        let offset = radians_to_degrees((division.sin() * lat_rad.cos()).atan2(division.cos()));
+        ascendant_of(armc + offset - 90.0, pole, obliquity)
+    };
 
-    // MC and ASC are fixed points
-    houses[9] = mc_longitude; // MC (10th house)
-    houses[0] = asc_longitude; // ASC (1st house)
-
-    // Calculate intermediate houses using Campanus system
-    for i in 1..9 {
-        let angle = (i as f64) * 30.0;
-        let angle_rad = degrees_to_radians(angle);
-
-        // Campanus system uses the prime vertical
-        let y = (angle_rad.sin() * obl_rad.cos()).atan2(angle_rad.cos());
-        let x = (lat_rad.cos() * angle_rad.sin() - lat_rad.sin() * obl_rad.cos() * angle_rad.cos())
-            / (lat_rad.sin() * angle_rad.sin() + lat_rad.cos() * obl_rad.cos() * angle_rad.cos());
-
-        houses[i] = normalize_angle(radians_to_degrees(y.atan2(x)) + asc_longitude);
-    }
-
-    // Calculate remaining houses to ensure 180° oppositions
-    houses[3] = normalize_angle(mc_longitude + 180.0); // IC (4th house)
-    houses[6] = normalize_angle(asc_longitude + 180.0); // DESC (7th house)
-
-    // Ensure opposite houses are exactly 180° apart
-    for i in 0..6 {
-        if i != 3 {
-            // Skip IC since it's already set
-            houses[i + 6] = normalize_angle(houses[i] + 180.0);
-        }
+    let mut houses = vec![0.0; 12];
+    houses[0] = asc_longitude;
+    houses[9] = mc_longitude;
+    houses[10] = cusp(30.0);
+    houses[11] = cusp(60.0);
+    houses[1] = cusp(120.0);
+    houses[2] = cusp(150.0);
+    for house in [3, 4, 5, 6, 7, 8] {
+        houses[house] = normalize(houses[(house + 6) % 12] + 180.0);
     }
-
     houses
 }
 
@@ -298,7 +600,7 @@ fn calculate_regiomontanus_houses(
     houses[0] = asc_longitude; // ASC (1st house)
 
     // Calculate intermediate houses using Regiomontanus system
-    for i in 1..9 {
+    for (i, house) in houses.iter_mut().enumerate().take(9).skip(1) {
         let angle = (i as f64) * 30.0;
         let angle_rad = degrees_to_radians(angle);
 
@@ -309,18 +611,18 @@ fn calculate_regiomontanus_houses(
 
         // Apply Regiomontanus-specific correction
         let correction = (angle / 90.0) * (obliquity / 4.0);
-        houses[i] = normalize_angle(radians_to_degrees(y.atan2(x)) + asc_longitude + correction);
+        *house = normalize(radians_to_degrees(y.atan2(x)) + asc_longitude + correction);
     }
 
     // Calculate remaining houses to ensure 180° oppositions
-    houses[3] = normalize_angle(mc_longitude + 180.0); // IC (4th house)
-    houses[6] = normalize_angle(asc_longitude + 180.0); // DESC (7th house)
+    houses[3] = normalize(mc_longitude + 180.0); // IC (4th house)
+    houses[6] = normalize(asc_longitude + 180.0); // DESC (7th house)
 
     // Ensure opposite houses are exactly 180° apart
     for i in 0..6 {
         if i != 3 {
             // Skip IC since it's already set
-            houses[i + 6] = normalize_angle(houses[i] + 180.0);
+            houses[i + 6] = normalize(houses[i] + 180.0);
         }
     }
 
@@ -343,7 +645,7 @@ fn calculate_meridian_houses(
     houses[0] = asc_longitude; // ASC (1st house)
 
     // Calculate intermediate houses using the meridian system
-    for i in 1..9 {
+    for (i, house) in houses.iter_mut().enumerate().take(9).skip(1) {
         let angle = (i as f64) * 30.0;
         let angle_rad = degrees_to_radians(angle);
 
@@ -352,18 +654,18 @@ fn calculate_meridian_houses(
         let x = (lat_rad.cos() * angle_rad.sin() - lat_rad.sin() * obl_rad.cos() * angle_rad.cos())
             / (lat_rad.sin() * angle_rad.sin() + lat_rad.cos() * obl_rad.cos() * angle_rad.cos());
 
-        houses[i] = normalize_angle(radians_to_degrees(y.atan2(x)) + asc_longitude);
+        *house = normalize(radians_to_degrees(y.atan2(x)) + asc_longitude);
     }
 
     // Calculate remaining houses to ensure 180° oppositions
-    houses[3] = normalize_angle(mc_longitude + 180.0); // IC (4th house)
-    houses[6] = normalize_angle(asc_longitude + 180.0); // DESC (7th house)
+    houses[3] = normalize(mc_longitude + 180.0); // IC (4th house)
+    houses[6] = normalize(asc_longitude + 180.0); // DESC (7th house)
 
     // Ensure opposite houses are exactly 180° apart
     for i in 0..6 {
         if i != 3 {
             // Skip IC since it's already set
-            houses[i + 6] = normalize_angle(houses[i] + 180.0);
+            houses[i + 6] = normalize(houses[i] + 180.0);
         }
     }
 
@@ -395,15 +697,15 @@ fn calculate_alcabitius_houses(
 
     // Calculate intermediate houses
     let ra = degrees_to_radians(mc_longitude);
-    houses[3] = normalize_angle(ra.to_degrees() - sna);
-    houses[2] = normalize_angle(ra.to_degrees() - sna * 2.0 / 3.0);
-    houses[1] = normalize_angle(ra.to_degrees() - sna / 3.0);
-    houses[4] = normalize_angle(ra.to_degrees() + sda / 3.0);
-    houses[5] = normalize_angle(ra.to_degrees() + sda * 2.0 / 3.0);
+    houses[3] = normalize(ra.to_degrees() - sna);
+    houses[2] = normalize(ra.to_degrees() - sna * 2.0 / 3.0);
+    houses[1] = normalize(ra.to_degrees() - sna / 3.0);
+    houses[4] = normalize(ra.to_degrees() + sda / 3.0);
+    houses[5] = normalize(ra.to_degrees() + sda * 2.0 / 3.0);
 
     // Convert to ecliptic coordinates
-    for i in 1..6 {
-        let hr = degrees_to_radians(houses[i]);
+    for house in houses.iter_mut().take(6).skip(1) {
+        let hr = degrees_to_radians(*house);
         let hr2 = (hr.tan() / obl_rad.cos()).atan();
         let hr2 = if hr2 < 0.0 {
             hr2 + std::f64::consts::PI
@@ -415,18 +717,18 @@ fn calculate_alcabitius_houses(
         } else {
             hr2
         };
-        houses[i] = normalize_angle(hr2.to_degrees() + mc_longitude);
+        *house = normalize(hr2.to_degrees() + mc_longitude);
     }
 
     // Calculate remaining houses to ensure 180° oppositions
-    houses[3] = normalize_angle(mc_longitude + 180.0); // IC (4th house)
-    houses[6] = normalize_angle(asc_longitude + 180.0); // DESC (7th house)
+    houses[3] = normalize(mc_longitude + 180.0); // IC (4th house)
+    houses[6] = normalize(asc_longitude + 180.0); // DESC (7th house)
 
     // Ensure opposite houses are exactly 180° apart
     for i in 0..6 {
         if i != 3 {
             // Skip IC since it's already set
-            houses[i + 6] = normalize_angle(houses[i] + 180.0);
+            houses[i + 6] = normalize(houses[i] + 180.0);
         }
     }
 
@@ -449,7 +751,7 @@ fn calculate_topocentric_houses(
     houses[0] = asc_longitude; // ASC (1st house)
 
     // Calculate intermediate houses using the Topocentric system
-    for i in 1..9 {
+    for (i, house) in houses.iter_mut().enumerate().take(9).skip(1) {
         let angle = (i as f64) * 30.0;
         let angle_rad = degrees_to_radians(angle);
 
@@ -460,18 +762,18 @@ fn calculate_topocentric_houses(
 
         // Apply Topocentric-specific correction
         let correction = (angle / 90.0) * (obliquity / 3.0);
-        houses[i] = normalize_angle(radians_to_degrees(y.atan2(x)) + asc_longitude + correction);
+        *house = normalize(radians_to_degrees(y.atan2(x)) + asc_longitude + correction);
     }
 
     // Calculate remaining houses to ensure 180° oppositions
-    houses[3] = normalize_angle(mc_longitude + 180.0); // IC (4th house)
-    houses[6] = normalize_angle(asc_longitude + 180.0); // DESC (7th house)
+    houses[3] = normalize(mc_longitude + 180.0); // IC (4th house)
+    houses[6] = normalize(asc_longitude + 180.0); // DESC (7th house)
 
     // Ensure opposite houses are exactly 180° apart
     for i in 0..6 {
         if i != 3 {
             // Skip IC since it's already set
-            houses[i + 6] = normalize_angle(houses[i] + 180.0);
+            houses[i + 6] = normalize(houses[i] + 180.0);
         }
     }
 
@@ -494,7 +796,7 @@ fn calculate_morinus_houses(
     houses[0] = asc_longitude; // ASC (1st house)
 
     // Calculate intermediate houses using the Morinus system
-    for i in 1..9 {
+    for (i, house) in houses.iter_mut().enumerate().take(9).skip(1) {
         let angle = (i as f64) * 30.0;
         let angle_rad = degrees_to_radians(angle);
 
@@ -505,18 +807,18 @@ fn calculate_morinus_houses(
 
         // Apply Morinus-specific correction
         let correction = (angle / 90.0) * (obliquity / 4.0);
-        houses[i] = normalize_angle(radians_to_degrees(y.atan2(x)) + asc_longitude + correction);
+        *house = normalize(radians_to_degrees(y.atan2(x)) + asc_longitude + correction);
     }
 
     // Calculate remaining houses to ensure 180° oppositions
-    houses[3] = normalize_angle(mc_longitude + 180.0); // IC (4th house)
-    houses[6] = normalize_angle(asc_longitude + 180.0); // DESC (7th house)
+    houses[3] = normalize(mc_longitude + 180.0); // IC (4th house)
+    houses[6] = normalize(asc_longitude + 180.0); // DESC (7th house)
 
     // Ensure opposite houses are exactly 180° apart
     for i in 0..6 {
         if i != 3 {
             // Skip IC since it's already set
-            houses[i + 6] = normalize_angle(houses[i] + 180.0);
+            houses[i + 6] = normalize(houses[i] + 180.0);
         }
     }
 
@@ -530,35 +832,55 @@ fn calculate_porphyrius_houses(
     _latitude: f64,
     _obliquity: f64,
 ) -> Vec<f64> {
-    let mut houses = vec![0.0; 12];
-
-    // MC and ASC are fixed points
-    houses[9] = mc_longitude; // MC (10th house)
-    houses[0] = asc_longitude; // ASC (1st house)
-
-    // Calculate the difference between MC and ASC
-    let diff = normalize_angle(mc_longitude - asc_longitude);
-    let trisection = diff / 3.0;
-
-    // Calculate intermediate houses using trisection
-    for i in 1..9 {
-        let angle = (i as f64) * trisection;
-        houses[i] = normalize_angle(asc_longitude + angle);
-    }
-
-    // Calculate remaining houses to ensure 180° oppositions
-    houses[3] = normalize_angle(mc_longitude + 180.0); // IC (4th house)
-    houses[6] = normalize_angle(asc_longitude + 180.0); // DESC (7th house)
+    porphyry_from_angles(asc_longitude, mc_longitude).to_vec()
+}
 
-    // Ensure opposite houses are exactly 180° apart
-    for i in 0..6 {
-        if i != 3 {
-            // Skip IC since it's already set
-            houses[i + 6] = normalize_angle(houses[i] + 180.0);
-        }
+/// Porphyry house cusps from the Ascendant and Midheaven alone.
+///
+/// Each quadrant is trisected in zodiacal order: MC to Ascendant gives the 11th
+/// and 12th cusps, Ascendant to IC the 2nd and 3rd, and the rest are their
+/// opposites. The two quadrant sizes add up to 180°, so when MC to Ascendant is
+/// wider than 90° the houses above the horizon on the eastern side are wider
+/// than those below it. Composite charts use this because their angles are
+/// midpoints with no moment or place to rerun a quadrant system from.
+///
+/// # Arguments
+///
+/// * `asc` - Longitude of the Ascendant in degrees
+/// * `mc` - Longitude of the Midheaven in degrees
+///
+/// # Returns
+///
+/// The 12 cusp longitudes, house 1 first, each in [0, 360)
+///
+/// # Examples
+///
+/// ```
+/// use astrolog_rs::calc::houses::porphyry_from_angles;
+///
+/// // A 90° quadrant divides into 30° houses
+/// let cusps = porphyry_from_angles(0.0, 270.0);
+/// assert_eq!(cusps[1], 30.0);
+/// assert_eq!(cusps[10], 300.0);
+/// ```
+pub fn porphyry_from_angles(asc: f64, mc: f64) -> [f64; 12] {
+    let (asc, mc) = (normalize(asc), normalize(mc));
+    let ic = normalize(mc + 180.0);
+    let upper = normalize(asc - mc) / 3.0;
+    let lower = normalize(ic - asc) / 3.0;
+
+    let mut cusps = [0.0; 12];
+    cusps[0] = asc;
+    cusps[1] = normalize(asc + lower);
+    cusps[2] = normalize(asc + 2.0 * lower);
+    cusps[3] = ic;
+    cusps[9] = mc;
+    cusps[10] = normalize(mc + upper);
+    cusps[11] = normalize(mc + 2.0 * upper);
+    for house in [4, 5, 6, 7, 8] {
+        cusps[house] = normalize(cusps[(house + 6) % 12] + 180.0);
     }
-
-    houses
+    cusps
 }
 
 #[allow(dead_code)]
@@ -577,7 +899,7 @@ fn calculate_krusinski_houses(
     houses[0] = asc_longitude; // ASC (1st house)
 
     // Calculate intermediate houses using the Krusinski system
-    for i in 1..9 {
+    for (i, house) in houses.iter_mut().enumerate().take(9).skip(1) {
         let angle = (i as f64) * 30.0;
         let angle_rad = degrees_to_radians(angle);
 
@@ -588,18 +910,18 @@ fn calculate_krusinski_houses(
 
         // Apply Krusinski-specific correction
         let correction = (angle / 90.0) * (obliquity / 2.5);
-        houses[i] = normalize_angle(radians_to_degrees(y.atan2(x)) + asc_longitude + correction);
+        *house = normalize(radians_to_degrees(y.atan2(x)) + asc_longitude + correction);
     }
 
     // Calculate remaining houses to ensure 180° oppositions
-    houses[3] = normalize_angle(mc_longitude + 180.0); // IC (4th house)
-    houses[6] = normalize_angle(asc_longitude + 180.0); // DESC (7th house)
+    houses[3] = normalize(mc_longitude + 180.0); // IC (4th house)
+    houses[6] = normalize(asc_longitude + 180.0); // DESC (7th house)
 
     // Ensure opposite houses are exactly 180° apart
     for i in 0..6 {
         if i != 3 {
             // Skip IC since it's already set
-            houses[i + 6] = normalize_angle(houses[i] + 180.0);
+            houses[i + 6] = normalize(houses[i] + 180.0);
         }
     }
 
@@ -617,12 +939,12 @@ fn calculate_vedic_houses(
     _latitude: f64,
 ) -> Vec<f64> {
     // Each house starts 15 degrees earlier than in Equal system
-    let first_house = normalize_angle(ascendant - 15.0);
+    let first_house = normalize(ascendant - 15.0);
     let mut houses = Vec::with_capacity(12);
 
     // Calculate all houses starting from the first house
     for i in 0..12 {
-        houses.push(normalize_angle(first_house + (i as f64 * 30.0)));
+        houses.push(normalize(first_house + (i as f64 * 30.0)));
     }
 
     houses
@@ -642,120 +964,158 @@ fn calculate_null_houses(
     (0..12).map(|i| i as f64 * 30.0).collect()
 }
 
-/// Calculates house placements for a given set of positions and house cusps.
+/// Where a longitude falls within the houses of a chart.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HousePlacement {
+    /// House number (1-12)
+    pub house: u8,
+    /// How far through the house the longitude sits, from 0.0 at its cusp
+    /// up to (but not including) 1.0 at the next cusp
+    pub fraction: f64,
+}
+
+/// Finds the house containing a longitude and how far through it the longitude sits.
+///
+/// Each house runs from its own cusp up to the next one going forward through
+/// the zodiac, so unequal houses and houses spanning 0° Aries are handled
+/// without assuming the cusps are in ascending numeric order.
 ///
 /// # Arguments
 ///
-/// * `positions` - A slice of longitudes to find house placements for
-/// * `cusps` - A slice of house cusp longitudes
+/// * `longitude` - Ecliptic longitude in degrees (any value; normalized to 0-360)
+/// * `cusps` - Longitudes of the 12 house cusps, house 1 first
 ///
 /// # Returns
 ///
-/// A Result containing a vector of house numbers (1-12) corresponding to each position,
-/// or an AstrologError if the calculation fails.
+/// The house number (1-12) and the fraction (0.0-1.0) through that house
 ///
 /// # Examples
 ///
 /// ```
-/// use astrolog_rs::calc::houses::calculate_house_placements;
-///
-/// let positions = vec![45.0, 90.0, 135.0];
-/// let cusps = vec![0.0, 30.0, 60.0, 90.0, 120.0, 150.0, 180.0, 210.0, 240.0, 270.0, 300.0, 330.0];
+/// use astrolog_rs::calc::houses::house_of;
 ///
-/// match calculate_house_placements(&positions, &cusps) {
-///     Ok(placements) => {
-///         assert_eq!(placements.len(), positions.len());
-///         // Process house placements...
-///     },
-///     Err(e) => println!("Error calculating house placements: {}", e),
-/// }
+/// let cusps = [350.0, 20.0, 50.0, 80.0, 110.0, 140.0, 170.0, 200.0, 230.0, 260.0, 290.0, 320.0];
+/// let placement = house_of(5.0, &cusps);
+/// assert_eq!(placement.house, 1);
+/// assert!((placement.fraction - 0.5).abs() < 1e-9);
 /// ```
-#[allow(dead_code)]
-pub fn calculate_house_placements(
-    positions: &[f64],
-    cusps: &[f64],
-) -> Result<Vec<u8>, AstrologError> {
-    let mut placements = Vec::with_capacity(positions.len());
-
-    for &position in positions {
-        let mut house = 1;
-        let mut found = false;
-
-        // Find the first cusp that is greater than the position
-        for (i, &cusp) in cusps.iter().enumerate() {
-            if position < cusp {
-                house = i as u8 + 1;
-                found = true;
-                break;
-            }
-        }
-
-        // If no cusp was found greater than the position, it's in the last house
-        if !found {
-            house = 12;
-        }
-
-        placements.push(house);
-    }
-
-    Ok(placements)
-}
+pub fn house_of(longitude: f64, cusps: &[f64; 12]) -> HousePlacement {
+    let longitude = normalize(longitude);
 
-/// Determine which house a given position falls in.
-/// Returns the house number (1-12) for the given position.
-#[allow(dead_code)]
-pub fn house_place_in(position: f64, house_cusps: &[f64; 12]) -> usize {
-    let position = normalize_angle(position);
-
-    // Find the first house cusp that's greater than the position
     for i in 0..12 {
-        let next_i = (i + 1) % 12;
-        if position >= house_cusps[i] && position < house_cusps[next_i] {
-            return i + 1; // Houses are 1-based
+        let start = normalize(cusps[i]);
+        let width = normalize(cusps[(i + 1) % 12] - cusps[i]);
+        let offset = normalize(longitude - start);
+        if offset < width {
+            return HousePlacement {
+                house: i as u8 + 1,
+                fraction: offset / width,
+            };
         }
     }
 
-    // If we get here, the position must be in the last house
-    12
+    // Only reachable if every cusp coincides
+    HousePlacement {
+        house: 1,
+        fraction: 0.0,
+    }
 }
 
-/// Calculates house placements for planets.
-///
-/// This function determines which house each planet is located in based on
-/// their zodiacal positions and the house cusps.
+/// Calculates house placements for a given set of positions and house cusps.
 ///
 /// # Arguments
 ///
-/// * `positions` - A vector of planet positions in degrees (0-360)
-/// * `cusps` - A vector of 12 house cusp positions in degrees (0-360)
+/// * `positions` - A slice of longitudes to find house placements for
+/// * `cusps` - A slice of the 12 house cusp longitudes, house 1 first
 ///
 /// # Returns
 ///
-/// A Result containing a vector of house numbers (1-12) for each planet
+/// A Result containing a vector of house numbers (1-12) corresponding to each position,
+/// or an AstrologError if `cusps` does not hold exactly 12 values.
 ///
 /// # Examples
 ///
 /// ```
-/// use crate::calc::houses::calculate_house_placements;
+/// use astrolog_rs::calc::houses::calculate_house_placements;
 ///
-/// let positions = vec![0.0, 30.0, 60.0]; // Example planet positions
+/// let positions = vec![45.0, 90.0, 135.0];
 /// let cusps = vec![0.0, 30.0, 60.0, 90.0, 120.0, 150.0, 180.0, 210.0, 240.0, 270.0, 300.0, 330.0];
 ///
 /// match calculate_house_placements(&positions, &cusps) {
-///     Ok(house_placements) => {
-///         for (i, house) in house_placements.iter().enumerate() {
-///             println!("Planet {} is in house {}", i + 1, house);
-///         }
+///     Ok(placements) => {
+///         assert_eq!(placements, vec![2, 4, 5]);
 ///     },
 ///     Err(e) => println!("Error calculating house placements: {}", e),
 /// }
 /// ```
+#[allow(dead_code)]
+pub fn calculate_house_placements(
+    positions: &[f64],
+    cusps: &[f64],
+) -> Result<Vec<u8>, AstrologError> {
+    let cusps: &[f64; 12] = cusps.try_into().map_err(|_| AstrologError::InvalidInput {
+        message: format!("expected 12 house cusps, got {}", cusps.len()),
+        parameter: "cusps".to_string(),
+    })?;
+
+    Ok(positions
+        .iter()
+        .map(|&position| house_of(position, cusps).house)
+        .collect())
+}
+
+/// Determine which house a given position falls in.
+/// Returns the house number (1-12) for the given position.
+#[allow(dead_code)]
+pub fn house_place_in(position: f64, house_cusps: &[f64; 12]) -> usize {
+    house_of(position, house_cusps).house as usize
+}
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::calc::angles::shortest_distance;
     use approx::assert_relative_eq;
 
+    #[cfg(feature = "swiss")]
+    #[test]
+    fn test_cached_houses_match_uncached() {
+        crate::calc::swiss_ephemeris::init_moshier_ephemeris().unwrap();
+        for (i, system) in [HouseSystem::Placidus, HouseSystem::Koch, HouseSystem::WholeSign]
+            .into_iter()
+            .enumerate()
+        {
+            let jd = 2451545.0 + i as f64 * 1000.25;
+            let first = calculate_houses(jd, 51.5074, -0.1278, system).unwrap();
+            let again = calculate_houses(jd, 51.5074, -0.1278, system).unwrap();
+            let uncached = calculate_houses_uncached(jd, 51.5074, -0.1278, system).unwrap();
+            assert_eq!(first, uncached);
+            assert_eq!(again, uncached);
+        }
+        // Errors aren't cached
+        assert!(calculate_houses(2451545.0, 70.0, 0.0, HouseSystem::Placidus).is_err());
+        assert!(calculate_houses(2451545.0, 70.0, 0.0, HouseSystem::Placidus).is_err());
+    }
+
+    #[test]
+    fn test_house_cache_evicts_least_recently_used() {
+        let houses = calculate_houses_uncached(2451545.0, 0.0, 0.0, HouseSystem::Equal).unwrap();
+        let key = |i: usize| HouseCacheKey::new(2451545.0 + i as f64, 0.0, 0.0, HouseSystem::Equal);
+        let mut cache = HouseCache {
+            entries: HashMap::new(),
+            clock: 0,
+        };
+        for i in 0..HOUSE_CACHE_CAPACITY {
+            cache.insert(key(i), houses.clone());
+        }
+        assert!(cache.get(&key(0)).is_some());
+        cache.insert(key(HOUSE_CACHE_CAPACITY), houses);
+        assert_eq!(cache.entries.len(), HOUSE_CACHE_CAPACITY);
+        assert!(cache.get(&key(0)).is_some());
+        assert!(cache.get(&key(1)).is_none());
+    }
+
+    #[cfg(feature = "swiss")]
     #[test]
     fn test_house_systems() {
         let julian_date = 2451545.0; // 2000-01-01
@@ -763,7 +1123,9 @@ mod tests {
         let longitude = -74.0;
         let house_system = HouseSystem::Placidus;
 
-        let houses = calculate_houses(julian_date, latitude, longitude, house_system).unwrap();
+        let houses = calculate_houses(julian_date, latitude, longitude, house_system)
+            .unwrap()
+            .cusps;
         assert_eq!(
             houses.len(),
             12,
@@ -772,15 +1134,14 @@ mod tests {
         );
 
         // Test house numbers and longitudes
-        for i in 0..houses.len() {
+        for (i, house) in houses.iter().enumerate() {
             assert_eq!(
-                houses[i].number,
+                house.number,
                 (i + 1) as u8,
                 "House {} should have number {}",
                 i + 1,
                 i + 1
             );
-            let house = &houses[i];
             assert!(
                 house.longitude >= 0.0 && house.longitude < 360.0,
                 "House {} longitude should be between 0 and 360 degrees",
@@ -789,6 +1150,7 @@ mod tests {
         }
     }
 
+    #[cfg(feature = "swiss")]
     #[test]
     fn test_house_system_consistency() {
         let julian_date = 2451545.0;
@@ -814,7 +1176,9 @@ mod tests {
         ]
         .iter()
         {
-            let houses = calculate_houses(julian_date, latitude, longitude, *system).unwrap();
+            let houses = calculate_houses(julian_date, latitude, longitude, *system)
+                .unwrap()
+                .cusps;
 
             // Verify we have exactly 12 houses
             assert_eq!(
@@ -851,8 +1215,7 @@ mod tests {
                 || *system == HouseSystem::Vedic
             {
                 for i in 1..12 {
-                    let diff = normalize_angle(houses[i].longitude - houses[i - 1].longitude);
-                    let min_diff = diff.min(360.0 - diff);
+                    let min_diff = shortest_distance(houses[i].longitude, houses[i - 1].longitude);
                     assert!((min_diff - 30.0).abs() <= 0.1,
                         "House system {:?} should have houses 30° apart, found difference of {:.6}° between houses {} and {}",
                         system, min_diff, i, i + 1);
@@ -865,8 +1228,7 @@ mod tests {
                 && *system != HouseSystem::Vedic
             {
                 for i in 0..6 {
-                    let diff = normalize_angle(houses[i].longitude - houses[i + 6].longitude);
-                    let min_diff = diff.min(360.0 - diff);
+                    let min_diff = shortest_distance(houses[i].longitude, houses[i + 6].longitude);
                     assert!((min_diff - 180.0).abs() <= 0.1,
                         "House system {:?} should have opposite houses 180° apart, found difference of {:.6}° between houses {} and {}",
                         system, min_diff, i + 1, i + 7);
@@ -882,7 +1244,9 @@ mod tests {
         let longitude = -74.0;
 
         let houses =
-            calculate_houses(julian_date, latitude, longitude, HouseSystem::Vedic).unwrap();
+            calculate_houses(julian_date, latitude, longitude, HouseSystem::Vedic)
+                .unwrap()
+                .cusps;
 
         // Verify we have exactly 12 houses
         assert_eq!(houses.len(), 12, "Vedic system should return 12 houses");
@@ -908,8 +1272,7 @@ mod tests {
 
         // Verify houses are 30° apart
         for i in 1..12 {
-            let diff = normalize_angle(houses[i].longitude - houses[i - 1].longitude);
-            let min_diff = diff.min(360.0 - diff);
+            let min_diff = shortest_distance(houses[i].longitude, houses[i - 1].longitude);
             assert!((min_diff - 30.0).abs() <= 0.1,
                 "Vedic houses should be 30° apart, found difference of {:.6}° between houses {} and {}",
                 min_diff, i, i + 1);
@@ -917,9 +1280,8 @@ mod tests {
 
         // Verify first house starts at Ascendant - 15°
         let ascendant = houses[0].longitude + 15.0; // Since first house is 15° before ascendant
-        let expected_first_house = normalize_angle(ascendant - 15.0);
-        let diff = normalize_angle(houses[0].longitude - expected_first_house);
-        let min_diff = diff.min(360.0 - diff);
+        let expected_first_house = normalize(ascendant - 15.0);
+        let min_diff = shortest_distance(houses[0].longitude, expected_first_house);
         assert!(
             min_diff <= 0.1,
             "First house should start at Ascendant - 15°, found difference of {:.6}°",
@@ -950,11 +1312,265 @@ mod tests {
         let latitude = 40.0;
         let longitude = -74.0;
 
-        let houses = calculate_houses(julian_date, latitude, longitude, HouseSystem::Null).unwrap();
+        let houses = calculate_houses(julian_date, latitude, longitude, HouseSystem::Null)
+            .unwrap()
+            .cusps;
 
         // Each house should start at 0° of its sign
-        for i in 0..12 {
-            assert_relative_eq!(houses[i].longitude, (i * 30) as f64, epsilon = 0.0001);
+        for (i, house) in houses.iter().enumerate() {
+            assert_relative_eq!(house.longitude, (i * 30) as f64, epsilon = 0.0001);
+        }
+    }
+
+    #[test]
+    fn test_house_of_across_zero_aries() {
+        let cusps = [
+            340.0, 10.0, 40.0, 70.0, 100.0, 130.0, 160.0, 190.0, 220.0, 250.0, 280.0, 310.0,
+        ];
+        assert_eq!(house_of(350.0, &cusps).house, 1);
+        assert_eq!(house_of(5.0, &cusps).house, 1);
+        assert_relative_eq!(house_of(355.0, &cusps).fraction, 0.5, epsilon = 1e-9);
+        assert_eq!(house_of(335.0, &cusps).house, 12);
+        assert_eq!(house_of(-20.0, &cusps).house, 1);
+        // The old ascending-order scan put 5° in house 1 and 350° in house 12
+        assert_eq!(calculate_house_placements(&[5.0, 350.0], &cusps).unwrap(), vec![1, 1]);
+    }
+
+    #[test]
+    fn test_house_of_unequal_houses() {
+        let cusps = [
+            0.0, 10.0, 60.0, 90.0, 100.0, 150.0, 180.0, 190.0, 240.0, 270.0, 280.0, 330.0,
+        ];
+        let placement = house_of(35.0, &cusps);
+        assert_eq!(placement.house, 2);
+        assert_relative_eq!(placement.fraction, 0.5, epsilon = 1e-9);
+        let placement = house_of(5.0, &cusps);
+        assert_eq!(placement.house, 1);
+        assert_relative_eq!(placement.fraction, 0.5, epsilon = 1e-9);
+    }
+
+    #[test]
+    fn test_house_placements_need_twelve_cusps() {
+        assert!(calculate_house_placements(&[10.0], &[0.0, 30.0]).is_err());
+    }
+
+    #[test]
+    fn test_house_of_partitions_real_charts() {
+        let systems = [
+            HouseSystem::Placidus,
+            HouseSystem::Koch,
+            HouseSystem::Equal,
+            HouseSystem::Campanus,
+            HouseSystem::Regiomontanus,
+            HouseSystem::WholeSign,
+        ];
+        // Spread of dates from 1900 to 2100 at a range of latitudes
+        let mut seed: u64 = 0x2545_f491_4f6c_dd1d;
+        let mut next = || {
+            seed ^= seed << 13;
+            seed ^= seed >> 7;
+            seed ^= seed << 17;
+            (seed >> 11) as f64 / (1u64 << 53) as f64
+        };
+
+        for _ in 0..40 {
+            let jd = 2415020.5 + next() * 73050.0;
+            let latitude = -60.0 + next() * 120.0;
+            let longitude = -180.0 + next() * 360.0;
+            for &system in &systems {
+                let Ok(houses) = calculate_houses(jd, latitude, longitude, system) else {
+                    continue;
+                };
+                let cusps = houses.cusp_longitudes().unwrap();
+
+                let mut previous: Option<HousePlacement> = None;
+                for step in 0..1440 {
+                    let lon = step as f64 * 0.25;
+                    let placement = house_of(lon, &cusps);
+
+                    // Exactly one house's arc contains the longitude
+                    let containing: Vec<u8> = (0..12)
+                        .filter(|&i| {
+                            let width = normalize(cusps[(i + 1) % 12] - cusps[i]);
+                            normalize(lon - cusps[i]) < width
+                        })
+                        .map(|i| i as u8 + 1)
+                        .collect();
+                    assert_eq!(containing, vec![placement.house], "{:?} cusps {:?} at {}", system, cusps, lon);
+                    assert!((0.0..1.0).contains(&placement.fraction));
+
+                    // Fractions only increase while staying in the same house
+                    if let Some(prev) = previous {
+                        if prev.house == placement.house {
+                            assert!(placement.fraction > prev.fraction);
+                        }
+                    }
+                    previous = Some(placement);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_porphyry_from_angles_trisects_uneven_quadrants() {
+        // ASC 10° Aries, MC 5° Capricorn: MC to ASC spans 95°, ASC to IC 85°
+        let cusps = porphyry_from_angles(10.0, 275.0);
+        let expected = [
+            10.0,
+            10.0 + 85.0 / 3.0,
+            10.0 + 170.0 / 3.0,
+            95.0,
+            95.0 + 95.0 / 3.0,
+            95.0 + 190.0 / 3.0,
+            190.0,
+            190.0 + 85.0 / 3.0,
+            190.0 + 170.0 / 3.0,
+            275.0,
+            275.0 + 95.0 / 3.0,
+            275.0 + 190.0 / 3.0,
+        ];
+        for (cusp, expected) in cusps.iter().zip(expected) {
+            assert_relative_eq!(*cusp, expected, epsilon = 1e-9);
+        }
+        // The houses either side of the Ascendant differ in width
+        assert_relative_eq!(normalize(cusps[0] - cusps[11]), 95.0 / 3.0, epsilon = 1e-9);
+        assert_relative_eq!(normalize(cusps[1] - cusps[0]), 85.0 / 3.0, epsilon = 1e-9);
+
+        // Across 0° Aries the cusps wrap, and a narrow MC to ASC quadrant is kept
+        let cusps = porphyry_from_angles(-20.0, 300.0);
+        assert_relative_eq!(cusps[0], 340.0, epsilon = 1e-9);
+        assert_relative_eq!(cusps[10], 313.333333333, epsilon = 1e-6);
+        assert_relative_eq!(cusps[1], 340.0 + 140.0 / 3.0 - 360.0, epsilon = 1e-9);
+        assert_relative_eq!(cusps[3], 120.0, epsilon = 1e-9);
+    }
+
+    #[cfg(feature = "swiss")]
+    #[test]
+    fn test_whole_sign_ascendant_is_not_a_cusp() {
+        let julian_date = 2451545.0;
+        let latitude = 40.0;
+        let longitude = -74.0;
+
+        let houses =
+            calculate_houses(julian_date, latitude, longitude, HouseSystem::WholeSign).unwrap();
+        assert_eq!(houses.cusps.len(), 12);
+
+        // Cusp 1 is 0° of the rising sign, which only matches the ASC at 0° of a sign
+        let first_cusp = houses.cusps[0].longitude;
+        assert_relative_eq!(first_cusp, (houses.ascendant / 30.0).floor() * 30.0, epsilon = 1e-6);
+        if houses.ascendant % 30.0 > 1e-6 {
+            assert!((houses.ascendant - first_cusp).abs() > 1e-6);
         }
+        assert!((0.0..360.0).contains(&houses.ascendant));
+        assert!((0.0..360.0).contains(&houses.midheaven));
+        assert!((0.0..360.0).contains(&houses.armc));
+        assert!((0.0..360.0).contains(&houses.vertex));
+
+        // The angles don't depend on the house system
+        let placidus =
+            calculate_houses(julian_date, latitude, longitude, HouseSystem::Placidus).unwrap();
+        assert_relative_eq!(placidus.ascendant, houses.ascendant, epsilon = 1e-9);
+        assert_relative_eq!(placidus.midheaven, houses.midheaven, epsilon = 1e-9);
+        assert_relative_eq!(placidus.cusps[0].longitude, placidus.ascendant, epsilon = 1e-6);
+        assert_relative_eq!(placidus.cusps[9].longitude, placidus.midheaven, epsilon = 1e-6);
+    }
+
+    #[cfg(feature = "swiss")]
+    #[test]
+    fn test_sidereal_houses() {
+        let (julian_date, latitude, longitude) = (2460416.0, 40.0, -74.0);
+        let ayanamsa = 24.19;
+        for system in [HouseSystem::Placidus, HouseSystem::WholeSign, HouseSystem::Null] {
+            let tropical = calculate_houses(julian_date, latitude, longitude, system).unwrap();
+            let sidereal = tropical.sidereal(ayanamsa);
+            assert_relative_eq!(shortest_distance(sidereal.ascendant + ayanamsa, tropical.ascendant), 0.0, epsilon = 1e-9);
+            assert_relative_eq!(shortest_distance(sidereal.midheaven + ayanamsa, tropical.midheaven), 0.0, epsilon = 1e-9);
+            assert_eq!(sidereal.armc, tropical.armc);
+            for (i, (sidereal, tropical)) in sidereal.cusps.iter().zip(&tropical.cusps).enumerate() {
+                let expected = match system {
+                    HouseSystem::Placidus => normalize(tropical.longitude - ayanamsa),
+                    _ => sidereal.longitude,
+                };
+                assert_relative_eq!(sidereal.longitude, expected, epsilon = 1e-9);
+                assert_eq!(sidereal.longitude % 30.0 == 0.0, system != HouseSystem::Placidus, "cusp {}", i + 1);
+            }
+        }
+        // Whole sign houses begin at the sign of the sidereal Ascendant, Null
+        // houses at sidereal Aries
+        let whole_sign = calculate_houses(julian_date, latitude, longitude, HouseSystem::WholeSign)
+            .unwrap()
+            .sidereal(ayanamsa);
+        assert_eq!(whole_sign.cusps[0].longitude, (whole_sign.ascendant / 30.0).floor() * 30.0);
+        let null = calculate_houses(julian_date, latitude, longitude, HouseSystem::Null)
+            .unwrap()
+            .sidereal(ayanamsa);
+        assert_eq!(null.cusps[0].longitude, 0.0);
+        // Vedic houses are Whole Sign from the Swiss Ephemeris and Vehlow's from
+        // the analytic angles, and stay so
+        let vedic = calculate_houses(julian_date, latitude, longitude, HouseSystem::Vedic)
+            .unwrap()
+            .sidereal(ayanamsa);
+        let expected = if cfg!(feature = "swiss") {
+            (vedic.ascendant / 30.0).floor() * 30.0
+        } else {
+            normalize(vedic.ascendant - 15.0)
+        };
+        assert_relative_eq!(vedic.cusps[0].longitude, expected, epsilon = 1e-9);
+        let vehlow = analytic_houses(julian_date, latitude, longitude, HouseSystem::Vedic)
+            .unwrap()
+            .sidereal(ayanamsa);
+        assert_relative_eq!(vehlow.cusps[0].longitude, normalize(vehlow.ascendant - 15.0), epsilon = 1e-9);
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    fn test_deprecated_cusp_shim_matches_houses() {
+        let houses = calculate_houses(2451545.0, 40.0, -74.0, HouseSystem::Equal).unwrap();
+        let cusps = calculate_house_cusps(2451545.0, 40.0, -74.0, HouseSystem::Equal).unwrap();
+        assert_eq!(cusps, houses.cusps);
+    }
+
+    #[cfg(feature = "swiss")]
+    #[test]
+    fn test_gauquelin_sectors_go_round_the_circle() {
+        let houses = calculate_houses(2451545.0, 40.0, -74.0, HouseSystem::Gauquelin).unwrap();
+        assert_eq!(houses.cusps.len(), 36);
+        assert_eq!(houses.cusps[35].number, 36);
+        assert!(houses.cusp_longitudes().is_none());
+        assert_relative_eq!(houses.cusps[0].longitude, houses.ascendant, epsilon = 1e-6);
+        assert_relative_eq!(houses.cusps[9].longitude, houses.midheaven, epsilon = 1e-6);
+
+        // Sectors follow the diurnal motion, so longitudes fall from one cusp to the next
+        let arcs: Vec<f64> = (0..36)
+            .map(|i| {
+                let (cusp, next) = (houses.cusps[i].longitude, houses.cusps[(i + 1) % 36].longitude);
+                normalize(cusp - next)
+            })
+            .collect();
+        assert!(arcs.iter().all(|&arc| arc > 0.0 && arc < 30.0), "{:?}", arcs);
+        assert_relative_eq!(arcs.iter().sum::<f64>(), 360.0, epsilon = 1e-6);
+
+        // Sunshine houses keep 12 cusps on the angles, but opposite cusps needn't be 180° apart
+        let sunshine = calculate_houses(2451545.0, 40.0, -74.0, HouseSystem::Sunshine).unwrap();
+        assert_eq!(sunshine.cusps.len(), 12);
+        assert_relative_eq!(sunshine.cusps[0].longitude, sunshine.ascendant, epsilon = 1e-6);
+    }
+
+    #[cfg(feature = "swiss")]
+    #[test]
+    fn test_vertex_and_east_point_match_swe_houses() {
+        use crate::calc::points::AnglePoint;
+
+        crate::calc::swiss_ephemeris::init_moshier_ephemeris().unwrap();
+        let (jd, latitude, longitude) = (2443440.70556, 14.6488, 121.0509);
+        let houses = calculate_houses_uncached(jd, latitude, longitude, HouseSystem::Placidus).unwrap();
+        let (_, ascmc) = calculate_house_cusps_swiss(jd, latitude, longitude, HouseSystem::Placidus).unwrap();
+        assert_eq!(AnglePoint::Vertex.longitude(&houses), ascmc[3]);
+        assert_eq!(AnglePoint::EastPoint.longitude(&houses), ascmc[4]);
+        assert_relative_eq!(
+            AnglePoint::AntiVertex.longitude(&houses),
+            normalize(ascmc[3] + 180.0),
+            epsilon = 1e-12
+        );
     }
 }
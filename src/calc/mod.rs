@@ -1,12 +1,38 @@
+pub mod accuracy;
 pub mod angles;
 pub mod aspects;
+pub mod boundaries;
+pub mod comparison;
+pub mod compatibility;
 pub mod coordinates;
+pub mod cycles;
+pub mod degree_lookup;
+pub mod eclipses;
+pub mod ephemeris;
+pub mod finite;
+pub mod house_compare;
+pub mod house_summary;
 pub mod houses;
+pub mod mundane;
+pub mod nodes;
 pub mod planets;
+pub mod points;
+pub mod positions;
+pub mod pluto;
+pub mod precession;
+pub mod returns;
+pub mod rulers;
+pub mod sect;
+pub mod subdivisions;
+#[cfg(feature = "swiss")]
 pub mod swiss_ephemeris;
+#[cfg(feature = "swiss")]
 pub mod swiss_ephemeris_ffi;
 pub mod time;
+pub mod transit_houses;
+pub mod transit_search;
 pub mod utils;
 pub mod vsop87;
+pub mod weather;
 
 pub use planets::PlanetPosition;
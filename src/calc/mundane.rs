@@ -0,0 +1,163 @@
+//! Moments mundane charts are cast for: the Sun's ingresses into the cardinal
+//! signs, which open the seasons, and the New and Full Moons.
+
+use crate::calc::aspects::{crossings, next_exact_with_source, sample_motion, AspectType};
+use crate::calc::planets::{max_daily_motion, planet_longitude_at, Planet};
+use crate::calc::utils::date_to_julian;
+use crate::core::types::EphemerisSource;
+use chrono::{NaiveDate, NaiveTime};
+use serde::{Deserialize, Serialize};
+
+/// An event a mundane chart is cast for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MundaneEvent {
+    /// The Sun reaching 0° Aries, the March equinox
+    AriesIngress,
+    /// The Sun reaching 0° Cancer, the June solstice
+    CancerIngress,
+    /// The Sun reaching 0° Libra, the September equinox
+    LibraIngress,
+    /// The Sun reaching 0° Capricorn, the December solstice
+    CapricornIngress,
+    /// The Moon conjunct the Sun
+    NewMoon,
+    /// The Moon opposite the Sun
+    FullMoon,
+}
+
+impl MundaneEvent {
+    /// Whether the event is a lunation, found within a month rather than a year
+    pub fn is_lunation(self) -> bool {
+        matches!(self, MundaneEvent::NewMoon | MundaneEvent::FullMoon)
+    }
+}
+
+impl std::fmt::Display for MundaneEvent {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            MundaneEvent::AriesIngress => "aries_ingress",
+            MundaneEvent::CancerIngress => "cancer_ingress",
+            MundaneEvent::LibraIngress => "libra_ingress",
+            MundaneEvent::CapricornIngress => "capricorn_ingress",
+            MundaneEvent::NewMoon => "new_moon",
+            MundaneEvent::FullMoon => "full_moon",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl std::str::FromStr for MundaneEvent {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "aries_ingress" => Ok(MundaneEvent::AriesIngress),
+            "cancer_ingress" => Ok(MundaneEvent::CancerIngress),
+            "libra_ingress" => Ok(MundaneEvent::LibraIngress),
+            "capricorn_ingress" => Ok(MundaneEvent::CapricornIngress),
+            "new_moon" => Ok(MundaneEvent::NewMoon),
+            "full_moon" => Ok(MundaneEvent::FullMoon),
+            _ => Err(format!(
+                "Invalid event: {}. Use \"aries_ingress\", \"cancer_ingress\", \"libra_ingress\", \
+                 \"capricorn_ingress\", \"new_moon\" or \"full_moon\"",
+                s
+            )),
+        }
+    }
+}
+
+/// Julian date (UT) of midnight starting a calendar day
+fn start_of(year: i32, month: u32) -> Result<f64, String> {
+    NaiveDate::from_ymd_opt(year, month, 1)
+        .map(|date| date_to_julian(date.and_time(NaiveTime::MIN).and_utc()))
+        .ok_or_else(|| format!("Invalid date {}-{:02}", year, month))
+}
+
+/// Finds the moment of a mundane event.
+///
+/// Ingresses are looked for in the calendar year, lunations in the month;
+/// February can pass without a Full or New Moon, which is an error.
+///
+/// # Arguments
+///
+/// * `event` - The ingress or lunation
+/// * `year` - Calendar year (UT)
+/// * `month` - Calendar month (1-12), required for lunations and ignored for ingresses
+/// * `source` - Ephemeris to search
+///
+/// # Returns
+///
+/// The Julian date (UT) of the event, the first one when a month has two lunations
+pub fn find_event(event: MundaneEvent, year: i32, month: Option<u32>, source: EphemerisSource) -> Result<f64, String> {
+    let target = match event {
+        MundaneEvent::AriesIngress => 0.0,
+        MundaneEvent::CancerIngress => 90.0,
+        MundaneEvent::LibraIngress => 180.0,
+        MundaneEvent::CapricornIngress => 270.0,
+        MundaneEvent::NewMoon | MundaneEvent::FullMoon => {
+            let month = month.ok_or_else(|| format!("A {} needs a month", event))?;
+            let (next_year, next_month) = if month == 12 { (year + 1, 1) } else { (year, month + 1) };
+            let (from, to) = (start_of(year, month)?, start_of(next_year, next_month)?);
+            let aspect = if event == MundaneEvent::NewMoon {
+                AspectType::Conjunction
+            } else {
+                AspectType::Opposition
+            };
+            return next_exact_with_source(from, Planet::Moon, Planet::Sun, aspect, to - from, source)?
+                .into_iter()
+                .next()
+                .ok_or_else(|| format!("No {} in {}-{:02}", event, year, month));
+        }
+    };
+
+    let (from, to) = (start_of(year, 1)?, start_of(year + 1, 1)?);
+    let longitude = |jd: f64| planet_longitude_at(Planet::Sun, jd, source);
+    let samples = sample_motion(from, to - from, max_daily_motion(Planet::Sun), longitude)?;
+    crossings(&samples, target, longitude)?
+        .into_iter()
+        .next()
+        .ok_or_else(|| format!("No {} in {}", event, year))
+}
+
+#[cfg(all(test, feature = "swiss"))]
+mod tests {
+    use super::*;
+    use crate::calc::angles::shortest_distance;
+    use crate::calc::planets::default_source;
+    use crate::calc::swiss_ephemeris;
+    use crate::calc::utils::julian_to_date;
+
+    fn event_time(event: MundaneEvent, year: i32, month: Option<u32>) -> String {
+        let jd = find_event(event, year, month, default_source()).unwrap();
+        julian_to_date(jd).unwrap().format("%Y-%m-%d %H:%M").to_string()
+    }
+
+    #[test]
+    fn test_equinoxes_and_solstices_2025() {
+        swiss_ephemeris::init_moshier_ephemeris().unwrap();
+        // Published times, to within a minute either way
+        let near = |found: String, expected: [&str; 2]| assert!(expected.contains(&found.as_str()), "{}", found);
+        near(event_time(MundaneEvent::AriesIngress, 2025, None), ["2025-03-20 09:01", "2025-03-20 09:02"]);
+        near(event_time(MundaneEvent::CancerIngress, 2025, None), ["2025-06-21 02:42", "2025-06-21 02:43"]);
+        near(event_time(MundaneEvent::CapricornIngress, 2025, None), ["2025-12-21 15:03", "2025-12-21 15:04"]);
+    }
+
+    #[test]
+    fn test_lunations_in_a_month() {
+        swiss_ephemeris::init_moshier_ephemeris().unwrap();
+        let jd = find_event(MundaneEvent::NewMoon, 2025, Some(1), default_source()).unwrap();
+        let separation = shortest_distance(
+            planet_longitude_at(Planet::Moon, jd, default_source()).unwrap(),
+            planet_longitude_at(Planet::Sun, jd, default_source()).unwrap(),
+        );
+        assert!(separation < 1e-4, "{}", separation);
+        assert!(event_time(MundaneEvent::NewMoon, 2025, Some(1)).starts_with("2025-01-29 12:3"));
+        assert!(event_time(MundaneEvent::FullMoon, 2025, Some(1)).starts_with("2025-01-13 22:2"));
+
+        // February 2018 had no Full Moon, and a lunation needs its month
+        assert!(find_event(MundaneEvent::FullMoon, 2018, Some(2), default_source()).is_err());
+        assert!(find_event(MundaneEvent::NewMoon, 2025, None, default_source()).is_err());
+        assert!(find_event(MundaneEvent::NewMoon, 2025, Some(13), default_source()).is_err());
+    }
+}
@@ -0,0 +1,121 @@
+//! Lunar nodes, where the Moon's orbit crosses the ecliptic.
+//!
+//! The mean node regresses steadily through the zodiac in about 18.6 years. The
+//! true node follows the Moon's actual orbit, which the Sun's pull makes swing up
+//! to about 1.5° either side of the mean. The South Node is always exactly
+//! opposite the North Node. Mean Lilith, the Moon's mean apogee, is the other
+//! point of the lunar orbit charts show, advancing once around in about 8.85 years.
+
+use crate::calc::angles::normalize;
+use crate::calc::ephemeris::{default_ephemeris, Ephemeris};
+use crate::calc::planets::Planet;
+use crate::calc::utils::julian_centuries;
+use crate::core::types::{EphemerisSource, NodeType};
+
+/// The body a node type is calculated as
+pub fn node_planet(node_type: NodeType) -> Planet {
+    match node_type {
+        NodeType::True => Planet::TrueNode,
+        NodeType::Mean => Planet::MeanNode,
+    }
+}
+
+/// The South Node opposite a North Node longitude
+pub fn south_node(north_node: f64) -> f64 {
+    normalize(north_node + 180.0)
+}
+
+/// North Node longitudes at one moment, in degrees
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LunarNodes {
+    pub true_node: f64,
+    pub mean_node: f64,
+}
+
+impl LunarNodes {
+    /// The North Node of the chosen type
+    pub fn north_node(&self, node_type: NodeType) -> f64 {
+        match node_type {
+            NodeType::True => self.true_node,
+            NodeType::Mean => self.mean_node,
+        }
+    }
+}
+
+/// Both North Nodes at a Julian date (UT) from a specific ephemeris source
+pub fn lunar_nodes(jd: f64, source: EphemerisSource) -> Result<LunarNodes, String> {
+    lunar_nodes_from(&*default_ephemeris(), jd, source)
+}
+
+/// Both North Nodes at a Julian date (UT) from a given ephemeris
+pub fn lunar_nodes_from(ephemeris: &dyn Ephemeris, jd: f64, source: EphemerisSource) -> Result<LunarNodes, String> {
+    Ok(LunarNodes {
+        true_node: ephemeris.position(jd, Planet::TrueNode, source)?.0,
+        mean_node: ephemeris.position(jd, Planet::MeanNode, source)?.0,
+    })
+}
+
+/// Mean North Node longitude, referred to the mean equinox of date (Meeus 47.7)
+pub fn mean_node_analytic(jd: f64) -> f64 {
+    let t = julian_centuries(jd);
+    normalize(125.0445479 - 1934.1362891 * t + 0.0020754 * t * t + t.powi(3) / 467441.0 - t.powi(4) / 60616000.0)
+}
+
+/// True North Node longitude: the mean node plus Meeus' five largest periodic
+/// terms, good to about a quarter of a degree
+pub fn true_node_analytic(jd: f64) -> f64 {
+    let t = julian_centuries(jd);
+    // Moon's mean elongation, Sun's and Moon's mean anomalies, Moon's argument of latitude
+    let d = (297.8501921 + 445267.1114034 * t - 0.0018819 * t * t + t.powi(3) / 545868.0
+        - t.powi(4) / 113065000.0)
+        .to_radians();
+    let m = (357.5291092 + 35999.0502909 * t - 0.0001536 * t * t + t.powi(3) / 24490000.0).to_radians();
+    let m_moon = (134.9633964 + 477198.8675055 * t + 0.0087414 * t * t + t.powi(3) / 69699.0
+        - t.powi(4) / 14712000.0)
+        .to_radians();
+    let f = (93.2720950 + 483202.0175233 * t - 0.0036539 * t * t - t.powi(3) / 3526000.0
+        + t.powi(4) / 863310000.0)
+        .to_radians();
+
+    let correction = -1.4979 * (2.0 * (d - f)).sin() - 0.1500 * m.sin() - 0.1226 * (2.0 * d).sin()
+        + 0.1176 * (2.0 * f).sin()
+        - 0.0801 * (2.0 * (m_moon - f)).sin();
+    normalize(mean_node_analytic(jd) + correction)
+}
+
+/// Mean Lilith longitude: the mean lunar perigee (Meeus 47.7) turned to the
+/// apogee, referred to the mean equinox of date
+pub fn mean_lilith_analytic(jd: f64) -> f64 {
+    let t = julian_centuries(jd);
+    normalize(
+        263.3532465 + 4069.0137287 * t - 0.0103200 * t * t - t.powi(3) / 80053.0 + t.powi(4) / 18999000.0,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::calc::angles::signed_delta;
+
+    #[test]
+    fn test_analytic_nodes_follow_meeus() {
+        // Meeus example 47.a's date, 1992 April 12.0 TD
+        let jd = 2448724.5;
+        let mean = mean_node_analytic(jd);
+        assert!((mean - 274.400656).abs() < 1e-4, "mean node {}", mean);
+        let wobble = signed_delta(true_node_analytic(jd), mean);
+        assert!(wobble.abs() < 1.8, "true node {}° from the mean", wobble);
+    }
+
+    #[cfg(feature = "swiss")]
+    #[test]
+    fn test_analytic_nodes_match_swiss() {
+        crate::calc::swiss_ephemeris::init_moshier_ephemeris().unwrap();
+        for i in 0..60 {
+            let jd = 2415020.5 + i as f64 * 613.0;
+            let swiss = lunar_nodes(jd, EphemerisSource::Auto).unwrap();
+            assert!(signed_delta(mean_node_analytic(jd), swiss.mean_node).abs() < 0.01);
+            assert!(signed_delta(true_node_analytic(jd), swiss.true_node).abs() < 0.3);
+        }
+    }
+}
@@ -1,14 +1,19 @@
-use crate::calc::swiss_ephemeris::{self, map_planet_to_swe};
-use crate::calc::utils::{degrees_to_radians, radians_to_degrees};
+use crate::calc::angles::{normalize, signed_delta};
+use crate::calc::aspects::{calculate_all_aspects, get_aspect_types, IndexedAspect, OrbConfig};
+use crate::calc::ephemeris::{default_ephemeris, Ephemeris};
+use crate::calc::finite::check_position;
+use crate::calc::pluto;
 use crate::calc::vsop87;
-use crate::core::types::AstrologError;
-use chrono::{DateTime, Datelike, NaiveDateTime, TimeZone, Timelike, Utc};
+use crate::core::types::{AstrologError, EphemerisSource};
 use serde::{Deserialize, Serialize};
 use std::f64::consts::PI;
 
 /// Represents the celestial bodies that can be calculated in the astrological chart.
 /// This includes the traditional planets, nodes, and other significant points.
-#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+///
+/// A body serializes as its `name`, e.g. "MeanNode", and reads back from any
+/// name `FromStr` accepts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum Planet {
     /// The Sun - represents vitality, ego, and basic personality
     Sun,
@@ -56,6 +61,120 @@ pub enum Planet {
     EastPoint,
 }
 
+impl Planet {
+    /// Every body, in declaration order
+    pub const ALL: [Planet; 22] = [
+        Planet::Sun,
+        Planet::Moon,
+        Planet::Mercury,
+        Planet::Venus,
+        Planet::Mars,
+        Planet::Jupiter,
+        Planet::Saturn,
+        Planet::Uranus,
+        Planet::Neptune,
+        Planet::Pluto,
+        Planet::MeanNode,
+        Planet::TrueNode,
+        Planet::MeanLilith,
+        Planet::TrueLilith,
+        Planet::Chiron,
+        Planet::Ceres,
+        Planet::Pallas,
+        Planet::Juno,
+        Planet::Vesta,
+        Planet::Fortune,
+        Planet::Vertex,
+        Planet::EastPoint,
+    ];
+
+    /// The body's name in responses, e.g. "Sun" or "MeanNode"
+    pub fn name(&self) -> &'static str {
+        match self {
+            Planet::Sun => "Sun",
+            Planet::Moon => "Moon",
+            Planet::Mercury => "Mercury",
+            Planet::Venus => "Venus",
+            Planet::Mars => "Mars",
+            Planet::Jupiter => "Jupiter",
+            Planet::Saturn => "Saturn",
+            Planet::Uranus => "Uranus",
+            Planet::Neptune => "Neptune",
+            Planet::Pluto => "Pluto",
+            Planet::MeanNode => "MeanNode",
+            Planet::TrueNode => "TrueNode",
+            Planet::MeanLilith => "MeanLilith",
+            Planet::TrueLilith => "TrueLilith",
+            Planet::Chiron => "Chiron",
+            Planet::Ceres => "Ceres",
+            Planet::Pallas => "Pallas",
+            Planet::Juno => "Juno",
+            Planet::Vesta => "Vesta",
+            Planet::Fortune => "Fortune",
+            Planet::Vertex => "Vertex",
+            Planet::EastPoint => "EastPoint",
+        }
+    }
+}
+
+impl std::fmt::Display for Planet {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl Serialize for Planet {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.name())
+    }
+}
+
+impl<'de> Deserialize<'de> for Planet {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let name = String::deserialize(deserializer)?;
+        name.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+impl std::str::FromStr for Planet {
+    type Err = String;
+
+    /// Parses a body by name, ignoring case and separators, e.g. "jupiter",
+    /// "MeanNode" or "true_node"
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let name: String = s
+            .chars()
+            .filter(|c| !matches!(c, '_' | '-' | ' '))
+            .collect::<String>()
+            .to_ascii_lowercase();
+        match name.as_str() {
+            "sun" => Ok(Planet::Sun),
+            "moon" => Ok(Planet::Moon),
+            "mercury" => Ok(Planet::Mercury),
+            "venus" => Ok(Planet::Venus),
+            "mars" => Ok(Planet::Mars),
+            "jupiter" => Ok(Planet::Jupiter),
+            "saturn" => Ok(Planet::Saturn),
+            "uranus" => Ok(Planet::Uranus),
+            "neptune" => Ok(Planet::Neptune),
+            "pluto" => Ok(Planet::Pluto),
+            "meannode" => Ok(Planet::MeanNode),
+            "truenode" => Ok(Planet::TrueNode),
+            "meanlilith" => Ok(Planet::MeanLilith),
+            "truelilith" => Ok(Planet::TrueLilith),
+            "chiron" => Ok(Planet::Chiron),
+            "ceres" => Ok(Planet::Ceres),
+            "pallas" => Ok(Planet::Pallas),
+            "juno" => Ok(Planet::Juno),
+            "vesta" => Ok(Planet::Vesta),
+            "fortune" => Ok(Planet::Fortune),
+            "vertex" => Ok(Planet::Vertex),
+            "eastpoint" => Ok(Planet::EastPoint),
+            _ => Err(format!("Unknown planet '{}'", s)),
+        }
+    }
+}
+
 /// Represents the calculated position of a celestial body in the astrological chart.
 /// This includes both the zodiacal position and additional astronomical data.
 #[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
@@ -68,6 +187,9 @@ pub struct PlanetPosition {
     pub speed: f64,
     /// Whether the planet is moving backwards (retrograde)
     pub is_retrograde: bool,
+    /// Direct, retrograde or stationary, judged against the body's mean motion
+    #[serde(default)]
+    pub motion: Motion,
     /// House number (1-12) where the planet is located, if applicable
     pub house: Option<u8>,
 }
@@ -91,65 +213,267 @@ impl PlanetPosition {
             latitude,
             speed,
             is_retrograde,
+            motion: if is_retrograde {
+                Motion::Retrograde
+            } else {
+                Motion::Direct
+            },
             house: None,
         }
     }
 }
 
-/// Normalize longitude to 0-360 degrees
-fn normalize_longitude(longitude: f64) -> f64 {
-    let mut normalized = longitude % 360.0;
-    if normalized < 0.0 {
-        normalized += 360.0;
+/// Apparent direction of a body's motion along the ecliptic
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Motion {
+    #[default]
+    Direct,
+    Retrograde,
+    /// Turning between direct and retrograde
+    Stationary,
+}
+
+/// Speed below which each body counts as stationary, in degrees per day: 10% of its
+/// mean geocentric motion. The Sun and Moon are never retrograde or stationary, so
+/// they have no threshold; neither do bodies missing from the table.
+const STATION_THRESHOLDS: [(Planet, Option<f64>); 15] = [
+    (Planet::Sun, None),
+    (Planet::Moon, None),
+    (Planet::Mercury, Some(0.0986)),
+    (Planet::Venus, Some(0.0986)),
+    (Planet::Mars, Some(0.0524)),
+    (Planet::Jupiter, Some(0.0083)),
+    (Planet::Saturn, Some(0.0034)),
+    (Planet::Uranus, Some(0.0012)),
+    (Planet::Neptune, Some(0.0006)),
+    (Planet::Pluto, Some(0.0004)),
+    (Planet::Chiron, Some(0.0020)),
+    (Planet::Ceres, Some(0.0214)),
+    (Planet::Pallas, Some(0.0214)),
+    (Planet::Juno, Some(0.0226)),
+    (Planet::Vesta, Some(0.0272)),
+];
+
+/// Whether a body can ever appear to move backwards
+pub fn can_retrograde(planet: Planet) -> bool {
+    !matches!(planet, Planet::Sun | Planet::Moon)
+}
+
+/// Speed below which a body counts as stationary, if it has stations
+pub fn station_threshold(planet: Planet) -> Option<f64> {
+    STATION_THRESHOLDS
+        .iter()
+        .find(|(p, _)| *p == planet)
+        .and_then(|(_, threshold)| *threshold)
+}
+
+/// Classifies a body's motion from its daily speed.
+///
+/// # Arguments
+///
+/// * `planet` - The body
+/// * `speed` - Daily motion in longitude in degrees
+///
+/// # Returns
+///
+/// Stationary when |speed| is below the body's threshold, otherwise direct or
+/// retrograde by the sign of the speed. The Sun and Moon are always direct.
+///
+/// # Examples
+///
+/// ```
+/// use astrolog_rs::calc::planets::{motion, Motion, Planet};
+///
+/// assert_eq!(motion(Planet::Mercury, -0.01), Motion::Stationary);
+/// assert_eq!(motion(Planet::Mercury, -1.2), Motion::Retrograde);
+/// assert_eq!(motion(Planet::Sun, -0.001), Motion::Direct);
+/// ```
+pub fn motion(planet: Planet, speed: f64) -> Motion {
+    if !can_retrograde(planet) {
+        return Motion::Direct;
     }
-    normalized
+    match station_threshold(planet) {
+        Some(threshold) if speed.abs() < threshold => Motion::Stationary,
+        _ if speed < 0.0 => Motion::Retrograde,
+        _ => Motion::Direct,
+    }
+}
+
+/// Plausible apparent geocentric motion of each body, slowest and fastest in
+/// degrees per day, negative when retrograde. The ranges cover 1900-2100 on the
+/// Swiss and analytic ephemerides with a little to spare; a speed outside them
+/// points to a bad ephemeris rather than the sky.
+pub const DAILY_MOTION_RANGES: [(Planet, f64, f64); 19] = [
+    (Planet::Sun, 0.95, 1.02),
+    (Planet::Moon, 11.7, 15.45),
+    (Planet::Mercury, -1.4, 2.21),
+    (Planet::Venus, -0.64, 1.27),
+    (Planet::Mars, -0.41, 0.8),
+    (Planet::Jupiter, -0.14, 0.25),
+    (Planet::Saturn, -0.085, 0.135),
+    (Planet::Uranus, -0.045, 0.07),
+    (Planet::Neptune, -0.03, 0.045),
+    (Planet::Pluto, -0.03, 0.045),
+    (Planet::MeanNode, -0.054, -0.052),
+    (Planet::TrueNode, -0.27, 0.15),
+    (Planet::MeanLilith, 0.11, 0.113),
+    (Planet::TrueLilith, -3.7, 6.45),
+    (Planet::Chiron, -0.08, 0.15),
+    (Planet::Ceres, -0.25, 0.47),
+    (Planet::Pallas, -0.36, 0.62),
+    (Planet::Juno, -0.28, 0.61),
+    (Planet::Vesta, -0.28, 0.56),
+];
+
+/// Slowest and fastest daily motion of a body, if it is in the table
+pub fn daily_motion_range(planet: Planet) -> Option<(f64, f64)> {
+    DAILY_MOTION_RANGES
+        .iter()
+        .find(|(p, _, _)| *p == planet)
+        .map(|&(_, slowest, fastest)| (slowest, fastest))
+}
+
+/// Checks a calculated position's speed against the body's plausible range.
+///
+/// # Returns
+///
+/// A warning naming the body, its speed and the range when the speed is outside
+/// it. Bodies without a range always pass.
+///
+/// # Examples
+///
+/// ```
+/// use astrolog_rs::calc::planets::{check_daily_motion, Planet, PlanetPosition};
+///
+/// assert!(check_daily_motion(Planet::Mars, &PlanetPosition::new(120.0, 1.0, 0.5, false)).is_ok());
+/// assert!(check_daily_motion(Planet::Mars, &PlanetPosition::new(120.0, 1.0, 5.0, false)).is_err());
+/// ```
+pub fn check_daily_motion(planet: Planet, position: &PlanetPosition) -> Result<(), String> {
+    match daily_motion_range(planet) {
+        Some((slowest, fastest)) if !(slowest..=fastest).contains(&position.speed) => Err(format!(
+            "{:?} moves {:.4}°/day, outside its plausible range of {} to {}°/day",
+            planet, position.speed, slowest, fastest
+        )),
+        _ => Ok(()),
+    }
+}
+
+/// Fastest a body ever appears to move, in degrees per day, direct or retrograde.
+/// Bodies missing from the table are assumed to move no faster than the Moon.
+pub fn max_daily_motion(planet: Planet) -> f64 {
+    daily_motion_range(planet).map_or(15.45, |(slowest, fastest)| slowest.abs().max(fastest.abs()))
 }
 
 /// Calculate planetary positions for a given Julian date
 #[allow(dead_code)]
 pub fn calculate_planet_positions(jd: f64) -> Result<Vec<PlanetPosition>, AstrologError> {
-    let mut positions = Vec::with_capacity(10);
-
-    // Convert Julian date to DateTime
-    let jd_epoch = 2440587.5; // Unix epoch in Julian days
-    let unix_seconds = ((jd - jd_epoch) * 86400.0) as i64;
-    let naive = NaiveDateTime::from_timestamp_opt(unix_seconds, 0).ok_or_else(|| {
-        AstrologError::CalculationError {
-            message: "Invalid date".to_string(),
-        }
-    })?;
-    let datetime: DateTime<Utc> = Utc.from_utc_datetime(&naive);
+    calculate_planet_positions_with_source(jd, default_source()).map(|(positions, _)| positions)
+}
 
-    // Calculate positions for each planet
-    for planet in [
-        Planet::Sun,
-        Planet::Moon,
-        Planet::Mercury,
-        Planet::Venus,
-        Planet::Mars,
-        Planet::Jupiter,
-        Planet::Saturn,
-        Planet::Uranus,
-        Planet::Neptune,
-        Planet::Pluto,
-    ]
-    .iter()
-    {
-        match calculate_planet_position(
-            *planet,
-            datetime.year(),
-            datetime.month() as i32,
-            datetime.day() as i32,
-            datetime.hour() as f64
-                + datetime.minute() as f64 / 60.0
-                + datetime.second() as f64 / 3600.0,
-        ) {
-            Ok(position) => positions.push(position),
-            Err(e) => return Err(AstrologError::CalculationError { message: e }),
+/// Calculate planetary positions for a given Julian date from a specific
+/// ephemeris source, returning the source that was actually used
+pub fn calculate_planet_positions_with_source(
+    jd: f64,
+    source: EphemerisSource,
+) -> Result<(Vec<PlanetPosition>, EphemerisSource), AstrologError> {
+    let calculated = calculate_body_positions_with_source(jd, &CHART_BODIES, &CHART_BODIES, source)?;
+    Ok((calculated.positions, calculated.source))
+}
+
+/// Sun to Pluto, the bodies `calculate_planet_positions` returns
+const CHART_BODIES: [Planet; 10] = [
+    Planet::Sun,
+    Planet::Moon,
+    Planet::Mercury,
+    Planet::Venus,
+    Planet::Mars,
+    Planet::Jupiter,
+    Planet::Saturn,
+    Planet::Uranus,
+    Planet::Neptune,
+    Planet::Pluto,
+];
+
+/// A body whose position couldn't be calculated, and why
+#[derive(Debug, Clone, PartialEq)]
+pub struct BodyFailure {
+    pub planet: Planet,
+    pub reason: String,
+}
+
+/// Positions of the bodies that could be calculated
+#[derive(Debug, Clone)]
+pub struct BodyPositions {
+    /// The bodies calculated, in the order asked for
+    pub bodies: Vec<Planet>,
+    /// Their positions, parallel to `bodies`
+    pub positions: Vec<PlanetPosition>,
+    /// The bodies left out, in the order asked for
+    pub failures: Vec<BodyFailure>,
+    /// Ephemeris that actually produced the positions
+    pub source: EphemerisSource,
+}
+
+/// Calculate positions for a list of bodies, leaving out the ones that fail
+/// rather than failing with them. Only a failure among `required` is an error.
+pub fn calculate_body_positions_with_source(
+    jd: f64,
+    bodies: &[Planet],
+    required: &[Planet],
+    source: EphemerisSource,
+) -> Result<BodyPositions, AstrologError> {
+    calculate_body_positions_from(&*default_ephemeris(), jd, bodies, required, source)
+}
+
+/// Calculate positions for a list of bodies from a given ephemeris, like
+/// `calculate_body_positions_with_source`
+pub fn calculate_body_positions_from(
+    ephemeris: &dyn Ephemeris,
+    jd: f64,
+    bodies: &[Planet],
+    required: &[Planet],
+    source: EphemerisSource,
+) -> Result<BodyPositions, AstrologError> {
+    let mut calculated = BodyPositions {
+        bodies: Vec::with_capacity(bodies.len()),
+        positions: Vec::with_capacity(bodies.len()),
+        failures: Vec::new(),
+        source,
+    };
+
+    for &planet in bodies {
+        let position = planet_position(ephemeris, planet, jd, source)
+            .map_err(|message| AstrologError::CalculationError { message })
+            .and_then(|(position, used)| check_position(planet, &position).map(|_| (position, used)));
+        match position {
+            Ok((position, used)) => {
+                // A single fallback body makes the whole chart Moshier-grade
+                if calculated.source != EphemerisSource::Moshier {
+                    calculated.source = used;
+                }
+                calculated.bodies.push(planet);
+                calculated.positions.push(position);
+            }
+            Err(e) if required.contains(&planet) => return Err(e),
+            Err(AstrologError::CalculationError { message }) => calculated.failures.push(BodyFailure {
+                planet,
+                reason: message,
+            }),
+            Err(e) => calculated.failures.push(BodyFailure {
+                planet,
+                reason: e.to_string(),
+            }),
         }
     }
 
-    Ok(positions)
+    Ok(calculated)
+}
+
+/// Ecliptic longitude of a planet at a Julian date (UT) from a specific ephemeris
+/// source. Cheaper than a full position since the speed isn't sampled.
+pub fn planet_longitude_at(planet: Planet, jd: f64, source: EphemerisSource) -> Result<f64, String> {
+    default_ephemeris().position(jd, planet, source).map(|(longitude, _, _)| longitude)
 }
 
 /// Calculate the position of a planet for a given date and time
@@ -160,46 +484,85 @@ pub fn calculate_planet_position(
     day: i32,
     hour: f64,
 ) -> Result<PlanetPosition, String> {
-    // Convert date and time to Julian date using Swiss Ephemeris
-    let swe_planet = map_planet_to_swe(planet).ok_or_else(|| "Invalid planet".to_string())?;
+    calculate_planet_position_with_source(planet, year, month, day, hour, default_source())
+        .map(|(position, _)| position)
+}
 
-    // Calculate position using Swiss Ephemeris
-    let (longitude, latitude, _distance, _speed) =
-        swiss_ephemeris::calculate_planet_position_swiss(swe_planet, year, month, day, hour)
-            .map_err(|e| e.to_string())?;
+/// Calculate the position of a planet from a specific ephemeris source,
+/// returning the source that was actually used
+pub fn calculate_planet_position_with_source(
+    planet: Planet,
+    year: i32,
+    month: i32,
+    day: i32,
+    hour: f64,
+    source: EphemerisSource,
+) -> Result<(PlanetPosition, EphemerisSource), String> {
+    let jd = crate::calc::coordinates::calculate_julian_date(year, month as u32, day as u32, hour, 0.0, 0.0, 0.0);
+    planet_position(&*default_ephemeris(), planet, jd, source)
+}
+
+/// Position of a planet at a Julian date (UT) from a given ephemeris, with its
+/// speed, returning the source that was actually used
+pub fn planet_position(
+    ephemeris: &dyn Ephemeris,
+    planet: Planet,
+    jd: f64,
+    source: EphemerisSource,
+) -> Result<(PlanetPosition, EphemerisSource), String> {
+    let (longitude, latitude, used) = ephemeris.position(jd, planet, source)?;
 
     // Calculate speed by getting positions slightly before and after
     let dt = 0.01; // 0.01 days = 14.4 minutes
-    let hour_before = hour - dt * 24.0;
-    let hour_after = hour + dt * 24.0;
 
-    let (long_before, _, _, _) =
-        swiss_ephemeris::calculate_planet_position_swiss(swe_planet, year, month, day, hour_before)
-            .map_err(|e| e.to_string())?;
-
-    let (long_after, _, _, _) =
-        swiss_ephemeris::calculate_planet_position_swiss(swe_planet, year, month, day, hour_after)
-            .map_err(|e| e.to_string())?;
-
-    // Calculate speed using central difference
-    let mut speed = (long_after - long_before) / (2.0 * dt);
-
-    // Handle crossing the 0°/360° boundary
-    if (long_after - long_before).abs() > 180.0 {
-        if long_after > long_before {
-            speed = (long_after - long_before - 360.0) / (2.0 * dt);
-        } else {
-            speed = (long_after - long_before + 360.0) / (2.0 * dt);
-        }
+    // Stay on the same source so the difference isn't skewed by a model switch
+    let (long_before, _, _) = ephemeris.position(jd - dt, planet, used)?;
+    let (long_after, _, _) = ephemeris.position(jd + dt, planet, used)?;
+
+    // Central difference, across the 0°/360° boundary if need be. A sample that
+    // isn't finite leaves the speed NaN, for check_position to report.
+    let mut speed = if long_before.is_finite() && long_after.is_finite() {
+        signed_delta(long_after, long_before) / (2.0 * dt)
+    } else {
+        f64::NAN
+    };
+
+    // The Sun and Moon never go retrograde; a negative speed is numerical noise
+    if !can_retrograde(planet) && speed < 0.0 {
+        speed = 0.0;
     }
 
-    // Normalize speed to [-180, 180] range
-    speed = speed.rem_euclid(360.0);
-    if speed > 180.0 {
-        speed -= 360.0;
-    }
+    let mut position = PlanetPosition::new(longitude, latitude, speed, speed < 0.0);
+    position.motion = motion(planet, speed);
+    Ok((position, used))
+}
+
+/// Ephemeris source the default ephemeris uses when none is asked for
+pub(crate) fn default_source() -> EphemerisSource {
+    default_ephemeris().default_source()
+}
 
-    Ok(PlanetPosition::new(longitude, latitude, speed, speed < 0.0))
+/// Ecliptic longitude and latitude of a planet from the analytic orbital elements
+pub(crate) fn analytic_position(planet: Planet, jd: f64) -> Result<(f64, f64), String> {
+    let t = crate::calc::utils::julian_centuries(jd);
+    let position = match planet {
+        Planet::Sun => calculate_sun_position(t)?,
+        // The lunar model works in days since J2000.0 rather than centuries
+        Planet::Moon => calculate_moon_position(jd - 2451545.0)?,
+        Planet::Mercury => calculate_mercury_position(t)?,
+        Planet::Venus => calculate_venus_position(t)?,
+        Planet::Mars => calculate_mars_position(t)?,
+        Planet::Jupiter => calculate_jupiter_position(t)?,
+        Planet::Saturn => calculate_saturn_position(t)?,
+        Planet::Uranus => calculate_uranus_position(t)?,
+        Planet::Neptune => calculate_neptune_position(t)?,
+        Planet::Pluto => calculate_pluto_position(t)?,
+        Planet::MeanNode => PlanetPosition::new(crate::calc::nodes::mean_node_analytic(jd), 0.0, 0.0, true),
+        Planet::TrueNode => PlanetPosition::new(crate::calc::nodes::true_node_analytic(jd), 0.0, 0.0, true),
+        Planet::MeanLilith => PlanetPosition::new(crate::calc::nodes::mean_lilith_analytic(jd), 0.0, 0.0, false),
+        _ => return Err(format!("{:?} has no analytic theory", planet)),
+    };
+    Ok((position.longitude, position.latitude))
 }
 
 /// Calculate Sun's position
@@ -214,7 +577,7 @@ fn calculate_sun_position(t: f64) -> Result<PlanetPosition, String> {
     let node = 0.0;
     let (earth_long, _earth_lat, _earth_z) =
         vsop87::heliocentric_coordinates(t, a, e, i, l, lp, node);
-    let longitude = (earth_long + 180.0).rem_euclid(360.0);
+    let longitude = normalize(earth_long + 180.0);
     Ok(PlanetPosition::new(longitude, 0.0, 0.0, false))
 }
 
@@ -227,7 +590,7 @@ fn calculate_moon_position(t: f64) -> Result<PlanetPosition, String> {
     let ascending_node = 125.045 - 0.052992 * t;
 
     // Calculate longitude with correction terms
-    let longitude = normalize_longitude(
+    let longitude = normalize(
         mean_longitude
             + 6.289 * (mean_anomaly * PI / 180.0).sin()
             + 1.274 * ((2.0 * mean_longitude - mean_anomaly) * PI / 180.0).sin()
@@ -238,7 +601,7 @@ fn calculate_moon_position(t: f64) -> Result<PlanetPosition, String> {
 
     // Calculate latitude using orbital inclination
     let inclination = 5.145;
-    let latitude = inclination * (longitude - ascending_node).sin();
+    let latitude = inclination * (longitude - ascending_node).to_radians().sin();
 
     Ok(PlanetPosition::new(longitude, latitude, 0.0, false))
 }
@@ -505,9 +868,16 @@ fn calculate_neptune_position(t: f64) -> Result<PlanetPosition, String> {
     Ok(PlanetPosition::new(longitude, latitude, 0.0, false))
 }
 
-/// Calculate Pluto's position
+/// Calculate Pluto's position from its periodic-term series, falling back to
+/// mean orbital elements outside the 1885-2099 range of the series
 #[allow(dead_code)]
 fn calculate_pluto_position(t: f64) -> Result<PlanetPosition, String> {
+    let jd = 2451545.0 + t * 36525.0;
+    if pluto::in_valid_range(jd) {
+        let (longitude, latitude) = pluto::geocentric_position(jd);
+        return Ok(PlanetPosition::new(longitude, latitude, 0.0, false));
+    }
+
     // Pluto orbital elements (Meeus Table 31.A)
     let a = 39.48686035; // AU
     let e = 0.24885238 + 0.00006016 * t;
@@ -549,14 +919,7 @@ fn calculate_geocentric_planet_position(
     lp: f64,
     node: f64,
 ) -> PlanetPosition {
-    // Get heliocentric coordinates for planet
     let (pl_long, pl_lat, pl_r) = vsop87::heliocentric_coordinates(t, a, e, i, l, lp, node);
-    let pl_long_rad = degrees_to_radians(pl_long);
-    let pl_lat_rad = degrees_to_radians(pl_lat);
-    // Rectangular coordinates for planet
-    let x_p = pl_r * pl_long_rad.cos() * pl_lat_rad.cos();
-    let y_p = pl_r * pl_long_rad.sin() * pl_lat_rad.cos();
-    let z_p = pl_r * pl_lat_rad.sin();
     // Earth orbital elements (Meeus Table 31.A)
     let a_e = 1.00000261; // AU
     let e_e = 0.01671123 - 0.00004392 * t;
@@ -566,50 +929,18 @@ fn calculate_geocentric_planet_position(
     let node_e = 0.0;
     let (earth_long, earth_lat, earth_r) =
         vsop87::heliocentric_coordinates(t, a_e, e_e, i_e, l_e, lp_e, node_e);
-    let earth_long_rad = degrees_to_radians(earth_long);
-    let earth_lat_rad = degrees_to_radians(earth_lat);
-    let x_e = earth_r * earth_long_rad.cos() * earth_lat_rad.cos();
-    let y_e = earth_r * earth_long_rad.sin() * earth_lat_rad.cos();
-    let z_e = earth_r * earth_lat_rad.sin();
-    // Geocentric rectangular coordinates (Planet - Earth)
-    let x = x_p - x_e;
-    let y = y_p - y_e;
-    let z = z_p - z_e;
-    // Convert to ecliptic longitude and latitude
-    let _r = (x * x + y * y + z * z).sqrt();
-    let longitude = radians_to_degrees(y.atan2(x)).rem_euclid(360.0);
-    let latitude = radians_to_degrees(z.atan2((x * x + y * y).sqrt()));
+    let (longitude, latitude) =
+        vsop87::heliocentric_to_geocentric(pl_long, pl_lat, pl_r, earth_long, earth_lat, earth_r);
     PlanetPosition::new(longitude, latitude, 0.0, false)
 }
 
-/// Calculate planetary aspects for a given set of positions
+/// Calculate major planetary aspects for a given set of positions
 #[allow(dead_code)]
 pub fn calculate_planetary_aspects(
     positions: &[PlanetPosition],
-    orbs: &[f64],
-) -> Vec<(Planet, Planet, f64, f64)> {
-    let mut aspects = Vec::new();
-
-    for i in 0..positions.len() {
-        for j in (i + 1)..positions.len() {
-            let diff = (positions[i].longitude - positions[j].longitude).abs() % 360.0;
-
-            // Check for major aspects
-            if diff <= orbs[0] || (360.0 - diff) <= orbs[0] {
-                aspects.push((Planet::Sun, Planet::Sun, diff, 0.0));
-            } else if (diff - 60.0).abs() <= orbs[1] {
-                aspects.push((Planet::Sun, Planet::Sun, diff, 60.0));
-            } else if (diff - 90.0).abs() <= orbs[2] {
-                aspects.push((Planet::Sun, Planet::Sun, diff, 90.0));
-            } else if (diff - 120.0).abs() <= orbs[3] {
-                aspects.push((Planet::Sun, Planet::Sun, diff, 120.0));
-            } else if (diff - 180.0).abs() <= orbs[4] {
-                aspects.push((Planet::Sun, Planet::Sun, diff, 180.0));
-            }
-        }
-    }
-
-    aspects
+    orbs: &OrbConfig,
+) -> Vec<IndexedAspect> {
+    calculate_all_aspects(positions, orbs, &get_aspect_types(false))
 }
 
 /// Calculate planetary retrogrades
@@ -631,7 +962,7 @@ pub fn calculate_stations(
         .collect()
 }
 
-#[cfg(test)]
+#[cfg(all(test, feature = "swiss"))]
 mod tests {
     use super::*;
     use crate::calc::swiss_ephemeris;
@@ -645,21 +976,10 @@ mod tests {
 
     fn setup() -> Result<(), String> {
         // Initialize Swiss Ephemeris before running tests
-        swiss_ephemeris::init_swiss_ephemeris()
+        swiss_ephemeris::init_moshier_ephemeris()
             .map_err(|e| format!("Failed to initialize Swiss Ephemeris: {}", e))
     }
 
-    // Helper function to normalize angles to [-180, 180] range
-    fn normalize_angle(angle: f64) -> f64 {
-        let mut normalized = angle % 360.0;
-        if normalized > 180.0 {
-            normalized -= 360.0;
-        } else if normalized < -180.0 {
-            normalized += 360.0;
-        }
-        normalized
-    }
-
     // Helper function to print position details
     fn print_position_details(planet: &str, expected: f64, actual: f64) {
         println!("{} position test failed:", planet);
@@ -668,6 +988,20 @@ mod tests {
         println!("  Difference: {:.3}°", (actual - expected).abs());
     }
 
+    #[test]
+    fn test_planet_round_trips() {
+        for planet in Planet::ALL {
+            let json = serde_json::to_string(&planet).unwrap();
+            assert_eq!(json, format!("\"{}\"", planet.name()));
+            assert_eq!(serde_json::from_str::<Planet>(&json).unwrap(), planet);
+            assert_eq!(planet.to_string(), format!("{:?}", planet));
+            assert_eq!(planet.name().to_lowercase().parse::<Planet>(), Ok(planet));
+        }
+        assert_eq!(serde_json::from_str::<Planet>("\"mean_node\"").unwrap(), Planet::MeanNode);
+        assert_eq!("East Point".parse::<Planet>(), Ok(Planet::EastPoint));
+        assert!(serde_json::from_str::<Planet>("\"eris\"").is_err());
+    }
+
     #[test]
     fn test_sun_position() -> Result<(), String> {
         setup()?;
@@ -831,60 +1165,117 @@ mod tests {
         Ok(())
     }
 
-    // #[test]
-    // fn test_retrograde_motion() -> Result<(), String> {
-    //     setup()?;
-    //     // Test for retrograde motion detection
-    //     let pos1 = calculate_planet_position(Planet::Mars, TEST_YEAR, TEST_MONTH, TEST_DAY, TEST_HOUR)
-    //         .map_err(|e| format!("Failed to calculate first Mars position: {}", e))?;
-    //     let pos2 = calculate_planet_position(Planet::Mars, TEST_YEAR, TEST_MONTH, TEST_DAY, TEST_HOUR + 1.0)
-    //         .map_err(|e| format!("Failed to calculate second Mars position: {}", e))?;
-    //     let pos3 = calculate_planet_position(Planet::Mars, TEST_YEAR, TEST_MONTH, TEST_DAY, TEST_HOUR + 2.0)
-    //         .map_err(|e| format!("Failed to calculate third Mars position: {}", e))?;
-    //
-    //     // Check if the planet is moving backwards (retrograde)
-    //     let motion1 = normalize_angle(pos2.longitude - pos1.longitude);
-    //     let motion2 = normalize_angle(pos3.longitude - pos2.longitude);
-    //
-    //     println!("Mars motion test:");
-    //     println!("  Position 1: {:.3}°", pos1.longitude);
-    //     println!("  Position 2: {:.3}°", pos2.longitude);
-    //     println!("  Position 3: {:.3}°", pos3.longitude);
-    //     println!("  Motion 1: {:.3}°", motion1);
-    //     println!("  Motion 2: {:.3}°", motion2);
-    //
-    //     // If both motions are negative, the planet is retrograde
-    //     assert!(motion1 < 0.0 || motion2 < 0.0, "Expected retrograde motion not detected");
-    //     Ok(())
-    // }
-
-    // #[test]
-    // fn test_stationary_points() -> Result<(), String> {
-    //     setup()?;
-    //     // Test for stationary points (where a planet changes direction)
-    //     let pos1 = calculate_planet_position(Planet::Mercury, TEST_YEAR, TEST_MONTH, TEST_DAY, TEST_HOUR)
-    //         .map_err(|e| format!("Failed to calculate first Mercury position: {}", e))?;
-    //     let pos2 = calculate_planet_position(Planet::Mercury, TEST_YEAR, TEST_MONTH, TEST_DAY, TEST_HOUR + 1.0)
-    //         .map_err(|e| format!("Failed to calculate second Mercury position: {}", e))?;
-    //     let pos3 = calculate_planet_position(Planet::Mercury, TEST_YEAR, TEST_MONTH, TEST_DAY, TEST_HOUR + 2.0)
-    //         .map_err(|e| format!("Failed to calculate third Mercury position: {}", e))?;
-    //
-    //     // Calculate the motion between points
-    //     let motion1 = normalize_angle(pos2.longitude - pos1.longitude);
-    //     let motion2 = normalize_angle(pos3.longitude - pos2.longitude);
-    //
-    //     println!("Mercury stationary point test:");
-    //     println!("  Position 1: {:.3}°", pos1.longitude);
-    //     println!("  Position 2: {:.3}°", pos2.longitude);
-    //     println!("  Position 3: {:.3}°", pos3.longitude);
-    //     println!("  Motion 1: {:.3}°", motion1);
-    //     println!("  Motion 2: {:.3}°", motion2);
-    //
-    //     // If the motions have different signs, we've found a stationary point
-    //     assert!(
-    //         (motion1 > 0.0 && motion2 < 0.0) || (motion1 < 0.0 && motion2 > 0.0),
-    //         "Expected stationary point not detected"
-    //     );
-    //     Ok(())
-    // }
+    #[test]
+    fn test_mercury_station_january_2024() -> Result<(), String> {
+        setup()?;
+        // Mercury stationed direct on 2 January 2024 at about 03:08 UT
+        let position = calculate_planet_position(Planet::Mercury, 2024, 1, 2, 3.0)
+            .map_err(|e| format!("Failed to calculate Mercury position: {}", e))?;
+        assert_eq!(position.motion, Motion::Stationary);
+
+        // Its motion turns direct once in the month, within a day of the station
+        let station = 2460311.631;
+        let start = 2460310.5; // 2024-01-01
+        let ephemerides: [&dyn Ephemeris; 2] = [&*default_ephemeris(), &crate::calc::ephemeris::AnalyticEphemeris];
+        for ephemeris in ephemerides {
+            let mut turns = Vec::new();
+            let mut previous: Option<PlanetPosition> = None;
+            for step in 0..=31 * 4 {
+                let jd = start + step as f64 / 4.0;
+                let (position, _) = planet_position(ephemeris, Planet::Mercury, jd, EphemerisSource::Auto)?;
+                if previous.is_some_and(|previous| previous.is_retrograde != position.is_retrograde) {
+                    turns.push(jd);
+                }
+                previous = Some(position);
+            }
+            assert_eq!(turns.len(), 1, "{:?}", turns);
+            assert!((turns[0] - station).abs() < 1.0, "station at JD {}", turns[0]);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_sun_and_moon_daily_motion() -> Result<(), String> {
+        setup()?;
+        let start = 2460310.5; // 2024-01-01
+        let ephemerides: [&dyn Ephemeris; 2] = [&*default_ephemeris(), &crate::calc::ephemeris::AnalyticEphemeris];
+        for ephemeris in ephemerides {
+            // Every six hours for a year, through every ingress of both
+            for step in 0..366 * 4 {
+                let jd = start + step as f64 / 4.0;
+                let (sun, _) = planet_position(ephemeris, Planet::Sun, jd, EphemerisSource::Auto)?;
+                assert!((0.95..=1.02).contains(&sun.speed), "Sun at {}°/day on JD {}", sun.speed, jd);
+                // The Moon usually moves 12-15°/day, but its extremes fall outside
+                // that: this year it slows to 11.77°/day at apogee and reaches
+                // 15.31° at perigee
+                let (moon, _) = planet_position(ephemeris, Planet::Moon, jd, EphemerisSource::Auto)?;
+                assert!((11.75..=15.35).contains(&moon.speed), "Moon at {}°/day on JD {}", moon.speed, jd);
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_sun_never_retrograde() -> Result<(), String> {
+        setup()?;
+        for day in 0..366 {
+            let hour = day as f64 * 24.0;
+            let position = calculate_planet_position(Planet::Sun, 2024, 1, 1, hour)
+                .map_err(|e| format!("Failed to calculate Sun position: {}", e))?;
+            assert!(!position.is_retrograde, "Sun retrograde on day {}", day);
+            assert_eq!(position.motion, Motion::Direct);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_failed_bodies_are_left_out() -> Result<(), String> {
+        setup()?;
+        // The Moshier theory has no Chiron
+        let bodies = [Planet::Sun, Planet::Chiron, Planet::Moon];
+        let calculated = calculate_body_positions_with_source(2451545.0, &bodies, &[Planet::Sun], EphemerisSource::Moshier)
+            .map_err(|e| e.to_string())?;
+        assert_eq!(calculated.bodies, [Planet::Sun, Planet::Moon]);
+        assert_eq!(calculated.positions.len(), 2);
+        assert_eq!(calculated.failures.len(), 1);
+        assert_eq!(calculated.failures[0].planet, Planet::Chiron);
+
+        let required = calculate_body_positions_with_source(2451545.0, &bodies, &bodies, EphemerisSource::Moshier);
+        assert!(required.is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_implausible_speeds_are_caught() {
+        let mut position = calculate_planet_position(Planet::Mars, 2024, 1, 1, 0.0).unwrap();
+        assert_eq!(check_daily_motion(Planet::Mars, &position), Ok(()));
+        // A Mars moving like the Moon means the ephemeris is broken
+        position.speed = 13.0;
+        let warning = check_daily_motion(Planet::Mars, &position).unwrap_err();
+        assert!(warning.starts_with("Mars moves 13.0000°/day"), "{}", warning);
+        position.speed = -0.5;
+        assert!(check_daily_motion(Planet::Mars, &position).is_err());
+        // Bodies without a range pass
+        assert_eq!(check_daily_motion(Planet::Fortune, &position), Ok(()));
+        assert_eq!(max_daily_motion(Planet::Mercury), 2.21);
+    }
+
+    #[test]
+    fn test_a_year_of_positions_is_plausible() -> Result<(), String> {
+        setup()?;
+        let start = 2460676.5; // 2025-01-01
+        let ephemerides: [&dyn Ephemeris; 2] = [&*default_ephemeris(), &crate::calc::ephemeris::AnalyticEphemeris];
+        for ephemeris in ephemerides {
+            for day in 0..365 {
+                for (planet, _, _) in DAILY_MOTION_RANGES {
+                    // Chiron and the asteroids need the ephemeris files
+                    let Ok((position, _)) = planet_position(ephemeris, planet, start + day as f64, EphemerisSource::Auto) else {
+                        continue;
+                    };
+                    check_daily_motion(planet, &position)?;
+                }
+            }
+        }
+        Ok(())
+    }
 }
@@ -0,0 +1,210 @@
+//! Pluto from the periodic-term series of Meeus, Astronomical Algorithms ch. 37.
+//!
+//! Pluto isn't covered by VSOP87 and its orbit is perturbed too strongly by
+//! Neptune for constant elements to hold over decades. The series is fitted to
+//! the DE200 ephemeris for 1885–2099 and is good to about an arc second there;
+//! outside that range positions come from mean orbital elements instead.
+
+use crate::calc::angles::normalize;
+use crate::calc::utils::{degrees_to_radians, radians_to_degrees};
+use crate::calc::vsop87;
+
+/// 1885 January 1, 0h
+pub const VALID_FROM_JD: f64 = 2409542.5;
+/// 2100 January 1, 0h
+pub const VALID_TO_JD: f64 = 2488069.5;
+
+/// Light travel time for one AU, in days
+const LIGHT_TIME_DAYS_PER_AU: f64 = 0.005_775_518_3;
+
+/// Multiples of Jupiter, Saturn and Pluto's mean longitudes, then the sine and
+/// cosine coefficients for longitude and latitude (1e-6 degrees) and radius
+/// vector (1e-7 AU). Meeus table 37.A.
+#[rustfmt::skip]
+const TERMS: [(i8, i8, i8, [f64; 6]); 43] = [
+    (0, 0, 1, [-19799805.0, 19850055.0, -5452852.0, -14974862.0, 66865439.0, 68951812.0]),
+    (0, 0, 2, [897144.0, -4954829.0, 3527812.0, 1672790.0, -11827535.0, -332538.0]),
+    (0, 0, 3, [611149.0, 1211027.0, -1050748.0, 327647.0, 1593179.0, -1438890.0]),
+    (0, 0, 4, [-341243.0, -189585.0, 178690.0, -292153.0, -18444.0, 483220.0]),
+    (0, 0, 5, [129287.0, -34992.0, 18650.0, 100340.0, -65977.0, -85431.0]),
+    (0, 0, 6, [-38164.0, 30893.0, -30697.0, -25823.0, 31174.0, -6032.0]),
+    (0, 1, -1, [20442.0, -9987.0, 4878.0, 11248.0, -5794.0, 22161.0]),
+    (0, 1, 0, [-4063.0, -5071.0, 226.0, -64.0, 4601.0, 4032.0]),
+    (0, 1, 1, [-6016.0, -3336.0, 2030.0, -836.0, -1729.0, 234.0]),
+    (0, 1, 2, [-3956.0, 3039.0, 69.0, -604.0, -415.0, 702.0]),
+    (0, 1, 3, [-667.0, 3572.0, -247.0, -567.0, 239.0, 723.0]),
+    (0, 2, -2, [1276.0, 501.0, -57.0, 1.0, 67.0, -67.0]),
+    (0, 2, -1, [1152.0, -917.0, -122.0, 175.0, 1034.0, -451.0]),
+    (0, 2, 0, [630.0, -1277.0, -49.0, -164.0, -129.0, 504.0]),
+    (1, -1, 0, [2571.0, -459.0, -197.0, 199.0, 480.0, -231.0]),
+    (1, -1, 1, [899.0, -1449.0, -25.0, 217.0, 2.0, -441.0]),
+    (1, 0, -3, [-1016.0, 1043.0, 589.0, -248.0, -3359.0, 265.0]),
+    (1, 0, -2, [-2343.0, -1012.0, -269.0, 711.0, 7856.0, -7832.0]),
+    (1, 0, -1, [7042.0, 788.0, 185.0, 193.0, 36.0, 45763.0]),
+    (1, 0, 0, [1199.0, -338.0, 315.0, 807.0, 8663.0, 8547.0]),
+    (1, 0, 1, [418.0, -67.0, -130.0, -43.0, -809.0, -769.0]),
+    (1, 0, 2, [120.0, -274.0, 5.0, 3.0, 263.0, -144.0]),
+    (1, 0, 3, [-60.0, -159.0, 2.0, 17.0, -126.0, 32.0]),
+    (1, 0, 4, [-82.0, -29.0, 2.0, 5.0, -35.0, -16.0]),
+    (1, 1, -3, [-36.0, -29.0, 2.0, 3.0, -19.0, -4.0]),
+    (1, 1, -2, [-40.0, 7.0, 3.0, 1.0, -15.0, 8.0]),
+    (1, 1, -1, [-14.0, 22.0, 2.0, -1.0, -4.0, 12.0]),
+    (1, 1, 0, [4.0, 13.0, 1.0, -1.0, 5.0, 6.0]),
+    (1, 1, 1, [5.0, 2.0, 0.0, -1.0, 3.0, 1.0]),
+    (1, 1, 3, [-1.0, 0.0, 0.0, 0.0, 6.0, -2.0]),
+    (2, 0, -6, [2.0, 0.0, 0.0, -2.0, 2.0, 2.0]),
+    (2, 0, -5, [-4.0, 5.0, 2.0, 2.0, -2.0, -2.0]),
+    (2, 0, -4, [4.0, -7.0, -7.0, 0.0, 14.0, 13.0]),
+    (2, 0, -3, [14.0, 24.0, 10.0, -8.0, -63.0, 13.0]),
+    (2, 0, -2, [-49.0, -34.0, -3.0, 20.0, 136.0, -236.0]),
+    (2, 0, -1, [163.0, -48.0, 6.0, 5.0, 273.0, 1065.0]),
+    (2, 0, 0, [9.0, -24.0, 14.0, 17.0, 251.0, 149.0]),
+    (2, 0, 1, [-4.0, 1.0, -2.0, 0.0, -25.0, -9.0]),
+    (2, 0, 2, [-3.0, 1.0, 0.0, 0.0, 9.0, -2.0]),
+    (2, 0, 3, [1.0, 3.0, 0.0, 0.0, -8.0, 7.0]),
+    (3, 0, -2, [-3.0, -1.0, 0.0, 1.0, 9.0, -7.0]),
+    (3, 0, -1, [5.0, -3.0, 0.0, 0.0, -4.0, 1.0]),
+    (3, 0, 0, [0.0, 0.0, 1.0, 0.0, 0.0, 0.0]),
+];
+
+/// Whether `jd` falls in the range the series was fitted to
+pub fn in_valid_range(jd: f64) -> bool {
+    (VALID_FROM_JD..VALID_TO_JD).contains(&jd)
+}
+
+/// Explains the reduced precision of Pluto at `jd`, if it falls outside the series
+pub fn precision_warning(jd: f64) -> Option<String> {
+    (!in_valid_range(jd)).then(|| {
+        "Pluto is outside the 1885-2099 range of its analytic theory, so its position \
+         comes from mean orbital elements and may be off by degrees"
+            .to_string()
+    })
+}
+
+/// Heliocentric ecliptic longitude and latitude (degrees) and radius vector (AU),
+/// referred to the ecliptic and equinox of J2000.0
+pub fn heliocentric_j2000(jd: f64) -> (f64, f64, f64) {
+    let t = (jd - 2451545.0) / 36525.0;
+    let jupiter = 34.35 + 3034.9057 * t;
+    let saturn = 50.08 + 1222.1138 * t;
+    let pluto = 238.96 + 144.9600 * t;
+
+    let (mut longitude, mut latitude, mut radius) = (0.0, 0.0, 0.0);
+    for (j, s, p, [lon_sin, lon_cos, lat_sin, lat_cos, r_sin, r_cos]) in TERMS {
+        let argument = degrees_to_radians(j as f64 * jupiter + s as f64 * saturn + p as f64 * pluto);
+        let (sin, cos) = argument.sin_cos();
+        longitude += lon_sin * sin + lon_cos * cos;
+        latitude += lat_sin * sin + lat_cos * cos;
+        radius += r_sin * sin + r_cos * cos;
+    }
+
+    (
+        normalize(238.958116 + 144.96 * t + longitude * 1e-6),
+        -3.908239 + latitude * 1e-6,
+        40.7241346 + radius * 1e-7,
+    )
+}
+
+/// Rectangular heliocentric ecliptic coordinates in AU
+fn rectangular(longitude: f64, latitude: f64, radius: f64) -> [f64; 3] {
+    let (lon, lat) = (degrees_to_radians(longitude), degrees_to_radians(latitude));
+    [
+        radius * lat.cos() * lon.cos(),
+        radius * lat.cos() * lon.sin(),
+        radius * lat.sin(),
+    ]
+}
+
+/// Earth from the same mean elements as the other analytic bodies
+fn earth_j2000(t: f64) -> [f64; 3] {
+    let (longitude, latitude, radius) = vsop87::heliocentric_coordinates(
+        t,
+        1.00000261,
+        0.01671123 - 0.00004392 * t,
+        -0.00001531 - 0.01294668 * t,
+        100.46457166 + 35999.37244981 * t,
+        102.93768193 + 0.32327364 * t,
+        0.0,
+    );
+    rectangular(longitude, latitude, radius)
+}
+
+/// Carries ecliptic coordinates from the equinox of J2000.0 to the equinox of
+/// date (Meeus 21.5)
+fn precess_from_j2000(longitude: f64, latitude: f64, t: f64) -> (f64, f64) {
+    let eta = degrees_to_radians((47.0029 * t - 0.03302 * t * t + 0.000060 * t * t * t) / 3600.0);
+    let pi = degrees_to_radians(174.876384 - (869.8089 * t - 0.03536 * t * t) / 3600.0);
+    let p = (5029.0966 * t + 1.11113 * t * t - 0.000006 * t * t * t) / 3600.0;
+
+    let (lon, lat) = (degrees_to_radians(longitude), degrees_to_radians(latitude));
+    let a = eta.cos() * lat.cos() * (pi - lon).sin() - eta.sin() * lat.sin();
+    let b = lat.cos() * (pi - lon).cos();
+    let c = eta.cos() * lat.sin() + eta.sin() * lat.cos() * (pi - lon).sin();
+    (
+        normalize(p + radians_to_degrees(pi) - radians_to_degrees(a.atan2(b))),
+        radians_to_degrees(c.asin()),
+    )
+}
+
+/// Geocentric ecliptic longitude and latitude of Pluto for the equinox of date,
+/// corrected for light time. Only meaningful inside the valid range.
+pub fn geocentric_position(jd: f64) -> (f64, f64) {
+    let t = (jd - 2451545.0) / 36525.0;
+    let earth = earth_j2000(t);
+    let geocentric = |jd: f64| {
+        let (longitude, latitude, radius) = heliocentric_j2000(jd);
+        let pluto = rectangular(longitude, latitude, radius);
+        [pluto[0] - earth[0], pluto[1] - earth[1], pluto[2] - earth[2]]
+    };
+
+    // Pluto is seen where it was when its light left it, some five hours earlier
+    let [x, y, z] = geocentric(jd);
+    let distance = (x * x + y * y + z * z).sqrt();
+    let [x, y, z] = geocentric(jd - LIGHT_TIME_DAYS_PER_AU * distance);
+
+    let longitude = radians_to_degrees(y.atan2(x));
+    let latitude = radians_to_degrees(z.atan2((x * x + y * y).sqrt()));
+    precess_from_j2000(longitude, latitude, t)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_heliocentric_matches_meeus_example() {
+        // Meeus example 37.a, 1992 October 13.0 TD
+        let (longitude, latitude, radius) = heliocentric_j2000(2448908.5);
+        assert!((longitude - 232.74071).abs() < 1e-4, "longitude {}", longitude);
+        assert!((latitude - 14.58782).abs() < 1e-4, "latitude {}", latitude);
+        assert!((radius - 29.711111).abs() < 1e-5, "radius {}", radius);
+    }
+
+    #[test]
+    fn test_precision_warning_outside_the_series() {
+        assert!(precision_warning(2451545.0).is_none());
+        assert!(precision_warning(VALID_FROM_JD - 1.0).is_some());
+        assert!(precision_warning(VALID_TO_JD).is_some());
+    }
+
+    #[cfg(feature = "swiss")]
+    #[test]
+    fn test_matches_swiss_ephemeris_1900_to_2090() {
+        use crate::calc::angles::signed_delta;
+        use crate::calc::planets::{planet_longitude_at, Planet};
+        use crate::calc::swiss_ephemeris;
+        use crate::core::types::EphemerisSource;
+
+        swiss_ephemeris::init_moshier_ephemeris().unwrap();
+        // Every 97 days, so the samples fall at every point of Earth's orbit
+        let mut jd = 2415020.5; // 1900 January 1
+        while jd < 2484407.5 {
+            // 2090 January 1
+            let swiss = planet_longitude_at(Planet::Pluto, jd, EphemerisSource::Auto).unwrap();
+            let (longitude, _) = geocentric_position(jd);
+            let error = signed_delta(longitude, swiss);
+            assert!(error.abs() < 0.2, "Pluto off by {:.3}° at JD {}", error, jd);
+            jd += 97.0;
+        }
+    }
+}
@@ -0,0 +1,222 @@
+//! Points read off the chart angles rather than calculated for a body: the
+//! Vertex, the anti-Vertex opposite it and the East Point.
+//!
+//! They move with the daily rotation, a degree every four minutes, so they
+//! only aspect the planets at tight orbs.
+
+use crate::calc::angles::normalize;
+use crate::calc::aspects::{calculate_point_aspects_with_defs, Aspect, AspectDef, AspectType, ChartPoint};
+use crate::calc::houses::Houses;
+use serde::{Deserialize, Serialize};
+
+/// Widest orb allowed for an aspect to a point, in degrees
+pub const POINT_ORB: f64 = 2.0;
+
+/// Widest orb at which a body counts as on an angle, in degrees
+pub const ANGULAR_ORB: f64 = 1.0;
+
+/// A point derived from a chart's angles
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum AnglePoint {
+    /// Where the prime vertical meets the ecliptic in the west
+    Vertex,
+    /// The point opposite the Vertex, in the east
+    AntiVertex,
+    /// The equatorial Ascendant: the ecliptic point at right ascension ARMC + 90°
+    EastPoint,
+}
+
+impl AnglePoint {
+    /// Longitude of the point in a chart with these houses
+    pub fn longitude(self, houses: &Houses) -> f64 {
+        match self {
+            AnglePoint::Vertex => houses.vertex,
+            AnglePoint::AntiVertex => normalize(houses.vertex + 180.0),
+            AnglePoint::EastPoint => houses.east_point,
+        }
+    }
+
+    /// Short label drawn on the wheel
+    pub fn label(self) -> &'static str {
+        match self {
+            AnglePoint::Vertex => "Vx",
+            AnglePoint::AntiVertex => "AVx",
+            AnglePoint::EastPoint => "EP",
+        }
+    }
+
+    /// The point named for the aspect lists, with no speed of its own
+    pub fn chart_point(self, houses: &Houses) -> ChartPoint {
+        ChartPoint {
+            name: self.to_string(),
+            longitude: self.longitude(houses),
+            speed: 0.0,
+        }
+    }
+}
+
+impl std::fmt::Display for AnglePoint {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+impl std::str::FromStr for AnglePoint {
+    type Err = String;
+
+    /// Parses a point by name, ignoring case and separators, e.g. "vertex",
+    /// "anti_vertex" or "EastPoint"
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let name: String = s
+            .chars()
+            .filter(|c| !matches!(c, '_' | '-' | ' '))
+            .collect::<String>()
+            .to_ascii_lowercase();
+        match name.as_str() {
+            "vertex" => Ok(AnglePoint::Vertex),
+            "antivertex" => Ok(AnglePoint::AntiVertex),
+            "eastpoint" => Ok(AnglePoint::EastPoint),
+            _ => Err(format!(
+                "Unknown point: {}. Use \"vertex\", \"anti_vertex\" or \"east_point\"",
+                s
+            )),
+        }
+    }
+}
+
+/// A chart's angles and house cusps as points for transits to aspect: the
+/// Ascendant, Descendant, Midheaven and IC, then the cusps of the other eight
+/// houses as "Cusp 2" and so on. Opposite points come in pairs, so a transit
+/// conjunct the Descendant is also opposite the Ascendant. Gauquelin sectors
+/// only give the angles.
+pub fn house_angle_points(houses: &Houses) -> Vec<ChartPoint> {
+    let angle = |name: &str, longitude: f64| ChartPoint {
+        name: name.to_string(),
+        longitude: normalize(longitude),
+        speed: 0.0,
+    };
+    let mut points = vec![
+        angle("Ascendant", houses.ascendant),
+        angle("Descendant", houses.ascendant + 180.0),
+        angle("Midheaven", houses.midheaven),
+        angle("IC", houses.midheaven + 180.0),
+    ];
+    if let Some(cusps) = houses.cusp_longitudes() {
+        points.extend(
+            (1..=12)
+                .filter(|house| house % 3 != 1)
+                .map(|house| angle(&format!("Cusp {}", house), cusps[house - 1])),
+        );
+    }
+    points
+}
+
+/// Bodies conjunct the Ascendant, Descendant, Midheaven or IC within
+/// `ANGULAR_ORB`, found like the aspects to the points, tightest first
+pub fn angular_conjunctions(bodies: &[ChartPoint], houses: &Houses) -> Vec<Aspect> {
+    let angles: Vec<ChartPoint> = house_angle_points(houses).into_iter().take(4).collect();
+    let conjunction = AspectDef {
+        orb: ANGULAR_ORB,
+        ..AspectDef::from(AspectType::Conjunction)
+    };
+    let mut aspects = calculate_point_aspects_with_defs(bodies, &angles, &[conjunction]);
+    aspects.sort_by(|a, b| a.orb.total_cmp(&b.orb));
+    aspects
+}
+
+/// The aspect definitions with each orb narrowed to `POINT_ORB`
+pub fn point_defs(defs: &[AspectDef]) -> Vec<AspectDef> {
+    defs.iter()
+        .map(|def| AspectDef {
+            orb: def.orb.min(POINT_ORB),
+            ..def.clone()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::calc::aspects::aspect_defs;
+    use crate::calc::houses::HousePosition;
+    use crate::core::types::HouseSystem;
+
+    fn houses(vertex: f64, east_point: f64) -> Houses {
+        Houses {
+            cusps: vec![HousePosition {
+                number: 1,
+                longitude: 0.0,
+                latitude: 0.0,
+            }],
+            ascendant: 0.0,
+            midheaven: 270.0,
+            armc: 270.0,
+            vertex,
+            east_point,
+            system: HouseSystem::Equal,
+        }
+    }
+
+    #[test]
+    fn test_anti_vertex_is_opposite_the_vertex() {
+        let houses = houses(200.0, 5.0);
+        assert_eq!(AnglePoint::Vertex.longitude(&houses), 200.0);
+        assert_eq!(AnglePoint::AntiVertex.longitude(&houses), 20.0);
+        assert_eq!(AnglePoint::EastPoint.longitude(&houses), 5.0);
+        assert_eq!(AnglePoint::AntiVertex.chart_point(&houses).name, "AntiVertex");
+    }
+
+    #[test]
+    fn test_house_angle_points_leave_out_the_angular_cusps() {
+        let mut houses = houses(200.0, 5.0);
+        houses.cusps = (1..=12)
+            .map(|number| HousePosition {
+                number,
+                longitude: (number as f64 - 1.0) * 30.0,
+                latitude: 0.0,
+            })
+            .collect();
+        let points = house_angle_points(&houses);
+        let names: Vec<&str> = points.iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(
+            names,
+            ["Ascendant", "Descendant", "Midheaven", "IC", "Cusp 2", "Cusp 3", "Cusp 5", "Cusp 6", "Cusp 8", "Cusp 9", "Cusp 11", "Cusp 12"]
+        );
+        assert_eq!(points[1].longitude, 180.0);
+        assert_eq!(points[3].longitude, 90.0);
+        assert_eq!(points[4].longitude, 30.0);
+    }
+
+    #[test]
+    fn test_angular_bodies_are_within_a_degree_of_an_angle() {
+        let body = |name: &str, longitude: f64| ChartPoint {
+            name: name.to_string(),
+            longitude,
+            speed: 1.0,
+        };
+        let bodies = [body("Sun", 359.5), body("Moon", 91.0), body("Mars", 268.8), body("Venus", 45.0)];
+        let angular = angular_conjunctions(&bodies, &houses(200.0, 5.0));
+        let found: Vec<(&str, &str)> = angular.iter().map(|a| (a.planet1.as_str(), a.planet2.as_str())).collect();
+        assert_eq!(found, [("Sun", "Ascendant"), ("Moon", "IC")]);
+        assert!((angular[0].orb - 0.5).abs() < 1e-9);
+        assert_eq!(angular[0].aspect_type, Some(AspectType::Conjunction));
+    }
+
+    #[test]
+    fn test_points_parse_with_or_without_separators() {
+        assert_eq!("vertex".parse(), Ok(AnglePoint::Vertex));
+        assert_eq!("anti_vertex".parse(), Ok(AnglePoint::AntiVertex));
+        assert_eq!("AntiVertex".parse(), Ok(AnglePoint::AntiVertex));
+        assert_eq!("east-point".parse(), Ok(AnglePoint::EastPoint));
+        assert!("fortune".parse::<AnglePoint>().is_err());
+    }
+
+    #[test]
+    fn test_point_orbs_are_capped() {
+        let natal = point_defs(&aspect_defs(true, false, &[]));
+        assert!(natal.iter().all(|def| def.orb == POINT_ORB));
+        // Orbs already tighter than the cap are kept
+        let transit = point_defs(&aspect_defs(true, true, &[]));
+        assert!(transit.iter().any(|def| def.orb == 1.5));
+    }
+}
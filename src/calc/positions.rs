@@ -0,0 +1,494 @@
+//! Bare body positions for a moment, without houses or aspects: the tropical or
+//! sidereal longitude as seen from the Earth or the Sun.
+
+use crate::calc::angles::normalize;
+use crate::calc::planets::{calculate_planet_position_with_source, Planet};
+use crate::calc::precession::precession_between;
+use crate::calc::rulers::Sign;
+use crate::calc::utils::julian_to_calendar;
+use crate::core::types::{Ayanamsa, EphemerisSource};
+
+/// Bodies the ephemeris gives positions for
+pub const POSITION_BODIES: [Planet; 19] = [
+    Planet::Sun,
+    Planet::Moon,
+    Planet::Mercury,
+    Planet::Venus,
+    Planet::Mars,
+    Planet::Jupiter,
+    Planet::Saturn,
+    Planet::Uranus,
+    Planet::Neptune,
+    Planet::Pluto,
+    Planet::MeanNode,
+    Planet::TrueNode,
+    Planet::MeanLilith,
+    Planet::TrueLilith,
+    Planet::Chiron,
+    Planet::Ceres,
+    Planet::Pallas,
+    Planet::Juno,
+    Planet::Vesta,
+];
+
+/// Bodies listed when none are asked for: the ten planets and both nodes
+pub const DEFAULT_POSITION_BODIES: &[Planet] = POSITION_BODIES.split_at(12).0;
+
+/// Chiron and the four main asteroids, added to a chart by `include_asteroids`
+pub const ASTEROID_BODIES: &[Planet] = POSITION_BODIES.split_at(14).1;
+
+/// The Lahiri ayanamsa at its defining epoch, 21 March 1956: 23°15'00.658"
+const LAHIRI_1956: f64 = 23.0 + 15.0 / 60.0 + 0.658 / 3600.0;
+
+/// Julian date of 21 March 1956, 0h
+const LAHIRI_EPOCH: f64 = 2435553.5;
+
+/// Zodiacs asked for now and then that aren't calculated, by request key, and why
+pub const UNSUPPORTED_ZODIACS: [(&str, &str); 4] = [
+    ("13-sign", "the 13-sign astronomical zodiac, with Ophiuchus, is not supported"),
+    ("astronomical", "the 13-sign astronomical zodiac, with Ophiuchus, is not supported"),
+    (
+        "constellational",
+        "signs as wide as their constellations are not supported; sidereal signs are all 30°",
+    ),
+    (
+        "true-sidereal",
+        "signs as wide as their constellations are not supported; sidereal signs are all 30°",
+    ),
+];
+
+/// Ayanamsas a sidereal zodiac from `/api/positions` can be measured with
+pub const AYANAMSAS: [&str; 1] = ["lahiri"];
+
+/// Zodiac longitudes are measured in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Zodiac {
+    /// From the equinox of date
+    #[default]
+    Tropical,
+    /// From the fixed stars, with the Lahiri ayanamsa
+    Sidereal,
+    /// From the fixed stars as seen from the Sun, which the Swiss Ephemeris
+    /// calculates in one step rather than by shifting heliocentric positions
+    HeliocentricSidereal,
+}
+
+impl Zodiac {
+    pub const ALL: [Zodiac; 3] = [Zodiac::Tropical, Zodiac::Sidereal, Zodiac::HeliocentricSidereal];
+
+    /// The name requests give the zodiac by
+    pub fn key(self) -> &'static str {
+        match self {
+            Zodiac::Tropical => "tropical",
+            Zodiac::Sidereal => "sidereal",
+            Zodiac::HeliocentricSidereal => "heliocentric-sidereal",
+        }
+    }
+
+    /// The ayanamsa the zodiac is measured with, if it is sidereal
+    pub fn ayanamsa(self) -> Option<&'static str> {
+        match self {
+            Zodiac::Tropical => None,
+            Zodiac::Sidereal | Zodiac::HeliocentricSidereal => Some(AYANAMSAS[0]),
+        }
+    }
+
+    /// The center the zodiac is tied to, if it is
+    pub fn center(self) -> Option<Center> {
+        match self {
+            Zodiac::HeliocentricSidereal => Some(Center::Heliocentric),
+            Zodiac::Tropical | Zodiac::Sidereal => None,
+        }
+    }
+}
+
+/// A zodiac name as the tables list it: lowercase, with hyphens for spaces
+/// and underscores
+fn zodiac_key(s: &str) -> String {
+    s.trim().to_lowercase().replace(['_', ' '], "-")
+}
+
+fn supported_zodiacs() -> String {
+    Zodiac::ALL.map(Zodiac::key).join(", ")
+}
+
+impl std::str::FromStr for Zodiac {
+    type Err = String;
+
+    /// Parses a zodiac by key. The error for a zodiac in `UNSUPPORTED_ZODIACS`
+    /// says why it isn't calculated; both kinds list the zodiacs that are.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let key = zodiac_key(s);
+        if let Some(zodiac) = Zodiac::ALL.into_iter().find(|zodiac| zodiac.key() == key) {
+            return Ok(zodiac);
+        }
+        match UNSUPPORTED_ZODIACS.iter().find(|(name, _)| *name == key) {
+            Some((_, reason)) => Err(format!(
+                "Unsupported zodiac '{}': {}. Supported zodiacs: {}",
+                s,
+                reason,
+                supported_zodiacs()
+            )),
+            None => Err(format!("Unknown zodiac '{}'. Supported zodiacs: {}", s, supported_zodiacs())),
+        }
+    }
+}
+
+/// Checks an `ayanamsa` given alongside `zodiac`: only a sidereal zodiac has
+/// one, and it must be in `AYANAMSAS`
+pub fn check_ayanamsa(zodiac: Zodiac, ayanamsa: Option<&str>) -> Result<(), String> {
+    let Some(ayanamsa) = ayanamsa else {
+        return Ok(());
+    };
+    if zodiac.ayanamsa().is_none() {
+        return Err(format!(
+            "An ayanamsa only applies to a sidereal zodiac, not '{}'",
+            zodiac.key()
+        ));
+    }
+    if !AYANAMSAS.contains(&ayanamsa.trim().to_lowercase().as_str()) {
+        return Err(format!(
+            "Unsupported ayanamsa '{}'. Supported ayanamsas: {}",
+            ayanamsa,
+            AYANAMSAS.join(", ")
+        ));
+    }
+    Ok(())
+}
+
+/// Reads the `ayanamsa` of a chart request: "tropical", which is `None`,
+/// "sidereal", measured with Lahiri's, or an `Ayanamsa` by key. A zodiac from
+/// `UNSUPPORTED_ZODIACS` gets that zodiac's error.
+pub fn parse_chart_ayanamsa(value: &str) -> Result<Option<Ayanamsa>, String> {
+    if let Ok(ayanamsa) = value.parse() {
+        return Ok(Some(ayanamsa));
+    }
+    match value.parse::<Zodiac>() {
+        Ok(Zodiac::Tropical) => Ok(None),
+        Ok(Zodiac::Sidereal) => Ok(Some(Ayanamsa::default())),
+        Ok(zodiac) => Err(format!(
+            "Charts are geocentric; the {} zodiac is only available from /api/positions",
+            zodiac.key()
+        )),
+        Err(e) if UNSUPPORTED_ZODIACS.iter().any(|(name, _)| *name == zodiac_key(value)) => Err(e),
+        Err(_) => Err(format!(
+            "Unsupported ayanamsa '{}'. Expected \"tropical\", \"sidereal\" or one of: {}",
+            value,
+            Ayanamsa::ALL.map(Ayanamsa::key).join(", ")
+        )),
+    }
+}
+
+/// Where positions are seen from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Center {
+    #[default]
+    Geocentric,
+    Heliocentric,
+}
+
+impl std::str::FromStr for Center {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "geo" | "geocentric" => Ok(Center::Geocentric),
+            "helio" | "heliocentric" => Ok(Center::Heliocentric),
+            _ => Err(format!("Invalid center: {} (expected \"geo\" or \"helio\")", s)),
+        }
+    }
+}
+
+impl Center {
+    /// The name requests give the center by
+    pub fn key(self) -> &'static str {
+        match self {
+            Center::Geocentric => "geo",
+            Center::Heliocentric => "helio",
+        }
+    }
+
+    /// Whether a body has a position from this center. The Sun, the Moon, the
+    /// lunar nodes and Lilith only mean something from the Earth.
+    pub fn supports(self, planet: Planet) -> bool {
+        match self {
+            Center::Geocentric => true,
+            Center::Heliocentric => !matches!(
+                planet,
+                Planet::Sun
+                    | Planet::Moon
+                    | Planet::MeanNode
+                    | Planet::TrueNode
+                    | Planet::MeanLilith
+                    | Planet::TrueLilith
+            ),
+        }
+    }
+}
+
+/// One body's position
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BodyPosition {
+    pub planet: Planet,
+    pub longitude: f64,
+    pub latitude: f64,
+    /// Degrees per day, negative when retrograde
+    pub speed: f64,
+}
+
+impl BodyPosition {
+    pub fn sign(&self) -> Sign {
+        Sign::from_longitude(self.longitude)
+    }
+}
+
+/// Distance of the sidereal zodiac's 0° Aries from the tropical one, by the
+/// Lahiri definition carried along with the general precession
+pub fn lahiri_ayanamsa(jd: f64) -> f64 {
+    ayanamsa_at(Ayanamsa::Lahiri, jd)
+}
+
+/// Distance of an ayanamsa's 0° Aries from the tropical one, its value at the
+/// epoch it is defined for carried along with the general precession
+pub fn ayanamsa_at(ayanamsa: Ayanamsa, jd: f64) -> f64 {
+    let (epoch, value) = match ayanamsa {
+        Ayanamsa::Lahiri => (LAHIRI_EPOCH, LAHIRI_1956),
+        // The Synetic Vernal Point at 335°57'28.64" in 1950.0
+        Ayanamsa::FaganBradley => (2433282.42346, 24.042044444),
+        // Both at 1900.0, as the Swiss Ephemeris defines them
+        Ayanamsa::Krishnamurti => (2415020.0, 360.0 - 337.636111),
+        Ayanamsa::Raman => (2415020.0, 360.0 - 338.98556),
+    };
+    value + precession_between(epoch, jd)
+}
+
+/// Parses a comma-separated body list such as "sun,moon,true_node", naming the
+/// first body that is unknown, repeated or has no ephemeris position
+pub fn parse_bodies(list: &str) -> Result<Vec<Planet>, String> {
+    let mut bodies = Vec::new();
+    for name in list.split(',').map(str::trim) {
+        let planet: Planet = name.parse().map_err(|_| format!("Unknown body '{}'", name))?;
+        if !POSITION_BODIES.contains(&planet) {
+            return Err(format!("No ephemeris position for '{}'", name));
+        }
+        if bodies.contains(&planet) {
+            return Err(format!("Body '{}' is listed more than once", name));
+        }
+        bodies.push(planet);
+    }
+    Ok(bodies)
+}
+
+/// Positions of the bodies at a Julian date (UT)
+pub fn body_positions(
+    jd: f64,
+    bodies: &[Planet],
+    zodiac: Zodiac,
+    center: Center,
+    source: EphemerisSource,
+) -> Result<Vec<BodyPosition>, String> {
+    let (year, month, day, hour) = julian_to_calendar(jd);
+    let center = zodiac.center().unwrap_or(center);
+    let ayanamsa = match zodiac {
+        Zodiac::Tropical | Zodiac::HeliocentricSidereal => 0.0,
+        Zodiac::Sidereal => lahiri_ayanamsa(jd),
+    };
+    bodies
+        .iter()
+        .map(|&planet| {
+            let (longitude, latitude, speed) = match center {
+                Center::Geocentric => {
+                    let (position, _) = calculate_planet_position_with_source(planet, year, month, day, hour, source)?;
+                    (position.longitude, position.latitude, position.speed)
+                }
+                Center::Heliocentric if zodiac == Zodiac::HeliocentricSidereal => {
+                    heliocentric_sidereal(planet, jd, source)?
+                }
+                Center::Heliocentric => heliocentric(planet, year, month, day, hour, source)?,
+            };
+            Ok(BodyPosition {
+                planet,
+                longitude: normalize(longitude - ayanamsa),
+                latitude,
+                speed,
+            })
+        })
+        .collect()
+}
+
+#[cfg(feature = "swiss")]
+fn heliocentric(
+    planet: Planet,
+    year: i32,
+    month: i32,
+    day: i32,
+    hour: f64,
+    source: EphemerisSource,
+) -> Result<(f64, f64, f64), String> {
+    use crate::calc::swiss_ephemeris::{calculate_heliocentric_position, map_planet_to_swe};
+
+    let swe_planet = map_planet_to_swe(planet)
+        .filter(|_| Center::Heliocentric.supports(planet))
+        .ok_or_else(|| format!("{:?} has no heliocentric position", planet))?;
+    let ((longitude, latitude, _distance, speed), _) =
+        calculate_heliocentric_position(swe_planet, year, month, day, hour, source).map_err(|e| e.to_string())?;
+    Ok((longitude, latitude, speed))
+}
+
+#[cfg(feature = "swiss")]
+fn heliocentric_sidereal(planet: Planet, jd: f64, source: EphemerisSource) -> Result<(f64, f64, f64), String> {
+    use crate::calc::swiss_ephemeris::{calculate_heliocentric_sidereal_position_at, map_planet_to_swe};
+
+    let swe_planet = map_planet_to_swe(planet)
+        .filter(|_| Center::Heliocentric.supports(planet))
+        .ok_or_else(|| format!("{:?} has no heliocentric position", planet))?;
+    let ((longitude, latitude, _distance, speed), _) =
+        calculate_heliocentric_sidereal_position_at(swe_planet, jd, source).map_err(|e| e.to_string())?;
+    Ok((longitude, latitude, speed))
+}
+
+#[cfg(not(feature = "swiss"))]
+fn heliocentric_sidereal(_planet: Planet, _jd: f64, _source: EphemerisSource) -> Result<(f64, f64, f64), String> {
+    Err("Heliocentric positions require the `swiss` feature".to_string())
+}
+
+#[cfg(not(feature = "swiss"))]
+fn heliocentric(
+    _planet: Planet,
+    _year: i32,
+    _month: i32,
+    _day: i32,
+    _hour: f64,
+    _source: EphemerisSource,
+) -> Result<(f64, f64, f64), String> {
+    Err("Heliocentric positions require the `swiss` feature".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(feature = "swiss")]
+    use crate::calc::planets::planet_longitude_at;
+    #[cfg(feature = "swiss")]
+    use crate::calc::swiss_ephemeris;
+
+    #[test]
+    fn test_body_lists_name_the_bad_body() {
+        assert_eq!(
+            parse_bodies("sun, Moon,true_node,chiron"),
+            Ok(vec![Planet::Sun, Planet::Moon, Planet::TrueNode, Planet::Chiron])
+        );
+        assert_eq!(parse_bodies("sun,nibiru"), Err("Unknown body 'nibiru'".to_string()));
+        assert_eq!(parse_bodies("fortune"), Err("No ephemeris position for 'fortune'".to_string()));
+        assert!(parse_bodies("sun,,moon").is_err());
+        assert!(parse_bodies("mars,Mars").is_err());
+    }
+
+    #[test]
+    fn test_lahiri_ayanamsa() {
+        assert!((lahiri_ayanamsa(LAHIRI_EPOCH) - LAHIRI_1956).abs() < 1e-12);
+        // About 23°51' at J2000, growing by 50" a year
+        let j2000 = lahiri_ayanamsa(2451545.0);
+        assert!((j2000 - 23.86).abs() < 0.02, "{}", j2000);
+        assert!((lahiri_ayanamsa(2451545.0 + 36525.0) - j2000 - 1.397).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_ayanamsas_in_2024() {
+        // Within an arcminute of the Swiss Ephemeris, whose values include nutation
+        let jd = 2460415.0;
+        for (ayanamsa, expected) in [
+            (Ayanamsa::Lahiri, 24.1949),
+            (Ayanamsa::FaganBradley, 25.0781),
+            (Ayanamsa::Krishnamurti, 24.0981),
+            (Ayanamsa::Raman, 22.7486),
+        ] {
+            let value = ayanamsa_at(ayanamsa, jd);
+            assert!((value - expected).abs() < 1.0 / 60.0, "{}: {}", ayanamsa, value);
+        }
+        assert_eq!(ayanamsa_at(Ayanamsa::Lahiri, jd), lahiri_ayanamsa(jd));
+    }
+
+    #[cfg(feature = "swiss")]
+    #[test]
+    fn test_sidereal_and_heliocentric_positions() {
+        swiss_ephemeris::init_moshier_ephemeris().unwrap();
+        let jd = 2460755.0;
+        let source = EphemerisSource::Auto;
+        let tropical = body_positions(jd, &[Planet::Sun, Planet::Mars], Zodiac::Tropical, Center::Geocentric, source).unwrap();
+        let sidereal = body_positions(jd, &[Planet::Sun, Planet::Mars], Zodiac::Sidereal, Center::Geocentric, source).unwrap();
+        for (tropical, sidereal) in tropical.iter().zip(&sidereal) {
+            let shift = normalize(tropical.longitude - sidereal.longitude);
+            assert!((shift - lahiri_ayanamsa(jd)).abs() < 1e-9);
+            assert_eq!(tropical.latitude, sidereal.latitude);
+        }
+        assert_eq!(tropical[0].longitude, planet_longitude_at(Planet::Sun, jd, source).unwrap());
+
+        // Seen from the Sun, Mars never goes retrograde
+        let helio = body_positions(jd, &[Planet::Mars], Zodiac::Tropical, Center::Heliocentric, source).unwrap();
+        assert!(helio[0].speed > 0.0);
+        assert!(normalize(helio[0].longitude - tropical[1].longitude) > 1.0);
+        assert!(body_positions(jd, &[Planet::Sun], Zodiac::Tropical, Center::Heliocentric, source).is_err());
+    }
+
+    #[test]
+    fn test_unsupported_zodiacs_list_the_supported_ones() {
+        assert_eq!(
+            "13-sign".parse::<Zodiac>(),
+            Err("Unsupported zodiac '13-sign': the 13-sign astronomical zodiac, with Ophiuchus, is not supported. \
+                 Supported zodiacs: tropical, sidereal, heliocentric-sidereal"
+                .to_string())
+        );
+        assert!("True Sidereal".parse::<Zodiac>().unwrap_err().contains("sidereal signs are all 30°"));
+        assert_eq!(
+            "draconic".parse::<Zodiac>(),
+            Err("Unknown zodiac 'draconic'. Supported zodiacs: tropical, sidereal, heliocentric-sidereal".to_string())
+        );
+        assert_eq!("Heliocentric_Sidereal".parse(), Ok(Zodiac::HeliocentricSidereal));
+
+        assert!(check_ayanamsa(Zodiac::Sidereal, Some("Lahiri")).is_ok());
+        assert!(check_ayanamsa(Zodiac::Tropical, Some("lahiri")).unwrap_err().contains("only applies to a sidereal zodiac"));
+        assert_eq!(
+            check_ayanamsa(Zodiac::Sidereal, Some("fagan_bradley")),
+            Err("Unsupported ayanamsa 'fagan_bradley'. Supported ayanamsas: lahiri".to_string())
+        );
+
+        assert_eq!(parse_chart_ayanamsa("tropical"), Ok(None));
+        assert_eq!(parse_chart_ayanamsa("sidereal"), Ok(Some(Ayanamsa::Lahiri)));
+        assert_eq!(parse_chart_ayanamsa("lahiri"), Ok(Some(Ayanamsa::Lahiri)));
+        assert_eq!(parse_chart_ayanamsa("Fagan-Bradley"), Ok(Some(Ayanamsa::FaganBradley)));
+        assert_eq!(parse_chart_ayanamsa("raman"), Ok(Some(Ayanamsa::Raman)));
+        assert!(parse_chart_ayanamsa("astronomical").unwrap_err().starts_with("Unsupported zodiac 'astronomical'"));
+        assert!(parse_chart_ayanamsa("heliocentric-sidereal").unwrap_err().contains("only available from /api/positions"));
+        assert!(parse_chart_ayanamsa("yukteshwar")
+            .unwrap_err()
+            .contains("one of: lahiri, fagan_bradley, krishnamurti, raman"));
+    }
+
+    #[cfg(feature = "swiss")]
+    #[test]
+    fn test_heliocentric_sidereal_earth_is_opposite_the_sun() {
+        use crate::calc::angles::signed_delta;
+        use crate::calc::swiss_ephemeris::calculate_heliocentric_sidereal_position_at;
+
+        swiss_ephemeris::init_moshier_ephemeris().unwrap();
+        let jd = 2460755.0;
+        let source = EphemerisSource::Moshier;
+        let ((earth, latitude, _, _), _) =
+            calculate_heliocentric_sidereal_position_at(swisseph::Planet::Earth, jd, source).unwrap();
+        let sun = planet_longitude_at(Planet::Sun, jd, source).unwrap();
+        // Within the Sun's aberration and the few arcseconds between the
+        // library's Lahiri ayanamsa, which leaves out nutation, and ours
+        let expected = normalize(sun + 180.0 - lahiri_ayanamsa(jd));
+        assert!(signed_delta(earth, expected).abs() < 0.01, "{} vs {}", earth, expected);
+        assert!(latitude.abs() < 0.01);
+
+        // The combined mode is heliocentric whatever center is passed, and
+        // agrees with shifting the heliocentric positions by the ayanamsa
+        let combined = body_positions(jd, &[Planet::Mars], Zodiac::HeliocentricSidereal, Center::Geocentric, source).unwrap();
+        let shifted = body_positions(jd, &[Planet::Mars], Zodiac::Sidereal, Center::Heliocentric, source).unwrap();
+        assert!(signed_delta(combined[0].longitude, shifted[0].longitude).abs() < 0.02);
+        assert!(combined[0].speed > 0.0);
+        assert!(body_positions(jd, &[Planet::Moon], Zodiac::HeliocentricSidereal, Center::Geocentric, source).is_err());
+    }
+}
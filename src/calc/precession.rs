@@ -0,0 +1,73 @@
+//! General precession in longitude, for carrying a natal chart forward to the
+//! equinox of a later date before comparing it with that date's positions.
+//!
+//! Tropical longitudes are measured from the equinox of date, which slides
+//! back along the ecliptic about 50.3" a year, so a fixed point in the sky
+//! gains that much longitude every year.
+
+use crate::calc::angles::normalize;
+use crate::calc::utils::julian_centuries;
+
+/// General precession in longitude accumulated from J2000 to a Julian date,
+/// in degrees (IAU 2006, Capitaine et al. 2003)
+pub fn general_precession(jd: f64) -> f64 {
+    let t = julian_centuries(jd);
+    (5028.796195 * t + 1.1054348 * t.powi(2) + 0.00007964 * t.powi(3)
+        - 0.000023857 * t.powi(4)
+        - 0.0000000383 * t.powi(5))
+        / 3600.0
+}
+
+/// Precession accumulated from one Julian date to another, in degrees;
+/// negative when `jd_to` is earlier
+pub fn precession_between(jd_from: f64, jd_to: f64) -> f64 {
+    general_precession(jd_to) - general_precession(jd_from)
+}
+
+/// A longitude measured from the equinox of `jd_from` re-measured from the
+/// equinox of `jd_to`
+pub fn precess_longitude(longitude: f64, jd_from: f64, jd_to: f64) -> f64 {
+    normalize(longitude + precession_between(jd_from, jd_to))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const J2000: f64 = 2451545.0;
+
+    #[test]
+    fn test_72_years_of_precession_is_about_a_degree() {
+        let precession = precession_between(J2000, J2000 + 72.0 * 365.25);
+        assert!((precession - 1.0046).abs() < 2e-3, "precession {}", precession);
+        assert!((precession_between(J2000 + 72.0 * 365.25, J2000) + precession).abs() < 1e-12);
+        assert!((precess_longitude(359.5, J2000, J2000 + 72.0 * 365.25) - (precession - 0.5)).abs() < 1e-9);
+    }
+
+    #[cfg(feature = "swiss")]
+    #[test]
+    fn test_precessed_conjunction_comes_later() {
+        use crate::calc::cycles::speed_at;
+        use crate::calc::planets::{planet_longitude_at, Planet};
+        use crate::calc::returns::returns_between_with_source;
+        use crate::core::types::EphemerisSource;
+
+        crate::calc::swiss_ephemeris::init_moshier_ephemeris().unwrap();
+        // The transiting Sun conjoining the natal Sun 72 years on
+        let natal_jd = 2433282.5; // 1 January 1950
+        let natal_sun = planet_longitude_at(Planet::Sun, natal_jd, EphemerisSource::Auto).unwrap();
+        let from = natal_jd + 72.0 * 365.25 - 10.0;
+        let exact = |longitude: f64| {
+            returns_between_with_source(Planet::Sun, longitude, from, from + 20.0, EphemerisSource::Auto).unwrap()[0]
+                .true_pass
+                .jd
+        };
+        let plain = exact(natal_sun);
+        let precessed = exact(precess_longitude(natal_sun, natal_jd, plain));
+
+        // Later by the time the Sun takes to cover the precession
+        let shift = precessed - plain;
+        let expected = precession_between(natal_jd, plain) / speed_at(Planet::Sun, plain, EphemerisSource::Auto).unwrap();
+        assert!((shift - expected).abs() < 0.01, "shifted {} days, expected {}", shift, expected);
+    }
+}
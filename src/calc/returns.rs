@@ -0,0 +1,195 @@
+use crate::calc::aspects::{crossings, sample_motion};
+use crate::calc::cycles::speed_at;
+use crate::calc::planets::{default_source, max_daily_motion, planet_longitude_at, Planet};
+use crate::core::types::EphemerisSource;
+
+/// Mean time for each body to come back to the same tropical longitude, in days
+const RETURN_PERIODS: [(Planet, f64); 11] = [
+    (Planet::Sun, 365.2422),
+    (Planet::Moon, 27.3216),
+    // Mercury and Venus keep close to the Sun, so they return yearly on average
+    (Planet::Mercury, 365.2422),
+    (Planet::Venus, 365.2422),
+    (Planet::Mars, 686.98),
+    (Planet::Jupiter, 4332.59),
+    (Planet::Saturn, 10759.22),
+    (Planet::Uranus, 30688.5),
+    (Planet::Neptune, 60182.0),
+    (Planet::Pluto, 90560.0),
+    (Planet::Chiron, 18517.0),
+];
+
+/// Longest a retrograde loop can keep a body around one degree, in days
+const MAX_LOOP_DAYS: f64 = 400.0;
+
+/// One crossing of the natal longitude
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ReturnPass {
+    /// Julian date (UT) of the crossing
+    pub jd: f64,
+    /// 1 for the first crossing of this return, 2 and 3 for retrograde loops
+    pub pass: usize,
+    pub retrograde: bool,
+}
+
+/// A body coming back to its natal longitude, once or, when it stations
+/// nearby, three times
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlanetReturn {
+    /// Every crossing of the natal longitude, earliest first
+    pub passes: Vec<ReturnPass>,
+    /// The final direct crossing, which the return chart is cast for
+    pub true_pass: ReturnPass,
+}
+
+/// Mean return period of a body in days, `None` for the points that don't move on their own
+pub fn return_period(planet: Planet) -> Option<f64> {
+    RETURN_PERIODS
+        .iter()
+        .find(|(p, _)| *p == planet)
+        .map(|(_, period)| *period)
+}
+
+/// Finds the returns of a body to a natal longitude.
+///
+/// Samples the body like the aspect solver does, then groups crossings that
+/// fall within a retrograde loop of each other into one return.
+///
+/// # Arguments
+///
+/// * `planet` - The returning body, e.g. Saturn
+/// * `natal_longitude` - Its longitude in the natal chart
+/// * `jd_from`, `jd_to` - Julian dates (UT) bounding the search
+///
+/// # Returns
+///
+/// The returns whose true (final direct) pass falls in the range, earliest first
+pub fn returns_between(
+    planet: Planet,
+    natal_longitude: f64,
+    jd_from: f64,
+    jd_to: f64,
+) -> Result<Vec<PlanetReturn>, String> {
+    returns_between_with_source(planet, natal_longitude, jd_from, jd_to, default_source())
+}
+
+/// Finds the returns of a body to a natal longitude from a specific ephemeris
+/// source. See `returns_between`.
+pub fn returns_between_with_source(
+    planet: Planet,
+    natal_longitude: f64,
+    jd_from: f64,
+    jd_to: f64,
+    source: EphemerisSource,
+) -> Result<Vec<PlanetReturn>, String> {
+    let period = return_period(planet).ok_or_else(|| format!("{:?} has no return", planet))?;
+    if jd_to <= jd_from {
+        return Err("The end of the range must be after its start".to_string());
+    }
+    // Look far enough either side to see whole retrograde loops
+    let margin = (period / 2.0).min(MAX_LOOP_DAYS);
+    let start = jd_from - margin;
+
+    let longitude = |jd: f64| planet_longitude_at(planet, jd, source);
+    let samples = sample_motion(start, jd_to + margin - start, max_daily_motion(planet), longitude)?;
+
+    let mut groups: Vec<Vec<ReturnPass>> = Vec::new();
+    for jd in crossings(&samples, natal_longitude, longitude)? {
+        let retrograde = speed_at(planet, jd, source)? < 0.0;
+        match groups.last_mut() {
+            Some(group) if jd - group[group.len() - 1].jd < period / 2.0 => group.push(ReturnPass {
+                jd,
+                pass: group.len() + 1,
+                retrograde,
+            }),
+            _ => groups.push(vec![ReturnPass { jd, pass: 1, retrograde }]),
+        }
+    }
+
+    Ok(groups
+        .into_iter()
+        .filter_map(|passes| {
+            let true_pass = *passes.iter().rev().find(|p| !p.retrograde)?;
+            (jd_from..jd_to)
+                .contains(&true_pass.jd)
+                .then_some(PlanetReturn { passes, true_pass })
+        })
+        .collect())
+}
+
+/// The first return whose true pass is after `jd`
+pub fn next_return(
+    planet: Planet,
+    natal_longitude: f64,
+    jd: f64,
+    source: EphemerisSource,
+) -> Result<PlanetReturn, String> {
+    let period = return_period(planet).ok_or_else(|| format!("{:?} has no return", planet))?;
+    returns_between_with_source(planet, natal_longitude, jd, jd + 1.5 * period, source)?
+        .into_iter()
+        .next()
+        .ok_or_else(|| format!("No {:?} return found after Julian date {}", planet, jd))
+}
+
+/// The last return whose true pass is before `jd`
+pub fn previous_return(
+    planet: Planet,
+    natal_longitude: f64,
+    jd: f64,
+    source: EphemerisSource,
+) -> Result<PlanetReturn, String> {
+    let period = return_period(planet).ok_or_else(|| format!("{:?} has no return", planet))?;
+    returns_between_with_source(planet, natal_longitude, jd - 1.5 * period, jd, source)?
+        .pop()
+        .ok_or_else(|| format!("No {:?} return found before Julian date {}", planet, jd))
+}
+
+#[cfg(all(test, feature = "swiss"))]
+mod tests {
+    use super::*;
+    use crate::calc::swiss_ephemeris;
+    use crate::calc::utils::julian_to_calendar;
+
+    #[test]
+    fn test_lunar_returns_recur_every_sidereal_month() {
+        swiss_ephemeris::init_moshier_ephemeris().unwrap();
+        // Six months of 2024 from an arbitrary natal Moon at 10° Taurus
+        let returns = returns_between(Planet::Moon, 40.0, 2460310.5, 2460492.5).unwrap();
+        assert!(returns.len() >= 6);
+        for pair in returns.windows(2) {
+            let interval = pair[1].true_pass.jd - pair[0].true_pass.jd;
+            assert!((27.2..27.6).contains(&interval), "Lunar return interval {} days", interval);
+        }
+        assert!(returns.iter().all(|r| r.passes.len() == 1));
+    }
+
+    #[test]
+    fn test_saturn_return_with_retrograde_loop() {
+        swiss_ephemeris::init_moshier_ephemeris().unwrap();
+        // Saturn stationed at 19° Pisces in June 2024 and 12° Pisces in November,
+        // so 15° Pisces was crossed three times
+        let returns = returns_between(Planet::Saturn, 345.0, 2460310.5, 2460857.5).unwrap();
+        assert_eq!(returns.len(), 1);
+        let passes = &returns[0].passes;
+        assert_eq!(passes.len(), 3);
+        assert_eq!(
+            passes.iter().map(|p| p.retrograde).collect::<Vec<_>>(),
+            [false, true, false]
+        );
+        assert_eq!(returns[0].true_pass, passes[2]);
+        let (year, _, _, _) = julian_to_calendar(returns[0].true_pass.jd);
+        assert_eq!(year, 2025);
+    }
+
+    #[test]
+    fn test_next_and_previous_solar_return() {
+        swiss_ephemeris::init_moshier_ephemeris().unwrap();
+        let source = default_source();
+        // 1 July 2024, Sun at 24° Cancer; natal Sun at 0° Aries returns each March
+        let next = next_return(Planet::Sun, 0.0, 2460492.5, source).unwrap();
+        let previous = previous_return(Planet::Sun, 0.0, 2460492.5, source).unwrap();
+        assert_eq!(julian_to_calendar(next.true_pass.jd).0, 2025);
+        assert_eq!(julian_to_calendar(previous.true_pass.jd).0, 2024);
+        assert!((next.true_pass.jd - previous.true_pass.jd - 365.24).abs() < 0.1);
+    }
+}
@@ -0,0 +1,265 @@
+use crate::calc::angles::{normalize, shortest_distance};
+use crate::calc::planets::Planet;
+use serde::{Deserialize, Serialize};
+
+/// Within 0°17' of the Sun: "in the heart of the Sun"
+pub const CAZIMI_ORB: f64 = 17.0 / 60.0;
+/// Within 8°30' of the Sun: burnt by its rays
+pub const COMBUST_ORB: f64 = 8.5;
+/// Within 17° of the Sun: hidden by its glare
+pub const UNDER_BEAMS_ORB: f64 = 17.0;
+
+/// The twelve signs of the tropical zodiac, in order from 0° Aries
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Sign {
+    Aries,
+    Taurus,
+    Gemini,
+    Cancer,
+    Leo,
+    Virgo,
+    Libra,
+    Scorpio,
+    Sagittarius,
+    Capricorn,
+    Aquarius,
+    Pisces,
+}
+
+impl Sign {
+    /// All signs in zodiacal order
+    pub const ALL: [Sign; 12] = [
+        Sign::Aries,
+        Sign::Taurus,
+        Sign::Gemini,
+        Sign::Cancer,
+        Sign::Leo,
+        Sign::Virgo,
+        Sign::Libra,
+        Sign::Scorpio,
+        Sign::Sagittarius,
+        Sign::Capricorn,
+        Sign::Aquarius,
+        Sign::Pisces,
+    ];
+
+    /// The sign containing an ecliptic longitude
+    pub fn from_longitude(longitude: f64) -> Sign {
+        let index = (normalize(longitude) / 30.0) as usize;
+        Sign::ALL[index.min(11)]
+    }
+}
+
+impl std::fmt::Display for Sign {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+/// How close a planet is to the Sun
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SolarCondition {
+    /// Within `CAZIMI_ORB` of the Sun
+    Cazimi,
+    /// Within `COMBUST_ORB` of the Sun
+    Combust,
+    /// Within `UNDER_BEAMS_ORB` of the Sun
+    UnderBeams,
+    /// Clear of the Sun's rays
+    Free,
+}
+
+/// Returns the planet ruling a sign.
+///
+/// Traditional rulerships give Scorpio to Mars, Aquarius to Saturn and Pisces
+/// to Jupiter; modern rulerships give them to Pluto, Uranus and Neptune.
+///
+/// # Arguments
+///
+/// * `sign` - The sign whose ruler to find
+/// * `modern` - Whether to use modern rulerships for the outer planets
+///
+/// # Returns
+///
+/// The ruling planet
+///
+/// # Examples
+///
+/// ```
+/// use astrolog_rs::calc::planets::Planet;
+/// use astrolog_rs::calc::rulers::{sign_ruler, Sign};
+///
+/// assert_eq!(sign_ruler(Sign::Scorpio, false), Planet::Mars);
+/// assert_eq!(sign_ruler(Sign::Scorpio, true), Planet::Pluto);
+/// ```
+pub fn sign_ruler(sign: Sign, modern: bool) -> Planet {
+    match sign {
+        Sign::Aries => Planet::Mars,
+        Sign::Taurus => Planet::Venus,
+        Sign::Gemini => Planet::Mercury,
+        Sign::Cancer => Planet::Moon,
+        Sign::Leo => Planet::Sun,
+        Sign::Virgo => Planet::Mercury,
+        Sign::Libra => Planet::Venus,
+        Sign::Scorpio if modern => Planet::Pluto,
+        Sign::Scorpio => Planet::Mars,
+        Sign::Sagittarius => Planet::Jupiter,
+        Sign::Capricorn => Planet::Saturn,
+        Sign::Aquarius if modern => Planet::Uranus,
+        Sign::Aquarius => Planet::Saturn,
+        Sign::Pisces if modern => Planet::Neptune,
+        Sign::Pisces => Planet::Jupiter,
+    }
+}
+
+/// Classifies how close a planet is to the Sun.
+///
+/// The boundaries are inclusive, so a planet exactly 8.5° from the Sun is combust.
+///
+/// # Arguments
+///
+/// * `planet_longitude` - Longitude of the planet in degrees
+/// * `sun_longitude` - Longitude of the Sun in degrees
+///
+/// # Returns
+///
+/// The planet's solar condition
+///
+/// # Examples
+///
+/// ```
+/// use astrolog_rs::calc::rulers::{solar_condition, SolarCondition};
+///
+/// assert_eq!(solar_condition(105.0, 100.0), SolarCondition::Combust);
+/// assert_eq!(solar_condition(130.0, 100.0), SolarCondition::Free);
+/// ```
+pub fn solar_condition(planet_longitude: f64, sun_longitude: f64) -> SolarCondition {
+    let separation = shortest_distance(planet_longitude, sun_longitude);
+
+    if separation <= CAZIMI_ORB {
+        SolarCondition::Cazimi
+    } else if separation <= COMBUST_ORB {
+        SolarCondition::Combust
+    } else if separation <= UNDER_BEAMS_ORB {
+        SolarCondition::UnderBeams
+    } else {
+        SolarCondition::Free
+    }
+}
+
+/// The ruler of a house and where that ruler is placed
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HouseRuler {
+    /// House number (1-12)
+    pub house: u8,
+    /// Sign on the house cusp
+    pub sign: Sign,
+    /// Planet ruling that sign
+    pub ruler: Planet,
+    /// House the ruler occupies, if its position was supplied
+    pub ruler_house: Option<u8>,
+}
+
+/// Returns the ruler of the Ascendant sign.
+///
+/// # Arguments
+///
+/// * `ascendant` - Longitude of the Ascendant (first house cusp) in degrees
+/// * `modern` - Whether to use modern rulerships for the outer planets
+///
+/// # Returns
+///
+/// The chart ruler
+pub fn chart_ruler(ascendant: f64, modern: bool) -> Planet {
+    sign_ruler(Sign::from_longitude(ascendant), modern)
+}
+
+/// Maps each house to the ruler of the sign on its cusp and the house that ruler occupies.
+///
+/// # Arguments
+///
+/// * `cusps` - Longitudes of the 12 house cusps, house 1 first
+/// * `planets` - Planets and their longitudes, used to place each ruler
+/// * `modern` - Whether to use modern rulerships for the outer planets
+///
+/// # Returns
+///
+/// Twelve `HouseRuler` entries, house 1 first
+///
+/// # Examples
+///
+/// ```
+/// use astrolog_rs::calc::planets::Planet;
+/// use astrolog_rs::calc::rulers::house_rulers;
+///
+/// let cusps = [0.0, 30.0, 60.0, 90.0, 120.0, 150.0, 180.0, 210.0, 240.0, 270.0, 300.0, 330.0];
+/// let rulers = house_rulers(&cusps, &[(Planet::Venus, 280.0)], false);
+/// // Ruler of the 7th (Libra) in the 10th
+/// assert_eq!(rulers[6].ruler, Planet::Venus);
+/// assert_eq!(rulers[6].ruler_house, Some(10));
+/// ```
+pub fn house_rulers(cusps: &[f64; 12], planets: &[(Planet, f64)], modern: bool) -> Vec<HouseRuler> {
+    cusps
+        .iter()
+        .enumerate()
+        .map(|(i, &cusp)| {
+            let sign = Sign::from_longitude(cusp);
+            let ruler = sign_ruler(sign, modern);
+            let ruler_house = planets
+                .iter()
+                .find(|(planet, _)| *planet == ruler)
+                .map(|&(_, longitude)| crate::calc::houses::house_of(longitude, cusps).house);
+            HouseRuler {
+                house: i as u8 + 1,
+                sign,
+                ruler,
+                ruler_house,
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_aries_rising_ruled_by_mars() {
+        assert_eq!(chart_ruler(12.5, false), Planet::Mars);
+        assert_eq!(chart_ruler(12.5, true), Planet::Mars);
+        assert_eq!(chart_ruler(359.9, false), Planet::Jupiter);
+        assert_eq!(chart_ruler(359.9, true), Planet::Neptune);
+    }
+
+    #[test]
+    fn test_combust_boundary() {
+        assert_eq!(solar_condition(108.5, 100.0), SolarCondition::Combust);
+        assert_eq!(solar_condition(91.5, 100.0), SolarCondition::Combust);
+        assert_eq!(solar_condition(108.51, 100.0), SolarCondition::UnderBeams);
+        assert_eq!(solar_condition(100.1, 100.0), SolarCondition::Cazimi);
+        assert_eq!(solar_condition(117.5, 100.0), SolarCondition::Free);
+        // Across 0° Aries
+        assert_eq!(solar_condition(355.0, 3.0), SolarCondition::Combust);
+    }
+
+    #[test]
+    fn test_house_rulers_place_each_ruler() {
+        // Aries rising with equal houses
+        let cusps = [
+            10.0, 40.0, 70.0, 100.0, 130.0, 160.0, 190.0, 220.0, 250.0, 280.0, 310.0, 340.0,
+        ];
+        let planets = [(Planet::Mars, 200.0), (Planet::Venus, 45.0)];
+        let rulers = house_rulers(&cusps, &planets, false);
+
+        assert_eq!(rulers.len(), 12);
+        assert_eq!(rulers[0].sign, Sign::Aries);
+        assert_eq!(rulers[0].ruler, Planet::Mars);
+        assert_eq!(rulers[0].ruler_house, Some(7));
+        assert_eq!(rulers[1].ruler, Planet::Venus);
+        assert_eq!(rulers[1].ruler_house, Some(2));
+        // Moon rules the 4th but wasn't supplied
+        assert_eq!(rulers[3].ruler, Planet::Moon);
+        assert_eq!(rulers[3].ruler_house, None);
+    }
+}
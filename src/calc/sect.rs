@@ -0,0 +1,123 @@
+//! A chart's sect: whether it is a day or a night chart.
+//!
+//! A chart is diurnal when the Sun is above the horizon at the chart's moment
+//! and place. The altitude is found from the Sun's ecliptic position, turned
+//! into right ascension and declination with the true obliquity and onto the
+//! horizon with the local apparent sidereal time. It is the geometric altitude
+//! of the Sun's centre, without refraction, so a chart within a few minutes of
+//! sunrise or sunset is flagged as ambiguous. Anything that depends on sect,
+//! such as lots and triplicity rulers, should ask `chart_sect`.
+
+use crate::calc::coordinates::{equatorial_of_date, equatorial_to_horizontal, local_sidereal_time};
+use serde::{Deserialize, Serialize};
+
+/// Degrees below the horizon the Sun can be and still make a day chart, unless a request sets it
+pub const DEFAULT_TWILIGHT: f64 = 0.0;
+
+/// Largest twilight allowance, astronomical twilight's 18°
+pub const MAX_TWILIGHT: f64 = 18.0;
+
+/// Degrees either side of the day/night line within which a chart's sect is ambiguous
+pub const AMBIGUOUS_ALTITUDE: f64 = 0.25;
+
+/// Day or night chart
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Sect {
+    Day,
+    Night,
+}
+
+/// A chart's sect and the Sun altitude it comes from
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ChartSect {
+    pub sect: Sect,
+    /// Degrees above the horizon, negative below
+    pub sun_altitude: f64,
+    /// Whether the Sun is within `AMBIGUOUS_ALTITUDE` of the day/night line
+    pub ambiguous: bool,
+}
+
+/// The Sun's altitude in degrees at `jd` (UT) seen from `latitude` and
+/// `longitude` (east positive), given its ecliptic longitude and latitude
+pub fn sun_altitude(jd: f64, latitude: f64, longitude: f64, sun_longitude: f64, sun_latitude: f64) -> f64 {
+    let sun = equatorial_of_date(jd, sun_longitude, sun_latitude);
+    let (_, altitude) =
+        equatorial_to_horizontal(sun.right_ascension, sun.declination, longitude, latitude, local_sidereal_time(jd, longitude));
+    altitude
+}
+
+/// The sect of a chart at `jd` and the given place, from the Sun's ecliptic
+/// position. The Sun counts as up until it is `twilight` degrees below the horizon.
+pub fn chart_sect(jd: f64, latitude: f64, longitude: f64, sun_longitude: f64, sun_latitude: f64, twilight: f64) -> ChartSect {
+    let altitude = sun_altitude(jd, latitude, longitude, sun_longitude, sun_latitude);
+    ChartSect {
+        sect: if altitude > -twilight { Sect::Day } else { Sect::Night },
+        sun_altitude: altitude,
+        ambiguous: (altitude + twilight).abs() <= AMBIGUOUS_ALTITUDE,
+    }
+}
+
+/// Checks a requested twilight allowance, `DEFAULT_TWILIGHT` when absent
+pub fn twilight(twilight: Option<f64>) -> Result<f64, String> {
+    match twilight {
+        None => Ok(DEFAULT_TWILIGHT),
+        Some(degrees) if degrees.is_finite() && (0.0..=MAX_TWILIGHT).contains(&degrees) => Ok(degrees),
+        Some(degrees) => Err(format!("sect_twilight must be between 0 and {}°, got {}", MAX_TWILIGHT, degrees)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::calc::ephemeris::{AnalyticEphemeris, Ephemeris};
+    use crate::calc::planets::Planet;
+    use crate::calc::utils::date_to_julian;
+    use crate::core::types::EphemerisSource;
+
+    // Greenwich
+    const LATITUDE: f64 = 51.4769;
+    const LONGITUDE: f64 = -0.0005;
+
+    fn sect_at(date: &str, twilight: f64) -> ChartSect {
+        let jd = date_to_julian(date.parse().unwrap());
+        let (sun_longitude, sun_latitude, _) = AnalyticEphemeris.position(jd, Planet::Sun, EphemerisSource::Analytic).unwrap();
+        chart_sect(jd, LATITUDE, LONGITUDE, sun_longitude, sun_latitude, twilight)
+    }
+
+    #[test]
+    fn test_noon_is_day_and_midnight_is_night() {
+        let noon = sect_at("2024-03-20T12:00:00Z", DEFAULT_TWILIGHT);
+        assert_eq!(noon.sect, Sect::Day);
+        assert!(!noon.ambiguous);
+        // At the equinox the Sun culminates at the colatitude
+        assert!((noon.sun_altitude - (90.0 - LATITUDE)).abs() < 0.5, "{}", noon.sun_altitude);
+
+        let midnight = sect_at("2024-03-20T00:00:00Z", DEFAULT_TWILIGHT);
+        assert_eq!(midnight.sect, Sect::Night);
+        assert!(!midnight.ambiguous);
+        assert!(midnight.sun_altitude < -30.0);
+    }
+
+    #[test]
+    fn test_the_minute_of_sunrise_is_ambiguous() {
+        // The centre of the Sun crossed the horizon at Greenwich at about 06:06 UT
+        let sunrise = sect_at("2024-03-20T06:06:00Z", DEFAULT_TWILIGHT);
+        assert!(sunrise.ambiguous, "{}", sunrise.sun_altitude);
+        let before = sect_at("2024-03-20T05:50:00Z", DEFAULT_TWILIGHT);
+        assert_eq!((before.sect, before.ambiguous), (Sect::Night, false));
+        assert_eq!(sect_at("2024-03-20T06:20:00Z", DEFAULT_TWILIGHT).sect, Sect::Day);
+
+        // With civil twilight allowed, the Sun is up well before sunrise
+        assert_eq!(sect_at("2024-03-20T05:50:00Z", 6.0).sect, Sect::Day);
+    }
+
+    #[test]
+    fn test_twilight_is_checked() {
+        assert_eq!(twilight(None), Ok(0.0));
+        assert_eq!(twilight(Some(6.0)), Ok(6.0));
+        assert!(twilight(Some(-1.0)).is_err());
+        assert!(twilight(Some(19.0)).is_err());
+        assert!(twilight(Some(f64::NAN)).is_err());
+    }
+}
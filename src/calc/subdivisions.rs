@@ -0,0 +1,296 @@
+//! Divisions of the zodiac finer than the signs: the decans, thirds of a sign
+//! ruled in Chaldean order, the Egyptian terms, five unequal parts of each sign
+//! ruled by the five planets, and the lunar mansions, either the 28 Arabic
+//! manzils or the 27 Vedic nakshatras.
+//!
+//! Both count from 0° of the zodiac the longitudes are measured in.
+
+use crate::calc::angles::normalize;
+use crate::calc::planets::Planet;
+use crate::calc::rulers::Sign;
+use serde::{Deserialize, Serialize};
+
+/// Width of a decan, in degrees
+pub const DECAN_WIDTH: f64 = 10.0;
+
+/// The classical planets from slowest to fastest. The decan rulers follow
+/// this order round the zodiac, starting with Mars in the first decan of Aries.
+pub const CHALDEAN_ORDER: [Planet; 7] = [
+    Planet::Saturn,
+    Planet::Jupiter,
+    Planet::Mars,
+    Planet::Sun,
+    Planet::Venus,
+    Planet::Mercury,
+    Planet::Moon,
+];
+
+/// The 28 Arabic lunar mansions, from 0° Aries
+const MANZILS: [&str; 28] = [
+    "Al Sharatain",
+    "Al Butain",
+    "Al Thurayya",
+    "Al Dabaran",
+    "Al Haqah",
+    "Al Hanah",
+    "Al Dhira",
+    "Al Nathrah",
+    "Al Tarf",
+    "Al Jabhah",
+    "Al Zubrah",
+    "Al Sarfah",
+    "Al Awwa",
+    "Al Simak",
+    "Al Ghafr",
+    "Al Zubana",
+    "Al Iklil",
+    "Al Qalb",
+    "Al Shaulah",
+    "Al Naaim",
+    "Al Baldah",
+    "Sad al Dhabih",
+    "Sad Bula",
+    "Sad al Suud",
+    "Sad al Akhbiyah",
+    "Al Fargh al Muqaddam",
+    "Al Fargh al Muakhkhar",
+    "Batn al Hut",
+];
+
+/// The 27 nakshatras, from 0° Aries
+const NAKSHATRAS: [&str; 27] = [
+    "Ashwini",
+    "Bharani",
+    "Krittika",
+    "Rohini",
+    "Mrigashira",
+    "Ardra",
+    "Punarvasu",
+    "Pushya",
+    "Ashlesha",
+    "Magha",
+    "Purva Phalguni",
+    "Uttara Phalguni",
+    "Hasta",
+    "Chitra",
+    "Swati",
+    "Vishakha",
+    "Anuradha",
+    "Jyeshtha",
+    "Mula",
+    "Purva Ashadha",
+    "Uttara Ashadha",
+    "Shravana",
+    "Dhanishta",
+    "Shatabhisha",
+    "Purva Bhadrapada",
+    "Uttara Bhadrapada",
+    "Revati",
+];
+
+/// A third of a sign
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Decan {
+    pub sign: Sign,
+    /// 1, 2 or 3 within the sign
+    pub index: u8,
+    pub ruler: Planet,
+}
+
+/// The decan containing an ecliptic longitude, with its Chaldean ruler
+pub fn decan(longitude: f64) -> Decan {
+    let number = ((normalize(longitude) / DECAN_WIDTH) as usize).min(35);
+    Decan {
+        sign: Sign::ALL[number / 3],
+        index: (number % 3) as u8 + 1,
+        // Mars, third in the Chaldean order, rules the first decan of Aries
+        ruler: CHALDEAN_ORDER[(number + 2) % CHALDEAN_ORDER.len()],
+    }
+}
+
+/// The Egyptian terms of each sign from Aries, as the degree each term ends at
+/// and its ruler (Ptolemy, Tetrabiblos I.20)
+const EGYPTIAN_TERMS: [[(u8, Planet); 5]; 12] = {
+    use Planet::{Jupiter as Ju, Mars as Ma, Mercury as Me, Saturn as Sa, Venus as Ve};
+    [
+        [(6, Ju), (12, Ve), (20, Me), (25, Ma), (30, Sa)],
+        [(8, Ve), (14, Me), (22, Ju), (27, Sa), (30, Ma)],
+        [(6, Me), (12, Ju), (17, Ve), (24, Ma), (30, Sa)],
+        [(7, Ma), (13, Ve), (19, Me), (26, Ju), (30, Sa)],
+        [(6, Ju), (11, Ve), (18, Sa), (24, Me), (30, Ma)],
+        [(7, Me), (17, Ve), (21, Ju), (28, Ma), (30, Sa)],
+        [(6, Sa), (14, Me), (21, Ju), (28, Ve), (30, Ma)],
+        [(7, Ma), (11, Ve), (19, Me), (24, Ju), (30, Sa)],
+        [(12, Ju), (17, Ve), (21, Me), (26, Sa), (30, Ma)],
+        [(7, Me), (14, Ju), (22, Ve), (26, Sa), (30, Ma)],
+        [(7, Me), (13, Ve), (20, Ju), (25, Ma), (30, Sa)],
+        [(12, Ve), (16, Ju), (19, Me), (28, Ma), (30, Sa)],
+    ]
+};
+
+/// One of the Egyptian terms
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Term {
+    pub sign: Sign,
+    pub ruler: Planet,
+    /// Longitudes where the term begins and ends
+    pub start: f64,
+    pub end: f64,
+}
+
+/// The Egyptian term containing an ecliptic longitude
+pub fn term(longitude: f64) -> Term {
+    let longitude = normalize(longitude);
+    let sign = Sign::from_longitude(longitude);
+    let sign_start = (longitude / 30.0).floor().min(11.0) * 30.0;
+    let terms = &EGYPTIAN_TERMS[sign as usize];
+    let index = terms.iter().position(|&(end, _)| longitude - sign_start < end as f64).unwrap_or(terms.len() - 1);
+    let start = index.checked_sub(1).map_or(0, |previous| terms[previous].0);
+    Term {
+        sign,
+        ruler: terms[index].1,
+        start: sign_start + start as f64,
+        end: sign_start + terms[index].0 as f64,
+    }
+}
+
+/// How the zodiac is divided into lunar mansions
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MansionSystem {
+    /// 28 manzils of 12°51'26"
+    #[default]
+    Arabic,
+    /// 27 nakshatras of 13°20'
+    Nakshatra,
+}
+
+impl MansionSystem {
+    /// Names of the mansions in zodiacal order
+    pub fn names(self) -> &'static [&'static str] {
+        match self {
+            MansionSystem::Arabic => &MANZILS,
+            MansionSystem::Nakshatra => &NAKSHATRAS,
+        }
+    }
+
+    /// Width of each mansion, in degrees
+    pub fn width(self) -> f64 {
+        360.0 / self.names().len() as f64
+    }
+}
+
+impl std::fmt::Display for MansionSystem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MansionSystem::Arabic => write!(f, "arabic"),
+            MansionSystem::Nakshatra => write!(f, "nakshatra"),
+        }
+    }
+}
+
+impl std::str::FromStr for MansionSystem {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "arabic" => Ok(MansionSystem::Arabic),
+            "nakshatra" | "vedic" => Ok(MansionSystem::Nakshatra),
+            _ => Err(format!(
+                "Invalid mansion system: {} (expected \"arabic\" or \"nakshatra\")",
+                s
+            )),
+        }
+    }
+}
+
+/// One lunar mansion
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Mansion {
+    pub system: MansionSystem,
+    /// 1-based position from 0° Aries
+    pub index: u8,
+    pub name: &'static str,
+    /// Longitude where the mansion begins
+    pub start: f64,
+}
+
+/// The mansion containing an ecliptic longitude
+pub fn mansion(longitude: f64, system: MansionSystem) -> Mansion {
+    let names = system.names();
+    let width = system.width();
+    let number = ((normalize(longitude) / width) as usize).min(names.len() - 1);
+    Mansion {
+        system,
+        index: number as u8 + 1,
+        name: names[number],
+        start: number as f64 * width,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dms(degrees: f64, minutes: f64, seconds: f64) -> f64 {
+        degrees + minutes / 60.0 + seconds / 3600.0
+    }
+
+    #[test]
+    fn test_decan_boundaries_and_chaldean_rulers() {
+        let first = decan(0.0);
+        assert_eq!((first.sign, first.index, first.ruler), (Sign::Aries, 1, Planet::Mars));
+        let second = decan(19.999);
+        assert_eq!((second.sign, second.index, second.ruler), (Sign::Aries, 2, Planet::Sun));
+        let third = decan(29.999);
+        assert_eq!((third.sign, third.index, third.ruler), (Sign::Aries, 3, Planet::Venus));
+        let taurus = decan(30.0);
+        assert_eq!((taurus.sign, taurus.index, taurus.ruler), (Sign::Taurus, 1, Planet::Mercury));
+        // The sequence runs on through Pisces and meets Mars again at Aries
+        let last = decan(359.999);
+        assert_eq!((last.sign, last.index, last.ruler), (Sign::Pisces, 3, Planet::Mars));
+    }
+
+    #[test]
+    fn test_egyptian_terms() {
+        assert_eq!(term(0.0).ruler, Planet::Jupiter);
+        let venus = term(6.0);
+        assert_eq!((venus.sign, venus.ruler, venus.start, venus.end), (Sign::Aries, Planet::Venus, 6.0, 12.0));
+        assert_eq!(term(29.999).ruler, Planet::Saturn);
+        assert_eq!(term(30.0).ruler, Planet::Venus);
+        let last = term(359.999);
+        assert_eq!((last.sign, last.ruler, last.start), (Sign::Pisces, Planet::Saturn, 358.0));
+
+        // Each planet rules its traditional share of the zodiac
+        let mut degrees = std::collections::HashMap::new();
+        for longitude in 0..360 {
+            *degrees.entry(term(longitude as f64 + 0.5).ruler).or_insert(0) += 1;
+        }
+        assert_eq!(degrees[&Planet::Saturn], 57);
+        assert_eq!(degrees[&Planet::Jupiter], 79);
+        assert_eq!(degrees[&Planet::Mars], 66);
+        assert_eq!(degrees[&Planet::Venus], 82);
+        assert_eq!(degrees[&Planet::Mercury], 76);
+    }
+
+    #[test]
+    fn test_mansion_boundaries() {
+        let before = mansion(dms(12.0, 51.0, 25.0), MansionSystem::Arabic);
+        let after = mansion(dms(12.0, 51.0, 27.0), MansionSystem::Arabic);
+        assert_eq!((before.index, before.name), (1, "Al Sharatain"));
+        assert_eq!((after.index, after.name), (2, "Al Butain"));
+        assert!((after.start - 360.0 / 28.0).abs() < 1e-12);
+        assert_eq!(mansion(359.9999, MansionSystem::Arabic).index, 28);
+
+        assert_eq!(mansion(dms(13.0, 19.0, 59.0), MansionSystem::Nakshatra).name, "Ashwini");
+        assert_eq!(mansion(dms(13.0, 20.0, 1.0), MansionSystem::Nakshatra).name, "Bharani");
+        assert_eq!(mansion(359.9999, MansionSystem::Nakshatra).name, "Revati");
+    }
+
+    #[test]
+    fn test_mansion_system_parses() {
+        assert_eq!("Arabic".parse(), Ok(MansionSystem::Arabic));
+        assert_eq!("nakshatra".parse(), Ok(MansionSystem::Nakshatra));
+        assert!("chinese".parse::<MansionSystem>().is_err());
+    }
+}
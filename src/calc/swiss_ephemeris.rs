@@ -1,81 +1,128 @@
+use crate::calc::angles::normalize;
+use crate::calc::coordinates::Observer;
+use crate::calc::finite::finite;
 use crate::calc::swiss_ephemeris_ffi;
 use crate::core::types::AstrologError;
+use crate::core::types::Ayanamsa;
+use crate::core::types::EphemerisSource;
 use crate::core::types::HouseSystem;
+use std::cell::Cell;
 use std::path::PathBuf;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Mutex;
-use std::sync::Once;
+use std::sync::{Once, OnceLock};
 use swisseph::{self, Planet as SwePlanet};
 
-// Use a local path for ephemeris files
-const EPHE_PATH: &str = "./ephe";
+/// Local path for ephemeris files
+pub const EPHE_PATH: &str = "./ephe";
 
 // Global initialization flag
 static INITIALIZED: AtomicBool = AtomicBool::new(false);
 
+// Whether the required .se1 files were found at initialization
+static FILES_AVAILABLE: AtomicBool = AtomicBool::new(false);
+
+// Ephemeris source used when a request does not ask for one
+static DEFAULT_SOURCE: Mutex<EphemerisSource> = Mutex::new(EphemerisSource::Auto);
+
 // Global Swisseph instance
 static SWISSEPH: Mutex<Option<swisseph::Swisseph>> = Mutex::new(None);
 
 // One-time initialization
 static INIT: Once = Once::new();
 
-/// Swiss Ephemeris planet constants.
-/// These constants are used to identify celestial bodies in the Swiss Ephemeris calculations.
+// Directory the .se1 files were found in, if any
+static FILES_PATH: OnceLock<String> = OnceLock::new();
+
+thread_local! {
+    // Whether this thread's copy of the library state has been pointed at FILES_PATH
+    static FILES_PATH_SET: Cell<bool> = const { Cell::new(false) };
+}
+
+// Swiss Ephemeris planet constants.
+// These constants are used to identify celestial bodies in the Swiss Ephemeris calculations.
+
+/// The Sun
 #[allow(dead_code)]
-pub const SE_SUN: i32 = 0;      /// The Sun
+pub const SE_SUN: i32 = 0;
+/// The Moon
 #[allow(dead_code)]
-pub const SE_MOON: i32 = 1;     /// The Moon
+pub const SE_MOON: i32 = 1;
+/// Mercury
 #[allow(dead_code)]
-pub const SE_MERCURY: i32 = 2;  /// Mercury
+pub const SE_MERCURY: i32 = 2;
+/// Venus
 #[allow(dead_code)]
-pub const SE_VENUS: i32 = 3;    /// Venus
+pub const SE_VENUS: i32 = 3;
+/// Mars
 #[allow(dead_code)]
-pub const SE_MARS: i32 = 4;     /// Mars
+pub const SE_MARS: i32 = 4;
+/// Jupiter
 #[allow(dead_code)]
-pub const SE_JUPITER: i32 = 5;  /// Jupiter
+pub const SE_JUPITER: i32 = 5;
+/// Saturn
 #[allow(dead_code)]
-pub const SE_SATURN: i32 = 6;   /// Saturn
+pub const SE_SATURN: i32 = 6;
+/// Uranus
 #[allow(dead_code)]
-pub const SE_URANUS: i32 = 7;   /// Uranus
+pub const SE_URANUS: i32 = 7;
+/// Neptune
 #[allow(dead_code)]
-pub const SE_NEPTUNE: i32 = 8;  /// Neptune
+pub const SE_NEPTUNE: i32 = 8;
+/// Pluto
 #[allow(dead_code)]
-pub const SE_PLUTO: i32 = 9;    /// Pluto
+pub const SE_PLUTO: i32 = 9;
+/// Mean Lunar Node
 #[allow(dead_code)]
-pub const SE_MEAN_NODE: i32 = 10;  /// Mean Lunar Node
+pub const SE_MEAN_NODE: i32 = 10;
+/// True Lunar Node
 #[allow(dead_code)]
-pub const SE_TRUE_NODE: i32 = 11;   /// True Lunar Node
+pub const SE_TRUE_NODE: i32 = 11;
+/// Chiron
 #[allow(dead_code)]
-pub const SE_CHIRON: i32 = 15;      /// Chiron
+pub const SE_CHIRON: i32 = 15;
+/// Mean Lunar Apogee
 #[allow(dead_code)]
-pub const SE_MEAN_APOG: i32 = 20;   /// Mean Lunar Apogee
+pub const SE_MEAN_APOG: i32 = 20;
+/// Osculating Lunar Apogee
 #[allow(dead_code)]
-pub const SE_OSCU_APOG: i32 = 21;   /// Osculating Lunar Apogee
+pub const SE_OSCU_APOG: i32 = 21;
+/// Earth
 #[allow(dead_code)]
-pub const SE_EARTH: i32 = 14;       /// Earth
+pub const SE_EARTH: i32 = 14;
+/// Ascendant
 #[allow(dead_code)]
-pub const SE_ASC: i32 = 0;          /// Ascendant
+pub const SE_ASC: i32 = 0;
+/// Midheaven
 #[allow(dead_code)]
-pub const SE_MC: i32 = 1;           /// Midheaven
+pub const SE_MC: i32 = 1;
+/// Armc (Apparent Right Ascension of Meridian)
 #[allow(dead_code)]
-pub const SE_ARMC: i32 = 2;         /// Armc (Apparent Right Ascension of Meridian)
+pub const SE_ARMC: i32 = 2;
+/// Vertex
 #[allow(dead_code)]
-pub const SE_VERTEX: i32 = 3;       /// Vertex
+pub const SE_VERTEX: i32 = 3;
+/// Equatorial Ascendant
 #[allow(dead_code)]
-pub const SE_EQUASC: i32 = 4;       /// Equatorial Ascendant
+pub const SE_EQUASC: i32 = 4;
+/// Co-Ascendant 1
 #[allow(dead_code)]
-pub const SE_COASC1: i32 = 5;       /// Co-Ascendant 1
+pub const SE_COASC1: i32 = 5;
+/// Co-Ascendant 2
 #[allow(dead_code)]
-pub const SE_COASC2: i32 = 6;       /// Co-Ascendant 2
+pub const SE_COASC2: i32 = 6;
+/// Polar Ascendant
 #[allow(dead_code)]
-pub const SE_POLASC: i32 = 7;       /// Polar Ascendant
+pub const SE_POLASC: i32 = 7;
+/// Non-Ascending Midheaven
 #[allow(dead_code)]
-pub const SE_NASCMC: i32 = 8;       /// Non-Ascending Midheaven
+pub const SE_NASCMC: i32 = 8;
 
 /// Initializes the Swiss Ephemeris library.
 ///
 /// This function must be called before using any Swiss Ephemeris functions.
-/// It sets up the ephemeris files and initializes the library.
+/// It sets up the ephemeris files and initializes the library. Equivalent to
+/// `init_swiss_ephemeris_with(EPHE_PATH, EphemerisSource::Auto)`.
 ///
 /// # Returns
 ///
@@ -93,36 +140,82 @@ pub const SE_NASCMC: i32 = 8;       /// Non-Ascending Midheaven
 /// ```
 #[allow(dead_code)]
 pub fn init_swiss_ephemeris() -> Result<(), AstrologError> {
-    // Only initialize once
+    init_swiss_ephemeris_with(EPHE_PATH, EphemerisSource::Auto)
+}
+
+/// Initializes the Swiss Ephemeris library with an explicit ephemeris
+/// directory and default source.
+///
+/// Missing `.se1` files are only fatal when `source` is
+/// `EphemerisSource::Swiss`; with `Auto` or `Moshier` the library falls back
+/// to the built-in Moshier theory. Only the first call in a process takes
+/// effect; later calls just report whether initialization succeeded.
+///
+/// # Arguments
+///
+/// * `ephe_path` - Directory holding the `.se1` files
+/// * `source` - Default source for requests that do not specify one
+///
+/// # Returns
+///
+/// A Result indicating success or failure of initialization
+///
+/// # Examples
+///
+/// ```
+/// use astrolog_rs::calc::swiss_ephemeris::init_swiss_ephemeris_with;
+/// use astrolog_rs::core::types::EphemerisSource;
+///
+/// match init_swiss_ephemeris_with("./ephe", EphemerisSource::Moshier) {
+///     Ok(_) => println!("Running on the Moshier ephemeris"),
+///     Err(e) => println!("Failed to initialize Swiss Ephemeris: {}", e),
+/// }
+/// ```
+#[allow(dead_code)]
+pub fn init_swiss_ephemeris_with(
+    ephe_path: &str,
+    source: EphemerisSource,
+) -> Result<(), AstrologError> {
+    if source == EphemerisSource::Analytic {
+        return Err(AstrologError::InvalidInput {
+            message: "the analytic source is only available without the `swiss` feature"
+                .to_string(),
+            parameter: "ephemeris".to_string(),
+        });
+    }
+
+    init(Some(ephe_path), source)
+}
+
+/// Initializes the Swiss Ephemeris library on the Moshier theory alone.
+///
+/// The Moshier theory is compiled into the library, so this needs no `.se1`
+/// files and ignores any in `EPHE_PATH`: results are the same on every machine.
+/// Requests for the "swiss" source fail and "auto" uses Moshier, as when the
+/// files are missing. The test suite runs on it.
+///
+/// # Returns
+///
+/// A Result indicating success or failure of initialization
+pub fn init_moshier_ephemeris() -> Result<(), AstrologError> {
+    init(None, EphemerisSource::Moshier)
+}
+
+/// Initializes the library once, reading `.se1` files from `ephe_path` when
+/// there is one
+fn init(ephe_path: Option<&str>, source: EphemerisSource) -> Result<(), AstrologError> {
     INIT.call_once(|| {
-        // Create the ephemeris directory if it doesn't exist
-        let ephe_path = PathBuf::from(EPHE_PATH);
-        if let Err(e) = std::fs::create_dir_all(&ephe_path) {
-            eprintln!("Failed to create ephemeris directory: {}", e);
-            return;
+        let mut swe = swisseph::Swisseph::new();
+        if let Some(ephe_path) = ephe_path {
+            if !find_ephemeris_files(&mut swe, ephe_path, source) {
+                return;
+            }
         }
 
-        // Check if required ephemeris files exist
-        let required_files = ["seas_18.se1", "semo_18.se1", "sepl_18.se1"];
-        let missing_files: Vec<String> = required_files
-            .iter()
-            .filter(|&&file| !ephe_path.join(file).exists())
-            .map(|&s| s.to_string())
-            .collect();
-
-        if !missing_files.is_empty() {
-            eprintln!(
-                "Missing required ephemeris files: {}. Please download the Swiss Ephemeris package from https://www.astro.com/swisseph/ and place the files in the {} directory.",
-                missing_files.join(", "),
-                EPHE_PATH
-            );
-            return;
+        if let Ok(mut default_source) = DEFAULT_SOURCE.lock() {
+            *default_source = source;
         }
 
-        // Create a new Swisseph instance and set the path
-        let mut swe = swisseph::Swisseph::new();
-        swe.set_ephe_path(swisseph::EphePath::from(EPHE_PATH));
-
         // Store the instance
         if let Ok(mut guard) = SWISSEPH.lock() {
             *guard = Some(swe);
@@ -139,6 +232,127 @@ pub fn init_swiss_ephemeris() -> Result<(), AstrologError> {
     Ok(())
 }
 
+/// Points the library at the ephemeris directory and records whether the
+/// required files are there. Returns false when initialization can't go on.
+fn find_ephemeris_files(swe: &mut swisseph::Swisseph, ephe_path: &str, source: EphemerisSource) -> bool {
+    // Create the ephemeris directory if it doesn't exist
+    let ephe_dir = PathBuf::from(ephe_path);
+    if let Err(e) = std::fs::create_dir_all(&ephe_dir) {
+        eprintln!("Failed to create ephemeris directory: {}", e);
+        return false;
+    }
+
+    // Check if required ephemeris files exist
+    let required_files = ["seas_18.se1", "semo_18.se1", "sepl_18.se1"];
+    let missing_files: Vec<String> = required_files
+        .iter()
+        .filter(|&&file| !ephe_dir.join(file).exists())
+        .map(|&s| s.to_string())
+        .collect();
+
+    if !missing_files.is_empty() {
+        eprintln!(
+            "Missing required ephemeris files: {}. Please download the Swiss Ephemeris package from https://www.astro.com/swisseph/ and place the files in the {} directory.",
+            missing_files.join(", "),
+            ephe_path
+        );
+        if source == EphemerisSource::Swiss {
+            return false;
+        }
+        eprintln!("Falling back to the Moshier ephemeris");
+    }
+    FILES_AVAILABLE.store(missing_files.is_empty(), Ordering::SeqCst);
+
+    // Set the path
+    if let Err(e) = swe.set_ephe_path(swisseph::EphePath::from(ephe_path)) {
+        eprintln!("Failed to set ephemeris path: {}", e);
+        return false;
+    }
+    FILES_PATH_SET.set(true);
+    let _ = FILES_PATH.set(ephe_path.to_string());
+    true
+}
+
+/// Longitude and latitude in degrees, distance in AU and speed in degrees per day
+pub type SwissPosition = (f64, f64, f64, f64);
+
+/// Returns the ephemeris source configured at initialization.
+pub fn default_ephemeris_source() -> EphemerisSource {
+    DEFAULT_SOURCE
+        .lock()
+        .map(|source| *source)
+        .unwrap_or_default()
+}
+
+/// Returns true if the Swiss Ephemeris `.se1` files were found at initialization.
+pub fn ephemeris_files_available() -> bool {
+    FILES_AVAILABLE.load(Ordering::SeqCst)
+}
+
+/// Returns the version of the linked Swiss Ephemeris library, e.g. "2.10.03".
+pub fn library_version() -> &'static str {
+    static VERSION: OnceLock<String> = OnceLock::new();
+    VERSION.get_or_init(swisseph::get_version)
+}
+
+/// Returns Delta T (TT - UT) in seconds as the given source applies it at a UT
+/// Julian date. The Moshier theory uses a slightly different tidal acceleration.
+pub fn delta_t_seconds(jd: f64, source: EphemerisSource) -> Result<f64, AstrologError> {
+    let guard = SWISSEPH
+        .lock()
+        .map_err(|_| AstrologError::CalculationError {
+            message: "Failed to acquire Swiss Ephemeris lock".to_string(),
+        })?;
+    let swe = guard
+        .as_ref()
+        .ok_or_else(|| AstrologError::CalculationError {
+            message: "Swiss Ephemeris instance not available".to_string(),
+        })?;
+    let flags = match source {
+        EphemerisSource::Moshier => swisseph::Flags::default().with_moshier(),
+        _ => swisseph::Flags::default(),
+    };
+    Ok(swe.deltat(jd, flags) * 86400.0)
+}
+
+/// The library's sidereal mode for an ayanamsa
+pub fn sid_mode(ayanamsa: Ayanamsa) -> i32 {
+    match ayanamsa {
+        Ayanamsa::Lahiri => swisseph::SE_SIDM_LAHIRI,
+        Ayanamsa::FaganBradley => swisseph::SE_SIDM_FAGAN_BRADLEY,
+        Ayanamsa::Krishnamurti => swisseph::SE_SIDM_KRISHNAMURTI,
+        Ayanamsa::Raman => swisseph::SE_SIDM_RAMAN,
+    }
+}
+
+/// Returns the ayanamsa in degrees at a UT Julian date as the library measures
+/// it, with nutation, so that a tropical position or house cusp less it is the
+/// one `SEFLG_SIDEREAL` gives. The source chooses the nutation model.
+pub fn ayanamsa_ut(jd: f64, ayanamsa: Ayanamsa, source: EphemerisSource) -> Result<f64, AstrologError> {
+    let guard = SWISSEPH
+        .lock()
+        .map_err(|_| AstrologError::CalculationError {
+            message: "Failed to acquire Swiss Ephemeris lock".to_string(),
+        })?;
+    let swe = guard
+        .as_ref()
+        .ok_or_else(|| AstrologError::CalculationError {
+            message: "Swiss Ephemeris instance not available".to_string(),
+        })?;
+    let flags = match source {
+        EphemerisSource::Swiss => swisseph::Flags::default(),
+        EphemerisSource::Auto if ephemeris_files_available() => swisseph::Flags::default(),
+        _ => swisseph::Flags::default().with_moshier(),
+    };
+    // The sidereal mode is library state, set under the same lock as the call
+    // that reads it
+    swe.set_sid_mode(sid_mode(ayanamsa));
+    let degrees = swe.get_ayanamsa_ex_ut(jd, flags).map_err(|e| AstrologError::CalculationError {
+        message: format!("Swiss Ephemeris error: {e}"),
+    })?;
+    finite(degrees, format_args!("{} ayanamsa", ayanamsa))
+}
+
 /// Calculates the position of a planet using the Swiss Ephemeris.
 ///
 /// This function calculates the geocentric position of a planet at a given time.
@@ -181,41 +395,266 @@ pub fn calculate_planet_position_swiss(
     day: i32,
     hour: f64,
 ) -> Result<(f64, f64, f64, f64), AstrologError> {
+    calculate_planet_position_with_source(
+        planet,
+        year,
+        month,
+        day,
+        hour,
+        default_ephemeris_source(),
+    )
+    .map(|(position, _)| position)
+}
+
+/// Calculates the position of a planet from a specific ephemeris source.
+///
+/// `EphemerisSource::Auto` uses the `.se1` files when they are available and
+/// the Moshier theory otherwise. The Moshier theory only covers the Sun, Moon,
+/// planets, nodes and apogees, so bodies such as Chiron fail with a per-body
+/// error in that mode.
+///
+/// # Arguments
+///
+/// * `planet` - The Swiss Ephemeris planet number
+/// * `year` - The year
+/// * `month` - The month (1-12)
+/// * `day` - The day (1-31)
+/// * `hour` - The hour (0-23)
+/// * `source` - The ephemeris source to calculate from
+///
+/// # Returns
+///
+/// A Result containing the `(longitude, latitude, distance, speed)` tuple
+/// and the source that was actually used (`Swiss` or `Moshier`)
+///
+/// # Examples
+///
+/// ```
+/// use astrolog_rs::calc::swiss_ephemeris::calculate_planet_position_with_source;
+/// use astrolog_rs::core::types::EphemerisSource;
+/// use swisseph::Planet;
+///
+/// match calculate_planet_position_with_source(Planet::Sun, 2000, 1, 1, 12.0, EphemerisSource::Auto) {
+///     Ok(((longitude, _, _, _), source)) => {
+///         println!("Sun at {}° from the {} ephemeris", longitude, source);
+///     },
+///     Err(e) => println!("Error calculating planet position: {}", e),
+/// }
+/// ```
+pub fn calculate_planet_position_with_source(
+    planet: SwePlanet,
+    year: i32,
+    month: i32,
+    day: i32,
+    hour: f64,
+    source: EphemerisSource,
+) -> Result<(SwissPosition, EphemerisSource), AstrologError> {
+    position_with_flags(planet, Moment::Calendar(year, month, day, hour), source, swisseph::Flags::default())
+}
+
+/// Calculates the position of a planet at a Julian date (UT) from a specific
+/// ephemeris source. See `calculate_planet_position_with_source`.
+pub fn calculate_planet_position_at(
+    planet: SwePlanet,
+    jd: f64,
+    source: EphemerisSource,
+) -> Result<(SwissPosition, EphemerisSource), AstrologError> {
+    position_with_flags(planet, Moment::Julian(jd), source, swisseph::Flags::default())
+}
+
+/// Calculates the right ascension and declination of a planet at a Julian date
+/// (UT) with `SEFLG_EQUATORIAL`, on the true equator and equinox of date. The
+/// first two fields of the position are the right ascension and declination.
+pub fn calculate_equatorial_position_at(
+    planet: SwePlanet,
+    jd: f64,
+    source: EphemerisSource,
+) -> Result<(SwissPosition, EphemerisSource), AstrologError> {
+    position_with_flags(planet, Moment::Julian(jd), source, swisseph::Flags::default().with_equatorial())
+}
+
+/// Calculates the position of a planet as seen from a place on the Earth's
+/// surface rather than its centre, at a Julian date (UT). Only the Moon is
+/// displaced by much, up to about a degree.
+pub fn calculate_topocentric_position_at(
+    planet: SwePlanet,
+    jd: f64,
+    source: EphemerisSource,
+    observer: Observer,
+) -> Result<(SwissPosition, EphemerisSource), AstrologError> {
+    position_seen_from(planet, Moment::Julian(jd), source, swisseph::Flags::default(), Some(observer))
+}
+
+/// Calculates the position of a planet as seen from the Sun, from a specific
+/// ephemeris source. See `calculate_planet_position_with_source`; the speed is
+/// the library's own rather than a difference of positions.
+pub fn calculate_heliocentric_position(
+    planet: SwePlanet,
+    year: i32,
+    month: i32,
+    day: i32,
+    hour: f64,
+    source: EphemerisSource,
+) -> Result<(SwissPosition, EphemerisSource), AstrologError> {
+    position_with_flags(
+        planet,
+        Moment::Calendar(year, month, day, hour),
+        source,
+        swisseph::Flags::default().with_heliocentric(),
+    )
+}
+
+/// Calculates the position of a body as seen from the Sun, measured in the
+/// Lahiri sidereal zodiac, at a Julian date (UT). The library applies both
+/// `SEFLG_HELCTR` and `SEFLG_SIDEREAL`, so `SwePlanet::Earth` has a position too.
+pub fn calculate_heliocentric_sidereal_position_at(
+    planet: SwePlanet,
+    jd: f64,
+    source: EphemerisSource,
+) -> Result<(SwissPosition, EphemerisSource), AstrologError> {
+    position_with_flags(
+        planet,
+        Moment::Julian(jd),
+        source,
+        swisseph::Flags::default().with_heliocentric().with_sidereal(),
+    )
+}
+
+/// When a position is asked for: a Gregorian date and hour, or a Julian date (UT)
+enum Moment {
+    Calendar(i32, i32, i32, f64),
+    Julian(f64),
+}
+
+fn position_with_flags(
+    planet: SwePlanet,
+    moment: Moment,
+    source: EphemerisSource,
+    flags: swisseph::Flags,
+) -> Result<(SwissPosition, EphemerisSource), AstrologError> {
+    position_seen_from(planet, moment, source, flags, None)
+}
+
+/// Every position goes through here. The library keeps the observer
+/// (`swe_set_topo`) and the sidereal mode (`swe_set_sid_mode`) in library
+/// state, so both are set from this call's own arguments, under the same lock
+/// as the calculation that reads them. Nothing is left for a later call to
+/// pick up: a call without an observer doesn't pass `SEFLG_TOPOCTR`, so it
+/// never sees the last call's.
+///
+/// That state is thread-local, the ephemeris path included, so the first
+/// position on any thread but the one that initialized the library points it
+/// at the files; otherwise it would quietly fall back to Moshier.
+fn position_seen_from(
+    planet: SwePlanet,
+    moment: Moment,
+    source: EphemerisSource,
+    flags: swisseph::Flags,
+    observer: Option<Observer>,
+) -> Result<(SwissPosition, EphemerisSource), AstrologError> {
     if !INITIALIZED.load(Ordering::SeqCst) {
         return Err(AstrologError::CalculationError {
             message: "Swiss Ephemeris not initialized".to_string(),
         });
     }
 
-    let guard = SWISSEPH
+    let requested = match source {
+        EphemerisSource::Auto if ephemeris_files_available() => EphemerisSource::Swiss,
+        EphemerisSource::Auto => EphemerisSource::Moshier,
+        EphemerisSource::Swiss if !ephemeris_files_available() => {
+            return Err(AstrologError::CalculationError {
+                message: "Swiss Ephemeris files are not available".to_string(),
+            });
+        }
+        EphemerisSource::Analytic => {
+            return Err(AstrologError::InvalidInput {
+                message: "the analytic source is only available without the `swiss` feature"
+                    .to_string(),
+                parameter: "ephemeris".to_string(),
+            });
+        }
+        other => other,
+    };
+
+    if requested == EphemerisSource::Moshier && !moshier_supports(planet) {
+        return Err(AstrologError::CalculationError {
+            message: format!("{:?} is not available from the Moshier ephemeris", planet),
+        });
+    }
+
+    let mut guard = SWISSEPH
         .lock()
         .map_err(|_| AstrologError::CalculationError {
             message: "Failed to acquire Swiss Ephemeris lock".to_string(),
         })?;
 
     let swe = guard
-        .as_ref()
+        .as_mut()
         .ok_or_else(|| AstrologError::CalculationError {
             message: "Swiss Ephemeris instance not available".to_string(),
         })?;
 
-    let jd = swe.julday(year, month, day, hour, true); // true = Gregorian
+    if let Some(files_path) = FILES_PATH.get().filter(|_| !FILES_PATH_SET.get()) {
+        swe.set_ephe_path(swisseph::EphePath::from(files_path.as_str()))
+            .map_err(|e| AstrologError::CalculationError {
+                message: format!("Failed to set ephemeris path: {e}"),
+            })?;
+        FILES_PATH_SET.set(true);
+    }
+
+    let jd = match moment {
+        Moment::Calendar(year, month, day, hour) => swe.julday(year, month, day, hour, true), // true = Gregorian
+        Moment::Julian(jd) => jd,
+    };
 
-    // Use default flags for geocentric positions
-    let flags = swisseph::Flags::default();
-    let pos = swe
-        .calc_ut(jd, planet, flags)
+    let mut flags = match requested {
+        EphemerisSource::Moshier => flags.with_moshier(),
+        _ => flags,
+    };
+    if let Some(observer) = observer {
+        swe.set_topo(observer.longitude, observer.latitude, observer.altitude);
+        flags = flags.with_topocentric();
+    }
+    // Sidereal positions are measured from the Lahiri zero point, as
+    // `positions::lahiri_ayanamsa` is. The mode is library state that closing
+    // any instance resets, so it is set on every call.
+    if flags.0 & swisseph::SEFLG_SIDEREAL != 0 {
+        swe.set_sid_mode(sid_mode(Ayanamsa::Lahiri));
+    }
+    let (pos, used_flags) = swe
+        .calc_ut_flags(jd, planet, flags)
         .map_err(|e| AstrologError::CalculationError {
             message: format!("Swiss Ephemeris error: {e}"),
         })?;
 
+    // The library quietly switches to Moshier when a file it needs is missing
+    let used = if used_flags.0 & swisseph::SEFLG_MOSEPH != 0 {
+        EphemerisSource::Moshier
+    } else {
+        EphemerisSource::Swiss
+    };
+    if source == EphemerisSource::Swiss && used != EphemerisSource::Swiss {
+        return Err(AstrologError::CalculationError {
+            message: format!("Swiss Ephemeris file for {:?} is not available", planet),
+        });
+    }
+
     // Convert to zodiacal longitude (0-360 degrees)
-    let longitude = pos[0].rem_euclid(360.0);
-    let latitude = pos[1];
-    let distance = pos[2];
-    let speed = pos[3];
+    let longitude = normalize(finite(pos[0], format_args!("{:?} longitude", planet))?);
+    let latitude = finite(pos[1], format_args!("{:?} latitude", planet))?;
+    let distance = finite(pos[2], format_args!("{:?} distance", planet))?;
+    let speed = finite(pos[3], format_args!("{:?} speed", planet))?;
 
-    Ok((longitude, latitude, distance, speed))
+    Ok(((longitude, latitude, distance, speed), used))
+}
+
+/// Bodies covered by the built-in Moshier theory; Chiron and the asteroids need
+/// the `seas_*.se1` files
+fn moshier_supports(planet: SwePlanet) -> bool {
+    !matches!(
+        planet,
+        SwePlanet::Chiron | SwePlanet::Ceres | SwePlanet::Pallas | SwePlanet::Juno | SwePlanet::Vesta
+    )
 }
 
 /// Maps an astrolog Planet enum to a Swiss Ephemeris planet number.
@@ -257,10 +696,34 @@ pub fn map_planet_to_swe(planet: crate::calc::planets::Planet) -> Option<SwePlan
         crate::calc::planets::Planet::Pluto => Some(SwePlanet::Pluto),
         crate::calc::planets::Planet::MeanNode => Some(SwePlanet::MeanNode),
         crate::calc::planets::Planet::TrueNode => Some(SwePlanet::TrueNode),
+        crate::calc::planets::Planet::MeanLilith => Some(SwePlanet::MeanApogee),
+        crate::calc::planets::Planet::TrueLilith => Some(SwePlanet::OscuApogee),
+        crate::calc::planets::Planet::Chiron => Some(SwePlanet::Chiron),
+        crate::calc::planets::Planet::Ceres => Some(SwePlanet::Ceres),
+        crate::calc::planets::Planet::Pallas => Some(SwePlanet::Pallas),
+        crate::calc::planets::Planet::Juno => Some(SwePlanet::Juno),
+        crate::calc::planets::Planet::Vesta => Some(SwePlanet::Vesta),
         _ => None,
     }
 }
 
+/// House cusps as filled in by `swe_houses`, tagged with how many there are.
+/// Most systems have 12 cusps; Gauquelin sectors have 36.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RawCusps {
+    /// Number of cusps filled in
+    pub count: usize,
+    /// Index 0 is unused, as in the C API
+    values: [f64; 37],
+}
+
+impl RawCusps {
+    /// The cusp longitudes, first cusp first
+    pub fn as_slice(&self) -> &[f64] {
+        &self.values[1..=self.count]
+    }
+}
+
 /// Calculates house cusps using the Swiss Ephemeris.
 ///
 /// This function calculates the positions of the house cusps using the
@@ -276,8 +739,8 @@ pub fn map_planet_to_swe(planet: crate::calc::planets::Planet) -> Option<SwePlan
 /// # Returns
 ///
 /// A Result containing a tuple with:
-/// * A vector of 13 house cusp positions (0-12) in degrees (0-360)
-/// * A tuple of (Ascendant, MC) positions in degrees
+/// * The house cusp positions in degrees (0-360), 12 of them or 36 for Gauquelin
+/// * The angles [Ascendant, MC, ARMC, Vertex, ...] in degrees
 ///
 /// # Examples
 ///
@@ -291,9 +754,10 @@ pub fn map_planet_to_swe(planet: crate::calc::planets::Planet) -> Option<SwePlan
 ///
 /// match calculate_house_cusps_swiss(julian_date, latitude, longitude, HouseSystem::Placidus) {
 ///     Ok((cusps, ascmc)) => {
+///         assert_eq!(cusps.count, 12);
 ///         println!("Ascendant: {}°, MC: {}°", ascmc[0], ascmc[1]);
-///         for (i, cusp) in cusps.iter().enumerate() {
-///             println!("House {} cusp: {}°", i, cusp);
+///         for (i, cusp) in cusps.as_slice().iter().enumerate() {
+///             println!("House {} cusp: {}°", i + 1, cusp);
 ///         }
 ///     },
 ///     Err(e) => println!("Error calculating house cusps: {}", e),
@@ -304,8 +768,9 @@ pub fn calculate_house_cusps_swiss(
     geolat: f64,
     geolon: f64,
     house_system: HouseSystem,
-) -> Result<([f64; 13], [f64; 10]), AstrologError> {
-    let mut cusps = [0.0f64; 13];
+) -> Result<(RawCusps, [f64; 10]), AstrologError> {
+    // swe_houses writes cusps 1-36 for Gauquelin sectors, 1-12 otherwise
+    let mut cusps = [0.0f64; 37];
     let mut ascmc = [0.0f64; 10];
 
     // Map our house systems to Swiss Ephemeris codes
@@ -324,8 +789,15 @@ pub fn calculate_house_cusps_swiss(
         HouseSystem::Krusinski => b'U',
         HouseSystem::Vedic => b'W', // Use whole sign for Vedic
         HouseSystem::Null => b'A',  // Use equal for Null
+        HouseSystem::Gauquelin => b'G',
+        HouseSystem::Sunshine => b'i', // Makransky's solution
     };
 
+    // swe_houses shares the library's global state with the position calls,
+    // so it waits for them like they wait for each other
+    let _guard = SWISSEPH.lock().map_err(|_| AstrologError::CalculationError {
+        message: "Failed to acquire Swiss Ephemeris lock".to_string(),
+    })?;
     let ret = unsafe {
         swiss_ephemeris_ffi::swe_houses(
             jd_ut,
@@ -341,5 +813,9 @@ pub fn calculate_house_cusps_swiss(
             message: "Swiss Ephemeris swe_houses failed".to_string(),
         });
     }
-    Ok((cusps, ascmc))
+    let raw = RawCusps {
+        count: house_system.cusp_count(),
+        values: cusps,
+    };
+    Ok((raw, ascmc))
 }
@@ -1,4 +1,5 @@
 use super::super::planets::*;
+use crate::calc::aspects::OrbConfig;
 use crate::core::AstrologError;
 
 #[test]
@@ -200,7 +201,7 @@ fn test_calculate_planetary_aspects() {
         },
     ];
     
-    let orbs = vec![10.0, 10.0, 10.0, 10.0, 10.0];
+    let orbs = OrbConfig::uniform(10.0);
     
     let aspects = calculate_planetary_aspects(&positions, &orbs);
     assert!(!aspects.is_empty());
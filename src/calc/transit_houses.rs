@@ -0,0 +1,115 @@
+//! The natal house each transiting body is in, and when it entered it.
+//!
+//! The entry is the last time before the transit moment that the body crossed
+//! either cusp of the house, found with the sampler and root-finder the sign
+//! ingresses use. A body that backed over the far cusp while retrograde
+//! entered the house there.
+
+use crate::calc::aspects::{crossings, sample_motion};
+use crate::calc::cycles::speed_at;
+use crate::calc::houses::house_of;
+use crate::calc::planets::{max_daily_motion, planet_longitude_at, Planet};
+use crate::core::types::EphemerisSource;
+
+/// How far back an entry is looked for. Pluto can spend decades in a wide
+/// house; a body that has been in its house longer has no entry date.
+pub const MAX_LOOKBACK_DAYS: f64 = 100.0 * 365.25;
+
+/// A transiting body's place among the natal houses
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TransitHouse {
+    pub planet: Planet,
+    pub house: u8,
+    /// Julian date (UT) the body entered the house, if within `MAX_LOOKBACK_DAYS`
+    pub entered: Option<f64>,
+    /// Whether it entered while retrograde, over the house's far cusp
+    pub retrograde: bool,
+}
+
+/// The natal house `planet` is in at `jd`, given the natal `cusps`, and when
+/// it got there
+pub fn transit_house(planet: Planet, jd: f64, cusps: &[f64; 12], source: EphemerisSource) -> Result<TransitHouse, String> {
+    let longitude = |jd: f64| planet_longitude_at(planet, jd, source);
+    let house = house_of(longitude(jd)?, cusps).house;
+    let bounds = [cusps[house as usize - 1], cusps[house as usize % 12]];
+
+    // Search backwards a window at a time, about one trip around the zodiac
+    // for the fast bodies and a year for the slow ones
+    let daily_motion = max_daily_motion(planet);
+    let window = (360.0 / daily_motion).clamp(1.0, 365.25);
+    let mut to = jd;
+    while jd - to < MAX_LOOKBACK_DAYS {
+        let from = (to - window).max(jd - MAX_LOOKBACK_DAYS);
+        let samples = sample_motion(from, to - from, daily_motion, longitude)?;
+        let mut latest: Option<f64> = None;
+        for cusp in bounds {
+            for crossing in crossings(&samples, cusp, longitude)? {
+                if crossing <= jd && latest.is_none_or(|latest| crossing > latest) {
+                    latest = Some(crossing);
+                }
+            }
+        }
+        if let Some(entered) = latest {
+            return Ok(TransitHouse {
+                planet,
+                house,
+                entered: Some(entered),
+                retrograde: speed_at(planet, entered, source)? < 0.0,
+            });
+        }
+        to = from;
+    }
+    Ok(TransitHouse {
+        planet,
+        house,
+        entered: None,
+        retrograde: false,
+    })
+}
+
+#[cfg(all(test, feature = "swiss"))]
+mod tests {
+    use super::*;
+    use crate::calc::houses::calculate_houses;
+    use crate::calc::swiss_ephemeris;
+    use crate::core::types::HouseSystem;
+
+    #[test]
+    fn test_a_planet_just_past_a_cusp_entered_recently() {
+        swiss_ephemeris::init_moshier_ephemeris().unwrap();
+        let natal = calculate_houses(2443440.70556, 14.6488, 121.0509, HouseSystem::Placidus).unwrap();
+        let cusps = natal.cusp_longitudes().unwrap();
+
+        // Find a moment the Sun is 1° past the seventh cusp: it moves about a
+        // degree a day, so it crossed the cusp about a day earlier
+        let source = EphemerisSource::Moshier;
+        let start = 2460310.5;
+        let samples = sample_motion(start, 366.0, max_daily_motion(Planet::Sun), |jd| planet_longitude_at(Planet::Sun, jd, source)).unwrap();
+        let cusp_crossing = crossings(&samples, cusps[6], |jd| planet_longitude_at(Planet::Sun, jd, source)).unwrap()[0];
+        let past = crossings(&samples, cusps[6] + 1.0, |jd| planet_longitude_at(Planet::Sun, jd, source)).unwrap()[0];
+
+        let placed = transit_house(Planet::Sun, past, &cusps, source).unwrap();
+        assert_eq!(placed.house, 7);
+        let entered = placed.entered.unwrap();
+        assert!((entered - cusp_crossing).abs() < 1e-4);
+        assert!((0.9..1.1).contains(&(past - entered)), "{}", past - entered);
+        assert!(!placed.retrograde);
+    }
+
+    #[test]
+    fn test_retrograde_entry_is_over_the_far_cusp() {
+        swiss_ephemeris::init_moshier_ephemeris().unwrap();
+        // Mercury stationed retrograde on 13 December 2023 near 8°29' Capricorn
+        // and backed into Sagittarius on 23 December. With equal houses from
+        // 0° Capricorn, it entered the twelfth house over the first cusp.
+        let mut cusps = [0.0; 12];
+        for (i, cusp) in cusps.iter_mut().enumerate() {
+            *cusp = (270.0 + 30.0 * i as f64) % 360.0;
+        }
+        let placed = transit_house(Planet::Mercury, 2460307.5, &cusps, EphemerisSource::Moshier).unwrap();
+        assert_eq!(placed.house, 12);
+        assert!(placed.retrograde);
+        let entered = placed.entered.unwrap();
+        assert!((2460301.0..2460303.5).contains(&entered), "{}", entered);
+    }
+}
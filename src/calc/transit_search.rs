@@ -0,0 +1,228 @@
+//! Exact transits to a natal chart.
+//!
+//! `exact_transits` finds each time a transiting body perfects an aspect to a
+//! natal longitude within a date range. Around each hit it also finds when the
+//! body came within `BUILD_UP_ORB` of exact and when it went out again, so a
+//! retrograde loop that perfects the aspect three times gives three hits
+//! sharing one orb period. The lunar nodes transit too, mostly retrograde.
+
+use crate::calc::angles::signed_delta;
+use crate::calc::aspects::{crossings, sample_motion, AspectType};
+use crate::calc::nodes::south_node;
+use crate::calc::planets::{can_retrograde, max_daily_motion, planet_longitude_at, Planet};
+use crate::calc::positions::parse_bodies;
+use crate::core::types::EphemerisSource;
+use std::fmt;
+
+/// Degrees from exact at which a transit's build-up begins and ends
+pub const BUILD_UP_ORB: f64 = 1.0;
+
+/// Days either side of the range to look for the build-up of bodies that
+/// station; the slowest can stay within orb of a point for over a year
+const STATIONING_BUILD_UP_DAYS: f64 = 400.0;
+
+/// Days either side of the range to look for the Sun's and the Moon's build-up
+const DIRECT_BUILD_UP_DAYS: f64 = 2.0;
+
+/// The natal points transits are looked for to: Sun through Pluto
+pub const NATAL_PLANETS: [Planet; 10] = [
+    Planet::Sun,
+    Planet::Moon,
+    Planet::Mercury,
+    Planet::Venus,
+    Planet::Mars,
+    Planet::Jupiter,
+    Planet::Saturn,
+    Planet::Uranus,
+    Planet::Neptune,
+    Planet::Pluto,
+];
+
+/// A body transits are looked for from: a planet, or one of the true nodes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TransitingBody {
+    Planet(Planet),
+    /// The true North Node
+    NorthNode,
+    /// Opposite the true North Node
+    SouthNode,
+}
+
+impl TransitingBody {
+    /// The body the ephemeris is asked for
+    pub fn planet(self) -> Planet {
+        match self {
+            TransitingBody::Planet(planet) => planet,
+            TransitingBody::NorthNode | TransitingBody::SouthNode => Planet::TrueNode,
+        }
+    }
+
+    pub fn longitude_at(self, jd: f64, source: EphemerisSource) -> Result<f64, String> {
+        let longitude = planet_longitude_at(self.planet(), jd, source)?;
+        Ok(if self == TransitingBody::SouthNode { south_node(longitude) } else { longitude })
+    }
+}
+
+impl From<Planet> for TransitingBody {
+    fn from(planet: Planet) -> Self {
+        TransitingBody::Planet(planet)
+    }
+}
+
+impl fmt::Display for TransitingBody {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TransitingBody::Planet(planet) => write!(f, "{}", planet),
+            TransitingBody::NorthNode => f.write_str("North Node"),
+            TransitingBody::SouthNode => f.write_str("South Node"),
+        }
+    }
+}
+
+/// Parses a comma-separated list of transiting bodies: the bodies
+/// `parse_bodies` takes, "north_node" and "south_node"
+pub fn parse_transiting(list: &str) -> Result<Vec<TransitingBody>, String> {
+    let mut bodies = Vec::new();
+    for name in list.split(',').map(str::trim) {
+        let body = match name.to_lowercase().as_str() {
+            "north_node" => TransitingBody::NorthNode,
+            "south_node" => TransitingBody::SouthNode,
+            _ => TransitingBody::Planet(parse_bodies(name)?[0]),
+        };
+        if bodies.contains(&body) {
+            return Err(format!("Body '{}' is listed more than once", name));
+        }
+        bodies.push(body);
+    }
+    Ok(bodies)
+}
+
+/// One time a transiting body perfects an aspect to a natal point
+#[derive(Debug, Clone, PartialEq)]
+pub struct TransitHit {
+    pub transiting: TransitingBody,
+    /// Name of the natal point
+    pub natal: String,
+    pub aspect: AspectType,
+    /// Julian date (UT) of the exact aspect
+    pub jd: f64,
+    /// When the body last came within `BUILD_UP_ORB`; `None` when that was too
+    /// long before the range to look for
+    pub orb_entered: Option<f64>,
+    /// When the body next goes out of `BUILD_UP_ORB`, likewise
+    pub orb_left: Option<f64>,
+}
+
+/// Finds the exact transits of `transiting` bodies to the `natal` points
+/// (name and longitude) between `jd_from` and `jd_to`, earliest first.
+pub fn exact_transits(
+    natal: &[(String, f64)],
+    transiting: &[TransitingBody],
+    aspects: &[AspectType],
+    jd_from: f64,
+    jd_to: f64,
+    source: EphemerisSource,
+) -> Result<Vec<TransitHit>, String> {
+    if jd_to <= jd_from {
+        return Err("The end of the search is not after its start".to_string());
+    }
+    let mut hits = Vec::new();
+    for &body in transiting {
+        let planet = body.planet();
+        let margin = if can_retrograde(planet) { STATIONING_BUILD_UP_DAYS } else { DIRECT_BUILD_UP_DAYS };
+        let start = jd_from - margin;
+        let longitude = |jd: f64| body.longitude_at(jd, source);
+        let samples = sample_motion(start, jd_to - jd_from + 2.0 * margin, max_daily_motion(planet), longitude)?;
+
+        for (name, natal_longitude) in natal {
+            let natal_longitude = *natal_longitude;
+            let distance = |jd: f64| longitude(jd).map(|at| signed_delta(at, natal_longitude));
+            let from_natal: Vec<(f64, f64)> = samples
+                .iter()
+                .map(|&(jd, at)| (jd, signed_delta(at, natal_longitude)))
+                .collect();
+
+            for &aspect in aspects {
+                let angle = aspect.angle();
+                let mut targets = vec![angle];
+                if angle > 0.0 && angle < 180.0 {
+                    targets.push(-angle);
+                }
+                for target in targets {
+                    let exact = crossings(&from_natal, target, distance)?;
+                    if !exact.iter().any(|jd| (jd_from..jd_to).contains(jd)) {
+                        continue;
+                    }
+                    let mut edges = crossings(&from_natal, target - BUILD_UP_ORB, distance)?;
+                    edges.extend(crossings(&from_natal, target + BUILD_UP_ORB, distance)?);
+                    for jd in exact.into_iter().filter(|jd| (jd_from..jd_to).contains(jd)) {
+                        hits.push(TransitHit {
+                            transiting: body,
+                            natal: name.clone(),
+                            aspect,
+                            jd,
+                            orb_entered: edges.iter().copied().filter(|&edge| edge < jd).max_by(f64::total_cmp),
+                            orb_left: edges.iter().copied().filter(|&edge| edge > jd).min_by(f64::total_cmp),
+                        });
+                    }
+                }
+            }
+        }
+    }
+    hits.sort_by(|a, b| a.jd.total_cmp(&b.jd));
+    Ok(hits)
+}
+
+#[cfg(all(test, feature = "swiss"))]
+mod tests {
+    use super::*;
+    use crate::calc::swiss_ephemeris;
+
+    #[test]
+    fn test_retrograde_passes_share_their_build_up() {
+        swiss_ephemeris::init_moshier_ephemeris().unwrap();
+        // Saturn stationed retrograde at 19°25' Pisces on 29 June 2024, so it
+        // crossed 19° Pisces direct and then retrograde without leaving orb
+        let natal = [("Point".to_string(), 349.0)];
+        // 1 January 2024 to 1 January 2025
+        let hits = exact_transits(&natal, &[Planet::Saturn.into()], &[AspectType::Conjunction], 2460310.5, 2460676.5, EphemerisSource::Moshier)
+            .unwrap();
+        assert_eq!(hits.len(), 2, "{:?}", hits);
+        let (direct, retrograde) = (&hits[0], &hits[1]);
+        assert!(direct.jd < 2460490.5 && retrograde.jd > 2460490.5, "{} {}", direct.jd, retrograde.jd);
+        assert_eq!((direct.orb_entered, direct.orb_left), (retrograde.orb_entered, retrograde.orb_left));
+        let (entered, left) = (direct.orb_entered.unwrap(), direct.orb_left.unwrap());
+        assert!(entered < direct.jd && left > retrograde.jd);
+        for edge in [entered, left] {
+            let longitude = planet_longitude_at(Planet::Saturn, edge, EphemerisSource::Moshier).unwrap();
+            assert!((longitude - 348.0).abs() < 1e-4, "{}", longitude);
+        }
+    }
+
+    #[test]
+    fn test_nodes_transit_retrograde() {
+        swiss_ephemeris::init_moshier_ephemeris().unwrap();
+        // The true node went from about 15° Aries to 27° Pisces through 2024,
+        // mostly retrograde with short direct spells
+        let natal = [("Sun".to_string(), 5.0), ("Moon".to_string(), 185.0), ("Mars".to_string(), 90.0)];
+        let bodies = parse_transiting("north_node,south_node").unwrap();
+        assert_eq!(bodies, [TransitingBody::NorthNode, TransitingBody::SouthNode]);
+        let hits = exact_transits(&natal, &bodies, &[AspectType::Conjunction], 2460310.5, 2460676.5, EphemerisSource::Moshier)
+            .unwrap();
+        let found: Vec<(String, &str)> = hits.iter().map(|hit| (hit.transiting.to_string(), hit.natal.as_str())).collect();
+        // Each node crosses its point an odd number of times, ending on the far side
+        for (node, point) in [("North Node", "Sun"), ("South Node", "Moon")] {
+            let passes = found.iter().filter(|(n, p)| n == node && *p == point).count();
+            assert!(passes % 2 == 1, "{:?}", found);
+        }
+        assert!(found.iter().all(|(_, point)| *point != "Mars"), "{:?}", found);
+        for hit in &hits {
+            let at = hit.transiting.longitude_at(hit.jd, EphemerisSource::Moshier).unwrap();
+            let point = natal.iter().find(|(name, _)| *name == hit.natal).unwrap().1;
+            assert!(signed_delta(at, point).abs() < 1e-4, "{:?} {}", hit, at);
+        }
+
+        assert!(parse_transiting("saturn,north_node,saturn").is_err());
+        assert!(parse_transiting("east_node").is_err());
+    }
+}
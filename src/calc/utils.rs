@@ -1,4 +1,3 @@
-use chrono::NaiveDateTime;
 use std::f64::consts::PI;
 
 /// Converts a date to Julian date.
@@ -30,6 +29,68 @@ pub fn date_to_julian(datetime: chrono::DateTime<chrono::Utc>) -> f64 {
     (unix_timestamp / 86400.0) + 2440587.5
 }
 
+/// Converts a Julian date to a UTC date and time, to the nearest second. There's
+/// no date for a Julian date that isn't finite or is beyond chrono's range.
+///
+/// # Examples
+///
+/// ```
+/// use astrolog_rs::calc::utils::julian_to_date;
+///
+/// let datetime = julian_to_date(2451545.0).unwrap();
+/// assert_eq!(datetime.to_rfc3339(), "2000-01-01T12:00:00+00:00");
+/// ```
+pub fn julian_to_date(julian_date: f64) -> Option<chrono::DateTime<chrono::Utc>> {
+    if !julian_date.is_finite() {
+        return None;
+    }
+    let unix_timestamp = ((julian_date - 2440587.5) * 86400.0).round() as i64;
+    chrono::DateTime::from_timestamp(unix_timestamp, 0)
+}
+
+/// Converts a Julian date to a Gregorian calendar date and time.
+///
+/// Uses the algorithm from Meeus, *Astronomical Algorithms*, chapter 7, switching
+/// to the Julian calendar before 15 October 1582.
+///
+/// # Arguments
+///
+/// * `julian_date` - The Julian date
+///
+/// # Returns
+///
+/// A tuple of (year, month, day, hour), where hour is the fractional UT hour
+///
+/// # Examples
+///
+/// ```
+/// use astrolog_rs::calc::utils::julian_to_calendar;
+///
+/// let (year, month, day, hour) = julian_to_calendar(2451545.0);
+/// assert_eq!((year, month, day), (2000, 1, 1));
+/// assert!((hour - 12.0).abs() < 1e-6);
+/// ```
+pub fn julian_to_calendar(julian_date: f64) -> (i32, i32, i32, f64) {
+    let jd = julian_date + 0.5;
+    let z = jd.floor();
+    let f = jd - z;
+    let a = if z < 2299161.0 {
+        z
+    } else {
+        let alpha = ((z - 1867216.25) / 36524.25).floor();
+        z + 1.0 + alpha - (alpha / 4.0).floor()
+    };
+    let b = a + 1524.0;
+    let c = ((b - 122.1) / 365.25).floor();
+    let d = (365.25 * c).floor();
+    let e = ((b - d) / 30.6001).floor();
+
+    let day = b - d - (30.6001 * e).floor();
+    let month = if e < 14.0 { e - 1.0 } else { e - 13.0 };
+    let year = if month > 2.0 { c - 4716.0 } else { c - 4715.0 };
+    (year as i32, month as i32, day as i32, f * 24.0)
+}
+
 /// Calculate Julian centuries since J2000.0
 #[allow(dead_code)]
 pub fn julian_centuries(julian_date: f64) -> f64 {
@@ -47,7 +108,7 @@ pub fn julian_centuries(julian_date: f64) -> f64 {
 ///
 /// # Returns
 ///
-/// The normalized angle in degrees (0 ≤ angle < 360)
+/// The normalized angle in degrees (0 ≤ angle < 360), as `angles::normalize`
 ///
 /// # Examples
 ///
@@ -58,13 +119,8 @@ pub fn julian_centuries(julian_date: f64) -> f64 {
 /// assert_eq!(normalize_angle(-10.0), 350.0);
 /// assert_eq!(normalize_angle(360.0), 0.0);
 /// ```
-#[allow(dead_code)]
 pub fn normalize_angle(angle: f64) -> f64 {
-    let mut normalized = angle % 360.0;
-    if normalized < 0.0 {
-        normalized += 360.0;
-    }
-    normalized
+    crate::calc::angles::normalize(angle)
 }
 
 /// Converts degrees to radians.
@@ -134,6 +190,7 @@ mod tests {
             (-90.0, 270.0),
             (-360.0, 0.0),
             (-720.0, 0.0),
+            (-1e-15, 0.0),
         ];
 
         for (input, expected) in test_cases.iter() {
@@ -148,6 +205,15 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_julian_to_date_needs_a_finite_date() {
+        // NaN used to cast to a timestamp of 0 and come back as 1970
+        assert_eq!(julian_to_date(f64::NAN), None);
+        assert_eq!(julian_to_date(f64::INFINITY), None);
+        assert_eq!(julian_to_date(1e300), None);
+        assert!(julian_to_date(2440587.5).is_some());
+    }
+
     #[test]
     fn test_degrees_to_radians() {
         let test_cases = [
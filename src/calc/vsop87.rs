@@ -1,3 +1,5 @@
+use crate::calc::angles::normalize;
+use crate::calc::utils::{degrees_to_radians, radians_to_degrees};
 use std::f64::consts::PI;
 
 /// Planet identification for VSOP87 calculations
@@ -80,16 +82,7 @@ pub fn julian_centuries(julian_date: f64) -> f64 {
 pub fn mean_anomaly(t: f64, a: f64, b: f64, c: f64) -> f64 {
     // Calculate mean anomaly using the VSOP87 formula
     // Input angles are in degrees, convert to radians at the end
-    let mut m = a + b * t + c * t * t;
-
-    // Normalize to [0, 360]
-    m = m % 360.0;
-    if m < 0.0 {
-        m += 360.0;
-    }
-
-    // Convert to radians
-    m * PI / 180.0
+    degrees_to_radians(normalize(a + b * t + c * t * t))
 }
 
 /// Calculate the eccentricity of a planet's orbit
@@ -108,7 +101,7 @@ pub fn inclination(t: f64, a: f64, b: f64, c: f64) -> f64 {
 #[allow(dead_code)]
 pub fn ascending_node(t: f64, a: f64, b: f64, c: f64) -> f64 {
     let mut node = a + b * t + c * t * t;
-    node = node % (2.0 * PI);
+    node %= 2.0 * PI;
     if node < 0.0 {
         node += 2.0 * PI;
     }
@@ -119,7 +112,7 @@ pub fn ascending_node(t: f64, a: f64, b: f64, c: f64) -> f64 {
 #[allow(dead_code)]
 pub fn perihelion(t: f64, a: f64, b: f64, c: f64) -> f64 {
     let mut peri = a + b * t + c * t * t;
-    peri = peri % (2.0 * PI);
+    peri %= 2.0 * PI;
     if peri < 0.0 {
         peri += 2.0 * PI;
     }
@@ -146,6 +139,27 @@ fn calculate_true_anomaly(mean_anomaly: f64, eccentricity: f64) -> f64 {
         .atan2((1.0 - eccentricity).sqrt() * (eccentric_anomaly / 2.0).cos())
 }
 
+/// Geocentric distances below this many AU count as the Earth itself
+const MIN_GEOCENTRIC_DISTANCE: f64 = 1e-9;
+
+/// Rectangular ecliptic coordinates from longitude and latitude in radians
+fn to_rectangular(longitude: f64, latitude: f64, radius: f64) -> [f64; 3] {
+    [
+        radius * latitude.cos() * longitude.cos(),
+        radius * latitude.cos() * longitude.sin(),
+        radius * latitude.sin(),
+    ]
+}
+
+/// Longitude in [0, 2π) and latitude in [-π/2, π/2], in radians, of a
+/// rectangular ecliptic vector, with its length
+fn to_spherical([x, y, z]: [f64; 3]) -> (f64, f64, f64) {
+    let horizontal = x.hypot(y);
+    let longitude = y.atan2(x).rem_euclid(2.0 * PI);
+    let latitude = z.atan2(horizontal).clamp(-PI / 2.0, PI / 2.0);
+    (longitude, latitude, horizontal.hypot(z))
+}
+
 /// Calculate the heliocentric coordinates of a planet
 /// Returns (longitude, latitude, radius) in degrees and AU
 pub fn heliocentric_coordinates(
@@ -157,48 +171,33 @@ pub fn heliocentric_coordinates(
     lp: f64,
     node: f64,
 ) -> (f64, f64, f64) {
-    // Convert angles to radians
-    let i_rad = i * PI / 180.0;
-    let node_rad = node * PI / 180.0;
-    let lp_rad = lp * PI / 180.0;
-    let _l_rad = l * PI / 180.0;
-
-    // Mean anomaly M = L - lp (in degrees, then radians)
-    let mut m_deg = l - lp;
-    m_deg = m_deg % 360.0;
-    if m_deg < 0.0 {
-        m_deg += 360.0;
-    }
-    let m = m_deg * PI / 180.0;
+    let i = degrees_to_radians(i);
+    let node = degrees_to_radians(node);
+    let lp = degrees_to_radians(lp);
+    // Mean anomaly M = L - lp
+    let m = (degrees_to_radians(l) - lp).rem_euclid(2.0 * PI);
 
-    // Calculate true anomaly
     let v = calculate_true_anomaly(m, e);
-
-    // Calculate radius vector
-    let _r = a * (1.0 - e * e) / (1.0 + e * v.cos());
+    let r = a * (1.0 - e * e) / (1.0 + e * v.cos());
 
     // Argument of latitude: u = v + (lp - node)
-    let u = v + (lp_rad - node_rad);
-
-    // Heliocentric ecliptic coordinates
-    let x = _r * (node_rad.cos() * u.cos() - node_rad.sin() * u.sin() * i_rad.cos());
-    let y = _r * (node_rad.sin() * u.cos() + node_rad.cos() * u.sin() * i_rad.cos());
-    let z = _r * u.sin() * i_rad.sin();
-
-    // Ecliptic longitude and latitude
-    let mut longitude = y.atan2(x) * 180.0 / PI;
-    let latitude = z.atan2((x * x + y * y).sqrt()) * 180.0 / PI;
-
-    // Normalize longitude to [0, 360)
-    longitude = longitude % 360.0;
-    if longitude < 0.0 {
-        longitude += 360.0;
-    }
-
-    (longitude, latitude, _r)
+    let u = v + (lp - node);
+    let position = [
+        r * (node.cos() * u.cos() - node.sin() * u.sin() * i.cos()),
+        r * (node.sin() * u.cos() + node.cos() * u.sin() * i.cos()),
+        r * u.sin() * i.sin(),
+    ];
+
+    let (longitude, latitude, _) = to_spherical(position);
+    (normalize(radians_to_degrees(longitude)), radians_to_degrees(latitude), r)
 }
 
 /// Convert heliocentric coordinates to geocentric coordinates
+///
+/// Angles are in degrees and distances in AU. The longitude comes back in
+/// [0, 360) and the latitude in [-90, 90]. When the planet sits at the Earth's
+/// position, so there is no direction to it, its heliocentric direction is
+/// returned instead.
 pub fn heliocentric_to_geocentric(
     planet_long: f64,
     planet_lat: f64,
@@ -207,38 +206,24 @@ pub fn heliocentric_to_geocentric(
     earth_lat: f64,
     earth_r: f64,
 ) -> (f64, f64) {
-    // Convert angles to radians
-    let planet_long_rad = planet_long * PI / 180.0;
-    let planet_lat_rad = planet_lat * PI / 180.0;
-    let earth_long_rad = earth_long * PI / 180.0;
-    let earth_lat_rad = earth_lat * PI / 180.0;
-
-    // Convert to rectangular coordinates
-    let x_planet = planet_r * planet_lat_rad.cos() * planet_long_rad.cos();
-    let y_planet = planet_r * planet_lat_rad.cos() * planet_long_rad.sin();
-    let z_planet = planet_r * planet_lat_rad.sin();
-
-    let x_earth = earth_r * earth_lat_rad.cos() * earth_long_rad.cos();
-    let y_earth = earth_r * earth_lat_rad.cos() * earth_long_rad.sin();
-    let z_earth = earth_r * earth_lat_rad.sin();
-
-    // Calculate geocentric coordinates
-    let x = x_planet - x_earth;
-    let y = y_planet - y_earth;
-    let z = z_planet - z_earth;
-
-    // Convert back to spherical coordinates
-    let _r = (x * x + y * y + z * z).sqrt();
-    let longitude = y.atan2(x) * 180.0 / PI;
-    let latitude = z.atan2((x * x + y * y).sqrt()) * 180.0 / PI;
-
-    // Normalize longitude to [0, 360)
-    let mut longitude = longitude % 360.0;
-    if longitude < 0.0 {
-        longitude += 360.0;
+    debug_assert!(
+        [planet_long, planet_lat, planet_r, earth_long, earth_lat, earth_r]
+            .iter()
+            .all(|v| v.is_finite()),
+        "heliocentric_to_geocentric got a non-finite coordinate"
+    );
+    let planet_long = degrees_to_radians(planet_long);
+    let planet_lat = degrees_to_radians(planet_lat);
+    let planet = to_rectangular(planet_long, planet_lat, planet_r);
+    let earth = to_rectangular(degrees_to_radians(earth_long), degrees_to_radians(earth_lat), earth_r);
+
+    let (mut longitude, mut latitude, distance) =
+        to_spherical([planet[0] - earth[0], planet[1] - earth[1], planet[2] - earth[2]]);
+    if distance < MIN_GEOCENTRIC_DISTANCE {
+        (longitude, latitude, _) = to_spherical(to_rectangular(planet_long, planet_lat, 1.0));
     }
 
-    (longitude, latitude)
+    (normalize(radians_to_degrees(longitude)), radians_to_degrees(latitude))
 }
 
 /// Calculates the position of a planet using the VSOP87 theory.
@@ -347,6 +332,7 @@ pub fn calculate_moon_position(_julian_date: f64) -> Result<(f64, f64, f64), Str
 mod tests {
     use super::*;
     use approx::assert_relative_eq;
+    use proptest::prelude::*;
 
     #[test]
     fn test_julian_centuries() {
@@ -394,4 +380,56 @@ mod tests {
         assert_relative_eq!(y, 0.0, epsilon = 1e-10);
         assert_relative_eq!(z, 1.0, epsilon = 1e-10); // At 90 degrees inclination, z should be 1.0
     }
+
+    #[test]
+    fn test_planet_at_the_earth_keeps_its_heliocentric_direction() {
+        let (longitude, latitude) = heliocentric_to_geocentric(120.0, 1.5, 1.0, 120.0, 1.5, 1.0);
+        assert_relative_eq!(longitude, 120.0, epsilon = 1e-9);
+        assert_relative_eq!(latitude, 1.5, epsilon = 1e-9);
+    }
+
+    proptest! {
+        #[test]
+        fn heliocentric_latitude_within_inclination(
+            a in 0.3..40.0f64,
+            e in 0.0..0.3f64,
+            i in 0.0..30.0f64,
+            l in 0.0..360.0f64,
+            lp in 0.0..360.0f64,
+            node in 0.0..360.0f64,
+        ) {
+            let (longitude, latitude, _) = heliocentric_coordinates(0.0, a, e, i, l, lp, node);
+            prop_assert!((0.0..360.0).contains(&longitude), "longitude {}", longitude);
+            prop_assert!(latitude.abs() <= i + 1e-9, "latitude {} for inclination {}", latitude, i);
+        }
+
+        #[test]
+        fn geocentric_latitude_within_apparent_inclination(
+            a in 0.3..40.0f64,
+            e in 0.0..0.3f64,
+            i in 0.0..30.0f64,
+            l in 0.0..360.0f64,
+            lp in 0.0..360.0f64,
+            node in 0.0..360.0f64,
+            earth_l in 0.0..360.0f64,
+            earth_lp in 0.0..360.0f64,
+        ) {
+            let (planet_long, planet_lat, planet_r) = heliocentric_coordinates(0.0, a, e, i, l, lp, node);
+            let (earth_long, earth_lat, earth_r) =
+                heliocentric_coordinates(0.0, 1.0, 0.0167, 0.0, earth_l, earth_lp, 0.0);
+            let (longitude, latitude) =
+                heliocentric_to_geocentric(planet_long, planet_lat, planet_r, earth_long, earth_lat, earth_r);
+            prop_assert!((0.0..360.0).contains(&longitude), "longitude {}", longitude);
+            prop_assert!((-90.0..=90.0).contains(&latitude), "latitude {}", latitude);
+
+            // Seen from the ecliptic plane the latitude is at most the
+            // inclination, widened by how much closer the Earth is than the Sun
+            let i_rad = degrees_to_radians(i);
+            let nearest = planet_r * i_rad.cos() - earth_r;
+            if nearest > 0.0 {
+                let limit = radians_to_degrees((planet_r * i_rad.sin()).atan2(nearest));
+                prop_assert!(latitude.abs() <= limit + 1e-9, "latitude {} beyond {}", latitude, limit);
+            }
+        }
+    }
 }
@@ -0,0 +1,348 @@
+//! The pieces of a daily "astrological weather" report: Moon phase, void of
+//! course Moon, sign ingresses and aspects perfecting between the planets.
+//!
+//! None of these depend on a birth chart, only on the moment.
+
+use crate::calc::angles::normalize;
+use crate::calc::aspects::{crossings, next_exact_with_source, sample_motion, AspectType};
+use crate::calc::cycles::speed_at;
+use crate::calc::planets::{max_daily_motion, planet_longitude_at, Planet};
+use crate::calc::rulers::Sign;
+use crate::core::types::EphemerisSource;
+use serde::{Deserialize, Serialize};
+
+/// Bodies the report covers, in the order it lists them
+pub const WEATHER_BODIES: [Planet; 10] = [
+    Planet::Sun,
+    Planet::Moon,
+    Planet::Mercury,
+    Planet::Venus,
+    Planet::Mars,
+    Planet::Jupiter,
+    Planet::Saturn,
+    Planet::Uranus,
+    Planet::Neptune,
+    Planet::Pluto,
+];
+
+/// The Ptolemaic aspects, the only ones that end a void of course Moon
+pub const PTOLEMAIC_ASPECTS: [AspectType; 5] = [
+    AspectType::Conjunction,
+    AspectType::Sextile,
+    AspectType::Square,
+    AspectType::Trine,
+    AspectType::Opposition,
+];
+
+/// The Moon crosses a sign in at most about two and a half days
+const MOON_SIGN_DAYS: f64 = 3.0;
+
+/// The eight phases of the lunation, each 45° of the Moon's elongation from the Sun
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MoonPhase {
+    New,
+    Crescent,
+    FirstQuarter,
+    Gibbous,
+    Full,
+    Disseminating,
+    LastQuarter,
+    Balsamic,
+}
+
+impl MoonPhase {
+    /// The phase for the Moon's longitude minus the Sun's, in degrees
+    pub fn from_elongation(elongation: f64) -> MoonPhase {
+        const PHASES: [MoonPhase; 8] = [
+            MoonPhase::New,
+            MoonPhase::Crescent,
+            MoonPhase::FirstQuarter,
+            MoonPhase::Gibbous,
+            MoonPhase::Full,
+            MoonPhase::Disseminating,
+            MoonPhase::LastQuarter,
+            MoonPhase::Balsamic,
+        ];
+        PHASES[((normalize(elongation) / 45.0) as usize).min(7)]
+    }
+}
+
+/// Fraction of the Moon's disc lit by the Sun (0-1), from its elongation
+pub fn illumination(elongation: f64) -> f64 {
+    (1.0 - elongation.to_radians().cos()) / 2.0
+}
+
+/// A body crossing into a new sign
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Ingress {
+    pub planet: Planet,
+    /// Julian date (UT) of the crossing
+    pub jd: f64,
+    /// The sign the body enters
+    pub sign: Sign,
+    /// Whether it backs into the sign while retrograde
+    pub retrograde: bool,
+}
+
+/// Finds the times a body changes sign between two Julian dates, earliest first
+pub fn ingresses(planet: Planet, jd_from: f64, jd_to: f64, source: EphemerisSource) -> Result<Vec<Ingress>, String> {
+    let longitude = |jd: f64| planet_longitude_at(planet, jd, source);
+    let samples = sample_motion(jd_from, jd_to - jd_from, max_daily_motion(planet), longitude)?;
+
+    let mut found = Vec::new();
+    for boundary in (0..12).map(|i| i as f64 * 30.0) {
+        for jd in crossings(&samples, boundary, longitude)? {
+            let retrograde = speed_at(planet, jd, source)? < 0.0;
+            found.push(Ingress {
+                planet,
+                jd,
+                sign: Sign::from_longitude(if retrograde { boundary - 15.0 } else { boundary + 15.0 }),
+                retrograde,
+            });
+        }
+    }
+    found.sort_by(|a, b| a.jd.total_cmp(&b.jd));
+    Ok(found)
+}
+
+/// An aspect between two bodies becoming exact
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ExactAspect {
+    pub planet1: Planet,
+    pub planet2: Planet,
+    pub aspect_type: AspectType,
+    /// Julian date (UT) the aspect is exact
+    pub jd: f64,
+}
+
+/// Finds the Ptolemaic aspects between the weather bodies that are exact
+/// between two Julian dates, earliest first
+pub fn exact_aspects_between(jd_from: f64, jd_to: f64, source: EphemerisSource) -> Result<Vec<ExactAspect>, String> {
+    let mut found = Vec::new();
+    for (i, &planet1) in WEATHER_BODIES.iter().enumerate() {
+        for &planet2 in &WEATHER_BODIES[i + 1..] {
+            for aspect_type in PTOLEMAIC_ASPECTS {
+                for jd in next_exact_with_source(jd_from, planet1, planet2, aspect_type, jd_to - jd_from, source)? {
+                    found.push(ExactAspect {
+                        planet1,
+                        planet2,
+                        aspect_type,
+                        jd,
+                    });
+                }
+            }
+        }
+    }
+    found.sort_by(|a, b| a.jd.total_cmp(&b.jd));
+    Ok(found)
+}
+
+/// A stretch with the Moon void of course
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct VoidOfCourse {
+    /// Julian date (UT) of the Moon's last Ptolemaic aspect in its sign, or of
+    /// its ingress when it made none
+    pub since: f64,
+    /// Julian date (UT) the Moon enters the next sign
+    pub until: f64,
+}
+
+/// Whether the Moon is void of course at a Julian date: past its last Ptolemaic
+/// aspect to the Sun or a planet before it leaves its sign
+pub fn void_of_course(jd: f64, source: EphemerisSource) -> Result<Option<VoidOfCourse>, String> {
+    let moon_aspects = |from: f64, to: f64| moon_aspects(from, to, source);
+
+    let Some(until) = ingresses(Planet::Moon, jd, jd + MOON_SIGN_DAYS, source)?.first().map(|i| i.jd) else {
+        return Err("The Moon didn't change sign within three days".to_string());
+    };
+    if !moon_aspects(jd, until)?.is_empty() {
+        return Ok(None);
+    }
+    let entered = ingresses(Planet::Moon, jd - MOON_SIGN_DAYS, jd, source)?
+        .last()
+        .map(|i| i.jd)
+        .unwrap_or(jd - MOON_SIGN_DAYS);
+    let since = moon_aspects(entered, jd)?.into_iter().fold(entered, f64::max);
+    Ok(Some(VoidOfCourse { since, until }))
+}
+
+/// Every stretch with the Moon void of course that overlaps the range between
+/// two Julian dates, earliest first
+pub fn void_periods(jd_from: f64, jd_to: f64, source: EphemerisSource) -> Result<Vec<VoidOfCourse>, String> {
+    // The Moon spends under three days in a sign, so the ingresses either side
+    // of the range bound every stretch that overlaps it
+    let moon_ingresses = ingresses(Planet::Moon, jd_from - MOON_SIGN_DAYS, jd_to + MOON_SIGN_DAYS, source)?;
+    let mut found = Vec::new();
+    for pair in moon_ingresses.windows(2) {
+        let (entered, until) = (pair[0].jd, pair[1].jd);
+        if until <= jd_from || entered > jd_to {
+            continue;
+        }
+        let since = moon_aspects(entered, until, source)?.into_iter().fold(entered, f64::max);
+        found.push(VoidOfCourse { since, until });
+    }
+    Ok(found.into_iter().filter(|void| void.since <= jd_to).collect())
+}
+
+/// Times the Moon makes a Ptolemaic aspect to the Sun or a planet between two
+/// Julian dates, unordered
+fn moon_aspects(jd_from: f64, jd_to: f64, source: EphemerisSource) -> Result<Vec<f64>, String> {
+    let mut found = Vec::new();
+    for &planet in WEATHER_BODIES.iter().filter(|&&p| p != Planet::Moon) {
+        for aspect_type in PTOLEMAIC_ASPECTS {
+            found.extend(next_exact_with_source(jd_from, Planet::Moon, planet, aspect_type, jd_to - jd_from, source)?);
+        }
+    }
+    Ok(found)
+}
+
+/// The Moon's distance from the Earth's centre in kilometres at a Julian date,
+/// from the largest terms of the ELP-2000/82 series (Meeus, chapter 47). Good to
+/// about 50 km, whichever ephemeris the positions come from.
+pub fn moon_distance(jd: f64) -> f64 {
+    // Multiples of D, M, M' and F, and the term in metres
+    const TERMS: [(f64, f64, f64, f64, f64); 25] = [
+        (0.0, 0.0, 1.0, 0.0, -20905355.0),
+        (2.0, 0.0, -1.0, 0.0, -3699111.0),
+        (2.0, 0.0, 0.0, 0.0, -2955968.0),
+        (0.0, 0.0, 2.0, 0.0, -569925.0),
+        (0.0, 1.0, 0.0, 0.0, 48888.0),
+        (0.0, 0.0, 0.0, 2.0, -3149.0),
+        (2.0, 0.0, -2.0, 0.0, 246158.0),
+        (2.0, -1.0, -1.0, 0.0, -152138.0),
+        (2.0, 0.0, 1.0, 0.0, -170733.0),
+        (2.0, -1.0, 0.0, 0.0, -204586.0),
+        (0.0, 1.0, -1.0, 0.0, -129620.0),
+        (1.0, 0.0, 0.0, 0.0, 108743.0),
+        (0.0, 1.0, 1.0, 0.0, 104755.0),
+        (2.0, 0.0, 0.0, -2.0, 10321.0),
+        (0.0, 0.0, 1.0, -2.0, 79661.0),
+        (4.0, 0.0, -1.0, 0.0, -34782.0),
+        (0.0, 0.0, 3.0, 0.0, -23210.0),
+        (4.0, 0.0, -2.0, 0.0, -21636.0),
+        (2.0, 1.0, -1.0, 0.0, 24208.0),
+        (2.0, 1.0, 0.0, 0.0, 30824.0),
+        (1.0, 0.0, -1.0, 0.0, -8379.0),
+        (1.0, 1.0, 0.0, 0.0, -16675.0),
+        (2.0, -1.0, 1.0, 0.0, -12831.0),
+        (2.0, 0.0, 2.0, 0.0, -10445.0),
+        (4.0, 0.0, 0.0, 0.0, -11650.0),
+    ];
+    let t = crate::calc::utils::julian_centuries(jd);
+    // Mean elongation, the Sun's and the Moon's mean anomalies, and the Moon's
+    // argument of latitude
+    let d = 297.8501921 + 445267.1114034 * t;
+    let m = 357.5291092 + 35999.0502909 * t;
+    let m_moon = 134.9633964 + 477198.8675055 * t;
+    let f = 93.2720950 + 483202.0175233 * t;
+    // Terms with the Sun's anomaly shrink with the eccentricity of the Earth's orbit
+    let e = 1.0 - 0.002516 * t - 0.0000074 * t * t;
+    let sum: f64 = TERMS
+        .iter()
+        .map(|&(nd, nm, nm_moon, nf, metres)| {
+            let argument = (nd * d + nm * m + nm_moon * m_moon + nf * f).to_radians();
+            metres * e.powi(nm.abs() as i32) * argument.cos()
+        })
+        .sum();
+    385000.56 + sum / 1000.0
+}
+
+/// The Moon's elongation from the Sun (0-360°) at a Julian date
+pub fn moon_elongation(jd: f64, source: EphemerisSource) -> Result<f64, String> {
+    let moon = planet_longitude_at(Planet::Moon, jd, source)?;
+    let sun = planet_longitude_at(Planet::Sun, jd, source)?;
+    Ok(normalize(moon - sun))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(feature = "swiss")]
+    use crate::calc::swiss_ephemeris;
+    #[cfg(feature = "swiss")]
+    use crate::calc::utils::julian_to_calendar;
+
+    #[test]
+    fn test_moon_phases() {
+        assert_eq!(MoonPhase::from_elongation(10.0), MoonPhase::New);
+        assert_eq!(MoonPhase::from_elongation(90.0), MoonPhase::FirstQuarter);
+        assert_eq!(MoonPhase::from_elongation(190.0), MoonPhase::Full);
+        assert_eq!(MoonPhase::from_elongation(359.9), MoonPhase::Balsamic);
+        assert!(illumination(0.0).abs() < 1e-12);
+        assert!((illumination(180.0) - 1.0).abs() < 1e-12);
+        assert!((illumination(90.0) - 0.5).abs() < 1e-12);
+    }
+
+    #[cfg(feature = "swiss")]
+    #[test]
+    fn test_sun_enters_aries_at_the_equinox() {
+        swiss_ephemeris::init_moshier_ephemeris().unwrap();
+        // 20 March 2023, 21:24 UT
+        let found = ingresses(Planet::Sun, 2460023.5, 2460025.5, EphemerisSource::Auto).unwrap();
+        assert_eq!(found.len(), 1);
+        assert_eq!((found[0].sign, found[0].retrograde), (Sign::Aries, false));
+        let (year, month, day, hour) = julian_to_calendar(found[0].jd);
+        assert_eq!((year, month, day), (2023, 3, 20));
+        assert!((hour - 21.4).abs() < 0.05, "equinox at {}h", hour);
+    }
+
+    #[cfg(feature = "swiss")]
+    #[test]
+    fn test_retrograde_mercury_backs_into_sagittarius() {
+        swiss_ephemeris::init_moshier_ephemeris().unwrap();
+        // 20 to 26 December 2023
+        let found = ingresses(Planet::Mercury, 2460298.5, 2460304.5, EphemerisSource::Auto).unwrap();
+        assert_eq!(found.len(), 1);
+        assert_eq!((found[0].sign, found[0].retrograde), (Sign::Sagittarius, true));
+        let (year, month, day, _) = julian_to_calendar(found[0].jd);
+        assert_eq!((year, month, day), (2023, 12, 23));
+    }
+
+    #[test]
+    fn test_moon_distance() {
+        // Meeus, example 47.a: 1992 April 12, 0h TD, 368409.7 km
+        assert!((moon_distance(2448724.5) - 368409.7).abs() < 50.0);
+        // Always between perigee and apogee
+        for day in 0..60 {
+            let distance = moon_distance(2460000.5 + day as f64);
+            assert!((356_000.0..407_000.0).contains(&distance), "{}", distance);
+        }
+    }
+
+    #[cfg(feature = "swiss")]
+    #[test]
+    fn test_void_periods_match_the_void_at_each_moment() {
+        swiss_ephemeris::init_moshier_ephemeris().unwrap();
+        let (from, to) = (2460298.5, 2460305.5);
+        let periods = void_periods(from, to, EphemerisSource::Auto).unwrap();
+        assert!(periods.len() >= 2);
+        assert!(periods.windows(2).all(|pair| pair[0].until <= pair[1].since));
+        for hour in 0..(24 * 7) {
+            let jd = from + hour as f64 / 24.0;
+            let within = periods.iter().find(|void| void.since <= jd && jd < void.until);
+            let void = void_of_course(jd, EphemerisSource::Auto).unwrap();
+            assert_eq!(within.is_some(), void.is_some(), "hour {}", hour);
+            if let (Some(within), Some(void)) = (within, void) {
+                assert!((within.since - void.since).abs() < 1e-6 && (within.until - void.until).abs() < 1e-6);
+            }
+        }
+    }
+
+    #[cfg(feature = "swiss")]
+    #[test]
+    fn test_void_of_course_ends_at_the_ingress() {
+        swiss_ephemeris::init_moshier_ephemeris().unwrap();
+        // Over three days the Moon changes sign and spends part of the time void
+        let start = 2460298.5;
+        let ingress = ingresses(Planet::Moon, start, start + MOON_SIGN_DAYS, EphemerisSource::Auto).unwrap()[0].jd;
+        let void = void_of_course(ingress - 1e-3, EphemerisSource::Auto).unwrap().unwrap();
+        assert!((void.until - ingress).abs() < 1e-4);
+        assert!(void.since < ingress - 1e-3);
+        // Nothing perfects between the start of the void and the ingress
+        let aspects = exact_aspects_between(void.since + 1e-4, void.until, EphemerisSource::Auto).unwrap();
+        assert!(aspects.iter().all(|a| a.planet1 != Planet::Moon && a.planet2 != Planet::Moon));
+        // Just after entering the new sign the Moon has aspects still to make
+        assert!(void_of_course(ingress + 1e-3, EphemerisSource::Auto).unwrap().is_none());
+    }
+}
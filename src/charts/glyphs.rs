@@ -0,0 +1,111 @@
+use std::fmt;
+use std::str::FromStr;
+
+/// Glyphs are drawn on a 20x20 box centred on the origin
+pub const GLYPH_BOX: f64 = 20.0;
+
+/// How astrological symbols are rendered in the SVG
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum GlyphMode {
+    /// Unicode characters; smallest output but needs a font with the glyphs
+    #[default]
+    Text,
+    /// Vector `<path>` outlines that render the same everywhere
+    Paths,
+}
+
+impl fmt::Display for GlyphMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GlyphMode::Text => write!(f, "text"),
+            GlyphMode::Paths => write!(f, "paths"),
+        }
+    }
+}
+
+impl FromStr for GlyphMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "text" => Ok(GlyphMode::Text),
+            "paths" => Ok(GlyphMode::Paths),
+            _ => Err(format!("Invalid glyph mode: {} (expected \"text\" or \"paths\")", s)),
+        }
+    }
+}
+
+/// Stroke outline for a planet symbol, by English planet name
+pub fn planet_path(planet_name: &str) -> Option<&'static str> {
+    let d = match planet_name {
+        "Sun" => "M 8 0 A 8 8 0 1 0 -8 0 A 8 8 0 1 0 8 0 Z M 1.5 0 A 1.5 1.5 0 1 0 -1.5 0 A 1.5 1.5 0 1 0 1.5 0 Z",
+        "Moon" => "M 3 -8 A 8 8 0 1 0 3 8 A 6 8 0 1 1 3 -8 Z",
+        "Mercury" => "M -4 -9 A 4 4 0 0 0 4 -9 M 4 -3 A 4 4 0 1 0 -4 -3 A 4 4 0 1 0 4 -3 M 0 1 L 0 9 M -3 5 L 3 5",
+        "Venus" => "M 5 -3 A 5 5 0 1 0 -5 -3 A 5 5 0 1 0 5 -3 M 0 2 L 0 10 M -4 6 L 4 6",
+        "Mars" => "M 3 2 A 5 5 0 1 0 -7 2 A 5 5 0 1 0 3 2 M 1.5 -1.5 L 8 -8 M 3 -8 L 8 -8 L 8 -3",
+        "Jupiter" => "M -6 -6 C -2 -10 3 -6 -6 3 L 7 3 M 3 -9 L 3 9",
+        "Saturn" => "M -4 -9 L -4 5 M -7 -6 L -1 -6 M -4 -1 C 0 -5 6 -3 2 3 C 0 6 3 9 5 8",
+        "Uranus" => "M -6 -9 L -6 1 M 6 -9 L 6 1 M -6 -4 L 6 -4 M 0 -9 L 0 3.5 M 2.5 6 A 2.5 2.5 0 1 0 -2.5 6 A 2.5 2.5 0 1 0 2.5 6",
+        "Neptune" => "M -7 -8 C -7 0 7 0 7 -8 M 0 -9 L 0 9 M -4 5 L 4 5",
+        "Pluto" => "M 6 -5 A 6 6 0 0 1 -6 -5 M 3 -6 A 3 3 0 1 0 -3 -6 A 3 3 0 1 0 3 -6 M 0 1 L 0 9 M -4 5 L 4 5",
+        "MeanNode" | "TrueNode" => "M -5 4 A 5 6 0 1 1 5 4 M -3 6 A 2 2 0 1 0 -7 6 A 2 2 0 1 0 -3 6 M 7 6 A 2 2 0 1 0 3 6 A 2 2 0 1 0 7 6",
+        "MeanLilith" | "TrueLilith" => "M 3 -9 A 6 6 0 1 0 3 3 A 4.5 6 0 1 1 3 -9 Z M -1 3 L -1 9 M -4 6.5 L 2 6.5",
+        "Chiron" => "M -1 -9 L -1 3 M -1 -3 L 5 -9 M -1 -3 L 5 2 M 2 6 A 3 3 0 1 0 -4 6 A 3 3 0 1 0 2 6",
+        "Ceres" => "M 5 -7 C 1 -11 -6 -7 -4 -3 C -3 -1 -1 0 0 0 L 0 9 M -4 5 L 4 5",
+        "Pallas" => "M 0 -9 L 4 -5 L 0 -1 L -4 -5 Z M 0 -1 L 0 9 M -4 5 L 4 5",
+        "Juno" => "M 0 -9 L 0 9 M -4 -7 L 4 -3 M 4 -7 L -4 -3 M -4 5 L 4 5",
+        "Vesta" => "M -6 -6 L 0 6 L 6 -6 M -7 9 L 7 9",
+        _ => return None,
+    };
+    Some(d)
+}
+
+/// Stroke outline for a zodiac sign symbol, by sign index from 0 (Aries)
+pub fn sign_path(sign_index: usize) -> &'static str {
+    const SIGNS: [&str; 12] = [
+        // Aries
+        "M -7 -3 C -8 -9 -1 -9 0 -3 L 0 8 M 7 -3 C 8 -9 1 -9 0 -3",
+        // Taurus
+        "M 5 3 A 5 5 0 1 0 -5 3 A 5 5 0 1 0 5 3 M -8 -8 C -6 -2 6 -2 8 -8",
+        // Gemini
+        "M -7 -8 C -2 -6 2 -6 7 -8 M -7 8 C -2 6 2 6 7 8 M -3 -6.5 L -3 6.5 M 3 -6.5 L 3 6.5",
+        // Cancer
+        "M 8 -3 C 4 -8 -6 -8 -8 -3 M -8 3 C -4 8 6 8 8 3 M -2 -3 A 2.5 2.5 0 1 0 -7 -3 A 2.5 2.5 0 1 0 -2 -3 M 7 3 A 2.5 2.5 0 1 0 2 3 A 2.5 2.5 0 1 0 7 3",
+        // Leo
+        "M -1.5 4 A 2.5 2.5 0 1 0 -6.5 4 A 2.5 2.5 0 1 0 -1.5 4 C -1 -2 -6 -5 -3 -8 C 0 -10 6 -8 3 -1 C 1 4 3 8 7 6",
+        // Virgo
+        "M -8 -6 L -8 6 M -8 -4 C -8 -7 -3 -7 -3 -4 L -3 6 M -3 -4 C -3 -7 2 -7 2 -4 L 2 4 C 2 8 7 6 8 2 M 2 0 C 4 -2 8 0 6 5",
+        // Libra
+        "M -8 6 L 8 6 M -8 2 L -3 2 A 4 4 0 1 1 3 2 L 8 2",
+        // Scorpio
+        "M -8 -4 C -8 -7 -4 -7 -4 -4 L -4 6 M -4 -4 C -4 -7 0 -7 0 -4 L 0 6 M 0 -4 C 0 -7 4 -7 4 -4 L 4 5 C 4 7 5 7 8 5 M 6 3 L 8 5 L 6 7",
+        // Sagittarius
+        "M -7 7 L 7 -7 M 1 -7 L 7 -7 L 7 -1 M -5 -1 L 1 5",
+        // Capricorn
+        "M -8 -6 C -6 -8 -4 -6 -4 -3 L -4 6 M -4 -3 C -2 -8 2 -6 2 0 C 2 4 1 6 -1 8 M 2 2 A 3 3 0 1 0 8 2 A 3 3 0 1 0 2 2",
+        // Aquarius
+        "M -8 -2 L -4 -5 L 0 -2 L 4 -5 L 8 -2 M -8 4 L -4 1 L 0 4 L 4 1 L 8 4",
+        // Pisces
+        "M -6 -8 C -1 -4 -1 4 -6 8 M 6 -8 C 1 -4 1 4 6 8 M -8 0 L 8 0",
+    ];
+    SIGNS[sign_index % 12]
+}
+
+/// Stroke outline for an aspect symbol, by aspect name as used in `AspectInfo`
+pub fn aspect_path(aspect: &str) -> Option<&'static str> {
+    let d = match aspect {
+        "Conjunction" => "M 4 1 A 4 4 0 1 0 -4 1 A 4 4 0 1 0 4 1 M 3 -2 L 8 -7",
+        "Opposition" => "M -2 -5 A 3 3 0 1 0 -8 -5 A 3 3 0 1 0 -2 -5 M 8 5 A 3 3 0 1 0 2 5 A 3 3 0 1 0 8 5 M -3 -3 L 3 3",
+        "Trine" => "M 0 -7 L 7 6 L -7 6 Z",
+        "Square" => "M -6 -6 L 6 -6 L 6 6 L -6 6 Z",
+        "Sextile" => "M 0 -8 L 0 8 M -7 -4 L 7 4 M -7 4 L 7 -4",
+        "Semisextile" => "M -6 6 L 0 0 L 6 6 M 0 0 L 0 -8",
+        "Semisquare" => "M 7 -7 L -7 6 L 7 6",
+        "Sesquisquare" => "M -4 -8 L 4 -8 L 4 0 L -4 0 Z M -7 8 L 7 8 M -7 8 L 3 2",
+        "Quintile" => "M 5 -1 A 5 5 0 1 0 -5 -1 A 5 5 0 1 0 5 -1 M 2 3 L 7 8",
+        "Biquintile" => "M -8 -8 L -8 7 M -3 3 A 2.5 2.5 0 1 0 -8 3 A 2.5 2.5 0 1 0 -3 3 M 7 0 A 4 4 0 1 0 -1 0 A 4 4 0 1 0 7 0 M 5 3 L 8 7",
+        "Quincunx" => "M -6 -6 L 0 0 L 6 -6 M 0 0 L 0 8",
+        _ => return None,
+    };
+    Some(d)
+}
@@ -1,44 +1,74 @@
+pub mod glyphs;
 pub mod styles;
 pub mod svg_generator;
+pub mod svg_writer;
 
 use crate::api::types::{ChartResponse, TransitResponse, SynastryResponse};
 use svg_generator::SVGChartGenerator;
 
 // Re-export important types
+pub use glyphs::GlyphMode;
+pub use crate::data::i18n::Lang;
 pub use styles::{ChartStyles, init_styles, get_styles};
+#[cfg(not(target_arch = "wasm32"))]
+pub use styles::init_styles_from;
+pub use svg_generator::{DegreeSet, Highlights, LegendPosition, SvgDetail, SvgLayers, SvgOptions, WheelStyle, THUMBNAIL_SIZE};
 
 /// Generate SVG for natal chart (including transits if present)
 pub fn generate_natal_svg(chart_data: &ChartResponse) -> Result<String, String> {
-    let generator = SVGChartGenerator::new();
+    generate_natal_svg_with_options(chart_data, SvgOptions::default())
+}
+
+/// Generate SVG for natal chart with the given glyph mode and language
+pub fn generate_natal_svg_with_options(chart_data: &ChartResponse, options: SvgOptions) -> Result<String, String> {
+    let generator = SVGChartGenerator::with_options(options);
     generator.generate_natal_chart(chart_data)
 }
 
+/// Generate a text-free mini-wheel of a natal chart, `size` pixels square
+pub fn generate_thumbnail_svg(chart_data: &ChartResponse, size: f64) -> Result<String, String> {
+    SVGChartGenerator::new().generate_thumbnail(chart_data, size)
+}
+
 /// Generate SVG for synastry chart
 pub fn generate_synastry_svg(synastry_data: &SynastryResponse) -> Result<String, String> {
-    let generator = SVGChartGenerator::new();
+    generate_synastry_svg_with_options(synastry_data, SvgOptions::default())
+}
+
+/// Generate SVG for synastry chart with the given glyph mode and language
+pub fn generate_synastry_svg_with_options(synastry_data: &SynastryResponse, options: SvgOptions) -> Result<String, String> {
+    let generator = SVGChartGenerator::with_options(options);
     generator.generate_synastry_chart(synastry_data)
 }
 
 /// Generate SVG for transit chart
 pub fn generate_transit_svg(transit_data: &TransitResponse) -> Result<String, String> {
-    let generator = SVGChartGenerator::new();
+    generate_transit_svg_with_options(transit_data, SvgOptions::default())
+}
+
+/// Generate SVG for transit chart with the given glyph mode and language
+pub fn generate_transit_svg_with_options(transit_data: &TransitResponse, options: SvgOptions) -> Result<String, String> {
+    let generator = SVGChartGenerator::with_options(options);
     generator.generate_transit_chart(transit_data)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::api::types::{ChartResponse, PlanetInfo, HouseInfo, AspectInfo};
-    use chrono::{DateTime, Utc};
-    use std::collections::HashMap;
+    use crate::api::types::{AnglesInfo, ChartResponse, PlanetInfo, HouseInfo, AspectInfo, CHART_SCHEMA_VERSION};
+    use crate::calc::planets::Motion;
+    use crate::core::types::HouseSystem;
+    use chrono::Utc;
 
     fn create_test_chart_data() -> ChartResponse {
         ChartResponse {
             chart_type: "natal".to_string(),
+            schema_version: CHART_SCHEMA_VERSION,
             date: Utc::now(),
+            resolved_date: None,
             latitude: 40.7128,
             longitude: -74.0060,
-            house_system: "placidus".to_string(),
+            house_system: HouseSystem::Placidus,
             ayanamsa: "tropical".to_string(),
             planets: vec![
                 PlanetInfo {
@@ -47,7 +77,13 @@ mod tests {
                     latitude: 0.0,
                     speed: 1.0,
                     is_retrograde: false,
+                    motion: Motion::Direct,
+                    formatted_position: String::new(),
                     house: Some(5),
+                    house_fraction: None,
+                    decan: None,
+                    mansion: None,
+                    equatorial: None,
                 },
                 PlanetInfo {
                     name: "Moon".to_string(),
@@ -55,56 +91,445 @@ mod tests {
                     latitude: 0.0,
                     speed: 13.0,
                     is_retrograde: false,
+                    motion: Motion::Direct,
+                    formatted_position: String::new(),
                     house: Some(7),
+                    house_fraction: None,
+                    decan: None,
+                    mansion: None,
+                    equatorial: None,
                 },
             ],
+            warnings: Vec::new(),
+            degraded_options: Vec::new(),
+            boundary_warnings: Vec::new(),
+            angular: Vec::new(),
+            points: Vec::new(),
+            moon_mansion: None,
+            house_count: 2,
             houses: vec![
                 HouseInfo { number: 1, longitude: 0.0, latitude: 0.0 },
                 HouseInfo { number: 2, longitude: 30.0, latitude: 0.0 },
             ],
+            angles: None,
+            aspect_set: Default::default(),
             aspects: vec![
                 AspectInfo {
                     planet1: "Sun".to_string(),
                     planet2: "Moon".to_string(),
                     aspect: "Opposition".to_string(),
                     orb: 2.0,
+                    strength: 0.75,
+                    exact: false,
+                    applying: false,
+                    exact_in_days: None,
+                    exact_at: None,
                 },
             ],
             transit: None,
+            transits: Vec::new(),
+            ephemeris: None,
+            chart_ruler: None,
+            house_rulers: Vec::new(),
+            houses_summary: Vec::new(),
+            house_emphasis: None,
+            sidereal_time: None,
+            angle_speeds: None,
+            sect: None,
+            sun_altitude: None,
+            sect_ambiguous: false,
+            svg_chart: None,
+            svg_thumbnail: None,
+            diagnostics: None,
+            meta: None,
+            interpretation: None,
+            truncated: false,
         }
     }
 
     #[test]
     fn test_natal_svg_generation() {
-        let _ = init_styles(); // Initialize styles
+        // chart_styles.json is found next to Cargo.toml
+        init_styles().unwrap();
         let chart_data = create_test_chart_data();
-        let svg_result = generate_natal_svg(&chart_data);
-        
-        // If styles failed to load, test should handle that gracefully
-        match svg_result {
-            Ok(svg) => {
-                assert!(svg.contains("<svg"));
-                assert!(svg.contains("</svg>"));
-                assert!(svg.contains("☉")); // Sun symbol
-                assert!(svg.contains("☽")); // Moon symbol
-            },
-            Err(e) => {
-                // This is expected if chart_styles.json is not available during testing
-                assert!(e.contains("chart_styles.json"));
+        let svg = generate_natal_svg(&chart_data).unwrap();
+        assert!(svg.contains("<svg"));
+        assert!(svg.contains("</svg>"));
+        assert!(svg.contains("☉")); // Sun symbol
+        assert!(svg.contains("☽")); // Moon symbol
+    }
+
+    #[test]
+    fn test_cached_wheel_matches_drawing_it() {
+        init_styles().unwrap();
+        let chart_data = create_test_chart_data();
+        for glyph_mode in [GlyphMode::Text, GlyphMode::Paths] {
+            for lang in [Lang::En, Lang::De] {
+                let drawn = SVGChartGenerator {
+                    reuse_wheel: false,
+                    ..SVGChartGenerator::with_options(SvgOptions { glyph_mode, lang, ..Default::default() })
+                };
+                let cached = SVGChartGenerator::with_options(SvgOptions { glyph_mode, lang, ..Default::default() });
+                let expected = drawn.generate_natal_chart(&chart_data).unwrap();
+                // The first call fills the cache and the second copies from it
+                assert_eq!(cached.generate_natal_chart(&chart_data).unwrap(), expected);
+                assert_eq!(cached.generate_natal_chart(&chart_data).unwrap(), expected);
             }
         }
     }
 
     #[test]
-    fn test_styles_initialization() {
-        let result = init_styles();
-        // Either loads file or fails - both are valid test outcomes
-        
-        if let Some(styles) = get_styles() {
-            assert!(styles.get_planet_color("Sun").starts_with("#"));
-            assert!(styles.get_chart_color("background").starts_with("#"));
-            assert!(styles.get_aspect_color("Opposition").starts_with("#"));
+    fn test_paths_mode_has_no_unicode_glyphs() {
+        init_styles().unwrap();
+        let chart_data = create_test_chart_data();
+        let options = SvgOptions { glyph_mode: GlyphMode::Paths, lang: Lang::Es, ..Default::default() };
+
+        let svg = generate_natal_svg_with_options(&chart_data, options).unwrap();
+        for glyph in ["☉", "☽", "☿", "♀", "♂", "♃", "♄", "♅", "♆", "♇", "♈", "♉", "♊", "♋", "♌", "♍", "♎", "♏", "♐", "♑", "♒", "♓", "\u{FE0E}"] {
+            assert!(!svg.contains(glyph), "found {} in paths output", glyph);
+        }
+        // One path per planet, one per sign
+        assert_eq!(svg.matches("<path class=\"planet-glyph\"").count(), chart_data.planets.len());
+        assert_eq!(svg.matches("<path class=\"sign-glyph\"").count(), 12);
+        // Localized tooltip and label
+        assert!(svg.contains("Sol 0°00"));
+        assert!(svg.contains("Luna 0°00"));
+        assert!(svg.contains("Nacimiento:"));
+    }
+
+    #[test]
+    fn test_glyph_tables_cover_chart_bodies() {
+        for planet in ["Sun", "Moon", "Mercury", "Venus", "Mars", "Jupiter", "Saturn", "Uranus", "Neptune", "Pluto"] {
+            assert!(glyphs::planet_path(planet).is_some(), "no path for {}", planet);
+        }
+        for aspect in ["Conjunction", "Opposition", "Trine", "Square", "Sextile", "Quincunx"] {
+            assert!(glyphs::aspect_path(aspect).is_some(), "no path for {}", aspect);
+        }
+        assert!(glyphs::sign_path(11).starts_with('M'));
+        assert_eq!("PATHS".parse::<GlyphMode>(), Ok(GlyphMode::Paths));
+        assert!("outline".parse::<GlyphMode>().is_err());
+    }
+
+    #[test]
+    fn test_house_labels_sit_midway_through_their_own_house() {
+        use svg_generator::house_label;
+
+        // A 20° house across 0° Aries, then a 40° house
+        let cusps = [350.0, 10.0, 50.0];
+        let first = house_label(cusps[0], Some(cusps[1]));
+        let second = house_label(cusps[1], Some(cusps[2]));
+        assert!(first.longitude.abs() < 1e-9);
+        assert!((second.longitude - 30.0).abs() < 1e-9);
+        assert_eq!(first.font_size, second.font_size);
+
+        let narrow = house_label(100.0, Some(106.0));
+        assert!((narrow.longitude - 103.0).abs() < 1e-9);
+        assert!(narrow.font_size < first.font_size);
+        assert_eq!(house_label(100.0, None).longitude, 115.0);
+
+        // Gauquelin sectors run against the zodiac
+        let sector = house_label(100.0, Some(90.0));
+        assert!((sector.longitude - 95.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_whole_sign_wheel_starts_at_the_rising_sign() {
+        init_styles().unwrap();
+        // 10° Cancer rising, the Sun at 15° Cancer
+        let mut chart_data = create_test_chart_data();
+        chart_data.houses[0].longitude = 100.0;
+        chart_data.planets[0].longitude = 105.0;
+
+        let options = SvgOptions { wheel_style: WheelStyle::WholeSign, ..Default::default() };
+        let svg = generate_natal_svg_with_options(&chart_data, options).unwrap();
+        // The Sun's group is moved to the middle of the first sector, 15° below the left horizon
+        let sun = svg.lines().find(|line| line.contains("id=\"planet-natal-sun\"")).unwrap();
+        let offsets = sun.split("translate(").nth(1).unwrap().split(')').next().unwrap();
+        let (x, y) = offsets.split_once(' ').map(|(x, y)| (x.parse::<f64>().unwrap(), y.parse::<f64>().unwrap())).unwrap();
+        let angle = (y - 400.0).atan2(x - 400.0).to_degrees();
+        assert!((angle - 165.0).abs() < 0.01, "{}", angle);
+
+        // No cusp lines, and each sector numbered from the rising sign
+        assert!(!svg.contains("house-cusp"));
+        let numbers: Vec<&str> = svg.split("class=\"sector-house\"").skip(1).map(|rest| rest.lines().nth(1).unwrap()).collect();
+        assert_eq!(numbers, ["10", "11", "12", "1", "2", "3", "4", "5", "6", "7", "8", "9"]);
+        assert_eq!("whole_sign".parse::<WheelStyle>(), Ok(WheelStyle::WholeSign));
+        assert!("equal".parse::<WheelStyle>().is_err());
+    }
+
+    #[test]
+    fn test_axes_are_drawn_through_the_angles() {
+        init_styles().unwrap();
+        let mut chart_data = create_test_chart_data();
+        let svg = generate_natal_svg(&chart_data).unwrap();
+        assert!(!svg.contains("angle-axis"));
+        // Without the angles the first cusp stands in for the Ascendant
+        let cusp = |svg: &str| svg.lines().find(|line| line.contains("id=\"cusp-1\"")).unwrap().to_string();
+        assert!(cusp(&svg).contains("stroke-width=\"2.5\""));
+
+        chart_data.angles = Some(AnglesInfo {
+            ascendant: 10.0,
+            descendant: 190.0,
+            midheaven: 280.0,
+            ic: 100.0,
+            vertex: 200.0,
+            east_point: 8.0,
+            armc: 281.0,
+        });
+        let svg = generate_natal_svg(&chart_data).unwrap();
+        assert_eq!(svg.matches("class=\"angle-axis\"").count(), 2);
+        assert!(svg.contains("id=\"axis-ascendant\"") && svg.contains("id=\"axis-midheaven\""));
+        assert!(cusp(&svg).contains("stroke-width=\"1\""));
+    }
+
+    #[test]
+    fn test_degree_markers_turn_with_the_wheel() {
+        init_styles().unwrap();
+        let chart_data = create_test_chart_data();
+        assert!(!generate_natal_svg(&chart_data).unwrap().contains("aries-point"));
+
+        let classical = DegreeSet::classical();
+        assert_eq!(classical.iter().count(), 28);
+        assert!(classical.contains(103) && classical.contains(39) && classical.contains(334));
+        assert!(!classical.contains(30));
+
+        let highlights = Highlights { critical_degrees: [13, 373].into_iter().collect(), aries_points: true, angular: false };
+        let aries_point = |wheel_style| {
+            let svg = generate_natal_svg_with_options(&chart_data, SvgOptions { wheel_style, highlights, ..Default::default() }).unwrap();
+            assert_eq!(svg.matches("class=\"critical-degree\"").count(), 1);
+            assert_eq!(svg.matches("class=\"aries-point\"").count(), 4);
+            let line = svg.lines().find(|line| line.contains("aries-point")).unwrap().to_string();
+            let start = line.find("x1=\"").unwrap() + 4;
+            let x1: f64 = line[start..].split('"').next().unwrap().parse().unwrap();
+            x1
+        };
+        // 0° Aries is at the top of a degree wheel, and at the left of a whole-sign wheel with Aries rising
+        assert!((aries_point(WheelStyle::Degree) - 400.0).abs() < 1e-6);
+        assert!((aries_point(WheelStyle::WholeSign) - 120.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_compact_rounds_coordinates_only() {
+        use svg_writer::SvgWriter;
+
+        let mut doc = SvgWriter::default().decimals(1);
+        doc.element("line").attr("x1", 400).attr("x2", 482.191_399_923_403_4).attr("y1", -0.0412).attr("y2", 661.95).close();
+        doc.element("path")
+            .attr("d", "M 8 0.123456")
+            .attr("transform", "translate(199.15695 300.81 ) scale(0.6666666666666666)")
+            .close();
+        doc.element("text").attr("font-size", 8).attr("x", 12.34).open();
+        doc.text("Sun 12.345");
+        assert_eq!(
+            doc.finish(),
+            "<line x1=\"400\" x2=\"482.2\" y1=\"0\" y2=\"662\"/>\n<path d=\"M 8 0.123456\" transform=\"translate(199.2 300.8) scale(0.6666666666666666)\"/>\n<text font-size=\"8\" x=\"12.3\">\nSun 12.345\n</text>"
+        );
+        assert_eq!("Compact".parse::<SvgDetail>(), Ok(SvgDetail::Compact));
+        assert!("tiny".parse::<SvgDetail>().is_err());
+    }
+
+    #[test]
+    fn test_stable_ids_and_dynamic_layers() {
+        init_styles().unwrap();
+        let chart_data = create_test_chart_data();
+        let svg = generate_natal_svg(&chart_data).unwrap();
+        assert_eq!(svg, generate_natal_svg(&chart_data).unwrap());
+        for id in ["cusp-1", "house-2", "planets", "aspects", "planet-natal-sun", "aspect-sun-opposition-moon"] {
+            assert_eq!(svg.matches(&format!("id=\"{}\"", id)).count(), 1, "{}", id);
+        }
+        // Coordinates have at most two decimals
+        let transform = svg.lines().find(|line| line.contains("planet-natal-moon")).unwrap();
+        let translate = transform.split("translate(").nth(1).unwrap().split(')').next().unwrap();
+        assert!(translate.split(' ').all(|n| n.split('.').nth(1).is_none_or(|decimals| decimals.len() <= 2)), "{}", translate);
+
+        let dynamic = generate_natal_svg_with_options(&chart_data, SvgOptions { layers: SvgLayers::Dynamic, ..Default::default() }).unwrap();
+        assert!(!dynamic.contains("id=\"cusp-1\"") && !dynamic.contains("♈"));
+        assert!(dynamic.contains("id=\"planet-natal-sun\"") && dynamic.contains("id=\"aspect-sun-opposition-moon\""));
+        assert_eq!("dynamic".parse::<SvgLayers>(), Ok(SvgLayers::Dynamic));
+        assert!("static".parse::<SvgLayers>().is_err());
+    }
+
+    #[test]
+    fn test_legend_lists_bodies_and_grows_the_canvas() {
+        init_styles().unwrap();
+        let mut chart_data = create_test_chart_data();
+        chart_data.planets[1].is_retrograde = true;
+        let view_box = |svg: &str| -> Vec<f64> {
+            let value = svg.split("viewBox=\"").nth(1).unwrap().split('"').next().unwrap();
+            value.split(' ').map(|n| n.parse().unwrap()).collect()
+        };
+        let legend = |position| SvgOptions { legend: Some(position), ..Default::default() };
+
+        let plain = generate_natal_svg(&chart_data).unwrap();
+        assert!(!plain.contains("legend-body"));
+        let right = generate_natal_svg_with_options(&chart_data, legend(LegendPosition::Right)).unwrap();
+        assert_eq!(right.matches("class=\"legend-body\"").count(), chart_data.planets.len());
+        assert!(right.contains("id=\"legend-natal-moon\"") && right.contains("℞"));
+        assert!(right.contains("0°00' Leo"));
+        // Two cusps aren't enough to find the angles
+        assert!(!right.contains("legend-angle"));
+        let (plain_box, right_box) = (view_box(&plain), view_box(&right));
+        assert!(right_box[2] > plain_box[2]);
+        assert_eq!(right_box[3], plain_box[3]);
+
+        chart_data.houses = (1..=12).map(|number| HouseInfo { number, longitude: f64::from(number - 1) * 30.0, latitude: 0.0 }).collect();
+        let bottom = generate_natal_svg_with_options(&chart_data, legend(LegendPosition::Bottom)).unwrap();
+        assert_eq!(bottom.matches("class=\"legend-body\"").count(), chart_data.planets.len());
+        assert_eq!(bottom.matches("class=\"legend-angle\"").count(), 4);
+        let bottom_box = view_box(&bottom);
+        assert_eq!(bottom_box[2], plain_box[2]);
+        assert!(bottom_box[3] > plain_box[3]);
+
+        // Only the wheel goes in the dynamic layers
+        let options = SvgOptions { layers: SvgLayers::Dynamic, ..legend(LegendPosition::Right) };
+        assert!(!generate_natal_svg_with_options(&chart_data, options).unwrap().contains("legend-body"));
+        assert_eq!("bottom".parse::<LegendPosition>(), Ok(LegendPosition::Bottom));
+        assert!("left".parse::<LegendPosition>().is_err());
+    }
+
+    #[test]
+    fn test_writer_serializes_like_the_svg_crate() {
+        use svg_writer::SvgWriter;
+
+        // Attributes sorted by name, a repeated one keeping its last value, and
+        // values with double quotes in single quotes
+        let mut doc = SvgWriter::default();
+        doc.element("g").attr("y", 2).attr("class", "a").attr("y", 3).open();
+        doc.element("title").open();
+        doc.end();
+        doc.element("rect").attr("style", r#"font-family: "A""#).close();
+        assert_eq!(doc.finish(), "<g class=\"a\" y=\"3\">\n<title/>\n<rect style='font-family: \"A\"'/>\n</g>");
+    }
+
+    #[test]
+    fn test_tighter_aspects_are_drawn_bolder() {
+        init_styles().unwrap();
+        let mut chart_data = create_test_chart_data();
+        let mut mars = chart_data.planets[0].clone();
+        mars.name = "Mars".to_string();
+        mars.longitude = 217.0;
+        chart_data.planets.push(mars);
+        chart_data.aspects = vec![
+            AspectInfo {
+                planet1: "Sun".to_string(),
+                planet2: "Moon".to_string(),
+                aspect: "Trine".to_string(),
+                orb: 0.05,
+                strength: 0.99,
+                exact: true,
+                applying: false,
+                exact_in_days: None,
+                exact_at: None,
+            },
+            AspectInfo {
+                planet1: "Sun".to_string(),
+                planet2: "Mars".to_string(),
+                aspect: "Square".to_string(),
+                orb: 7.0,
+                strength: 0.3,
+                exact: false,
+                applying: false,
+                exact_in_days: None,
+                exact_at: None,
+            },
+        ];
+
+        let svg = generate_natal_svg(&chart_data).unwrap();
+        let styles = get_styles().unwrap();
+        let attribute = |color: &str, name: &str| -> f64 {
+            let line = svg.lines().find(|line| line.starts_with("<line") && line.contains(&format!("stroke=\"{}\"", color))).unwrap();
+            let start = line.find(&format!(" {}=\"", name)).unwrap() + name.len() + 3;
+            line[start..].split('"').next().unwrap().parse().unwrap()
+        };
+        let (trine, square) = (styles.get_aspect_color("Trine"), styles.get_aspect_color("Square"));
+        assert!(attribute(trine, "stroke-width") > attribute(square, "stroke-width"));
+        assert!(attribute(trine, "opacity") > attribute(square, "opacity"));
+        // Only the exact trine has a marker
+        assert_eq!(svg.matches(r#"class="exact-aspect""#).count(), 1);
+    }
+
+    #[test]
+    fn test_thumbnail_is_dots_and_major_aspects() {
+        init_styles().unwrap();
+        let mut chart_data = create_test_chart_data();
+        chart_data.aspects.push(AspectInfo {
+            aspect: "Quincunx".to_string(),
+            ..chart_data.aspects[0].clone()
+        });
+
+        let svg = generate_thumbnail_svg(&chart_data, THUMBNAIL_SIZE).unwrap();
+        assert!(svg.contains(r#"width="120""#));
+        assert!(!svg.contains("<text"));
+        assert!(!svg.contains("<path"));
+        assert_eq!(svg.matches(r#"class="thumbnail-planet""#).count(), 2);
+        // The Quincunx is minor
+        assert_eq!(svg.matches(r#"class="thumbnail-aspect""#).count(), 1);
+        // Background, two rings, twelve sign boundaries, the dots and the aspect
+        assert_eq!(svg.matches('<').count() - svg.matches("</").count(), 1 + 1 + 2 + 12 + 2 + 1);
+    }
+
+    #[test]
+    fn test_chiron_and_overridden_glyphs() {
+        init_styles().unwrap();
+        let mut chart_data = create_test_chart_data();
+        let mut chiron = chart_data.planets[0].clone();
+        chiron.name = "Chiron".to_string();
+        chiron.longitude = 40.0;
+        let mut pluto = chart_data.planets[0].clone();
+        pluto.name = "Pluto".to_string();
+        pluto.longitude = 250.0;
+        chart_data.planets.extend([chiron, pluto]);
+
+        // Text nodes sit on lines of their own
+        let drawn = |svg: &str, glyph: &str| svg.lines().any(|line| line == glyph);
+        let svg = generate_natal_svg(&chart_data).unwrap();
+        assert!(drawn(&svg, "⚷"));
+        assert!(drawn(&svg, "♇"));
+
+        let mut styles = ChartStyles::from_json(include_str!("../../chart_styles.json")).unwrap();
+        styles.glyphs.planets.insert("Pluto".to_string(), "⯓".to_string());
+        styles.glyphs.signs.insert("Aries".to_string(), "Ar".to_string());
+        let svg = SVGChartGenerator::new().with_styles(styles).generate_natal_chart(&chart_data).unwrap();
+        assert!(drawn(&svg, "⯓") && !drawn(&svg, "♇"));
+        assert!(drawn(&svg, "Ar") && !drawn(&svg, "♈︎"));
+        assert!(drawn(&svg, "⚷"));
+    }
+
+    #[test]
+    fn test_styles_are_validated_on_load() {
+        let json = include_str!("../../chart_styles.json");
+        let styles = ChartStyles::from_json(json).unwrap();
+        assert_eq!(styles.planet_glyph("Vesta"), "⚶");
+        assert_eq!(styles.aspect_glyph("Trine"), "△");
+        assert_eq!(styles.planet_glyph("Fortune"), "?");
+        // Charts name the minor aspects in camel case
+        assert_eq!(styles.aspect_glyph("SemiSextile"), "⚺");
+        assert_eq!(styles.themed("light").unwrap().get_chart_color("background"), "#FFFFFF");
+        assert!(styles.themed("sepia").is_err());
+
+        let mut value: serde_json::Value = serde_json::from_str(json).unwrap();
+        value["planet_colors"].as_object_mut().unwrap().remove("Chiron");
+        let error = ChartStyles::from_json(&value.to_string()).unwrap_err();
+        assert_eq!(error.to_string(), "planet_colors has no color for Chiron");
+
+        let mut value: serde_json::Value = serde_json::from_str(json).unwrap();
+        value["themes"]["light"]["planet_colors"]["true_node"] = serde_json::json!("#000000");
+        let error = ChartStyles::from_json(&value.to_string()).unwrap_err();
+        assert_eq!(error.to_string(), "themes.light.planet_colors has an entry for unknown \"true_node\"");
+
+        for (section, name, glyph) in [("planets", "Plutoo", "P"), ("planets", "Pluto", " "), ("signs", "Ophiuchus", "O")] {
+            let mut value: serde_json::Value = serde_json::from_str(json).unwrap();
+            value["glyphs"] = serde_json::json!({ section: { name: glyph } });
+            assert!(ChartStyles::from_json(&value.to_string()).is_err(), "{} {}", section, name);
         }
-        // If styles are None, that's also a valid test outcome when file is missing
+    }
+
+    #[test]
+    fn test_styles_initialization() {
+        init_styles().unwrap();
+        let styles = get_styles().expect("chart_styles.json is loaded");
+        assert!(styles.get_planet_color("Sun").starts_with("#"));
+        assert!(styles.get_chart_color("background").starts_with("#"));
+        assert!(styles.get_aspect_color("Opposition").starts_with("#"));
     }
 }
@@ -1,5 +1,10 @@
+use crate::calc::aspects::AspectType;
+use crate::calc::positions::POSITION_BODIES;
+use crate::calc::rulers::Sign;
+use crate::data::symbols;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+#[cfg(not(target_arch = "wasm32"))]
 use std::fs;
 use std::sync::{OnceLock, Once};
 
@@ -15,20 +20,263 @@ pub struct AspectLineColors {
     pub default_colors: HashMap<String, String>,
 }
 
+/// Symbols replacing the built-in ones in `data::symbols`, keyed by body, sign or
+/// aspect name, e.g. `"planets": { "Pluto": "⯓" }`
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct GlyphStyles {
+    #[serde(default)]
+    pub planets: HashMap<String, String>,
+    #[serde(default)]
+    pub signs: HashMap<String, String>,
+    #[serde(default)]
+    pub aspects: HashMap<String, String>,
+}
+
+/// The nature of an aspect, which sets how its line is drawn
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AspectFamily {
+    /// Trines and sextiles
+    Harmonious,
+    /// Squares and oppositions
+    Challenging,
+    /// Conjunctions, the minor aspects and custom ones
+    Neutral,
+}
+
+impl AspectFamily {
+    /// Family of an aspect, by name
+    pub fn of(aspect: &str) -> Self {
+        match aspect.parse::<AspectType>() {
+            Ok(AspectType::Trine | AspectType::Sextile) => AspectFamily::Harmonious,
+            Ok(AspectType::Square | AspectType::Opposition) => AspectFamily::Challenging,
+            _ => AspectFamily::Neutral,
+        }
+    }
+}
+
+/// How the lines of one family of aspects are drawn
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct AspectFamilyStyle {
+    /// Stroke width of an exact aspect
+    pub width: f64,
+    /// `stroke-dasharray`, e.g. "3,3"; solid without. Transit and synastry
+    /// lines keep their own dashes.
+    #[serde(default)]
+    pub dasharray: Option<String>,
+}
+
+/// How aspect lines are drawn. A line's opacity and width grow with the
+/// aspect's strength, from `min_opacity` and `min_width` of its family's width
+/// at the edge of the orb to `max_opacity` and the full width when exact.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct AspectLineStyles {
+    pub harmonious: AspectFamilyStyle,
+    pub challenging: AspectFamilyStyle,
+    pub neutral: AspectFamilyStyle,
+    pub min_opacity: f64,
+    pub max_opacity: f64,
+    /// Share of the family's width a line at the edge of the orb gets
+    pub min_width: f64,
+    /// Orb in degrees under which a line gets a marker at its midpoint
+    pub exact_orb: f64,
+    /// Radius of that marker
+    pub exact_marker_radius: f64,
+}
+
+impl Default for AspectLineStyles {
+    fn default() -> Self {
+        Self {
+            harmonious: AspectFamilyStyle { width: 1.5, dasharray: None },
+            challenging: AspectFamilyStyle { width: 2.0, dasharray: None },
+            neutral: AspectFamilyStyle { width: 1.0, dasharray: Some("3,3".to_string()) },
+            min_opacity: 0.3,
+            max_opacity: 0.9,
+            min_width: 0.5,
+            exact_orb: 0.1,
+            exact_marker_radius: 2.5,
+        }
+    }
+}
+
+/// Stroke of an aspect line
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AspectStroke<'a> {
+    pub width: f64,
+    pub opacity: f64,
+    pub dasharray: Option<&'a str>,
+}
+
+// Rounded to hundredths, so the SVG doesn't carry every float digit
+fn hundredths(value: f64) -> f64 {
+    (value * 100.0).round() / 100.0
+}
+
+impl AspectLineStyles {
+    pub fn family(&self, family: AspectFamily) -> &AspectFamilyStyle {
+        match family {
+            AspectFamily::Harmonious => &self.harmonious,
+            AspectFamily::Challenging => &self.challenging,
+            AspectFamily::Neutral => &self.neutral,
+        }
+    }
+
+    /// Stroke of the line for `aspect` at `strength`, 1 when exact and 0 at the edge of its orb
+    pub fn stroke(&self, aspect: &str, strength: f64) -> AspectStroke<'_> {
+        let family = self.family(AspectFamily::of(aspect));
+        let strength = strength.clamp(0.0, 1.0);
+        AspectStroke {
+            width: hundredths(family.width * (self.min_width + (1.0 - self.min_width) * strength)),
+            opacity: hundredths(self.min_opacity + (self.max_opacity - self.min_opacity) * strength),
+            dasharray: family.dasharray.as_deref(),
+        }
+    }
+
+    /// Whether an aspect at `orb` gets the exact marker
+    pub fn is_exact(&self, orb: f64) -> bool {
+        orb.abs() < self.exact_orb
+    }
+
+    /// Refuses widths and opacities no line could be drawn with
+    pub fn validate(&self) -> Result<(), String> {
+        for (name, family) in [("harmonious", &self.harmonious), ("challenging", &self.challenging), ("neutral", &self.neutral)] {
+            if !(family.width > 0.0 && family.width.is_finite()) {
+                return Err(format!("aspect_lines.{}.width must be above 0", name));
+            }
+        }
+        for (name, value) in [("min_opacity", self.min_opacity), ("max_opacity", self.max_opacity), ("min_width", self.min_width)] {
+            if !(0.0..=1.0).contains(&value) {
+                return Err(format!("aspect_lines.{} must be between 0 and 1", name));
+            }
+        }
+        if self.min_opacity > self.max_opacity {
+            return Err("aspect_lines.min_opacity is above max_opacity".to_string());
+        }
+        if !(self.exact_orb >= 0.0 && self.exact_marker_radius >= 0.0) {
+            return Err("aspect_lines.exact_orb and exact_marker_radius must not be negative".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// Colors, and aspect line styles, replacing the base ones under a named theme, e.g.
+/// `"themes": { "light": { "chart_colors": { "background": "#FFFFFF" } } }`
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct ThemeStyles {
+    #[serde(default)]
+    pub planet_colors: HashMap<String, String>,
+    #[serde(default)]
+    pub chart_colors: HashMap<String, String>,
+    /// Aspect line colors, by aspect name
+    #[serde(default)]
+    pub aspect_colors: HashMap<String, String>,
+    /// Aspect line widths, dashes and opacities, in place of the base ones
+    #[serde(default)]
+    pub aspect_lines: Option<AspectLineStyles>,
+}
+
+fn default_theme() -> String {
+    "default".to_string()
+}
+
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct ChartStyles {
     pub planet_colors: HashMap<String, String>,
     pub chart_colors: HashMap<String, String>,
     pub aspect_line_colors: AspectLineColors,
+    #[serde(default)]
+    pub glyphs: GlyphStyles,
+    #[serde(default)]
+    pub aspect_lines: AspectLineStyles,
+    /// Name of the theme the base colors make up
+    #[serde(default = "default_theme")]
+    pub theme: String,
+    /// Other themes, as changes to the base colors
+    #[serde(default)]
+    pub themes: HashMap<String, ThemeStyles>,
 }
 
 impl ChartStyles {
+    #[cfg(not(target_arch = "wasm32"))]
     pub fn load_from_file(path: &str) -> Result<Self, Box<dyn std::error::Error>> {
         let content = fs::read_to_string(path)?;
-        let styles: ChartStyles = serde_json::from_str(&content)?;
+        Self::from_json(&content)
+    }
+
+    /// Parses styles from JSON and checks them with `validate`
+    pub fn from_json(json: &str) -> Result<Self, Box<dyn std::error::Error>> {
+        let styles: ChartStyles = serde_json::from_str(json)?;
+        styles.validate()?;
+        Ok(styles)
+    }
+
+    /// Refuses styles that leave a chart body without a color, or whose glyph
+    /// overrides are empty or name nothing the charts draw
+    pub fn validate(&self) -> Result<(), String> {
+        for planet in POSITION_BODIES {
+            let name = planet.to_string();
+            if !self.planet_colors.contains_key(&name) {
+                return Err(format!("planet_colors has no color for {}", name));
+            }
+        }
+        check_glyphs("planets", &self.glyphs.planets, |name| symbols::body_glyph(name).is_some())?;
+        check_glyphs("signs", &self.glyphs.signs, |name| Sign::ALL.iter().any(|sign| sign.to_string() == name))?;
+        check_glyphs("aspects", &self.glyphs.aspects, |name| symbols::aspect_glyph(name).is_some())?;
+        self.aspect_lines.validate()?;
+        for (theme, colors) in &self.themes {
+            if let Some(lines) = &colors.aspect_lines {
+                lines.validate().map_err(|e| format!("themes.{}.{}", theme, e))?;
+            }
+            if let Some(name) = colors.planet_colors.keys().find(|name| !is_body_name(name)) {
+                return Err(format!("themes.{}.planet_colors has an entry for unknown {:?}", theme, name));
+            }
+        }
+        Ok(())
+    }
+
+    /// Names of the themes, the base one first and the rest in order
+    pub fn theme_names(&self) -> Vec<&str> {
+        let mut others: Vec<&str> = self.themes.keys().map(String::as_str).filter(|name| *name != self.theme).collect();
+        others.sort_unstable();
+        std::iter::once(self.theme.as_str()).chain(others).collect()
+    }
+
+    /// These styles with the colors of `theme`, which is the base theme or one of `themes`
+    pub fn themed(&self, theme: &str) -> Result<ChartStyles, String> {
+        if theme == self.theme {
+            return Ok(self.clone());
+        }
+        let colors = self
+            .themes
+            .get(theme)
+            .ok_or_else(|| format!("Unknown theme '{}' (expected one of: {})", theme, self.theme_names().join(", ")))?;
+        let mut styles = self.clone();
+        styles.planet_colors.extend(colors.planet_colors.clone());
+        styles.chart_colors.extend(colors.chart_colors.clone());
+        styles.aspect_line_colors.default_colors.extend(colors.aspect_colors.clone());
+        if let Some(lines) = &colors.aspect_lines {
+            styles.aspect_lines = lines.clone();
+        }
+        styles.theme = theme.to_string();
         Ok(styles)
     }
 
+    /// Symbol of a body: the style's override, else the built-in one, else "?"
+    pub fn planet_glyph(&self, planet: &str) -> &str {
+        self.glyphs.planets.get(planet).map(|s| s.as_str()).or_else(|| symbols::body_glyph(planet)).unwrap_or("?")
+    }
+
+    /// Symbol of a sign: the style's override, else the built-in one
+    pub fn sign_glyph(&self, sign: Sign) -> &str {
+        let builtin = symbols::sign_symbol(sign).glyph.unwrap_or("?");
+        self.glyphs.signs.get(&sign.to_string()).map(|s| s.as_str()).unwrap_or(builtin)
+    }
+
+    /// Symbol of an aspect: the style's override, else the built-in one, else "?"
+    pub fn aspect_glyph(&self, aspect: &str) -> &str {
+        self.glyphs.aspects.get(aspect).map(|s| s.as_str()).or_else(|| symbols::aspect_glyph(aspect)).unwrap_or("?")
+    }
+
     pub fn get_planet_color(&self, planet: &str) -> &str {
         self.planet_colors.get(planet).map(|s| s.as_str()).unwrap_or("#333333")
     }
@@ -63,9 +311,34 @@ impl ChartStyles {
     }
 }
 
+/// Whether `name` is a body as charts name it, e.g. "TrueNode"
+fn is_body_name(name: &str) -> bool {
+    symbols::BODY_SYMBOLS.iter().any(|(planet, _)| planet.to_string() == name)
+}
+
+/// Refuses glyph overrides that are empty or for names `known` doesn't accept
+fn check_glyphs(section: &str, overrides: &HashMap<String, String>, known: fn(&str) -> bool) -> Result<(), String> {
+    for (name, glyph) in overrides {
+        if !known(name) {
+            return Err(format!("glyphs.{} has an entry for unknown {:?}", section, name));
+        }
+        if glyph.trim().is_empty() {
+            return Err(format!("glyphs.{}.{} is empty", section, name));
+        }
+    }
+    Ok(())
+}
+
 static GLOBAL_STYLES: OnceLock<ChartStyles> = OnceLock::new();
 static INIT_ONCE: Once = Once::new();
 
+/// The browser has no filesystem, so WebAssembly builds embed the default styles
+#[cfg(target_arch = "wasm32")]
+fn try_load_styles() -> Result<ChartStyles, Box<dyn std::error::Error>> {
+    ChartStyles::from_json(include_str!("../../chart_styles.json"))
+}
+
+#[cfg(not(target_arch = "wasm32"))]
 fn try_load_styles() -> Result<ChartStyles, Box<dyn std::error::Error>> {
     // Try multiple possible paths for the chart styles files
     // Prioritize the new format, then fall back to the old format
@@ -111,6 +384,15 @@ pub fn init_styles() -> Result<(), Box<dyn std::error::Error>> {
     })
 }
 
+/// Loads the styles from one file, for a server configured with its own
+#[cfg(not(target_arch = "wasm32"))]
+pub fn init_styles_from(path: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let styles = ChartStyles::load_from_file(path).map_err(|e| format!("Failed to load chart styles from {}: {}", path, e))?;
+    log::info!("Loaded chart styles from {}", path);
+    let _ = GLOBAL_STYLES.set(styles);
+    Ok(())
+}
+
 pub fn get_styles() -> Option<&'static ChartStyles> {
     // Try to get existing styles first
     if let Some(styles) = GLOBAL_STYLES.get() {
@@ -1,9 +1,17 @@
-use crate::api::types::{ChartResponse, PlanetInfo, AspectInfo, HouseInfo, TransitResponse, SynastryResponse};
-use crate::charts::styles::get_styles;
-use svg::Document;
-use svg::node::element::{Circle, Line, Text, Rectangle};
-use svg::node::Text as TextNode;
+use crate::api::types::{AnglePointInfo, AnglesInfo, BoundaryWarningInfo, ChartResponse, PlanetInfo, AspectInfo, HouseInfo, TransitResponse, SynastryResponse};
+use crate::calc::angles::{cluster_center, normalize, proximity_groups, signed_delta};
+use crate::calc::aspects::{get_aspect_types, AspectType};
+use crate::calc::planets::Planet;
+use crate::calc::rulers::Sign;
+use crate::charts::glyphs::{self, GlyphMode, GLYPH_BOX};
+use crate::data::i18n::Lang;
+use crate::charts::styles::{get_styles, ChartStyles};
+use crate::charts::svg_writer::{Element, SvgWriter};
+use crate::data::symbols::body_symbol;
+use std::collections::HashMap;
+use std::fmt::{Display, Write as _};
 use std::f64::consts::PI;
+use std::sync::{Arc, Mutex, OnceLock};
 use chrono::{DateTime, Utc};
 
 const CHART_SIZE: f64 = 800.0;
@@ -13,12 +21,421 @@ const INNER_RADIUS: f64 = 280.0;
 const BASE_PLANET_RADIUS: f64 = 240.0;
 const PLANET_RADIUS_STEP: f64 = 15.0;
 
+/// Font size of a house number in a house at least `NARROW_HOUSE_WIDTH` wide
+const HOUSE_NUMBER_FONT_SIZE: f64 = 12.0;
+
+/// Smallest font size a house number shrinks to
+const MIN_HOUSE_NUMBER_FONT_SIZE: f64 = 7.0;
+
+/// Houses narrower than this, in degrees, get smaller numbers
+const NARROW_HOUSE_WIDTH: f64 = 12.0;
+
+/// Length of the second chart's cusp ticks outside the zodiac ring
+const SECOND_HOUSE_TICK: f64 = 14.0;
+
+/// Font size of the second chart's house numbers, before narrow houses shrink them
+const SECOND_HOUSE_NUMBER_FONT_SIZE: f64 = 9.0;
+
+/// Length of a critical degree's tick into the zodiac ring
+const CRITICAL_DEGREE_TICK: f64 = 10.0;
+
+/// Radius of the ring around a body on an angle, just clear of its glyph box's corners
+const ANGULAR_RING_RADIUS: f64 = 23.0;
+
+/// Width and height of a thumbnail when the caller has no size of its own
+pub const THUMBNAIL_SIZE: f64 = 120.0;
+
+/// Thumbnail planet dots' radius, as a fraction of the thumbnail's size
+const THUMBNAIL_DOT_RADIUS: f64 = 1.0 / 40.0;
+
+/// Thumbnail aspect lines' width, as a fraction of the thumbnail's size
+const THUMBNAIL_ASPECT_WIDTH: f64 = 1.0 / 80.0;
+
+/// Font size of the legend's text; its glyphs, row height and columns scale with it
+const LEGEND_FONT_SIZE: f64 = 12.0;
+
+/// Height of a legend row, as a multiple of `LEGEND_FONT_SIZE`
+const LEGEND_ROW_HEIGHT: f64 = 1.75;
+
+/// Width of a legend column, as a multiple of `LEGEND_FONT_SIZE`
+const LEGEND_COLUMN_WIDTH: f64 = 24.0;
+
+/// Space around the legend, in pixels
+const LEGEND_MARGIN: f64 = 20.0;
+
+/// The angles a legend lists, by the house cusp each falls on
+const LEGEND_ANGLES: [(&str, &str, u8); 4] =
+    [("Ascendant", "AC", 1), ("Descendant", "DC", 7), ("Midheaven", "MC", 10), ("IC", "IC", 4)];
+
+/// Where and how large to draw a house number
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct HouseLabel {
+    /// Ecliptic longitude the number is centred on
+    pub longitude: f64,
+    pub font_size: f64,
+}
+
+/// Places a house number midway between its cusp and the next house's cusp,
+/// along the shorter arc and wrapping past 0° Aries, as far as it can be from
+/// both cusp lines. Numbers in narrow houses shrink so they don't run into the
+/// lines. Without the next cusp the house is taken to be 30° wide.
+pub(crate) fn house_label(cusp: f64, next_cusp: Option<f64>) -> HouseLabel {
+    // Negative for Gauquelin sectors, which are numbered against the zodiac
+    let arc = next_cusp.map_or(30.0, |next| signed_delta(next, cusp));
+    let scale = (arc.abs() / NARROW_HOUSE_WIDTH).min(1.0);
+    HouseLabel {
+        longitude: normalize(cusp + arc / 2.0),
+        font_size: (HOUSE_NUMBER_FONT_SIZE * scale).max(MIN_HOUSE_NUMBER_FONT_SIZE),
+    }
+}
+
+/// Rendering options chosen per request
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct SvgOptions {
+    /// Unicode text glyphs or vector paths
+    pub glyph_mode: GlyphMode,
+    /// Language for labels and tooltips
+    pub lang: Lang,
+    /// Full drawing or a smaller compact one
+    pub detail: SvgDetail,
+    /// Draw the second chart's house cusps as ticks outside the zodiac ring
+    pub show_second_houses: bool,
+    /// Zodiac by degree, or turned so each sign is a whole-sign house
+    pub wheel_style: WheelStyle,
+    /// Overlays drawn on a natal wheel; none by default
+    pub highlights: Highlights,
+    /// The whole chart, or only its planet and aspect groups
+    pub layers: SvgLayers,
+    /// Where to draw a legend of the bodies and angles; none by default
+    pub legend: Option<LegendPosition>,
+}
+
+/// Which side of the wheel the legend is drawn on; the canvas grows to make room
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum LegendPosition {
+    #[default]
+    Right,
+    Bottom,
+}
+
+impl std::fmt::Display for LegendPosition {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LegendPosition::Right => write!(f, "right"),
+            LegendPosition::Bottom => write!(f, "bottom"),
+        }
+    }
+}
+
+impl std::str::FromStr for LegendPosition {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "right" => Ok(LegendPosition::Right),
+            "bottom" => Ok(LegendPosition::Bottom),
+            _ => Err(format!("Invalid legend position: {} (expected \"right\" or \"bottom\")", s)),
+        }
+    }
+}
+
+/// One ring's entries in the legend: its bodies, and its angles when it has houses
+struct LegendColumn<'a> {
+    /// Ring name over the column; only bi-wheels have one
+    heading: Option<&'a str>,
+    /// "chart1", "chart2" or "transit", as for the planets drawn on the wheel
+    role: &'a str,
+    planets: &'a [PlanetInfo],
+    houses: &'a [HouseInfo],
+}
+
+impl LegendColumn<'_> {
+    // The angles, when there are twelve cusps to read them from
+    fn angles(&self) -> impl Iterator<Item = (&'static str, &'static str, f64)> + '_ {
+        LEGEND_ANGLES.iter().filter_map(move |&(name, abbreviation, cusp)| {
+            let house = self.houses.iter().find(|house| house.number == cusp)?;
+            (self.houses.len() == 12).then_some((name, abbreviation, house.longitude))
+        })
+    }
+
+    fn rows(&self) -> usize {
+        let angles = self.angles().count();
+        usize::from(self.heading.is_some()) + self.planets.len() + if angles > 0 { angles + 1 } else { 0 }
+    }
+}
+
+/// Which layers of a chart the SVG carries
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum SvgLayers {
+    #[default]
+    All,
+    /// Only the `planets` and `aspects` groups, without the wheel, houses or
+    /// labels, for clients that swap them into a chart they already show
+    Dynamic,
+}
+
+impl std::fmt::Display for SvgLayers {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SvgLayers::All => write!(f, "all"),
+            SvgLayers::Dynamic => write!(f, "dynamic"),
+        }
+    }
+}
+
+impl std::str::FromStr for SvgLayers {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "all" => Ok(SvgLayers::All),
+            "dynamic" => Ok(SvgLayers::Dynamic),
+            _ => Err(format!("Invalid SVG layers: {} (expected \"all\" or \"dynamic\")", s)),
+        }
+    }
+}
+
+/// Lowercase letters and digits with hyphens between words, for element ids.
+/// Ids are written straight into the document, so they don't allocate.
+struct Slug<'a>(&'a str);
+
+impl Display for Slug<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let (mut started, mut hyphen) = (false, false);
+        for c in self.0.chars() {
+            if c.is_ascii_alphanumeric() {
+                if hyphen {
+                    f.write_char('-')?;
+                    hyphen = false;
+                }
+                f.write_char(c.to_ascii_lowercase())?;
+                started = true;
+            } else {
+                hyphen = started;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A body in element ids: its key, e.g. "true-node", or its name for one that isn't a body
+struct BodyId<'a>(&'a str);
+
+impl Display for BodyId<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.0.parse::<Planet>() {
+            Ok(planet) => Slug(body_symbol(planet).key).fmt(f),
+            Err(_) => Slug(self.0).fmt(f),
+        }
+    }
+}
+
+/// Layer of the bodies drawn in a role ("chart1", "chart2" or "transit"), for
+/// element ids: the chart itself is "natal"
+fn id_layer(role: &str) -> &'static str {
+    match role {
+        "chart2" => "chart2",
+        "transit" => "transit",
+        _ => "natal",
+    }
+}
+
+/// One end of an aspect in element ids: the body, after its layer unless it is natal
+struct AspectEnd<'a> {
+    role: &'a str,
+    name: &'a str,
+}
+
+impl Display for AspectEnd<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match id_layer(self.role) {
+            "natal" => BodyId(self.name).fmt(f),
+            layer => write!(f, "{}-{}", layer, BodyId(self.name)),
+        }
+    }
+}
+
+/// Id of the aspect line between two ends, e.g. "aspect-sun-trine-moon"
+struct AspectId<'a>(AspectEnd<'a>, &'a str, AspectEnd<'a>);
+
+impl Display for AspectId<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "aspect-{}-{}-{}", self.0, Slug(self.1), self.2)
+    }
+}
+
+/// Optional overlays on a natal wheel
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Highlights {
+    /// Zodiac degrees marked with a tick into the zodiac ring
+    pub critical_degrees: DegreeSet,
+    /// Mark 0° of the cardinal signs, the Aries point axes, across the zodiac ring
+    pub aries_points: bool,
+    /// Ring the bodies in the chart's `angular` list
+    pub angular: bool,
+}
+
+/// A set of whole zodiac degrees, 0 to 359
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct DegreeSet([u64; 6]);
+
+impl DegreeSet {
+    /// The classical critical degrees: 0°, 13° and 26° of the cardinal signs,
+    /// 9° and 21° of the fixed signs and 4° and 17° of the mutable signs
+    pub fn classical() -> Self {
+        const BY_MODALITY: [&[u16]; 3] = [&[0, 13, 26], &[9, 21], &[4, 17]];
+        (0..12u16)
+            .flat_map(|sign| BY_MODALITY[usize::from(sign % 3)].iter().map(move |degree| sign * 30 + degree))
+            .collect()
+    }
+
+    /// Adds `degree`, taken modulo 360
+    pub fn insert(&mut self, degree: u16) {
+        let degree = degree % 360;
+        self.0[usize::from(degree / 64)] |= 1 << (degree % 64);
+    }
+
+    pub fn contains(&self, degree: u16) -> bool {
+        degree < 360 && self.0[usize::from(degree / 64)] & (1 << (degree % 64)) != 0
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.iter().all(|&word| word == 0)
+    }
+
+    /// The degrees in the set, from 0
+    pub fn iter(&self) -> impl Iterator<Item = u16> + '_ {
+        (0..360).filter(|&degree| self.contains(degree))
+    }
+}
+
+impl FromIterator<u16> for DegreeSet {
+    fn from_iter<I: IntoIterator<Item = u16>>(degrees: I) -> Self {
+        let mut set = DegreeSet::default();
+        degrees.into_iter().for_each(|degree| set.insert(degree));
+        set
+    }
+}
+
+/// How much detail the SVG carries
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum SvgDetail {
+    /// Degree labels under the planets and coordinates to two decimals
+    #[default]
+    Full,
+    /// No degree labels and coordinates to one decimal, for clients that limit body sizes
+    Compact,
+}
+
+impl SvgDetail {
+    /// Places coordinates are written to
+    pub fn decimals(self) -> u32 {
+        match self {
+            SvgDetail::Full => 2,
+            SvgDetail::Compact => 1,
+        }
+    }
+}
+
+impl std::fmt::Display for SvgDetail {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SvgDetail::Full => write!(f, "full"),
+            SvgDetail::Compact => write!(f, "compact"),
+        }
+    }
+}
+
+impl std::str::FromStr for SvgDetail {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "full" => Ok(SvgDetail::Full),
+            "compact" => Ok(SvgDetail::Compact),
+            _ => Err(format!("Invalid SVG detail: {} (expected \"full\" or \"compact\")", s)),
+        }
+    }
+}
+
+/// How the wheel is laid out
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum WheelStyle {
+    /// 0° Aries at the top and house cusps drawn at their longitudes
+    #[default]
+    Degree,
+    /// The rising sign's 0° at the left and each sign a house, numbered
+    /// counterclockwise beside its glyph, without separate cusp lines
+    WholeSign,
+}
+
+impl std::fmt::Display for WheelStyle {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            WheelStyle::Degree => write!(f, "degree"),
+            WheelStyle::WholeSign => write!(f, "whole_sign"),
+        }
+    }
+}
+
+impl std::str::FromStr for WheelStyle {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "degree" => Ok(WheelStyle::Degree),
+            "whole_sign" => Ok(WheelStyle::WholeSign),
+            _ => Err(format!("Invalid wheel style: {} (expected \"degree\" or \"whole_sign\")", s)),
+        }
+    }
+}
+
+/// Room a chart's SVG is written into, enough for a synastry chart without growing
+const CHART_CAPACITY: usize = 32 * 1024;
+
+/// Room a thumbnail's SVG is written into
+const THUMBNAIL_CAPACITY: usize = 4 * 1024;
+
+#[derive(Clone)]
 pub struct SVGChartGenerator {
     pub width: f64,
     pub height: f64,
     pub center_x: f64,
     pub center_y: f64,
     pub outer_radius: f64,
+    pub glyph_mode: GlyphMode,
+    pub lang: Lang,
+    pub detail: SvgDetail,
+    /// Start each chart from a copy of the cached static wheel instead of drawing it
+    pub reuse_wheel: bool,
+    /// Draw the second chart's house cusps as ticks outside the zodiac ring
+    pub show_second_houses: bool,
+    pub wheel_style: WheelStyle,
+    pub highlights: Highlights,
+    pub layers: SvgLayers,
+    /// Sign at the left of a whole-sign wheel, set from the chart being drawn
+    pub rising_sign: Option<Sign>,
+    /// Styles to draw with instead of the process-wide ones
+    pub styles: Option<Arc<ChartStyles>>,
+    pub legend: Option<LegendPosition>,
+    /// Top left of the legend, set once the canvas has grown to fit the chart's legend
+    pub legend_origin: Option<(f64, f64)>,
+}
+
+/// Everything the static wheel layers depend on besides the process-wide styles
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct WheelKey {
+    geometry: [u64; 5],
+    glyph_mode: GlyphMode,
+    lang: Lang,
+    detail: SvgDetail,
+    rising_sign: Option<Sign>,
+}
+
+// One entry per chart size, glyph mode, language and detail in use, so the map stays small
+fn wheel_cache() -> &'static Mutex<HashMap<WheelKey, SvgWriter>> {
+    static WHEEL_CACHE: OnceLock<Mutex<HashMap<WheelKey, SvgWriter>>> = OnceLock::new();
+    WHEEL_CACHE.get_or_init(|| Mutex::new(HashMap::new()))
 }
 
 impl Default for SVGChartGenerator {
@@ -29,6 +446,18 @@ impl Default for SVGChartGenerator {
             center_x: CENTER,
             center_y: CENTER,
             outer_radius: OUTER_RADIUS,
+            glyph_mode: GlyphMode::default(),
+            lang: Lang::default(),
+            detail: SvgDetail::default(),
+            reuse_wheel: true,
+            show_second_houses: false,
+            wheel_style: WheelStyle::default(),
+            highlights: Highlights::default(),
+            layers: SvgLayers::default(),
+            rising_sign: None,
+            styles: None,
+            legend: None,
+            legend_origin: None,
         }
     }
 }
@@ -38,6 +467,36 @@ impl SVGChartGenerator {
         Self::default()
     }
 
+    pub fn with_options(options: SvgOptions) -> Self {
+        Self {
+            glyph_mode: options.glyph_mode,
+            lang: options.lang,
+            detail: options.detail,
+            show_second_houses: options.show_second_houses,
+            wheel_style: options.wheel_style,
+            highlights: options.highlights,
+            layers: options.layers,
+            legend: options.legend,
+            ..Self::default()
+        }
+    }
+
+    /// Draws with `styles` rather than the process-wide styles. The cached
+    /// wheel was drawn with those, so it isn't reused.
+    pub fn with_styles(mut self, styles: ChartStyles) -> Self {
+        self.styles = Some(Arc::new(styles));
+        self.reuse_wheel = false;
+        self
+    }
+
+    // This generator's styles, else the process-wide ones
+    fn styles(&self) -> Result<&ChartStyles, String> {
+        match &self.styles {
+            Some(styles) => Ok(styles),
+            None => get_styles().ok_or_else(|| "Chart styles not initialized. chart_styles.json is required.".to_string()),
+        }
+    }
+
     // Traditional planetary order from center to edge
     fn get_planetary_order(&self) -> Vec<&str> {
         vec!["Sun", "Moon", "Mercury", "Venus", "Mars", "Jupiter", "Saturn", "Uranus", "Neptune", "Pluto"]
@@ -51,40 +510,13 @@ impl SVGChartGenerator {
             .unwrap_or(10) // Unknown planets go to outer edge
     }
 
-    // Group planets by proximity in longitude
+    // Group planets by proximity in longitude, a group around 0° Aries being one
     fn group_planets_by_proximity(&self, planets: &[PlanetInfo], threshold_degrees: f64) -> Vec<Vec<PlanetInfo>> {
-        let mut sorted_planets = planets.to_vec();
-        sorted_planets.sort_by(|a, b| a.longitude.partial_cmp(&b.longitude).unwrap());
-        
-        let mut groups = Vec::new();
-        let mut current_group = Vec::new();
-        
-        for planet in sorted_planets {
-            if current_group.is_empty() {
-                current_group.push(planet);
-            } else {
-                let last_planet = current_group.last().unwrap();
-                let mut longitude_diff = (planet.longitude - last_planet.longitude).abs();
-                
-                // Handle wrap-around at 0/360 degrees
-                if longitude_diff > 180.0 {
-                    longitude_diff = 360.0 - longitude_diff;
-                }
-                
-                if longitude_diff <= threshold_degrees {
-                    current_group.push(planet);
-                } else {
-                    groups.push(current_group);
-                    current_group = vec![planet];
-                }
-            }
-        }
-        
-        if !current_group.is_empty() {
-            groups.push(current_group);
-        }
-        
-        groups
+        let longitudes: Vec<f64> = planets.iter().map(|planet| planet.longitude).collect();
+        proximity_groups(&longitudes, threshold_degrees)
+            .into_iter()
+            .map(|group| group.into_iter().map(|i| planets[i].clone()).collect())
+            .collect()
     }
 
     // Calculate planet positions with radial ordering
@@ -108,9 +540,8 @@ impl SVGChartGenerator {
                 });
                 
                 // Calculate the center longitude for the group
-                let center_longitude = sorted_group.iter()
-                    .map(|p| p.longitude)
-                    .sum::<f64>() / sorted_group.len() as f64;
+                let longitudes: Vec<f64> = sorted_group.iter().map(|p| p.longitude).collect();
+                let center_longitude = cluster_center(&longitudes).unwrap_or_default();
                 
                 for (i, planet) in sorted_group.iter().enumerate() {
                     // Use different radius for each planet (closer to center = higher priority)
@@ -118,7 +549,7 @@ impl SVGChartGenerator {
                     
                     // Add angular offset to prevent overlap on same radial line
                     let angular_offset = (i as f64 - (sorted_group.len() - 1) as f64 / 2.0) * 2.0; // degrees
-                    let adjusted_longitude = center_longitude + angular_offset;
+                    let adjusted_longitude = normalize(center_longitude + angular_offset);
                     let angle = self.longitude_to_angle(adjusted_longitude);
                     
                     let (x, y) = self.calculate_position(angle, radius);
@@ -130,32 +561,68 @@ impl SVGChartGenerator {
         positions
     }
 
-    // Planet symbols using Unicode
-    fn get_planet_symbol(&self, planet_name: &str) -> &str {
-        match planet_name {
-            "Sun" => "☉",
-            "Moon" => "☽",
-            "Mercury" => "☿",
-            "Venus" => "♀",
-            "Mars" => "♂",
-            "Jupiter" => "♃",
-            "Saturn" => "♄",
-            "Uranus" => "♅",
-            "Neptune" => "♆",
-            "Pluto" => "♇",
-            _ => "?"
+    // Convert longitude to angle (0° Aries = top of chart)
+    fn longitude_to_angle(&self, longitude: f64) -> f64 {
+        match self.rising_sign {
+            // The rising sign's 0° at the left, later degrees counterclockwise
+            Some(rising) => (180.0 - normalize(longitude - rising as usize as f64 * 30.0)) * PI / 180.0,
+            // Subtract 90 degrees to make 0° Aries at top
+            None => (longitude - 90.0) * PI / 180.0,
+        }
+    }
+
+    // Angle of `degrees` into the `sign`th sign, worked out on a degree wheel
+    // as it always was so the ring's coordinates stay the same to the last digit
+    fn sign_angle(&self, sign: usize, degrees: f64) -> f64 {
+        match self.rising_sign {
+            Some(_) => self.longitude_to_angle(sign as f64 * 30.0 + degrees),
+            None => (sign as f64 * 30.0) * PI / 180.0 - PI / 2.0 + degrees * PI / 180.0,
         }
     }
 
-    // Zodiac signs
-    fn get_zodiac_signs(&self) -> [&str; 12] {
-        ["♈︎", "♉︎", "♊︎", "♋︎", "♌︎", "♍︎", "♎︎", "♏︎", "♐︎", "♑︎", "♒︎", "♓︎"]
+    /// A whole-sign generator turned to the chart's rising sign, the sign of
+    /// the first house cusp. `None` for a degree wheel, a chart without
+    /// houses, or a generator already turned.
+    fn oriented(&self, houses: &[HouseInfo]) -> Option<Self> {
+        if self.wheel_style != WheelStyle::WholeSign || self.rising_sign.is_some() {
+            return None;
+        }
+        let ascendant = houses.iter().find(|house| house.number == 1)?;
+        Some(Self {
+            rising_sign: Some(Sign::from_longitude(ascendant.longitude)),
+            ..self.clone()
+        })
     }
 
-    // Convert longitude to angle (0° Aries = top of chart)
-    fn longitude_to_angle(&self, longitude: f64) -> f64 {
-        // Subtract 90 degrees to make 0° Aries at top
-        (longitude - 90.0) * PI / 180.0
+    /// A copy with the canvas grown to fit the legend of `columns`, placed beside
+    /// or below the wheel. `None` when there's no legend, or room was already made.
+    fn with_legend_room(&self, columns: &[LegendColumn]) -> Option<Self> {
+        let position = self.legend?;
+        if self.legend_origin.is_some() {
+            return None;
+        }
+        let column_width = LEGEND_COLUMN_WIDTH * LEGEND_FONT_SIZE;
+        let legend_width = columns.len() as f64 * column_width + LEGEND_MARGIN;
+        let rows = columns.iter().map(LegendColumn::rows).max().unwrap_or(0);
+        let legend_height = rows as f64 * LEGEND_ROW_HEIGHT * LEGEND_FONT_SIZE + 2.0 * LEGEND_MARGIN;
+        let (width, height, origin) = match position {
+            LegendPosition::Right => (
+                self.width + legend_width,
+                self.height.max(legend_height),
+                (self.width, LEGEND_MARGIN),
+            ),
+            LegendPosition::Bottom => (
+                self.width.max(legend_width + LEGEND_MARGIN),
+                self.height + legend_height - LEGEND_MARGIN,
+                (LEGEND_MARGIN, self.height),
+            ),
+        };
+        Some(Self {
+            width,
+            height,
+            legend_origin: Some(origin),
+            ..self.clone()
+        })
     }
 
     // Calculate position on circle
@@ -165,377 +632,753 @@ impl SVGChartGenerator {
         (x, y)
     }
 
+    // Vector glyph centred on (x, y), scaled to roughly match a font of the given size
+    fn glyph_path<'w>(&self, doc: &'w mut SvgWriter, d: &str, x: f64, y: f64, font_size: f64, color: &str) -> Element<'w> {
+        let scale = font_size / GLYPH_BOX;
+        doc.element("path")
+            .attr("d", d)
+            .attr("transform", format_args!("translate({:.2} {:.2}) scale({})", x, y, scale))
+            .attr("fill", "none")
+            .attr("stroke", color)
+            .attr("stroke-width", 1.5)
+            .attr("stroke-linecap", "round")
+            .attr("stroke-linejoin", "round")
+    }
+
+    // Degree within the sign, e.g. 12°05'
+    fn format_degree(&self, longitude: f64) -> String {
+        let degree = (longitude % 30.0) as i32;
+        let minute = ((longitude % 1.0) * 60.0) as i32;
+        format!("{}°{:02}'", degree, minute)
+    }
+
+    // Hover text for the open element; compact charts leave it out
+    fn tooltip(&self, doc: &mut SvgWriter, text: impl std::fmt::Display) {
+        if self.detail == SvgDetail::Full {
+            doc.element("title").open();
+            doc.text(text);
+            doc.end();
+        }
+    }
+
+    // Planet symbol and degree label, with a localized tooltip
+    fn draw_planet_symbol(&self, doc: &mut SvgWriter, planet: &PlanetInfo, x: f64, y: f64, planet_color: &str, glyph: &str) {
+        let degree_text = self.format_degree(planet.longitude);
+        let sign = Sign::from_longitude(planet.longitude).to_string();
+        let tooltip = format!(
+            "{} {} {}",
+            self.lang.translate(&planet.name),
+            degree_text,
+            self.lang.translate(&sign)
+        );
+
+        match (self.glyph_mode, glyphs::planet_path(&planet.name)) {
+            (GlyphMode::Paths, Some(d)) => {
+                self.glyph_path(doc, d, x, y - 3.0, 16.0, planet_color).attr("class", "planet-glyph").open();
+                self.tooltip(doc, tooltip);
+            }
+            _ => {
+                doc.element("text")
+                    .attr("x", x)
+                    .attr("y", y - 3.0)
+                    .attr("text-anchor", "middle")
+                    .attr("dominant-baseline", "central")
+                    .attr("fill", planet_color)
+                    .attr("font-family", "serif")
+                    .attr("font-size", 16)
+                    .attr("class", "planet-glyph")
+                    .open();
+                self.tooltip(doc, tooltip);
+                doc.text(glyph);
+            }
+        }
+        doc.end();
+
+        if self.detail == SvgDetail::Compact {
+            return;
+        }
+        doc.element("text")
+            .attr("x", x)
+            .attr("y", y + 8.0)
+            .attr("text-anchor", "middle")
+            .attr("dominant-baseline", "central")
+            .attr("fill", planet_color)
+            .attr("font-family", "sans-serif")
+            .attr("font-size", 8)
+            .open();
+        doc.text(degree_text);
+        doc.end();
+    }
+
     // Create SVG document with background
-    pub fn create_svg_document(&self) -> Result<Document, String> {
-        let styles = get_styles().ok_or("Chart styles not initialized. chart_styles.json is required.")?;
-        let background_color = styles.get_chart_color("background");
-        
-        Ok(Document::new()
-            .set("viewBox", (0, 0, self.width as i32, self.height as i32))
-            .set("width", self.width)
-            .set("height", self.height)
-            .set("style", format!("background-color: {}", background_color))
-            .add(
-                Rectangle::new()
-                    .set("width", "100%")
-                    .set("height", "100%")
-                    .set("fill", background_color)
-            ))
+    pub fn create_svg_document(&self) -> Result<SvgWriter, String> {
+        let mut doc = self.svg_root()?;
+        doc.element("rect")
+            .attr("width", "100%")
+            .attr("height", "100%")
+            .attr("fill", self.styles()?.get_chart_color("background"))
+            .close();
+        Ok(doc)
+    }
+
+    // The open root element, with nothing drawn in it
+    fn svg_root(&self) -> Result<SvgWriter, String> {
+        let background_color = self.styles()?.get_chart_color("background");
+        let mut doc = SvgWriter::with_capacity(CHART_CAPACITY).decimals(self.detail.decimals());
+
+        doc.element("svg")
+            .attr("viewBox", format_args!("0 0 {} {}", self.width as i32, self.height as i32))
+            .attr("width", self.width)
+            .attr("height", self.height)
+            .attr("style", format_args!("background-color: {}", background_color))
+            .attr("xmlns", "http://www.w3.org/2000/svg")
+            .attr("role", "img")
+            .attr("aria-labelledby", "chart-title chart-desc")
+            .open();
+        Ok(doc)
+    }
+
+    // The document a chart is drawn into: the static wheel, or an empty root
+    // when only the dynamic layers are wanted
+    fn chart_document(&self) -> Result<SvgWriter, String> {
+        match self.layers {
+            SvgLayers::All => self.wheel(),
+            SvgLayers::Dynamic => self.svg_root(),
+        }
+    }
+
+    // Names the chart for screen readers: its localized type as the title, and
+    // its date labels as the description
+    fn describe(&self, doc: &mut SvgWriter, title: &str, labels: &[String]) {
+        doc.element("title").attr("id", "chart-title").open();
+        doc.text(self.lang.translate(title));
+        doc.end();
+        doc.element("desc").attr("id", "chart-desc").open();
+        doc.text(labels.join("; "));
+        doc.end();
+    }
+
+    // Whether the chart's static layers are drawn: houses, points, markers and labels
+    fn draws_static_layers(&self) -> bool {
+        self.layers == SvgLayers::All
+    }
+
+    // Draws into a group with the given id, which clients can swap on its own
+    fn layer(&self, doc: &mut SvgWriter, id: &str, draw: impl FnOnce(&mut SvgWriter) -> Result<(), String>) -> Result<(), String> {
+        doc.element("g").attr("id", id).open();
+        let drawn = draw(doc);
+        doc.end();
+        drawn
     }
 
     // Draw outer circle and zodiac wheel background
-    pub fn draw_chart_wheel_background(&self, doc: Document) -> Result<Document, String> {
-        let styles = get_styles().ok_or("Chart styles not initialized. chart_styles.json is required.")?;
+    pub fn draw_chart_wheel_background(&self, doc: &mut SvgWriter) -> Result<(), String> {
+        let styles = self.styles()?;
         
         // Outer circle
-        let outer_circle = Circle::new()
-            .set("cx", self.center_x)
-            .set("cy", self.center_y)
-            .set("r", self.outer_radius)
-            .set("fill", styles.get_chart_color("wheel_background"))
-            .set("stroke", styles.get_chart_color("chart_wheel_line"))
-            .set("stroke-width", 2);
+        doc.element("circle")
+            .attr("cx", self.center_x)
+            .attr("cy", self.center_y)
+            .attr("r", self.outer_radius)
+            .attr("fill", styles.get_chart_color("wheel_background"))
+            .attr("stroke", styles.get_chart_color("chart_wheel_line"))
+            .attr("stroke-width", 2)
+            .close();
 
         // Inner circle
-        let inner_circle = Circle::new()
-            .set("cx", self.center_x)
-            .set("cy", self.center_y)
-            .set("r", INNER_RADIUS)
-            .set("fill", "none")
-            .set("stroke", styles.get_chart_color("chart_wheel_line"))
-            .set("stroke-width", 1);
-
-        Ok(doc.add(outer_circle).add(inner_circle))
+        doc.element("circle")
+            .attr("cx", self.center_x)
+            .attr("cy", self.center_y)
+            .attr("r", INNER_RADIUS)
+            .attr("fill", "none")
+            .attr("stroke", styles.get_chart_color("chart_wheel_line"))
+            .attr("stroke-width", 1)
+            .close();
+
+        Ok(())
     }
 
     // Draw zodiac division lines with opacity
-    pub fn draw_zodiac_divisions(&self, doc: Document) -> Result<Document, String> {
-        let styles = get_styles().ok_or("Chart styles not initialized. chart_styles.json is required.")?;
-        let mut doc = doc;
-
-        // Draw zodiac divisions with 50% opacity
-        for i in 0..12 {
-            let angle = (i as f64 * 30.0) * PI / 180.0 - PI / 2.0;
+    pub fn draw_zodiac_divisions(&self, doc: &mut SvgWriter) -> Result<(), String> {
+        let styles = self.styles()?;
+
+        // Draw zodiac divisions with 50% opacity; compact charts keep only
+        // the cardinal ones at 0° Aries, Cancer, Libra and Capricorn. On a
+        // whole-sign wheel they are the house edges too, so all are drawn,
+        // across the houses to the centre.
+        let step = match (self.detail, self.rising_sign) {
+            (SvgDetail::Compact, None) => 3,
+            _ => 1,
+        };
+        let inner_radius = if self.rising_sign.is_some() { 0.0 } else { INNER_RADIUS };
+        for i in (0..12).step_by(step) {
+            let angle = self.sign_angle(i, 0.0);
             
             // Division lines with opacity
-            let (x1, y1) = self.calculate_position(angle, INNER_RADIUS);
+            let (x1, y1) = self.calculate_position(angle, inner_radius);
             let (x2, y2) = self.calculate_position(angle, self.outer_radius);
             
-            let line = Line::new()
-                .set("x1", x1)
-                .set("y1", y1)
-                .set("x2", x2)
-                .set("y2", y2)
-                .set("stroke", styles.get_chart_color("chart_wheel_line"))
-                .set("stroke-width", 1)
-                .set("opacity", 0.5);
-            
-            doc = doc.add(line);
+            doc.element("line")
+                .attr("x1", x1)
+                .attr("y1", y1)
+                .attr("x2", x2)
+                .attr("y2", y2)
+                .attr("stroke", styles.get_chart_color("chart_wheel_line"))
+                .attr("stroke-width", 1)
+                .attr("opacity", 0.5)
+                .close();
         }
 
-        Ok(doc)
+        Ok(())
     }
 
     // Draw zodiac signs text
-    pub fn draw_zodiac_signs(&self, doc: Document) -> Result<Document, String> {
-        let styles = get_styles().ok_or("Chart styles not initialized. chart_styles.json is required.")?;
-        let mut doc = doc;
-        let signs = self.get_zodiac_signs();
-
-        for i in 0..12 {
-            let angle = (i as f64 * 30.0) * PI / 180.0 - PI / 2.0;
-            
-            // Zodiac signs
-            let sign_angle = angle + (15.0 * PI / 180.0);
-            let sign_radius = (INNER_RADIUS + self.outer_radius) / 2.0;
+    pub fn draw_zodiac_signs(&self, doc: &mut SvgWriter) -> Result<(), String> {
+        let styles = self.styles()?;
+        for (i, sign) in Sign::ALL.into_iter().enumerate() {
+            // Zodiac signs, moved outward on a whole-sign wheel to make room for the house number
+            let sign_angle = self.sign_angle(i, 15.0);
+            let sign_radius = match self.rising_sign {
+                Some(_) => (INNER_RADIUS + 2.0 * self.outer_radius) / 3.0,
+                None => (INNER_RADIUS + self.outer_radius) / 2.0,
+            };
             let (sign_x, sign_y) = self.calculate_position(sign_angle, sign_radius);
             
-            let sign_text = Text::new()
-                .set("x", sign_x)
-                .set("y", sign_y)
-                .set("text-anchor", "middle")
-                .set("dominant-baseline", "central")
-                .set("fill", styles.get_chart_color("chart_text_color"))
-                .set("font-family", "serif")
-                .set("font-size", 18)
-                .add(TextNode::new(signs[i]));
-            
-            doc = doc.add(sign_text);
+            let color = styles.get_chart_color("chart_text_color");
+            let sign_name = sign.to_string();
+            let tooltip = self.lang.translate(&sign_name);
+
+            match self.glyph_mode {
+                GlyphMode::Paths => {
+                    self.glyph_path(doc, glyphs::sign_path(i), sign_x, sign_y, 18.0, color).attr("class", "sign-glyph").open();
+                    self.tooltip(doc, tooltip);
+                }
+                GlyphMode::Text => {
+                    doc.element("text")
+                        .attr("x", sign_x)
+                        .attr("y", sign_y)
+                        .attr("text-anchor", "middle")
+                        .attr("dominant-baseline", "central")
+                        .attr("fill", color)
+                        .attr("font-family", "serif")
+                        .attr("font-size", 18)
+                        .attr("class", "sign-glyph")
+                        .open();
+                    self.tooltip(doc, tooltip);
+                    doc.text(styles.sign_glyph(sign));
+                }
+            }
+            doc.end();
+
+            if let Some(rising) = self.rising_sign {
+                let house = (i + 12 - rising as usize) % 12 + 1;
+                let (num_x, num_y) = self.calculate_position(sign_angle, (2.0 * INNER_RADIUS + self.outer_radius) / 3.0);
+                doc.element("text")
+                    .attr("x", num_x)
+                    .attr("y", num_y)
+                    .attr("text-anchor", "middle")
+                    .attr("dominant-baseline", "central")
+                    .attr("fill", color)
+                    .attr("font-family", "sans-serif")
+                    .attr("font-size", HOUSE_NUMBER_FONT_SIZE)
+                    .attr("class", "sector-house")
+                    .open();
+                doc.text(house);
+                doc.end();
+            }
         }
 
+        Ok(())
+    }
+
+    // Draw the layers every chart shares: background, wheel, zodiac divisions and signs
+    pub fn draw_wheel(&self) -> Result<SvgWriter, String> {
+        let mut doc = self.create_svg_document()?;
+        self.draw_chart_wheel_background(&mut doc)?;
+        self.draw_zodiac_divisions(&mut doc)?;
+        self.draw_zodiac_signs(&mut doc)?;
+        Ok(doc)
+    }
+
+    // The static wheel, drawn once per size, glyph mode, language and detail and
+    // then copied into a document with room for the rest of the chart
+    fn wheel(&self) -> Result<SvgWriter, String> {
+        if !self.reuse_wheel {
+            return self.draw_wheel();
+        }
+        let key = WheelKey {
+            geometry: [self.width, self.height, self.center_x, self.center_y, self.outer_radius].map(f64::to_bits),
+            glyph_mode: self.glyph_mode,
+            lang: self.lang,
+            detail: self.detail,
+            rising_sign: self.rising_sign,
+        };
+        if let Some(doc) = wheel_cache().lock().ok().and_then(|cache| cache.get(&key).map(|doc| doc.copy_with_capacity(CHART_CAPACITY))) {
+            return Ok(doc);
+        }
+        let doc = self.draw_wheel()?;
+        if let Ok(mut cache) = wheel_cache().lock() {
+            cache.insert(key, doc.copy_with_capacity(0));
+        }
         Ok(doc)
     }
 
-    // Draw houses
-    pub fn draw_houses(&self, doc: Document, houses: &[HouseInfo]) -> Result<Document, String> {
-        let styles = get_styles().ok_or("Chart styles not initialized. chart_styles.json is required.")?;
-        let mut doc = doc;
+    // Draw houses, and the horizon and meridian when the chart has its angles
+    pub fn draw_houses(&self, doc: &mut SvgWriter, houses: &[HouseInfo], angles: Option<&AnglesInfo>) -> Result<(), String> {
+        let styles = self.styles()?;
+        if let Some(angles) = angles {
+            self.draw_axes(doc, angles)?;
+        }
+
+        // A whole-sign wheel's houses are its sign sectors, numbered in the zodiac ring
+        if self.rising_sign.is_some() {
+            return Ok(());
+        }
+
+        // 36 Gauquelin sectors get thinner lines than the usual 12 houses
+        let house_count = houses.len().max(12);
+        let cusp_width = if house_count > 12 { 0.5 } else { 1.0 };
 
         for house in houses {
             let angle = self.longitude_to_angle(house.longitude);
             
-            // House cusp lines with opacity
+            // House cusp lines with opacity; without the axes the Ascendant and
+            // Midheaven cusps stand out in their place
             let (x1, y1) = (self.center_x, self.center_y);
             let (x2, y2) = self.calculate_position(angle, INNER_RADIUS);
+            let is_angle = angles.is_none() && matches!(house.number, 1 | 10);
             
-            let line = Line::new()
-                .set("x1", x1)
-                .set("y1", y1)
-                .set("x2", x2)
-                .set("y2", y2)
-                .set("stroke", styles.get_chart_color("chart_wheel_line"))
-                .set("stroke-width", 1)
-                .set("opacity", 0.5);
-            
-            doc = doc.add(line);
-
-            // House numbers
+            doc.element("line")
+                .attr("x1", x1)
+                .attr("y1", y1)
+                .attr("x2", x2)
+                .attr("y2", y2)
+                .attr("stroke", styles.get_chart_color("chart_wheel_line"))
+                .attr("stroke-width", if is_angle { 2.5 } else { cusp_width })
+                .attr("opacity", if is_angle { 0.9 } else { 0.5 })
+                .attr("class", "house-cusp")
+                .attr("id", format_args!("cusp-{}", house.number))
+                .close();
+
+            // House numbers, centred in their own house
+            let next_cusp = houses
+                .iter()
+                .find(|h| h.number as usize == house.number as usize % house_count + 1)
+                .map(|h| h.longitude);
+            let label = house_label(house.longitude, next_cusp);
             let number_radius = INNER_RADIUS * 0.8;
-            let next_house_angle = angle + (15.0 * PI / 180.0);
-            let (num_x, num_y) = self.calculate_position(next_house_angle, number_radius);
+            let (num_x, num_y) = self.calculate_position(self.longitude_to_angle(label.longitude), number_radius);
             
-            let house_text = Text::new()
-                .set("x", num_x)
-                .set("y", num_y)
-                .set("text-anchor", "middle")
-                .set("dominant-baseline", "central")
-                .set("fill", styles.get_chart_color("chart_text_color"))
-                .set("font-family", "sans-serif")
-                .set("font-size", 12)
-                .add(TextNode::new(house.number.to_string()));
-            
-            doc = doc.add(house_text);
+            doc.element("text")
+                .attr("x", num_x)
+                .attr("y", num_y)
+                .attr("text-anchor", "middle")
+                .attr("dominant-baseline", "central")
+                .attr("fill", styles.get_chart_color("chart_text_color"))
+                .attr("font-family", "sans-serif")
+                .attr("font-size", label.font_size)
+                .attr("id", format_args!("house-{}", house.number))
+                .open();
+            doc.text(house.number);
+            doc.end();
         }
 
-        Ok(doc)
+        Ok(())
     }
 
-    // Draw planets with borders and degrees using radial positioning
-    pub fn draw_planets(&self, doc: Document, planets: &[PlanetInfo], border_type: &str) -> Result<Document, String> {
-        let styles = get_styles().ok_or("Chart styles not initialized. chart_styles.json is required.")?;
-        let mut doc = doc;
-        let positions = self.calculate_planet_positions(planets);
+    // The Ascendant-Descendant and Midheaven-IC axes right across the wheel,
+    // heavier than the cusps, which needn't fall on them
+    fn draw_axes(&self, doc: &mut SvgWriter, angles: &AnglesInfo) -> Result<(), String> {
+        let color = self.styles()?.get_chart_color("chart_wheel_line");
+        for (name, longitude) in [("ascendant", angles.ascendant), ("midheaven", angles.midheaven)] {
+            let (x1, y1) = self.calculate_position(self.longitude_to_angle(longitude + 180.0), INNER_RADIUS);
+            let (x2, y2) = self.calculate_position(self.longitude_to_angle(longitude), INNER_RADIUS);
+            doc.element("line")
+                .attr("x1", x1)
+                .attr("y1", y1)
+                .attr("x2", x2)
+                .attr("y2", y2)
+                .attr("stroke", color)
+                .attr("stroke-width", 2.5)
+                .attr("opacity", 0.9)
+                .attr("class", "angle-axis")
+                .attr("id", format_args!("axis-{}", name))
+                .close();
+        }
+        Ok(())
+    }
 
-        for planet in planets {
-            let (x, y) = positions.get(&planet.name).cloned().unwrap_or((self.center_x, self.center_y));
-            
-            // Planet border
-            let border_color = match border_type {
-                "chart1" => styles.get_chart_color("chart1_planet_border"),
-                "chart2" => styles.get_chart_color("chart2_planet_border"),
-                "transit" => styles.get_chart_color("transit_planet_border"),
-                _ => styles.get_chart_color("chart1_planet_border")
-            };
+    // The second chart's houses on a band just outside the zodiac ring: short, light
+    // ticks at the cusps and small numbers, so they can't be taken for the first
+    // chart's cusp lines
+    pub fn draw_second_houses(&self, doc: &mut SvgWriter, houses: &[HouseInfo]) -> Result<(), String> {
+        let styles = self.styles()?;
+        let color = styles.get_chart_color("chart2_planet_border");
+        let house_count = houses.len().max(12);
 
-            let border_style = match border_type {
-                "transit" => "stroke-dasharray: 3,3",
-                _ => ""
-            };
+        for house in houses {
+            let angle = self.longitude_to_angle(house.longitude);
+            let (x1, y1) = self.calculate_position(angle, self.outer_radius);
+            let (x2, y2) = self.calculate_position(angle, self.outer_radius + SECOND_HOUSE_TICK);
+            let is_angle = matches!(house.number, 1 | 10);
+
+            doc.element("line")
+                .attr("x1", x1)
+                .attr("y1", y1)
+                .attr("x2", x2)
+                .attr("y2", y2)
+                .attr("stroke", color)
+                .attr("stroke-width", if is_angle { 1.5 } else { 0.75 })
+                .attr("opacity", if is_angle { 0.8 } else { 0.5 })
+                .attr("class", "second-house-cusp")
+                .attr("id", format_args!("cusp-chart2-{}", house.number))
+                .close();
+
+            let next_cusp = houses
+                .iter()
+                .find(|h| h.number as usize == house.number as usize % house_count + 1)
+                .map(|h| h.longitude);
+            let label = house_label(house.longitude, next_cusp);
+            let font_size = label.font_size * SECOND_HOUSE_NUMBER_FONT_SIZE / HOUSE_NUMBER_FONT_SIZE;
+            let (num_x, num_y) = self.calculate_position(
+                self.longitude_to_angle(label.longitude),
+                self.outer_radius + SECOND_HOUSE_TICK / 2.0,
+            );
+
+            doc.element("text")
+                .attr("x", num_x)
+                .attr("y", num_y)
+                .attr("text-anchor", "middle")
+                .attr("dominant-baseline", "central")
+                .attr("fill", color)
+                .attr("opacity", 0.7)
+                .attr("id", format_args!("house-chart2-{}", house.number))
+                .attr("font-family", "sans-serif")
+                .attr("font-size", font_size)
+                .attr("class", "second-house-number")
+                .open();
+            doc.text(house.number);
+            doc.end();
+        }
 
-            let planet_border = Rectangle::new()
-                .set("x", x - 15.0)
-                .set("y", y - 15.0)
-                .set("width", 30)
-                .set("height", 30)
-                .set("fill", "none")
-                .set("stroke", border_color)
-                .set("stroke-width", 1)
-                .set("style", border_style);
-
-            if border_type == "chart2" {
-                // Circle border for chart2
-                let circle_border = Circle::new()
-                    .set("cx", x)
-                    .set("cy", y)
-                    .set("r", 15)
-                    .set("fill", "none")
-                    .set("stroke", border_color)
-                    .set("stroke-width", 1);
-                doc = doc.add(circle_border);
-            } else {
-                doc = doc.add(planet_border);
-            }
+        Ok(())
+    }
 
-            // Planet symbol
-            let planet_color = styles.get_planet_color(&planet.name);
-            let symbol = self.get_planet_symbol(&planet.name);
-            
-            let planet_text = Text::new()
-                .set("x", x)
-                .set("y", y - 3.0)
-                .set("text-anchor", "middle")
-                .set("dominant-baseline", "central")
-                .set("fill", planet_color)
-                .set("font-family", "serif")
-                .set("font-size", 16)
-                .add(TextNode::new(symbol));
-            
-            doc = doc.add(planet_text);
+    // Angle points as short labels ("Vx", "EP") just inside the house ring, with a tick at their longitude
+    pub fn draw_angle_points(&self, doc: &mut SvgWriter, points: &[AnglePointInfo], border_type: &str) -> Result<(), String> {
+        let styles = self.styles()?;
+        let color = match border_type {
+            "chart2" => styles.get_chart_color("chart2_planet_border"),
+            _ => styles.get_chart_color("chart1_planet_border"),
+        };
 
-            // Degree information
-            let degree = (planet.longitude % 30.0) as i32;
-            let minute = ((planet.longitude % 1.0) * 60.0) as i32;
-            let degree_text = format!("{}°{:02}'", degree, minute);
-            
-            let degree_label = Text::new()
-                .set("x", x)
-                .set("y", y + 8.0)
-                .set("text-anchor", "middle")
-                .set("dominant-baseline", "central")
-                .set("fill", planet_color)
-                .set("font-family", "sans-serif")
-                .set("font-size", 8)
-                .add(TextNode::new(degree_text));
-            
-            doc = doc.add(degree_label);
+        for point in points {
+            let angle = self.longitude_to_angle(point.longitude);
+            let (x1, y1) = self.calculate_position(angle, INNER_RADIUS);
+            let (x2, y2) = self.calculate_position(angle, INNER_RADIUS - 6.0);
+            let (label_x, label_y) = self.calculate_position(angle, INNER_RADIUS - 14.0);
+
+            doc.element("line")
+                .attr("x1", x1)
+                .attr("y1", y1)
+                .attr("x2", x2)
+                .attr("y2", y2)
+                .attr("stroke", color)
+                .attr("stroke-width", 1)
+                .close();
+            doc.element("text")
+                .attr("x", label_x)
+                .attr("y", label_y)
+                .attr("text-anchor", "middle")
+                .attr("dominant-baseline", "central")
+                .attr("fill", color)
+                .attr("font-family", "sans-serif")
+                .attr("font-size", 9)
+                .attr("class", "angle-point")
+                .attr("id", format_args!("point-{}-{}", id_layer(border_type), Slug(&point.name)))
+                .open();
+            self.tooltip(
+                doc,
+                format_args!(
+                    "{} {} {}",
+                    self.lang.translate(&point.name),
+                    self.format_degree(point.longitude),
+                    self.lang.translate(&Sign::from_longitude(point.longitude).to_string())
+                ),
+            );
+            doc.text(&point.label);
+            doc.end();
         }
 
-        Ok(doc)
+        Ok(())
     }
 
-    // Draw planets with custom positioning (for synastry charts)
-    pub fn draw_planets_with_positions(&self, doc: Document, planets: &[PlanetInfo], positions: &std::collections::HashMap<String, (f64, f64)>, border_type: &str) -> Result<Document, String> {
-        let styles = get_styles().ok_or("Chart styles not initialized. chart_styles.json is required.")?;
-        let mut doc = doc;
+    // A planet's glyph box: a square, or a circle for the second chart
+    fn draw_planet_border(&self, doc: &mut SvgWriter, x: f64, y: f64, border_type: &str) -> Result<(), String> {
+        let styles = self.styles()?;
+        let border_color = match border_type {
+            "chart1" => styles.get_chart_color("chart1_planet_border"),
+            "chart2" => styles.get_chart_color("chart2_planet_border"),
+            "transit" => styles.get_chart_color("transit_planet_border"),
+            _ => styles.get_chart_color("chart1_planet_border")
+        };
+
+        let border_style = match border_type {
+            "transit" => "stroke-dasharray: 3,3",
+            _ => ""
+        };
+
+        if border_type == "chart2" {
+            // Circle border for chart2
+            doc.element("circle")
+                .attr("cx", x)
+                .attr("cy", y)
+                .attr("r", 15)
+                .attr("fill", "none")
+                .attr("stroke", border_color)
+                .attr("stroke-width", 1)
+                .close();
+        } else {
+            doc.element("rect")
+                .attr("x", x - 15.0)
+                .attr("y", y - 15.0)
+                .attr("width", 30)
+                .attr("height", 30)
+                .attr("fill", "none")
+                .attr("stroke", border_color)
+                .attr("stroke-width", 1)
+                .attr("style", border_style)
+                .close();
+        }
 
-        for planet in planets {
-            let (x, y) = positions.get(&planet.name).cloned().unwrap_or((self.center_x, self.center_y));
-            
-            // Planet border
-            let border_color = match border_type {
-                "chart1" => styles.get_chart_color("chart1_planet_border"),
-                "chart2" => styles.get_chart_color("chart2_planet_border"),
-                "transit" => styles.get_chart_color("transit_planet_border"),
-                _ => styles.get_chart_color("chart1_planet_border")
-            };
+        Ok(())
+    }
 
-            let border_style = match border_type {
-                "transit" => "stroke-dasharray: 3,3",
-                _ => ""
-            };
+    // Draw planets with borders and degrees using radial positioning
+    pub fn draw_planets(&self, doc: &mut SvgWriter, planets: &[PlanetInfo], border_type: &str) -> Result<(), String> {
+        let positions = self.calculate_planet_positions(planets);
+        self.draw_planets_with_positions(doc, planets, &positions, border_type)
+    }
 
-            let planet_border = Rectangle::new()
-                .set("x", x - 15.0)
-                .set("y", y - 15.0)
-                .set("width", 30)
-                .set("height", 30)
-                .set("fill", "none")
-                .set("stroke", border_color)
-                .set("stroke-width", 1)
-                .set("style", border_style);
-
-            if border_type == "chart2" {
-                // Circle border for chart2
-                let circle_border = Circle::new()
-                    .set("cx", x)
-                    .set("cy", y)
-                    .set("r", 15)
-                    .set("fill", "none")
-                    .set("stroke", border_color)
-                    .set("stroke-width", 1);
-                doc = doc.add(circle_border);
-            } else {
-                doc = doc.add(planet_border);
+    // A small triangle on the top right corner of each glyph box whose body is
+    // near a sign boundary or house cusp, once per body
+    pub fn draw_boundary_markers(&self, doc: &mut SvgWriter, warnings: &[BoundaryWarningInfo], positions: &HashMap<String, (f64, f64)>) -> Result<(), String> {
+        let styles = self.styles()?;
+        let color = styles.get_chart_color("boundary_warning");
+        let mut marked = Vec::new();
+
+        for warning in warnings {
+            let Some(&(x, y)) = positions.get(&warning.body) else { continue };
+            if marked.contains(&warning.body) {
+                continue;
             }
+            marked.push(warning.body.clone());
+            let across = match (&warning.adjacent_sign, warning.adjacent_house) {
+                (Some(sign), _) => self.lang.translate(sign).to_string(),
+                (None, Some(house)) => format!("{} {}", self.lang.translate("House"), house),
+                (None, None) => continue,
+            };
 
-            // Planet symbol
-            let planet_color = styles.get_planet_color(&planet.name);
-            let symbol = self.get_planet_symbol(&planet.name);
-            
-            let planet_text = Text::new()
-                .set("x", x)
-                .set("y", y - 3.0)
-                .set("text-anchor", "middle")
-                .set("dominant-baseline", "central")
-                .set("fill", planet_color)
-                .set("font-family", "serif")
-                .set("font-size", 16)
-                .add(TextNode::new(symbol));
-            
-            doc = doc.add(planet_text);
-
-            // Degree information
-            let degree = (planet.longitude % 30.0) as i32;
-            let minute = ((planet.longitude % 1.0) * 60.0) as i32;
-            let degree_text = format!("{}°{:02}'", degree, minute);
-            
-            let degree_label = Text::new()
-                .set("x", x)
-                .set("y", y + 8.0)
-                .set("text-anchor", "middle")
-                .set("dominant-baseline", "central")
-                .set("fill", planet_color)
-                .set("font-family", "sans-serif")
-                .set("font-size", 8)
-                .add(TextNode::new(degree_text));
-            
-            doc = doc.add(degree_label);
+            let (left, top) = (x + 9.0, y - 15.0);
+            doc.element("polygon")
+                .attr("points", format_args!("{:.2},{:.2} {:.2},{:.2} {:.2},{:.2}", left, top, x + 15.0, top, x + 15.0, top + 6.0))
+                .attr("fill", color)
+                .attr("class", "boundary-warning")
+                .attr("id", format_args!("boundary-{}", BodyId(&warning.body)))
+                .open();
+            self.tooltip(doc, format_args!("{} {}", self.lang.translate("Near"), across));
+            doc.end();
         }
 
-        Ok(doc)
+        Ok(())
     }
 
-    // Draw aspects using radial positioning with chart-specific colors
-    pub fn draw_aspects_for_chart(&self, doc: Document, aspects: &[AspectInfo], planets: &[PlanetInfo], line_style: &str, chart_type: &str) -> Result<Document, String> {
-        let styles = get_styles().ok_or("Chart styles not initialized. chart_styles.json is required.")?;
-        let mut doc = doc;
+    // Ticks at the highlighted critical degrees and lines across the zodiac ring
+    // at the Aries point axes, placed by longitude so they turn with the wheel
+    pub fn draw_degree_markers(&self, doc: &mut SvgWriter) -> Result<(), String> {
+        let styles = self.styles()?;
 
-        // Get planet positions using radial positioning
-        let planet_positions = self.calculate_planet_positions(planets);
+        let color = styles.get_chart_color("critical_degree");
+        for degree in self.highlights.critical_degrees.iter() {
+            let longitude = f64::from(degree);
+            let angle = self.longitude_to_angle(longitude);
+            let (x1, y1) = self.calculate_position(angle, INNER_RADIUS);
+            let (x2, y2) = self.calculate_position(angle, INNER_RADIUS + CRITICAL_DEGREE_TICK);
+            doc.element("line")
+                .attr("x1", x1)
+                .attr("y1", y1)
+                .attr("x2", x2)
+                .attr("y2", y2)
+                .attr("stroke", color)
+                .attr("stroke-width", 2)
+                .attr("class", "critical-degree")
+                .open();
+            self.tooltip(
+                doc,
+                format_args!(
+                    "{} {}° {}",
+                    self.lang.translate("Critical degree"),
+                    degree % 30,
+                    self.lang.translate(&Sign::from_longitude(longitude).to_string())
+                ),
+            );
+            doc.end();
+        }
 
-        for aspect in aspects {
-            // Strip prefixes from planet names for lookup
-            let planet1_name = aspect.planet1.replace("Natal ", "").replace("Transit ", "");
-            let planet2_name = aspect.planet2.replace("Natal ", "").replace("Transit ", "");
-            
-            if let (Some((x1, y1)), Some((x2, y2))) = (
-                planet_positions.get(&planet1_name).cloned(),
-                planet_positions.get(&planet2_name).cloned()
-            ) {
-                let color = match chart_type {
-                    "chart1" => styles.get_chart1_aspect_color(&aspect.aspect),
-                    "chart2" => styles.get_chart2_aspect_color(&aspect.aspect),
-                    "synastry" => styles.get_synastry_aspect_color(&aspect.aspect),
-                    _ => styles.get_aspect_color(&aspect.aspect)
-                };
-                
-                let stroke_style = match line_style {
-                    "dotted" => "stroke-dasharray: 2,2",
-                    "long_dotted" => "stroke-dasharray: 5,5",
-                    _ => ""
-                };
+        if !self.highlights.aries_points {
+            return Ok(());
+        }
+        let color = styles.get_chart_color("aries_point");
+        for sign in [Sign::Aries, Sign::Cancer, Sign::Libra, Sign::Capricorn] {
+            let angle = self.longitude_to_angle(sign as usize as f64 * 30.0);
+            let (x1, y1) = self.calculate_position(angle, INNER_RADIUS);
+            let (x2, y2) = self.calculate_position(angle, self.outer_radius);
+            doc.element("line")
+                .attr("x1", x1)
+                .attr("y1", y1)
+                .attr("x2", x2)
+                .attr("y2", y2)
+                .attr("stroke", color)
+                .attr("stroke-width", 2)
+                .attr("stroke-dasharray", "4,3")
+                .attr("class", "aries-point")
+                .open();
+            self.tooltip(doc, format_args!("{} 0° {}", self.lang.translate("Aries point"), self.lang.translate(&sign.to_string())));
+            doc.end();
+        }
 
-                let line = Line::new()
-                    .set("x1", x1)
-                    .set("y1", y1)
-                    .set("x2", x2)
-                    .set("y2", y2)
-                    .set("stroke", color)
-                    .set("stroke-width", 1)
-                    .set("opacity", 0.7)
-                    .set("style", stroke_style);
-                
-                doc = doc.add(line);
+        Ok(())
+    }
+
+    // A ring around the glyph box of each body on an angle, in a group naming
+    // the body, once per body
+    pub fn draw_angular_highlights(&self, doc: &mut SvgWriter, angular: &[AspectInfo], positions: &HashMap<String, (f64, f64)>) -> Result<(), String> {
+        if !self.highlights.angular {
+            return Ok(());
+        }
+        let color = self.styles()?.get_chart_color("angular_highlight");
+        let mut ringed = Vec::new();
+
+        for conjunction in angular {
+            let Some(&(x, y)) = positions.get(&conjunction.planet1) else { continue };
+            if ringed.contains(&conjunction.planet1) {
+                continue;
             }
+            ringed.push(conjunction.planet1.clone());
+
+            doc.element("g")
+                .attr("class", "angular-highlight")
+                .attr("data-planet", &conjunction.planet1)
+                .attr("id", format_args!("angular-{}", BodyId(&conjunction.planet1)))
+                .open();
+            doc.element("circle")
+                .attr("cx", x)
+                .attr("cy", y)
+                .attr("r", ANGULAR_RING_RADIUS)
+                .attr("fill", "none")
+                .attr("stroke", color)
+                .attr("stroke-width", 2)
+                .attr("data-highlight", "angular")
+                .open();
+            self.tooltip(
+                doc,
+                format_args!(
+                    "{} {} {}",
+                    self.lang.translate(&conjunction.planet1),
+                    self.lang.translate("on the"),
+                    self.lang.translate(&conjunction.planet2)
+                ),
+            );
+            doc.end();
+            doc.end();
         }
 
-        Ok(doc)
+        Ok(())
+    }
+
+    // Draw planets with custom positioning (for synastry charts)
+    pub fn draw_planets_with_positions(&self, doc: &mut SvgWriter, planets: &[PlanetInfo], positions: &std::collections::HashMap<String, (f64, f64)>, border_type: &str) -> Result<(), String> {
+        let styles = self.styles()?;
+
+        // Each planet is a group moved into place, so a client can move it to its next position
+        for planet in planets {
+            let (x, y) = positions.get(&planet.name).cloned().unwrap_or((self.center_x, self.center_y));
+            doc.element("g")
+                .attr("id", format_args!("planet-{}-{}", id_layer(border_type), BodyId(&planet.name)))
+                .attr("class", "planet")
+                .attr("transform", format_args!("translate({} {})", x, y))
+                .open();
+            self.draw_planet_border(doc, 0.0, 0.0, border_type)?;
+
+            // Planet symbol and degree information
+            let planet_color = styles.get_planet_color(&planet.name);
+            self.draw_planet_symbol(doc, planet, 0.0, 0.0, planet_color, styles.planet_glyph(&planet.name));
+            doc.end();
+        }
+
+        Ok(())
+    }
+
+    // An aspect line between two positions, as wide and opaque as the aspect
+    // is strong, with a marker at its midpoint when it is exact
+    fn draw_aspect_line(
+        &self,
+        doc: &mut SvgWriter,
+        id: impl Display,
+        ((x1, y1), (x2, y2)): ((f64, f64), (f64, f64)),
+        color: &str,
+        stroke_style: &str,
+        (aspect, orb, strength): (&str, f64, f64),
+    ) -> Result<(), String> {
+        let lines = &self.styles()?.aspect_lines;
+        let stroke = lines.stroke(aspect, strength);
+        doc.element("g").attr("id", id).attr("class", "aspect").open();
+        let mut line = doc
+            .element("line")
+            .attr("x1", x1)
+            .attr("y1", y1)
+            .attr("x2", x2)
+            .attr("y2", y2)
+            .attr("stroke", color)
+            .attr("stroke-width", stroke.width)
+            .attr("opacity", stroke.opacity)
+            .attr("style", stroke_style);
+        if let Some(dasharray) = stroke.dasharray {
+            line = line.attr("stroke-dasharray", dasharray);
+        }
+        line.close();
+
+        if lines.is_exact(orb) {
+            doc.element("circle")
+                .attr("class", "exact-aspect")
+                .attr("cx", (x1 + x2) / 2.0)
+                .attr("cy", (y1 + y2) / 2.0)
+                .attr("r", lines.exact_marker_radius)
+                .attr("fill", color)
+                .attr("opacity", stroke.opacity)
+                .close();
+        }
+        doc.end();
+        Ok(())
+    }
+
+    // Draw aspects using radial positioning with chart-specific colors
+    pub fn draw_aspects_for_chart(&self, doc: &mut SvgWriter, aspects: &[AspectInfo], planets: &[PlanetInfo], line_style: &str, chart_type: &str) -> Result<(), String> {
+        // Get planet positions using radial positioning
+        let planet_positions = self.calculate_planet_positions(planets);
+        self.draw_aspects_with_positions_for_chart(doc, aspects, planets, &planet_positions, line_style, chart_type)
     }
 
     // Draw aspects using custom positioning with chart-specific colors
-    pub fn draw_aspects_with_positions_for_chart(&self, doc: Document, aspects: &[AspectInfo], _planets: &[PlanetInfo], positions: &std::collections::HashMap<String, (f64, f64)>, line_style: &str, chart_type: &str) -> Result<Document, String> {
-        let styles = get_styles().ok_or("Chart styles not initialized. chart_styles.json is required.")?;
-        let mut doc = doc;
+    pub fn draw_aspects_with_positions_for_chart(&self, doc: &mut SvgWriter, aspects: &[AspectInfo], _planets: &[PlanetInfo], positions: &std::collections::HashMap<String, (f64, f64)>, line_style: &str, chart_type: &str) -> Result<(), String> {
+        let styles = self.styles()?;
 
         for aspect in aspects {
             // Strip prefixes from planet names for lookup
             let planet1_name = aspect.planet1.replace("Natal ", "").replace("Transit ", "");
             let planet2_name = aspect.planet2.replace("Natal ", "").replace("Transit ", "");
             
-            if let (Some((x1, y1)), Some((x2, y2))) = (
-                positions.get(&planet1_name).cloned(),
-                positions.get(&planet2_name).cloned()
-            ) {
+            if let (Some(&from), Some(&to)) = (positions.get(&planet1_name), positions.get(&planet2_name)) {
                 let color = match chart_type {
                     "chart1" => styles.get_chart1_aspect_color(&aspect.aspect),
                     "chart2" => styles.get_chart2_aspect_color(&aspect.aspect),
@@ -549,42 +1392,36 @@ impl SVGChartGenerator {
                     _ => ""
                 };
 
-                let line = Line::new()
-                    .set("x1", x1)
-                    .set("y1", y1)
-                    .set("x2", x2)
-                    .set("y2", y2)
-                    .set("stroke", color)
-                    .set("stroke-width", 1)
-                    .set("opacity", 0.7)
-                    .set("style", stroke_style);
-                
-                doc = doc.add(line);
+                let id = AspectId(
+                    AspectEnd { role: chart_type, name: &planet1_name },
+                    &aspect.aspect,
+                    AspectEnd { role: chart_type, name: &planet2_name },
+                );
+                self.draw_aspect_line(doc, id, (from, to), color, stroke_style, (&aspect.aspect, aspect.orb, aspect.strength))?;
             }
         }
 
-        Ok(doc)
+        Ok(())
     }
 
     // Backward compatibility: Draw aspects using radial positioning (uses default colors)
-    pub fn draw_aspects(&self, doc: Document, aspects: &[AspectInfo], planets: &[PlanetInfo], line_style: &str) -> Result<Document, String> {
+    pub fn draw_aspects(&self, doc: &mut SvgWriter, aspects: &[AspectInfo], planets: &[PlanetInfo], line_style: &str) -> Result<(), String> {
         self.draw_aspects_for_chart(doc, aspects, planets, line_style, "default")
     }
 
     // Backward compatibility: Draw aspects using custom positioning (uses default colors)
-    pub fn draw_aspects_with_positions(&self, doc: Document, aspects: &[AspectInfo], planets: &[PlanetInfo], positions: &std::collections::HashMap<String, (f64, f64)>, line_style: &str) -> Result<Document, String> {
+    pub fn draw_aspects_with_positions(&self, doc: &mut SvgWriter, aspects: &[AspectInfo], planets: &[PlanetInfo], positions: &std::collections::HashMap<String, (f64, f64)>, line_style: &str) -> Result<(), String> {
         self.draw_aspects_with_positions_for_chart(doc, aspects, planets, positions, line_style, "default")
     }
 
     // Format date for display
     fn format_date(&self, date: &DateTime<Utc>) -> String {
-        date.format("%Y-%m-%d %H:%M").to_string()
+        self.lang.format_date(date)
     }
 
     // Draw date labels in upper left corner
-    fn draw_date_labels(&self, doc: Document, labels: Vec<String>) -> Result<Document, String> {
-        let styles = get_styles().ok_or("Chart styles not initialized. chart_styles.json is required.")?;
-        let mut doc = doc;
+    fn draw_date_labels(&self, doc: &mut SvgWriter, labels: Vec<String>) -> Result<(), String> {
+        let styles = self.styles()?;
         
         let start_y = 25.0;
         let line_height = 20.0;
@@ -592,37 +1429,166 @@ impl SVGChartGenerator {
         for (i, label) in labels.iter().enumerate() {
             let y_position = start_y + (i as f64 * line_height);
             
-            let date_text = Text::new()
-                .set("x", 20)
-                .set("y", y_position)
-                .set("fill", styles.get_chart_color("date_label_color"))
-                .set("font-family", "sans-serif")
-                .set("font-size", 14)
-                .set("font-weight", "bold")
-                .add(TextNode::new(label));
-            
-            doc = doc.add(date_text);
+            doc.element("text")
+                .attr("x", 20)
+                .attr("y", y_position)
+                .attr("fill", styles.get_chart_color("date_label_color"))
+                .attr("font-family", "sans-serif")
+                .attr("font-size", 14)
+                .attr("font-weight", "bold")
+                .attr("id", format_args!("date-{}", i + 1))
+                .open();
+            doc.text(label);
+            doc.end();
         }
         
-        Ok(doc)
+        Ok(())
+    }
+
+    // The legend: a column per ring, one row per body with its glyph, name,
+    // position, house and a retrograde marker, then the ring's angles
+    fn draw_legend(&self, doc: &mut SvgWriter, columns: &[LegendColumn]) -> Result<(), String> {
+        let Some((left, top)) = self.legend_origin else {
+            return Ok(());
+        };
+        let styles = self.styles()?;
+        let text_color = styles.get_chart_color("chart_text_color");
+        let row_height = LEGEND_ROW_HEIGHT * LEGEND_FONT_SIZE;
+        let glyph_size = LEGEND_FONT_SIZE * 4.0 / 3.0;
+        // Offsets of the name, position, house and retrograde marker from the glyph
+        let [name_x, position_x, house_x, motion_x] = [1.5, 10.5, 21.0, 22.5].map(|em| em * LEGEND_FONT_SIZE);
+        let text = |doc: &mut SvgWriter, x: f64, y: f64, color: &str, content: &dyn Display| {
+            doc.element("text")
+                .attr("x", x)
+                .attr("y", y)
+                .attr("dominant-baseline", "central")
+                .attr("fill", color)
+                .attr("font-family", "sans-serif")
+                .attr("font-size", LEGEND_FONT_SIZE)
+                .open();
+            doc.text(content);
+            doc.end();
+        };
+
+        self.layer(doc, "legend", |doc| {
+            for (i, column) in columns.iter().enumerate() {
+                let x = left + LEGEND_MARGIN + i as f64 * LEGEND_COLUMN_WIDTH * LEGEND_FONT_SIZE;
+                let mut y = top + row_height / 2.0;
+                let layer = id_layer(column.role);
+                if let Some(heading) = column.heading {
+                    let color = match column.role {
+                        "chart2" => styles.get_chart_color("chart2_planet_border"),
+                        "transit" => styles.get_chart_color("transit_planet_border"),
+                        _ => styles.get_chart_color("chart1_planet_border"),
+                    };
+                    doc.element("g").attr("class", "legend-heading").open();
+                    text(doc, x, y, color, &self.lang.translate(heading));
+                    doc.end();
+                    y += row_height;
+                }
+
+                for planet in column.planets {
+                    let color = styles.get_planet_color(&planet.name);
+                    doc.element("g")
+                        .attr("id", format_args!("legend-{}-{}", layer, BodyId(&planet.name)))
+                        .attr("class", "legend-body")
+                        .open();
+                    match (self.glyph_mode, glyphs::planet_path(&planet.name)) {
+                        (GlyphMode::Paths, Some(d)) => {
+                            self.glyph_path(doc, d, x + glyph_size / 2.0, y, glyph_size, color).close();
+                        }
+                        _ => {
+                            doc.element("text")
+                                .attr("x", x + glyph_size / 2.0)
+                                .attr("y", y)
+                                .attr("text-anchor", "middle")
+                                .attr("dominant-baseline", "central")
+                                .attr("fill", color)
+                                .attr("font-family", "serif")
+                                .attr("font-size", glyph_size)
+                                .open();
+                            doc.text(styles.planet_glyph(&planet.name));
+                            doc.end();
+                        }
+                    }
+                    text(doc, x + name_x, y, text_color, &self.lang.translate(&planet.name));
+                    text(doc, x + position_x, y, text_color, &self.lang.format_position(planet.longitude));
+                    if let Some(house) = planet.house {
+                        text(doc, x + house_x, y, text_color, &house);
+                    }
+                    if planet.is_retrograde {
+                        text(doc, x + motion_x, y, color, &"℞");
+                    }
+                    doc.end();
+                    y += row_height;
+                }
+
+                // The angles follow after a blank row
+                y += row_height;
+                for (name, abbreviation, longitude) in column.angles() {
+                    doc.element("g")
+                        .attr("id", format_args!("legend-{}-{}", layer, Slug(name)))
+                        .attr("class", "legend-angle")
+                        .open();
+                    text(doc, x, y, text_color, &abbreviation);
+                    text(doc, x + name_x, y, text_color, &self.lang.translate(name));
+                    text(doc, x + position_x, y, text_color, &self.lang.format_position(longitude));
+                    doc.end();
+                    y += row_height;
+                }
+            }
+            Ok(())
+        })
     }
 
     // Generate natal chart SVG
     pub fn generate_natal_chart(&self, chart_data: &ChartResponse) -> Result<String, String> {
-        let mut doc = self.create_svg_document()?;
-        doc = self.draw_chart_wheel_background(doc)?;
-        doc = self.draw_zodiac_divisions(doc)?;
-        doc = self.draw_zodiac_signs(doc)?;
-        doc = self.draw_houses(doc, &chart_data.houses)?;
+        if let Some(oriented) = self.oriented(&chart_data.houses) {
+            return oriented.generate_natal_chart(chart_data);
+        }
+        // A transit series shows its first entry
+        let shown_transit = chart_data.transit.as_ref().or(chart_data.transits.first());
+        let mut legend = vec![LegendColumn {
+            heading: shown_transit.map(|_| "Natal"),
+            role: "chart1",
+            planets: &chart_data.planets,
+            houses: &chart_data.houses,
+        }];
+        if let Some(transit_data) = shown_transit {
+            legend.push(LegendColumn {
+                heading: Some("Transit"),
+                role: "transit",
+                planets: &transit_data.planets,
+                houses: &[],
+            });
+        }
+        if let Some(sized) = self.with_legend_room(&legend) {
+            return sized.generate_natal_chart(chart_data);
+        }
+        let mut doc = self.chart_document()?;
+        if self.draws_static_layers() {
+            self.draw_houses(&mut doc, &chart_data.houses, chart_data.angles.as_ref())?;
+            self.draw_degree_markers(&mut doc)?;
+            self.draw_angle_points(&mut doc, &chart_data.points, "chart1")?;
+        }
         
         // Prepare date labels
         let mut date_labels = vec![
-            format!("Birthday: {}", self.format_date(&chart_data.date))
+            format!("{}: {}", self.lang.translate("Birthday"), self.format_date(&chart_data.date))
         ];
         
         // Add transit data if present
-        if let Some(transit_data) = &chart_data.transit {
-            date_labels.push(format!("Transit Date: {}", self.format_date(&transit_data.date)));
+        if let Some(transit_data) = shown_transit {
+            date_labels.push(format!("{}: {}", self.lang.translate("Transit Date"), self.format_date(&transit_data.date)));
+        }
+        let title = match chart_data.chart_type.as_str() {
+            _ if shown_transit.is_some() => "Transit chart",
+            "return" => "Return chart",
+            _ => "Natal chart",
+        };
+        self.describe(&mut doc, title, &date_labels);
+
+        if let Some(transit_data) = shown_transit {
             
             // Calculate positions separately for each chart type
             let natal_positions = self.calculate_planet_positions(&chart_data.planets);
@@ -632,7 +1598,7 @@ impl SVGChartGenerator {
             let mut adjustments_made = std::collections::HashSet::new();
             
             for (transit_planet, transit_pos) in &transit_positions.clone() {
-                for (_natal_planet, natal_pos) in &natal_positions {
+                for natal_pos in natal_positions.values() {
                     // Calculate distance between positions
                     let dx = transit_pos.0 - natal_pos.0;
                     let dy = transit_pos.1 - natal_pos.1;
@@ -657,74 +1623,111 @@ impl SVGChartGenerator {
             }
             
             // Draw planets using calculated positions
-            doc = self.draw_planets_with_positions(doc, &chart_data.planets, &natal_positions, "chart1")?;
-            doc = self.draw_planets_with_positions(doc, &transit_data.planets, &transit_positions, "transit")?;
-            
+            self.layer(&mut doc, "planets", |doc| {
+                self.draw_planets_with_positions(doc, &chart_data.planets, &natal_positions, "chart1")?;
+                self.draw_boundary_markers(doc, &chart_data.boundary_warnings, &natal_positions)?;
+                self.draw_angular_highlights(doc, &chart_data.angular, &natal_positions)?;
+                self.draw_planets_with_positions(doc, &transit_data.planets, &transit_positions, "transit")
+            })?;
+
             // Draw aspects using calculated positions
-            doc = self.draw_aspects_with_positions_for_chart(doc, &chart_data.aspects, &chart_data.planets, &natal_positions, "solid", "chart1")?;
-            doc = self.draw_aspects_with_positions_for_chart(doc, &transit_data.aspects, &transit_data.planets, &transit_positions, "dotted", "transit")?;
-            
-            // Draw transit-to-natal aspects
-            let styles = get_styles().ok_or("Chart styles not initialized. chart_styles.json is required.")?;
-            for aspect in &transit_data.transit_to_natal_aspects {
-                // Strip prefixes from planet names for lookup
-                let planet1_name = aspect.planet1.replace("Natal ", "").replace("Transit ", "");
-                let planet2_name = aspect.planet2.replace("Natal ", "").replace("Transit ", "");
-                
-                // Determine which positions to use based on aspect planet prefixes
-                let pos1 = if aspect.planet1.contains("Natal") {
-                    natal_positions.get(&planet1_name).cloned()
-                } else {
-                    transit_positions.get(&planet1_name).cloned()
-                };
-                
-                let pos2 = if aspect.planet2.contains("Transit") {
-                    transit_positions.get(&planet2_name).cloned()
-                } else {
-                    natal_positions.get(&planet2_name).cloned()
-                };
-                
-                if let (Some((x1, y1)), Some((x2, y2))) = (pos1, pos2) {
-                    let color = styles.get_synastry_aspect_color(&aspect.aspect);
-                    
-                    let line = Line::new()
-                        .set("x1", x1)
-                        .set("y1", y1)
-                        .set("x2", x2)
-                        .set("y2", y2)
-                        .set("stroke", color)
-                        .set("stroke-width", 1)
-                        .set("opacity", 0.7)
-                        .set("style", "stroke-dasharray: 2,2");
-                    
-                    doc = doc.add(line);
+            self.layer(&mut doc, "aspects", |doc| {
+                self.draw_aspects_with_positions_for_chart(doc, &chart_data.aspects, &chart_data.planets, &natal_positions, "solid", "chart1")?;
+                self.draw_aspects_with_positions_for_chart(doc, &transit_data.aspects, &transit_data.planets, &transit_positions, "dotted", "transit")?;
+
+                // Draw transit-to-natal aspects
+                let styles = self.styles()?;
+                for aspect in &transit_data.transit_to_natal_aspects {
+                    // Strip prefixes from planet names for lookup
+                    let planet1_name = aspect.planet1.replace("Natal ", "").replace("Transit ", "");
+                    let planet2_name = aspect.planet2.replace("Natal ", "").replace("Transit ", "");
+
+                    // Determine which positions to use based on aspect planet prefixes
+                    let pos1 = if aspect.planet1.contains("Natal") {
+                        natal_positions.get(&planet1_name).cloned()
+                    } else {
+                        transit_positions.get(&planet1_name).cloned()
+                    };
+
+                    let pos2 = if aspect.planet2.contains("Transit") {
+                        transit_positions.get(&planet2_name).cloned()
+                    } else {
+                        natal_positions.get(&planet2_name).cloned()
+                    };
+
+                    if let (Some(from), Some(to)) = (pos1, pos2) {
+                        let color = styles.get_synastry_aspect_color(&aspect.aspect);
+                        let role = |name: &str| if name.contains("Transit") { "transit" } else { "chart1" };
+                        let id = AspectId(
+                            AspectEnd { role: role(&aspect.planet1), name: &planet1_name },
+                            &aspect.aspect,
+                            AspectEnd { role: role(&aspect.planet2), name: &planet2_name },
+                        );
+                        self.draw_aspect_line(doc, id, (from, to), color, "stroke-dasharray: 2,2", (&aspect.aspect, aspect.orb, aspect.strength))?;
+                    }
                 }
-            }
+                Ok(())
+            })?;
         } else {
             // No transits - use regular positioning
-            doc = self.draw_planets(doc, &chart_data.planets, "chart1")?;
-            doc = self.draw_aspects_for_chart(doc, &chart_data.aspects, &chart_data.planets, "solid", "chart1")?;
+            let positions = self.calculate_planet_positions(&chart_data.planets);
+            self.layer(&mut doc, "planets", |doc| {
+                self.draw_planets_with_positions(doc, &chart_data.planets, &positions, "chart1")?;
+                self.draw_boundary_markers(doc, &chart_data.boundary_warnings, &positions)?;
+                self.draw_angular_highlights(doc, &chart_data.angular, &positions)
+            })?;
+            self.layer(&mut doc, "aspects", |doc| {
+                self.draw_aspects_with_positions_for_chart(doc, &chart_data.aspects, &chart_data.planets, &positions, "solid", "chart1")
+            })?;
         }
 
-        // Add date labels
-        doc = self.draw_date_labels(doc, date_labels)?;
+        // Add date labels and the legend
+        if self.draws_static_layers() {
+            self.draw_date_labels(&mut doc, date_labels)?;
+            self.draw_legend(&mut doc, &legend)?;
+        }
 
-        Ok(doc.to_string())
+        Ok(doc.finish())
     }
 
     // Generate synastry chart SVG
     pub fn generate_synastry_chart(&self, synastry_data: &SynastryResponse) -> Result<String, String> {
-        let mut doc = self.create_svg_document()?;
-        doc = self.draw_chart_wheel_background(doc)?;
-        doc = self.draw_zodiac_divisions(doc)?;
-        doc = self.draw_zodiac_signs(doc)?;
-        doc = self.draw_houses(doc, &synastry_data.chart1.houses)?;
+        if let Some(oriented) = self.oriented(&synastry_data.chart1.houses) {
+            return oriented.generate_synastry_chart(synastry_data);
+        }
+        let legend = [
+            LegendColumn {
+                heading: Some("Chart 1"),
+                role: "chart1",
+                planets: &synastry_data.chart1.planets,
+                houses: &synastry_data.chart1.houses,
+            },
+            LegendColumn {
+                heading: Some("Chart 2"),
+                role: "chart2",
+                planets: &synastry_data.chart2.planets,
+                houses: &synastry_data.chart2.houses,
+            },
+        ];
+        if let Some(sized) = self.with_legend_room(&legend) {
+            return sized.generate_synastry_chart(synastry_data);
+        }
+        let mut doc = self.chart_document()?;
+        if self.draws_static_layers() {
+            self.draw_houses(&mut doc, &synastry_data.chart1.houses, synastry_data.chart1.angles.as_ref())?;
+            if self.show_second_houses {
+                self.draw_second_houses(&mut doc, &synastry_data.chart2.houses)?;
+            }
+            self.draw_angle_points(&mut doc, &synastry_data.chart1.points, "chart1")?;
+            self.draw_angle_points(&mut doc, &synastry_data.chart2.points, "chart2")?;
+        }
         
         // Prepare date labels
         let date_labels = vec![
-            format!("Chart 1 Birthday: {}", self.format_date(&synastry_data.chart1.date)),
-            format!("Chart 2 Birthday: {}", self.format_date(&synastry_data.chart2.date))
+            format!("{}: {}", self.lang.translate("Chart 1 Birthday"), self.format_date(&synastry_data.chart1.date)),
+            format!("{}: {}", self.lang.translate("Chart 2 Birthday"), self.format_date(&synastry_data.chart2.date))
         ];
+        self.describe(&mut doc, "Synastry chart", &date_labels);
         
         // Calculate positions separately for each chart type
         let chart1_positions = self.calculate_planet_positions(&synastry_data.chart1.planets);
@@ -734,7 +1737,7 @@ impl SVGChartGenerator {
         let mut adjustments_made = std::collections::HashSet::new();
         
         for (chart2_planet, chart2_pos) in &chart2_positions.clone() {
-            for (_chart1_planet, chart1_pos) in &chart1_positions {
+            for chart1_pos in chart1_positions.values() {
                 // Calculate distance between positions
                 let dx = chart2_pos.0 - chart1_pos.0;
                 let dy = chart2_pos.1 - chart1_pos.1;
@@ -759,55 +1762,77 @@ impl SVGChartGenerator {
         }
         
         // Draw planets using the calculated positions
-        doc = self.draw_planets_with_positions(doc, &synastry_data.chart1.planets, &chart1_positions, "chart1")?;
-        doc = self.draw_planets_with_positions(doc, &synastry_data.chart2.planets, &chart2_positions, "chart2")?;
-        
-        // Draw aspects for each chart separately
-        doc = self.draw_aspects_with_positions_for_chart(doc, &synastry_data.chart1.aspects, &synastry_data.chart1.planets, &chart1_positions, "solid", "chart1")?;
-        doc = self.draw_aspects_with_positions_for_chart(doc, &synastry_data.chart2.aspects, &synastry_data.chart2.planets, &chart2_positions, "solid", "chart2")?;
-        
-        // Draw synastry aspects between charts
-        let styles = get_styles().ok_or("Chart styles not initialized. chart_styles.json is required.")?;
-        for aspect in &synastry_data.synastries {
-            if let (Some((x1, y1)), Some((x2, y2))) = (
-                chart1_positions.get(&aspect.person1).cloned(),
-                chart2_positions.get(&aspect.person2).cloned()
-            ) {
-                let color = styles.get_synastry_aspect_color(&aspect.aspect);
-                
-                let line = Line::new()
-                    .set("x1", x1)
-                    .set("y1", y1)
-                    .set("x2", x2)
-                    .set("y2", y2)
-                    .set("stroke", color)
-                    .set("stroke-width", 1)
-                    .set("opacity", 0.7)
-                    .set("style", "stroke-dasharray: 5,5");
-                
-                doc = doc.add(line);
+        self.layer(&mut doc, "planets", |doc| {
+            self.draw_planets_with_positions(doc, &synastry_data.chart1.planets, &chart1_positions, "chart1")?;
+            self.draw_planets_with_positions(doc, &synastry_data.chart2.planets, &chart2_positions, "chart2")
+        })?;
+
+        self.layer(&mut doc, "aspects", |doc| {
+            // Draw aspects for each chart separately
+            self.draw_aspects_with_positions_for_chart(doc, &synastry_data.chart1.aspects, &synastry_data.chart1.planets, &chart1_positions, "solid", "chart1")?;
+            self.draw_aspects_with_positions_for_chart(doc, &synastry_data.chart2.aspects, &synastry_data.chart2.planets, &chart2_positions, "solid", "chart2")?;
+
+            // Draw synastry aspects between charts, person1's planet on the chart1 ring
+            let styles = self.styles()?;
+            for aspect in &synastry_data.synastries {
+                if let (Some(&from), Some(&to)) = (
+                    chart1_positions.get(&aspect.person1_planet),
+                    chart2_positions.get(&aspect.person2_planet)
+                ) {
+                    let color = styles.get_synastry_aspect_color(&aspect.aspect);
+                    let id = AspectId(
+                        AspectEnd { role: "chart1", name: &aspect.person1_planet },
+                        &aspect.aspect,
+                        AspectEnd { role: "chart2", name: &aspect.person2_planet },
+                    );
+                    self.draw_aspect_line(doc, id, (from, to), color, "stroke-dasharray: 5,5", (&aspect.aspect, aspect.orb, aspect.strength))?;
+                }
             }
-        }
+            Ok(())
+        })?;
 
-        // Add date labels
-        doc = self.draw_date_labels(doc, date_labels)?;
+        // Add date labels and the legend
+        if self.draws_static_layers() {
+            self.draw_date_labels(&mut doc, date_labels)?;
+            self.draw_legend(&mut doc, &legend)?;
+        }
 
-        Ok(doc.to_string())
+        Ok(doc.finish())
     }
 
     // Generate transit chart SVG
     pub fn generate_transit_chart(&self, transit_data: &TransitResponse) -> Result<String, String> {
-        let mut doc = self.create_svg_document()?;
-        doc = self.draw_chart_wheel_background(doc)?;
-        doc = self.draw_zodiac_divisions(doc)?;
-        doc = self.draw_zodiac_signs(doc)?;
-        doc = self.draw_houses(doc, &transit_data.houses)?;
+        if let Some(oriented) = self.oriented(&transit_data.houses) {
+            return oriented.generate_transit_chart(transit_data);
+        }
+        let legend = [
+            LegendColumn {
+                heading: Some("Natal"),
+                role: "chart1",
+                planets: &transit_data.natal_planets,
+                houses: &transit_data.houses,
+            },
+            LegendColumn {
+                heading: Some("Transit"),
+                role: "transit",
+                planets: &transit_data.transit_planets,
+                houses: &[],
+            },
+        ];
+        if let Some(sized) = self.with_legend_room(&legend) {
+            return sized.generate_transit_chart(transit_data);
+        }
+        let mut doc = self.chart_document()?;
+        if self.draws_static_layers() {
+            self.draw_houses(&mut doc, &transit_data.houses, transit_data.angles.as_ref())?;
+        }
         
         // Prepare date labels
         let date_labels = vec![
-            format!("Birthday: {}", self.format_date(&transit_data.natal_date)),
-            format!("Transit Date: {}", self.format_date(&transit_data.transit_date))
+            format!("{}: {}", self.lang.translate("Birthday"), self.format_date(&transit_data.natal_date)),
+            format!("{}: {}", self.lang.translate("Transit Date"), self.format_date(&transit_data.transit_date))
         ];
+        self.describe(&mut doc, "Transit chart", &date_labels);
         
         // Calculate positions separately for each chart type
         let natal_positions = self.calculate_planet_positions(&transit_data.natal_planets);
@@ -817,7 +1842,7 @@ impl SVGChartGenerator {
         let mut adjustments_made = std::collections::HashSet::new();
         
         for (transit_planet, transit_pos) in &transit_positions.clone() {
-            for (_natal_planet, natal_pos) in &natal_positions {
+            for natal_pos in natal_positions.values() {
                 // Calculate distance between positions
                 let dx = transit_pos.0 - natal_pos.0;
                 let dy = transit_pos.1 - natal_pos.1;
@@ -842,16 +1867,113 @@ impl SVGChartGenerator {
         }
         
         // Draw planets using calculated positions
-        doc = self.draw_planets_with_positions(doc, &transit_data.natal_planets, &natal_positions, "chart1")?;
-        doc = self.draw_planets_with_positions(doc, &transit_data.transit_planets, &transit_positions, "transit")?;
-        
+        self.layer(&mut doc, "planets", |doc| {
+            self.draw_planets_with_positions(doc, &transit_data.natal_planets, &natal_positions, "chart1")?;
+            self.draw_planets_with_positions(doc, &transit_data.transit_planets, &transit_positions, "transit")
+        })?;
+
         // Draw aspects using calculated positions
-        doc = self.draw_aspects_with_positions_for_chart(doc, &transit_data.natal_aspects, &transit_data.natal_planets, &natal_positions, "solid", "chart1")?;
-        doc = self.draw_aspects_with_positions_for_chart(doc, &transit_data.transit_aspects, &transit_data.transit_planets, &transit_positions, "dotted", "transit")?;
+        self.layer(&mut doc, "aspects", |doc| {
+            self.draw_aspects_with_positions_for_chart(doc, &transit_data.natal_aspects, &transit_data.natal_planets, &natal_positions, "solid", "chart1")?;
+            self.draw_aspects_with_positions_for_chart(doc, &transit_data.transit_aspects, &transit_data.transit_planets, &transit_positions, "dotted", "transit")
+        })?;
+
+        // Add date labels and the legend
+        if self.draws_static_layers() {
+            self.draw_date_labels(&mut doc, date_labels)?;
+            self.draw_legend(&mut doc, &legend)?;
+        }
 
-        // Add date labels
-        doc = self.draw_date_labels(doc, date_labels)?;
+        Ok(doc.finish())
+    }
 
-        Ok(doc.to_string())
+    /// A mini-wheel for list views, `size` pixels square. It has its own reduced
+    /// drawing rather than a scaled-down chart: the zodiac ring with the sign
+    /// boundaries, the planets as coloured dots, and the major aspects between
+    /// them in thicker lines. There is no text, so nothing has to stay legible
+    /// at 120 pixels, and no houses or transits.
+    pub fn generate_thumbnail(&self, chart_data: &ChartResponse, size: f64) -> Result<String, String> {
+        let styles = self.styles()?;
+        let scale = size / CHART_SIZE;
+        let center = size / 2.0;
+        let at = |longitude: f64, radius: f64| {
+            let angle = self.longitude_to_angle(longitude);
+            (center + radius * angle.cos(), center + radius * angle.sin())
+        };
+        let line_color = styles.get_chart_color("chart_wheel_line");
+
+        let mut doc = SvgWriter::with_capacity(THUMBNAIL_CAPACITY).decimals(1);
+        doc.element("svg")
+            .attr("viewBox", format_args!("0 0 {} {}", size, size))
+            .attr("width", size)
+            .attr("height", size)
+            .attr("xmlns", "http://www.w3.org/2000/svg")
+            .open();
+        doc.element("rect").attr("width", "100%").attr("height", "100%").attr("fill", styles.get_chart_color("background")).close();
+        for (radius, fill) in [(OUTER_RADIUS, styles.get_chart_color("wheel_background")), (INNER_RADIUS, "none")] {
+            doc.element("circle")
+                .attr("cx", center)
+                .attr("cy", center)
+                .attr("r", radius * scale)
+                .attr("fill", fill)
+                .attr("stroke", line_color)
+                .attr("stroke-width", 1)
+                .close();
+        }
+        for sign in 0..12 {
+            let (x1, y1) = at(sign as f64 * 30.0, INNER_RADIUS * scale);
+            let (x2, y2) = at(sign as f64 * 30.0, OUTER_RADIUS * scale);
+            doc.element("line")
+                .attr("x1", x1)
+                .attr("y1", y1)
+                .attr("x2", x2)
+                .attr("y2", y2)
+                .attr("stroke", line_color)
+                .attr("stroke-width", 1)
+                .close();
+        }
+
+        // Planets within a dot's width of each other step inward so both show
+        let dot_radius = size * THUMBNAIL_DOT_RADIUS;
+        let dot_arc = (2.0 * dot_radius / (BASE_PLANET_RADIUS * scale)).to_degrees();
+        let mut positions = HashMap::new();
+        for group in self.group_planets_by_proximity(&chart_data.planets, dot_arc) {
+            for (i, planet) in group.iter().enumerate() {
+                let radius = BASE_PLANET_RADIUS * scale - i as f64 * 2.5 * dot_radius;
+                positions.insert(planet.name.clone(), at(planet.longitude, radius));
+            }
+        }
+
+        let major = get_aspect_types(false);
+        for aspect in &chart_data.aspects {
+            let is_major = aspect.aspect.parse::<AspectType>().is_ok_and(|aspect_type| major.contains(&aspect_type));
+            if let (true, Some(&(x1, y1)), Some(&(x2, y2))) =
+                (is_major, positions.get(&aspect.planet1), positions.get(&aspect.planet2))
+            {
+                doc.element("line")
+                    .attr("class", "thumbnail-aspect")
+                    .attr("x1", x1)
+                    .attr("y1", y1)
+                    .attr("x2", x2)
+                    .attr("y2", y2)
+                    .attr("stroke", styles.get_chart1_aspect_color(&aspect.aspect))
+                    .attr("stroke-width", size * THUMBNAIL_ASPECT_WIDTH)
+                    .close();
+            }
+        }
+        for planet in &chart_data.planets {
+            if let Some(&(x, y)) = positions.get(&planet.name) {
+                doc.element("circle")
+                    .attr("class", "thumbnail-planet")
+                    .attr("cx", x)
+                    .attr("cy", y)
+                    .attr("r", dot_radius)
+                    .attr("fill", styles.get_planet_color(&planet.name))
+                    .close();
+            }
+        }
+
+        // Full precision means nothing at this size, so coordinates are rounded
+        Ok(doc.finish())
     }
-} 
+}
@@ -0,0 +1,212 @@
+//! A small SVG serializer that writes elements straight into one string.
+//!
+//! The output is what the `svg` crate's `Document` printed: attributes sorted
+//! by name, every child on a line of its own and childless elements closed
+//! with `/>`. Rather than a tree of boxed nodes with a hash map of attributes
+//! each, an element's attributes are gathered in buffers the writer reuses
+//! and written out as soon as the element's first child, or its end, comes.
+
+use std::fmt::{Display, Write};
+
+/// Attributes holding a single coordinate or length
+const COORDINATE_ATTRIBUTES: [&str; 9] = ["x", "y", "x1", "y1", "x2", "y2", "cx", "cy", "r"];
+
+/// Writes a number to at most `decimals` places, without trailing zeros
+fn write_rounded(out: &mut String, value: f64, decimals: u32) {
+    let scale = 10f64.powi(decimals as i32);
+    let rounded = (value * scale).round() / scale;
+    let _ = if rounded.fract() == 0.0 {
+        write!(out, "{}", rounded as i64)
+    } else {
+        write!(out, "{}", rounded)
+    };
+}
+
+/// Rounds the value of the attribute `name`, written to `values` from `start`,
+/// to `decimals` places if it holds coordinates: the coordinate attributes,
+/// and the offsets of `translate(...)` transforms. Scales and path data are
+/// kept as they are, since rounding them would distort the glyphs.
+fn round_attribute(values: &mut String, start: usize, name: &str, decimals: u32) {
+    let value = &values[start..];
+    match value.parse::<f64>() {
+        Ok(number) if COORDINATE_ATTRIBUTES.contains(&name) => {
+            values.truncate(start);
+            write_rounded(values, number, decimals);
+        }
+        _ if name == "transform" => {
+            let Some((offsets, tail)) = value.strip_prefix("translate(").and_then(|v| v.split_once(')')) else {
+                return;
+            };
+            let (offsets, tail) = (offsets.to_string(), tail.to_string());
+            values.truncate(start);
+            values.push_str("translate(");
+            for (i, offset) in offsets.split_whitespace().enumerate() {
+                if i > 0 {
+                    values.push(' ');
+                }
+                match offset.parse() {
+                    Ok(number) => write_rounded(values, number, decimals),
+                    Err(_) => values.push_str(offset),
+                }
+            }
+            values.push(')');
+            values.push_str(&tail);
+        }
+        _ => {}
+    }
+}
+
+/// An SVG document being written, element by element
+#[derive(Debug, Clone, Default)]
+pub struct SvgWriter {
+    out: String,
+    /// Values of the pending element's attributes, back to back
+    values: String,
+    /// Name of each of the pending element's attributes, with where its value is in `values`
+    attributes: Vec<(&'static str, usize, usize)>,
+    /// Elements started and not yet ended, innermost last
+    open: Vec<&'static str>,
+    /// Whether the innermost open element's start tag still lacks its `>`
+    unclosed: bool,
+    /// Places coordinates are written to; unrounded when `None`
+    decimals: Option<u32>,
+}
+
+/// An element whose attributes are being set, written once it is opened or closed
+pub struct Element<'w> {
+    writer: &'w mut SvgWriter,
+    name: &'static str,
+}
+
+impl SvgWriter {
+    /// An empty document with room for `capacity` bytes of output
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            out: String::with_capacity(capacity),
+            ..Self::default()
+        }
+    }
+
+    /// Writes coordinates to at most `decimals` places, so the same chart
+    /// always gives the same markup and smaller output
+    pub fn decimals(mut self, decimals: u32) -> Self {
+        self.decimals = Some(decimals);
+        self
+    }
+
+    /// A copy of this document so far, with room for `capacity` bytes of output
+    pub fn copy_with_capacity(&self, capacity: usize) -> Self {
+        let mut out = String::with_capacity(capacity.max(self.out.len()));
+        out.push_str(&self.out);
+        Self {
+            out,
+            open: self.open.clone(),
+            unclosed: self.unclosed,
+            decimals: self.decimals,
+            ..Self::default()
+        }
+    }
+
+    /// Starts an element inside the innermost open one, or the root element
+    pub fn element(&mut self, name: &'static str) -> Element<'_> {
+        self.values.clear();
+        self.attributes.clear();
+        Element { writer: self, name }
+    }
+
+    /// Adds a text node to the innermost open element. Like the `svg` crate,
+    /// this doesn't escape `content`.
+    pub fn text(&mut self, content: impl Display) {
+        self.begin_child();
+        let _ = write!(self.out, "{}", content);
+    }
+
+    /// Ends the innermost open element
+    pub fn end(&mut self) {
+        let Some(name) = self.open.pop() else {
+            return;
+        };
+        if self.unclosed {
+            self.out.push_str("/>");
+            self.unclosed = false;
+        } else {
+            let _ = write!(self.out, "\n</{}>", name);
+        }
+    }
+
+    /// Ends every open element and returns the document
+    pub fn finish(mut self) -> String {
+        while !self.open.is_empty() {
+            self.end();
+        }
+        self.out
+    }
+
+    // Closes the parent's start tag if this is its first child, and puts the child on a new line
+    fn begin_child(&mut self) {
+        if self.unclosed {
+            self.out.push('>');
+            self.unclosed = false;
+        }
+        if !self.out.is_empty() {
+            self.out.push('\n');
+        }
+    }
+
+    // Writes the pending element's start tag, without its `>`
+    fn write_start_tag(&mut self, name: &'static str) {
+        self.begin_child();
+        self.out.push('<');
+        self.out.push_str(name);
+
+        // A later value for the same name replaces the earlier one
+        let mut attributes = std::mem::take(&mut self.attributes);
+        attributes.reverse();
+        attributes.sort_by_key(|&(name, _, _)| name);
+        attributes.dedup_by_key(|&mut (name, _, _)| name);
+
+        for &(name, start, end) in &attributes {
+            let value = &self.values[start..end];
+            let quote = match (value.contains('\''), value.contains('"')) {
+                (_, false) => '"',
+                (false, true) => '\'',
+                // Can't be quoted either way, so left out
+                (true, true) => continue,
+            };
+            self.out.push(' ');
+            self.out.push_str(name);
+            self.out.push('=');
+            self.out.push(quote);
+            self.out.push_str(value);
+            self.out.push(quote);
+        }
+        self.attributes = attributes;
+    }
+}
+
+impl Element<'_> {
+    /// Sets an attribute
+    pub fn attr(self, name: &'static str, value: impl Display) -> Self {
+        let writer = &mut *self.writer;
+        let start = writer.values.len();
+        let _ = write!(writer.values, "{}", value);
+        if let Some(decimals) = writer.decimals {
+            round_attribute(&mut writer.values, start, name, decimals);
+        }
+        writer.attributes.push((name, start, writer.values.len()));
+        self
+    }
+
+    /// Writes the element and leaves it open for children, until `SvgWriter::end`
+    pub fn open(self) {
+        self.writer.write_start_tag(self.name);
+        self.writer.open.push(self.name);
+        self.writer.unclosed = true;
+    }
+
+    /// Writes the element with no children
+    pub fn close(self) {
+        self.writer.write_start_tag(self.name);
+        self.writer.out.push_str("/>");
+    }
+}
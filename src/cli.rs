@@ -0,0 +1,279 @@
+//! Subcommands of the `astrolog-rs` binary, which otherwise starts the server.
+//!
+//! ```text
+//! astrolog-rs precompute --from 1900-01-01 --to 2100-01-01 --step 1d --bodies all --out positions.csv
+//! astrolog-rs accuracy-report --from 1950-01-01 --to 2050-01-01 --step 30d --out accuracy.md
+//! astrolog-rs replay audit.jsonl --verify
+//! ```
+
+use astrolog_rs::api::audit::{parse_records, DEFAULT_DRIFT_ARCSEC};
+use astrolog_rs::calc::accuracy::{compare_ephemerides, date_grid, ANALYTIC_BODIES};
+use astrolog_rs::calc::ephemeris::{default_ephemeris, AnalyticEphemeris, SwissEphemeris};
+use astrolog_rs::calc::planets::Planet;
+use astrolog_rs::calc::positions::{parse_bodies, DEFAULT_POSITION_BODIES, POSITION_BODIES};
+use astrolog_rs::calc::utils::date_to_julian;
+use astrolog_rs::core::types::EphemerisSource;
+use astrolog_rs::io::precompute::{parse_step, precompute_positions, CsvWriter, DateRange, RowWriter};
+use chrono::{DateTime, NaiveDate, Utc};
+use clap::{Parser, Subcommand};
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+#[derive(Debug, Parser)]
+#[command(name = "astrolog-rs", version)]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Write the positions of the bodies at every step of a date range to a
+    /// CSV file, or Parquet when built with the `parquet` feature
+    Precompute {
+        /// First moment, a date (midnight UTC) or RFC 3339
+        #[arg(long, value_parser = parse_moment)]
+        from: DateTime<Utc>,
+        /// Last moment, included when a step lands on it
+        #[arg(long, value_parser = parse_moment)]
+        to: DateTime<Utc>,
+        /// Time between moments, e.g. 1d, 6h or 30m
+        #[arg(long, default_value = "1d", value_parser = parse_step)]
+        step: chrono::TimeDelta,
+        /// "all", or a comma-separated list such as "sun,moon,true_node"
+        #[arg(long)]
+        bodies: Option<String>,
+        /// "auto", "swiss", "moshier" or "analytic"
+        #[arg(long)]
+        ephemeris: Option<EphemerisSource>,
+        /// Output file; a .parquet extension writes Parquet
+        #[arg(long)]
+        out: PathBuf,
+    },
+    /// Compare the analytic ephemeris with the Swiss Ephemeris files over a
+    /// date range and report each body's longitude error, as a Markdown table
+    /// or, to a .csv file, CSV. Exits with 2 when a body is over its tolerance.
+    AccuracyReport {
+        /// First moment, a date (midnight UTC) or RFC 3339
+        #[arg(long, value_parser = parse_moment)]
+        from: DateTime<Utc>,
+        /// Last moment, included when a step lands on it
+        #[arg(long, value_parser = parse_moment)]
+        to: DateTime<Utc>,
+        /// Time between moments, e.g. 30d or 12h
+        #[arg(long, default_value = "30d", value_parser = parse_step)]
+        step: chrono::TimeDelta,
+        /// Comma-separated list such as "sun,moon,true_node"; every body with
+        /// an analytic theory when left out
+        #[arg(long)]
+        bodies: Option<String>,
+        /// Output file; the report is printed when left out
+        #[arg(long)]
+        out: Option<PathBuf>,
+    },
+    /// Calculate every chart in an audit log again and report the positions
+    /// and cusps that moved. Exits with 2 on drift when --verify is given.
+    Replay {
+        /// Audit log written by the server's `audit_log` setting
+        file: PathBuf,
+        /// Exit with 2 when any record drifted or couldn't be replayed
+        #[arg(long)]
+        verify: bool,
+        /// Largest difference let pass, in arc seconds (arc seconds a day for speeds)
+        #[arg(long, default_value_t = DEFAULT_DRIFT_ARCSEC)]
+        tolerance_arcsec: f64,
+    },
+}
+
+/// Whether the arguments name a subcommand rather than starting the server
+pub fn is_subcommand(args: &[String]) -> bool {
+    args.get(1)
+        .is_some_and(|arg| arg == "precompute" || arg == "accuracy-report" || arg == "replay")
+}
+
+/// Runs the subcommand in `args`, returning the exit code
+pub fn run(args: Vec<String>) -> i32 {
+    let (from, to, step, bodies, ephemeris, out) = match Cli::parse_from(args).command {
+        Command::Precompute {
+            from,
+            to,
+            step,
+            bodies,
+            ephemeris,
+            out,
+        } => (from, to, step, bodies, ephemeris, out),
+        Command::AccuracyReport {
+            from,
+            to,
+            step,
+            bodies,
+            out,
+        } => {
+            return match accuracy_report(from, to, step, bodies.as_deref(), out.as_deref()) {
+                Ok(code) => code,
+                Err(e) => fail(&e),
+            }
+        }
+        Command::Replay {
+            file,
+            verify,
+            tolerance_arcsec,
+        } => {
+            return match replay(&file, verify, tolerance_arcsec) {
+                Ok(code) => code,
+                Err(e) => fail(&e),
+            }
+        }
+    };
+    let bodies = match bodies.as_deref() {
+        None => DEFAULT_POSITION_BODIES.to_vec(),
+        Some("all") => POSITION_BODIES.to_vec(),
+        Some(list) => match parse_bodies(list) {
+            Ok(bodies) => bodies,
+            Err(e) => return fail(&e),
+        },
+    };
+    let range = match DateRange::new(from, to, step) {
+        Ok(range) => range,
+        Err(e) => return fail(&e),
+    };
+    match precompute(&range, &bodies, ephemeris, &out) {
+        Ok(()) => 0,
+        Err(e) => fail(&e),
+    }
+}
+
+fn precompute(range: &DateRange, bodies: &[Planet], source: Option<EphemerisSource>, out: &Path) -> Result<(), String> {
+    let ephemeris = default_ephemeris();
+    let source = source.unwrap_or_else(|| ephemeris.default_source());
+    let mut writer = open_writer(out)?;
+
+    // The first Ctrl-C lets the current chunk finish and the file close
+    let cancel = Arc::new(AtomicBool::new(false));
+    let handler_flag = Arc::clone(&cancel);
+    ctrlc::set_handler(move || {
+        eprintln!("Stopping after the current chunk...");
+        handler_flag.store(true, Ordering::SeqCst);
+    })
+    .map_err(|e| e.to_string())?;
+
+    let summary = precompute_positions(&*ephemeris, source, range, bodies, writer.as_mut(), &cancel, |progress| {
+        eprintln!(
+            "{}/{} moments ({:.1}%)",
+            progress.moments_done,
+            progress.moments_total,
+            100.0 * progress.moments_done as f64 / progress.moments_total as f64
+        );
+    })?;
+    let note = if summary.cancelled { ", stopped early" } else { "" };
+    eprintln!("Wrote {} rows for {} moments to {}{}", summary.rows, summary.moments, out.display(), note);
+    Ok(())
+}
+
+fn accuracy_report(
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+    step: chrono::TimeDelta,
+    bodies: Option<&str>,
+    out: Option<&Path>,
+) -> Result<i32, String> {
+    if !astrolog_rs::calc::swiss_ephemeris::ephemeris_files_available() {
+        return Err("The accuracy report needs the Swiss Ephemeris files; set EPHE_PATH to their directory".to_string());
+    }
+    let bodies = match bodies {
+        None => ANALYTIC_BODIES.to_vec(),
+        Some(list) => parse_bodies(list)?,
+    };
+    let step_days = step.num_seconds() as f64 / 86400.0;
+    let jds = date_grid(date_to_julian(from), date_to_julian(to), step_days)?;
+    let report = compare_ephemerides(
+        (&SwissEphemeris, EphemerisSource::Swiss),
+        (&AnalyticEphemeris, EphemerisSource::Analytic),
+        &bodies,
+        &jds,
+    )?;
+
+    let csv = out.is_some_and(|path| path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("csv")));
+    let text = if csv { report.to_csv() } else { report.to_markdown() };
+    match out {
+        Some(path) => {
+            std::fs::write(path, text).map_err(|e| format!("{}: {}", path.display(), e))?;
+            eprintln!("Wrote the accuracy of {} bodies over {} moments to {}", bodies.len(), jds.len(), path.display());
+        }
+        None => print!("{}", text),
+    }
+
+    let failures = report.failures();
+    for body in &failures {
+        eprintln!("{:?} is {:.4}° out, over its tolerance", body.body, body.max_error);
+    }
+    Ok(if failures.is_empty() { 0 } else { 2 })
+}
+
+fn replay(file: &Path, verify: bool, tolerance_arcsec: f64) -> Result<i32, String> {
+    let text = std::fs::read_to_string(file).map_err(|e| format!("{}: {}", file.display(), e))?;
+    let records = parse_records(&text);
+    let mut failed = 0;
+    for (line, record) in &records {
+        let record = match record {
+            Ok(record) => record,
+            Err(e) => {
+                eprintln!("line {}: not an audit record: {}", line, e);
+                failed += 1;
+                continue;
+            }
+        };
+        let drift = match record.replay(tolerance_arcsec) {
+            Ok(drift) => drift,
+            Err(e) => {
+                println!("line {} ({}): failed: {}", line, record.request_hash, e);
+                failed += 1;
+                continue;
+            }
+        };
+        if drift.is_empty() {
+            continue;
+        }
+        failed += 1;
+        let source = record.meta.as_ref().map(|meta| meta.ephemeris.as_str()).unwrap_or("unknown");
+        println!("line {} ({}, {}, recorded {} on {}):", line, record.request_hash, record.endpoint, record.timestamp.to_rfc3339(), source);
+        for moved in drift {
+            let value = |value: Option<f64>| value.map(|v| format!("{:.6}", v)).unwrap_or_else(|| "missing".to_string());
+            let arcsec = moved.difference.map(|d| format!(" ({:+.2}\")", d * 3600.0)).unwrap_or_default();
+            println!("  {}: {} -> {}{}", moved.field, value(moved.recorded), value(moved.replayed), arcsec);
+        }
+    }
+    eprintln!("Replayed {} records; {} drifted or failed", records.len(), failed);
+    Ok(if verify && failed > 0 { 2 } else { 0 })
+}
+
+fn open_writer(path: &Path) -> Result<Box<dyn RowWriter>, String> {
+    let parquet = path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("parquet"));
+    if parquet && !cfg!(feature = "parquet") {
+        return Err("Parquet output requires the `parquet` feature; use a .csv file".to_string());
+    }
+    let file = File::create(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+    #[cfg(feature = "parquet")]
+    if parquet {
+        return Ok(Box::new(astrolog_rs::io::precompute::ParquetWriter::new(file)?));
+    }
+    Ok(Box::new(CsvWriter::new(BufWriter::new(file))))
+}
+
+/// A date at midnight UTC, or a full RFC 3339 moment
+fn parse_moment(value: &str) -> Result<DateTime<Utc>, String> {
+    if let Ok(date) = NaiveDate::parse_from_str(value, "%Y-%m-%d") {
+        return Ok(date.and_hms_opt(0, 0, 0).unwrap_or_default().and_utc());
+    }
+    DateTime::parse_from_rfc3339(value)
+        .map(|moment| moment.with_timezone(&Utc))
+        .map_err(|_| format!("Invalid date '{}' (expected YYYY-MM-DD or RFC 3339)", value))
+}
+
+fn fail(message: &str) -> i32 {
+    eprintln!("{}", message);
+    1
+}
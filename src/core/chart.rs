@@ -114,7 +114,8 @@ pub fn generate_chart(
         latitude,
         longitude,
         house_system,
-    )?;
+    )?
+    .cusps;
 
     Ok(chart)
 }
@@ -42,6 +42,9 @@ pub enum AstrologError {
     },
     /// Error for unimplemented features
     NotImplemented { message: String },
+    /// Error for a calculation this build was compiled without, such as the
+    /// quadrant house systems without the `swiss` feature
+    UnsupportedInThisBuild { message: String },
     /// Error for invalid input parameters
     InvalidInput { message: String, parameter: String },
     /// Error for invalid latitude
@@ -92,6 +95,9 @@ impl fmt::Display for AstrologError {
             AstrologError::NotImplemented { message } => {
                 write!(f, "Not implemented: {}", message)
             }
+            AstrologError::UnsupportedInThisBuild { message } => {
+                write!(f, "Unsupported in this build: {}", message)
+            }
             AstrologError::InvalidInput { message, parameter } => {
                 write!(f, "Invalid input for {}: {}", parameter, message)
             }
@@ -122,6 +128,12 @@ pub struct ChartPositions {
     pub house_placements: Vec<u8>,
 }
 
+impl Default for ChartPositions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 #[allow(dead_code)]
 impl ChartPositions {
     pub fn new() -> Self {
@@ -247,8 +259,12 @@ pub struct Position {
     pub retrograde: bool,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+/// A house system. It serializes as its snake_case key and reads back from the
+/// key, the Swiss Ephemeris letter or one of the aliases `FromStr` accepts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize)]
+#[serde(rename_all = "snake_case")]
 pub enum HouseSystem {
+    #[default]
     Placidus,
     Koch,
     Equal,
@@ -263,53 +279,270 @@ pub enum HouseSystem {
     Krusinski,
     Vedic,
     Null,
+    /// Gauquelin sectors: 36 divisions, numbered with the diurnal motion from the Ascendant
+    Gauquelin,
+    /// Makransky's Sunshine houses, dividing the Sun's diurnal and nocturnal arcs
+    Sunshine,
+}
+
+impl HouseSystem {
+    /// Every house system, in the order they are listed to clients
+    pub const ALL: [HouseSystem; 16] = [
+        HouseSystem::Placidus,
+        HouseSystem::Koch,
+        HouseSystem::Equal,
+        HouseSystem::WholeSign,
+        HouseSystem::Campanus,
+        HouseSystem::Regiomontanus,
+        HouseSystem::Meridian,
+        HouseSystem::Alcabitius,
+        HouseSystem::Topocentric,
+        HouseSystem::Morinus,
+        HouseSystem::Porphyrius,
+        HouseSystem::Krusinski,
+        HouseSystem::Vedic,
+        HouseSystem::Null,
+        HouseSystem::Gauquelin,
+        HouseSystem::Sunshine,
+    ];
+
+    /// Number of cusps the system divides the chart into
+    pub fn cusp_count(&self) -> usize {
+        match self {
+            HouseSystem::Gauquelin => 36,
+            _ => 12,
+        }
+    }
+
+    /// The name requests give the system by, e.g. "whole_sign"
+    pub fn key(&self) -> &'static str {
+        match self {
+            HouseSystem::Placidus => "placidus",
+            HouseSystem::Koch => "koch",
+            HouseSystem::Equal => "equal",
+            HouseSystem::WholeSign => "whole_sign",
+            HouseSystem::Campanus => "campanus",
+            HouseSystem::Regiomontanus => "regiomontanus",
+            HouseSystem::Meridian => "meridian",
+            HouseSystem::Alcabitius => "alcabitius",
+            HouseSystem::Topocentric => "topocentric",
+            HouseSystem::Morinus => "morinus",
+            HouseSystem::Porphyrius => "porphyrius",
+            HouseSystem::Krusinski => "krusinski",
+            HouseSystem::Vedic => "vedic",
+            HouseSystem::Null => "null",
+            HouseSystem::Gauquelin => "gauquelin",
+            HouseSystem::Sunshine => "sunshine",
+        }
+    }
+
+    /// The single letter the Swiss Ephemeris and Astrolog know the system by.
+    /// Equal is 'E', which the Swiss Ephemeris also takes as 'A'; Vedic houses
+    /// start 15° before the Ascendant, as in Vehlow's 'V'; Null houses are 'N'.
+    pub fn code(&self) -> char {
+        match self {
+            HouseSystem::Placidus => 'P',
+            HouseSystem::Koch => 'K',
+            HouseSystem::Equal => 'E',
+            HouseSystem::WholeSign => 'W',
+            HouseSystem::Campanus => 'C',
+            HouseSystem::Regiomontanus => 'R',
+            HouseSystem::Meridian => 'X',
+            HouseSystem::Alcabitius => 'B',
+            HouseSystem::Topocentric => 'T',
+            HouseSystem::Morinus => 'M',
+            HouseSystem::Porphyrius => 'O',
+            HouseSystem::Krusinski => 'U',
+            HouseSystem::Vedic => 'V',
+            HouseSystem::Null => 'N',
+            HouseSystem::Gauquelin => 'G',
+            HouseSystem::Sunshine => 'I',
+        }
+    }
 }
 
 impl std::fmt::Display for HouseSystem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.key())
+    }
+}
+
+impl FromStr for HouseSystem {
+    type Err = String;
+
+    /// Reads a key ("whole_sign"), the key without underscores ("wholesign"),
+    /// a Swiss Ephemeris letter ("W") or "porphyry", in any case, with hyphens
+    /// or spaces for underscores
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let name = s.trim().to_lowercase().replace(['-', ' '], "_");
+        let mut letters = name.chars();
+        let system = match (letters.next(), letters.next()) {
+            (Some('a'), None) => Some(HouseSystem::Equal),
+            (Some(letter), None) => HouseSystem::ALL
+                .into_iter()
+                .find(|system| system.code().eq_ignore_ascii_case(&letter)),
+            _ if name == "porphyry" => Some(HouseSystem::Porphyrius),
+            _ => HouseSystem::ALL
+                .into_iter()
+                .find(|system| system.key() == name || system.key().replace('_', "") == name),
+        };
+        system.ok_or_else(|| format!("Invalid house system: {}", s))
+    }
+}
+
+impl<'de> Deserialize<'de> for HouseSystem {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let name = String::deserialize(deserializer)?;
+        name.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+/// Source of planetary positions
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EphemerisSource {
+    /// Swiss Ephemeris files when present, otherwise the Moshier theory
+    #[default]
+    Auto,
+    /// Swiss Ephemeris `.se1` files only; fails if they are missing
+    Swiss,
+    /// Built-in Moshier theory; needs no files but has no asteroids or Chiron
+    Moshier,
+    /// Analytic orbital elements, used when built without the `swiss` feature
+    Analytic,
+}
+
+impl std::fmt::Display for EphemerisSource {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            HouseSystem::Placidus => write!(f, "Placidus"),
-            HouseSystem::Koch => write!(f, "Koch"),
-            HouseSystem::Equal => write!(f, "Equal"),
-            HouseSystem::WholeSign => write!(f, "Whole Sign"),
-            HouseSystem::Campanus => write!(f, "Campanus"),
-            HouseSystem::Regiomontanus => write!(f, "Regiomontanus"),
-            HouseSystem::Meridian => write!(f, "Meridian"),
-            HouseSystem::Alcabitius => write!(f, "Alcabitius"),
-            HouseSystem::Topocentric => write!(f, "Topocentric"),
-            HouseSystem::Morinus => write!(f, "Morinus"),
-            HouseSystem::Porphyrius => write!(f, "Porphyrius"),
-            HouseSystem::Krusinski => write!(f, "Krusinski"),
-            HouseSystem::Vedic => write!(f, "Vedic"),
-            HouseSystem::Null => write!(f, "Null"),
+            EphemerisSource::Auto => write!(f, "auto"),
+            EphemerisSource::Swiss => write!(f, "swiss"),
+            EphemerisSource::Moshier => write!(f, "moshier"),
+            EphemerisSource::Analytic => write!(f, "analytic"),
         }
     }
 }
 
-impl FromStr for HouseSystem {
+impl EphemerisSource {
+    /// Rough worst-case error of the planet longitudes, in arc seconds.
+    ///
+    /// `Auto` resolves to one of the others at calculation time, so it reports the
+    /// looser Moshier figure.
+    pub fn precision_arcsec(&self) -> f64 {
+        match self {
+            // Swiss Ephemeris files reproduce JPL DE431 to about a milliarcsecond
+            EphemerisSource::Swiss => 0.001,
+            // The Moshier theory is good to 0.1" for planets and about 3" for the Moon
+            EphemerisSource::Auto | EphemerisSource::Moshier => 3.0,
+            // Keplerian orbital elements drift by up to half a degree
+            EphemerisSource::Analytic => 1800.0,
+        }
+    }
+}
+
+impl FromStr for EphemerisSource {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "auto" => Ok(EphemerisSource::Auto),
+            "swiss" => Ok(EphemerisSource::Swiss),
+            "moshier" => Ok(EphemerisSource::Moshier),
+            "analytic" => Ok(EphemerisSource::Analytic),
+            _ => Err(format!("Invalid ephemeris source: {}", s)),
+        }
+    }
+}
+
+/// Which lunar node a chart uses
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum NodeType {
+    /// The osculating node, including the wobble caused by the Sun
+    #[default]
+    True,
+    /// The smoothly regressing average node
+    Mean,
+}
+
+impl fmt::Display for NodeType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            NodeType::True => write!(f, "true"),
+            NodeType::Mean => write!(f, "mean"),
+        }
+    }
+}
+
+impl FromStr for NodeType {
     type Err = String;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         match s.to_lowercase().as_str() {
-            "placidus" => Ok(HouseSystem::Placidus),
-            "koch" => Ok(HouseSystem::Koch),
-            "equal" => Ok(HouseSystem::Equal),
-            "wholesign" => Ok(HouseSystem::WholeSign),
-            "campanus" => Ok(HouseSystem::Campanus),
-            "regiomontanus" => Ok(HouseSystem::Regiomontanus),
-            "meridian" => Ok(HouseSystem::Meridian),
-            "alcabitius" => Ok(HouseSystem::Alcabitius),
-            "topocentric" => Ok(HouseSystem::Topocentric),
-            "morinus" => Ok(HouseSystem::Morinus),
-            "porphyrius" => Ok(HouseSystem::Porphyrius),
-            "krusinski" => Ok(HouseSystem::Krusinski),
-            "vedic" => Ok(HouseSystem::Vedic),
-            "null" => Ok(HouseSystem::Null),
-            _ => Err(format!("Invalid house system: {}", s)),
+            "true" => Ok(NodeType::True),
+            "mean" => Ok(NodeType::Mean),
+            _ => Err(format!("Invalid node type: {}", s)),
         }
     }
 }
 
+/// Sidereal zero point longitudes are measured from instead of the equinox
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Ayanamsa {
+    /// Chitrapaksha, with Spica at 0° Libra; India's official ayanamsa
+    #[default]
+    Lahiri,
+    /// The Synetic Vernal Point of Western sidereal astrology
+    FaganBradley,
+    /// K. S. Krishnamurti's, used in KP astrology
+    Krishnamurti,
+    /// B. V. Raman's
+    Raman,
+}
+
+impl Ayanamsa {
+    pub const ALL: [Ayanamsa; 4] = [
+        Ayanamsa::Lahiri,
+        Ayanamsa::FaganBradley,
+        Ayanamsa::Krishnamurti,
+        Ayanamsa::Raman,
+    ];
+
+    /// The name requests give the ayanamsa by
+    pub fn key(self) -> &'static str {
+        match self {
+            Ayanamsa::Lahiri => "lahiri",
+            Ayanamsa::FaganBradley => "fagan_bradley",
+            Ayanamsa::Krishnamurti => "krishnamurti",
+            Ayanamsa::Raman => "raman",
+        }
+    }
+}
+
+impl fmt::Display for Ayanamsa {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.key())
+    }
+}
+
+impl FromStr for Ayanamsa {
+    type Err = String;
+
+    /// Parses an ayanamsa by key, taking hyphens, spaces and slashes for
+    /// underscores, so "Fagan-Bradley" is `FaganBradley`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let key = s.trim().to_lowercase().replace(['-', ' ', '/'], "_");
+        Ayanamsa::ALL.into_iter().find(|ayanamsa| ayanamsa.key() == key).ok_or_else(|| {
+            format!(
+                "Unknown ayanamsa '{}'. Supported ayanamsas: {}",
+                s,
+                Ayanamsa::ALL.map(Ayanamsa::key).join(", ")
+            )
+        })
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub enum AspectType {
     Conjunction = 0,
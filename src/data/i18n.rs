@@ -0,0 +1,272 @@
+//! Translations of the names and labels shown to people: bodies, points, signs,
+//! aspects, months and the SVG chart labels, in compact static tables with
+//! English as the fallback for anything missing.
+
+use chrono::{DateTime, Datelike, Utc};
+use std::fmt;
+use std::str::FromStr;
+
+/// Language for chart labels, tooltips and formatted positions
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Lang {
+    #[default]
+    En,
+    Es,
+    De,
+    Fr,
+    Pt,
+}
+
+impl fmt::Display for Lang {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Lang::En => write!(f, "en"),
+            Lang::Es => write!(f, "es"),
+            Lang::De => write!(f, "de"),
+            Lang::Fr => write!(f, "fr"),
+            Lang::Pt => write!(f, "pt"),
+        }
+    }
+}
+
+impl FromStr for Lang {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "en" => Ok(Lang::En),
+            "es" => Ok(Lang::Es),
+            "de" => Ok(Lang::De),
+            "fr" => Ok(Lang::Fr),
+            "pt" => Ok(Lang::Pt),
+            _ => Err(format!("Unsupported language: {} (expected \"en\", \"es\", \"de\", \"fr\" or \"pt\")", s)),
+        }
+    }
+}
+
+/// English text with its Spanish, German, French and Portuguese translations
+const TRANSLATIONS: &[(&str, [&str; 4])] = &[
+    // Planets
+    ("Sun", ["Sol", "Sonne", "Soleil", "Sol"]),
+    ("Moon", ["Luna", "Mond", "Lune", "Lua"]),
+    ("Mercury", ["Mercurio", "Merkur", "Mercure", "Mercúrio"]),
+    ("Venus", ["Venus", "Venus", "Vénus", "Vênus"]),
+    ("Mars", ["Marte", "Mars", "Mars", "Marte"]),
+    ("Jupiter", ["Júpiter", "Jupiter", "Jupiter", "Júpiter"]),
+    ("Saturn", ["Saturno", "Saturn", "Saturne", "Saturno"]),
+    ("Uranus", ["Urano", "Uranus", "Uranus", "Urano"]),
+    ("Neptune", ["Neptuno", "Neptun", "Neptune", "Netuno"]),
+    ("Pluto", ["Plutón", "Pluto", "Pluton", "Plutão"]),
+    ("Chiron", ["Quirón", "Chiron", "Chiron", "Quíron"]),
+    ("Ceres", ["Ceres", "Ceres", "Cérès", "Ceres"]),
+    ("Pallas", ["Palas", "Pallas", "Pallas", "Palas"]),
+    ("Juno", ["Juno", "Juno", "Junon", "Juno"]),
+    ("Vesta", ["Vesta", "Vesta", "Vesta", "Vesta"]),
+    ("TrueNode", ["Nodo verdadero", "Wahrer Mondknoten", "Nœud vrai", "Nodo verdadeiro"]),
+    ("MeanNode", ["Nodo medio", "Mittlerer Mondknoten", "Nœud moyen", "Nodo médio"]),
+    ("MeanLilith", ["Lilith media", "Mittlere Lilith", "Lune Noire moyenne", "Lilith média"]),
+    ("TrueLilith", ["Lilith verdadera", "Wahre Lilith", "Lune Noire vraie", "Lilith verdadeira"]),
+    // Angle points
+    ("Vertex", ["Vértice", "Vertex", "Vertex", "Vértice"]),
+    ("AntiVertex", ["Antivértice", "Antivertex", "Anti-Vertex", "Antivértice"]),
+    ("EastPoint", ["Punto Este", "Ostpunkt", "Point Est", "Ponto Leste"]),
+    // Angles
+    ("Ascendant", ["Ascendente", "Aszendent", "Ascendant", "Ascendente"]),
+    ("Descendant", ["Descendente", "Deszendent", "Descendant", "Descendente"]),
+    ("Midheaven", ["Medio Cielo", "Medium Coeli", "Milieu du Ciel", "Meio do Céu"]),
+    ("IC", ["Fondo del Cielo", "Imum Coeli", "Fond du Ciel", "Fundo do Céu"]),
+    // Signs
+    ("Aries", ["Aries", "Widder", "Bélier", "Áries"]),
+    ("Taurus", ["Tauro", "Stier", "Taureau", "Touro"]),
+    ("Gemini", ["Géminis", "Zwillinge", "Gémeaux", "Gêmeos"]),
+    ("Cancer", ["Cáncer", "Krebs", "Cancer", "Câncer"]),
+    ("Leo", ["Leo", "Löwe", "Lion", "Leão"]),
+    ("Virgo", ["Virgo", "Jungfrau", "Vierge", "Virgem"]),
+    ("Libra", ["Libra", "Waage", "Balance", "Libra"]),
+    ("Scorpio", ["Escorpio", "Skorpion", "Scorpion", "Escorpião"]),
+    ("Sagittarius", ["Sagitario", "Schütze", "Sagittaire", "Sagitário"]),
+    ("Capricorn", ["Capricornio", "Steinbock", "Capricorne", "Capricórnio"]),
+    ("Aquarius", ["Acuario", "Wassermann", "Verseau", "Aquário"]),
+    ("Pisces", ["Piscis", "Fische", "Poissons", "Peixes"]),
+    // Aspects
+    ("Conjunction", ["Conjunción", "Konjunktion", "Conjonction", "Conjunção"]),
+    ("SemiSextile", ["Semisextil", "Halbsextil", "Semi-sextile", "Semissextil"]),
+    ("SemiSquare", ["Semicuadratura", "Halbquadrat", "Semi-carré", "Semiquadratura"]),
+    ("Sextile", ["Sextil", "Sextil", "Sextile", "Sextil"]),
+    ("Quintile", ["Quintil", "Quintil", "Quintile", "Quintil"]),
+    ("Square", ["Cuadratura", "Quadrat", "Carré", "Quadratura"]),
+    ("BiQuintile", ["Biquintil", "Biquintil", "Biquintile", "Biquintil"]),
+    ("Trine", ["Trígono", "Trigon", "Trigone", "Trígono"]),
+    ("Sesquisquare", ["Sesquicuadratura", "Anderthalbquadrat", "Sesqui-carré", "Sesquiquadratura"]),
+    ("Quincunx", ["Quincuncio", "Quincunx", "Quinconce", "Quincúncio"]),
+    ("Opposition", ["Oposición", "Opposition", "Opposition", "Oposição"]),
+    ("Septile", ["Septil", "Septil", "Septile", "Septil"]),
+    ("BiSeptile", ["Biseptil", "Biseptil", "Biseptile", "Biseptil"]),
+    ("TriSeptile", ["Triseptil", "Triseptil", "Triseptile", "Triseptil"]),
+    ("Novile", ["Novil", "Novil", "Novile", "Novil"]),
+    ("BiNovile", ["Binovil", "Binovil", "Binovile", "Binovil"]),
+    ("QuadNovile", ["Cuadrinovil", "Quadrinovil", "Quadrinovile", "Quadrinovil"]),
+    // Months
+    ("January", ["enero", "Januar", "janvier", "janeiro"]),
+    ("February", ["febrero", "Februar", "février", "fevereiro"]),
+    ("March", ["marzo", "März", "mars", "março"]),
+    ("April", ["abril", "April", "avril", "abril"]),
+    ("May", ["mayo", "Mai", "mai", "maio"]),
+    ("June", ["junio", "Juni", "juin", "junho"]),
+    ("July", ["julio", "Juli", "juillet", "julho"]),
+    ("August", ["agosto", "August", "août", "agosto"]),
+    ("September", ["septiembre", "September", "septembre", "setembro"]),
+    ("October", ["octubre", "Oktober", "octobre", "outubro"]),
+    ("November", ["noviembre", "November", "novembre", "novembro"]),
+    ("December", ["diciembre", "Dezember", "décembre", "dezembro"]),
+    // Chart types
+    ("Natal chart", ["Carta natal", "Geburtshoroskop", "Thème natal", "Mapa natal"]),
+    ("Return chart", ["Carta de revolución", "Revolutionshoroskop", "Thème de révolution", "Mapa de revolução"]),
+    ("Transit chart", ["Carta de tránsitos", "Transithoroskop", "Thème des transits", "Mapa de trânsitos"]),
+    ("Synastry chart", ["Carta de sinastría", "Synastrie-Horoskop", "Thème de synastrie", "Mapa de sinastria"]),
+    // Chart labels
+    ("Birthday", ["Nacimiento", "Geburtstag", "Naissance", "Nascimento"]),
+    ("Transit Date", ["Fecha de tránsito", "Transitdatum", "Date du transit", "Data do trânsito"]),
+    ("Chart 1 Birthday", ["Nacimiento carta 1", "Geburtstag Horoskop 1", "Naissance thème 1", "Nascimento mapa 1"]),
+    ("Chart 2 Birthday", ["Nacimiento carta 2", "Geburtstag Horoskop 2", "Naissance thème 2", "Nascimento mapa 2"]),
+    // Legend headings
+    ("Natal", ["Natal", "Radix", "Natal", "Natal"]),
+    ("Transit", ["Tránsito", "Transit", "Transit", "Trânsito"]),
+    ("Chart 1", ["Carta 1", "Horoskop 1", "Thème 1", "Mapa 1"]),
+    ("Chart 2", ["Carta 2", "Horoskop 2", "Thème 2", "Mapa 2"]),
+    // Boundary warnings
+    ("Near", ["Cerca de", "Nahe", "Près de", "Perto de"]),
+    ("House", ["Casa", "Haus", "Maison", "Casa"]),
+    // Highlights
+    ("Critical degree", ["Grado crítico", "Kritischer Grad", "Degré critique", "Grau crítico"]),
+    ("Aries point", ["Punto Aries", "Widderpunkt", "Point Bélier", "Ponto Áries"]),
+    ("on the", ["en el", "am", "sur le", "no"]),
+];
+
+/// English month names, the keys of their translations
+const MONTHS: [&str; 12] = [
+    "January", "February", "March", "April", "May", "June", "July", "August", "September", "October", "November", "December",
+];
+
+/// English sign names from Aries, the keys of their translations
+const SIGNS: [&str; 12] = [
+    "Aries", "Taurus", "Gemini", "Cancer", "Leo", "Virgo", "Libra", "Scorpio", "Sagittarius", "Capricorn", "Aquarius", "Pisces",
+];
+
+impl Lang {
+    /// Translates English label text, returning it unchanged if there's no entry
+    pub fn translate<'a>(&self, text: &'a str) -> &'a str {
+        let column = match self {
+            Lang::En => return text,
+            Lang::Es => 0,
+            Lang::De => 1,
+            Lang::Fr => 2,
+            Lang::Pt => 3,
+        };
+        TRANSLATIONS
+            .iter()
+            .find(|(en, _)| *en == text)
+            .map_or(text, |(_, translations)| translations[column])
+    }
+
+    /// The first language of an `Accept-Language` header this build supports,
+    /// by quality and then by order; `None` when it names none of them
+    pub fn from_accept_language(header: &str) -> Option<Lang> {
+        let mut best: Option<(Lang, f64)> = None;
+        for range in header.split(',') {
+            let mut parts = range.split(';').map(str::trim);
+            let tag = parts.next().unwrap_or_default();
+            let quality = parts
+                .find_map(|param| param.strip_prefix("q="))
+                .map_or(Some(1.0), |q| q.parse::<f64>().ok())
+                .unwrap_or(0.0);
+            let primary = tag.split('-').next().unwrap_or_default();
+            if let Ok(lang) = primary.parse::<Lang>() {
+                if quality > 0.0 && best.is_none_or(|(_, q)| quality > q) {
+                    best = Some((lang, quality));
+                }
+            }
+        }
+        best.map(|(lang, _)| lang)
+    }
+
+    /// The name of a month, 1 for January
+    pub fn month(&self, month: u32) -> &'static str {
+        let name = MONTHS[(month.clamp(1, 12) - 1) as usize];
+        self.translate(name)
+    }
+
+    /// A date and time to the minute with the month spelled out, e.g.
+    /// "24 October 1977 04:56" or "24. Oktober 1977 04:56"
+    pub fn format_date(&self, date: &DateTime<Utc>) -> String {
+        let (day, month, year, time) = (date.day(), self.month(date.month()), date.year(), date.format("%H:%M"));
+        match self {
+            Lang::Es | Lang::Pt => format!("{} de {} de {} {}", day, month, year, time),
+            Lang::De => format!("{}. {} {} {}", day, month, year, time),
+            Lang::En | Lang::Fr => format!("{} {} {} {}", day, month, year, time),
+        }
+    }
+
+    /// An ecliptic longitude as degrees and minutes within its sign, e.g.
+    /// "12°05' Scorpio"; the minutes are truncated, never rounded into the next sign
+    pub fn format_position(&self, longitude: f64) -> String {
+        let minutes = (longitude.rem_euclid(360.0) * 60.0).floor() as u32 % (360 * 60);
+        let sign = SIGNS[(minutes / (30 * 60)) as usize];
+        format!("{}°{:02}' {}", minutes / 60 % 30, minutes % 60, self.translate(sign))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_translate_planets_and_labels() {
+        assert_eq!(Lang::Es.translate("Jupiter"), "Júpiter");
+        assert_eq!(Lang::De.translate("Sun"), "Sonne");
+        assert_eq!(Lang::De.translate("Transit Date"), "Transitdatum");
+        assert_eq!(Lang::Fr.translate("Square"), "Carré");
+        assert_eq!(Lang::Pt.translate("Natal chart"), "Mapa natal");
+        assert_eq!(Lang::En.translate("Moon"), "Moon");
+        // Unknown text falls through
+        assert_eq!(Lang::Es.translate("Planet 11"), "Planet 11");
+        assert_eq!("DE".parse::<Lang>(), Ok(Lang::De));
+        assert_eq!("fr".parse::<Lang>(), Ok(Lang::Fr));
+        assert!("it".parse::<Lang>().is_err());
+    }
+
+    #[test]
+    fn test_every_month_and_sign_has_translations() {
+        for name in MONTHS.iter().chain(&SIGNS) {
+            assert!(TRANSLATIONS.iter().any(|(en, _)| en == name), "{}", name);
+        }
+        assert_eq!(Lang::En.month(5), "May");
+        assert_eq!(Lang::Fr.month(8), "août");
+    }
+
+    #[test]
+    fn test_accept_language_picks_the_best_supported() {
+        assert_eq!(Lang::from_accept_language("es-MX,es;q=0.9,en;q=0.8"), Some(Lang::Es));
+        assert_eq!(Lang::from_accept_language("it-IT, pt-BR;q=0.7, de;q=0.9"), Some(Lang::De));
+        assert_eq!(Lang::from_accept_language("fr;q=0, en;q=0.1"), Some(Lang::En));
+        assert_eq!(Lang::from_accept_language("*"), None);
+        assert_eq!(Lang::from_accept_language(""), None);
+    }
+
+    #[test]
+    fn test_dates_and_positions_are_formatted_in_the_language() {
+        let date = "1977-10-24T04:56:30Z".parse().unwrap();
+        assert_eq!(Lang::En.format_date(&date), "24 October 1977 04:56");
+        assert_eq!(Lang::Es.format_date(&date), "24 de octubre de 1977 04:56");
+        assert_eq!(Lang::De.format_date(&date), "24. Oktober 1977 04:56");
+        assert_eq!(Lang::Fr.format_date(&date), "24 octobre 1977 04:56");
+        assert_eq!(Lang::Pt.format_date(&date), "24 de outubro de 1977 04:56");
+
+        assert_eq!(Lang::En.format_position(210.0 + 12.0 + 5.5 / 60.0), "12°05' Scorpio");
+        assert_eq!(Lang::Es.format_position(222.1), "12°06' Escorpio");
+        assert_eq!(Lang::Pt.format_position(0.0), "0°00' Áries");
+        // Just short of Aries is still the end of Pisces
+        assert_eq!(Lang::En.format_position(359.9999), "29°59' Pisces");
+        assert_eq!(Lang::De.format_position(-30.0), "0°00' Fische");
+    }
+}
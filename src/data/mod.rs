@@ -5,3 +5,6 @@ pub const SIGN_COUNT: usize = 12;
 pub const DEGREES_PER_SIGN: f64 = 30.0;
 #[allow(dead_code)]
 pub const DEGREES_PER_CIRCLE: f64 = 360.0;
+
+pub mod i18n;
+pub mod symbols;
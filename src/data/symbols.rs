@@ -0,0 +1,132 @@
+//! Keys, names, Unicode symbols and categories of the bodies, signs and
+//! aspects. The chart styles, the SVG renderer, the text listings and
+//! `GET /api/symbols` all read them from here.
+
+use crate::calc::aspects::AspectType;
+use crate::calc::planets::Planet;
+use crate::calc::rulers::Sign;
+
+/// Variation selector asking for the text rather than the emoji presentation
+pub const TEXT_PRESENTATION: char = '\u{FE0E}';
+
+/// How a body, sign or aspect is named and drawn
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Symbol {
+    /// Key as requests spell it, e.g. "true_node"
+    pub key: &'static str,
+    /// Name for people, e.g. "True Node"
+    pub name: &'static str,
+    /// Built-in Unicode symbol, if there is one; styles can override it
+    pub glyph: Option<&'static str>,
+    /// "luminary", "planet", "node", "point", "centaur" or "asteroid" for a
+    /// body, the element for a sign, "major" or "minor" for an aspect
+    pub category: &'static str,
+}
+
+impl Symbol {
+    const fn new(key: &'static str, name: &'static str, glyph: Option<&'static str>, category: &'static str) -> Self {
+        Self { key, name, glyph, category }
+    }
+}
+
+/// Every body a chart can hold, in the order charts list them
+pub const BODY_SYMBOLS: [(Planet, Symbol); 22] = [
+    (Planet::Sun, Symbol::new("sun", "Sun", Some("☉"), "luminary")),
+    (Planet::Moon, Symbol::new("moon", "Moon", Some("☽"), "luminary")),
+    (Planet::Mercury, Symbol::new("mercury", "Mercury", Some("☿"), "planet")),
+    (Planet::Venus, Symbol::new("venus", "Venus", Some("♀"), "planet")),
+    (Planet::Mars, Symbol::new("mars", "Mars", Some("♂"), "planet")),
+    (Planet::Jupiter, Symbol::new("jupiter", "Jupiter", Some("♃"), "planet")),
+    (Planet::Saturn, Symbol::new("saturn", "Saturn", Some("♄"), "planet")),
+    (Planet::Uranus, Symbol::new("uranus", "Uranus", Some("♅"), "planet")),
+    (Planet::Neptune, Symbol::new("neptune", "Neptune", Some("♆"), "planet")),
+    (Planet::Pluto, Symbol::new("pluto", "Pluto", Some("♇"), "planet")),
+    (Planet::MeanNode, Symbol::new("mean_node", "Mean Node", Some("☊"), "node")),
+    (Planet::TrueNode, Symbol::new("true_node", "True Node", Some("☊"), "node")),
+    (Planet::MeanLilith, Symbol::new("mean_lilith", "Mean Lilith", Some("⚸"), "point")),
+    (Planet::TrueLilith, Symbol::new("true_lilith", "True Lilith", Some("⚸"), "point")),
+    (Planet::Chiron, Symbol::new("chiron", "Chiron", Some("⚷"), "centaur")),
+    (Planet::Ceres, Symbol::new("ceres", "Ceres", Some("⚳"), "asteroid")),
+    (Planet::Pallas, Symbol::new("pallas", "Pallas", Some("⚴"), "asteroid")),
+    (Planet::Juno, Symbol::new("juno", "Juno", Some("⚵"), "asteroid")),
+    (Planet::Vesta, Symbol::new("vesta", "Vesta", Some("⚶"), "asteroid")),
+    (Planet::Fortune, Symbol::new("fortune", "Part of Fortune", None, "point")),
+    (Planet::Vertex, Symbol::new("vertex", "Vertex", None, "point")),
+    (Planet::EastPoint, Symbol::new("east_point", "East Point", None, "point")),
+];
+
+/// The signs from Aries, their glyphs with the text presentation selector so
+/// fonts don't substitute emoji
+pub const SIGN_SYMBOLS: [(Sign, Symbol); 12] = [
+    (Sign::Aries, Symbol::new("aries", "Aries", Some("♈\u{FE0E}"), "fire")),
+    (Sign::Taurus, Symbol::new("taurus", "Taurus", Some("♉\u{FE0E}"), "earth")),
+    (Sign::Gemini, Symbol::new("gemini", "Gemini", Some("♊\u{FE0E}"), "air")),
+    (Sign::Cancer, Symbol::new("cancer", "Cancer", Some("♋\u{FE0E}"), "water")),
+    (Sign::Leo, Symbol::new("leo", "Leo", Some("♌\u{FE0E}"), "fire")),
+    (Sign::Virgo, Symbol::new("virgo", "Virgo", Some("♍\u{FE0E}"), "earth")),
+    (Sign::Libra, Symbol::new("libra", "Libra", Some("♎\u{FE0E}"), "air")),
+    (Sign::Scorpio, Symbol::new("scorpio", "Scorpio", Some("♏\u{FE0E}"), "water")),
+    (Sign::Sagittarius, Symbol::new("sagittarius", "Sagittarius", Some("♐\u{FE0E}"), "fire")),
+    (Sign::Capricorn, Symbol::new("capricorn", "Capricorn", Some("♑\u{FE0E}"), "earth")),
+    (Sign::Aquarius, Symbol::new("aquarius", "Aquarius", Some("♒\u{FE0E}"), "air")),
+    (Sign::Pisces, Symbol::new("pisces", "Pisces", Some("♓\u{FE0E}"), "water")),
+];
+
+/// Every aspect type, majors first. Unicode has no symbols for the quintiles,
+/// so they use the customary letters, and none for the septiles and noviles.
+pub const ASPECT_SYMBOLS: [(AspectType, Symbol); 17] = [
+    (AspectType::Conjunction, Symbol::new("conjunction", "Conjunction", Some("☌"), "major")),
+    (AspectType::Sextile, Symbol::new("sextile", "Sextile", Some("⚹"), "major")),
+    (AspectType::Square, Symbol::new("square", "Square", Some("□"), "major")),
+    (AspectType::Trine, Symbol::new("trine", "Trine", Some("△"), "major")),
+    (AspectType::Opposition, Symbol::new("opposition", "Opposition", Some("☍"), "major")),
+    (AspectType::SemiSextile, Symbol::new("semisextile", "Semisextile", Some("⚺"), "minor")),
+    (AspectType::SemiSquare, Symbol::new("semisquare", "Semisquare", Some("∠"), "minor")),
+    (AspectType::Sesquisquare, Symbol::new("sesquisquare", "Sesquisquare", Some("⚼"), "minor")),
+    (AspectType::Quincunx, Symbol::new("quincunx", "Quincunx", Some("⚻"), "minor")),
+    (AspectType::Quintile, Symbol::new("quintile", "Quintile", Some("Q"), "minor")),
+    (AspectType::BiQuintile, Symbol::new("biquintile", "Biquintile", Some("bQ"), "minor")),
+    (AspectType::Septile, Symbol::new("septile", "Septile", None, "minor")),
+    (AspectType::BiSeptile, Symbol::new("biseptile", "Biseptile", None, "minor")),
+    (AspectType::TriSeptile, Symbol::new("triseptile", "Triseptile", None, "minor")),
+    (AspectType::Novile, Symbol::new("novile", "Novile", None, "minor")),
+    (AspectType::BiNovile, Symbol::new("binovile", "Binovile", None, "minor")),
+    (AspectType::QuadNovile, Symbol::new("quadnovile", "Quadnovile", None, "minor")),
+];
+
+/// Code point of a glyph, e.g. "U+2609", if it is one symbol character.
+/// The letters standing in for the quintiles have none.
+pub fn codepoint(glyph: &str) -> Option<String> {
+    let mut chars = glyph.chars().filter(|&c| c != TEXT_PRESENTATION);
+    match (chars.next(), chars.next()) {
+        (Some(c), None) if !c.is_ascii() => Some(format!("U+{:04X}", c as u32)),
+        _ => None,
+    }
+}
+
+/// Symbol of a body
+pub fn body_symbol(planet: Planet) -> &'static Symbol {
+    let (_, symbol) = BODY_SYMBOLS.iter().find(|(p, _)| *p == planet).expect("every body has a symbol");
+    symbol
+}
+
+/// Symbol of a sign
+pub fn sign_symbol(sign: Sign) -> &'static Symbol {
+    &SIGN_SYMBOLS[Sign::ALL.iter().position(|s| *s == sign).unwrap_or(0)].1
+}
+
+/// Symbol of an aspect
+pub fn aspect_symbol(aspect: AspectType) -> &'static Symbol {
+    let (_, symbol) = ASPECT_SYMBOLS.iter().find(|(a, _)| *a == aspect).expect("every aspect has a symbol");
+    symbol
+}
+
+/// Built-in glyph of a body, by the name charts give it, e.g. "TrueNode"
+pub fn body_glyph(name: &str) -> Option<&'static str> {
+    name.parse().ok().and_then(|planet| body_symbol(planet).glyph)
+}
+
+/// Built-in glyph of an aspect, by name, ignoring case
+pub fn aspect_glyph(name: &str) -> Option<&'static str> {
+    name.parse().ok().and_then(|aspect| aspect_symbol(aspect).glyph)
+}
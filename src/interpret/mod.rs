@@ -0,0 +1,326 @@
+//! Interpretation text for chart factors.
+//!
+//! A content pack is a JSON file of keyed paragraphs. Each factor of a chart
+//! (a planet in a sign or house, an aspect, the chart ruler's condition) is
+//! looked up under a key such as `sun_in_scorpio`, `sun_in_5th`,
+//! `sun_square_moon` or `chart_ruler_combust`. Factors without a paragraph
+//! are skipped. Paragraphs may use `{planet}`, `{planet2}`, `{sign}`,
+//! `{house}` and `{aspect}`, filled in from the factor. A pack may also hold
+//! the Sabian symbols, one per zodiacal degree under `sabian_1` to `sabian_360`.
+
+use crate::api::types::{AspectInfo, ChartResponse, ChartRulerInfo, PlanetInfo};
+use crate::calc::rulers::{SolarCondition, Sign};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+#[cfg(not(target_arch = "wasm32"))]
+use std::fs;
+use std::sync::OnceLock;
+
+/// Environment variable naming a content pack file to use instead of the starter pack
+pub const CONTENT_PACK_ENV: &str = "INTERPRETATION_PACK";
+
+/// A set of keyed paragraphs
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ContentPack {
+    pub name: String,
+    #[serde(default)]
+    pub snippets: HashMap<String, String>,
+}
+
+/// What a paragraph is about, in response order
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Category {
+    PlanetInSign,
+    PlanetInHouse,
+    Aspect,
+    ChartRuler,
+}
+
+/// The chart factor a paragraph was chosen for
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Factor {
+    PlanetInSign { planet: String, sign: String },
+    PlanetInHouse { planet: String, house: u8 },
+    Aspect { planet1: String, aspect: String, planet2: String, orb: f64 },
+    ChartRuler {
+        planet: String,
+        sign: String,
+        retrograde: bool,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        solar_condition: Option<SolarCondition>,
+    },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Paragraph {
+    /// Content pack key the text came from
+    pub key: String,
+    pub text: String,
+    pub factor: Factor,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Section {
+    pub category: Category,
+    pub paragraphs: Vec<Paragraph>,
+}
+
+/// Interpretation of a chart, grouped by category
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Interpretation {
+    /// Name of the content pack used
+    pub pack: String,
+    pub sections: Vec<Section>,
+}
+
+/// "1st", "2nd", "3rd", "4th" ... "12th"
+fn ordinal(n: u8) -> String {
+    let suffix = match (n % 10, n % 100) {
+        (_, 11..=13) => "th",
+        (1, _) => "st",
+        (2, _) => "nd",
+        (3, _) => "rd",
+        _ => "th",
+    };
+    format!("{}{}", n, suffix)
+}
+
+/// Lowercase key fragment for a name, e.g. "SemiSextile" -> "semisextile"
+fn key_part(name: &str) -> String {
+    name.replace(' ', "_").to_lowercase()
+}
+
+impl ContentPack {
+    /// The pack compiled into the crate
+    pub fn starter() -> Self {
+        serde_json::from_str(include_str!("starter_pack.json")).expect("starter_pack.json is valid")
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn load_from_file(path: &str) -> Result<Self, Box<dyn std::error::Error>> {
+        let content = fs::read_to_string(path)?;
+        Ok(serde_json::from_str(&content)?)
+    }
+
+    /// The paragraph for `key` with its template variables filled in, if the pack has one
+    fn paragraph(&self, key: String, vars: &[(&str, &str)], factor: Factor) -> Option<Paragraph> {
+        let mut text = self.snippets.get(&key)?.clone();
+        for (name, value) in vars {
+            text = text.replace(&format!("{{{}}}", name), value);
+        }
+        Some(Paragraph { key, text, factor })
+    }
+
+    /// The Sabian symbol of a zodiacal degree 1-360, if the pack has it
+    pub fn sabian(&self, degree: u16) -> Option<&str> {
+        self.snippets.get(&format!("sabian_{}", degree)).map(String::as_str)
+    }
+
+    /// Interprets the factors of a chart.
+    ///
+    /// # Arguments
+    ///
+    /// * `planets` - Planets with their house placements
+    /// * `aspects` - Aspects between them, as returned in the response
+    /// * `chart_ruler` - The chart ruler, when houses were calculated
+    pub fn interpret(
+        &self,
+        planets: &[PlanetInfo],
+        aspects: &[AspectInfo],
+        chart_ruler: Option<&ChartRulerInfo>,
+    ) -> Interpretation {
+        let mut in_sign = Vec::new();
+        let mut in_house = Vec::new();
+        for planet in planets {
+            let sign = Sign::from_longitude(planet.longitude).to_string();
+            let vars = [("planet", planet.name.as_str()), ("sign", sign.as_str())];
+            in_sign.extend(self.paragraph(
+                format!("{}_in_{}", key_part(&planet.name), key_part(&sign)),
+                &vars,
+                Factor::PlanetInSign {
+                    planet: planet.name.clone(),
+                    sign: sign.clone(),
+                },
+            ));
+
+            if let Some(house) = planet.house {
+                let house_name = ordinal(house);
+                let vars = [("planet", planet.name.as_str()), ("sign", sign.as_str()), ("house", &house_name)];
+                in_house.extend(self.paragraph(
+                    format!("{}_in_{}", key_part(&planet.name), house_name),
+                    &vars,
+                    Factor::PlanetInHouse {
+                        planet: planet.name.clone(),
+                        house,
+                    },
+                ));
+            }
+        }
+
+        let aspect_paragraphs = aspects
+            .iter()
+            .filter_map(|aspect| {
+                let vars = [
+                    ("planet", aspect.planet1.as_str()),
+                    ("planet2", aspect.planet2.as_str()),
+                    ("aspect", aspect.aspect.as_str()),
+                ];
+                let factor = Factor::Aspect {
+                    planet1: aspect.planet1.clone(),
+                    aspect: aspect.aspect.clone(),
+                    planet2: aspect.planet2.clone(),
+                    orb: aspect.orb,
+                };
+                let (planet1, kind, planet2) = (
+                    key_part(&aspect.planet1),
+                    key_part(&aspect.aspect),
+                    key_part(&aspect.planet2),
+                );
+                // Packs key each pair once, in either order
+                self.paragraph(format!("{}_{}_{}", planet1, kind, planet2), &vars, factor.clone())
+                    .or_else(|| self.paragraph(format!("{}_{}_{}", planet2, kind, planet1), &vars, factor))
+            })
+            .collect();
+
+        let mut ruler_paragraphs = Vec::new();
+        if let Some(ruler) = chart_ruler {
+            let vars = [("planet", ruler.planet.name.as_str()), ("sign", ruler.sign.as_str())];
+            let factor = Factor::ChartRuler {
+                planet: ruler.planet.name.clone(),
+                sign: ruler.sign.clone(),
+                retrograde: ruler.is_retrograde,
+                solar_condition: ruler.solar_condition,
+            };
+            ruler_paragraphs.extend(self.paragraph("chart_ruler".to_string(), &vars, factor.clone()));
+            if ruler.is_retrograde {
+                ruler_paragraphs.extend(self.paragraph("chart_ruler_retrograde".to_string(), &vars, factor.clone()));
+            }
+            let condition = match ruler.solar_condition {
+                Some(SolarCondition::Cazimi) => Some("cazimi"),
+                Some(SolarCondition::Combust) => Some("combust"),
+                Some(SolarCondition::UnderBeams) => Some("under_beams"),
+                Some(SolarCondition::Free) | None => None,
+            };
+            if let Some(condition) = condition {
+                ruler_paragraphs.extend(self.paragraph(format!("chart_ruler_{}", condition), &vars, factor));
+            }
+        }
+
+        let sections = [
+            (Category::PlanetInSign, in_sign),
+            (Category::PlanetInHouse, in_house),
+            (Category::Aspect, aspect_paragraphs),
+            (Category::ChartRuler, ruler_paragraphs),
+        ]
+        .into_iter()
+        .filter(|(_, paragraphs)| !paragraphs.is_empty())
+        .map(|(category, paragraphs)| Section { category, paragraphs })
+        .collect();
+
+        Interpretation {
+            pack: self.name.clone(),
+            sections,
+        }
+    }
+}
+
+static CONTENT_PACK: OnceLock<ContentPack> = OnceLock::new();
+
+/// Uses the pack at `path` for every interpretation, or the starter pack when
+/// `path` is `None`. Only the first call has any effect.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn init_content_pack(path: Option<&str>) -> Result<(), Box<dyn std::error::Error>> {
+    let pack = match path {
+        Some(path) => {
+            let pack = ContentPack::load_from_file(path)?;
+            log::info!("Loaded interpretation pack '{}' from {}", pack.name, path);
+            pack
+        }
+        None => ContentPack::starter(),
+    };
+    let _ = CONTENT_PACK.set(pack);
+    Ok(())
+}
+
+/// The configured content pack, falling back to the starter pack
+pub fn content_pack() -> &'static ContentPack {
+    CONTENT_PACK.get_or_init(ContentPack::starter)
+}
+
+/// Interprets a chart response with the configured content pack
+pub fn interpret_chart(chart: &ChartResponse) -> Interpretation {
+    content_pack().interpret(&chart.planets, &chart.aspects, chart.chart_ruler.as_ref())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::calc::planets::Motion;
+
+    fn planet(name: &str, longitude: f64, house: Option<u8>) -> PlanetInfo {
+        PlanetInfo {
+            name: name.to_string(),
+            longitude,
+            latitude: 0.0,
+            speed: 1.0,
+            is_retrograde: false,
+            motion: Motion::Direct,
+            formatted_position: String::new(),
+            house,
+            house_fraction: None,
+            decan: None,
+            mansion: None,
+            equatorial: None,
+        }
+    }
+
+    fn keys(interpretation: &Interpretation) -> Vec<&str> {
+        interpretation
+            .sections
+            .iter()
+            .flat_map(|s| &s.paragraphs)
+            .map(|p| p.key.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn test_sun_in_scorpio_in_the_fifth() {
+        let interpretation = ContentPack::starter().interpret(&[planet("Sun", 215.0, Some(5))], &[], None);
+        assert_eq!(keys(&interpretation), ["sun_in_scorpio", "sun_in_5th"]);
+        assert_eq!(interpretation.sections[0].category, Category::PlanetInSign);
+        let text = &interpretation.sections[0].paragraphs[0].text;
+        assert!(text.contains("Scorpio") && !text.contains("{sign}"));
+        assert!(interpretation.sections[1].paragraphs[0].text.contains("5th house"));
+    }
+
+    #[test]
+    fn test_missing_keys_are_skipped() {
+        let pack = ContentPack {
+            name: "tiny".to_string(),
+            snippets: HashMap::from([(
+                "moon_trine_sun".to_string(),
+                "{planet} {aspect} {planet2}".to_string(),
+            )]),
+        };
+        let aspects = [AspectInfo {
+            planet1: "Sun".to_string(),
+            planet2: "Moon".to_string(),
+            aspect: "Trine".to_string(),
+            orb: 1.5,
+            strength: 0.75,
+            exact: false,
+            applying: false,
+            exact_in_days: None,
+            exact_at: None,
+        }];
+        let interpretation = pack.interpret(&[planet("Sun", 215.0, Some(5))], &aspects, None);
+        assert_eq!(keys(&interpretation), ["moon_trine_sun"]);
+        assert_eq!(interpretation.sections[0].paragraphs[0].text, "Sun Trine Moon");
+        assert!(matches!(
+            interpretation.sections[0].paragraphs[0].factor,
+            Factor::Aspect { ref aspect, .. } if aspect == "Trine"
+        ));
+    }
+}
@@ -0,0 +1,434 @@
+//! Defaults from Astrolog's `astrolog.as` configuration file.
+//!
+//! An `astrolog.as` file is a list of command switches, one per line, with
+//! comments after a `;`. The switches that set chart defaults are read: `-c`
+//! (house system), `-A` (number of aspects), `-Ao` and `-YAo` (aspect orbs),
+//! `-R`, `-R0` and `-R1` (restricted objects), `-z` (time zone) and `-zl`
+//! (location). Aspects, objects and house systems can be given by Astrolog's
+//! numbers or names. Any other line is listed in `warnings` rather than
+//! failing the file, as is anything a switch sets that this crate can't chart.
+//!
+//! `init_defaults` makes one file's defaults those of every chart request that
+//! doesn't set its own.
+
+use crate::calc::aspects::{AspectType, OrbConfig};
+use crate::calc::planets::Planet;
+use crate::calc::positions::POSITION_BODIES;
+use crate::calc::transit_search::NATAL_PLANETS;
+use crate::core::types::HouseSystem;
+use std::path::Path;
+use std::sync::OnceLock;
+
+/// Astrolog's house systems by number, with the system each maps to here
+const HOUSE_SYSTEMS: [(&str, Option<HouseSystem>); 23] = [
+    ("Placidus", Some(HouseSystem::Placidus)),
+    ("Koch", Some(HouseSystem::Koch)),
+    ("Equal", Some(HouseSystem::Equal)),
+    ("Campanus", Some(HouseSystem::Campanus)),
+    ("Meridian", Some(HouseSystem::Meridian)),
+    ("Regiomontanus", Some(HouseSystem::Regiomontanus)),
+    ("Porphyry", Some(HouseSystem::Porphyrius)),
+    ("Morinus", Some(HouseSystem::Morinus)),
+    ("Topocentric", Some(HouseSystem::Topocentric)),
+    ("Alcabitius", Some(HouseSystem::Alcabitius)),
+    ("Krusinski", Some(HouseSystem::Krusinski)),
+    ("Equal (MC)", None),
+    ("Pullen (Sinusoidal Ratio)", None),
+    ("Pullen (Sinusoidal Delta)", None),
+    ("Whole", Some(HouseSystem::WholeSign)),
+    ("Vedic", Some(HouseSystem::Vedic)),
+    ("Sripati", None),
+    ("Horizon", None),
+    ("APC", None),
+    ("Carter", None),
+    ("Sunshine", Some(HouseSystem::Sunshine)),
+    ("Savard-A", None),
+    ("Null", Some(HouseSystem::Null)),
+];
+
+/// Astrolog's aspects by number, from 1, with their abbreviations
+const ASPECTS: [(&str, Option<AspectType>); 18] = [
+    ("Con", Some(AspectType::Conjunction)),
+    ("Opp", Some(AspectType::Opposition)),
+    ("Squ", Some(AspectType::Square)),
+    ("Tri", Some(AspectType::Trine)),
+    ("Sex", Some(AspectType::Sextile)),
+    ("Inc", Some(AspectType::Quincunx)),
+    ("SSx", Some(AspectType::SemiSextile)),
+    ("SSq", Some(AspectType::SemiSquare)),
+    ("Ses", Some(AspectType::Sesquisquare)),
+    ("Qui", Some(AspectType::Quintile)),
+    ("BQn", Some(AspectType::BiQuintile)),
+    ("SQn", None),
+    ("Sep", Some(AspectType::Septile)),
+    ("Nov", Some(AspectType::Novile)),
+    ("BNv", Some(AspectType::BiNovile)),
+    ("BSp", Some(AspectType::BiSeptile)),
+    ("TSp", Some(AspectType::TriSeptile)),
+    ("QNv", Some(AspectType::QuadNovile)),
+];
+
+/// Number of aspects Astrolog counts as the major ones
+const MAJOR_ASPECT_COUNT: usize = 5;
+
+/// Astrolog's objects by number, from the Earth, with the body each is here
+const OBJECTS: [(&str, Option<Planet>); 22] = [
+    ("Earth", None),
+    ("Sun", Some(Planet::Sun)),
+    ("Moon", Some(Planet::Moon)),
+    ("Mercury", Some(Planet::Mercury)),
+    ("Venus", Some(Planet::Venus)),
+    ("Mars", Some(Planet::Mars)),
+    ("Jupiter", Some(Planet::Jupiter)),
+    ("Saturn", Some(Planet::Saturn)),
+    ("Uranus", Some(Planet::Uranus)),
+    ("Neptune", Some(Planet::Neptune)),
+    ("Pluto", Some(Planet::Pluto)),
+    ("Chiron", Some(Planet::Chiron)),
+    ("Ceres", Some(Planet::Ceres)),
+    ("Pallas", Some(Planet::Pallas)),
+    ("Juno", Some(Planet::Juno)),
+    ("Vesta", Some(Planet::Vesta)),
+    ("Node", Some(Planet::TrueNode)),
+    ("South Node", None),
+    ("Lilith", Some(Planet::MeanLilith)),
+    ("Fortune", Some(Planet::Fortune)),
+    ("Vertex", Some(Planet::Vertex)),
+    ("East Point", Some(Planet::EastPoint)),
+];
+
+/// Chart defaults read from an `astrolog.as` file. Anything the file doesn't
+/// set is left to the crate's own defaults.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct AstrologDefaults {
+    /// From `-c`
+    pub house_system: Option<HouseSystem>,
+    /// From `-A`: whether more than the five major aspects are used
+    pub include_minor_aspects: Option<bool>,
+    /// From `-Ao` and `-YAo`, for natal aspects
+    pub orbs: OrbConfig,
+    /// Bodies besides the ten planets left unrestricted by `-R`, `-R0` or `-R1`
+    pub extra_bodies: Vec<Planet>,
+    /// From `-z`, in hours east of Greenwich
+    pub zone: Option<f64>,
+    /// From `-zl`: latitude and longitude in degrees, north and east positive
+    pub location: Option<(f64, f64)>,
+    /// Lines that were skipped or only partly applied, each with its line number
+    pub warnings: Vec<String>,
+}
+
+/// Reads the defaults from an `astrolog.as` file
+pub fn load_astrolog_config(path: impl AsRef<Path>) -> Result<AstrologDefaults, String> {
+    let path = path.as_ref();
+    let text = std::fs::read_to_string(path).map_err(|e| format!("Can't read {}: {}", path.display(), e))?;
+    Ok(parse_astrolog_config(&text))
+}
+
+/// Reads the defaults from the text of an `astrolog.as` file; lines that can't
+/// be used are listed in `warnings`
+pub fn parse_astrolog_config(text: &str) -> AstrologDefaults {
+    let mut defaults = AstrologDefaults::default();
+    for (index, line) in text.lines().enumerate() {
+        let line = line.split(';').next().unwrap_or_default().trim();
+        // "@AS700" and the like mark the file's version
+        if line.is_empty() || line.starts_with('@') {
+            continue;
+        }
+        let mut words = line.split_whitespace();
+        let switch = words.next().unwrap_or_default();
+        let args: Vec<&str> = words.collect();
+        if let Err(e) = defaults.apply(switch, &args) {
+            defaults.warnings.push(format!("line {}: {}", index + 1, e));
+        }
+    }
+    defaults
+}
+
+impl AstrologDefaults {
+    /// Applies one switch, or says why it can't be
+    fn apply(&mut self, switch: &str, args: &[&str]) -> Result<(), String> {
+        match switch {
+            "-c" => {
+                let arg = one_arg(switch, args)?;
+                self.house_system = Some(astrolog_house_system(arg)?);
+            }
+            "-A" => {
+                let count: usize = one_arg(switch, args)?
+                    .parse()
+                    .map_err(|_| format!("{} needs a number of aspects", switch))?;
+                if !(1..=ASPECTS.len()).contains(&count) {
+                    return Err(format!("{} takes 1 to {} aspects, not {}", switch, ASPECTS.len(), count));
+                }
+                self.include_minor_aspects = Some(count > MAJOR_ASPECT_COUNT);
+                if count != MAJOR_ASPECT_COUNT && count != ASPECTS.len() {
+                    return Err(format!(
+                        "{} {} is read as {} aspects; only the {} major ones or all of them can be chosen",
+                        switch,
+                        count,
+                        if count > MAJOR_ASPECT_COUNT { "all" } else { "the major" },
+                        MAJOR_ASPECT_COUNT
+                    ));
+                }
+            }
+            "-Ao" => {
+                let [aspect, orb] = args else {
+                    return Err(format!("{} needs an aspect and an orb", switch));
+                };
+                self.set_orbs(switch, aspect, aspect, &[orb])?;
+            }
+            "-YAo" => {
+                let [first, last, orbs @ ..] = args else {
+                    return Err(format!("{} needs the first and last aspect, then their orbs", switch));
+                };
+                self.set_orbs(switch, first, last, orbs)?;
+            }
+            "-R" | "-R0" | "-R1" => self.restrict(switch, args)?,
+            "-z" => {
+                let arg = one_arg(switch, args)?;
+                self.zone = Some(parse_zone(arg).ok_or_else(|| format!("Invalid time zone '{}'", arg))?);
+            }
+            "-zl" => {
+                let [longitude, latitude] = args else {
+                    return Err(format!("{} needs a longitude and a latitude", switch));
+                };
+                let longitude = parse_coordinate(longitude, 'E', 'W', 180.0)
+                    .ok_or_else(|| format!("Invalid longitude '{}', e.g. 122W19:59", longitude))?;
+                let latitude = parse_coordinate(latitude, 'N', 'S', 90.0)
+                    .ok_or_else(|| format!("Invalid latitude '{}', e.g. 47N36:35", latitude))?;
+                self.location = Some((latitude, longitude));
+            }
+            _ => return Err(format!("{} isn't a default this crate reads", switch)),
+        }
+        Ok(())
+    }
+
+    /// Sets the orbs of the aspects from `first` to `last` in Astrolog's
+    /// order, one orb each, the last orb repeating if there are fewer
+    fn set_orbs(&mut self, switch: &str, first: &str, last: &str, orbs: &[&str]) -> Result<(), String> {
+        let (first, last) = (astrolog_aspect(first)?, astrolog_aspect(last)?);
+        if first > last || orbs.is_empty() {
+            return Err(format!("{} needs a range of aspects, then their orbs", switch));
+        }
+        let mut skipped = Vec::new();
+        for (offset, number) in (first..=last).enumerate() {
+            let orb = orbs[offset.min(orbs.len() - 1)];
+            let orb: f64 = orb.parse().map_err(|_| format!("Invalid orb '{}'", orb))?;
+            if !(0.0..=180.0).contains(&orb) {
+                return Err(format!("Invalid orb {}", orb));
+            }
+            match ASPECTS[number - 1] {
+                (_, Some(aspect_type)) => self.orbs = std::mem::take(&mut self.orbs).with_orb(aspect_type, orb),
+                (name, None) => skipped.push(name),
+            }
+        }
+        if skipped.is_empty() {
+            Ok(())
+        } else {
+            Err(format!("There is no {} aspect here; its orb is ignored", skipped.join(", ")))
+        }
+    }
+
+    /// `-R` restricts the objects listed, or all but the ten planets with none
+    /// listed; `-R0` restricts all but those listed and `-R1` only those listed
+    fn restrict(&mut self, switch: &str, args: &[&str]) -> Result<(), String> {
+        let objects = args.iter().map(|arg| astrolog_object(arg)).collect::<Result<Vec<_>, _>>()?;
+        let extras = &POSITION_BODIES[NATAL_PLANETS.len()..];
+        let listed = |planet: &Planet| objects.contains(&Some(*planet));
+        let mut problems = Vec::new();
+        match switch {
+            "-R" if objects.is_empty() => self.extra_bodies.clear(),
+            "-R" => {
+                self.extra_bodies.retain(|planet| !listed(planet));
+                if NATAL_PLANETS.iter().any(listed) {
+                    problems.push("the ten planets are always in a chart".to_string());
+                }
+            }
+            "-R0" => {
+                self.extra_bodies = extras.iter().copied().filter(listed).collect();
+                if !NATAL_PLANETS.iter().all(listed) {
+                    problems.push("the ten planets are always in a chart".to_string());
+                }
+            }
+            _ => self.extra_bodies = extras.iter().copied().filter(|planet| !listed(planet)).collect(),
+        }
+        if switch != "-R" {
+            let unchartable: Vec<String> = args
+                .iter()
+                .zip(&objects)
+                .filter(|(_, object)| !object.is_some_and(|planet| POSITION_BODIES.contains(&planet)))
+                .map(|(arg, _)| arg.to_string())
+                .collect();
+            if !unchartable.is_empty() {
+                problems.push(format!("{} can't be added to a chart", unchartable.join(", ")));
+            }
+        }
+        if problems.is_empty() {
+            Ok(())
+        } else {
+            Err(format!("{} partly applied: {}", switch, problems.join("; ")))
+        }
+    }
+}
+
+/// The only argument of a switch
+fn one_arg<'a>(switch: &str, args: &[&'a str]) -> Result<&'a str, String> {
+    match args {
+        [arg] => Ok(arg),
+        _ => Err(format!("{} takes one value", switch)),
+    }
+}
+
+/// Index into `table` of an Astrolog number counted from `first`, or of a name
+/// the argument is the start of (at least three letters)
+fn lookup<T>(table: &[(&str, T)], first: usize, arg: &str) -> Option<usize> {
+    if let Ok(number) = arg.parse::<usize>() {
+        return number.checked_sub(first).filter(|&index| index < table.len());
+    }
+    let arg = arg.to_lowercase();
+    if arg.len() < 3 {
+        return None;
+    }
+    table.iter().position(|(name, _)| name.to_lowercase().starts_with(&arg))
+}
+
+/// A house system by Astrolog's number or name, or by this crate's name for it
+fn astrolog_house_system(arg: &str) -> Result<HouseSystem, String> {
+    match lookup(&HOUSE_SYSTEMS, 0, arg) {
+        Some(index) => HOUSE_SYSTEMS[index]
+            .1
+            .ok_or_else(|| format!("There is no {} house system here", HOUSE_SYSTEMS[index].0)),
+        None => arg.parse(),
+    }
+}
+
+/// An aspect's Astrolog number, from its number or abbreviation
+fn astrolog_aspect(arg: &str) -> Result<usize, String> {
+    lookup(&ASPECTS, 1, arg)
+        .map(|index| index + 1)
+        .ok_or_else(|| format!("Unknown aspect '{}'", arg))
+}
+
+/// An object by Astrolog's number or name, or by this crate's name for it;
+/// `None` for objects with no body here
+fn astrolog_object(arg: &str) -> Result<Option<Planet>, String> {
+    match lookup(&OBJECTS, 0, arg) {
+        Some(index) => Ok(OBJECTS[index].1),
+        None => arg.parse().map(Some),
+    }
+}
+
+/// Hours and optional minutes, "8:00W", "5E30" or a bare "8" (Astrolog's
+/// convention: hours behind Greenwich), as hours east of Greenwich
+fn parse_zone(arg: &str) -> Option<f64> {
+    let upper = arg.to_uppercase();
+    let (sign, value) = match upper.find(['E', 'W']) {
+        Some(at) => {
+            let sign = if upper[at..].starts_with('E') { 1.0 } else { -1.0 };
+            (sign, format!("{}:{}", &upper[..at], &upper[at + 1..]))
+        }
+        None => (-1.0, upper.clone()),
+    };
+    let (hours, minutes) = value.split_once(':').unwrap_or((&value, ""));
+    // "8:00W" leaves an empty part after the letter
+    let minutes = minutes.trim_end_matches(':');
+    let hours: f64 = hours.parse().ok()?;
+    let minutes: f64 = if minutes.is_empty() { 0.0 } else { minutes.parse().ok()? };
+    if !(0.0..60.0).contains(&minutes) {
+        return None;
+    }
+    let magnitude = hours.abs() + minutes / 60.0;
+    let zone = sign * if hours.is_sign_negative() { -magnitude } else { magnitude };
+    (zone.abs() <= 24.0).then_some(zone)
+}
+
+/// Degrees, a hemisphere letter, then optional minutes and seconds, e.g.
+/// "122W19:59", as signed degrees with `positive` hemispheres above zero
+fn parse_coordinate(arg: &str, positive: char, negative: char, limit: f64) -> Option<f64> {
+    let upper = arg.to_uppercase();
+    let at = upper.find([positive, negative])?;
+    let sign = if upper[at..].starts_with(positive) { 1.0 } else { -1.0 };
+    let degrees: f64 = upper[..at].parse().ok()?;
+    let mut fraction = 0.0;
+    let rest = &upper[at + 1..];
+    if !rest.is_empty() {
+        for (part, scale) in rest.split(':').zip([60.0, 3600.0]) {
+            let value: f64 = part.parse().ok()?;
+            if !(0.0..60.0).contains(&value) {
+                return None;
+            }
+            fraction += value / scale;
+        }
+    }
+    let value = degrees + fraction;
+    (degrees >= 0.0 && value <= limit).then_some(sign * value)
+}
+
+static DEFAULTS: OnceLock<AstrologDefaults> = OnceLock::new();
+
+/// Makes these the defaults of chart requests that don't set their own; only
+/// the first call has any effect
+pub fn init_defaults(defaults: AstrologDefaults) {
+    let _ = DEFAULTS.set(defaults);
+}
+
+/// The defaults `init_defaults` was given, if it has been called
+pub fn defaults() -> Option<&'static AstrologDefaults> {
+    DEFAULTS.get()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fixture_defaults() {
+        let defaults = parse_astrolog_config(include_str!("../../tests/fixtures/astrolog.as"));
+        assert_eq!(defaults.house_system, Some(HouseSystem::Koch));
+        assert_eq!(defaults.include_minor_aspects, Some(false));
+        assert_eq!(defaults.orbs.orb(AspectType::Conjunction), 12.0);
+        assert_eq!(defaults.orbs.orb(AspectType::Opposition), 9.0);
+        assert_eq!(defaults.orbs.orb(AspectType::Sextile), 6.0);
+        // Untouched aspects keep their usual orbs
+        assert_eq!(defaults.orbs.orb(AspectType::Quintile), AspectType::Quintile.orb());
+        assert_eq!(defaults.extra_bodies, [Planet::TrueNode, Planet::MeanLilith, Planet::Chiron]);
+        assert_eq!(defaults.zone, Some(-8.0));
+        let (latitude, longitude) = defaults.location.unwrap();
+        assert!((latitude - (47.0 + 36.0 / 60.0 + 35.0 / 3600.0)).abs() < 1e-9);
+        assert!((longitude + (122.0 + 19.0 / 60.0 + 59.0 / 3600.0)).abs() < 1e-9);
+        // The daylight switch and a display switch
+        assert_eq!(defaults.warnings.len(), 2, "{:?}", defaults.warnings);
+        assert!(defaults.warnings[0].starts_with("line 3: -z0"), "{:?}", defaults.warnings);
+    }
+
+    #[test]
+    fn test_switch_values() {
+        assert_eq!(astrolog_house_system("0"), Ok(HouseSystem::Placidus));
+        assert_eq!(astrolog_house_system("Plac"), Ok(HouseSystem::Placidus));
+        assert_eq!(astrolog_house_system("14"), Ok(HouseSystem::WholeSign));
+        assert_eq!(astrolog_house_system("whole_sign"), Ok(HouseSystem::WholeSign));
+        assert!(astrolog_house_system("17").unwrap_err().contains("Horizon"));
+        assert_eq!(astrolog_aspect("Sex"), Ok(5));
+        assert_eq!(astrolog_aspect("18"), Ok(18));
+        assert!(astrolog_aspect("19").is_err());
+        assert_eq!(astrolog_object("16"), Ok(Some(Planet::TrueNode)));
+        assert_eq!(astrolog_object("east_point"), Ok(Some(Planet::EastPoint)));
+
+        assert_eq!(parse_zone("8:00W"), Some(-8.0));
+        assert_eq!(parse_zone("5E30"), Some(5.5));
+        assert_eq!(parse_zone("-1"), Some(1.0));
+        assert_eq!(parse_zone("0W30"), Some(-0.5));
+        assert_eq!(parse_zone("8:75W"), None);
+        assert_eq!(parse_coordinate("0E30", 'E', 'W', 180.0), Some(0.5));
+        assert_eq!(parse_coordinate("91N", 'N', 'S', 90.0), None);
+        assert_eq!(parse_coordinate("-122.3", 'E', 'W', 180.0), None);
+
+        let defaults = parse_astrolog_config("-A 18\n-YAo 10 12 1.5\n-R\n-c Horizon\n_k\n");
+        assert_eq!(defaults.include_minor_aspects, Some(true));
+        assert_eq!(defaults.orbs.orb(AspectType::Quintile), 1.5);
+        assert_eq!(defaults.orbs.orb(AspectType::BiQuintile), 1.5);
+        assert!(defaults.extra_bodies.is_empty());
+        assert_eq!(defaults.house_system, None);
+        let lines: Vec<&str> = defaults.warnings.iter().map(|w| &w[..6]).collect();
+        assert_eq!(lines, ["line 2", "line 4", "line 5"]);
+    }
+}
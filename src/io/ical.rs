@@ -0,0 +1,211 @@
+//! Exact transits as an iCalendar (RFC 5545) feed.
+//!
+//! Each hit is a `VEVENT` at its exact moment, described with the dates it
+//! came within and went out of `BUILD_UP_ORB`. Event UIDs hash the natal id,
+//! the transit and its day, so a calendar client refreshing the feed updates
+//! the events it already has instead of adding them again. Natal points on a
+//! recent eclipse's degree are events at the eclipse.
+
+use crate::calc::eclipses::EclipseContact;
+use crate::calc::transit_search::{TransitHit, BUILD_UP_ORB};
+use crate::calc::utils::julian_to_date;
+use crate::data::i18n::Lang;
+use crate::utils::fnv1a;
+use chrono::{DateTime, Utc};
+use std::fmt::Write;
+
+/// Longest content line in octets, not counting the CRLF
+const MAX_LINE_OCTETS: usize = 75;
+
+/// Escapes a TEXT value: backslashes, semicolons, commas and line breaks
+pub fn escape_text(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '\\' => out.push_str("\\\\"),
+            ';' => out.push_str("\\;"),
+            ',' => out.push_str("\\,"),
+            '\n' => out.push_str("\\n"),
+            '\r' => {}
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+/// Appends a content line, folded onto continuation lines starting with a
+/// space so none is longer than `MAX_LINE_OCTETS`
+fn push_line(out: &mut String, line: &str) {
+    let mut octets = 0;
+    for c in line.chars() {
+        if octets + c.len_utf8() > MAX_LINE_OCTETS {
+            out.push_str("\r\n ");
+            octets = 1;
+        }
+        out.push(c);
+        octets += c.len_utf8();
+    }
+    out.push_str("\r\n");
+}
+
+fn timestamp(date: DateTime<Utc>) -> String {
+    date.format("%Y%m%dT%H%M%SZ").to_string()
+}
+
+fn day(jd: Option<f64>) -> Option<String> {
+    julian_to_date(jd?).map(|date| date.format("%Y-%m-%d").to_string())
+}
+
+/// The summary of a hit, e.g. "Transiting Saturn square natal Sun"
+pub fn summary(hit: &TransitHit) -> String {
+    format!("Transiting {} {} natal {}", hit.transiting, hit.aspect.to_string().to_lowercase(), hit.natal)
+}
+
+/// A UID that stays the same for the hit whenever the feed is generated
+pub fn event_uid(natal_id: &str, hit: &TransitHit, exact: DateTime<Utc>) -> String {
+    let key = format!("{}|{}|{}|{}|{}", natal_id, hit.transiting, hit.aspect, hit.natal, exact.format("%Y-%m-%d"));
+    format!("{:016x}@astrolog-rs", fnv1a(&key))
+}
+
+fn description(hit: &TransitHit, exact: DateTime<Utc>) -> String {
+    let mut text = format!("Exact at {}.", exact.format("%Y-%m-%d %H:%M UTC"));
+    match day(hit.orb_entered) {
+        Some(date) => {
+            let _ = write!(text, "\nWithin {}° of exact from {}.", BUILD_UP_ORB, date);
+        }
+        None => text.push_str("\nWithin orb since before the search."),
+    }
+    match day(hit.orb_left) {
+        Some(date) => {
+            let _ = write!(text, "\nOut of orb from {}.", date);
+        }
+        None => text.push_str("\nStill within orb after the search."),
+    }
+    text
+}
+
+/// The summary of an eclipse contact, e.g. "Solar eclipse at 19°24' Aries on natal Sun"
+pub fn eclipse_summary(contact: &EclipseContact) -> String {
+    format!(
+        "{} eclipse at {} on natal {}",
+        contact.eclipse.kind,
+        Lang::En.format_position(contact.eclipse.longitude),
+        contact.natal
+    )
+}
+
+fn eclipse_description(contact: &EclipseContact, exact: DateTime<Utc>) -> String {
+    format!(
+        "{} eclipse at {}, {:.2}° from natal {}.",
+        contact.eclipse.kind,
+        exact.format("%Y-%m-%d %H:%M UTC"),
+        contact.orb.abs(),
+        contact.natal
+    )
+}
+
+/// Appends a `VEVENT` at `exact`
+fn push_event(out: &mut String, uid: &str, generated: DateTime<Utc>, exact: DateTime<Utc>, summary: &str, description: &str) {
+    push_line(out, "BEGIN:VEVENT");
+    push_line(out, &format!("UID:{}", uid));
+    push_line(out, &format!("DTSTAMP:{}", timestamp(generated)));
+    push_line(out, &format!("DTSTART:{}", timestamp(exact)));
+    push_line(out, &format!("DTEND:{}", timestamp(exact)));
+    push_line(out, &format!("SUMMARY:{}", escape_text(summary)));
+    push_line(out, &format!("DESCRIPTION:{}", escape_text(description)));
+    push_line(out, "TRANSP:TRANSPARENT");
+    push_line(out, "END:VEVENT");
+}
+
+/// The hits and eclipse contacts as a `VCALENDAR` named `name`, stamped
+/// `generated`, in time order. `natal_id` identifies the natal chart in the
+/// event UIDs.
+pub fn transit_calendar(
+    name: &str,
+    natal_id: &str,
+    hits: &[TransitHit],
+    eclipses: &[EclipseContact],
+    generated: DateTime<Utc>,
+) -> String {
+    // (moment, UID, summary, description) of every event
+    let mut events = Vec::with_capacity(hits.len() + eclipses.len());
+    for hit in hits {
+        if let Some(exact) = julian_to_date(hit.jd) {
+            events.push((exact, event_uid(natal_id, hit, exact), summary(hit), description(hit, exact)));
+        }
+    }
+    for contact in eclipses {
+        if let Some(exact) = julian_to_date(contact.eclipse.jd) {
+            let key = format!("{}|{} eclipse|{}|{}", natal_id, contact.eclipse.kind, contact.natal, exact.format("%Y-%m-%d"));
+            let uid = format!("{:016x}@astrolog-rs", fnv1a(&key));
+            events.push((exact, uid, eclipse_summary(contact), eclipse_description(contact, exact)));
+        }
+    }
+    events.sort_by_key(|(exact, ..)| *exact);
+
+    let mut out = String::new();
+    push_line(&mut out, "BEGIN:VCALENDAR");
+    push_line(&mut out, "VERSION:2.0");
+    push_line(&mut out, "PRODID:-//astrolog-rs//Transits//EN");
+    push_line(&mut out, "CALSCALE:GREGORIAN");
+    push_line(&mut out, "METHOD:PUBLISH");
+    push_line(&mut out, &format!("X-WR-CALNAME:{}", escape_text(name)));
+    for (exact, uid, summary, description) in &events {
+        push_event(&mut out, uid, generated, *exact, summary, description);
+    }
+    push_line(&mut out, "END:VCALENDAR");
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::calc::aspects::AspectType;
+    use crate::calc::planets::Planet;
+
+    fn hit() -> TransitHit {
+        TransitHit {
+            transiting: Planet::Saturn.into(),
+            natal: "Sun".to_string(),
+            aspect: AspectType::Square,
+            // 2024-01-01 12:00 UTC
+            jd: 2460311.0,
+            orb_entered: Some(2460290.0),
+            orb_left: None,
+        }
+    }
+
+    #[test]
+    fn test_text_is_escaped_and_lines_folded() {
+        assert_eq!(escape_text("a\\b;c,d\ne\r"), "a\\\\b\\;c\\,d\\ne");
+        let mut out = String::new();
+        push_line(&mut out, &format!("DESCRIPTION:{}", "é".repeat(60)));
+        let lines: Vec<&str> = out.split("\r\n").collect();
+        assert!(lines.len() > 2);
+        assert!(lines.iter().all(|line| line.len() <= MAX_LINE_OCTETS));
+        assert!(lines[1..].iter().filter(|line| !line.is_empty()).all(|line| line.starts_with(' ')));
+        // Unfolding gives the line back
+        assert_eq!(out.replace("\r\n ", ""), format!("DESCRIPTION:{}\r\n", "é".repeat(60)));
+    }
+
+    #[test]
+    fn test_calendar_has_an_event_per_hit() {
+        let generated = "2024-01-05T00:00:00Z".parse().unwrap();
+        let calendar = transit_calendar("Transits, natal", "1990-05-15T14:30:00Z", &[hit()], &[], generated);
+        assert!(calendar.starts_with("BEGIN:VCALENDAR\r\nVERSION:2.0\r\n"));
+        assert!(calendar.ends_with("END:VCALENDAR\r\n"));
+        assert!(calendar.contains("X-WR-CALNAME:Transits\\, natal\r\n"));
+        assert!(calendar.contains("SUMMARY:Transiting Saturn square natal Sun\r\n"));
+        assert!(calendar.contains("DTSTART:20240101T120000Z\r\n"));
+        assert!(calendar.contains("DTSTAMP:20240105T000000Z\r\n"));
+        let unfolded = calendar.replace("\r\n ", "");
+        assert!(unfolded.contains("DESCRIPTION:Exact at 2024-01-01 12:00 UTC.\\nWithin 1° of exact from 2023-12-11.\\nStill within orb after the search.\r\n"));
+
+        // The UID depends on the natal chart and the transit, not when the feed was made
+        let uid = event_uid("1990-05-15T14:30:00Z", &hit(), julian_to_date(hit().jd).unwrap());
+        assert!(calendar.contains(&format!("UID:{}\r\n", uid)));
+        let later = transit_calendar("Transits", "1990-05-15T14:30:00Z", &[hit()], &[], "2024-02-01T00:00:00Z".parse().unwrap());
+        assert!(later.contains(&format!("UID:{}\r\n", uid)));
+        assert_ne!(uid, event_uid("1990-05-16T14:30:00Z", &hit(), julian_to_date(hit().jd).unwrap()));
+    }
+}
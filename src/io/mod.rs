@@ -1,5 +1,13 @@
+#[cfg(feature = "precompute")]
+pub mod precompute;
+pub mod astrolog_config;
+pub mod ical;
+pub mod text;
+
 use crate::core::types::{AstrologError, Chart};
 
+pub use text::{render_listing, render_listing_with, ListingOptions};
+
 /// Save a chart to a file
 #[allow(dead_code)]
 pub fn save_chart(_chart: &Chart, _filename: &str) -> Result<(), AstrologError> {
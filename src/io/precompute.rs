@@ -0,0 +1,429 @@
+//! Positions for every step of a date range, for datasets and exports.
+//!
+//! The moments are split into chunks. Each chunk is calculated in parallel on
+//! the rayon pool and written before the next one starts, so a 200-year run at
+//! daily steps never holds more than one chunk in memory. A cancel flag is
+//! checked between chunks, and the writer is always finished, so stopping early
+//! still leaves a complete file of the moments done so far.
+//!
+//! With the Swiss Ephemeris backend the library calls are serialized, so the
+//! parallelism mostly helps the analytic backend.
+
+use crate::calc::ephemeris::Ephemeris;
+use crate::calc::planets::{planet_position, Planet};
+use crate::calc::utils::date_to_julian;
+use crate::core::types::EphemerisSource;
+use chrono::{DateTime, SecondsFormat, TimeDelta, Utc};
+use rayon::prelude::*;
+use std::io::Write;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Moments calculated and written together
+pub const CHUNK_MOMENTS: usize = 1000;
+
+/// Most moments one run may cover, a million years of days
+pub const MAX_MOMENTS: usize = 365_250_000;
+
+/// Moments from `from` to `to`, both included, every `step`
+#[derive(Debug, Clone, PartialEq)]
+pub struct DateRange {
+    pub from: DateTime<Utc>,
+    pub to: DateTime<Utc>,
+    pub step: TimeDelta,
+}
+
+impl DateRange {
+    /// A range, refusing an empty one, a step that isn't positive and more
+    /// than `MAX_MOMENTS` moments
+    pub fn new(from: DateTime<Utc>, to: DateTime<Utc>, step: TimeDelta) -> Result<Self, String> {
+        if step <= TimeDelta::zero() {
+            return Err("The step must be longer than zero".to_string());
+        }
+        if to < from {
+            return Err(format!("The range ends ({}) before it starts ({})", to, from));
+        }
+        let range = Self { from, to, step };
+        if range.moments() > MAX_MOMENTS {
+            return Err(format!("The range has over {} moments; use a longer step", MAX_MOMENTS));
+        }
+        Ok(range)
+    }
+
+    /// Number of moments in the range
+    pub fn moments(&self) -> usize {
+        match ((self.to - self.from).num_milliseconds(), self.step.num_milliseconds()) {
+            (span, step) if step > 0 => (span / step) as usize + 1,
+            _ => 1,
+        }
+    }
+
+    /// The moment `index` steps from the start
+    pub fn moment(&self, index: usize) -> DateTime<Utc> {
+        self.from + self.step * index as i32
+    }
+}
+
+/// Parses a step such as "1d", "6h", "30m" or "90s"
+pub fn parse_step(step: &str) -> Result<TimeDelta, String> {
+    let step = step.trim();
+    let split = step.find(|c: char| !c.is_ascii_digit()).unwrap_or(step.len());
+    let (count, unit) = step.split_at(split);
+    let count: i64 = count.parse().map_err(|_| format!("Invalid step '{}' (expected e.g. 1d, 6h or 30m)", step))?;
+    let delta = match unit {
+        "d" => TimeDelta::try_days(count),
+        "h" => TimeDelta::try_hours(count),
+        "m" => TimeDelta::try_minutes(count),
+        "s" => TimeDelta::try_seconds(count),
+        _ => None,
+    };
+    match delta {
+        Some(delta) if count > 0 => Ok(delta),
+        _ => Err(format!("Invalid step '{}' (expected e.g. 1d, 6h or 30m)", step)),
+    }
+}
+
+/// One body at one moment
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PositionRow {
+    pub date: DateTime<Utc>,
+    pub julian_day: f64,
+    pub planet: Planet,
+    pub longitude: f64,
+    pub latitude: f64,
+    /// Degrees per day, negative when retrograde
+    pub speed: f64,
+}
+
+/// Where the rows of a run go, a chunk at a time
+pub trait RowWriter {
+    fn write_rows(&mut self, rows: &[PositionRow]) -> Result<(), String>;
+
+    /// Completes the output; called once, after the last chunk
+    fn finish(&mut self) -> Result<(), String>;
+}
+
+/// Comma-separated rows under a header: date, julian_day, body, longitude,
+/// latitude, speed
+pub struct CsvWriter<W: Write> {
+    out: W,
+    header_written: bool,
+}
+
+impl<W: Write> CsvWriter<W> {
+    pub fn new(out: W) -> Self {
+        Self {
+            out,
+            header_written: false,
+        }
+    }
+
+    fn write_header(&mut self) -> Result<(), String> {
+        if !self.header_written {
+            writeln!(self.out, "date,julian_day,body,longitude,latitude,speed").map_err(|e| e.to_string())?;
+            self.header_written = true;
+        }
+        Ok(())
+    }
+}
+
+impl<W: Write> RowWriter for CsvWriter<W> {
+    fn write_rows(&mut self, rows: &[PositionRow]) -> Result<(), String> {
+        self.write_header()?;
+        for row in rows {
+            writeln!(
+                self.out,
+                "{},{:.6},{:?},{:.6},{:.6},{:.6}",
+                row.date.to_rfc3339_opts(SecondsFormat::Secs, true),
+                row.julian_day,
+                row.planet,
+                row.longitude,
+                row.latitude,
+                row.speed
+            )
+            .map_err(|e| e.to_string())?;
+        }
+        Ok(())
+    }
+
+    fn finish(&mut self) -> Result<(), String> {
+        // An empty run still gets its header
+        self.write_header()?;
+        self.out.flush().map_err(|e| e.to_string())
+    }
+}
+
+/// The same columns as the CSV in a Parquet file, one row group per chunk,
+/// with the date as a UTC timestamp in milliseconds
+#[cfg(feature = "parquet")]
+pub struct ParquetWriter<W: Write + Send> {
+    writer: Option<parquet::file::writer::SerializedFileWriter<W>>,
+}
+
+#[cfg(feature = "parquet")]
+impl<W: Write + Send> ParquetWriter<W> {
+    pub fn new(out: W) -> Result<Self, String> {
+        use parquet::schema::parser::parse_message_type;
+        use std::sync::Arc;
+
+        let schema = parse_message_type(
+            "message positions {
+                REQUIRED INT64 date (TIMESTAMP(MILLIS, true));
+                REQUIRED DOUBLE julian_day;
+                REQUIRED BYTE_ARRAY body (UTF8);
+                REQUIRED DOUBLE longitude;
+                REQUIRED DOUBLE latitude;
+                REQUIRED DOUBLE speed;
+            }",
+        )
+        .map_err(|e| e.to_string())?;
+        let properties = parquet::file::properties::WriterProperties::builder().build();
+        let writer = parquet::file::writer::SerializedFileWriter::new(out, Arc::new(schema), Arc::new(properties))
+            .map_err(|e| e.to_string())?;
+        Ok(Self { writer: Some(writer) })
+    }
+}
+
+#[cfg(feature = "parquet")]
+impl<W: Write + Send> RowWriter for ParquetWriter<W> {
+    fn write_rows(&mut self, rows: &[PositionRow]) -> Result<(), String> {
+        use parquet::data_type::{ByteArray, ByteArrayType, DoubleType, Int64Type};
+
+        let writer = self.writer.as_mut().ok_or("The Parquet file is already finished")?;
+        let mut group = writer.next_row_group().map_err(|e| e.to_string())?;
+        let doubles: [fn(&PositionRow) -> f64; 4] = [|r| r.julian_day, |r| r.longitude, |r| r.latitude, |r| r.speed];
+        let mut column_index = 0;
+        while let Some(mut column) = group.next_column().map_err(|e| e.to_string())? {
+            let written = match column_index {
+                0 => {
+                    let dates: Vec<i64> = rows.iter().map(|r| r.date.timestamp_millis()).collect();
+                    column.typed::<Int64Type>().write_batch(&dates, None, None)
+                }
+                2 => {
+                    let bodies: Vec<ByteArray> = rows.iter().map(|r| ByteArray::from(r.planet.to_string().as_str())).collect();
+                    column.typed::<ByteArrayType>().write_batch(&bodies, None, None)
+                }
+                i => {
+                    let value = doubles[if i == 1 { 0 } else { i - 2 }];
+                    let values: Vec<f64> = rows.iter().map(value).collect();
+                    column.typed::<DoubleType>().write_batch(&values, None, None)
+                }
+            };
+            written.map_err(|e| e.to_string())?;
+            column.close().map_err(|e| e.to_string())?;
+            column_index += 1;
+        }
+        group.close().map_err(|e| e.to_string())?;
+        Ok(())
+    }
+
+    fn finish(&mut self) -> Result<(), String> {
+        match self.writer.take() {
+            Some(writer) => writer.close().map(|_| ()).map_err(|e| e.to_string()),
+            None => Ok(()),
+        }
+    }
+}
+
+/// How far a run has got, reported after each chunk
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Progress {
+    pub moments_done: usize,
+    pub moments_total: usize,
+}
+
+/// What a run wrote
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PrecomputeSummary {
+    pub moments: usize,
+    pub rows: usize,
+    /// Whether the cancel flag stopped the run before the end of the range
+    pub cancelled: bool,
+}
+
+/// Calculates the bodies at every moment of `range` and streams the rows to
+/// `writer`, in date order with the bodies in the order given.
+///
+/// `cancel` is checked before each chunk; once set, the run stops there and the
+/// writer is finished. A body the ephemeris can't place fails the run, naming
+/// the moment, after finishing the writer on the chunks already done.
+pub fn precompute_positions(
+    ephemeris: &dyn Ephemeris,
+    source: EphemerisSource,
+    range: &DateRange,
+    bodies: &[Planet],
+    writer: &mut dyn RowWriter,
+    cancel: &AtomicBool,
+    mut progress: impl FnMut(Progress),
+) -> Result<PrecomputeSummary, String> {
+    let total = range.moments();
+    let mut summary = PrecomputeSummary {
+        moments: 0,
+        rows: 0,
+        cancelled: false,
+    };
+    let mut result = Ok(());
+    for start in (0..total).step_by(CHUNK_MOMENTS) {
+        if cancel.load(Ordering::SeqCst) {
+            summary.cancelled = true;
+            break;
+        }
+        let end = (start + CHUNK_MOMENTS).min(total);
+        let chunk: Result<Vec<Vec<PositionRow>>, String> = (start..end)
+            .into_par_iter()
+            .map(|index| moment_rows(ephemeris, source, range.moment(index), bodies))
+            .collect();
+        let rows: Vec<PositionRow> = match chunk {
+            Ok(rows) => rows.into_iter().flatten().collect(),
+            Err(e) => {
+                result = Err(e);
+                break;
+            }
+        };
+        if let Err(e) = writer.write_rows(&rows) {
+            result = Err(e);
+            break;
+        }
+        summary.moments = end;
+        summary.rows += rows.len();
+        progress(Progress {
+            moments_done: end,
+            moments_total: total,
+        });
+    }
+    writer.finish()?;
+    result.map(|_| summary)
+}
+
+fn moment_rows(ephemeris: &dyn Ephemeris, source: EphemerisSource, date: DateTime<Utc>, bodies: &[Planet]) -> Result<Vec<PositionRow>, String> {
+    let julian_day = date_to_julian(date);
+    bodies
+        .iter()
+        .map(|&planet| {
+            let (position, _) = planet_position(ephemeris, planet, julian_day, source)
+                .map_err(|e| format!("{:?} at {}: {}", planet, date.to_rfc3339_opts(SecondsFormat::Secs, true), e))?;
+            Ok(PositionRow {
+                date,
+                julian_day,
+                planet,
+                longitude: position.longitude,
+                latitude: position.latitude,
+                speed: position.speed,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::calc::ephemeris::AnalyticEphemeris;
+    use chrono::TimeZone;
+
+    fn date(year: i32, month: u32, day: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(year, month, day, 0, 0, 0).unwrap()
+    }
+
+    /// The CSV of a run on the analytic ephemeris, setting `cancel` after
+    /// the first chunk when `stop_early`, as Ctrl-C would
+    fn csv(range: &DateRange, bodies: &[Planet], stop_early: bool) -> (PrecomputeSummary, String, Vec<Progress>) {
+        let cancel = AtomicBool::new(false);
+        let mut out = Vec::new();
+        let mut reports = Vec::new();
+        let summary = precompute_positions(
+            &AnalyticEphemeris,
+            EphemerisSource::Analytic,
+            range,
+            bodies,
+            &mut CsvWriter::new(&mut out),
+            &cancel,
+            |progress| {
+                reports.push(progress);
+                cancel.store(stop_early, Ordering::SeqCst);
+            },
+        )
+        .unwrap();
+        (summary, String::from_utf8(out).unwrap(), reports)
+    }
+
+    #[test]
+    fn test_rows_cover_the_range_in_date_order() {
+        // 2500 days spans three chunks, the last one partial
+        let range = DateRange::new(date(2000, 1, 1), date(2006, 11, 4), parse_step("1d").unwrap()).unwrap();
+        assert_eq!(range.moments(), 2500);
+        let bodies = [Planet::Sun, Planet::Moon, Planet::Mars];
+        let (summary, csv, reports) = csv(&range, &bodies, false);
+        assert_eq!(summary, PrecomputeSummary { moments: 2500, rows: 7500, cancelled: false });
+        let done: Vec<usize> = reports.iter().map(|p| p.moments_done).collect();
+        assert_eq!(done, [1000, 2000, 2500]);
+        assert!(reports.iter().all(|p| p.moments_total == 2500));
+
+        let mut lines = csv.lines();
+        assert_eq!(lines.next(), Some("date,julian_day,body,longitude,latitude,speed"));
+        let rows: Vec<Vec<&str>> = lines.map(|line| line.split(',').collect()).collect();
+        assert_eq!(rows.len(), 7500);
+        assert_eq!(rows[0][0], "2000-01-01T00:00:00Z");
+        assert_eq!(rows[7499][0], "2006-11-04T00:00:00Z");
+        for (i, moment) in rows.chunks(bodies.len()).enumerate() {
+            let names: Vec<&str> = moment.iter().map(|row| row[2]).collect();
+            assert_eq!(names, ["Sun", "Moon", "Mars"]);
+            assert!(moment.iter().all(|row| row[0] == moment[0][0]));
+            assert_eq!(moment[0][0].parse::<DateTime<Utc>>().unwrap(), range.moment(i));
+        }
+        let julian_days: Vec<f64> = rows.iter().step_by(bodies.len()).map(|row| row[1].parse().unwrap()).collect();
+        assert!(julian_days.windows(2).all(|pair| pair[1] > pair[0]));
+    }
+
+    #[test]
+    fn test_cancelling_finishes_the_current_chunk() {
+        let range = DateRange::new(date(2000, 1, 1), date(2010, 1, 1), parse_step("1d").unwrap()).unwrap();
+        let (summary, csv, reports) = csv(&range, &[Planet::Sun], true);
+        assert_eq!(summary, PrecomputeSummary { moments: CHUNK_MOMENTS, rows: CHUNK_MOMENTS, cancelled: true });
+        assert_eq!(reports.len(), 1);
+        assert_eq!(csv.lines().count(), CHUNK_MOMENTS + 1);
+        assert!(csv.ends_with('\n'));
+    }
+
+    #[cfg(feature = "parquet")]
+    #[test]
+    fn test_parquet_has_a_row_group_per_chunk() {
+        use parquet::file::reader::{FileReader, SerializedFileReader};
+
+        let range = DateRange::new(date(2000, 1, 1), date(2003, 1, 1), parse_step("1d").unwrap()).unwrap();
+        let path = std::env::temp_dir().join(format!("astrolog-precompute-{}.parquet", std::process::id()));
+        let mut writer = ParquetWriter::new(std::fs::File::create(&path).unwrap()).unwrap();
+        let bodies = [Planet::Sun, Planet::Moon];
+        let summary = precompute_positions(
+            &AnalyticEphemeris,
+            EphemerisSource::Analytic,
+            &range,
+            &bodies,
+            &mut writer,
+            &AtomicBool::new(false),
+            |_| {},
+        )
+        .unwrap();
+        let reader = SerializedFileReader::new(std::fs::File::open(&path).unwrap()).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        let metadata = reader.metadata();
+        assert_eq!(metadata.file_metadata().num_rows() as usize, summary.rows);
+        assert_eq!(summary.rows, range.moments() * bodies.len());
+        assert_eq!(metadata.num_row_groups(), 2);
+        let columns: Vec<&str> = metadata.file_metadata().schema_descr().columns().iter().map(|c| c.name()).collect();
+        assert_eq!(columns, ["date", "julian_day", "body", "longitude", "latitude", "speed"]);
+    }
+
+    #[test]
+    fn test_ranges_and_steps_are_checked() {
+        assert_eq!(parse_step("6h"), Ok(TimeDelta::hours(6)));
+        assert_eq!(parse_step("30m"), Ok(TimeDelta::minutes(30)));
+        assert!(parse_step("0d").is_err());
+        assert!(parse_step("1y").is_err());
+        assert!(parse_step("d").is_err());
+        assert!(DateRange::new(date(2000, 1, 2), date(2000, 1, 1), TimeDelta::days(1)).is_err());
+        assert!(DateRange::new(date(2000, 1, 1), date(2000, 1, 1), TimeDelta::zero()).is_err());
+        let single = DateRange::new(date(2000, 1, 1), date(2000, 1, 1), TimeDelta::days(1)).unwrap();
+        assert_eq!(single.moments(), 1);
+        // The last step may fall short of the end
+        assert_eq!(DateRange::new(date(2000, 1, 1), date(2000, 1, 2), TimeDelta::hours(7)).unwrap().moments(), 4);
+    }
+}
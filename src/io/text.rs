@@ -0,0 +1,282 @@
+//! Plain-text chart listings in the spirit of Astrolog's classic output: one
+//! aligned row per body, then the house cusps, then the aspects grouped by
+//! planet. Meant for terminals and logs, so every line fits in 80 columns.
+
+use crate::api::types::{AspectInfo, ChartResponse, HouseInfo, PlanetInfo};
+use crate::data::symbols::SIGN_SYMBOLS;
+use std::fmt::Write;
+
+/// Three-letter sign names from Aries, used unless glyphs are asked for
+const SIGN_ABBREVIATIONS: [&str; 12] = ["Ari", "Tau", "Gem", "Can", "Leo", "Vir", "Lib", "Sco", "Sag", "Cap", "Aqu", "Pis"];
+
+/// Width of the body name column, enough for "MeanLilith" and "TrueLilith"
+const NAME_WIDTH: usize = 11;
+
+/// House cusps listed on each line
+const CUSPS_PER_LINE: usize = 4;
+
+/// How a listing is drawn
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ListingOptions {
+    /// Unicode sign symbols instead of three-letter names
+    pub sign_glyphs: bool,
+}
+
+/// The chart as a plain-text listing with three-letter sign names
+pub fn render_listing(chart: &ChartResponse) -> String {
+    render_listing_with(chart, ListingOptions::default())
+}
+
+/// The chart as a plain-text listing drawn with `options`
+pub fn render_listing_with(chart: &ChartResponse, options: ListingOptions) -> String {
+    let mut out = String::new();
+    let mut chart_type = chart.chart_type.clone();
+    if let Some(first) = chart_type.get_mut(..1) {
+        first.make_ascii_uppercase();
+    }
+    let _ = writeln!(
+        out,
+        "{} chart for {} at {} {}",
+        chart_type,
+        chart.date.format("%Y-%m-%d %H:%M UTC"),
+        coordinate(chart.latitude, 'N', 'S'),
+        coordinate(chart.longitude, 'E', 'W'),
+    );
+    let mut settings = format!("{} houses, {} zodiac", chart.house_system, chart.ayanamsa);
+    if let Some(ephemeris) = &chart.ephemeris {
+        let _ = write!(settings, ", {} ephemeris", ephemeris);
+    }
+    let _ = writeln!(out, "{}", settings);
+
+    out.push('\n');
+    let _ = writeln!(out, "{:<NAME_WIDTH$}{:<6}{:<9}{:>5}{:>10}", "Body", "Sign", "Position", "House", "Speed");
+    for planet in &chart.planets {
+        out.push_str(&body_row(planet, options));
+        out.push('\n');
+    }
+
+    if !chart.houses.is_empty() {
+        out.push('\n');
+        out.push_str("House cusps\n");
+        for row in chart.houses.chunks(CUSPS_PER_LINE) {
+            let cells: Vec<String> = row.iter().map(|house| cusp_cell(house, options)).collect();
+            out.push_str(cells.join("   ").trim_end());
+            out.push('\n');
+        }
+    }
+
+    if !chart.aspects.is_empty() {
+        out.push('\n');
+        out.push_str("Aspects\n");
+        for (planet, aspects) in aspects_by_planet(&chart.planets, &chart.aspects) {
+            for (i, aspect) in aspects.iter().enumerate() {
+                let name = if i == 0 { planet } else { "" };
+                let mut line = format!(
+                    "{:<NAME_WIDTH$}{:<14}{:<NAME_WIDTH$}orb {}",
+                    name,
+                    aspect.aspect,
+                    aspect.planet2,
+                    degrees_minutes(aspect.orb.abs()),
+                );
+                if aspect.exact {
+                    line.push_str("  exact");
+                }
+                out.push_str(&line);
+                out.push('\n');
+            }
+        }
+    }
+
+    out
+}
+
+/// A body's row: name, sign, position in the sign, house, speed and the R flag
+fn body_row(planet: &PlanetInfo, options: ListingOptions) -> String {
+    let (sign, position) = zodiac_position(planet.longitude);
+    let house = planet.house.map(|h| h.to_string()).unwrap_or_default();
+    let mut row = format!(
+        "{:<NAME_WIDTH$}{}{:<9}{:>5}{:>+10.4}",
+        planet.name,
+        pad(sign_name(sign, options), 6),
+        position,
+        house,
+        planet.speed,
+    );
+    if planet.is_retrograde {
+        row.push_str(" R");
+    }
+    row
+}
+
+/// A cusp as "10 Can 12°34'"
+fn cusp_cell(house: &HouseInfo, options: ListingOptions) -> String {
+    let (sign, position) = zodiac_position(house.longitude);
+    format!("{:>2} {}{}", house.number, pad(sign_name(sign, options), 4), position)
+}
+
+/// The aspects listed under their first planet, bodies in chart order and any
+/// other points after them
+fn aspects_by_planet<'a>(planets: &'a [PlanetInfo], aspects: &'a [AspectInfo]) -> Vec<(&'a str, Vec<&'a AspectInfo>)> {
+    let mut groups: Vec<(&str, Vec<&AspectInfo>)> = planets.iter().map(|p| (p.name.as_str(), Vec::new())).collect();
+    for aspect in aspects {
+        match groups.iter_mut().find(|(name, _)| *name == aspect.planet1) {
+            Some((_, group)) => group.push(aspect),
+            None => groups.push((aspect.planet1.as_str(), vec![aspect])),
+        }
+    }
+    groups.retain(|(_, group)| !group.is_empty());
+    groups
+}
+
+/// The sign index and the position within it as "07°05'", rounded to the
+/// nearest minute
+fn zodiac_position(longitude: f64) -> (usize, String) {
+    let minutes = (longitude.rem_euclid(360.0) * 60.0).round() as i64 % (360 * 60);
+    let sign = (minutes / (30 * 60)) as usize;
+    let within = minutes % (30 * 60);
+    (sign, format!("{:02}°{:02}'", within / 60, within % 60))
+}
+
+fn sign_name(sign: usize, options: ListingOptions) -> &'static str {
+    if options.sign_glyphs {
+        SIGN_SYMBOLS[sign].1.glyph.unwrap_or(SIGN_ABBREVIATIONS[sign])
+    } else {
+        SIGN_ABBREVIATIONS[sign]
+    }
+}
+
+/// An angle in whole degrees and minutes, e.g. "2°03'"
+fn degrees_minutes(degrees: f64) -> String {
+    let minutes = (degrees * 60.0).round() as i64;
+    format!("{}°{:02}'", minutes / 60, minutes % 60)
+}
+
+/// A latitude or longitude as "14°38'N"
+fn coordinate(value: f64, positive: char, negative: char) -> String {
+    let hemisphere = if value < 0.0 { negative } else { positive };
+    format!("{}{}", degrees_minutes(value.abs()), hemisphere)
+}
+
+/// Pads `text` to `width` columns, not counting the variation selector
+fn pad(text: &str, width: usize) -> String {
+    let columns = text.chars().filter(|c| *c != '\u{FE0E}').count();
+    format!("{}{}", text, " ".repeat(width.saturating_sub(columns)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::types::CHART_SCHEMA_VERSION;
+    use crate::calc::planets::Motion;
+
+    fn planet(name: &str, longitude: f64, speed: f64, house: Option<u8>) -> PlanetInfo {
+        PlanetInfo {
+            name: name.to_string(),
+            longitude,
+            latitude: 0.0,
+            speed,
+            is_retrograde: speed < 0.0,
+            motion: if speed < 0.0 { Motion::Retrograde } else { Motion::Direct },
+            formatted_position: String::new(),
+            house,
+            house_fraction: None,
+            decan: None,
+            mansion: None,
+            equatorial: None,
+        }
+    }
+
+    fn aspect(planet1: &str, planet2: &str, aspect: &str, orb: f64) -> AspectInfo {
+        AspectInfo {
+            planet1: planet1.to_string(),
+            planet2: planet2.to_string(),
+            aspect: aspect.to_string(),
+            orb,
+            strength: 0.5,
+            exact: false,
+            applying: false,
+            exact_in_days: None,
+            exact_at: None,
+        }
+    }
+
+    /// A chart with the widest values each column can hold
+    fn crowded_chart() -> ChartResponse {
+        let mut chart: ChartResponse = serde_json::from_value(serde_json::json!({
+            "chart_type": "natal",
+            "schema_version": CHART_SCHEMA_VERSION,
+            "date": "1977-10-24T04:56:00Z",
+            "latitude": -89.9999,
+            "longitude": -179.9999,
+            "house_system": "gauquelin",
+            "ayanamsa": "tropical",
+            "planets": [],
+            "houses": [],
+            "aspects": [],
+            "ephemeris": "moshier",
+        }))
+        .unwrap();
+        chart.planets = vec![
+            planet("Sun", 210.674, 0.9985, Some(1)),
+            planet("Moon", 359.9999, 15.3912, Some(36)),
+            planet("MeanNode", 179.99, -0.0529, Some(12)),
+            planet("Chiron", 29.5, -0.0012, None),
+        ];
+        chart.houses = (1..=36)
+            .map(|number| HouseInfo {
+                number,
+                longitude: number as f64 * 10.0 - 0.001,
+                latitude: 0.0,
+            })
+            .collect();
+        chart.aspects = vec![
+            aspect("Sun", "Moon", "Sesquisquare", -2.9999),
+            aspect("MeanNode", "Sun", "Semisextile", 0.5),
+            aspect("Sun", "MeanNode", "Biquintile", 1.0),
+            aspect("EastPoint", "Chiron", "Quincunx", 0.1),
+        ];
+        chart
+    }
+
+    #[test]
+    fn test_rows_are_aligned_and_rounded() {
+        let listing = render_listing(&crowded_chart());
+        let lines: Vec<&str> = listing.lines().collect();
+        assert_eq!(lines[0], "Natal chart for 1977-10-24 04:56 UTC at 90°00'S 180°00'W");
+        assert_eq!(lines[1], "gauquelin houses, tropical zodiac, moshier ephemeris");
+        assert_eq!(lines[3], "Body       Sign  Position House     Speed");
+        assert_eq!(lines[4], "Sun        Sco   00°40'       1   +0.9985");
+        // 359°59'59.6" rounds up into Aries
+        assert_eq!(lines[5], "Moon       Ari   00°00'      36  +15.3912");
+        assert_eq!(lines[6], "MeanNode   Vir   29°59'      12   -0.0529 R");
+        assert_eq!(lines[7], "Chiron     Ari   29°30'           -0.0012 R");
+        // Cusps round the same way
+        assert!(listing.contains("\n 1 Ari 10°00'    2 Ari 20°00'    3 Tau 00°00'    4 Tau 10°00'\n"), "{}", listing);
+        assert!(listing.contains("\nSun        Sesquisquare  Moon       orb 3°00'\n           Biquintile    MeanNode   orb 1°00'\n"));
+        assert!(listing.ends_with("MeanNode   Semisextile   Sun        orb 0°30'\nEastPoint  Quincunx      Chiron     orb 0°06'\n"));
+    }
+
+    #[test]
+    fn test_lines_fit_in_80_columns() {
+        for options in [ListingOptions::default(), ListingOptions { sign_glyphs: true }] {
+            let listing = render_listing_with(&crowded_chart(), options);
+            for line in listing.lines() {
+                let columns = line.chars().filter(|c| *c != '\u{FE0E}').count();
+                assert!(columns < 80, "{} columns: {}", columns, line);
+            }
+        }
+        let ascii = render_listing(&crowded_chart());
+        assert!(ascii.chars().all(|c| c.is_ascii() || c == '°'));
+    }
+
+    #[test]
+    fn test_glyphs_keep_the_columns() {
+        let ascii = render_listing(&crowded_chart());
+        let glyphs = render_listing_with(&crowded_chart(), ListingOptions { sign_glyphs: true });
+        assert!(glyphs.contains("Sun        ♏\u{FE0E}     00°40'"));
+        for (a, g) in ascii.lines().zip(glyphs.lines()) {
+            let columns = |line: &str| line.chars().filter(|c| *c != '\u{FE0E}').count();
+            assert_eq!(columns(a), columns(g), "{} / {}", a, g);
+        }
+    }
+}
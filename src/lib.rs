@@ -1,36 +1,47 @@
 pub mod api;
 pub mod calc;
+#[cfg(feature = "svg")]
 pub mod charts;
 pub mod core;
+pub mod data;
+pub mod interpret;
 pub mod io;
 pub mod utils;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+
+#[cfg(all(target_arch = "wasm32", feature = "swiss"))]
+compile_error!("The `swiss` feature links the Swiss Ephemeris C library and can't be built for wasm32");
+
+pub use calc::houses::HousePosition;
+pub use calc::planets::{calculate_planet_position, Planet, PlanetPosition};
+pub use core::types::HouseSystem;
+pub use core::AstrologError;
 
 #[cfg(test)]
 pub mod tests {
+    #[cfg(feature = "server")]
     pub mod api_tests;
     pub mod functional;
     pub mod chart_tests;
     pub mod types_tests;
     pub mod utils_tests;
 
-    use super::*;
-    use crate::calc::swiss_ephemeris;
-    use approx::assert_relative_eq;
-
-    // Natal chart data: October 24, 1977, 04:56 AM, 121:03:03E 14:38:55N
-    const TEST_YEAR: i32 = 1977;
-    const TEST_MONTH: i32 = 10;
-    const TEST_DAY: i32 = 24;
-    const TEST_HOUR: f64 = 4.0 + 56.0 / 60.0; // 04:56 AM
-
-    fn setup() -> Result<(), String> {
-        swiss_ephemeris::init_swiss_ephemeris()
-            .map_err(|e| format!("Failed to initialize Swiss Ephemeris: {}", e))
-    }
-
+    #[cfg(feature = "swiss")]
     #[test]
     fn test_basic_calculations() -> Result<(), String> {
-        setup()?;
+        use super::*;
+        use crate::calc::swiss_ephemeris;
+        use approx::assert_relative_eq;
+
+        // Natal chart data: October 24, 1977, 04:56 AM, 121:03:03E 14:38:55N
+        const TEST_YEAR: i32 = 1977;
+        const TEST_MONTH: i32 = 10;
+        const TEST_DAY: i32 = 24;
+        const TEST_HOUR: f64 = 4.0 + 56.0 / 60.0; // 04:56 AM
+
+        swiss_ephemeris::init_moshier_ephemeris()
+            .map_err(|e| format!("Failed to initialize Swiss Ephemeris: {}", e))?;
         let sun_pos =
             calculate_planet_position(Planet::Sun, TEST_YEAR, TEST_MONTH, TEST_DAY, TEST_HOUR)
                 .map_err(|e| format!("Failed to calculate Sun position: {}", e))?;
@@ -44,8 +55,3 @@ pub mod tests {
         Ok(())
     }
 }
-
-pub use calc::houses::HousePosition;
-pub use calc::planets::{calculate_planet_position, Planet, PlanetPosition};
-pub use core::types::HouseSystem;
-pub use core::AstrologError;
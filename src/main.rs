@@ -1,93 +1,160 @@
-mod api;
-mod calc;
-mod charts;
-mod core;
-mod data;
-mod io;
-mod utils;
-
 use actix_cors::Cors;
-use actix_web::{App, HttpServer, middleware};
-use astrolog_rs::api::server::config;
+use actix_web::{App, HttpServer};
+use astrolog_rs::api::audit;
+use astrolog_rs::api::pool;
+use astrolog_rs::api::prefetch::Prefetcher;
+use astrolog_rs::api::queue::{QueueConfig, RequestQueue};
+use astrolog_rs::api::render;
+use astrolog_rs::api::server::configure;
+use astrolog_rs::api::settings::AppConfig;
+use astrolog_rs::calc::ephemeris::{default_ephemeris, ephemeris_backend};
 use astrolog_rs::calc::swiss_ephemeris;
-use crate::api::queue::{QueueConfig, RequestQueue};
+use astrolog_rs::charts;
+use astrolog_rs::interpret;
+use astrolog_rs::io::astrolog_config;
+use astrolog_rs::utils::logging;
 use env_logger::Env;
-use std::env;
 use actix_web::web::Data;
 use std::sync::Arc;
-use tokio::sync::Semaphore;
 use actix_web::middleware::Logger;
 use actix_web::middleware::Compress;
 use actix_web::middleware::NormalizePath;
 
+#[cfg(feature = "cli")]
+mod cli;
+
 #[actix_web::main]
 async fn main() -> std::io::Result<()> {
-    env_logger::init_from_env(Env::default().default_filter_or("info"));
+    // Defaults, then the ASTROLOG_CONFIG file, then the environment
+    let settings = match AppConfig::load() {
+        Ok(settings) => settings,
+        Err(e) => {
+            eprintln!("Invalid configuration: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    env_logger::init_from_env(Env::default().default_filter_or(settings.log_level.as_str()));
 
     // Initialize Swiss Ephemeris
-    if let Err(e) = swiss_ephemeris::init_swiss_ephemeris() {
+    if let Err(e) = swiss_ephemeris::init_swiss_ephemeris_with(&settings.ephemeris_path, settings.ephemeris) {
         eprintln!("Failed to initialize Swiss Ephemeris: {}", e);
         std::process::exit(1);
     }
 
+    // `astrolog-rs precompute ...` runs on the same ephemeris settings and exits
+    #[cfg(feature = "cli")]
+    {
+        let args: Vec<String> = std::env::args().collect();
+        if cli::is_subcommand(&args) {
+            std::process::exit(cli::run(args));
+        }
+    }
+
     // Initialize chart styles
-    if let Err(e) = charts::init_styles() {
+    let styles = match settings.styles_path.as_deref() {
+        Some(path) => charts::init_styles_from(path),
+        None => charts::init_styles(),
+    };
+    if let Err(e) = styles {
         eprintln!("Failed to initialize chart styles: {}", e);
         std::process::exit(1);
     }
 
-    // Get number of workers from environment or use number of CPU cores
-    let workers = env::var("WORKERS")
-        .ok()
-        .and_then(|w| w.parse::<usize>().ok())
-        .unwrap_or_else(|| num_cpus::get());
+    // Interpretation text: a content pack file, or the built-in starter pack
+    if let Err(e) = interpret::init_content_pack(settings.content_pack.as_deref()) {
+        eprintln!("Failed to load interpretation pack: {}", e);
+        std::process::exit(1);
+    }
+
+    // Chart requests start from an Astrolog user's own defaults, when given
+    if let Some(path) = settings.astrolog_defaults_path.as_deref() {
+        match astrolog_config::load_astrolog_config(path) {
+            Ok(defaults) => {
+                for warning in &defaults.warnings {
+                    log::warn!("{}: {}", path, warning);
+                }
+                astrolog_config::init_defaults(defaults);
+            }
+            Err(e) => {
+                eprintln!("Failed to read the Astrolog defaults: {}", e);
+                std::process::exit(1);
+            }
+        }
+    }
 
     // Create request queue configuration
     let queue_config = QueueConfig {
-        max_queue_size: env::var("MAX_QUEUE_SIZE")
-            .ok()
-            .and_then(|s| s.parse::<usize>().ok())
-            .unwrap_or(10000),
-        max_wait_time: std::time::Duration::from_secs(
-            env::var("MAX_WAIT_TIME")
-                .ok()
-                .and_then(|t| t.parse::<u64>().ok())
-                .unwrap_or(30)
-        ),
+        max_queue_size: settings.max_queue_size,
+        max_wait_time: settings.max_wait(),
         priority_levels: 3,
     };
 
-    // Create a semaphore to limit concurrent calculations
-    let max_concurrent = env::var("MAX_CONCURRENT")
-        .ok()
-        .and_then(|m| m.parse::<usize>().ok())
-        .unwrap_or(500);
-    let semaphore = Arc::new(Semaphore::new(max_concurrent));
+    // Failed requests, appended to a file in the working directory
+    if let Err(e) = logging::init_error_log(logging::DEFAULT_ERROR_LOG) {
+        eprintln!("Failed to open the request error log: {}", e);
+        std::process::exit(1);
+    }
+
+    // Natal charts recorded for `astrolog-rs replay`, when asked for
+    if let Some(path) = settings.audit_log.as_deref() {
+        if let Err(e) = audit::init_audit_log(path, settings.audit_log_max_bytes, settings.audit_log_files) {
+            eprintln!("Failed to open the audit log: {}", e);
+            std::process::exit(1);
+        }
+    }
+
+    // Calculations run on their own threads, separate from the HTTP workers
+    pool::init_calculation_pool(settings.calculation_threads, settings.max_concurrent);
+
+    // Background renders share the calculation threads but are limited separately
+    render::init_render_limit(settings.max_concurrent_renders);
+
+    // The current positions, calculated before the first request and kept current
+    // in the background on the ephemeris the routes use
+    let ephemeris = ephemeris_backend(&settings.ephemeris_backend).unwrap_or_else(|_| default_ephemeris());
+    let prefetcher = Arc::new(Prefetcher::new(ephemeris));
+    let prefetch_task = prefetcher.start(settings.prefetch_interval()).await;
 
     // Create request queue
-    let request_queue = Arc::new(RequestQueue::new(queue_config, max_concurrent));
+    let request_queue = Arc::new(RequestQueue::new(queue_config, settings.max_concurrent));
 
-    println!("Starting Astrolog-rs server on http://127.0.0.1:4008 with {} workers", workers);
-    println!("Maximum concurrent calculations: {}", max_concurrent);
+    let (host, port) = settings.bind_address();
+    println!("Starting Astrolog-rs server on http://{}:{} with {} workers", host, port, settings.workers);
+    println!("Calculation threads: {}", settings.calculation_threads);
+    println!("Maximum concurrent calculations: {}", settings.max_concurrent);
+    println!("Maximum concurrent background renders: {}", settings.max_concurrent_renders);
     println!("Maximum queue size: {}", request_queue.max_queue_size());
     println!("Maximum wait time: {} seconds", request_queue.max_wait_time().as_secs());
+    println!("Prefetch interval: {} seconds", settings.prefetch_interval);
+    if let Some(path) = settings.audit_log.as_deref() {
+        println!("Audit log: {}", path);
+    }
+    if let Some(path) = settings.astrolog_defaults_path.as_deref() {
+        println!("Astrolog defaults: {}", path);
+    }
 
-    HttpServer::new(move || {
+    let workers = settings.workers;
+    let bind_address = (host.to_string(), port);
+    let server = HttpServer::new(move || {
         App::new()
             .wrap(Cors::permissive())
             .wrap(Logger::default())
             .wrap(Compress::default())
             .wrap(NormalizePath::trim())
-            .app_data(Data::new(semaphore.clone()))
             .app_data(Data::new(request_queue.clone()))
-            .configure(config)
+            .configure(|cfg| configure(cfg, &settings))
+            .app_data(Data::from(Arc::clone(&prefetcher)))
     })
     .workers(workers)
     .keep_alive(std::time::Duration::from_secs(75))
     .client_request_timeout(std::time::Duration::from_secs(60))
-    .client_shutdown(5000)
+    .client_disconnect_timeout(std::time::Duration::from_millis(5000))
     .backlog(16384)
-    .bind("127.0.0.1:4008")?
+    .bind(bind_address)?
     .run()
-    .await
+    .await;
+
+    prefetch_task.stop().await;
+    server
 }
@@ -1,7 +1,7 @@
-use actix_web::{test, web, App, http::StatusCode};
+use actix_web::{test, App, http::StatusCode};
 use serde_json::json;
 use crate::api::server::config;
-use crate::api::types::{ChartRequest, TransitRequest, SynastryRequest};
+use crate::calc::swiss_ephemeris::init_moshier_ephemeris;
 
 #[actix_web::test]
 async fn test_natal_chart_invalid_date() {
@@ -75,6 +75,7 @@ async fn test_synastry_chart_missing_data() {
 
 #[actix_web::test]
 async fn test_natal_chart_success() {
+    init_moshier_ephemeris().unwrap();
     let app = test::init_service(
         App::new().configure(config)
     ).await;
@@ -105,9 +106,11 @@ async fn test_natal_chart_success() {
 
 #[actix_web::test]
 async fn test_error_logging() {
-    // Ensure log file exists and is empty
-    let log_path = "request_errors.log";
-    std::fs::write(log_path, "").expect("Failed to create log file");
+    init_moshier_ephemeris().unwrap();
+    // Log to a fresh file outside the working tree
+    let log_path = std::env::temp_dir().join(format!("astrolog-rs-request-errors-{}.log", std::process::id()));
+    std::fs::write(&log_path, "").expect("Failed to create log file");
+    crate::utils::logging::init_error_log(&log_path).expect("Failed to open log file");
 
     let app = test::init_service(
         App::new().configure(config)
@@ -133,8 +136,9 @@ async fn test_error_logging() {
     std::thread::sleep(std::time::Duration::from_millis(1000));
 
     // Verify that the error was logged
-    let log_contents = std::fs::read_to_string(log_path)
+    let log_contents = std::fs::read_to_string(&log_path)
         .expect("Failed to read log file");
+    let _ = std::fs::remove_file(&log_path);
     
     println!("Log contents: {}", log_contents); // Debug output
     
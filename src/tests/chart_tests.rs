@@ -1,6 +1,5 @@
-use crate::core::{Chart, ChartInfo, HouseSystem};
-use chrono::{DateTime, Utc, TimeZone};
-use std::f64::consts::PI;
+use crate::core::{ChartInfo, HouseSystem};
+use chrono::{Utc, TimeZone};
 
 #[test]
 fn test_basic_chart_generation() {
@@ -5,11 +5,13 @@ use crate::calc::{
     aspects::calculate_aspects,
     coordinates::calculate_julian_date,
 };
+use crate::calc::swiss_ephemeris::init_moshier_ephemeris;
 use crate::calc::utils::date_to_julian;
-use chrono::{DateTime, Utc, TimeZone, Datelike, Timelike};
+use chrono::{Utc, TimeZone, Datelike, Timelike};
 use approx::assert_relative_eq;
 
 /// Test data from original Astrolog output
+#[allow(dead_code)]
 const TEST_CHART_DATA: &str = r#"
 Astrolog 7.50 chart for Mon Oct 24, 1977  4:56am (ST Zone 0W) 121.05E 14.65N
 Body  Locat. Ret. Lati. Rul.      House  Rul. Veloc.    Equal Houses
@@ -38,6 +40,7 @@ East: 315.072   +0.0000 (-) [ 1st house] [R] +362.4                       <:11
 
 #[test]
 fn test_chart_generation() {
+    init_moshier_ephemeris().unwrap();
     // Create chart info for the test case
     let chart_info = ChartInfo {
         date: Utc.with_ymd_and_hms(1977, 10, 24, 4, 56, 0).unwrap(),
@@ -81,7 +84,7 @@ fn test_chart_generation() {
         130.315, 160.315, 190.315, 220.315, 250.315, 280.315
     ];
 
-    let mut chart_positions = ChartPositions {
+    let chart_positions = ChartPositions {
         zodiac_positions: positions.iter().map(|p| p.longitude).collect(),
         house_placements: vec![0; positions.len()],
         house_cusps: house_cusps.clone(),
@@ -95,6 +98,7 @@ fn test_chart_generation() {
 
 #[test]
 fn test_house_placements() {
+    init_moshier_ephemeris().unwrap();
     // Create chart info for the test case
     let chart_info = ChartInfo {
         date: Utc.with_ymd_and_hms(1977, 10, 24, 4, 56, 0).unwrap(),
@@ -171,6 +175,7 @@ fn test_house_placements() {
 
 #[test]
 fn test_chart_calculations() {
+    init_moshier_ephemeris().unwrap();
     // Test data
     let date = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
     let jd = date_to_julian(date);
@@ -180,7 +185,8 @@ fn test_chart_calculations() {
 
     // Calculate houses
     let houses = calculate_houses(jd, latitude, longitude, house_system)
-        .expect("Failed to calculate houses");
+        .expect("Failed to calculate houses")
+        .cusps;
 
     // Calculate planet positions
     let positions = calculate_planet_positions(jd)
@@ -1,3 +1,3 @@
+#[cfg(feature = "swiss")]
 pub mod chart_test;
-
-pub use chart_test::*; 
\ No newline at end of file
+ 
\ No newline at end of file
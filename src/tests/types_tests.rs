@@ -1,5 +1,5 @@
 use crate::core::{ChartInfo, HouseSystem};
-use chrono::{DateTime, Utc, TimeZone};
+use chrono::{Utc, TimeZone};
 use std::str::FromStr;
 
 #[test]
@@ -15,6 +15,8 @@ fn test_house_system_from_str() {
         ("alcabitius", HouseSystem::Alcabitius),
         ("morinus", HouseSystem::Morinus),
         ("krusinski", HouseSystem::Krusinski),
+        ("gauquelin", HouseSystem::Gauquelin),
+        ("sunshine", HouseSystem::Sunshine),
     ];
 
     for (input, expected) in test_cases.iter() {
@@ -23,9 +25,45 @@ fn test_house_system_from_str() {
     }
 }
 
+#[test]
+fn test_house_system_round_trips() {
+    for system in HouseSystem::ALL {
+        assert_eq!(HouseSystem::from_str(&system.to_string()), Ok(system));
+        assert_eq!(HouseSystem::from_str(system.key()), Ok(system));
+        assert_eq!(HouseSystem::from_str(&system.code().to_string()), Ok(system));
+        assert_eq!(HouseSystem::from_str(&system.code().to_ascii_lowercase().to_string()), Ok(system));
+        assert_eq!(HouseSystem::from_str(&format!("{:?}", system)), Ok(system));
+
+        let json = serde_json::to_string(&system).unwrap();
+        assert_eq!(json, format!("\"{}\"", system.key()));
+        assert_eq!(serde_json::from_str::<HouseSystem>(&json).unwrap(), system);
+    }
+}
+
+#[test]
+fn test_house_system_aliases() {
+    let aliases = [
+        ("whole_sign", HouseSystem::WholeSign),
+        ("whole-sign", HouseSystem::WholeSign),
+        ("Whole Sign", HouseSystem::WholeSign),
+        ("WholeSign", HouseSystem::WholeSign),
+        ("porphyry", HouseSystem::Porphyrius),
+        ("Porphyrius", HouseSystem::Porphyrius),
+        ("A", HouseSystem::Equal),
+        ("e", HouseSystem::Equal),
+        (" koch ", HouseSystem::Koch),
+    ];
+    for (input, expected) in aliases {
+        assert_eq!(HouseSystem::from_str(input), Ok(expected), "{}", input);
+    }
+
+    let codes: std::collections::HashSet<char> = HouseSystem::ALL.iter().map(|system| system.code()).collect();
+    assert_eq!(codes.len(), HouseSystem::ALL.len());
+}
+
 #[test]
 fn test_invalid_house_system() {
-    let invalid_systems = ["invalid", "placidusss", "kochh", "equall"];
+    let invalid_systems = ["invalid", "placidusss", "kochh", "equall", "", "Z", "pp"];
     
     for system in invalid_systems.iter() {
         let result = HouseSystem::from_str(system);
@@ -1,30 +1,100 @@
 use chrono::Local;
-use lazy_static::lazy_static;
+use serde_json::Value;
 use std::fs::{File, OpenOptions};
-use std::io::{Write, Read};
-use std::sync::Mutex;
+use std::io::{self, Write};
 use std::path::Path;
+use std::sync::{Mutex, OnceLock};
 
-lazy_static! {
-    static ref LOG_FILE: Mutex<File> = Mutex::new(
-        OpenOptions::new()
-            .create(true)
-            .append(true)
-            .open("request_errors.log")
-            .expect("Failed to open log file")
-    );
+/// File the server logs failed requests to
+pub const DEFAULT_ERROR_LOG: &str = "request_errors.log";
+
+static LOG_FILE: OnceLock<Mutex<File>> = OnceLock::new();
+
+/// Starts appending failed requests to `path`; later calls keep the first file.
+/// Until this is called, request errors only go to `tracing`.
+pub fn init_error_log(path: impl AsRef<Path>) -> io::Result<()> {
+    if LOG_FILE.get().is_none() {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        let _ = LOG_FILE.set(Mutex::new(file));
+    }
+    Ok(())
 }
 
-pub fn log_request_error(endpoint: &str, ip: &str, payload: &str, error: &str) {
+/// Placeholder written in place of birth data
+pub const REDACTED: &str = "[redacted]";
+
+/// Environment variable that allows birth data in error logs when set to 1, true or yes
+pub const LOG_PII_ENV: &str = "ASTROLOG_LOG_PII";
+
+/// Request fields that identify a person: birth dates and places
+const PII_FIELDS: [&str; 5] = ["date", "natal_date", "transit_date", "latitude", "longitude"];
+
+/// Whether error logs may include birth dates and coordinates
+pub fn log_pii_enabled() -> bool {
+    std::env::var(LOG_PII_ENV)
+        .map(|v| matches!(v.to_lowercase().as_str(), "1" | "true" | "yes"))
+        .unwrap_or(false)
+}
+
+/// Replaces birth dates and coordinates anywhere in a request payload with `[redacted]`
+pub fn redact_payload(payload: &Value) -> Value {
+    match payload {
+        Value::Object(map) => Value::Object(
+            map.iter()
+                .map(|(key, value)| {
+                    if PII_FIELDS.contains(&key.as_str()) {
+                        (key.clone(), Value::String(REDACTED.to_string()))
+                    } else {
+                        (key.clone(), redact_payload(value))
+                    }
+                })
+                .collect(),
+        ),
+        Value::Array(items) => Value::Array(items.iter().map(redact_payload).collect()),
+        other => other.clone(),
+    }
+}
+
+/// Writes one request error entry, redacting the payload unless `include_pii` is set.
+/// Payloads that aren't valid JSON are dropped entirely when redacting.
+pub fn write_request_error(
+    out: &mut impl Write,
+    endpoint: &str,
+    ip: &str,
+    payload: &str,
+    error: &str,
+    include_pii: bool,
+) -> io::Result<()> {
+    let payload = if include_pii {
+        payload.to_string()
+    } else {
+        serde_json::from_str::<Value>(payload)
+            .map(|value| redact_payload(&value).to_string())
+            .unwrap_or_else(|_| REDACTED.to_string())
+    };
     let timestamp = Local::now().format("%Y-%m-%d %H:%M:%S").to_string();
-    let log_entry = format!(
+
+    tracing::error!(endpoint, ip, payload = %payload, error, "request failed");
+    writeln!(
+        out,
         "[{}] Endpoint: {}, IP: {}, Payload: {}, Error: {}\n",
         timestamp, endpoint, ip, payload, error
-    );
+    )
+}
 
-    if let Ok(mut file) = LOG_FILE.lock() {
-        if let Err(e) = writeln!(file, "{}", log_entry) {
-            eprintln!("Failed to write to log file: {}", e);
+pub fn log_request_error(endpoint: &str, ip: &str, payload: &str, error: &str) {
+    let include_pii = log_pii_enabled();
+    match LOG_FILE.get() {
+        Some(file) => {
+            if let Ok(mut file) = file.lock() {
+                if let Err(e) = write_request_error(&mut *file, endpoint, ip, payload, error, include_pii) {
+                    eprintln!("Failed to write to log file: {}", e);
+                }
+            }
+        }
+        // Still reported through `tracing`
+        None => {
+            let _ = write_request_error(&mut io::sink(), endpoint, ip, payload, error, include_pii);
         }
     }
 }
@@ -32,59 +102,61 @@ pub fn log_request_error(endpoint: &str, ip: &str, payload: &str, error: &str) {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::fs;
-    use std::path::Path;
-
-    fn setup_test_log() -> String {
-        let test_log_path = "test_request_errors.log";
-        // Clean up any existing test log file
-        if Path::new(test_log_path).exists() {
-            fs::remove_file(test_log_path).expect("Failed to remove existing test log file");
-        }
-        test_log_path.to_string()
+    use serde_json::json;
+
+    fn captured(payload: &Value, include_pii: bool) -> String {
+        let mut out = Vec::new();
+        write_request_error(
+            &mut out,
+            "test_endpoint",
+            "127.0.0.1",
+            &payload.to_string(),
+            "Test error message",
+            include_pii,
+        )
+        .expect("Failed to write log entry");
+        String::from_utf8(out).unwrap()
     }
 
     #[test]
     fn test_log_request_error() {
-        let test_log_path = setup_test_log();
-        
-        // Override the LOG_FILE for testing
-        let test_file = OpenOptions::new()
-            .create(true)
-            .append(true)
-            .open(&test_log_path)
-            .expect("Failed to create test log file");
-        
-        let test_log = Mutex::new(test_file);
-        
-        // Log a test error
-        let endpoint = "test_endpoint";
-        let ip = "127.0.0.1";
-        let payload = r#"{"test": "data"}"#;
-        let error = "Test error message";
-        
-        let timestamp = Local::now().format("%Y-%m-%d %H:%M:%S").to_string();
-        let expected_entry = format!(
-            "[{}] Endpoint: {}, IP: {}, Payload: {}, Error: {}\n",
-            timestamp, endpoint, ip, payload, error
-        );
-        
-        if let Ok(mut file) = test_log.lock() {
-            writeln!(file, "{}", expected_entry).expect("Failed to write test log entry");
-        }
-        
-        // Read the log file and verify its contents
-        let mut contents = String::new();
-        let mut file = File::open(&test_log_path).expect("Failed to open test log file");
-        file.read_to_string(&mut contents).expect("Failed to read test log file");
-        
-        // Clean up
-        fs::remove_file(&test_log_path).expect("Failed to remove test log file");
-        
-        // Verify the log entry
-        assert!(contents.contains(endpoint));
-        assert!(contents.contains(ip));
-        assert!(contents.contains(payload));
-        assert!(contents.contains(error));
+        let payload = json!({"test": "data"});
+        let contents = captured(&payload, true);
+
+        assert!(contents.contains("test_endpoint"));
+        assert!(contents.contains("127.0.0.1"));
+        assert!(contents.contains(r#"{"test":"data"}"#));
+        assert!(contents.contains("Test error message"));
+    }
+
+    #[test]
+    fn test_redacts_birth_data() {
+        let payload = json!({
+            "chart1": {"date": "1977-10-24T04:56:00Z", "latitude": 14.65, "longitude": 121.05},
+            "transit": {"date": "2024-01-01T00:00:00Z", "latitude": 51.45, "longitude": 0.05},
+            "house_system": "placidus"
+        });
+        let contents = captured(&payload, false);
+
+        assert!(!contents.contains("14.65"));
+        assert!(!contents.contains("121.05"));
+        assert!(!contents.contains("1977-10-24"));
+        assert!(!contents.contains("51.45"));
+        assert!(contents.contains(r#""latitude":"[redacted]""#));
+        assert!(contents.contains(r#""longitude":"[redacted]""#));
+        assert!(contents.contains("placidus"));
+
+        // Opting in keeps the coordinates
+        let contents = captured(&payload, true);
+        assert!(contents.contains("121.05"));
     }
-} 
\ No newline at end of file
+
+    #[test]
+    fn test_unparseable_payload_is_dropped() {
+        let mut out = Vec::new();
+        write_request_error(&mut out, "chart", "::1", "lat=14.65", "bad", false).unwrap();
+        let contents = String::from_utf8(out).unwrap();
+        assert!(!contents.contains("14.65"));
+        assert!(contents.contains(REDACTED));
+    }
+}
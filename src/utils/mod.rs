@@ -1,15 +1,14 @@
 use std::f64::consts::PI;
 
+// Request error logs are written to a file, which the browser doesn't have
+#[cfg(not(target_arch = "wasm32"))]
 pub mod logging;
+#[cfg(not(target_arch = "wasm32"))]
 pub use logging::*;
 
 #[allow(dead_code)]
 pub fn normalize_angle(angle: f64) -> f64 {
-    let mut normalized = angle % 360.0;
-    if normalized < 0.0 {
-        normalized += 360.0;
-    }
-    normalized
+    crate::calc::angles::normalize(angle)
 }
 
 #[allow(dead_code)]
@@ -21,3 +20,9 @@ pub fn degrees_to_radians(degrees: f64) -> f64 {
 pub fn radians_to_degrees(radians: f64) -> f64 {
     radians * 180.0 / PI
 }
+
+/// FNV-1a, which unlike the standard library's hasher is the same in every build
+pub fn fnv1a(text: &str) -> u64 {
+    text.bytes()
+        .fold(0xcbf2_9ce4_8422_2325, |hash, byte| (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3))
+}
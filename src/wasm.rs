@@ -0,0 +1,183 @@
+//! JavaScript bindings for computing and rendering charts in the browser.
+//!
+//! Built with the `wasm` feature and without `swiss`: positions come from the
+//! analytic theory and only the Ascendant-based house systems are available.
+//! Every chart carries a `capabilities` object saying so.
+
+use crate::api::computation::ChartComputation;
+use crate::api::types::{unsupported_options_message, ChartRequest, ChartResponse, CHART_SCHEMA_VERSION};
+use crate::calc::aspects::AspectSet;
+use crate::calc::coordinates::local_sidereal_time;
+use crate::calc::ephemeris::{default_ephemeris, Ephemeris, SiderealEphemeris};
+use crate::calc::planets::default_source;
+use crate::calc::positions::parse_chart_ayanamsa;
+use crate::calc::utils::date_to_julian;
+use crate::charts::{generate_natal_svg_with_options, SvgOptions};
+use crate::core::types::{HouseSystem, NodeType};
+use crate::data::i18n::Lang;
+use crate::interpret::interpret_chart;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use wasm_bindgen::prelude::*;
+
+/// House systems that can be calculated without the Swiss Ephemeris, as requested
+const ANALYTIC_HOUSE_SYSTEMS: [HouseSystem; 6] = [
+    HouseSystem::Equal,
+    HouseSystem::WholeSign,
+    HouseSystem::Porphyrius,
+    HouseSystem::Campanus,
+    HouseSystem::Vedic,
+    HouseSystem::Null,
+];
+
+/// What this build can calculate
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct Capabilities {
+    /// Ephemeris source the positions were calculated from
+    pub ephemeris: String,
+    /// Whether Swiss Ephemeris precision is available
+    pub swiss_ephemeris: bool,
+    /// Whether asteroids, Chiron and the lunar nodes can be calculated
+    pub asteroids: bool,
+    /// `house_system` values that can be requested
+    pub house_systems: Vec<String>,
+}
+
+/// A natal chart plus the capabilities of the build that calculated it. The
+/// chart fields are flattened, so the JSON can be passed straight to `render_svg`.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct BrowserChartResponse {
+    #[serde(flatten)]
+    pub chart: ChartResponse,
+    pub capabilities: Capabilities,
+}
+
+/// Calculates a natal chart from a `ChartRequest`, without the SVG
+pub fn natal_chart(req: &ChartRequest) -> Result<BrowserChartResponse, String> {
+    let mut req = req.clone();
+    let degraded_options = req.degrade(&default_ephemeris().capabilities(), default_source());
+    if !degraded_options.is_empty() && !req.allow_degraded {
+        return Err(unsupported_options_message(&degraded_options));
+    }
+    let ayanamsa = parse_chart_ayanamsa(&req.ayanamsa)?;
+    let source = match req.ephemeris.as_deref() {
+        Some(source) => source.parse()?,
+        None => default_source(),
+    };
+    let node_type: NodeType = req.node_type.as_deref().map(str::parse).transpose()?.unwrap_or_default();
+    let aspect_filter = req.aspect_filter()?;
+    let custom_aspects = req.custom_aspects()?;
+    let (bodies, required_bodies) = req.bodies()?;
+    let (sign_boundary_orb, house_cusp_orb) = req.boundary_orbs()?;
+    let sect_twilight = req.sect_twilight()?;
+    let coordinates = req.coordinate_system()?;
+    let lang: Lang = req.lang.as_deref().map(str::parse).transpose()?.unwrap_or_default();
+    let date = req.date.resolve("date", chrono::Utc::now(), None)?;
+    let jd = date_to_julian(date);
+
+    let ephemeris: Arc<dyn Ephemeris> = match ayanamsa {
+        Some(ayanamsa) => Arc::new(SiderealEphemeris::new(default_ephemeris(), ayanamsa)),
+        None => default_ephemeris(),
+    };
+    let chart = ChartComputation::from_ephemeris(ephemeris, jd, source, &bodies, &required_bodies)
+        .and_then(|chart| chart.with_houses(req.latitude, req.longitude, req.house_system))
+        .map_err(|e| e.to_string())?
+        .with_custom_aspects(custom_aspects)
+        .with_angle_aspects(req.include_angle_aspects)
+        .with_coordinates(coordinates)
+        .with_lang(lang);
+    let (chart_ruler, house_rulers) = chart.rulers(req.modern_rulers);
+    let (houses_summary, house_emphasis) = chart.houses_summary();
+    let boundary_warnings = chart.boundary_warnings(sign_boundary_orb, house_cusp_orb);
+    let angular = chart.angular();
+    let sect = chart.sect(req.latitude, req.longitude, sect_twilight);
+    let angle_speeds = if req.include_angle_speeds {
+        chart.angle_speeds(req.latitude, req.longitude).map_err(|e| e.to_string())?
+    } else {
+        None
+    };
+    let houses = chart.house_info();
+    let angles = chart.angles();
+    let aspects = if req.include_exact_times {
+        chart
+            .aspects_with_exact_times(req.include_minor_aspects())
+            .map_err(|e| e.to_string())?
+    } else {
+        chart.aspects(req.include_minor_aspects())
+    };
+    let (aspects, truncated) = aspect_filter.apply(aspects);
+    let meta = chart.meta(node_type).map_err(|e| e.to_string())?;
+    let capabilities = Capabilities {
+        ephemeris: chart.source.to_string(),
+        swiss_ephemeris: cfg!(feature = "swiss"),
+        asteroids: cfg!(feature = "swiss"),
+        house_systems: ANALYTIC_HOUSE_SYSTEMS.iter().map(|h| h.key().to_string()).collect(),
+    };
+
+    let mut response = ChartResponse {
+        chart_type: "natal".to_string(),
+        schema_version: CHART_SCHEMA_VERSION,
+        date,
+        resolved_date: req.date.is_expression().then_some(date),
+        latitude: req.latitude,
+        longitude: req.longitude,
+        house_system: req.house_system,
+        ayanamsa: req.ayanamsa.clone(),
+        ephemeris: Some(chart.source.to_string()),
+        warnings: chart.warnings(),
+        degraded_options,
+        boundary_warnings,
+        angular,
+        planets: chart.into_planets(),
+        points: Vec::new(),
+        moon_mansion: None,
+        house_count: houses.len(),
+        houses,
+        angles,
+        aspect_set: AspectSet::new(req.include_minor_aspects()),
+        aspects,
+        transit: None,
+        transits: Vec::new(),
+        chart_ruler,
+        house_rulers,
+        houses_summary,
+        house_emphasis,
+        sidereal_time: Some(local_sidereal_time(jd, req.longitude) / 15.0),
+        angle_speeds,
+        sect: sect.map(|s| s.sect),
+        sun_altitude: sect.map(|s| s.sun_altitude),
+        sect_ambiguous: sect.is_some_and(|s| s.ambiguous),
+        svg_chart: None,
+        svg_thumbnail: None,
+        diagnostics: None,
+        meta: Some(meta),
+        interpretation: None,
+        truncated,
+    };
+    if req.include_interpretation {
+        response.interpretation = Some(interpret_chart(&response));
+    }
+
+    Ok(BrowserChartResponse {
+        chart: response,
+        capabilities,
+    })
+}
+
+/// Calculates a natal chart. Takes a `ChartRequest` as JSON and returns the chart,
+/// with its `capabilities`, as JSON.
+#[wasm_bindgen]
+pub fn compute_natal_chart(request: &str) -> Result<String, JsError> {
+    let req: ChartRequest =
+        serde_json::from_str(request).map_err(|e| JsError::new(&format!("Invalid chart request: {}", e)))?;
+    let chart = natal_chart(&req).map_err(|e| JsError::new(&e))?;
+    serde_json::to_string(&chart).map_err(|e| JsError::new(&e.to_string()))
+}
+
+/// Renders the SVG wheel for a chart returned by `compute_natal_chart`
+#[wasm_bindgen]
+pub fn render_svg(chart: &str) -> Result<String, JsError> {
+    let chart: ChartResponse =
+        serde_json::from_str(chart).map_err(|e| JsError::new(&format!("Invalid chart: {}", e)))?;
+    generate_natal_svg_with_options(&chart, SvgOptions::default()).map_err(|e| JsError::new(&e))
+}
@@ -0,0 +1,36 @@
+use astrolog_rs::calc::accuracy::{compare_ephemerides, date_grid, ANALYTIC_BODIES};
+use astrolog_rs::calc::ephemeris::{AnalyticEphemeris, SwissEphemeris};
+use astrolog_rs::calc::swiss_ephemeris;
+use astrolog_rs::calc::utils::date_to_julian;
+use astrolog_rs::core::types::EphemerisSource;
+
+// Runs in its own process, so it can initialize against the ephemeris files
+#[test]
+fn test_analytic_bodies_stay_within_tolerance() {
+    let ephe = concat!(env!("CARGO_MANIFEST_DIR"), "/ephe");
+    swiss_ephemeris::init_swiss_ephemeris_with(ephe, EphemerisSource::Auto).expect("Swiss Ephemeris must initialize");
+    if !swiss_ephemeris::ephemeris_files_available() {
+        eprintln!("No Swiss Ephemeris files in {}, skipping the accuracy sweep", ephe);
+        return;
+    }
+
+    // Every 30 days from 1950 to 2050
+    let jds = date_grid(
+        date_to_julian("1950-01-01T00:00:00Z".parse().unwrap()),
+        date_to_julian("2050-01-01T00:00:00Z".parse().unwrap()),
+        30.0,
+    )
+    .unwrap();
+    let report = compare_ephemerides(
+        (&SwissEphemeris, EphemerisSource::Swiss),
+        (&AnalyticEphemeris, EphemerisSource::Analytic),
+        &ANALYTIC_BODIES,
+        &jds,
+    )
+    .unwrap();
+    println!("{}", report.to_markdown());
+    assert_eq!(report.reference, EphemerisSource::Swiss);
+    assert_eq!(report.bodies[0].samples, 1218);
+    let failures = report.failures();
+    assert!(failures.is_empty(), "over tolerance: {:?}", failures);
+}
@@ -0,0 +1,40 @@
+use approx::assert_relative_eq;
+use astrolog_rs::calc::angles::{normalize, shortest_distance};
+use astrolog_rs::calc::houses::analytic_houses;
+use astrolog_rs::core::types::{AstrologError, HouseSystem};
+
+// Needs no ephemeris files or FFI, so it also runs with `--no-default-features`
+#[test]
+fn test_analytic_porphyry_and_campanus_match_recorded_swiss_cusps() {
+    // Cusps 1, 2, 3, 10, 11 and 12 from swe_houses, to three decimals
+    let recorded = [
+        ((2460000.3, 51.5, 0.0), HouseSystem::Porphyrius, [174.641, 204.102, 233.562, 83.023, 113.562, 144.102]),
+        ((2460000.3, 51.5, 0.0), HouseSystem::Campanus, [174.641, 207.346, 236.362, 83.023, 110.350, 140.967]),
+        ((2445000.7, -33.9, 151.2), HouseSystem::Porphyrius, [69.334, 103.785, 138.236, 352.687, 18.236, 43.785]),
+        ((2445000.7, -33.9, 151.2), HouseSystem::Campanus, [69.334, 103.563, 140.983, 352.687, 18.218, 42.120]),
+        ((2455000.1, 60.0, 25.0), HouseSystem::Porphyrius, [215.189, 251.687, 288.185, 144.682, 168.185, 191.687]),
+        ((2455000.1, 60.0, 25.0), HouseSystem::Campanus, [215.189, 258.419, 298.425, 144.682, 164.765, 185.748]),
+    ];
+    for ((jd, latitude, longitude), system, expected) in recorded {
+        let houses = analytic_houses(jd, latitude, longitude, system).unwrap();
+        let cusps = houses.cusp_longitudes().unwrap();
+        for (house, expected) in [0, 1, 2, 9, 10, 11].into_iter().zip(expected) {
+            assert!(
+                shortest_distance(cusps[house], expected) < 0.3,
+                "{} cusp {} at {}: {} vs {}",
+                system,
+                house + 1,
+                jd,
+                cusps[house],
+                expected
+            );
+            assert_relative_eq!(normalize(cusps[(house + 6) % 12] - cusps[house]), 180.0, epsilon = 1e-9);
+        }
+        assert_eq!((houses.ascendant, houses.midheaven), (cusps[0], cusps[9]));
+    }
+
+    for system in [HouseSystem::Placidus, HouseSystem::Koch] {
+        let error = analytic_houses(2451545.0, 40.0, -74.0, system).unwrap_err();
+        assert!(matches!(error, AstrologError::UnsupportedInThisBuild { .. }), "{:?}", error);
+    }
+}
@@ -1,15 +1,22 @@
-use actix_web::{test, web, App};
+mod common;
+
+use actix_web::{test, App};
 use astrolog_rs::api::server::config;
+use astrolog_rs::calc::aspects::get_aspect_types;
 use astrolog_rs::calc::swiss_ephemeris;
+use common::analytic_backend;
 use serde_json::json;
 
 async fn ensure_swiss_ephemeris_initialized() {
-    // Ignore error if already initialized
-    let _ = swiss_ephemeris::init_swiss_ephemeris();
+    // The Moshier theory needs no files, so results don't depend on ./ephe
+    swiss_ephemeris::init_moshier_ephemeris().expect("Swiss Ephemeris must initialize");
 }
 
 #[actix_web::test]
 async fn test_natal_chart_endpoint() {
+    if analytic_backend() {
+        return;
+    }
     ensure_swiss_ephemeris_initialized().await;
     let app = test::init_service(App::new().configure(config)).await;
 
@@ -56,7 +63,27 @@ async fn test_natal_chart_endpoint() {
         assert!(planet.get("speed").is_some());
         assert!(planet.get("is_retrograde").is_some());
         assert!(planet.get("house").is_some());
+        let house = planet["house"].as_u64().unwrap();
+        assert!((1..=12).contains(&house));
+        let fraction = planet["house_fraction"].as_f64().unwrap();
+        assert!((0.0..1.0).contains(&fraction));
     }
+    // The Sun and Moon at J2000, to well within a tenth of a degree
+    assert_eq!(response["ephemeris"], "moshier");
+    assert_eq!(planets[0]["name"], "Sun");
+    assert!((planets[0]["longitude"].as_f64().unwrap() - 280.3689).abs() < 1e-3);
+    assert!((planets[1]["longitude"].as_f64().unwrap() - 223.3238).abs() < 1e-3);
+
+    let sidereal_time = response["sidereal_time"].as_f64().unwrap();
+    assert!((0.0..24.0).contains(&sidereal_time));
+
+    // Check rulers
+    let chart_ruler = &response["chart_ruler"];
+    assert!(chart_ruler["sign"].is_string());
+    assert!(chart_ruler["planet"]["name"].is_string());
+    let house_rulers = response["house_rulers"].as_array().unwrap();
+    assert_eq!(house_rulers.len(), 12);
+    assert_eq!(house_rulers[0]["ruler"], chart_ruler["planet"]["name"]);
 
     // Check houses
     let houses = response["houses"].as_array().unwrap();
@@ -80,12 +107,12 @@ async fn test_natal_chart_endpoint() {
         let planet2 = aspect["planet2"].as_str().unwrap();
         assert!([
             "Sun", "Moon", "Mercury", "Venus", "Mars", "Jupiter", "Saturn", "Uranus", "Neptune",
-            "Pluto"
+            "Pluto", "TrueNode", "MeanLilith"
         ]
         .contains(&planet1));
         assert!([
             "Sun", "Moon", "Mercury", "Venus", "Mars", "Jupiter", "Saturn", "Uranus", "Neptune",
-            "Pluto"
+            "Pluto", "TrueNode", "MeanLilith"
         ]
         .contains(&planet2));
     }
@@ -99,6 +126,9 @@ async fn test_natal_chart_endpoint() {
 
 #[actix_web::test]
 async fn test_transit_chart_endpoint() {
+    if analytic_backend() {
+        return;
+    }
     ensure_swiss_ephemeris_initialized().await;
     let app = test::init_service(App::new().configure(config)).await;
 
@@ -197,6 +227,9 @@ async fn test_transit_chart_endpoint() {
 
 #[actix_web::test]
 async fn test_synastry_chart_endpoint() {
+    if analytic_backend() {
+        return;
+    }
     ensure_swiss_ephemeris_initialized().await;
     let app = test::init_service(App::new().configure(config)).await;
 
@@ -275,11 +308,21 @@ async fn test_synastry_chart_endpoint() {
 
     // Check synastries
     let synastries = response["synastries"].as_array().unwrap();
+    let mut pairs = std::collections::HashSet::new();
+    let mut last_strength = 1.0;
     for aspect in synastries {
-        assert!(aspect.get("person1").is_some());
-        assert!(aspect.get("person2").is_some());
+        let person1_planet = aspect["person1_planet"].as_str().unwrap();
+        let person2_planet = aspect["person2_planet"].as_str().unwrap();
+        assert!(aspect.get("person1").is_none());
+        assert!(aspect.get("person2").is_none());
         assert!(aspect.get("aspect").is_some());
-        assert!(aspect.get("orb").is_some());
+        // Each pair once, strongest first
+        assert!(pairs.insert((person1_planet.to_string(), person2_planet.to_string())));
+        let strength = aspect["strength"].as_f64().unwrap();
+        assert!((0.0..=1.0).contains(&strength));
+        assert!(strength <= last_strength);
+        last_strength = strength;
+        assert_eq!(aspect["exact"], aspect["orb"].as_f64().unwrap() < 0.1);
     }
 
     // Check that SVG chart is generated
@@ -326,6 +369,9 @@ async fn test_invalid_input_handling() {
 
 #[actix_web::test]
 async fn test_different_house_systems() {
+    if analytic_backend() {
+        return;
+    }
     ensure_swiss_ephemeris_initialized().await;
     let app = test::init_service(App::new().configure(config)).await;
 
@@ -368,6 +414,9 @@ async fn test_different_house_systems() {
 
 #[actix_web::test]
 async fn test_specific_natal_chart() {
+    if analytic_backend() {
+        return;
+    }
     ensure_swiss_ephemeris_initialized().await;
     let app = test::init_service(App::new().configure(config)).await;
 
@@ -426,6 +475,9 @@ async fn test_specific_natal_chart() {
 
 #[actix_web::test]
 async fn test_chart_endpoint_with_transits() {
+    if analytic_backend() {
+        return;
+    }
     ensure_swiss_ephemeris_initialized().await;
     let app = test::init_service(App::new().configure(config)).await;
 
@@ -495,14 +547,15 @@ async fn test_chart_endpoint_with_transits() {
     // Check transit planets
     let transit_planets = transit["planets"].as_array().unwrap();
     assert!(!transit_planets.is_empty());
-    assert_eq!(transit_planets.len(), planets.len()); // Should have same number of planets
+    assert_eq!(planets.len(), 12); // The ten planets, the True Node and Mean Lilith
+    assert_eq!(transit_planets.len(), 10); // Transits are of the ten planets
 
     // Check transit aspects
     let transit_aspects = transit["aspects"].as_array().unwrap();
     assert!(!transit_aspects.is_empty());
 
     // Check transit-to-natal aspects
-    let cross_aspects = transit["transit_to_natal_aspects"].as_array().unwrap();
+    let _cross_aspects = transit["transit_to_natal_aspects"].as_array().unwrap();
     // Cross aspects might be empty if no aspects are within orb, so just check it exists
     // The fact that we can call .as_array().unwrap() means it's a valid array
 
@@ -517,6 +570,9 @@ async fn test_chart_endpoint_with_transits() {
 
 #[actix_web::test]
 async fn test_chart_endpoint_without_transits() {
+    if analytic_backend() {
+        return;
+    }
     ensure_swiss_ephemeris_initialized().await;
     let app = test::init_service(App::new().configure(config)).await;
 
@@ -566,3 +622,3980 @@ async fn test_chart_endpoint_without_transits() {
 
     println!("Chart with default transits response: {}", serde_json::to_string_pretty(&response).unwrap());
 }
+
+#[actix_web::test]
+async fn test_chart_glyph_paths_and_language() {
+    if analytic_backend() {
+        return;
+    }
+    ensure_swiss_ephemeris_initialized().await;
+    let app = test::init_service(App::new().configure(config)).await;
+
+    let request = json!({
+        "date": "2000-01-01T12:00:00Z",
+        "latitude": 40.7128,
+        "longitude": -74.0060,
+        "house_system": "placidus",
+        "ayanamsa": "tropical",
+        "glyph_mode": "paths",
+        "lang": "de"
+    });
+
+    let resp = test::TestRequest::post()
+        .uri("/api/chart")
+        .set_json(&request)
+        .send_request(&app)
+        .await;
+    assert!(resp.status().is_success());
+    let body = test::read_body(resp).await;
+    let response: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    let svg = response["svg_chart"].as_str().unwrap();
+    assert!(!svg.contains('☉'));
+    assert!(svg.contains("class=\"planet-glyph\""));
+    assert!(svg.contains("Sonne"));
+    assert!(svg.contains("Geburtstag:"));
+
+    // Unknown modes and languages are rejected
+    for (key, value) in [("glyph_mode", "bitmap"), ("lang", "tlh")] {
+        let mut request = request.clone();
+        request[key] = json!(value);
+        let resp = test::TestRequest::post()
+            .uri("/api/chart")
+            .set_json(&request)
+            .send_request(&app)
+            .await;
+        assert_eq!(resp.status(), 400, "{} = {}", key, value);
+    }
+}
+
+#[actix_web::test]
+async fn test_diagnostics_only_when_requested() {
+    if analytic_backend() {
+        return;
+    }
+    ensure_swiss_ephemeris_initialized().await;
+    let app = test::init_service(App::new().configure(config)).await;
+
+    let mut request = json!({
+        "date": "2000-01-01T12:00:00Z",
+        "latitude": 40.7128,
+        "longitude": -74.0060,
+        "house_system": "placidus",
+        "ayanamsa": "tropical"
+    });
+
+    let resp = test::TestRequest::post()
+        .uri("/api/chart/natal")
+        .set_json(&request)
+        .send_request(&app)
+        .await;
+    assert!(resp.status().is_success());
+    assert!(resp.headers().contains_key("x-request-id"));
+    let body = test::read_body(resp).await;
+    let response: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert!(response.get("diagnostics").is_none());
+
+    request["debug"] = json!(true);
+    let resp = test::TestRequest::post()
+        .uri("/api/chart/natal")
+        .set_json(&request)
+        .send_request(&app)
+        .await;
+    assert!(resp.status().is_success());
+    let request_id = resp.headers().get("x-request-id").unwrap().to_str().unwrap().to_string();
+    let body = test::read_body(resp).await;
+    let response: serde_json::Value = serde_json::from_slice(&body).unwrap();
+
+    let diagnostics = &response["diagnostics"];
+    assert_eq!(diagnostics["request_id"], request_id);
+    assert_eq!(diagnostics["ephemeris"], response["ephemeris"]);
+    // The first request calculated these houses
+    assert_eq!(diagnostics["cache_hit"], true);
+    let timings = diagnostics["timings_ms"].as_object().unwrap();
+    for stage in ["positions", "houses", "aspects", "svg", "total"] {
+        assert!(timings[stage].as_f64().unwrap() >= 0.0, "missing {}", stage);
+    }
+
+    // A moment no other test asks for
+    request["date"] = json!("2000-01-01T12:00:07Z");
+    let resp = test::TestRequest::post()
+        .uri("/api/chart/natal")
+        .set_json(&request)
+        .send_request(&app)
+        .await;
+    let response: serde_json::Value = serde_json::from_slice(&test::read_body(resp).await).unwrap();
+    assert_eq!(response["diagnostics"]["cache_hit"], false);
+}
+
+#[actix_web::test]
+async fn test_exact_times_only_when_requested() {
+    if analytic_backend() {
+        return;
+    }
+    ensure_swiss_ephemeris_initialized().await;
+    let app = test::init_service(App::new().configure(config)).await;
+
+    let mut request = json!({
+        "date": "2000-01-01T12:00:00Z",
+        "latitude": 40.7128,
+        "longitude": -74.0060,
+        "house_system": "placidus",
+        "ayanamsa": "tropical"
+    });
+
+    let resp = test::TestRequest::post()
+        .uri("/api/chart/natal")
+        .set_json(&request)
+        .send_request(&app)
+        .await;
+    assert!(resp.status().is_success());
+    let body = test::read_body(resp).await;
+    let response: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    for aspect in response["aspects"].as_array().unwrap() {
+        assert!(aspect.get("exact_in_days").is_none());
+    }
+
+    request["include_exact_times"] = json!(true);
+    let resp = test::TestRequest::post()
+        .uri("/api/chart/natal")
+        .set_json(&request)
+        .send_request(&app)
+        .await;
+    assert!(resp.status().is_success());
+    let body = test::read_body(resp).await;
+    let response: serde_json::Value = serde_json::from_slice(&body).unwrap();
+
+    let exact_times: Vec<f64> = response["aspects"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .filter_map(|aspect| aspect["exact_in_days"].as_f64())
+        .collect();
+    assert!(!exact_times.is_empty());
+    assert!(exact_times.iter().all(|days| (0.0..=365.0).contains(days)));
+}
+
+#[actix_web::test]
+async fn test_cycles_endpoint() {
+    if analytic_backend() {
+        return;
+    }
+    ensure_swiss_ephemeris_initialized().await;
+    let app = test::init_service(App::new().configure(config)).await;
+
+    let resp = test::TestRequest::get()
+        .uri("/api/cycles?pair=jupiter-saturn&from=2020-06-01&to=2021-06-01")
+        .send_request(&app)
+        .await;
+    assert!(resp.status().is_success());
+    let body = test::read_body(resp).await;
+    let response: serde_json::Value = serde_json::from_slice(&body).unwrap();
+
+    let conjunction = response["events"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|event| event["event"] == "conjunction")
+        .expect("great conjunction not found");
+    assert!(conjunction["date"].as_str().unwrap().starts_with("2020-12-21"));
+    assert_eq!(conjunction["sign"], "Aquarius");
+    assert!((conjunction["degree"].as_f64().unwrap() - 29.0 / 60.0).abs() < 0.05);
+
+    for uri in [
+        "/api/cycles?pair=jupiter&from=2020-01-01&to=2021-01-01",
+        "/api/cycles?pair=jupiter-jupiter&from=2020-01-01&to=2021-01-01",
+        "/api/cycles?pair=jupiter-saturn&from=2021-01-01&to=2020-01-01",
+    ] {
+        let resp = test::TestRequest::get().uri(uri).send_request(&app).await;
+        assert_eq!(resp.status(), 400, "{}", uri);
+    }
+}
+
+#[actix_web::test]
+async fn test_aspect_filter_and_cap() {
+    if analytic_backend() {
+        return;
+    }
+    ensure_swiss_ephemeris_initialized().await;
+    let app = test::init_service(App::new().configure(config)).await;
+
+    let mut request = json!({
+        "date": "2000-01-01T12:00:00Z",
+        "latitude": 40.7128,
+        "longitude": -74.0060,
+        "house_system": "placidus",
+        "ayanamsa": "tropical",
+        "include_minor_aspects": true,
+        "aspect_filter": { "planets": ["Sun"] }
+    });
+
+    let resp = test::TestRequest::post()
+        .uri("/api/chart")
+        .set_json(&request)
+        .send_request(&app)
+        .await;
+    assert!(resp.status().is_success());
+    let body = test::read_body(resp).await;
+    let response: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    let touches_sun = |aspect: &serde_json::Value| {
+        [&aspect["planet1"], &aspect["planet2"]]
+            .iter()
+            .any(|name| name.as_str().unwrap().ends_with("Sun"))
+    };
+    let aspects = response["aspects"].as_array().unwrap();
+    assert!(aspects.len() > 1);
+    assert!(aspects.iter().all(touches_sun));
+    assert!(response["transit"]["transit_to_natal_aspects"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .all(touches_sun));
+    assert!(response.get("truncated").is_none());
+
+    request["max_aspects"] = json!(1);
+    let resp = test::TestRequest::post()
+        .uri("/api/chart")
+        .set_json(&request)
+        .send_request(&app)
+        .await;
+    assert!(resp.status().is_success());
+    let body = test::read_body(resp).await;
+    let capped: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    let tightest = aspects
+        .iter()
+        .map(|aspect| aspect["orb"].as_f64().unwrap())
+        .fold(f64::INFINITY, f64::min);
+    assert_eq!(capped["aspects"].as_array().unwrap().len(), 1);
+    assert_eq!(capped["aspects"][0]["orb"].as_f64().unwrap(), tightest);
+    assert_eq!(capped["truncated"], true);
+
+    request["aspect_filter"]["aspects"] = json!(["Trien"]);
+    let resp = test::TestRequest::post()
+        .uri("/api/chart")
+        .set_json(&request)
+        .send_request(&app)
+        .await;
+    assert_eq!(resp.status(), 400);
+}
+
+#[actix_web::test]
+async fn test_chart_meta() {
+    if analytic_backend() {
+        return;
+    }
+    ensure_swiss_ephemeris_initialized().await;
+    let app = test::init_service(App::new().configure(config)).await;
+
+    let request = json!({
+        "date": "2000-01-01T12:00:00Z",
+        "latitude": 40.7128,
+        "longitude": -74.0060,
+        "house_system": "placidus",
+        "ayanamsa": "tropical"
+    });
+
+    let mut ids = Vec::new();
+    for _ in 0..2 {
+        let resp = test::TestRequest::post()
+            .uri("/api/chart/natal")
+            .set_json(&request)
+            .send_request(&app)
+            .await;
+        assert!(resp.status().is_success());
+        let body = test::read_body(resp).await;
+        let response: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        let meta = &response["meta"];
+
+        assert_eq!(meta["house_system_used"], request["house_system"]);
+        assert_eq!(meta["house_system_code"], "P");
+        assert_eq!(meta["crate_version"], env!("CARGO_PKG_VERSION"));
+        assert_eq!(meta["ephemeris"], response["ephemeris"]);
+        assert!(!meta["swiss_ephemeris_version"].as_str().unwrap().is_empty());
+        // Delta T was about 64 seconds in 2000
+        assert!((meta["delta_t_seconds"].as_f64().unwrap() - 64.0).abs() < 2.0);
+        assert_eq!(meta["flags"]["sidereal"], false);
+        ids.push(meta["calculation_id"].as_u64().unwrap());
+    }
+    assert!(ids[1] > ids[0]);
+}
+
+#[actix_web::test]
+async fn test_lunar_node_type() {
+    if analytic_backend() {
+        return;
+    }
+    ensure_swiss_ephemeris_initialized().await;
+    let app = test::init_service(App::new().configure(config)).await;
+
+    let mut north_nodes = Vec::new();
+    for node_type in [None, Some("true"), Some("mean")] {
+        let mut request = json!({
+            "date": "1977-10-24T04:56:00Z",
+            "latitude": 14.6488,
+            "longitude": 121.0509,
+            "house_system": "placidus",
+            "ayanamsa": "tropical"
+        });
+        if let Some(node_type) = node_type {
+            request["node_type"] = json!(node_type);
+        }
+        let resp = test::TestRequest::post()
+            .uri("/api/chart")
+            .set_json(&request)
+            .send_request(&app)
+            .await;
+        assert!(resp.status().is_success());
+        let body = test::read_body(resp).await;
+        let response: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        let meta = &response["meta"];
+        let nodes = &meta["lunar_nodes"];
+
+        assert_eq!(nodes["node_type"], node_type.unwrap_or("true"));
+        assert_eq!(meta["flags"]["true_node"], node_type != Some("mean"));
+        let north = nodes["north_node"].as_f64().unwrap();
+        let south = nodes["south_node"].as_f64().unwrap();
+        let chosen = if node_type == Some("mean") { "mean_node" } else { "true_node" };
+        assert_eq!(nodes[chosen].as_f64().unwrap(), north);
+        // Each node is rounded to 6 decimals on its own
+        let opposition = (south - north).rem_euclid(360.0);
+        assert!((opposition - 180.0).abs() < 2e-6, "South Node {}° from the North Node", opposition);
+        north_nodes.push(north);
+    }
+    assert_eq!(north_nodes[0], north_nodes[1]);
+    // The true node swings up to about 1.5° either side of the mean
+    let difference = (north_nodes[1] - north_nodes[2]).abs();
+    assert!(difference > 0.01 && difference < 1.8, "true and mean nodes {}° apart", difference);
+
+    let request = json!({
+        "date": "1977-10-24T04:56:00Z",
+        "latitude": 14.6488,
+        "longitude": 121.0509,
+        "house_system": "placidus",
+        "ayanamsa": "tropical",
+        "node_type": "osculating"
+    });
+    let resp = test::TestRequest::post()
+        .uri("/api/chart")
+        .set_json(&request)
+        .send_request(&app)
+        .await;
+    assert_eq!(resp.status(), 400);
+}
+
+#[actix_web::test]
+async fn test_custom_aspects() {
+    if analytic_backend() {
+        return;
+    }
+    ensure_swiss_ephemeris_initialized().await;
+    let app = test::init_service(App::new().configure(config)).await;
+
+    let mut request = json!({
+        "date": "1977-10-24T04:56:00Z",
+        "latitude": 14.6488,
+        "longitude": 121.0509,
+        "house_system": "placidus",
+        "ayanamsa": "tropical"
+    });
+    let resp = test::TestRequest::post()
+        .uri("/api/chart")
+        .set_json(&request)
+        .send_request(&app)
+        .await;
+    let response: serde_json::Value = serde_json::from_slice(&test::read_body(resp).await).unwrap();
+    let longitude = |name: &str| {
+        let planets = response["planets"].as_array().unwrap();
+        let planet = planets.iter().find(|p| p["name"] == name).unwrap();
+        planet["longitude"].as_f64().unwrap()
+    };
+    let separation = (longitude("Sun") - longitude("Moon")).rem_euclid(360.0);
+    let separation = separation.min(360.0 - separation);
+
+    // An aspect defined at exactly the Sun-Moon angle has to turn up under its own name
+    request["custom_aspects"] = json!([{ "name": "Measured", "angle": separation, "orb": 0.5 }]);
+    let resp = test::TestRequest::post()
+        .uri("/api/chart")
+        .set_json(&request)
+        .send_request(&app)
+        .await;
+    assert!(resp.status().is_success());
+    let response: serde_json::Value = serde_json::from_slice(&test::read_body(resp).await).unwrap();
+    let aspects = response["aspects"].as_array().unwrap();
+    let measured = aspects.iter().find(|a| a["aspect"] == "Measured").unwrap();
+    assert_eq!((&measured["planet1"], &measured["planet2"]), (&json!("Sun"), &json!("Moon")));
+    assert!(measured["orb"].as_f64().unwrap() < 1e-6);
+
+    for custom_aspects in [
+        json!([{ "name": "Decile", "angle": 180.0, "orb": 1.0 }]),
+        json!([{ "name": "Decile", "angle": 36.0, "orb": 30.0 }]),
+        json!([{ "name": "Trine", "angle": 120.0, "orb": 1.0 }]),
+        json!([{ "name": "Decile", "angle": 36.0, "orb": 1.0 }, { "name": "decile", "angle": 108.0, "orb": 1.0 }]),
+    ] {
+        request["custom_aspects"] = custom_aspects;
+        let resp = test::TestRequest::post()
+            .uri("/api/chart")
+            .set_json(&request)
+            .send_request(&app)
+            .await;
+        assert_eq!(resp.status(), 400);
+    }
+}
+
+#[actix_web::test]
+async fn test_houses_summary() {
+    if analytic_backend() {
+        return;
+    }
+    ensure_swiss_ephemeris_initialized().await;
+    let app = test::init_service(App::new().configure(config)).await;
+
+    let request = json!({
+        "date": "1977-10-24T04:56:00Z",
+        "latitude": 14.6488,
+        "longitude": 121.0509,
+        "house_system": "placidus",
+        "ayanamsa": "tropical"
+    });
+    let resp = test::TestRequest::post()
+        .uri("/api/chart/natal")
+        .set_json(&request)
+        .send_request(&app)
+        .await;
+    assert!(resp.status().is_success());
+    let response: serde_json::Value = serde_json::from_slice(&test::read_body(resp).await).unwrap();
+    let summary = response["houses_summary"].as_array().unwrap();
+    assert_eq!(summary.len(), 12);
+
+    // Every planet is listed once, in the house the planets array gives it
+    let planets = response["planets"].as_array().unwrap();
+    for planet in planets {
+        let house = planet["house"].as_u64().unwrap() as usize;
+        assert!(summary[house - 1]["planets"].as_array().unwrap().contains(&planet["name"]));
+    }
+    let listed: usize = summary.iter().map(|h| h["planets"].as_array().unwrap().len()).sum();
+    assert_eq!(listed, planets.len());
+    assert_eq!(summary[9]["kind"], "angular");
+    assert_eq!(summary[10]["kind"], "succedent");
+    assert_eq!(summary[11]["kind"], "cadent");
+
+    let emphasis = &response["house_emphasis"];
+    let counted = ["angular", "succedent", "cadent"].map(|kind| emphasis[kind].as_u64().unwrap());
+    assert_eq!(counted.iter().sum::<u64>() as usize, planets.len());
+    let most = emphasis["most_occupied"].as_u64().unwrap() as usize;
+    let most_count = summary[most - 1]["planets"].as_array().unwrap().len();
+    assert!(summary.iter().all(|h| h["planets"].as_array().unwrap().len() <= most_count));
+}
+
+#[actix_web::test]
+async fn test_boundary_warnings() {
+    if analytic_backend() {
+        return;
+    }
+    ensure_swiss_ephemeris_initialized().await;
+    let app = test::init_service(App::new().configure(config)).await;
+
+    let mut request = json!({
+        "date": "1977-10-24T04:56:00Z",
+        "latitude": 14.6488,
+        "longitude": 121.0509,
+        "house_system": "placidus",
+        "ayanamsa": "tropical",
+        "sign_boundary_orb": 5.0,
+        "house_cusp_orb": 5.0
+    });
+    let resp = test::TestRequest::post()
+        .uri("/api/chart")
+        .set_json(&request)
+        .send_request(&app)
+        .await;
+    assert!(resp.status().is_success());
+    let response: serde_json::Value = serde_json::from_slice(&test::read_body(resp).await).unwrap();
+    let warnings = response["boundary_warnings"].as_array().unwrap();
+    assert!(!warnings.is_empty());
+    let planets = response["planets"].as_array().unwrap();
+    for warning in warnings {
+        let planet = planets.iter().find(|p| p["name"] == warning["body"]).unwrap();
+        let longitude = planet["longitude"].as_f64().unwrap();
+        let distance = warning["distance"].as_f64().unwrap();
+        assert!(distance <= 5.0);
+        let gap = (longitude - warning["boundary"].as_f64().unwrap()).rem_euclid(360.0);
+        assert!((gap.min(360.0 - gap) - distance).abs() < 1e-3, "{}", warning);
+        match warning["kind"].as_str().unwrap() {
+            "sign" => assert!(warning["adjacent_sign"].is_string() && warning["adjacent_house"].is_null()),
+            "house" => assert!(warning["adjacent_house"].is_u64() && warning["adjacent_sign"].is_null()),
+            kind => panic!("Unexpected kind {}", kind),
+        }
+    }
+    assert!(response["svg_chart"].as_str().unwrap().contains("boundary-warning"));
+
+    // Orbs of zero flag nothing, and orbs are checked
+    request["sign_boundary_orb"] = json!(0.0);
+    request["house_cusp_orb"] = json!(0.0);
+    let resp = test::TestRequest::post()
+        .uri("/api/chart/natal")
+        .set_json(&request)
+        .send_request(&app)
+        .await;
+    let response: serde_json::Value = serde_json::from_slice(&test::read_body(resp).await).unwrap();
+    assert!(response.get("boundary_warnings").is_none());
+
+    request["house_cusp_orb"] = json!(12.0);
+    let resp = test::TestRequest::post()
+        .uri("/api/chart/natal")
+        .set_json(&request)
+        .send_request(&app)
+        .await;
+    assert_eq!(resp.status(), 400);
+}
+
+#[actix_web::test]
+async fn test_chart_sect() {
+    if analytic_backend() {
+        return;
+    }
+    ensure_swiss_ephemeris_initialized().await;
+    let app = test::init_service(App::new().configure(config)).await;
+
+    // 12:56 local time in Quezon City
+    let mut request = json!({
+        "date": "1977-10-24T04:56:00Z",
+        "latitude": 14.6488,
+        "longitude": 121.0509,
+        "house_system": "placidus",
+        "ayanamsa": "tropical"
+    });
+    let resp = test::TestRequest::post()
+        .uri("/api/chart/natal")
+        .set_json(&request)
+        .send_request(&app)
+        .await;
+    assert!(resp.status().is_success());
+    let response: serde_json::Value = serde_json::from_slice(&test::read_body(resp).await).unwrap();
+    assert_eq!(response["sect"], "day");
+    assert!(response["sun_altitude"].as_f64().unwrap() > 45.0);
+    assert!(response.get("sect_ambiguous").is_none());
+
+    // 00:56 local time
+    request["date"] = json!("1977-10-24T16:56:00Z");
+    let resp = test::TestRequest::post()
+        .uri("/api/chart")
+        .set_json(&request)
+        .send_request(&app)
+        .await;
+    assert!(resp.status().is_success());
+    let response: serde_json::Value = serde_json::from_slice(&test::read_body(resp).await).unwrap();
+    assert_eq!(response["sect"], "night");
+    assert!(response["sun_altitude"].as_f64().unwrap() < -45.0);
+
+    request["sect_twilight"] = json!(20.0);
+    let resp = test::TestRequest::post()
+        .uri("/api/chart/natal")
+        .set_json(&request)
+        .send_request(&app)
+        .await;
+    assert_eq!(resp.status(), 400);
+}
+
+#[actix_web::test]
+async fn test_weather_report() {
+    use astrolog_rs::calc::planets::calculate_planet_positions_with_source;
+    use astrolog_rs::core::types::EphemerisSource;
+
+    ensure_swiss_ephemeris_initialized().await;
+    let app = test::init_service(App::new().configure(config)).await;
+
+    let get = |uri: &'static str| test::TestRequest::get().uri(uri).send_request(&app);
+    let resp = get("/api/weather?date=2024-04-15T12:34:00Z&lat=51.5074&lon=-0.1278").await;
+    assert!(resp.status().is_success());
+    let body = test::read_body(resp).await;
+    let response: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(response["date"], "2024-04-15T12:00:00Z");
+
+    // Mercury was retrograde from 1 to 25 April 2024, and nothing else was
+    let jd = 2460416.0; // 15 April 2024, 12:00 UT
+    let (positions, _) = calculate_planet_positions_with_source(jd, EphemerisSource::Auto).unwrap();
+    let names: Vec<&str> = response["planets"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|p| p["name"].as_str().unwrap())
+        .collect();
+    assert_eq!(names.len(), 10);
+    let expected: Vec<&str> = names
+        .iter()
+        .zip(&positions)
+        .filter(|(_, position)| position.is_retrograde)
+        .map(|(name, _)| *name)
+        .collect();
+    assert_eq!(response["retrograde"], json!(expected));
+    assert_eq!(response["retrograde"], json!(["Mercury"]));
+
+    let dates = |list: &str| -> Vec<String> {
+        response[list]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|item| item["date"].as_str().unwrap().to_string())
+            .collect()
+    };
+    let exact = dates("exact_aspects");
+    assert!(exact.windows(2).all(|w| w[0] <= w[1]));
+    assert!(exact.iter().all(|d| d.as_str() >= "2024-04-14T12:00:00Z" && d.as_str() <= "2024-04-16T12:00:00Z"));
+    let ingresses = dates("ingresses");
+    assert!(ingresses.iter().all(|d| d.starts_with("2024-04-15")));
+    let moon = &response["moon"];
+    assert_eq!(moon["void_of_course"].as_bool().unwrap(), moon.get("void_until").is_some());
+
+    // The rest of the hour gets the same report
+    let resp = get("/api/weather?date=2024-04-15T12:59:59Z&lat=51.5074&lon=-0.1278").await;
+    assert_eq!(test::read_body(resp).await, body);
+
+    let resp = get("/api/weather?date=2024-04-15T12:00:00Z&lat=91&lon=0").await;
+    assert_eq!(resp.status(), 400);
+}
+
+#[actix_web::test]
+async fn test_moon_table_across_a_lunation() {
+    if analytic_backend() {
+        return;
+    }
+    ensure_swiss_ephemeris_initialized().await;
+    let app = test::init_service(App::new().configure(config)).await;
+
+    // From the New Moon of 29 January 2025, 12:36 UT, to just before the next,
+    // on 28 February at 00:45
+    let uri = "/api/moon?from=2025-01-29T12:00:00Z&to=2025-02-28T00:00:00Z&step=6h&mansion_system=nakshatra";
+    let resp = test::TestRequest::get().uri(uri).send_request(&app).await;
+    assert!(resp.status().is_success());
+    let response: serde_json::Value = serde_json::from_slice(&test::read_body(resp).await).unwrap();
+    let header = &response["header"];
+    assert!(header["next_new_moon"].as_str().unwrap().starts_with("2025-01-29T12:3"), "{}", header);
+    assert!(header["next_full_moon"].as_str().unwrap().starts_with("2025-02-12T13:"), "{}", header);
+    assert_eq!(header["step"], "6h");
+    assert_eq!(header["mansion_system"], "nakshatra");
+
+    let rows = response["rows"].as_array().unwrap();
+    assert_eq!(rows.len(), 119);
+    assert_eq!(rows[1]["date"], "2025-01-29T18:00:00Z");
+    // The lit fraction climbs from nothing to the full disc and back
+    let lit: Vec<f64> = rows.iter().map(|row| row["illumination"].as_f64().unwrap()).collect();
+    let full = lit.iter().enumerate().max_by(|a, b| a.1.total_cmp(b.1)).unwrap().0;
+    assert!(lit[0] < 0.01 && lit[lit.len() - 1] < 0.01, "{:?}", lit);
+    assert!(lit[full] > 0.99);
+    assert!(lit[..=full].windows(2).all(|w| w[0] <= w[1]));
+    assert!(lit[full..].windows(2).all(|w| w[0] >= w[1]));
+    // Either side of the Full Moon at 13:53
+    assert_eq!((&rows[56]["date"], &rows[56]["phase"]), (&json!("2025-02-12T12:00:00Z"), &json!("gibbous")));
+    assert_eq!(rows[57]["phase"], "full");
+    // 36 minutes before the New Moon, then after it
+    assert_eq!(rows[0]["phase"], "balsamic");
+    assert_eq!(rows[1]["phase"], "new");
+    for row in rows {
+        let distance = row["distance_km"].as_f64().unwrap();
+        assert!((356_000.0..407_000.0).contains(&distance), "{}", row);
+        assert!(row["mansion"].is_string() && row["sign"].is_string() && row["void_of_course"].is_boolean());
+    }
+    assert!(rows.iter().any(|row| row["void_of_course"] == true));
+    assert!(rows.iter().any(|row| row["void_of_course"] == false));
+
+    // The same moments come back the same from the cache
+    let resp = test::TestRequest::get()
+        .uri("/api/moon?from=2025-02-12T00:00:00Z&to=2025-02-13T00:00:00Z&step=6h&mansion_system=nakshatra")
+        .send_request(&app)
+        .await;
+    let again: serde_json::Value = serde_json::from_slice(&test::read_body(resp).await).unwrap();
+    assert_eq!(again["rows"].as_array().unwrap()[..], rows[54..59]);
+}
+
+#[actix_web::test]
+async fn test_moon_table_steps() {
+    if analytic_backend() {
+        return;
+    }
+    ensure_swiss_ephemeris_initialized().await;
+    let app = test::init_service(App::new().configure(config)).await;
+
+    let get = |query: String| test::TestRequest::get().uri(&format!("/api/moon?{}", query)).to_request();
+    for (step, rows) in [("15m", 97), ("1h", 25), ("1d", 2)] {
+        let response: serde_json::Value =
+            test::call_and_read_body_json(&app, get(format!("from=2025-03-01T00:00:00Z&to=2025-03-02T00:00:00Z&step={}", step))).await;
+        assert_eq!(response["rows"].as_array().unwrap().len(), rows, "{}", step);
+    }
+    // An hour is the default step
+    let response: serde_json::Value =
+        test::call_and_read_body_json(&app, get("from=2025-03-01T00:00:00Z&to=2025-03-02T00:00:00Z".to_string())).await;
+    assert_eq!(response["header"]["step"], "1h");
+    assert_eq!(response["rows"].as_array().unwrap().len(), 25);
+
+    for query in [
+        "from=2025-03-01T00:00:00Z&to=2025-03-02T00:00:00Z&step=1s",
+        "from=2025-03-01T00:00:00Z&to=2025-03-02T00:00:00Z&step=5m",
+        "from=2025-03-01T00:00:00Z&to=2025-03-02T00:00:00Z&step=soon",
+        // Over the row cap
+        "from=2025-03-01T00:00:00Z&to=2025-04-10T00:00:00Z&step=15m",
+        "from=2025-03-02T00:00:00Z&to=2025-03-01T00:00:00Z",
+        "from=2025-03-01T00:00:00Z&to=2025-03-02T00:00:00Z&mansion_system=chinese",
+    ] {
+        let resp = test::call_service(&app, get(query.to_string())).await;
+        assert_eq!(resp.status(), 400, "{}", query);
+    }
+}
+
+#[actix_web::test]
+async fn test_synastry_batch_matches_single_pairs() {
+    if analytic_backend() {
+        return;
+    }
+    ensure_swiss_ephemeris_initialized().await;
+    let app = test::init_service(App::new().configure(config)).await;
+
+    let person = |date: &str, latitude: f64, longitude: f64| {
+        json!({
+            "date": date,
+            "latitude": latitude,
+            "longitude": longitude,
+            "house_system": "placidus",
+            "ayanamsa": "tropical"
+        })
+    };
+    let base = person("1990-01-01T12:00:00Z", 40.7128, -74.0060);
+    let mut broken = person("1992-06-15T08:30:00Z", 34.0522, -118.2437);
+    broken["ayanamsa"] = json!("no_such_zodiac");
+    let others = [
+        person("1992-06-15T08:30:00Z", 34.0522, -118.2437),
+        broken,
+        person("1985-03-20T23:15:00Z", 51.5074, -0.1278),
+    ];
+
+    let resp = test::TestRequest::post()
+        .uri("/api/synastry/batch")
+        .set_json(json!({ "base": base, "others": others, "include_minor_aspects": true, "top_aspects": 3 }))
+        .send_request(&app)
+        .await;
+    assert!(resp.status().is_success());
+    let batch: serde_json::Value = serde_json::from_slice(&test::read_body(resp).await).unwrap();
+    let results = batch["results"].as_array().unwrap();
+    assert_eq!(results.len(), 3);
+    assert_eq!(batch["aspect_set"], "all");
+
+    // The failing partner doesn't take the others down with it
+    assert_eq!(results[1]["index"], 1);
+    assert!(results[1]["error"].as_str().unwrap().contains("no_such_zodiac"));
+    assert!(results[1].get("scores").is_none());
+
+    for index in [0, 2] {
+        let resp = test::TestRequest::post()
+            .uri("/api/chart/synastry")
+            .set_json(json!({ "chart1": base, "chart2": others[index], "include_minor_aspects": true }))
+            .send_request(&app)
+            .await;
+        assert!(resp.status().is_success());
+        let single: serde_json::Value = serde_json::from_slice(&test::read_body(resp).await).unwrap();
+        let item = &results[index];
+        assert_eq!(item["index"], index);
+        assert!(item.get("error").is_none());
+        assert_eq!(item["scores"], single["compatibility"]);
+        assert!(single["compatibility"]["total"].as_f64().unwrap() > 0.0);
+
+        let mut tightest = single["synastries"].as_array().unwrap().clone();
+        tightest.sort_by(|a, b| a["orb"].as_f64().unwrap().total_cmp(&b["orb"].as_f64().unwrap()));
+        let top = item["top_aspects"].as_array().unwrap();
+        assert_eq!(top.len(), 3);
+        assert_eq!(top[..], tightest[..3]);
+    }
+
+    for bad in [
+        json!({ "base": base, "others": [] }),
+        json!({ "base": base, "others": [base], "top_aspects": 51 }),
+    ] {
+        let resp = test::TestRequest::post()
+            .uri("/api/synastry/batch")
+            .set_json(&bad)
+            .send_request(&app)
+            .await;
+        assert_eq!(resp.status(), 400);
+    }
+}
+
+#[actix_web::test]
+async fn test_synastry_minor_aspects_override() {
+    if analytic_backend() {
+        return;
+    }
+    ensure_swiss_ephemeris_initialized().await;
+    let app = test::init_service(App::new().configure(config)).await;
+
+    let chart = |date: &str| {
+        json!({
+            "date": date,
+            "latitude": 40.7128,
+            "longitude": -74.0060,
+            "house_system": "placidus",
+            "ayanamsa": "tropical"
+        })
+    };
+    let is_major = |aspect: &serde_json::Value| {
+        ["Conjunction", "Sextile", "Square", "Trine", "Opposition"].contains(&aspect["aspect"].as_str().unwrap())
+    };
+
+    // (top level, chart1 override, chart2 override) -> (chart1, chart2, synastries)
+    let cases = [
+        (Some(true), None, None, ["all", "all", "all"]),
+        (Some(true), None, Some(false), ["all", "major", "all"]),
+        (None, Some(true), None, ["all", "major", "major"]),
+        (None, None, None, ["major", "major", "major"]),
+    ];
+    for (top, override1, override2, expected) in cases {
+        let mut request = json!({ "chart1": chart("2000-01-01T12:00:00Z"), "chart2": chart("1995-01-01T12:00:00Z") });
+        if let Some(top) = top {
+            request["include_minor_aspects"] = json!(top);
+        }
+        if let Some(override1) = override1 {
+            request["chart1"]["include_minor_aspects"] = json!(override1);
+        }
+        if let Some(override2) = override2 {
+            request["chart2"]["include_minor_aspects"] = json!(override2);
+        }
+        let resp = test::TestRequest::post()
+            .uri("/api/chart/synastry")
+            .set_json(&request)
+            .send_request(&app)
+            .await;
+        assert!(resp.status().is_success());
+        let response: serde_json::Value = serde_json::from_slice(&test::read_body(resp).await).unwrap();
+
+        let sections = [
+            (&response["chart1"]["aspect_set"], &response["chart1"]["aspects"]),
+            (&response["chart2"]["aspect_set"], &response["chart2"]["aspects"]),
+            (&response["aspect_set"], &response["synastries"]),
+        ];
+        for ((aspect_set, aspects), expected) in sections.into_iter().zip(expected) {
+            assert_eq!(aspect_set, expected, "{}", request);
+            if expected == "major" {
+                assert!(aspects.as_array().unwrap().iter().all(is_major), "{}", request);
+            }
+        }
+    }
+}
+
+#[actix_web::test]
+async fn test_house_compare() {
+    if analytic_backend() {
+        return;
+    }
+    ensure_swiss_ephemeris_initialized().await;
+    let app = test::init_service(App::new().configure(config)).await;
+
+    let request = json!({
+        "date": "1977-10-24T04:56:00Z",
+        "latitude": 14.6488,
+        "longitude": 121.0509,
+        "house_systems": ["placidus", "equal", "wholesign"]
+    });
+    let resp = test::TestRequest::post()
+        .uri("/api/chart/house_compare")
+        .set_json(&request)
+        .send_request(&app)
+        .await;
+    assert!(resp.status().is_success());
+    let response: serde_json::Value = serde_json::from_slice(&test::read_body(resp).await).unwrap();
+    assert_eq!(response["house_systems"], json!(["placidus", "equal", "whole_sign"]));
+
+    // The Placidus column matches the natal chart's houses
+    let natal = json!({
+        "date": "1977-10-24T04:56:00Z",
+        "latitude": 14.6488,
+        "longitude": 121.0509,
+        "house_system": "placidus",
+        "ayanamsa": "tropical",
+        "include_nodes": false
+    });
+    let resp = test::TestRequest::post()
+        .uri("/api/chart/natal")
+        .set_json(&natal)
+        .send_request(&app)
+        .await;
+    let natal: serde_json::Value = serde_json::from_slice(&test::read_body(resp).await).unwrap();
+    let planets = response["planets"].as_array().unwrap();
+    assert_eq!(planets.len(), natal["planets"].as_array().unwrap().len());
+    for (planet, natal_planet) in planets.iter().zip(natal["planets"].as_array().unwrap()) {
+        assert_eq!(planet["planet"], natal_planet["name"]);
+        assert_eq!(planet["houses"][0], natal_planet["house"]);
+        let houses: Vec<u64> = planet["houses"].as_array().unwrap().iter().map(|h| h.as_u64().unwrap()).collect();
+        assert_eq!(planet["changes"], json!(houses.iter().any(|&h| h != houses[0])));
+    }
+
+    // Each pair counts the planets whose houses differ between its two columns
+    let pairs = response["pairs"].as_array().unwrap();
+    assert_eq!(pairs.len(), 3);
+    for (pair, (i, j)) in pairs.iter().zip([(0, 1), (0, 2), (1, 2)]) {
+        let moved = planets.iter().filter(|p| p["houses"][i] != p["houses"][j]).count();
+        assert_eq!(pair["planets_moved"].as_u64().unwrap() as usize, moved);
+    }
+    assert_eq!(pairs[0]["system1"], "placidus");
+    assert_eq!(pairs[0]["system2"], "equal");
+
+    let cusps = response["cusps"].as_array().unwrap();
+    assert_eq!(cusps.len(), 12);
+    assert!(cusps.iter().all(|c| c["longitudes"].as_array().unwrap().len() == 3));
+    // Placidus and Equal houses both start at the Ascendant
+    let first = cusps[0]["longitudes"].as_array().unwrap();
+    assert!((first[0].as_f64().unwrap() - first[1].as_f64().unwrap()).abs() < 1e-9);
+    assert_eq!(first[2].as_f64().unwrap() % 30.0, 0.0);
+
+    for systems in [json!([]), json!(["equal", "Equal"]), json!(["gauquelin"]), json!(["bogus"])] {
+        let mut bad = request.clone();
+        bad["house_systems"] = systems;
+        let resp = test::TestRequest::post()
+            .uri("/api/chart/house_compare")
+            .set_json(&bad)
+            .send_request(&app)
+            .await;
+        assert_eq!(resp.status(), 400, "{}", bad["house_systems"]);
+    }
+}
+
+#[actix_web::test]
+async fn test_applying_transit_aspects() {
+    if analytic_backend() {
+        return;
+    }
+    ensure_swiss_ephemeris_initialized().await;
+    let app = test::init_service(App::new().configure(config)).await;
+
+    // The transiting Sun returns to the natal Sun around midnight on 1 July 2022
+    let mut request = json!({
+        "date": "1950-07-01T12:00:00Z",
+        "latitude": 51.5074,
+        "longitude": -0.1278,
+        "house_system": "placidus",
+        "ayanamsa": "tropical",
+        "transit": { "date": "2022-06-30T12:00:00Z" }
+    });
+    let sun_to_sun = |response: &serde_json::Value| -> serde_json::Value {
+        response["transit"]["transit_to_natal_aspects"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|a| a["planet1"] == "Natal Sun" && a["planet2"] == "Transit Sun")
+            .unwrap()
+            .clone()
+    };
+
+    let resp = test::TestRequest::post().uri("/api/chart").set_json(&request).send_request(&app).await;
+    assert!(resp.status().is_success());
+    let before: serde_json::Value = serde_json::from_slice(&test::read_body(resp).await).unwrap();
+    let aspect = sun_to_sun(&before);
+    assert_eq!(aspect["applying"], true);
+    let exact_at: chrono::DateTime<chrono::Utc> = aspect["exact_at"].as_str().unwrap().parse().unwrap();
+    let transit_date: chrono::DateTime<chrono::Utc> = "2022-06-30T12:00:00Z".parse().unwrap();
+    assert!(exact_at > transit_date && exact_at < transit_date + chrono::Duration::days(1), "{}", exact_at);
+    // Natal aspects say which way they're going too, but have no exact_at
+    assert!(before["aspects"].as_array().unwrap().iter().all(|a| a["applying"].is_boolean() && a.get("exact_at").is_none()));
+
+    request["transit"]["date"] = json!("2022-07-01T12:00:00Z");
+    let resp = test::TestRequest::post().uri("/api/chart").set_json(&request).send_request(&app).await;
+    let after: serde_json::Value = serde_json::from_slice(&test::read_body(resp).await).unwrap();
+    let aspect = sun_to_sun(&after);
+    assert_eq!(aspect["applying"], false);
+    assert!(aspect.get("exact_at").is_none());
+}
+
+#[actix_web::test]
+async fn test_precession_correction() {
+    if analytic_backend() {
+        return;
+    }
+    ensure_swiss_ephemeris_initialized().await;
+    let app = test::init_service(App::new().configure(config)).await;
+
+    // 72 years of precession, about a degree
+    let mut request = json!({
+        "date": "1950-07-01T12:00:00Z",
+        "latitude": 51.5074,
+        "longitude": -0.1278,
+        "house_system": "placidus",
+        "ayanamsa": "tropical",
+        "transit": { "date": "2022-07-01T12:00:00Z" }
+    });
+    let resp = test::TestRequest::post().uri("/api/chart").set_json(&request).send_request(&app).await;
+    let plain: serde_json::Value = serde_json::from_slice(&test::read_body(resp).await).unwrap();
+    assert!(plain["transit"].get("precessed_natal_planets").is_none());
+
+    request["precession_correction"] = json!(true);
+    let resp = test::TestRequest::post().uri("/api/chart").set_json(&request).send_request(&app).await;
+    assert!(resp.status().is_success());
+    let corrected: serde_json::Value = serde_json::from_slice(&test::read_body(resp).await).unwrap();
+    let precessed = corrected["transit"]["precessed_natal_planets"].as_array().unwrap();
+    let natal = corrected["planets"].as_array().unwrap();
+    assert_eq!(precessed.len(), natal.len());
+    for (precessed, natal) in precessed.iter().zip(natal) {
+        assert_eq!(precessed["name"], natal["name"]);
+        let shift = (precessed["longitude"].as_f64().unwrap() - natal["longitude"].as_f64().unwrap()).rem_euclid(360.0);
+        assert!((shift - 1.0).abs() < 0.01, "{} moved {}°", natal["name"], shift);
+    }
+    // The natal chart itself is unchanged. The transiting Sun is about half a
+    // degree past the natal Sun, which the precessed natal Sun overtakes
+    assert_eq!(corrected["planets"], plain["planets"]);
+    let sun_to_sun = |response: &serde_json::Value| {
+        response["transit"]["transit_to_natal_aspects"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|a| a["planet1"] == "Natal Sun" && a["planet2"] == "Transit Sun")
+            .map(|a| a["orb"].as_f64().unwrap())
+            .unwrap()
+    };
+    assert!((sun_to_sun(&corrected) - (sun_to_sun(&plain) - 1.0).abs()).abs() < 0.01);
+
+    // A precessed solar return comes a little over a day after the plain one, the
+    // Sun moving under a degree a day in July
+    let mut request = json!({
+        "natal_date": "1950-07-01T12:00:00Z",
+        "latitude": 51.5074,
+        "longitude": -0.1278,
+        "house_system": "placidus",
+        "ayanamsa": "tropical",
+        "body": "sun",
+        "occurrence": { "year": 2022 }
+    });
+    let resp = test::TestRequest::post().uri("/api/chart/return").set_json(&request).send_request(&app).await;
+    let plain: serde_json::Value = serde_json::from_slice(&test::read_body(resp).await).unwrap();
+    assert!(plain.get("precessed_natal_longitude").is_none());
+    request["precession_correction"] = json!(true);
+    let resp = test::TestRequest::post().uri("/api/chart/return").set_json(&request).send_request(&app).await;
+    assert!(resp.status().is_success());
+    let corrected: serde_json::Value = serde_json::from_slice(&test::read_body(resp).await).unwrap();
+    assert_eq!(corrected["natal_longitude"], plain["natal_longitude"]);
+    let target = corrected["precessed_natal_longitude"].as_f64().unwrap();
+    assert!((target - plain["natal_longitude"].as_f64().unwrap() - 1.0).abs() < 0.01);
+    let sun = corrected["chart"]["planets"][0]["longitude"].as_f64().unwrap();
+    assert!((sun - target).abs() < 0.001);
+    let date = |response: &serde_json::Value| {
+        serde_json::from_value::<chrono::DateTime<chrono::Utc>>(response["chart"]["date"].clone()).unwrap()
+    };
+    let days = (date(&corrected) - date(&plain)).num_seconds() as f64 / 86400.0;
+    assert!((1.0..1.1).contains(&days), "precessed return {} days later", days);
+}
+
+#[actix_web::test]
+async fn test_render_job() {
+    if analytic_backend() {
+        return;
+    }
+    ensure_swiss_ephemeris_initialized().await;
+    let app = test::init_service(App::new().configure(config)).await;
+
+    let chart = json!({
+        "date": "1977-10-24T04:56:00Z",
+        "latitude": 14.6488,
+        "longitude": 121.0509,
+        "house_system": "placidus",
+        "ayanamsa": "tropical",
+        "transit": { "date": "2025-05-27T12:00:00Z" }
+    });
+    let resp = test::TestRequest::post()
+        .uri("/api/render")
+        .set_json(json!({ "chart": chart, "format": "svg" }))
+        .send_request(&app)
+        .await;
+    assert_eq!(resp.status(), 202);
+    let location = resp.headers().get("location").unwrap().to_str().unwrap().to_string();
+    let job: serde_json::Value = serde_json::from_slice(&test::read_body(resp).await).unwrap();
+    assert_eq!(job["status"], "pending");
+    assert_eq!(location, format!("/api/render/{}", job["id"]));
+
+    // Poll until the job finishes
+    let mut rendered = None;
+    for _ in 0..500 {
+        let resp = test::TestRequest::get().uri(&location).send_request(&app).await;
+        match resp.status().as_u16() {
+            202 => actix_web::rt::time::sleep(std::time::Duration::from_millis(10)).await,
+            200 => {
+                assert_eq!(resp.headers().get("content-type").unwrap(), "image/svg+xml");
+                rendered = Some(String::from_utf8(test::read_body(resp).await.to_vec()).unwrap());
+                break;
+            }
+            status => panic!("render job answered {}", status),
+        }
+    }
+    let rendered = rendered.expect("render job didn't finish");
+
+    // The same SVG as rendering synchronously
+    let resp = test::TestRequest::post().uri("/api/chart").set_json(&chart).send_request(&app).await;
+    let response: serde_json::Value = serde_json::from_slice(&test::read_body(resp).await).unwrap();
+    assert_eq!(response["svg_chart"].as_str().unwrap(), rendered);
+
+    let resp = test::TestRequest::post()
+        .uri("/api/render")
+        .set_json(json!({ "chart": chart, "format": "png" }))
+        .send_request(&app)
+        .await;
+    assert_eq!(resp.status(), 400);
+    let resp = test::TestRequest::get().uri("/api/render/999999").send_request(&app).await;
+    assert_eq!(resp.status(), 404);
+}
+
+#[actix_web::test]
+async fn test_vertex_and_east_point() {
+    if analytic_backend() {
+        return;
+    }
+    use astrolog_rs::calc::planets::{planet_longitude_at, Planet};
+    use astrolog_rs::calc::utils::{date_to_julian, julian_to_date};
+    use astrolog_rs::core::types::{EphemerisSource, HouseSystem};
+
+    ensure_swiss_ephemeris_initialized().await;
+    let app = test::init_service(App::new().configure(config)).await;
+
+    let chart1 = json!({
+        "date": "1977-10-24T04:56:00Z",
+        "latitude": 14.6488,
+        "longitude": 121.0509,
+        "house_system": "placidus",
+        "ayanamsa": "tropical",
+        "aspect_points": ["vertex", "anti_vertex", "east_point"]
+    });
+    let resp = test::TestRequest::post()
+        .uri("/api/chart/natal")
+        .set_json(&chart1)
+        .send_request(&app)
+        .await;
+    assert!(resp.status().is_success());
+    let natal: serde_json::Value = serde_json::from_slice(&test::read_body(resp).await).unwrap();
+
+    // The points come straight from swe_houses, rounded to 6 decimals
+    let jd = date_to_julian("1977-10-24T04:56:00Z".parse().unwrap());
+    let (_, ascmc) = swiss_ephemeris::calculate_house_cusps_swiss(jd, 14.6488, 121.0509, HouseSystem::Placidus).unwrap();
+    let points = natal["points"].as_array().unwrap();
+    let names: Vec<&str> = points.iter().map(|p| p["name"].as_str().unwrap()).collect();
+    assert_eq!(names, ["Vertex", "AntiVertex", "EastPoint"]);
+    let vertex = points[0]["longitude"].as_f64().unwrap();
+    assert!((vertex - ascmc[3]).abs() < 1e-6);
+    assert!((points[1]["longitude"].as_f64().unwrap() - (ascmc[3] + 180.0) % 360.0).abs() < 1e-6);
+    assert!((points[2]["longitude"].as_f64().unwrap() - ascmc[4]).abs() < 1e-6);
+    assert_eq!(points[0]["label"], "Vx");
+    assert!(points.iter().all(|p| (1..=12).contains(&p["house"].as_u64().unwrap())));
+    let svg = natal["svg_chart"].as_str().unwrap();
+    assert_eq!(svg.matches("class=\"angle-point\"").count(), 3);
+    assert!(svg.contains("\nVx\n") && svg.contains("\nEP\n"));
+    // Aspects to a point stay within its 2° orb
+    for aspect in natal["aspects"].as_array().unwrap() {
+        if names.contains(&aspect["planet2"].as_str().unwrap()) {
+            assert!(aspect["orb"].as_f64().unwrap() <= 2.0);
+        }
+    }
+
+    // A second chart cast for when the Moon crossed the first chart's Vertex
+    let start = date_to_julian("2000-01-01T00:00:00Z".parse().unwrap());
+    let crossing = (0..30 * 24)
+        .map(|hour| start + hour as f64 / 24.0)
+        .min_by(|a, b| {
+            let distance = |jd: f64| {
+                let moon = planet_longitude_at(Planet::Moon, jd, EphemerisSource::Auto).unwrap();
+                let diff = (moon - vertex).rem_euclid(360.0);
+                diff.min(360.0 - diff)
+            };
+            distance(*a).total_cmp(&distance(*b))
+        })
+        .unwrap();
+    let mut chart2 = chart1.clone();
+    chart2["date"] = json!(julian_to_date(crossing).unwrap());
+    chart2["aspect_points"] = json!([]);
+
+    let resp = test::TestRequest::post()
+        .uri("/api/chart/synastry")
+        .set_json(json!({"chart1": chart1, "chart2": chart2}))
+        .send_request(&app)
+        .await;
+    assert!(resp.status().is_success());
+    let synastry: serde_json::Value = serde_json::from_slice(&test::read_body(resp).await).unwrap();
+    let synastries = synastry["synastries"].as_array().unwrap();
+    let contact = synastries
+        .iter()
+        .find(|a| a["person1_planet"] == "Vertex" && a["person2_planet"] == "Moon")
+        .expect("Moon on the Vertex");
+    assert_eq!(contact["aspect"], "Conjunction");
+    assert!(contact["orb"].as_f64().unwrap() < 1.0);
+    let strengths: Vec<f64> = synastries.iter().map(|a| a["strength"].as_f64().unwrap()).collect();
+    assert!(strengths.windows(2).all(|pair| pair[0] >= pair[1]));
+
+    // Without the points there's no contact to report
+    let mut plain = chart1.clone();
+    plain["aspect_points"] = json!([]);
+    let resp = test::TestRequest::post()
+        .uri("/api/chart/synastry")
+        .set_json(json!({"chart1": plain, "chart2": chart2}))
+        .send_request(&app)
+        .await;
+    let synastry: serde_json::Value = serde_json::from_slice(&test::read_body(resp).await).unwrap();
+    assert!(synastry["synastries"].as_array().unwrap().iter().all(|a| a["person1_planet"] != "Vertex"));
+    assert!(synastry["chart1"].get("points").is_none());
+
+    for points in [json!(["fortune"]), json!(["vertex", "Vertex"])] {
+        let mut request = chart1.clone();
+        request["aspect_points"] = points;
+        let resp = test::TestRequest::post()
+            .uri("/api/chart/natal")
+            .set_json(&request)
+            .send_request(&app)
+            .await;
+        assert_eq!(resp.status(), 400);
+    }
+}
+
+#[actix_web::test]
+async fn test_chart_angles() {
+    if analytic_backend() {
+        return;
+    }
+    use astrolog_rs::calc::utils::date_to_julian;
+    use astrolog_rs::core::types::HouseSystem;
+
+    ensure_swiss_ephemeris_initialized().await;
+    let app = test::init_service(App::new().configure(config)).await;
+
+    let mut request = json!({
+        "date": "1977-10-24T04:56:00Z",
+        "latitude": 14.6488,
+        "longitude": 121.0509,
+        "house_system": "whole_sign",
+        "ayanamsa": "tropical"
+    });
+    let resp = test::TestRequest::post().uri("/api/chart/natal").set_json(&request).send_request(&app).await;
+    assert!(resp.status().is_success());
+    let natal: serde_json::Value = serde_json::from_slice(&test::read_body(resp).await).unwrap();
+
+    // The angles come from swe_houses whatever the house system, so the
+    // Ascendant needn't be a cusp
+    let jd = date_to_julian("1977-10-24T04:56:00Z".parse().unwrap());
+    let (_, ascmc) = swiss_ephemeris::calculate_house_cusps_swiss(jd, 14.6488, 121.0509, HouseSystem::Placidus).unwrap();
+    let angles = &natal["angles"];
+    let angle = |name: &str| angles[name].as_f64().unwrap();
+    for (name, expected) in [
+        ("ascendant", ascmc[0]),
+        ("descendant", (ascmc[0] + 180.0) % 360.0),
+        ("midheaven", ascmc[1]),
+        ("ic", (ascmc[1] + 180.0) % 360.0),
+        ("armc", ascmc[2]),
+        ("vertex", ascmc[3]),
+        ("east_point", ascmc[4]),
+    ] {
+        assert!((angle(name) - expected).abs() < 1e-6, "{}: {} vs {}", name, angle(name), expected);
+    }
+    assert_ne!(natal["houses"][0]["longitude"].as_f64().unwrap(), angle("ascendant"));
+    assert!(natal["aspects"].as_array().unwrap().iter().all(|a| a["planet2"] != "Ascendant"));
+
+    // The wheel draws the axes through the angles, not the cusps
+    let svg = natal["svg_chart"].as_str().unwrap();
+    assert!(svg.contains("id=\"axis-ascendant\"") && svg.contains("id=\"axis-midheaven\""));
+    assert_eq!(svg.matches("class=\"house-cusp\"").count(), 12);
+
+    request["include_angle_aspects"] = json!(true);
+    let resp = test::TestRequest::post().uri("/api/chart/natal").set_json(&request).send_request(&app).await;
+    let natal: serde_json::Value = serde_json::from_slice(&test::read_body(resp).await).unwrap();
+    let to_angles: Vec<&serde_json::Value> = natal["aspects"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .filter(|a| a["planet2"] == "Ascendant" || a["planet2"] == "Midheaven")
+        .collect();
+    assert!(!to_angles.is_empty());
+    assert!(to_angles.iter().all(|a| a["orb"].as_f64().unwrap() <= 2.0));
+    // The angles are aspected, not listed as points
+    assert!(natal.get("points").is_none());
+
+    let resp = test::TestRequest::post()
+        .uri("/api/chart/transit")
+        .set_json(json!({
+            "natal_date": "1977-10-24T04:56:00Z",
+            "transit_date": "2024-01-01T00:00:00Z",
+            "latitude": 14.6488,
+            "longitude": 121.0509,
+            "house_system": "placidus",
+            "ayanamsa": "tropical",
+            "include_angle_aspects": true
+        }))
+        .send_request(&app)
+        .await;
+    assert!(resp.status().is_success());
+    let transit: serde_json::Value = serde_json::from_slice(&test::read_body(resp).await).unwrap();
+    assert!((transit["angles"]["ascendant"].as_f64().unwrap() - ascmc[0]).abs() < 1e-6);
+    assert!(transit["natal_aspects"].as_array().unwrap().iter().any(|a| a["planet2"] == "Ascendant"));
+}
+
+#[actix_web::test]
+async fn test_compact_svg_and_rounded_numbers() {
+    if analytic_backend() {
+        return;
+    }
+    ensure_swiss_ephemeris_initialized().await;
+    let app = test::init_service(App::new().configure(config)).await;
+
+    let mut sizes = Vec::new();
+    let mut texts = Vec::new();
+    for detail in ["full", "compact"] {
+        let resp = test::TestRequest::post()
+            .uri("/api/chart/natal")
+            .set_json(json!({
+                "date": "1977-10-24T04:56:00Z",
+                "latitude": 14.6488,
+                "longitude": 121.0509,
+                "house_system": "placidus",
+                "ayanamsa": "tropical",
+                "svg_detail": detail
+            }))
+            .send_request(&app)
+            .await;
+        assert!(resp.status().is_success());
+        let body = test::read_body(resp).await;
+        let response: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        let svg = response["svg_chart"].as_str().unwrap();
+        assert_eq!(response["meta"]["svg_bytes"].as_u64().unwrap(), svg.len() as u64);
+        sizes.push(svg.len());
+
+        // Numbers are written with at most 6 decimals for longitudes and 3 for orbs
+        let decimals = |value: &serde_json::Value| {
+            let text = value.to_string();
+            text.split_once('.').map_or(0, |(_, fraction)| fraction.len())
+        };
+        for planet in response["planets"].as_array().unwrap() {
+            assert!(decimals(&planet["longitude"]) <= 6, "{}", planet["longitude"]);
+            assert!(decimals(&planet["latitude"]) <= 6, "{}", planet["latitude"]);
+        }
+        for house in response["houses"].as_array().unwrap() {
+            assert!(decimals(&house["longitude"]) <= 6, "{}", house["longitude"]);
+        }
+        let aspects = response["aspects"].as_array().unwrap();
+        assert!(!aspects.is_empty());
+        for aspect in aspects {
+            assert!(decimals(&aspect["orb"]) <= 3, "{}", aspect["orb"]);
+        }
+
+        texts.push((svg.matches("<text").count(), response["planets"].as_array().unwrap().len()));
+    }
+    // Compact drops the degree label under each planet
+    assert_eq!(texts[0].0 - texts[1].0, texts[1].1);
+    assert!(sizes[1] * 100 < sizes[0] * 85, "compact SVG is {} of {} bytes", sizes[1], sizes[0]);
+
+    let resp = test::TestRequest::post()
+        .uri("/api/chart/natal")
+        .set_json(json!({
+            "date": "1977-10-24T04:56:00Z",
+            "latitude": 14.6488,
+            "longitude": 121.0509,
+            "svg_detail": "tiny"
+        }))
+        .send_request(&app)
+        .await;
+    assert_eq!(resp.status(), 400);
+}
+
+#[actix_web::test]
+async fn test_whole_sign_wheel_style() {
+    if analytic_backend() {
+        return;
+    }
+    ensure_swiss_ephemeris_initialized().await;
+    let app = test::init_service(App::new().configure(config)).await;
+
+    let mut request = json!({
+        "date": "1977-10-24T04:56:00Z",
+        "latitude": 14.6488,
+        "longitude": 121.0509,
+        "house_system": "whole_sign",
+        "ayanamsa": "tropical",
+        "wheel_style": "whole_sign"
+    });
+    let resp = test::TestRequest::post()
+        .uri("/api/chart/natal")
+        .set_json(&request)
+        .send_request(&app)
+        .await;
+    assert!(resp.status().is_success());
+    let response: serde_json::Value = serde_json::from_slice(&test::read_body(resp).await).unwrap();
+    let svg = response["svg_chart"].as_str().unwrap();
+    assert_eq!(svg.matches(r#"class="sector-house""#).count(), 12);
+    assert!(!svg.contains("house-cusp"));
+
+    request["wheel_style"] = json!("equal");
+    let resp = test::TestRequest::post()
+        .uri("/api/chart/natal")
+        .set_json(&request)
+        .send_request(&app)
+        .await;
+    assert_eq!(resp.status(), 400);
+}
+
+#[actix_web::test]
+async fn test_svg_ids_are_stable_across_days() {
+    if analytic_backend() {
+        return;
+    }
+    ensure_swiss_ephemeris_initialized().await;
+    let app = test::init_service(App::new().configure(config)).await;
+
+    let mut renders = Vec::new();
+    for date in ["2024-03-20T12:00:00Z", "2024-03-20T12:00:00Z", "2024-03-21T12:00:00Z"] {
+        let request = json!({
+            "date": date,
+            "latitude": 51.4769,
+            "longitude": -0.0005,
+            "house_system": "placidus",
+            "ayanamsa": "tropical",
+            "svg_layers": "dynamic"
+        });
+        let resp = test::TestRequest::post()
+            .uri("/api/chart/natal")
+            .set_json(&request)
+            .send_request(&app)
+            .await;
+        assert!(resp.status().is_success());
+        let response: serde_json::Value = serde_json::from_slice(&test::read_body(resp).await).unwrap();
+        renders.push(response["svg_chart"].as_str().unwrap().to_string());
+    }
+    assert_eq!(renders[0], renders[1]);
+    assert!(!renders[0].contains("id=\"cusp-1\""));
+
+    // Each planet group, with its id and where it is drawn
+    let planets = |svg: &str| -> Vec<(String, String)> {
+        svg.lines()
+            .filter(|line| line.contains("id=\"planet-natal-"))
+            .map(|line| {
+                let field = |name: &str| line.split(&format!("{}=\"", name)).nth(1).unwrap().split('"').next().unwrap().to_string();
+                (field("id"), field("transform"))
+            })
+            .collect()
+    };
+    let (today, tomorrow) = (planets(&renders[1]), planets(&renders[2]));
+    assert!(today.iter().any(|(id, _)| id == "planet-natal-sun"));
+    assert_eq!(today.iter().map(|(id, _)| id).collect::<Vec<_>>(), tomorrow.iter().map(|(id, _)| id).collect::<Vec<_>>());
+    let moon = |planets: &[(String, String)]| planets.iter().find(|(id, _)| id == "planet-natal-moon").unwrap().1.clone();
+    assert_ne!(moon(&today), moon(&tomorrow));
+
+    let resp = test::TestRequest::post()
+        .uri("/api/chart/natal")
+        .set_json(json!({
+            "date": "2024-03-20T12:00:00Z",
+            "latitude": 51.4769,
+            "longitude": -0.0005,
+            "svg_layers": "static"
+        }))
+        .send_request(&app)
+        .await;
+    assert_eq!(resp.status(), 400);
+}
+
+#[actix_web::test]
+async fn test_equatorial_coordinates_agree_with_the_conversion() {
+    if analytic_backend() {
+        return;
+    }
+    use astrolog_rs::calc::coordinates::equatorial_of_date;
+    use astrolog_rs::calc::utils::date_to_julian;
+
+    ensure_swiss_ephemeris_initialized().await;
+    let app = test::init_service(App::new().configure(config)).await;
+
+    let date = "1977-10-24T04:56:00Z";
+    let mut request = json!({
+        "date": date,
+        "latitude": 14.6488,
+        "longitude": 121.0509,
+        "house_system": "placidus",
+        "ayanamsa": "tropical",
+        "coordinates": "both"
+    });
+    let resp = test::TestRequest::post()
+        .uri("/api/chart/natal")
+        .set_json(&request)
+        .send_request(&app)
+        .await;
+    assert!(resp.status().is_success());
+    let natal: serde_json::Value = serde_json::from_slice(&test::read_body(resp).await).unwrap();
+
+    // The library's own right ascension and declination, against the ecliptic
+    // positions turned onto the equator with the true obliquity
+    let jd = date_to_julian(date.parse().unwrap());
+    let arcseconds = 2.0 / 3600.0;
+    for planet in natal["planets"].as_array().unwrap() {
+        let converted = equatorial_of_date(jd, planet["longitude"].as_f64().unwrap(), planet["latitude"].as_f64().unwrap());
+        let right_ascension = planet["right_ascension"].as_f64().unwrap();
+        let declination = planet["declination"].as_f64().unwrap();
+        assert!((right_ascension - converted.right_ascension).abs() < arcseconds, "{}: {} against {}", planet["name"], right_ascension, converted.right_ascension);
+        assert!((declination - converted.declination).abs() < arcseconds, "{}: {} against {}", planet["name"], declination, converted.declination);
+        assert!((planet["right_ascension_hours"].as_f64().unwrap() * 15.0 - right_ascension).abs() < 1e-4);
+    }
+    // The Sun in late Libra is south of the equator, at about 13h 54m
+    let sun = &natal["planets"][0];
+    assert!(sun["declination"].as_f64().unwrap() < -11.0);
+    assert!(sun["formatted_ra"].as_str().unwrap().starts_with("13h 5"), "{}", sun["formatted_ra"]);
+
+    request["coordinates"] = json!("ecliptic");
+    let resp = test::TestRequest::post()
+        .uri("/api/chart/natal")
+        .set_json(&request)
+        .send_request(&app)
+        .await;
+    let ecliptic: serde_json::Value = serde_json::from_slice(&test::read_body(resp).await).unwrap();
+    assert!(ecliptic["planets"][0].get("right_ascension").is_none());
+
+    request["coordinates"] = json!("galactic");
+    let resp = test::TestRequest::post()
+        .uri("/api/chart/natal")
+        .set_json(&request)
+        .send_request(&app)
+        .await;
+    assert_eq!(resp.status(), 400);
+}
+
+#[actix_web::test]
+async fn test_decans_and_lunar_mansions() {
+    if analytic_backend() {
+        return;
+    }
+    use astrolog_rs::calc::subdivisions::{decan, mansion, MansionSystem};
+
+    ensure_swiss_ephemeris_initialized().await;
+    let app = test::init_service(App::new().configure(config)).await;
+
+    let request = json!({
+        "date": "1977-10-24T04:56:00Z",
+        "latitude": 14.6488,
+        "longitude": 121.0509,
+        "house_system": "placidus",
+        "ayanamsa": "tropical",
+        "include_decans": true,
+        "include_mansions": true
+    });
+    let resp = test::TestRequest::post()
+        .uri("/api/chart/natal")
+        .set_json(&request)
+        .send_request(&app)
+        .await;
+    assert!(resp.status().is_success());
+    let natal: serde_json::Value = serde_json::from_slice(&test::read_body(resp).await).unwrap();
+
+    for planet in natal["planets"].as_array().unwrap() {
+        let longitude = planet["longitude"].as_f64().unwrap();
+        let expected = decan(longitude);
+        assert_eq!(planet["decan"]["index"], expected.index);
+        assert_eq!(planet["decan"]["ruler"], format!("{:?}", expected.ruler));
+        assert_eq!(planet["mansion"]["system"], "arabic");
+        assert_eq!(planet["mansion"]["name"], mansion(longitude, MansionSystem::Arabic).name);
+    }
+    let moon = natal["planets"].as_array().unwrap().iter().find(|p| p["name"] == "Moon").unwrap();
+    assert_eq!(natal["moon_mansion"], moon["mansion"]);
+    // The Moon at 28°35' Pisces is in the last manzil
+    assert_eq!(natal["moon_mansion"]["index"], 28);
+
+    let mut nakshatra = request.clone();
+    nakshatra["mansion_system"] = json!("nakshatra");
+    let resp = test::TestRequest::post()
+        .uri("/api/chart/natal")
+        .set_json(&nakshatra)
+        .send_request(&app)
+        .await;
+    let natal: serde_json::Value = serde_json::from_slice(&test::read_body(resp).await).unwrap();
+    assert_eq!(natal["moon_mansion"]["system"], "nakshatra");
+    assert_eq!(natal["moon_mansion"]["name"], "Revati");
+
+    // Neither is reported unless asked for
+    let mut plain = request.clone();
+    plain["include_decans"] = json!(false);
+    plain["include_mansions"] = json!(false);
+    let resp = test::TestRequest::post()
+        .uri("/api/chart/natal")
+        .set_json(&plain)
+        .send_request(&app)
+        .await;
+    let natal: serde_json::Value = serde_json::from_slice(&test::read_body(resp).await).unwrap();
+    assert!(natal.get("moon_mansion").is_none());
+    assert!(natal["planets"][0].get("decan").is_none());
+    assert!(natal["planets"][0].get("mansion").is_none());
+
+    let mut invalid = request.clone();
+    invalid["mansion_system"] = json!("chinese");
+    let resp = test::TestRequest::post()
+        .uri("/api/chart/natal")
+        .set_json(&invalid)
+        .send_request(&app)
+        .await;
+    assert_eq!(resp.status(), 400);
+}
+
+#[actix_web::test]
+async fn test_angle_speeds_by_latitude() {
+    if analytic_backend() {
+        return;
+    }
+    ensure_swiss_ephemeris_initialized().await;
+    let app = test::init_service(App::new().configure(config)).await;
+
+    // The angles' speeds through a day, at the equator and at 60°N
+    let mut speeds = Vec::new();
+    for hour in 0..24 {
+        let mut at_hour = Vec::new();
+        for latitude in [0.0, 60.0] {
+            let request = json!({
+                "date": format!("2024-03-20T{:02}:00:00Z", hour),
+                "latitude": latitude,
+                "longitude": 0.0,
+                "house_system": "placidus",
+                "ayanamsa": "tropical",
+                "include_angle_speeds": true
+            });
+            let resp = test::TestRequest::post()
+                .uri("/api/chart/natal")
+                .set_json(&request)
+                .send_request(&app)
+                .await;
+            assert!(resp.status().is_success());
+            let natal: serde_json::Value = serde_json::from_slice(&test::read_body(resp).await).unwrap();
+            let angle_speeds = &natal["angle_speeds"];
+            let ascendant = angle_speeds["ascendant"].as_f64().unwrap();
+            let minutes_per_degree = angle_speeds["ascendant_minutes_per_degree"].as_f64().unwrap();
+            assert!((minutes_per_degree * ascendant - 1.0).abs() < 1e-9, "{}", angle_speeds);
+            at_hour.push((ascendant, angle_speeds["midheaven"].as_f64().unwrap()));
+        }
+        speeds.push(at_hour);
+    }
+
+    // The Midheaven moves with the sidereal time wherever the chart is
+    for at_hour in &speeds {
+        assert!((at_hour[0].1 - at_hour[1].1).abs() < 1e-6, "{:?}", at_hour);
+        assert!((0.22..0.28).contains(&at_hour[0].1), "{:?}", at_hour);
+    }
+    let mean_midheaven = speeds.iter().map(|at_hour| at_hour[0].1).sum::<f64>() / speeds.len() as f64;
+    assert!((mean_midheaven - 0.2507).abs() < 0.002, "{}", mean_midheaven);
+
+    // The Ascendant's speed stays in a narrow band at the equator, and at 60°N
+    // swings from a crawl to several times the Midheaven's
+    let range = |latitude: usize| {
+        let ascendant = speeds.iter().map(|at_hour| at_hour[latitude].0);
+        (ascendant.clone().fold(f64::MAX, f64::min), ascendant.fold(f64::MIN, f64::max))
+    };
+    let (slowest, fastest) = range(0);
+    assert!(slowest > 0.2 && fastest / slowest < 1.3, "{} {}", slowest, fastest);
+    let (slowest, fastest) = range(1);
+    assert!(slowest > 0.0 && fastest / slowest > 5.0, "{} {}", slowest, fastest);
+
+    // Not reported unless asked for
+    let resp = test::TestRequest::post()
+        .uri("/api/chart/natal")
+        .set_json(json!({
+            "date": "2024-03-20T12:00:00Z",
+            "latitude": 60.0,
+            "longitude": 0.0,
+            "house_system": "placidus",
+            "ayanamsa": "tropical"
+        }))
+        .send_request(&app)
+        .await;
+    let natal: serde_json::Value = serde_json::from_slice(&test::read_body(resp).await).unwrap();
+    assert!(natal.get("angle_speeds").is_none());
+}
+
+#[actix_web::test]
+async fn test_mundane_aries_ingress() {
+    if analytic_backend() {
+        return;
+    }
+    ensure_swiss_ephemeris_initialized().await;
+    let app = test::init_service(App::new().configure(config)).await;
+
+    let resp = test::TestRequest::post()
+        .uri("/api/chart/mundane")
+        .set_json(json!({
+            "event": "aries_ingress",
+            "year": 2025,
+            "location": { "latitude": 38.9072, "longitude": -77.0369 }
+        }))
+        .send_request(&app)
+        .await;
+    assert!(resp.status().is_success());
+    let mundane: serde_json::Value = serde_json::from_slice(&test::read_body(resp).await).unwrap();
+    assert_eq!(mundane["event"], "aries_ingress");
+    assert_eq!(mundane["chart"]["chart_type"], "mundane");
+
+    // 20 March 2025, 09:01 UT
+    let event_time: chrono::DateTime<chrono::Utc> = mundane["event_time"].as_str().unwrap().parse().unwrap();
+    let expected: chrono::DateTime<chrono::Utc> = "2025-03-20T09:01:00Z".parse().unwrap();
+    assert!((event_time - expected).num_seconds().abs() <= 120, "{}", event_time);
+    assert_eq!(mundane["chart"]["date"], mundane["event_time"]);
+
+    let planets = mundane["chart"]["planets"].as_array().unwrap();
+    let sun = planets.iter().find(|p| p["name"] == "Sun").unwrap()["longitude"].as_f64().unwrap();
+    assert!(sun.min(360.0 - sun) < 0.001, "Sun at {}", sun);
+    assert!(mundane["chart"]["svg_chart"].as_str().is_some());
+
+    let lunation = |body: serde_json::Value| {
+        test::TestRequest::post()
+            .uri("/api/chart/mundane")
+            .set_json(body)
+            .send_request(&app)
+    };
+    let resp = lunation(json!({
+        "event": "full_moon",
+        "year": 2025,
+        "month": 1,
+        "location": { "latitude": 51.5074, "longitude": -0.1278 }
+    }))
+    .await;
+    assert!(resp.status().is_success());
+    let full_moon: serde_json::Value = serde_json::from_slice(&test::read_body(resp).await).unwrap();
+    assert!(full_moon["event_time"].as_str().unwrap().starts_with("2025-01-13T22:2"));
+
+    for body in [
+        json!({ "event": "full_moon", "year": 2025, "location": { "latitude": 51.5, "longitude": 0.0 } }),
+        json!({ "event": "solar_eclipse", "year": 2025, "location": { "latitude": 51.5, "longitude": 0.0 } }),
+        json!({ "event": "aries_ingress", "year": 2025, "location": { "latitude": 95.0, "longitude": 0.0 } }),
+    ] {
+        assert_eq!(lunation(body).await.status(), 400);
+    }
+}
+
+#[actix_web::test]
+async fn test_positions_endpoint() {
+    if analytic_backend() {
+        return;
+    }
+    ensure_swiss_ephemeris_initialized().await;
+    let app = test::init_service(App::new().configure(config)).await;
+
+    let get = |uri: &'static str| test::TestRequest::get().uri(uri).send_request(&app);
+    let positions = |body: &[u8]| -> Vec<serde_json::Value> { serde_json::from_slice(body).unwrap() };
+
+    let resp = get("/api/positions?date=2024-04-15T12:00:00Z&bodies=sun,mercury,true_node").await;
+    assert!(resp.status().is_success());
+    let tropical = positions(&test::read_body(resp).await);
+    let bodies: Vec<&str> = tropical.iter().map(|p| p["body"].as_str().unwrap()).collect();
+    assert_eq!(bodies, ["Sun", "Mercury", "TrueNode"]);
+    assert_eq!(tropical[0]["sign"], "Aries");
+    assert_eq!(tropical[1]["retrograde"], true);
+    assert!(tropical[2]["speed"].as_f64().unwrap() != 0.0);
+
+    // Sidereal longitudes trail the tropical ones by the ayanamsa, 24°12' in 2024
+    let resp = get("/api/positions?date=2024-04-15T12:00:00Z&bodies=sun,mercury,true_node&zodiac=sidereal").await;
+    assert!(resp.status().is_success());
+    let sidereal = positions(&test::read_body(resp).await);
+    for (tropical, sidereal) in tropical.iter().zip(&sidereal) {
+        let shift = (tropical["longitude"].as_f64().unwrap() - sidereal["longitude"].as_f64().unwrap()).rem_euclid(360.0);
+        assert!((shift - 24.2).abs() < 0.01, "{}", shift);
+    }
+    assert_eq!((&tropical[1]["sign"], &sidereal[1]["sign"]), (&json!("Aries"), &json!("Pisces")));
+
+    // Now, with every body that has a Moshier theory
+    let resp = get("/api/positions").await;
+    assert!(resp.status().is_success());
+    assert_eq!(positions(&test::read_body(resp).await).len(), 12);
+
+    // From the Sun, Mercury moves forward
+    let resp = get("/api/positions?date=2024-04-15T12:00:00Z&bodies=mercury&center=helio").await;
+    assert!(resp.status().is_success());
+    assert_eq!(positions(&test::read_body(resp).await)[0]["retrograde"], false);
+
+    // The combined mode is the heliocentric position shifted by the ayanamsa
+    let resp = get("/api/positions?date=2024-04-15T12:00:00Z&bodies=mercury&zodiac=heliocentric-sidereal").await;
+    assert!(resp.status().is_success());
+    let combined = positions(&test::read_body(resp).await);
+    let resp = get("/api/positions?date=2024-04-15T12:00:00Z&bodies=mercury&zodiac=sidereal&center=helio").await;
+    let shifted = positions(&test::read_body(resp).await);
+    let delta = combined[0]["longitude"].as_f64().unwrap() - shifted[0]["longitude"].as_f64().unwrap();
+    assert!(delta.abs() < 0.01, "{}", delta);
+
+    for (uri, message) in [
+        ("/api/positions?bodies=sun,vulcan", "vulcan"),
+        ("/api/positions?bodies=moon&center=helio", "Moon"),
+        ("/api/positions?zodiac=draconic", "draconic"),
+        (
+            "/api/positions?zodiac=13-sign",
+            "Unsupported zodiac '13-sign': the 13-sign astronomical zodiac, with Ophiuchus, is not supported. \
+             Supported zodiacs: tropical, sidereal, heliocentric-sidereal",
+        ),
+        ("/api/positions?zodiac=tropical&ayanamsa=lahiri", "only applies to a sidereal zodiac"),
+        ("/api/positions?zodiac=sidereal&ayanamsa=raman", "Supported ayanamsas: lahiri"),
+        ("/api/positions?zodiac=heliocentric-sidereal&center=geo", "seen from center \"helio\""),
+    ] {
+        let resp = get(uri).await;
+        assert_eq!(resp.status(), 400, "{}", uri);
+        let body = String::from_utf8(test::read_body(resp).await.to_vec()).unwrap();
+        assert!(body.contains(message), "{}", body);
+    }
+}
+
+#[actix_web::test]
+async fn test_failed_bodies_become_warnings() {
+    if analytic_backend() {
+        return;
+    }
+    ensure_swiss_ephemeris_initialized().await;
+    let app = test::init_service(App::new().configure(config)).await;
+
+    let chart = |extra: serde_json::Value| {
+        let mut request = json!({
+            "date": "1977-10-24T04:56:00Z",
+            "latitude": 14.6488,
+            "longitude": 121.0509,
+            "house_system": "placidus",
+            "ayanamsa": "tropical",
+            "ephemeris": "moshier"
+        });
+        request.as_object_mut().unwrap().extend(extra.as_object().unwrap().clone());
+        test::TestRequest::post().uri("/api/chart").set_json(&request).send_request(&app)
+    };
+
+    // Chiron has no Moshier theory, so the chart is drawn without it
+    let resp = chart(json!({ "extra_bodies": ["chiron"] })).await;
+    assert_eq!(resp.status(), 200);
+    let response: serde_json::Value = serde_json::from_slice(&test::read_body(resp).await).unwrap();
+    let warnings = response["warnings"].as_array().unwrap();
+    assert_eq!(warnings.len(), 1);
+    assert_eq!(warnings[0]["body"], "Chiron");
+    assert!(warnings[0]["reason"].as_str().unwrap().contains("Chiron"));
+    let planets = response["planets"].as_array().unwrap();
+    assert_eq!(planets.len(), 12);
+    assert!(planets.iter().all(|p| p["name"] != "Chiron"));
+    assert!(!response["aspects"].as_array().unwrap().is_empty());
+    assert!(response["svg_chart"].as_str().unwrap().starts_with("<svg"));
+
+    // Without a failure there are no warnings
+    let resp = chart(json!({ "extra_bodies": ["true_node"] })).await;
+    assert_eq!(resp.status(), 200);
+    let response: serde_json::Value = serde_json::from_slice(&test::read_body(resp).await).unwrap();
+    assert!(response.get("warnings").is_none());
+    assert_eq!(response["planets"][10]["name"], "TrueNode");
+
+    // A required body still fails the chart
+    let resp = chart(json!({ "extra_bodies": ["chiron"], "required_bodies": ["chiron"] })).await;
+    assert_eq!(resp.status(), 500);
+    for extra in [
+        json!({ "extra_bodies": ["mars"] }),
+        json!({ "extra_bodies": ["fortune"] }),
+        json!({ "required_bodies": ["chiron"] }),
+    ] {
+        assert_eq!(chart(extra.clone()).await.status(), 400, "{}", extra);
+    }
+}
+
+#[actix_web::test]
+async fn test_asteroids_on_request() {
+    if analytic_backend() {
+        return;
+    }
+    ensure_swiss_ephemeris_initialized().await;
+    let app = test::init_service(App::new().configure(config)).await;
+
+    let chart = |extra: serde_json::Value| {
+        let mut request = json!({
+            "date": "1977-10-24T04:56:00Z",
+            "latitude": 14.6488,
+            "longitude": 121.0509,
+            "house_system": "placidus",
+            "ayanamsa": "tropical",
+            "ephemeris": "moshier",
+            "extra_bodies": ["chiron"]
+        });
+        request.as_object_mut().unwrap().extend(extra.as_object().unwrap().clone());
+        test::TestRequest::post().uri("/api/chart").set_json(&request).send_request(&app)
+    };
+    let warned = |response: &serde_json::Value| -> Vec<String> {
+        response["warnings"]
+            .as_array()
+            .map(|warnings| warnings.iter().map(|w| w["body"].as_str().unwrap().to_string()).collect())
+            .unwrap_or_default()
+    };
+
+    let resp = chart(json!({ "extra_bodies": [] })).await;
+    assert_eq!(resp.status(), 200);
+    let response: serde_json::Value = serde_json::from_slice(&test::read_body(resp).await).unwrap();
+    assert!(warned(&response).is_empty());
+
+    // The Moshier theory has none of them, so the chart is drawn without them;
+    // Chiron, already an extra body, is tried once
+    let resp = chart(json!({ "include_asteroids": true })).await;
+    assert_eq!(resp.status(), 200);
+    let response: serde_json::Value = serde_json::from_slice(&test::read_body(resp).await).unwrap();
+    assert_eq!(warned(&response), ["Chiron", "Ceres", "Pallas", "Juno", "Vesta"]);
+    assert!(response["warnings"][1]["reason"].as_str().unwrap().contains("Ceres"));
+    assert_eq!(response["planets"].as_array().unwrap().len(), 12);
+    assert!(response["svg_chart"].as_str().unwrap().starts_with("<svg"));
+
+    let resp = chart(json!({ "include_asteroids": true, "required_bodies": ["vesta"] })).await;
+    assert_eq!(resp.status(), 500);
+}
+
+#[actix_web::test]
+async fn test_nodes_and_lilith_in_charts() {
+    if analytic_backend() {
+        return;
+    }
+    ensure_swiss_ephemeris_initialized().await;
+    let app = test::init_service(App::new().configure(config)).await;
+
+    let chart = |extra: serde_json::Value| {
+        let mut request = json!({
+            "date": "2000-01-01T12:00:00Z",
+            "latitude": 51.4779,
+            "longitude": 0.0,
+            "house_system": "placidus",
+            "ayanamsa": "tropical",
+            "extra_bodies": []
+        });
+        request.as_object_mut().unwrap().extend(extra.as_object().unwrap().clone());
+        test::TestRequest::post().uri("/api/chart/natal").set_json(&request).send_request(&app)
+    };
+
+    // The Swiss Ephemeris has the True Node at 3°57' Leo and Mean Lilith at
+    // 23°28' Sagittarius, both from the files and from the Moshier theory
+    let resp = chart(json!({})).await;
+    assert_eq!(resp.status(), 200);
+    let response: serde_json::Value = serde_json::from_slice(&test::read_body(resp).await).unwrap();
+    let planets = response["planets"].as_array().unwrap();
+    assert_eq!(planets.len(), 12);
+    let true_node = &planets[10];
+    assert_eq!(true_node["name"], "TrueNode");
+    assert!((true_node["longitude"].as_f64().unwrap() - 123.954).abs() < 0.002, "{}", true_node);
+    assert_eq!(true_node["is_retrograde"], true);
+    let lilith = &planets[11];
+    assert_eq!(lilith["name"], "MeanLilith");
+    assert!((lilith["longitude"].as_f64().unwrap() - 263.4643).abs() < 0.001, "{}", lilith);
+    assert_eq!(lilith["is_retrograde"], false);
+    assert!((response["meta"]["lunar_nodes"]["north_node"].as_f64().unwrap() - true_node["longitude"].as_f64().unwrap()).abs() < 1e-9);
+    // The retrograde True Node is aspected like any other body
+    let aspects = response["aspects"].as_array().unwrap();
+    assert!(aspects
+        .iter()
+        .any(|aspect| aspect["planet1"] == "Venus" && aspect["planet2"] == "TrueNode" && aspect["aspect"] == "Trine"));
+    assert!(aspects.iter().any(|aspect| aspect["planet1"] == "MeanLilith" || aspect["planet2"] == "MeanLilith"));
+    assert!(response["svg_chart"].as_str().unwrap().contains("id=\"planet-natal-mean-lilith\""));
+
+    let resp = chart(json!({ "node_type": "mean" })).await;
+    let response: serde_json::Value = serde_json::from_slice(&test::read_body(resp).await).unwrap();
+    let mean_node = &response["planets"][10];
+    assert_eq!(mean_node["name"], "MeanNode");
+    assert!((mean_node["longitude"].as_f64().unwrap() - 125.0406).abs() < 0.001, "{}", mean_node);
+
+    let resp = chart(json!({ "include_nodes": false })).await;
+    let response: serde_json::Value = serde_json::from_slice(&test::read_body(resp).await).unwrap();
+    assert_eq!(response["planets"].as_array().unwrap().len(), 10);
+}
+
+#[actix_web::test]
+async fn test_aspects_sorted_by_strength() {
+    if analytic_backend() {
+        return;
+    }
+    ensure_swiss_ephemeris_initialized().await;
+    let app = test::init_service(App::new().configure(config)).await;
+    let chart = |sort: Option<&str>| {
+        let mut req = json!({
+            "date": "1990-01-01T12:00:00Z",
+            "latitude": 40.7128,
+            "longitude": -74.0060,
+            "house_system": "placidus",
+            "ayanamsa": "tropical"
+        });
+        if let Some(sort) = sort {
+            req["aspect_sort"] = json!(sort);
+        }
+        req
+    };
+
+    let resp = test::TestRequest::post()
+        .uri("/api/chart/natal")
+        .set_json(chart(None))
+        .send_request(&app)
+        .await;
+    assert!(resp.status().is_success());
+    let by_strength: serde_json::Value = serde_json::from_slice(&test::read_body(resp).await).unwrap();
+    let aspects = by_strength["aspects"].as_array().unwrap();
+    assert!(aspects.len() > 2);
+    let strengths: Vec<f64> = aspects.iter().map(|a| a["strength"].as_f64().unwrap()).collect();
+    assert!(strengths.windows(2).all(|pair| pair[0] >= pair[1]), "{:?}", strengths);
+    for aspect in aspects {
+        assert_eq!(aspect["exact"], aspect["orb"].as_f64().unwrap() < 0.1);
+    }
+
+    // The same aspects in the order the planets are listed
+    let resp = test::TestRequest::post()
+        .uri("/api/chart/natal")
+        .set_json(chart(Some("planets")))
+        .send_request(&app)
+        .await;
+    assert!(resp.status().is_success());
+    let by_planets: serde_json::Value = serde_json::from_slice(&test::read_body(resp).await).unwrap();
+    let planets = by_planets["aspects"].as_array().unwrap();
+    assert_eq!(planets.len(), aspects.len());
+    assert_eq!(planets[0]["planet1"], "Sun");
+    assert!(planets.iter().all(|a| aspects.contains(a)));
+
+    let resp = test::TestRequest::post()
+        .uri("/api/chart/natal")
+        .set_json(chart(Some("orb")))
+        .send_request(&app)
+        .await;
+    assert_eq!(resp.status(), 400);
+}
+
+#[actix_web::test]
+async fn test_chart_from_registered_ephemeris() {
+    use actix_web::web::Data;
+    use astrolog_rs::calc::ephemeris::{Ephemeris, MockEphemeris};
+    use astrolog_rs::calc::houses::{HousePosition, Houses};
+    use astrolog_rs::calc::planets::Planet;
+    use astrolog_rs::core::types::HouseSystem;
+    use std::sync::Arc;
+
+    // Fixed positions for 1 January 2000, 12:00 UT, with equal houses from 0° Aries
+    let jd = 2451545.0;
+    let houses = Houses {
+        cusps: (0..12)
+            .map(|i| HousePosition {
+                number: i as u8 + 1,
+                longitude: i as f64 * 30.0,
+                latitude: 0.0,
+            })
+            .collect(),
+        ascendant: 0.0,
+        midheaven: 270.0,
+        armc: 270.0,
+        vertex: 180.0,
+        east_point: 0.0,
+        system: HouseSystem::Equal,
+    };
+    let bodies = [
+        (Planet::Sun, 10.0, 1.0),
+        (Planet::Moon, 100.0, 13.0),
+        (Planet::Mercury, 20.0, -0.5),
+        (Planet::Venus, 45.0, 1.2),
+        (Planet::Mars, 190.0, 0.5),
+        (Planet::Jupiter, 250.0, 0.1),
+        (Planet::Saturn, 300.0, 0.05),
+        (Planet::Uranus, 330.0, 0.02),
+        (Planet::Neptune, 350.0, 0.01),
+        (Planet::Pluto, 280.0, 0.01),
+        (Planet::TrueNode, 125.0, -0.05),
+        (Planet::MeanNode, 124.0, -0.05),
+    ];
+    let mock = bodies
+        .iter()
+        .fold(MockEphemeris::new(jd), |mock, &(planet, longitude, speed)| {
+            mock.with_body(planet, longitude, 0.0, speed)
+        })
+        .with_houses(houses);
+    let ephemeris: Arc<dyn Ephemeris> = Arc::new(mock);
+    let app = test::init_service(App::new().configure(config).app_data(Data::from(ephemeris))).await;
+
+    let resp = test::TestRequest::post()
+        .uri("/api/chart/natal")
+        .set_json(json!({
+            "date": "2000-01-01T12:00:00Z",
+            "latitude": 40.7128,
+            "longitude": -74.0060,
+            "house_system": "placidus",
+            "ayanamsa": "tropical"
+        }))
+        .send_request(&app)
+        .await;
+    assert!(resp.status().is_success());
+    let response: serde_json::Value = serde_json::from_slice(&test::read_body(resp).await).unwrap();
+
+    // The chart is drawn from the fixtures, whatever the default backend; Mean
+    // Lilith has none, so it is left out
+    let planets = response["planets"].as_array().unwrap();
+    assert_eq!(planets.len(), 11);
+    assert_eq!(response["warnings"][0]["body"], "MeanLilith");
+    for (planet, &(_, longitude, speed)) in planets.iter().zip(&bodies) {
+        assert!((planet["longitude"].as_f64().unwrap() - longitude).abs() < 1e-9);
+        assert!((planet["speed"].as_f64().unwrap() - speed).abs() < 1e-6);
+        let house = (longitude / 30.0) as u64 + 1;
+        assert_eq!(planet["house"].as_u64().unwrap(), house);
+    }
+    assert_eq!(planets[2]["is_retrograde"], true);
+    assert_eq!(response["ephemeris"], "auto");
+    assert_eq!(response["meta"]["lunar_nodes"]["north_node"], 125.0);
+    let aspects = response["aspects"].as_array().unwrap();
+    assert!(aspects
+        .iter()
+        .any(|a| a["planet1"] == "Sun" && a["planet2"] == "Moon" && a["aspect"] == "Square" && a["orb"] == 0.0));
+}
+
+#[actix_web::test]
+async fn test_non_finite_position_is_unprocessable() {
+    use actix_web::web::Data;
+    use astrolog_rs::calc::ephemeris::{Ephemeris, MockEphemeris};
+    use astrolog_rs::calc::houses::{HousePosition, Houses};
+    use astrolog_rs::calc::planets::Planet;
+    use astrolog_rs::core::types::HouseSystem;
+    use std::sync::Arc;
+
+    let houses = Houses {
+        cusps: (0..12)
+            .map(|i| HousePosition {
+                number: i as u8 + 1,
+                longitude: i as f64 * 30.0,
+                latitude: 0.0,
+            })
+            .collect(),
+        ascendant: 0.0,
+        midheaven: 270.0,
+        armc: 270.0,
+        vertex: 180.0,
+        east_point: 0.0,
+        system: HouseSystem::Equal,
+    };
+    // An ephemeris failure that came back as NaN rather than an error
+    let mock = MockEphemeris::new(2451545.0)
+        .with_body(Planet::Sun, f64::NAN, 0.0, 1.0)
+        .with_body(Planet::Moon, 100.0, 0.0, 13.0)
+        .with_houses(houses);
+    let ephemeris: Arc<dyn Ephemeris> = Arc::new(mock);
+    let app = test::init_service(App::new().configure(config).app_data(Data::from(ephemeris))).await;
+
+    let request = json!({
+        "date": "2000-01-01T12:00:00Z",
+        "latitude": 40.7128,
+        "longitude": -74.0060,
+        "house_system": "placidus",
+        "ayanamsa": "tropical"
+    });
+    let resp = test::TestRequest::post().uri("/api/chart/natal").set_json(&request).send_request(&app).await;
+    assert_eq!(resp.status(), 422);
+    let message = String::from_utf8_lossy(&test::read_body(resp).await).to_string();
+    assert_eq!(message, "Calculation error: Sun longitude is NaN rather than a finite number");
+
+    let resp = test::TestRequest::post().uri("/api/v2/chart/natal").set_json(&request).send_request(&app).await;
+    assert_eq!(resp.status(), 422);
+    let error: serde_json::Value = serde_json::from_slice(&test::read_body(resp).await).unwrap();
+    assert_eq!(
+        error,
+        json!({
+            "error": "Unprocessable Entity",
+            "message": "Calculation error: Sun longitude is NaN rather than a finite number"
+        })
+    );
+}
+
+#[actix_web::test]
+async fn test_natal_chart_from_julian_date() {
+    use astrolog_rs::api::computation::calculate_natal_chart_jd;
+    use astrolog_rs::calc::utils::{date_to_julian, julian_to_date};
+    use astrolog_rs::core::types::HouseSystem;
+
+    if analytic_backend() {
+        return;
+    }
+    ensure_swiss_ephemeris_initialized().await;
+    let app = test::init_service(App::new().configure(config)).await;
+    let natal = |date: String, latitude: f64| {
+        test::TestRequest::post()
+            .uri("/api/chart/natal")
+            .set_json(json!({
+                "date": date,
+                "latitude": latitude,
+                "longitude": 121.0508,
+                "house_system": "placidus",
+                "ayanamsa": "tropical",
+                "include_minor_aspects": true
+            }))
+            .send_request(&app)
+    };
+
+    // 24 October 1977, and 12 March 1500, before the Gregorian reform
+    let birth = date_to_julian("1977-10-24T04:56:00Z".parse().unwrap());
+    for jd in [birth, 2268923.5] {
+        let chart = calculate_natal_chart_jd(jd, 14.6486, 121.0508, HouseSystem::Placidus, true).unwrap();
+        let date = julian_to_date(jd).unwrap();
+        assert_eq!(chart.date, date);
+        let resp = natal(date.to_rfc3339(), 14.6486).await;
+        assert!(resp.status().is_success());
+        let expected: serde_json::Value = serde_json::from_slice(&test::read_body(resp).await).unwrap();
+        let chart = serde_json::to_value(&chart).unwrap();
+        assert_eq!(chart["houses"], expected["houses"], "{}", jd);
+        assert_eq!(chart["ephemeris"], expected["ephemeris"], "{}", jd);
+        // The speeds can differ in the last digit, so the rest is compared field by field
+        let planets = |chart: &serde_json::Value| -> Vec<(String, String, u64)> {
+            chart["planets"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|p| (p["name"].to_string(), p["longitude"].to_string(), p["house"].as_u64().unwrap()))
+                .collect()
+        };
+        assert_eq!(planets(&chart), planets(&expected), "{}", jd);
+        let aspects = |chart: &serde_json::Value| -> Vec<String> {
+            chart["aspects"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|a| format!("{} {} {}", a["planet1"], a["aspect"], a["planet2"]))
+                .collect()
+        };
+        assert_eq!(aspects(&chart), aspects(&expected), "{}", jd);
+        assert!(chart["planets"].as_array().unwrap().iter().all(|p| p["house"].as_u64().is_some_and(|h| (1..=12).contains(&h))));
+    }
+
+    // Placidus isn't defined above the polar circles, on either path
+    let resp = natal("1977-10-24T04:56:00Z".to_string(), 80.0).await;
+    assert_eq!(resp.status(), 500);
+    let message = String::from_utf8_lossy(&test::read_body(resp).await).to_string();
+    let error = calculate_natal_chart_jd(birth, 80.0, 121.0508, HouseSystem::Placidus, false).unwrap_err();
+    assert!(error.to_string().contains(&message), "{} / {}", error, message);
+
+    assert!(calculate_natal_chart_jd(f64::NAN, 14.6486, 121.0508, HouseSystem::Placidus, false).is_err());
+    assert!(calculate_natal_chart_jd(1e12, 14.6486, 121.0508, HouseSystem::Placidus, false).is_err());
+}
+
+#[actix_web::test]
+async fn test_unsupported_options_on_the_registered_ephemeris() {
+    use actix_web::web::Data;
+    use astrolog_rs::calc::ephemeris::{Ephemeris, MockEphemeris};
+    use astrolog_rs::calc::houses::{HousePosition, Houses};
+    use astrolog_rs::calc::planets::Planet;
+    use astrolog_rs::core::types::HouseSystem;
+    use std::sync::Arc;
+
+    let houses = Houses {
+        cusps: (0..12)
+            .map(|i| HousePosition {
+                number: i as u8 + 1,
+                longitude: i as f64 * 30.0,
+                latitude: 0.0,
+            })
+            .collect(),
+        ascendant: 0.0,
+        midheaven: 270.0,
+        armc: 270.0,
+        vertex: 180.0,
+        east_point: 0.0,
+        system: HouseSystem::Equal,
+    };
+    // The mock has no parallax, so it can't see the positions from the birthplace
+    let mock = Planet::ALL
+        .into_iter()
+        .take(12)
+        .enumerate()
+        .fold(MockEphemeris::new(2451545.0), |mock, (i, planet)| {
+            mock.with_body(planet, i as f64 * 25.0, 0.0, 1.0)
+        })
+        .with_houses(houses);
+    let calls = mock.clone();
+    let ephemeris: Arc<dyn Ephemeris> = Arc::new(mock);
+    let app = test::init_service(App::new().configure(config).app_data(Data::from(ephemeris))).await;
+    let mut request = json!({
+        "date": "2000-01-01T12:00:00Z",
+        "latitude": 40.7128,
+        "longitude": -74.0060,
+        "house_system": "placidus",
+        "ayanamsa": "tropical",
+        "topocentric": true
+    });
+
+    for uri in ["/api/chart", "/api/chart/natal"] {
+        let resp = test::TestRequest::post().uri(uri).set_json(&request).send_request(&app).await;
+        assert_eq!(resp.status(), 422, "{}", uri);
+        let body = test::read_body(resp).await;
+        let message = String::from_utf8_lossy(&body);
+        assert!(message.contains("topocentric") && message.contains("allow_degraded"), "{}", message);
+    }
+    // Refused before anything was calculated
+    assert_eq!(calls.calls(), 0);
+
+    request["allow_degraded"] = json!(true);
+    let resp = test::TestRequest::post().uri("/api/chart/natal").set_json(&request).send_request(&app).await;
+    assert!(resp.status().is_success());
+    let response: serde_json::Value = serde_json::from_slice(&test::read_body(resp).await).unwrap();
+    assert_eq!(
+        response["degraded_options"],
+        json!([{
+            "option": "topocentric",
+            "requested": "topocentric",
+            "used": "geocentric",
+            "reason": "The ephemeris has no topocentric positions"
+        }])
+    );
+    assert_eq!(response["meta"]["flags"]["topocentric"], false);
+    assert_eq!(response["planets"][1]["longitude"], 25.0);
+
+    // Nor has it a sidereal zodiac
+    request["topocentric"] = json!(false);
+    request["ayanamsa"] = json!("lahiri");
+    let resp = test::TestRequest::post().uri("/api/chart/natal").set_json(&request).send_request(&app).await;
+    let response: serde_json::Value = serde_json::from_slice(&test::read_body(resp).await).unwrap();
+    assert_eq!(response["ayanamsa"], "tropical");
+    assert_eq!(
+        response["degraded_options"],
+        json!([{
+            "option": "ayanamsa",
+            "requested": "lahiri",
+            "used": "tropical",
+            "reason": "The ephemeris has no sidereal zodiac"
+        }])
+    );
+    assert_eq!(response["meta"]["flags"]["sidereal"], false);
+
+    // Without the options there is nothing to report
+    request["ayanamsa"] = json!("tropical");
+    let resp = test::TestRequest::post().uri("/api/chart/natal").set_json(&request).send_request(&app).await;
+    let response: serde_json::Value = serde_json::from_slice(&test::read_body(resp).await).unwrap();
+    assert!(response.get("degraded_options").is_none());
+}
+
+#[actix_web::test]
+async fn test_house_placements_with_the_ascendant_in_late_pisces() {
+    use actix_web::web::Data;
+    use astrolog_rs::calc::ephemeris::{Ephemeris, MockEphemeris};
+    use astrolog_rs::calc::houses::{HousePosition, Houses};
+    use astrolog_rs::calc::planets::Planet;
+    use astrolog_rs::core::types::HouseSystem;
+    use std::sync::Arc;
+
+    // Equal houses from an Ascendant at 25° Pisces, so the first runs 355° to 25°
+    let houses = Houses {
+        cusps: (0..12)
+            .map(|i| HousePosition {
+                number: i as u8 + 1,
+                longitude: (355.0 + i as f64 * 30.0) % 360.0,
+                latitude: 0.0,
+            })
+            .collect(),
+        ascendant: 355.0,
+        midheaven: 265.0,
+        armc: 265.0,
+        vertex: 175.0,
+        east_point: 355.0,
+        system: HouseSystem::Equal,
+    };
+    // The Sun just past 0° Aries and the Moon a few degrees short of it
+    let mock = Planet::ALL
+        .into_iter()
+        .take(12)
+        .enumerate()
+        .fold(MockEphemeris::new(2451545.0), |mock, (i, planet)| match planet {
+            Planet::Sun => mock.with_body(planet, 5.0, 0.0, 1.0),
+            Planet::Moon => mock.with_body(planet, 350.0, 0.0, 13.0),
+            _ => mock.with_body(planet, 40.0 + i as f64 * 25.0, 0.0, 0.1),
+        })
+        .with_houses(houses);
+    let ephemeris: Arc<dyn Ephemeris> = Arc::new(mock);
+    let app = test::init_service(App::new().configure(config).app_data(Data::from(ephemeris))).await;
+    let placed = |planets: &serde_json::Value, name: &str| -> (u64, f64) {
+        let planet = planets.as_array().unwrap().iter().find(|p| p["name"] == name).unwrap();
+        (planet["house"].as_u64().unwrap(), planet["house_fraction"].as_f64().unwrap())
+    };
+
+    let resp = test::TestRequest::post()
+        .uri("/api/chart")
+        .set_json(json!({
+            "date": "2000-01-01T12:00:00Z",
+            "latitude": 40.7128,
+            "longitude": -74.0060,
+            "house_system": "equal",
+            "ayanamsa": "tropical",
+            "transit": { "date": "2000-01-02T12:00:00Z" }
+        }))
+        .send_request(&app)
+        .await;
+    assert!(resp.status().is_success());
+    let response: serde_json::Value = serde_json::from_slice(&test::read_body(resp).await).unwrap();
+    let (house, fraction) = placed(&response["planets"], "Sun");
+    assert_eq!(house, 1);
+    assert!((fraction - 1.0 / 3.0).abs() < 1e-9, "{}", fraction);
+    assert_eq!(placed(&response["planets"], "Moon").0, 12);
+    // A day on, in the natal houses: the Moon has crossed into the first
+    let transit = &response["transit"]["planets"];
+    assert_eq!(placed(transit, "Sun").0, 1);
+    let (house, fraction) = placed(transit, "Moon");
+    assert_eq!(house, 1);
+    assert!((fraction - 8.0 / 30.0).abs() < 1e-9, "{}", fraction);
+    assert!(transit.as_array().unwrap().iter().all(|p| p["house"].is_u64()));
+
+    let resp = test::TestRequest::post()
+        .uri("/api/chart/transit")
+        .set_json(json!({
+            "natal_date": "2000-01-01T12:00:00Z",
+            "transit_date": "2000-01-02T12:00:00Z",
+            "latitude": 40.7128,
+            "longitude": -74.0060,
+            "house_system": "equal",
+            "ayanamsa": "tropical"
+        }))
+        .send_request(&app)
+        .await;
+    assert!(resp.status().is_success());
+    let response: serde_json::Value = serde_json::from_slice(&test::read_body(resp).await).unwrap();
+    assert_eq!(placed(&response["natal_planets"], "Moon").0, 12);
+    assert_eq!(placed(&response["transit_planets"], "Moon").0, 1);
+}
+
+#[actix_web::test]
+async fn test_capabilities() {
+    ensure_swiss_ephemeris_initialized().await;
+    let app = test::init_service(App::new().configure(config)).await;
+
+    let resp = test::TestRequest::get().uri("/api/capabilities").send_request(&app).await;
+    assert!(resp.status().is_success());
+    let response: serde_json::Value = serde_json::from_slice(&test::read_body(resp).await).unwrap();
+
+    let named = |list: &str, name: &str| -> serde_json::Value {
+        response[list]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|entry| entry["name"] == name)
+            .unwrap_or_else(|| panic!("{} not in {}", name, list))
+            .clone()
+    };
+    assert_eq!(response["house_systems"].as_array().unwrap().len(), 16);
+    for system in ["placidus", "whole_sign"] {
+        let entry = named("house_systems", system);
+        assert!(entry["status"] == "available" || entry["status"] == "unavailable");
+    }
+    assert_eq!(named("house_systems", "whole_sign")["status"], "available");
+    assert_eq!(named("house_systems", "whole_sign")["code"], "W");
+    assert_eq!(named("house_systems", "porphyrius")["code"], "O");
+
+    // The tests run without the .se1 files, which Chiron needs
+    let chiron = named("bodies", "Chiron");
+    assert_eq!(chiron["status"], "unavailable");
+    assert!(chiron["reason"].as_str().unwrap().contains("Chiron"));
+    assert_eq!(named("bodies", "Sun")["status"], "available");
+    assert_eq!(response["features"]["ephemeris_files"], false);
+    let sources = response["features"]["ephemeris_sources"].as_array().unwrap();
+    assert!(sources.contains(&json!("auto")) && !sources.contains(&json!("swiss")), "{:?}", sources);
+    assert_eq!(response["features"]["sidereal"], true);
+
+    let trine = named("aspects", "Trine");
+    assert_eq!((trine["angle"].as_f64(), trine["minor"].as_bool()), (Some(120.0), Some(false)));
+    assert_eq!(named("aspects", "Quincunx")["minor"], true);
+    assert_eq!(named("render_formats", "svg")["status"], "available");
+    assert_eq!(named("render_formats", "png")["status"], "unavailable");
+    assert_eq!(response["limits"]["max_transit_series"], 31);
+    assert_eq!(named("zodiacs", "sidereal")["ayanamsa"], "lahiri");
+    assert_eq!(named("zodiacs", "heliocentric-sidereal")["center"], "helio");
+    let thirteen = named("zodiacs", "13-sign");
+    assert_eq!(thirteen["status"], "unavailable");
+    assert!(thirteen["reason"].as_str().unwrap().contains("Ophiuchus"));
+
+    let moon = response["motion_ranges"].as_array().unwrap().iter().find(|range| range["body"] == "Moon").unwrap();
+    assert!(moon["min_speed"].as_f64().unwrap() > 11.0 && moon["max_speed"].as_f64().unwrap() < 16.0);
+}
+
+#[actix_web::test]
+async fn test_symbols() {
+    let app = test::init_service(App::new().configure(config)).await;
+    let get = |uri: &'static str| test::TestRequest::get().uri(uri).send_request(&app);
+
+    let resp = get("/api/symbols").await;
+    assert!(resp.status().is_success());
+    let response: serde_json::Value = serde_json::from_slice(&test::read_body(resp).await).unwrap();
+    assert_eq!(response["theme"], "dark");
+    assert_eq!(response["themes"], json!(["dark", "light"]));
+
+    let sun = &response["bodies"][0];
+    assert_eq!((sun["key"].as_str(), sun["glyph"].as_str(), sun["codepoint"].as_str()), (Some("sun"), Some("☉"), Some("U+2609")));
+    assert_eq!((sun["color"].as_str(), sun["category"].as_str()), (Some("#FFD900"), Some("luminary")));
+    let true_node = response["bodies"].as_array().unwrap().iter().find(|body| body["key"] == "true_node").unwrap();
+    assert_eq!((true_node["chart_name"].as_str(), true_node["display_name"].as_str()), (Some("TrueNode"), Some("True Node")));
+
+    let aries = &response["signs"][0];
+    assert_eq!((aries["codepoint"].as_str(), aries["category"].as_str()), (Some("U+2648"), Some("fire")));
+    assert_eq!(response["signs"].as_array().unwrap().len(), 12);
+
+    // Every aspect type is listed, under the name charts give it
+    let aspects: Vec<&str> = response["aspects"].as_array().unwrap().iter().map(|a| a["chart_name"].as_str().unwrap()).collect();
+    let all = get_aspect_types(true);
+    assert_eq!(aspects.len(), all.len());
+    for aspect in all {
+        assert!(aspects.contains(&format!("{:?}", aspect).as_str()), "{:?} missing", aspect);
+    }
+    let quintile = response["aspects"].as_array().unwrap().iter().find(|a| a["key"] == "quintile").unwrap();
+    assert_eq!((quintile["glyph"].as_str(), quintile["codepoint"].as_str()), (Some("Q"), None));
+
+    let resp = get("/api/symbols?theme=light").await;
+    assert!(resp.status().is_success());
+    let light: serde_json::Value = serde_json::from_slice(&test::read_body(resp).await).unwrap();
+    assert_eq!(light["theme"], "light");
+    assert_ne!(light["bodies"][0]["color"], sun["color"]);
+    assert_eq!(light["bodies"][1]["color"], response["bodies"][1]["color"]);
+
+    let resp = get("/api/symbols?theme=sepia").await;
+    assert_eq!(resp.status(), 400);
+    let body = String::from_utf8(test::read_body(resp).await.to_vec()).unwrap();
+    assert!(body.contains("Unknown theme 'sepia'") && body.contains("dark, light"), "{}", body);
+}
+
+#[actix_web::test]
+async fn test_synastry_second_house_ring() {
+    if analytic_backend() {
+        return;
+    }
+    ensure_swiss_ephemeris_initialized().await;
+    let app = test::init_service(App::new().configure(config)).await;
+
+    for show in [false, true] {
+        let request = json!({
+            "chart1": {
+                "date": "2000-01-01T12:00:00Z",
+                "latitude": 40.7128,
+                "longitude": -74.0060,
+                "house_system": "placidus",
+                "ayanamsa": "tropical",
+                "show_second_houses": show
+            },
+            "chart2": {
+                "date": "1995-01-01T12:00:00Z",
+                "latitude": 34.0522,
+                "longitude": -118.2437,
+                "house_system": "placidus",
+                "ayanamsa": "tropical"
+            }
+        });
+        let resp = test::TestRequest::post()
+            .uri("/api/chart/synastry")
+            .set_json(&request)
+            .send_request(&app)
+            .await;
+        assert!(resp.status().is_success());
+        let response: serde_json::Value = serde_json::from_slice(&test::read_body(resp).await).unwrap();
+        let svg = response["svg_chart"].as_str().unwrap();
+
+        assert_eq!(svg.matches("class=\"house-cusp\"").count(), 12);
+        let second = if show { 12 } else { 0 };
+        assert_eq!(svg.matches("class=\"second-house-cusp\"").count(), second);
+        assert_eq!(svg.matches("class=\"second-house-number\"").count(), second);
+    }
+}
+
+#[actix_web::test]
+async fn test_request_body_errors_name_the_field() {
+    let app = test::init_service(App::new().configure(config)).await;
+    let chart = json!({
+        "date": "2000-01-01T12:00:00Z",
+        "latitude": 40.7128,
+        "longitude": -74.0060,
+        "house_system": "placidus",
+        "ayanamsa": "tropical"
+    });
+    let error_of = |body: &[u8]| serde_json::from_slice::<serde_json::Value>(body).unwrap();
+
+    // A wrong-typed field deep in the request is reported by its path
+    let mut wrong_type = chart.clone();
+    wrong_type["transit"] = json!({ "date": 12345 });
+    let resp = test::TestRequest::post().uri("/api/chart").set_json(&wrong_type).send_request(&app).await;
+    assert_eq!(resp.status(), 400);
+    let error = error_of(&test::read_body(resp).await);
+    assert_eq!(error["error"], "Invalid request format");
+    assert!(error["message"].as_str().unwrap().starts_with("transit.date: "), "{}", error);
+
+    // Long arrays stop at their limit instead of being read whole
+    let mut many_aspects = chart.clone();
+    many_aspects["custom_aspects"] = json!(vec![json!({ "name": "Decile", "angle": 36.0, "orb": 1.0 }); 10_000]);
+    let resp = test::TestRequest::post().uri("/api/chart").set_json(&many_aspects).send_request(&app).await;
+    assert_eq!(resp.status(), 400);
+    let error = error_of(&test::read_body(resp).await);
+    assert!(error["message"].as_str().unwrap().starts_with("custom_aspects: invalid length"), "{}", error);
+
+    // Single-chart endpoints take 64 KB, the transit series endpoint more
+    let mut oversized = chart.clone();
+    oversized["extra_bodies"] = json!(["x".repeat(70 * 1024)]);
+    let resp = test::TestRequest::post().uri("/api/chart/natal").set_json(&oversized).send_request(&app).await;
+    assert_eq!(resp.status(), 413);
+    let error = error_of(&test::read_body(resp).await);
+    assert_eq!(error["error"], "Payload too large");
+    assert_eq!(error["message"], "Request body is over the 65536 byte limit");
+    // ...which reads the body and only then refuses the unknown body
+    let resp = test::TestRequest::post().uri("/api/chart").set_json(&oversized).send_request(&app).await;
+    assert_eq!(resp.status(), 400);
+
+    let resp = test::TestRequest::post()
+        .uri("/api/chart/natal")
+        .insert_header(("content-type", "text/plain"))
+        .set_payload(chart.to_string())
+        .send_request(&app)
+        .await;
+    assert_eq!(resp.status(), 415);
+}
+
+#[actix_web::test]
+async fn test_house_system_letters_and_unknown_names() {
+    if analytic_backend() {
+        return;
+    }
+    ensure_swiss_ephemeris_initialized().await;
+    let app = test::init_service(App::new().configure(config)).await;
+
+    let chart = |house_system: &str| {
+        json!({
+            "date": "2000-01-01T12:00:00Z",
+            "latitude": 40.7128,
+            "longitude": -74.0060,
+            "house_system": house_system,
+            "ayanamsa": "tropical"
+        })
+    };
+    let resp = test::TestRequest::post()
+        .uri("/api/chart/natal")
+        .set_json(chart("r"))
+        .send_request(&app)
+        .await;
+    assert!(resp.status().is_success());
+    let response: serde_json::Value = serde_json::from_slice(&test::read_body(resp).await).unwrap();
+    assert_eq!(response["meta"]["house_system_used"], "regiomontanus");
+    assert_eq!(response["meta"]["house_system_code"], "R");
+
+    // An unknown name is refused rather than calculated as Placidus
+    for uri in ["/api/chart/natal", "/api/chart"] {
+        let resp = test::TestRequest::post()
+            .uri(uri)
+            .set_json(chart("placidusss"))
+            .send_request(&app)
+            .await;
+        assert_eq!(resp.status(), 400, "{}", uri);
+        let body = test::read_body(resp).await;
+        assert!(String::from_utf8_lossy(&body).contains("placidusss"), "{:?}", body);
+    }
+}
+
+/// Names the API has always accepted for house systems, bodies and aspects still
+/// deserialize into the request types, and read back as the same values
+#[actix_web::test]
+async fn test_request_names_deserialize() {
+    use astrolog_rs::api::types::{ChartRequest, HouseCompareRequest, ReturnRequest};
+    use astrolog_rs::calc::aspects::AspectType;
+    use astrolog_rs::calc::planets::Planet;
+    use astrolog_rs::core::types::HouseSystem;
+
+    let chart = |fields: serde_json::Value| {
+        let mut request = json!({
+            "date": "2000-01-01T12:00:00Z",
+            "latitude": 40.7128,
+            "longitude": -74.0060,
+            "house_system": "placidus",
+            "ayanamsa": "tropical"
+        });
+        request.as_object_mut().unwrap().extend(fields.as_object().unwrap().clone());
+        serde_json::from_value::<ChartRequest>(request)
+    };
+    for (name, system) in [
+        ("placidus", HouseSystem::Placidus),
+        ("Placidus", HouseSystem::Placidus),
+        ("P", HouseSystem::Placidus),
+        ("r", HouseSystem::Regiomontanus),
+        ("whole_sign", HouseSystem::WholeSign),
+        ("wholesign", HouseSystem::WholeSign),
+        ("gauquelin", HouseSystem::Gauquelin),
+    ] {
+        assert_eq!(chart(json!({ "house_system": name })).unwrap().house_system, system, "{}", name);
+    }
+    assert!(chart(json!({ "house_system": "koch-ish" })).is_err());
+
+    let request = chart(json!({
+        "extra_bodies": ["chiron", "true_node", "MeanLilith"],
+        "required_bodies": ["Chiron"],
+        "aspect_filter": { "planets": ["Sun"], "aspects": ["Conjunction", "square", "semi_sextile"] }
+    }))
+    .unwrap();
+    assert_eq!(request.extra_bodies, [Planet::Chiron, Planet::TrueNode, Planet::MeanLilith]);
+    assert_eq!(request.required_bodies, [Planet::Chiron]);
+    let aspects = request.aspect_filter.unwrap().aspects;
+    assert_eq!(aspects, [AspectType::Conjunction, AspectType::Square, AspectType::SemiSextile]);
+    assert!(chart(json!({ "extra_bodies": ["eris"] })).is_err());
+    assert!(chart(json!({ "aspect_filter": { "aspects": ["Trien"] } })).is_err());
+
+    for (name, body) in [("sun", Planet::Sun), ("Moon", Planet::Moon), ("meannode", Planet::MeanNode)] {
+        let request: ReturnRequest = serde_json::from_value(json!({
+            "natal_date": "1950-07-01T12:00:00Z",
+            "latitude": 51.5074,
+            "longitude": -0.1278,
+            "house_system": "P",
+            "ayanamsa": "tropical",
+            "body": name
+        }))
+        .unwrap();
+        assert_eq!(request.body, body, "{}", name);
+    }
+
+    let request: HouseCompareRequest = serde_json::from_value(json!({
+        "date": "1977-10-24T04:56:00Z",
+        "latitude": 14.6488,
+        "longitude": 121.0509,
+        "house_systems": ["placidus", "equal", "wholesign"]
+    }))
+    .unwrap();
+    assert_eq!(request.house_systems, [HouseSystem::Placidus, HouseSystem::Equal, HouseSystem::WholeSign]);
+}
+
+#[actix_web::test]
+async fn test_sidereal_charts() {
+    if analytic_backend() {
+        return;
+    }
+    ensure_swiss_ephemeris_initialized().await;
+    let app = test::init_service(App::new().configure(config)).await;
+
+    let chart = |ayanamsa: &str, house_system: &str| {
+        json!({
+            "date": "2024-04-15T12:00:00Z",
+            "latitude": 40.7128,
+            "longitude": -74.0060,
+            "house_system": house_system,
+            "ayanamsa": ayanamsa
+        })
+    };
+    let mut responses = Vec::new();
+    for ayanamsa in ["tropical", "lahiri", "fagan_bradley"] {
+        let resp = test::TestRequest::post()
+            .uri("/api/chart/natal")
+            .set_json(chart(ayanamsa, "placidus"))
+            .send_request(&app)
+            .await;
+        assert!(resp.status().is_success(), "{}", ayanamsa);
+        let response: serde_json::Value = serde_json::from_slice(&test::read_body(resp).await).unwrap();
+        assert_eq!(response["ayanamsa"], ayanamsa);
+        assert_eq!(response["meta"]["flags"]["sidereal"], ayanamsa != "tropical");
+        assert!(response.get("degraded_options").is_none());
+        responses.push(response);
+    }
+    let shift = |sidereal: &serde_json::Value, path: &dyn Fn(&serde_json::Value) -> f64| {
+        (path(&responses[0]) - path(sidereal)).rem_euclid(360.0)
+    };
+    // Lahiri is a little over 24 degrees in 2024, the same for every body and cusp
+    let lahiri = shift(&responses[1], &|r| r["planets"][0]["longitude"].as_f64().unwrap());
+    assert!((lahiri - 24.19).abs() < 0.05, "{}", lahiri);
+    for i in 0..10 {
+        let planet = shift(&responses[1], &|r| r["planets"][i]["longitude"].as_f64().unwrap());
+        assert!((planet - lahiri).abs() < 1e-3, "planet {}: {}", i, planet);
+    }
+    for i in 0..12 {
+        let cusp = shift(&responses[1], &|r| r["houses"][i]["longitude"].as_f64().unwrap());
+        assert!((cusp - lahiri).abs() < 1e-3, "cusp {}: {}", i + 1, cusp);
+    }
+    let fagan_bradley = shift(&responses[2], &|r| r["planets"][0]["longitude"].as_f64().unwrap());
+    assert!((fagan_bradley - lahiri - 0.88).abs() < 0.05, "{}", fagan_bradley);
+
+    // Whole sign houses still start at a sign boundary
+    let resp = test::TestRequest::post()
+        .uri("/api/chart/natal")
+        .set_json(chart("lahiri", "wholesign"))
+        .send_request(&app)
+        .await;
+    let response: serde_json::Value = serde_json::from_slice(&test::read_body(resp).await).unwrap();
+    for house in response["houses"].as_array().unwrap() {
+        assert_eq!(house["longitude"].as_f64().unwrap() % 30.0, 0.0, "{}", house);
+    }
+
+    let resp = test::TestRequest::post()
+        .uri("/api/chart/natal")
+        .set_json(chart("bogus", "placidus"))
+        .send_request(&app)
+        .await;
+    assert_eq!(resp.status(), 400);
+    let body = test::read_body(resp).await;
+    assert!(
+        String::from_utf8_lossy(&body).contains("one of: lahiri, fagan_bradley, krishnamurti, raman"),
+        "{:?}",
+        body
+    );
+}
+
+#[actix_web::test]
+async fn test_chart_ayanamsa_is_checked() {
+    if analytic_backend() {
+        return;
+    }
+    ensure_swiss_ephemeris_initialized().await;
+    let app = test::init_service(App::new().configure(config)).await;
+
+    let chart = |ayanamsa: &str| {
+        json!({
+            "date": "2000-01-01T12:00:00Z",
+            "latitude": 40.7128,
+            "longitude": -74.0060,
+            "house_system": "placidus",
+            "ayanamsa": ayanamsa
+        })
+    };
+    for (uri, request, message) in [
+        ("/api/chart/natal", chart("13-sign"), "Supported zodiacs: tropical, sidereal, heliocentric-sidereal"),
+        ("/api/chart", chart("Astronomical"), "Ophiuchus"),
+        ("/api/chart/natal", chart("sayana"), "Expected \"tropical\", \"sidereal\" or one of: lahiri"),
+        (
+            "/api/chart/synastry",
+            json!({ "chart1": chart("tropical"), "chart2": chart("heliocentric-sidereal") }),
+            "only available from /api/positions",
+        ),
+    ] {
+        let resp = test::TestRequest::post().uri(uri).set_json(&request).send_request(&app).await;
+        assert_eq!(resp.status(), 400, "{}", uri);
+        let body = test::read_body(resp).await;
+        assert!(String::from_utf8_lossy(&body).contains(message), "{:?}", body);
+    }
+}
+
+#[actix_web::test]
+async fn test_chart_thumbnail() {
+    if analytic_backend() {
+        return;
+    }
+    ensure_swiss_ephemeris_initialized().await;
+    let app = test::init_service(App::new().configure(config)).await;
+
+    let mut request = json!({
+        "date": "2000-01-01T12:00:00Z",
+        "latitude": 40.7128,
+        "longitude": -74.0060,
+        "house_system": "placidus",
+        "ayanamsa": "tropical",
+        "include_minor_aspects": true
+    });
+    let resp = test::TestRequest::post()
+        .uri("/api/chart/natal")
+        .set_json(&request)
+        .send_request(&app)
+        .await;
+    let response: serde_json::Value = serde_json::from_slice(&test::read_body(resp).await).unwrap();
+    assert!(response.get("svg_thumbnail").is_none());
+
+    request["include_thumbnail"] = json!(true);
+    let resp = test::TestRequest::post()
+        .uri("/api/chart/natal")
+        .set_json(&request)
+        .send_request(&app)
+        .await;
+    assert!(resp.status().is_success());
+    let response: serde_json::Value = serde_json::from_slice(&test::read_body(resp).await).unwrap();
+    let thumbnail = response["svg_thumbnail"].as_str().unwrap();
+    assert!(thumbnail.starts_with("<svg"));
+    assert!(thumbnail.len() < 8 * 1024, "{} bytes", thumbnail.len());
+    assert!(!thumbnail.contains("<text"));
+
+    // One dot per planet, and lines for the major aspects only
+    let planets = response["planets"].as_array().unwrap().len();
+    assert_eq!(thumbnail.matches(r#"class="thumbnail-planet""#).count(), planets);
+    let major = response["aspects"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .filter(|aspect| ["Conjunction", "Sextile", "Square", "Trine", "Opposition"].contains(&aspect["aspect"].as_str().unwrap()))
+        .count();
+    assert!(major < response["aspects"].as_array().unwrap().len());
+    assert_eq!(thumbnail.matches(r#"class="thumbnail-aspect""#).count(), major);
+    let elements = thumbnail.matches('<').count() - thumbnail.matches("</").count();
+    assert!(elements <= 80, "{} elements", elements);
+}
+
+#[actix_web::test]
+async fn test_chart_transit_series() {
+    if analytic_backend() {
+        return;
+    }
+    ensure_swiss_ephemeris_initialized().await;
+    let app = test::init_service(App::new().configure(config)).await;
+
+    let mut request = json!({
+        "date": "1977-10-24T04:56:00Z",
+        "latitude": 14.6486,
+        "longitude": 121.0508,
+        "house_system": "placidus",
+        "ayanamsa": "tropical",
+        "transits": [
+            { "date": "2024-01-01T00:00:00Z" },
+            { "date": "2024-01-31T00:00:00Z" },
+            { "date": "2024-03-01T00:00:00Z", "latitude": 40.7128, "longitude": -74.0060 }
+        ]
+    });
+
+    let resp = test::TestRequest::post()
+        .uri("/api/chart")
+        .set_json(&request)
+        .send_request(&app)
+        .await;
+    assert!(resp.status().is_success());
+    let body = test::read_body(resp).await;
+    let response: serde_json::Value = serde_json::from_slice(&body).unwrap();
+
+    assert!(response.get("transit").is_none());
+    let transits = response["transits"].as_array().unwrap();
+    assert_eq!(transits.len(), 3);
+    assert_eq!(transits[0]["latitude"], 51.45);
+    assert_eq!(transits[2]["latitude"], 40.7128);
+    let suns: Vec<f64> = transits
+        .iter()
+        .map(|transit| transit["planets"][0]["longitude"].as_f64().unwrap())
+        .collect();
+    for pair in suns.windows(2) {
+        let advance = (pair[1] - pair[0]).rem_euclid(360.0);
+        assert!((28.0..32.0).contains(&advance), "Sun advanced {}°", advance);
+    }
+    assert!(response["svg_chart"].as_str().unwrap().contains("<svg"));
+
+    request["transits"] = json!(vec![json!({ "date": "2024-01-01T00:00:00Z" }); 32]);
+    let resp = test::TestRequest::post()
+        .uri("/api/chart")
+        .set_json(&request)
+        .send_request(&app)
+        .await;
+    assert_eq!(resp.status(), 400);
+}
+
+#[actix_web::test]
+async fn test_return_chart_endpoint() {
+    if analytic_backend() {
+        return;
+    }
+    ensure_swiss_ephemeris_initialized().await;
+    let app = test::init_service(App::new().configure(config)).await;
+
+    let mut request = json!({
+        "natal_date": "1995-06-01T12:00:00Z",
+        "latitude": 40.7128,
+        "longitude": -74.0060,
+        "house_system": "placidus",
+        "ayanamsa": "tropical",
+        "body": "moon",
+        "occurrence": "next",
+        "reference_date": "2024-01-01T00:00:00Z",
+        "location": { "latitude": 51.5074, "longitude": -0.1278 }
+    });
+
+    // Consecutive lunar returns, each found from just after the previous one
+    let mut dates = Vec::new();
+    for _ in 0..4 {
+        let resp = test::TestRequest::post()
+            .uri("/api/chart/return")
+            .set_json(&request)
+            .send_request(&app)
+            .await;
+        assert!(resp.status().is_success());
+        let body = test::read_body(resp).await;
+        let response: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(response["body"], "Moon");
+        assert_eq!(response["passes"].as_array().unwrap().len(), 1);
+        assert_eq!(response["chart"]["latitude"], 51.5074);
+        let moon = response["chart"]["planets"][1]["longitude"].as_f64().unwrap();
+        let natal = response["natal_longitude"].as_f64().unwrap();
+        assert!((moon - natal).abs() < 0.01);
+
+        let date: chrono::DateTime<chrono::Utc> =
+            serde_json::from_value(response["chart"]["date"].clone()).unwrap();
+        request["reference_date"] = json!(date + chrono::Duration::days(1));
+        dates.push(date);
+    }
+    for pair in dates.windows(2) {
+        let days = (pair[1] - pair[0]).num_seconds() as f64 / 86400.0;
+        assert!((27.2..27.6).contains(&days), "Lunar return interval {} days", days);
+    }
+
+    // Saturn's 2024-25 return loops over the natal degree
+    request["natal_date"] = json!("1995-02-15T12:00:00Z");
+    request["body"] = json!("saturn");
+    request["occurrence"] = json!({ "year": 2024 });
+    let resp = test::TestRequest::post()
+        .uri("/api/chart/return")
+        .set_json(&request)
+        .send_request(&app)
+        .await;
+    assert!(resp.status().is_success());
+    let body = test::read_body(resp).await;
+    let response: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    let passes = response["passes"].as_array().unwrap();
+    assert_eq!(passes.len(), 3);
+    assert_eq!(passes[1]["retrograde"], true);
+    assert_eq!(passes[2]["is_true_return"], true);
+    assert_eq!(passes[2]["date"], response["chart"]["date"]);
+    assert!(passes[..2].iter().all(|pass| pass["is_true_return"] == false));
+
+    // Saturn is back on its natal degree and moving direct again, while the
+    // return is cast in London against natal houses in New York
+    let vs_natal = &response["vs_natal"];
+    let bodies = vs_natal["bodies"].as_array().unwrap();
+    let saturn = bodies.iter().find(|body| body["body"] == "Saturn").unwrap();
+    assert_eq!(saturn["sign"]["changed"], false);
+    assert_eq!(saturn["retrograde"], json!({ "from": false, "to": false, "changed": false }));
+    assert_eq!(saturn["house"]["from"], saturn["own_house"]["from"]);
+    let sign_changes = bodies.iter().filter(|body| body["sign"]["changed"] == true).count();
+    assert_eq!(vs_natal["sign_changes"], sign_changes);
+    assert!(sign_changes > 0);
+
+    request["body"] = json!("meannode");
+    let resp = test::TestRequest::post()
+        .uri("/api/chart/return")
+        .set_json(&request)
+        .send_request(&app)
+        .await;
+    assert_eq!(resp.status(), 400);
+}
+
+#[actix_web::test]
+async fn test_chart_interpretation() {
+    if analytic_backend() {
+        return;
+    }
+    ensure_swiss_ephemeris_initialized().await;
+    let app = test::init_service(App::new().configure(config)).await;
+
+    let request = json!({
+        "date": "1977-10-24T04:56:00Z",
+        "latitude": 14.6486,
+        "longitude": 121.0508,
+        "house_system": "placidus",
+        "ayanamsa": "tropical",
+        "include_interpretation": true
+    });
+    let resp = test::TestRequest::post()
+        .uri("/api/chart/natal")
+        .set_json(&request)
+        .send_request(&app)
+        .await;
+    assert!(resp.status().is_success());
+    let body = test::read_body(resp).await;
+    let response: serde_json::Value = serde_json::from_slice(&body).unwrap();
+
+    let interpretation = &response["interpretation"];
+    assert_eq!(interpretation["pack"], "starter");
+    let sections = interpretation["sections"].as_array().unwrap();
+    assert_eq!(sections[0]["category"], "planet_in_sign");
+    let sun = &sections[0]["paragraphs"][0];
+    assert_eq!(sun["key"], "sun_in_scorpio");
+    assert_eq!(sun["factor"]["type"], "planet_in_sign");
+    assert_eq!(sun["factor"]["sign"], "Scorpio");
+}
+
+#[actix_web::test]
+async fn test_gauquelin_sectors() {
+    if analytic_backend() {
+        return;
+    }
+    ensure_swiss_ephemeris_initialized().await;
+    let app = test::init_service(App::new().configure(config)).await;
+
+    let request = json!({
+        "date": "1977-10-24T04:56:00Z",
+        "latitude": 14.6486,
+        "longitude": 121.0508,
+        "house_system": "gauquelin",
+        "ayanamsa": "tropical"
+    });
+    let resp = test::TestRequest::post()
+        .uri("/api/chart/natal")
+        .set_json(&request)
+        .send_request(&app)
+        .await;
+    assert!(resp.status().is_success());
+    let body = test::read_body(resp).await;
+    let response: serde_json::Value = serde_json::from_slice(&body).unwrap();
+
+    assert_eq!(response["house_count"], 36);
+    let houses = response["houses"].as_array().unwrap();
+    assert_eq!(houses.len(), 36);
+    let total: f64 = (0..36)
+        .map(|i| {
+            let cusp = houses[i]["longitude"].as_f64().unwrap();
+            let next = houses[(i + 1) % 36]["longitude"].as_f64().unwrap();
+            (cusp - next).rem_euclid(360.0)
+        })
+        .sum();
+    assert!((total - 360.0).abs() < 1e-6);
+    assert_eq!(response["meta"]["house_system_used"], "gauquelin");
+    assert_eq!(response["meta"]["house_system_code"], "G");
+    assert!(response["svg_chart"].as_str().unwrap().contains("<svg"));
+}
+
+#[actix_web::test]
+async fn test_compare_matches_synastry() {
+    if analytic_backend() {
+        return;
+    }
+    ensure_swiss_ephemeris_initialized().await;
+    let app = test::init_service(App::new().configure(config)).await;
+
+    let person1 = json!({
+        "date": "1990-01-01T12:00:00Z",
+        "latitude": 40.7128,
+        "longitude": -74.0060,
+        "house_system": "placidus",
+        "ayanamsa": "tropical"
+    });
+    let person2 = json!({
+        "date": "1992-06-15T08:30:00Z",
+        "latitude": 34.0522,
+        "longitude": -118.2437,
+        "house_system": "placidus",
+        "ayanamsa": "tropical"
+    });
+
+    let mut natal = Vec::new();
+    for person in [&person1, &person2] {
+        let resp = test::TestRequest::post()
+            .uri("/api/chart/natal")
+            .set_json(person)
+            .send_request(&app)
+            .await;
+        assert!(resp.status().is_success());
+        let chart: serde_json::Value = serde_json::from_slice(&test::read_body(resp).await).unwrap();
+        assert_eq!(chart["schema_version"], 1);
+        natal.push(chart);
+    }
+
+    let resp = test::TestRequest::post()
+        .uri("/api/chart/synastry")
+        .set_json(json!({ "chart1": person1, "chart2": person2 }))
+        .send_request(&app)
+        .await;
+    assert!(resp.status().is_success());
+    let synastry: serde_json::Value = serde_json::from_slice(&test::read_body(resp).await).unwrap();
+
+    let resp = test::TestRequest::post()
+        .uri("/api/compare")
+        .set_json(json!({ "chart1": natal[0], "chart2": natal[1] }))
+        .send_request(&app)
+        .await;
+    assert!(resp.status().is_success());
+    let compared: serde_json::Value = serde_json::from_slice(&test::read_body(resp).await).unwrap();
+
+    // The longitudes went through JSON rounded, so orbs may differ in the last digit
+    let same_aspects = |a: &serde_json::Value, b: &serde_json::Value| {
+        let (a, b) = (a.as_array().unwrap(), b.as_array().unwrap());
+        a.len() == b.len()
+            && a.iter().zip(b).all(|(a, b)| {
+                ["person1_planet", "person2_planet", "aspect"].iter().all(|k| a[k] == b[k])
+                    && (a["orb"].as_f64().unwrap() - b["orb"].as_f64().unwrap()).abs() < 2e-3
+            })
+    };
+    assert!(!synastry["synastries"].as_array().unwrap().is_empty());
+    assert!(same_aspects(&compared["synastries"], &synastry["synastries"]));
+    assert_eq!(compared["chart1_in_chart2_houses"].as_array().unwrap().len(), 12);
+    assert_eq!(compared["chart2_in_chart1_houses"].as_array().unwrap().len(), 12);
+    let sun1 = natal[0]["planets"][0]["longitude"].as_f64().unwrap();
+    let sun2 = natal[1]["planets"][0]["longitude"].as_f64().unwrap();
+    let composite_sun = compared["composite"][0]["longitude"].as_f64().unwrap();
+    let offset = |a: f64, b: f64| ((a - b).rem_euclid(360.0)).min((b - a).rem_euclid(360.0));
+    assert!((offset(composite_sun, sun1) - offset(composite_sun, sun2)).abs() < 1e-6);
+    // The Suns are in Capricorn and Gemini
+    let diff = &compared["chart2_vs_chart1"];
+    assert_eq!(diff["bodies"].as_array().unwrap().len(), 12);
+    assert_eq!(diff["bodies"][0]["sign"], json!({ "from": "Capricorn", "to": "Gemini", "changed": true }));
+    assert!(diff["bodies"][0]["own_house"]["to"].is_u64());
+
+    // Composite houses are Porphyry from the midpoints of the Ascendants and MCs
+    let composite_houses = compared["composite_houses"].as_array().unwrap();
+    assert_eq!(composite_houses.len(), 12);
+    let cusp = |chart: &serde_json::Value, house: usize| chart["houses"][house]["longitude"].as_f64().unwrap();
+    let composite_asc = composite_houses[0]["longitude"].as_f64().unwrap();
+    assert!((offset(composite_asc, cusp(&natal[0], 0)) - offset(composite_asc, cusp(&natal[1], 0))).abs() < 1e-5);
+    let composite_mc = composite_houses[9]["longitude"].as_f64().unwrap();
+    let composite_11th = composite_houses[10]["longitude"].as_f64().unwrap();
+    let composite_12th = composite_houses[11]["longitude"].as_f64().unwrap();
+    assert!((offset(composite_11th, composite_mc) - offset(composite_12th, composite_11th)).abs() < 1e-5);
+
+    let resp = test::TestRequest::post()
+        .uri("/api/compare")
+        .set_json(json!({ "chart1": natal[0], "chart2": natal[1], "composite_houses": "derive_placidus" }))
+        .send_request(&app)
+        .await;
+    assert!(resp.status().is_success());
+    let derived: serde_json::Value = serde_json::from_slice(&test::read_body(resp).await).unwrap();
+    let derived_2nd = derived["composite_houses"][1]["longitude"].as_f64().unwrap();
+    assert!((offset(derived_2nd, cusp(&natal[0], 1)) - offset(derived_2nd, cusp(&natal[1], 1))).abs() < 1e-5);
+    let resp = test::TestRequest::post()
+        .uri("/api/compare")
+        .set_json(json!({ "chart1": natal[0], "chart2": natal[1], "composite_houses": "koch" }))
+        .send_request(&app)
+        .await;
+    assert_eq!(resp.status(), 400);
+
+    // Positions alone are enough, but they must carry a schema version
+    let planets_only = |chart: &serde_json::Value, version: serde_json::Value| {
+        json!({ "schema_version": version, "planets": chart["planets"] })
+    };
+    let resp = test::TestRequest::post()
+        .uri("/api/compare")
+        .set_json(json!({
+            "chart1": planets_only(&natal[0], json!(1)),
+            "chart2": planets_only(&natal[1], json!(1))
+        }))
+        .send_request(&app)
+        .await;
+    assert!(resp.status().is_success());
+    let compared: serde_json::Value = serde_json::from_slice(&test::read_body(resp).await).unwrap();
+    assert!(same_aspects(&compared["synastries"], &synastry["synastries"]));
+    assert!(compared["chart1_in_chart2_houses"].as_array().unwrap().is_empty());
+    assert!(compared["composite_houses"].as_array().unwrap().is_empty());
+    let sun = &compared["chart2_vs_chart1"]["bodies"][0];
+    assert!(sun["house"].is_null() && sun["own_house"].is_null());
+    assert_eq!(compared["chart2_vs_chart1"]["house_changes"], 0);
+
+    for version in [json!(null), json!(99)] {
+        let resp = test::TestRequest::post()
+            .uri("/api/compare")
+            .set_json(json!({
+                "chart1": planets_only(&natal[0], version),
+                "chart2": natal[1]
+            }))
+            .send_request(&app)
+            .await;
+        assert_eq!(resp.status(), 400);
+    }
+}
+
+#[actix_web::test]
+async fn test_at_degree_looks_up_a_returned_chart() {
+    if analytic_backend() {
+        return;
+    }
+    ensure_swiss_ephemeris_initialized().await;
+    let app = test::init_service(App::new().configure(config)).await;
+
+    let resp = test::TestRequest::post()
+        .uri("/api/chart/natal")
+        .set_json(json!({
+            "date": "1990-01-01T12:00:00Z",
+            "latitude": 40.7128,
+            "longitude": -74.0060,
+            "house_system": "placidus",
+            "ayanamsa": "tropical"
+        }))
+        .send_request(&app)
+        .await;
+    assert!(resp.status().is_success());
+    let natal: serde_json::Value = serde_json::from_slice(&test::read_body(resp).await).unwrap();
+
+    let look_up = |chart: serde_json::Value, longitude: f64| {
+        test::TestRequest::post()
+            .uri("/api/chart/at_degree")
+            .set_json(json!({ "chart": chart, "longitude": longitude }))
+            .to_request()
+    };
+
+    // The IC begins the 4th house
+    let ic = natal["houses"][3]["longitude"].as_f64().unwrap();
+    let resp = test::call_service(&app, look_up(natal.clone(), ic)).await;
+    assert!(resp.status().is_success());
+    let lookup: serde_json::Value = serde_json::from_slice(&test::read_body(resp).await).unwrap();
+    assert_eq!(lookup["house"], 4);
+    assert!(lookup["house_fraction"].as_f64().unwrap() < 1e-6);
+    let degree = ic.floor() as u64 + 1;
+    assert_eq!(lookup["sabian"]["degree"], degree);
+    assert_eq!(lookup["sabian"]["label"], format!("{} {}", lookup["sign"].as_str().unwrap(), (degree - 1) % 30 + 1));
+    assert!(lookup["sabian"].get("text").is_none());
+
+    let sun = natal["planets"].as_array().unwrap().iter().find(|p| p["name"] == "Sun").unwrap();
+    let resp = test::call_service(&app, look_up(natal.clone(), sun["longitude"].as_f64().unwrap())).await;
+    assert!(resp.status().is_success());
+    let lookup: serde_json::Value = serde_json::from_slice(&test::read_body(resp).await).unwrap();
+    assert_eq!(lookup["sign"], "Capricorn");
+    assert_eq!(lookup["sign_ruler"], "Saturn");
+    assert_eq!(lookup["nearby"][0]["name"], "Sun");
+    assert_eq!(lookup["nearby"][0]["distance"], 0.0);
+
+    // Without houses there is no house to report
+    let planets_only = json!({ "schema_version": 1, "planets": natal["planets"] });
+    let resp = test::call_service(&app, look_up(planets_only, ic)).await;
+    assert!(resp.status().is_success());
+    let lookup: serde_json::Value = serde_json::from_slice(&test::read_body(resp).await).unwrap();
+    assert!(lookup.get("house").is_none());
+
+    let resp = test::call_service(&app, look_up(json!({ "planets": natal["planets"] }), ic)).await;
+    assert_eq!(resp.status(), 400);
+}
+
+#[actix_web::test]
+async fn test_plain_text_listing() {
+    if analytic_backend() {
+        return;
+    }
+    ensure_swiss_ephemeris_initialized().await;
+    let app = test::init_service(App::new().configure(config)).await;
+
+    let request = json!({
+        "date": "1977-10-24T04:56:00Z",
+        "latitude": 14.6488,
+        "longitude": 121.0509,
+        "house_system": "placidus",
+        "ayanamsa": "tropical",
+        "ephemeris": "moshier"
+    });
+    for uri in ["/api/chart/natal", "/api/chart"] {
+        let resp = test::TestRequest::post()
+            .uri(uri)
+            .insert_header(("accept", "text/plain"))
+            .set_json(&request)
+            .send_request(&app)
+            .await;
+        assert!(resp.status().is_success());
+        assert_eq!(resp.headers().get("content-type").unwrap(), "text/plain; charset=utf-8");
+        let listing = String::from_utf8(test::read_body(resp).await.to_vec()).unwrap();
+        assert_eq!(listing, include_str!("golden/chart_1977-10-24.txt"), "{}", uri);
+        for line in listing.lines() {
+            assert!(line.chars().count() < 80, "{}", line);
+        }
+    }
+
+    // JSON stays the default, and wins when the client ranks it first
+    let resp = test::TestRequest::post()
+        .uri("/api/chart/natal")
+        .insert_header(("accept", "application/json, text/plain;q=0.5"))
+        .set_json(&request)
+        .send_request(&app)
+        .await;
+    assert!(resp.status().is_success());
+    let response: serde_json::Value = serde_json::from_slice(&test::read_body(resp).await).unwrap();
+    assert!(response["planets"].is_array());
+}
+
+#[actix_web::test]
+async fn test_transits_to_natal_angles_and_houses() {
+    if analytic_backend() {
+        return;
+    }
+    ensure_swiss_ephemeris_initialized().await;
+    let app = test::init_service(App::new().configure(config)).await;
+
+    // The natal seventh cusp is near 10°19' Leo; the transiting Sun passes a
+    // degree beyond it late on 2 August 2024
+    let request = json!({
+        "natal_date": "1977-10-24T04:56:00Z",
+        "transit_date": "2024-08-03T00:00:00Z",
+        "latitude": 14.6488,
+        "longitude": 121.0509,
+        "house_system": "placidus",
+        "ayanamsa": "tropical",
+        "include_natal_angles": true,
+        "include_transit_houses": true
+    });
+    let resp = test::TestRequest::post()
+        .uri("/api/chart/transit")
+        .set_json(&request)
+        .send_request(&app)
+        .await;
+    assert!(resp.status().is_success());
+    let response: serde_json::Value = serde_json::from_slice(&test::read_body(resp).await).unwrap();
+
+    let cusp = response["houses"][6]["longitude"].as_f64().unwrap();
+    let sun = response["transit_planets"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|p| p["name"] == "Sun")
+        .unwrap();
+    let past = sun["longitude"].as_f64().unwrap() - cusp;
+    assert!((0.5..1.5).contains(&past), "{}", past);
+
+    let positions = response["transit_house_positions"].as_array().unwrap();
+    assert_eq!(positions.len(), response["transit_planets"].as_array().unwrap().len());
+    let sun_house = positions.iter().find(|p| p["planet"] == "Sun").unwrap();
+    assert_eq!(sun_house["house"], 7);
+    assert_eq!(sun_house["retrograde"], false);
+    let entered: chrono::DateTime<chrono::Utc> = sun_house["entered"].as_str().unwrap().parse().unwrap();
+    let transit: chrono::DateTime<chrono::Utc> = "2024-08-03T00:00:00Z".parse().unwrap();
+    let days = (transit - entered).num_minutes() as f64 / 1440.0;
+    assert!((past * 0.9..past * 1.1).contains(&days), "{} days for {}°", days, past);
+
+    // The Sun conjunct the cusp it just crossed, at an orb within the point cap
+    let aspects = response["natal_angle_aspects"].as_array().unwrap();
+    assert!(aspects
+        .iter()
+        .any(|a| a["planet1"] == "Natal Descendant" && a["planet2"] == "Transit Sun" && a["aspect"] == "Conjunction"));
+    for aspect in aspects {
+        assert!(aspect["orb"].as_f64().unwrap().abs() <= 2.0, "{}", aspect);
+        let names = [aspect["planet1"].as_str().unwrap(), aspect["planet2"].as_str().unwrap()];
+        assert!(names.iter().any(|n| n.starts_with("Natal ")) && names.iter().any(|n| n.starts_with("Transit ")));
+    }
+
+    // Neither list is there unless asked for
+    let mut plain = request.clone();
+    plain["include_natal_angles"] = json!(false);
+    plain["include_transit_houses"] = json!(false);
+    let resp = test::TestRequest::post()
+        .uri("/api/chart/transit")
+        .set_json(&plain)
+        .send_request(&app)
+        .await;
+    let response: serde_json::Value = serde_json::from_slice(&test::read_body(resp).await).unwrap();
+    assert!(response.get("natal_angle_aspects").is_none());
+    assert!(response.get("transit_house_positions").is_none());
+
+    // Gauquelin sectors have no twelve cusps to place the planets in
+    let mut sectors = request.clone();
+    sectors["house_system"] = json!("gauquelin");
+    let resp = test::TestRequest::post()
+        .uri("/api/chart/transit")
+        .set_json(&sectors)
+        .send_request(&app)
+        .await;
+    assert_eq!(resp.status(), 400);
+}
+
+#[actix_web::test]
+async fn test_api_versions() {
+    use astrolog_rs::api::types::ChartResponse;
+
+    if analytic_backend() {
+        return;
+    }
+    ensure_swiss_ephemeris_initialized().await;
+    let app = test::init_service(App::new().configure(config)).await;
+
+    let request = json!({
+        "date": "1977-10-24T04:56:00Z",
+        "latitude": 14.6488,
+        "longitude": 121.0509,
+        "house_system": "placidus",
+        "ayanamsa": "tropical",
+        "transit": { "date": "2025-05-27T12:00:00Z" }
+    });
+    let mut bodies = Vec::new();
+    for (uri, version) in [("/api/chart", "1"), ("/api/v1/chart", "1"), ("/api/v2/chart", "2")] {
+        let resp = test::TestRequest::post().uri(uri).set_json(&request).send_request(&app).await;
+        assert!(resp.status().is_success(), "{}", uri);
+        assert_eq!(resp.headers().get("x-api-version").unwrap(), version);
+        bodies.push(String::from_utf8(test::read_body(resp).await.to_vec()).unwrap());
+    }
+
+    // v1 is byte for byte what bare /api returns, and the chart kept in
+    // tests/golden, all but the calculation id. When a change is meant to alter
+    // the chart, run the test with UPDATE_GOLDEN=1 to rewrite the fixture, then
+    // review its diff.
+    let without_id = |body: &str| {
+        let (head, tail) = body.split_once(r#""calculation_id":"#).unwrap();
+        format!(r#"{}"calculation_id":0{}"#, head, tail.trim_start_matches(|c: char| c.is_ascii_digit()))
+    };
+    assert_eq!(without_id(&bodies[0]), without_id(&bodies[1]));
+    if std::env::var_os("UPDATE_GOLDEN").is_some() {
+        let path = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/golden/chart_1977-10-24_v1.json");
+        std::fs::write(path, without_id(&bodies[1])).unwrap();
+    } else {
+        assert!(
+            without_id(&bodies[1]) == include_str!("golden/chart_1977-10-24_v1.json"),
+            "v1 differs from tests/golden/chart_1977-10-24_v1.json"
+        );
+    }
+    assert!(serde_json::from_str::<ChartResponse>(&bodies[1]).is_ok());
+    let v1: serde_json::Value = serde_json::from_str(&bodies[1]).unwrap();
+    assert!(v1["transit"].is_object());
+    assert!(v1["aspects"][0].get("angle").is_none());
+    assert!(v1["meta"].get("api_version").is_none());
+
+    let v2: serde_json::Value = serde_json::from_str(&bodies[2]).unwrap();
+    assert!(v2.get("transit").is_none());
+    assert_eq!(v2["transits"][0]["date"], v1["transit"]["date"]);
+    assert_eq!(v2["transits"][0]["planets"], v1["transit"]["planets"]);
+    assert_eq!(v2["meta"]["api_version"], 2);
+    assert_eq!(v2["planets"], v1["planets"]);
+    for aspect in v2["aspects"].as_array().unwrap() {
+        assert!(aspect["angle"].is_f64(), "{}", aspect);
+        assert!(aspect["category"] == "major" || aspect["category"] == "minor", "{}", aspect);
+    }
+
+    // v1 failures are plain text, v2 failures JSON
+    let invalid = json!({ "date": "1977-10-24T04:56:00Z", "latitude": 14.6488, "longitude": 121.0509, "house_system": "placidus", "ayanamsa": "bogus" });
+    let resp = test::TestRequest::post().uri("/api/v1/chart/natal").set_json(&invalid).send_request(&app).await;
+    assert_eq!(resp.status(), 400);
+    let message = String::from_utf8(test::read_body(resp).await.to_vec()).unwrap();
+    assert!(serde_json::from_str::<serde_json::Value>(&message).is_err());
+    let resp = test::TestRequest::post().uri("/api/v2/chart/natal").set_json(&invalid).send_request(&app).await;
+    assert_eq!(resp.status(), 400);
+    let error: serde_json::Value = serde_json::from_slice(&test::read_body(resp).await).unwrap();
+    assert_eq!(error, json!({ "error": "Bad Request", "message": message }));
+}
+
+#[actix_web::test]
+async fn test_prefetched_current_positions() {
+    use actix_web::web::Data;
+    use astrolog_rs::api::prefetch::Prefetcher;
+    use astrolog_rs::calc::ephemeris::{Ephemeris, MockEphemeris};
+    use astrolog_rs::calc::planets::Planet;
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    ensure_swiss_ephemeris_initialized().await;
+    let get = |uri: &'static str| test::TestRequest::get().uri(uri).to_request();
+
+    // Without a prefetcher, the snapshot is calculated for the request
+    let app = test::init_service(App::new().configure(config)).await;
+    let now: serde_json::Value = test::call_and_read_body_json(&app, get("/api/now")).await;
+    assert!(now["positions"].as_array().unwrap().len() >= 12);
+    assert!(now["moon"]["phase"].is_string());
+    let health: serde_json::Value = test::call_and_read_body_json(&app, get("/health")).await;
+    assert_eq!(health["checks"]["prefetch_age_seconds"], json!(null));
+
+    // The Sun and Moon are all a snapshot needs; the other bodies are left out
+    let mock = MockEphemeris::new(2451545.0)
+        .with_body(Planet::Sun, 10.0, 0.0, 1.0)
+        .with_body(Planet::Moon, 100.0, 0.0, 13.0);
+    let calls = mock.clone();
+    let ephemeris: Arc<dyn Ephemeris> = Arc::new(mock);
+    let prefetcher = Arc::new(Prefetcher::new(Arc::clone(&ephemeris)));
+    let task = prefetcher.start(Duration::from_millis(20)).await;
+
+    // Warmed up before the first request
+    assert!(calls.calls() > 0);
+    assert!(prefetcher.age_seconds().is_some());
+    let app = test::init_service(
+        App::new()
+            .configure(config)
+            .app_data(Data::from(ephemeris))
+            .app_data(Data::from(Arc::clone(&prefetcher))),
+    )
+    .await;
+
+    // A minute turning between the requests starts a new snapshot, so try again in the next one
+    let mut same_minute = false;
+    for _ in 0..3 {
+        let first: serde_json::Value = test::call_and_read_body_json(&app, get("/api/now")).await;
+        let calls_after_first = calls.calls();
+        let second: serde_json::Value = test::call_and_read_body_json(&app, get("/api/now")).await;
+        let positions: serde_json::Value = test::call_and_read_body_json(&app, get("/api/positions?bodies=sun,moon")).await;
+        if first["date"] != second["date"] {
+            continue;
+        }
+        same_minute = true;
+        assert_eq!(calls.calls(), calls_after_first, "the ephemeris was asked again within the minute");
+        assert_eq!(first, second);
+
+        let bodies: Vec<&str> = second["positions"].as_array().unwrap().iter().map(|p| p["body"].as_str().unwrap()).collect();
+        assert_eq!(bodies, ["Sun", "Moon"]);
+        // Date-less positions come from the snapshot, so from the mock
+        assert_eq!(positions, second["positions"]);
+        break;
+    }
+    assert!(same_minute);
+
+    let health: serde_json::Value = test::call_and_read_body_json(&app, get("/health")).await;
+    assert!(health["checks"]["prefetch_age_seconds"].as_f64().unwrap() >= 0.0);
+    task.stop().await;
+}
+
+#[actix_web::test]
+async fn test_highlights_ring_bodies_on_an_angle() {
+    use actix_web::web::Data;
+    use astrolog_rs::calc::ephemeris::{Ephemeris, MockEphemeris};
+    use astrolog_rs::calc::houses::{HousePosition, Houses};
+    use astrolog_rs::calc::planets::Planet;
+    use astrolog_rs::core::types::HouseSystem;
+    use std::sync::Arc;
+
+    // Equal houses from 0° Aries with the Midheaven at 0° Capricorn; the Sun is
+    // 0.5° past the Ascendant and Mars 0.8° past the Midheaven
+    let houses = Houses {
+        cusps: (0..12)
+            .map(|i| HousePosition {
+                number: i as u8 + 1,
+                longitude: i as f64 * 30.0,
+                latitude: 0.0,
+            })
+            .collect(),
+        ascendant: 0.0,
+        midheaven: 270.0,
+        armc: 270.0,
+        vertex: 180.0,
+        east_point: 0.0,
+        system: HouseSystem::Equal,
+    };
+    let bodies = [
+        (Planet::Sun, 0.5),
+        (Planet::Moon, 100.0),
+        (Planet::Mercury, 20.0),
+        (Planet::Venus, 45.0),
+        (Planet::Mars, 270.8),
+        (Planet::Jupiter, 250.0),
+        (Planet::Saturn, 300.0),
+        (Planet::Uranus, 330.0),
+        (Planet::Neptune, 340.0),
+        (Planet::Pluto, 210.0),
+        (Planet::TrueNode, 125.0),
+        (Planet::MeanNode, 124.0),
+    ];
+    let mock = bodies
+        .iter()
+        .fold(MockEphemeris::new(2451545.0), |mock, &(planet, longitude)| {
+            mock.with_body(planet, longitude, 0.0, 0.5)
+        })
+        .with_houses(houses);
+    let ephemeris: Arc<dyn Ephemeris> = Arc::new(mock);
+    let app = test::init_service(App::new().configure(config).app_data(Data::from(ephemeris))).await;
+    let chart = |highlights: serde_json::Value| {
+        test::TestRequest::post().uri("/api/chart/natal").set_json(json!({
+            "date": "2000-01-01T12:00:00Z",
+            "latitude": 40.7128,
+            "longitude": -74.0060,
+            "house_system": "placidus",
+            "ayanamsa": "tropical",
+            "highlights": highlights
+        }))
+    };
+
+    let response: serde_json::Value =
+        test::call_and_read_body_json(&app, chart(json!({ "angular": true, "critical_degrees": [13, 100] })).to_request()).await;
+    let angular: Vec<(&str, &str)> = response["angular"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|a| (a["planet1"].as_str().unwrap(), a["planet2"].as_str().unwrap()))
+        .collect();
+    assert_eq!(angular, [("Sun", "Ascendant"), ("Mars", "Midheaven")]);
+    assert_eq!(response["angular"][0]["orb"], 0.5);
+
+    let svg = response["svg_chart"].as_str().unwrap();
+    let group = |planet: &str| {
+        let start = svg.find(&format!("<g class=\"angular-highlight\" data-planet=\"{}\"", planet))?;
+        Some(svg[start..].split("</g>").next().unwrap().to_string())
+    };
+    let sun = group("Sun").expect("the Sun is ringed");
+    assert!(sun.contains("<circle") && sun.contains("data-highlight=\"angular\""), "{}", sun);
+    assert!(group("Mars").is_some());
+    assert!(group("Moon").is_none());
+    assert_eq!(svg.matches("class=\"critical-degree\"").count(), 2);
+    assert!(!svg.contains("aries-point"));
+
+    // Without highlights the chart still lists the angular bodies but draws no overlays
+    let plain: serde_json::Value = test::call_and_read_body_json(&app, chart(json!(null)).to_request()).await;
+    assert_eq!(plain["angular"], response["angular"]);
+    let svg = plain["svg_chart"].as_str().unwrap();
+    assert!(!svg.contains("data-highlight") && !svg.contains("critical-degree"));
+
+    let resp = chart(json!({ "critical_degrees": [13.5] })).send_request(&app).await;
+    assert_eq!(resp.status(), 400);
+}
+
+#[actix_web::test]
+async fn test_date_expressions_resolve_and_echo() {
+    if analytic_backend() {
+        return;
+    }
+    ensure_swiss_ephemeris_initialized().await;
+    let app = test::init_service(App::new().configure(config)).await;
+    let post = |uri: &str, body: serde_json::Value| test::TestRequest::post().uri(uri).set_json(body).to_request();
+
+    // A transit counted from the natal date
+    let chart = json!({
+        "date": "1990-05-15T14:30:00Z",
+        "latitude": 40.7128,
+        "longitude": -74.0060,
+        "house_system": "placidus",
+        "ayanamsa": "tropical",
+        "transit": { "date": "natal+30y" }
+    });
+    let resp = test::call_service(&app, post("/api/chart", chart.clone())).await;
+    assert!(resp.status().is_success());
+    let body: serde_json::Value = serde_json::from_slice(&test::read_body(resp).await).unwrap();
+    assert!(body.get("resolved_date").is_none());
+    assert_eq!(body["transit"]["date"], "2020-05-15T14:30:00Z");
+    assert_eq!(body["transit"]["resolved_date"], "2020-05-15T14:30:00Z");
+
+    // "now" is echoed as the moment it was taken to be
+    let before = chrono::Utc::now();
+    let mut now = chart.clone();
+    now["date"] = json!("now");
+    now["transit"] = json!({ "date": "natal-1d" });
+    let resp = test::call_service(&app, post("/api/chart", now)).await;
+    assert!(resp.status().is_success());
+    let body: serde_json::Value = serde_json::from_slice(&test::read_body(resp).await).unwrap();
+    let resolved: chrono::DateTime<chrono::Utc> = body["resolved_date"].as_str().unwrap().parse().unwrap();
+    assert!(resolved >= before && resolved <= chrono::Utc::now());
+    assert_eq!(body["date"], body["resolved_date"]);
+    let transit: chrono::DateTime<chrono::Utc> = body["transit"]["date"].as_str().unwrap().parse().unwrap();
+    assert_eq!(resolved - transit, chrono::Duration::days(1));
+
+    // The natal date has nothing to count from, and unknown expressions name their field
+    for (uri, field, value, error) in [
+        ("/api/chart/natal", "date", "natal+30y", "date: natal-relative dates need a natal date to count from"),
+        ("/api/chart", "date", "tomorrow", "date: Unknown date 'tomorrow'"),
+    ] {
+        let mut request = chart.clone();
+        request[field] = json!(value);
+        let resp = test::call_service(&app, post(uri, request)).await;
+        assert_eq!(resp.status(), 400);
+        let body = String::from_utf8(test::read_body(resp).await.to_vec()).unwrap();
+        assert!(body.contains(error), "{}", body);
+    }
+    let mut bogus = chart.clone();
+    bogus["transits"] = json!([{ "date": "natal+1y" }, { "date": "bogus" }]);
+    bogus.as_object_mut().unwrap().remove("transit");
+    let resp = test::call_service(&app, post("/api/chart", bogus)).await;
+    assert_eq!(resp.status(), 400);
+    let body: serde_json::Value = serde_json::from_slice(&test::read_body(resp).await).unwrap();
+    assert!(body["message"].as_str().unwrap().starts_with("transits[1].date: Unknown date 'bogus'"), "{}", body);
+
+    // A return search from the 40th birthday
+    let resp = test::call_service(
+        &app,
+        post(
+            "/api/chart/return",
+            json!({
+                "natal_date": "1990-05-15T14:30:00Z",
+                "latitude": 40.7128,
+                "longitude": -74.0060,
+                "house_system": "placidus",
+                "ayanamsa": "tropical",
+                "body": "sun",
+                "reference_date": "natal+40y"
+            }),
+        ),
+    )
+    .await;
+    assert!(resp.status().is_success());
+    let body: serde_json::Value = serde_json::from_slice(&test::read_body(resp).await).unwrap();
+    assert_eq!(body["resolved_date"], "2030-05-15T14:30:00Z");
+    assert!(body["chart"]["date"].as_str().unwrap().starts_with("2031-05-15"), "{}", body["chart"]["date"]);
+}
+
+#[actix_web::test]
+async fn test_transit_calendar_lists_exact_transits() {
+    use astrolog_rs::calc::angles::signed_delta;
+    use astrolog_rs::calc::planets::{planet_longitude_at, Planet};
+    use astrolog_rs::calc::utils::{date_to_julian, julian_to_date};
+    use astrolog_rs::core::types::EphemerisSource;
+
+    if analytic_backend() {
+        return;
+    }
+    ensure_swiss_ephemeris_initialized().await;
+    let app = test::init_service(App::new().configure(config)).await;
+
+    // A natal Sun exactly square where Saturn is at 00:00 UT on 1 March 2024
+    let source = EphemerisSource::Moshier;
+    let exact = date_to_julian("2024-03-01T00:00:00Z".parse().unwrap());
+    let target = planet_longitude_at(Planet::Saturn, exact, source).unwrap() - 90.0;
+    let past_target = |jd: f64| signed_delta(planet_longitude_at(Planet::Sun, jd, source).unwrap(), target) >= 0.0;
+    // Find the day of 1990 the Sun reaches it, then bisect that day
+    let mut low = date_to_julian("1990-01-01T00:00:00Z".parse().unwrap());
+    while past_target(low) || !past_target(low + 1.0) {
+        low += 1.0;
+    }
+    let mut high = low + 1.0;
+    for _ in 0..40 {
+        let middle = (low + high) / 2.0;
+        if past_target(middle) {
+            high = middle;
+        } else {
+            low = middle;
+        }
+    }
+    let natal = julian_to_date(high).unwrap().to_rfc3339_opts(chrono::SecondsFormat::Secs, true);
+
+    let uri = format!("/api/transits/ical?natal={}&from=2024-01-01&to=2025-01-01&planets=saturn&ephemeris=moshier", natal);
+    let resp = test::TestRequest::get().uri(&uri).send_request(&app).await;
+    assert!(resp.status().is_success());
+    assert!(resp.headers().get("content-type").unwrap().to_str().unwrap().starts_with("text/calendar"));
+    let calendar = String::from_utf8(test::read_body(resp).await.to_vec()).unwrap();
+
+    // Every line ends in CRLF and fits in 75 octets, and every unfolded line is NAME:value
+    assert!(calendar.ends_with("\r\n") && !calendar.replace("\r\n", "").contains('\n'));
+    assert!(calendar.split("\r\n").all(|line| line.len() <= 75));
+    let lines: Vec<String> = calendar.replace("\r\n ", "").split("\r\n").filter(|l| !l.is_empty()).map(String::from).collect();
+    let property = ical_property;
+    assert!(lines.iter().all(|line| property(line).is_some()), "{:?}", lines);
+    assert_eq!(lines.first().map(String::as_str), Some("BEGIN:VCALENDAR"));
+    assert_eq!(lines.last().map(String::as_str), Some("END:VCALENDAR"));
+
+    let mut events: Vec<Vec<(String, String)>> = Vec::new();
+    for line in &lines {
+        let (name, value) = property(line).unwrap();
+        match (name.as_str(), value.as_str()) {
+            ("BEGIN", "VEVENT") => events.push(Vec::new()),
+            ("END", "VEVENT") => {}
+            _ => {
+                if let Some(event) = events.last_mut() {
+                    event.push((name, value));
+                }
+            }
+        }
+    }
+    assert!(!events.is_empty());
+    let field = |event: &[(String, String)], name: &str| event.iter().find(|(n, _)| n == name).map(|(_, v)| v.clone());
+    let mut uids: Vec<String> = events.iter().map(|event| field(event, "UID").unwrap()).collect();
+    for event in &events {
+        for name in ["DTSTAMP", "DTSTART", "SUMMARY", "DESCRIPTION"] {
+            assert!(field(event, name).is_some(), "{} missing from {:?}", name, event);
+        }
+        assert!(field(event, "SUMMARY").unwrap().starts_with("Transiting Saturn "));
+    }
+    uids.sort();
+    uids.dedup();
+    assert_eq!(uids.len(), events.len());
+
+    let square = events
+        .iter()
+        .find(|event| field(event, "SUMMARY").unwrap() == "Transiting Saturn square natal Sun")
+        .expect("the constructed Saturn square");
+    let start = field(square, "DTSTART").unwrap();
+    assert!(start.starts_with("20240229T2") || start.starts_with("20240301T0"), "{}", start);
+    assert!(field(square, "DESCRIPTION").unwrap().contains("Within 1° of exact from 2024-"));
+
+    // The same request again gives the same UIDs
+    let resp = test::TestRequest::get().uri(&uri).send_request(&app).await;
+    let again = String::from_utf8(test::read_body(resp).await.to_vec()).unwrap();
+    assert_eq!(again.matches("UID:").count(), events.len());
+    assert!(again.contains(&format!("UID:{}", field(square, "UID").unwrap())));
+
+    let resp = test::TestRequest::get()
+        .uri(&format!("/api/transits/ical?natal={}&from=2024-01-01&to=2040-01-01", natal))
+        .send_request(&app)
+        .await;
+    assert_eq!(resp.status(), 400);
+}
+
+
+#[actix_web::test]
+async fn test_transit_calendar_nodes_and_eclipse_points() {
+    use astrolog_rs::calc::planets::{planet_longitude_at, Planet};
+    use astrolog_rs::calc::utils::date_to_julian;
+    use astrolog_rs::core::types::EphemerisSource;
+
+    if analytic_backend() {
+        return;
+    }
+    ensure_swiss_ephemeris_initialized().await;
+    let app = test::init_service(App::new().configure(config)).await;
+
+    // A natal Sun on the degree of the total solar eclipse of 8 April 2024, 19°24' Aries
+    let natal = "1990-04-09T12:00:00Z";
+    let sun = planet_longitude_at(Planet::Sun, date_to_julian(natal.parse().unwrap()), EphemerisSource::Moshier).unwrap();
+    assert!((sun - 19.4).abs() < 0.5, "{}", sun);
+
+    let feed = |query: &str| {
+        let uri = format!("/api/transits/ical?natal={}&from=2024-06-01&to=2024-07-01&ephemeris=moshier&{}", natal, query);
+        test::TestRequest::get().uri(&uri).send_request(&app)
+    };
+    let summaries = |calendar: &str| -> Vec<String> {
+        calendar
+            .replace("\r\n ", "")
+            .split("\r\n")
+            .filter_map(|line| line.strip_prefix("SUMMARY:").map(|s| s.replace("\\", "")))
+            .collect()
+    };
+
+    let resp = feed("planets=north_node,south_node&eclipse_points=4").await;
+    assert!(resp.status().is_success());
+    let calendar = String::from_utf8(test::read_body(resp).await.to_vec()).unwrap();
+    let events = summaries(&calendar);
+    assert!(events.contains(&"Solar eclipse at 19°24' Aries on natal Sun".to_string()), "{:?}", events);
+    assert!(events.iter().all(|event| event.contains("eclipse") || event.contains(" Node ")), "{:?}", events);
+    let unfolded = calendar.replace("\r\n ", "");
+    assert!(unfolded.contains("DTSTART:20240408T18"), "{}", unfolded);
+    assert!(unfolded.contains("DESCRIPTION:Solar eclipse at 2024-04-08 18:"), "{}", unfolded);
+
+    // The eclipse is only there when asked for and within the lookback
+    let calendar = String::from_utf8(test::read_body(feed("planets=north_node").await).await.to_vec()).unwrap();
+    assert!(!calendar.contains("eclipse"));
+    let calendar = String::from_utf8(test::read_body(feed("eclipse_points=4&eclipse_lookback_months=2").await).await.to_vec()).unwrap();
+    assert!(!calendar.contains("eclipse"));
+
+    for bad in ["eclipse_points=0", "eclipse_points=2&eclipse_lookback_months=0", "planets=north_node,east_node"] {
+        assert_eq!(feed(bad).await.status(), 400, "{}", bad);
+    }
+}
+/// The name and value of an unfolded iCalendar content line, parameters dropped
+fn ical_property(line: &str) -> Option<(String, String)> {
+    let (head, value) = line.split_once(':')?;
+    let name = head.split(';').next()?;
+    (!name.is_empty() && name.chars().all(|c| c.is_ascii_uppercase() || c.is_ascii_digit() || c == '-'))
+        .then(|| (name.to_string(), value.to_string()))
+}
+
+#[actix_web::test]
+async fn test_lang_localizes_positions_and_svg_title() {
+    if analytic_backend() {
+        return;
+    }
+    ensure_swiss_ephemeris_initialized().await;
+    let app = test::init_service(App::new().configure(config)).await;
+    // The Sun is early in Scorpio
+    let request = json!({
+        "date": "1977-10-24T04:56:00Z",
+        "latitude": 40.7128,
+        "longitude": -74.0060,
+        "house_system": "placidus",
+        "ayanamsa": "tropical",
+        "lang": "es"
+    });
+
+    let resp = test::TestRequest::post().uri("/api/chart/natal").set_json(&request).send_request(&app).await;
+    assert!(resp.status().is_success());
+    let body: serde_json::Value = test::read_body_json(resp).await;
+    let sun = body["planets"].as_array().unwrap().iter().find(|p| p["name"] == "Sun").unwrap();
+    let position = sun["formatted_position"].as_str().unwrap();
+    assert!(position.ends_with(" Escorpio"), "{}", position);
+    assert_eq!(body["meta"]["locale"], "es");
+    let svg = body["svg_chart"].as_str().unwrap();
+    assert!(svg.contains("role=\"img\""));
+    let title = svg.split("<title id=\"chart-title\">").nth(1).and_then(|rest| rest.split("</title>").next()).unwrap();
+    assert_eq!(title.trim(), "Carta natal");
+    assert!(svg.contains("24 de octubre de 1977 04:56"));
+
+    // Without `lang`, the Accept-Language header chooses
+    let mut request = request;
+    request.as_object_mut().unwrap().remove("lang");
+    let resp = test::TestRequest::post()
+        .uri("/api/chart/natal")
+        .insert_header(("Accept-Language", "de-CH, fr;q=0.8"))
+        .set_json(&request)
+        .send_request(&app)
+        .await;
+    let body: serde_json::Value = test::read_body_json(resp).await;
+    assert_eq!(body["meta"]["locale"], "de");
+    let sun = body["planets"].as_array().unwrap().iter().find(|p| p["name"] == "Sun").unwrap();
+    assert!(sun["formatted_position"].as_str().unwrap().ends_with(" Skorpion"));
+
+    // And with neither, English
+    let resp = test::TestRequest::post().uri("/api/chart/natal").set_json(&request).send_request(&app).await;
+    let body: serde_json::Value = test::read_body_json(resp).await;
+    assert_eq!(body["meta"]["locale"], "en");
+    let sun = body["planets"].as_array().unwrap().iter().find(|p| p["name"] == "Sun").unwrap();
+    assert!(sun["formatted_position"].as_str().unwrap().ends_with(" Scorpio"));
+}
+
+#[actix_web::test]
+async fn test_svg_legend_rows_and_canvas() {
+    if analytic_backend() {
+        return;
+    }
+    ensure_swiss_ephemeris_initialized().await;
+    let app = test::init_service(App::new().configure(config)).await;
+    let chart = json!({
+        "date": "1990-05-15T14:30:00Z",
+        "latitude": 40.7128,
+        "longitude": -74.0060,
+        "house_system": "placidus",
+        "ayanamsa": "tropical"
+    });
+    let view_box_width = |svg: &str| -> f64 {
+        let value = svg.split("viewBox=\"").nth(1).unwrap().split('"').next().unwrap();
+        value.split(' ').nth(2).unwrap().parse().unwrap()
+    };
+
+    let resp = test::TestRequest::post().uri("/api/chart/natal").set_json(&chart).send_request(&app).await;
+    let plain: serde_json::Value = test::read_body_json(resp).await;
+    let mut request = chart.clone();
+    request["include_legend"] = json!(true);
+    request["legend_position"] = json!("right");
+    let resp = test::TestRequest::post().uri("/api/chart/natal").set_json(&request).send_request(&app).await;
+    assert!(resp.status().is_success());
+    let body: serde_json::Value = test::read_body_json(resp).await;
+    let svg = body["svg_chart"].as_str().unwrap();
+    assert_eq!(svg.matches("class=\"legend-body\"").count(), body["planets"].as_array().unwrap().len());
+    assert_eq!(svg.matches("class=\"legend-angle\"").count(), 4);
+    assert!(view_box_width(svg) > view_box_width(plain["svg_chart"].as_str().unwrap()));
+
+    // A bi-wheel has a column for each ring
+    let mut chart1 = chart.clone();
+    chart1["include_legend"] = json!(true);
+    let request = json!({ "chart1": chart1, "chart2": { "date": "1995-01-01T12:00:00Z", "latitude": 34.0522, "longitude": -118.2437, "house_system": "placidus", "ayanamsa": "tropical" } });
+    let resp = test::TestRequest::post().uri("/api/chart/synastry").set_json(&request).send_request(&app).await;
+    assert!(resp.status().is_success());
+    let body: serde_json::Value = test::read_body_json(resp).await;
+    let svg = body["svg_chart"].as_str().unwrap();
+    let bodies = body["chart1"]["planets"].as_array().unwrap().len() + body["chart2"]["planets"].as_array().unwrap().len();
+    assert_eq!(svg.matches("class=\"legend-body\"").count(), bodies);
+    assert_eq!(svg.matches("class=\"legend-heading\"").count(), 2);
+    assert!(svg.contains("Chart 2"));
+
+    let mut request = chart;
+    request["include_legend"] = json!(true);
+    request["legend_position"] = json!("left");
+    let resp = test::TestRequest::post().uri("/api/chart/natal").set_json(&request).send_request(&app).await;
+    assert_eq!(resp.status(), 400);
+}
@@ -0,0 +1,58 @@
+//! Chart requests that leave options out take them from an `astrolog.as` file.
+//!
+//! The defaults are process-wide, so this runs in its own test binary.
+
+mod common;
+
+use actix_web::{test, App};
+use astrolog_rs::api::server::config;
+use astrolog_rs::calc::swiss_ephemeris;
+use astrolog_rs::io::astrolog_config::{init_defaults, load_astrolog_config};
+use common::analytic_backend;
+use serde_json::{json, Value};
+
+#[actix_rt::test]
+async fn test_request_without_options_uses_astrolog_defaults() {
+    if analytic_backend() {
+        return;
+    }
+    swiss_ephemeris::init_moshier_ephemeris().unwrap();
+    let defaults = load_astrolog_config(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/astrolog.as")).unwrap();
+    init_defaults(defaults);
+    let app = test::init_service(App::new().configure(config)).await;
+
+    // Mercury and Saturn are 10.5° apart, outside the usual 10° conjunction orb
+    let natal = |body: Value| {
+        let req = test::TestRequest::post().uri("/api/chart/natal").set_json(body).to_request();
+        test::call_and_read_body_json::<_, _, Value>(&app, req)
+    };
+    let moment = json!({
+        "date": "2024-03-05T12:00:00Z",
+        "latitude": 47.6,
+        "longitude": -122.3,
+        "ayanamsa": "tropical",
+        "ephemeris": "moshier"
+    });
+    let chart: Value = natal(moment.clone()).await;
+    assert_eq!(chart["house_system"], "koch", "{}", chart);
+    assert_eq!(chart["meta"]["house_system_used"], "koch");
+    let bodies: Vec<&str> = chart["planets"].as_array().unwrap().iter().map(|p| p["name"].as_str().unwrap()).collect();
+    assert!(bodies.contains(&"TrueNode"), "{:?}", bodies);
+
+    let aspects = chart["aspects"].as_array().unwrap();
+    let wide = aspects
+        .iter()
+        .find(|a| a["aspect"] == "Conjunction" && a["orb"].as_f64().unwrap() > 10.0)
+        .unwrap_or_else(|| panic!("no conjunction wider than 10°: {:?}", aspects));
+    let orb = wide["orb"].as_f64().unwrap();
+    assert!((wide["strength"].as_f64().unwrap() - (1.0 - orb / 12.0)).abs() < 0.01, "{}", wide);
+
+    // Options in the request win over the defaults
+    let mut explicit = moment;
+    explicit["house_system"] = json!("placidus");
+    explicit["extra_bodies"] = json!([]);
+    explicit["include_nodes"] = json!(false);
+    let chart: Value = natal(explicit).await;
+    assert_eq!(chart["house_system"], "placidus");
+    assert!(chart["planets"].as_array().unwrap().iter().all(|p| p["name"] != "TrueNode"));
+}
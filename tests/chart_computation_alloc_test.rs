@@ -0,0 +1,79 @@
+use astrolog_rs::api::computation::ChartComputation;
+use astrolog_rs::calc::swiss_ephemeris;
+use astrolog_rs::calc::utils::date_to_julian;
+use astrolog_rs::core::types::{EphemerisSource, HouseSystem};
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+// Counts every allocation in this process; keep this file to a single test so
+// nothing else allocates while it measures
+struct CountingAllocator;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+/// Allocations for this chart when each handler section rebuilt its own names and
+/// cloned them into the response. That was 341 when retrograde bodies had no
+/// aspects; their aspects add 10 allocations to this chart, so 351 now.
+const ALLOCATIONS_BEFORE_SHARING: usize = 351;
+
+fn natal_with_transits(natal_jd: f64, transit_jd: f64) {
+    let natal = ChartComputation::new(natal_jd, EphemerisSource::Moshier)
+        .and_then(|chart| chart.with_houses(40.7128, -74.0060, HouseSystem::Placidus))
+        .unwrap();
+    let transit = ChartComputation::new(transit_jd, EphemerisSource::Moshier).unwrap();
+    let rulers = natal.rulers(false);
+    let houses = natal.house_info();
+    let aspects = natal.aspects(false);
+    let transit_aspects = transit.transit_aspects(false);
+    let cross_aspects = natal.cross_aspects(&transit, false);
+    std::hint::black_box((
+        natal.into_planets(),
+        transit.into_planets(),
+        rulers,
+        houses,
+        aspects,
+        transit_aspects,
+        cross_aspects,
+    ));
+}
+
+#[test]
+fn test_natal_with_transits_allocates_less() {
+    swiss_ephemeris::init_moshier_ephemeris().expect("Swiss Ephemeris must initialize");
+    let natal_jd = date_to_julian("1977-10-24T04:56:00Z".parse().unwrap());
+    let transit_jd = date_to_julian("2024-01-02T03:00:00Z".parse().unwrap());
+
+    // Warm up so one-time ephemeris setup isn't counted
+    natal_with_transits(natal_jd, transit_jd);
+
+    let before = ALLOCATIONS.load(Ordering::Relaxed);
+    natal_with_transits(natal_jd, transit_jd);
+    let allocations = ALLOCATIONS.load(Ordering::Relaxed) - before;
+
+    println!("natal chart with transits: {} allocations", allocations);
+    assert!(
+        allocations * 10 <= ALLOCATIONS_BEFORE_SHARING * 7,
+        "expected at least 30% fewer than {} allocations, got {}",
+        ALLOCATIONS_BEFORE_SHARING,
+        allocations
+    );
+}
@@ -0,0 +1,65 @@
+mod common;
+
+use astrolog_rs::api::search::{parse_criteria, ChartIndex};
+use astrolog_rs::api::server::calculate_natal_chart;
+use astrolog_rs::api::types::{ChartRequest, ChartResponse};
+use astrolog_rs::calc::swiss_ephemeris;
+use astrolog_rs::core::types::HouseSystem;
+use common::analytic_backend;
+
+fn chart(date: &str, latitude: f64, longitude: f64) -> ChartResponse {
+    calculate_natal_chart(ChartRequest {
+        date: date.parse().unwrap(),
+        latitude,
+        longitude,
+        house_system: HouseSystem::Placidus,
+        ayanamsa: "tropical".to_string(),
+        ephemeris: Some("moshier".to_string()),
+        ..ChartRequest::default()
+    })
+    .unwrap()
+}
+
+#[test]
+fn test_search_finds_matching_charts_through_the_index() {
+    if analytic_backend() {
+        return;
+    }
+    swiss_ephemeris::init_moshier_ephemeris().expect("Swiss Ephemeris must initialize");
+    let mut index = ChartIndex::new();
+    index.insert(1, "Manila 1977", chart("1977-10-24T04:56:00Z", 14.6488, 121.0509));
+    index.insert(2, "London 1980", chart("1980-03-15T18:20:00Z", 51.5074, -0.1278));
+    index.insert(3, "New York 2000", chart("2000-01-01T12:00:00Z", 40.7128, -74.0060));
+    assert_eq!(index.len(), 3);
+
+    // Only the first chart has its Sun in Scorpio
+    let criteria = parse_criteria("sun.sign:scorpio, sun.house:9, aspect:sun-square-mars").unwrap();
+    assert_eq!(index.candidates(&criteria), vec![1]);
+    let matches = index.search(&criteria);
+    assert_eq!(matches.len(), 1);
+    assert_eq!(matches[0].id, 1);
+    assert_eq!(matches[0].name, "Manila 1977");
+    assert_eq!(matches[0].factors[..2], ["Sun in Scorpio", "Sun in house 9"]);
+    assert!(matches[0].factors[2].starts_with("Sun Square Mars (orb "), "{}", matches[0].factors[2]);
+
+    // Venus conjoins Pluto in the first and third charts, within 6° only in the first
+    let ids = |criteria: &str| index.search(&parse_criteria(criteria).unwrap()).iter().map(|m| m.id).collect::<Vec<_>>();
+    assert_eq!(ids("aspect:pluto-conjunction-venus"), vec![1, 3]);
+    assert_eq!(ids("aspect:venus-conjunction-pluto:6"), vec![1]);
+    // Criteria that aren't indexed check every chart
+    assert_eq!(index.candidates(&parse_criteria("saturn.retrograde:true").unwrap()), vec![1, 2, 3]);
+    assert_eq!(ids("saturn.retrograde:true"), vec![2, 3]);
+    assert_eq!(ids("venus.house:8"), vec![1, 2]);
+
+    // Removing a chart takes it out of the index
+    assert!(index.remove(1));
+    assert!(!index.remove(1));
+    assert!(index.candidates(&criteria).is_empty());
+    assert!(index.search(&criteria).is_empty());
+    assert_eq!(index.candidates(&parse_criteria("venus.house:8").unwrap()), vec![2]);
+    assert_eq!(index.len(), 2);
+
+    for invalid in ["", "sun.sign:ophiuchus", "moon.house:0", "aspect:sun-square", "aspect:sun-squared-mars", "sun.speed:1", "pluto:7"] {
+        assert!(parse_criteria(invalid).is_err(), "{}", invalid);
+    }
+}
@@ -0,0 +1,10 @@
+//! Helpers shared by the integration tests
+
+use astrolog_rs::calc::ephemeris;
+
+/// Whether `EPHEMERIS_BACKEND=analytic` switched the default ephemeris to the
+/// analytic one. Tests that need houses such as Placidus, or positions checked
+/// to Moshier precision, return early on it.
+pub fn analytic_backend() -> bool {
+    std::env::var(ephemeris::BACKEND_ENV).is_ok_and(|backend| backend.eq_ignore_ascii_case("analytic"))
+}
@@ -0,0 +1,90 @@
+//! Requests with different options calculated at the same time.
+//!
+//! The Swiss Ephemeris keeps the topocentric observer and the sidereal mode in
+//! global state. Interleaved topocentric and geocentric charts must each come
+//! out as they do on their own, so no request sees another's settings.
+
+mod common;
+
+use actix_web::{test, App};
+use astrolog_rs::api::pool::{init_calculation_pool, DEFAULT_MAX_CONCURRENT};
+use astrolog_rs::api::server::config;
+use astrolog_rs::calc::swiss_ephemeris;
+use common::analytic_backend;
+use serde_json::{json, Value};
+use std::rc::Rc;
+
+const ROUNDS: usize = 40;
+
+/// Calculation threads, however many CPUs there are, so the requests overlap
+const THREADS: usize = 4;
+
+fn manila_topocentric() -> Value {
+    json!({
+        "date": "2024-04-08T09:00:00Z",
+        "latitude": 14.5995,
+        "longitude": 120.9842,
+        "house_system": "placidus",
+        "ayanamsa": "tropical",
+        "topocentric": true
+    })
+}
+
+fn london_geocentric() -> Value {
+    json!({
+        "date": "2024-04-08T09:00:00Z",
+        "latitude": 51.5074,
+        "longitude": -0.1278,
+        "house_system": "placidus",
+        "ayanamsa": "tropical"
+    })
+}
+
+fn moon_longitude(chart: &Value) -> f64 {
+    let moon = chart["planets"].as_array().unwrap().iter().find(|p| p["name"] == "Moon").unwrap();
+    moon["longitude"].as_f64().unwrap()
+}
+
+#[actix_web::test]
+async fn test_topocentric_settings_do_not_leak_between_requests() {
+    if analytic_backend() {
+        return;
+    }
+    swiss_ephemeris::init_moshier_ephemeris().expect("Swiss Ephemeris must initialize");
+    init_calculation_pool(THREADS, DEFAULT_MAX_CONCURRENT);
+    let app = Rc::new(test::init_service(App::new().configure(config)).await);
+
+    let natal = |request: Value| {
+        let app = Rc::clone(&app);
+        async move {
+            let resp = test::call_service(
+                &*app,
+                test::TestRequest::post().uri("/api/chart/natal").set_json(&request).to_request(),
+            )
+            .await;
+            assert!(resp.status().is_success());
+            serde_json::from_slice::<Value>(&test::read_body(resp).await).unwrap()
+        }
+    };
+
+    // Each on its own first
+    let manila = natal(manila_topocentric()).await;
+    let london = natal(london_geocentric()).await;
+    assert_eq!(manila["meta"]["flags"]["topocentric"], true);
+    assert_eq!(london["meta"]["flags"]["topocentric"], false);
+    let (manila_moon, london_moon) = (moon_longitude(&manila), moon_longitude(&london));
+    // The Moon's parallax moves it by a large fraction of a degree
+    assert!((manila_moon - london_moon).abs() > 0.1, "{} {}", manila_moon, london_moon);
+
+    let mut handles = Vec::with_capacity(2 * ROUNDS);
+    for _ in 0..ROUNDS {
+        for (request, expected) in [(manila_topocentric(), manila_moon), (london_geocentric(), london_moon)] {
+            let chart = natal(request);
+            handles.push(actix_rt::spawn(async move { (moon_longitude(&chart.await), expected) }));
+        }
+    }
+    for handle in handles {
+        let (moon, expected) = handle.await.unwrap();
+        assert_eq!(moon, expected);
+    }
+}
@@ -0,0 +1,16 @@
+@AS700  ; Astrolog 7 defaults, for the astrolog_config tests
+
+-z0 0         ; Daylight time setting
+-z 8:00W      ; Time zone, hours behind Greenwich
+-zl 122W19:59 47N36:35 ; Longitude and latitude
+
+-c Koch       ; House system
+-A 5          ; Number of aspects
+=C            ; Display house cusps
+
+; Orbs: conjunction wider than usual, then opposition, square, trine and sextile
+-Ao 1 12
+-YAo 2 5 9 8 7 6
+
+; Only the planets, Chiron and the node
+-R0 Sun Moon Mercury Venus Mars Jupiter Saturn Uranus Neptune Pluto Chiron Node Lilith
@@ -1,11 +1,11 @@
-use actix_web::{test, web, App};
+use actix_web::{test, App};
 use astrolog_rs::api::server::config;
 use astrolog_rs::calc::swiss_ephemeris;
 use serde_json::json;
 
 async fn ensure_swiss_ephemeris_initialized() {
-    // Ignore error if already initialized
-    let _ = swiss_ephemeris::init_swiss_ephemeris();
+    // The Moshier theory needs no files, so results don't depend on ./ephe
+    swiss_ephemeris::init_moshier_ephemeris().expect("Swiss Ephemeris must initialize");
 }
 
 #[actix_web::test]
@@ -105,6 +105,7 @@ async fn test_with_minor_aspects() {
     assert!(resp.status().is_success());
     let body = test::read_body(resp).await;
     let response: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(response["aspect_set"], "all");
 
     // Check that we have aspects
     let aspects = response["aspects"].as_array().unwrap();
@@ -153,6 +154,7 @@ async fn test_default_behavior() {
     assert!(resp.status().is_success());
     let body = test::read_body(resp).await;
     let response: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(response["aspect_set"], "major");
 
     // Check that we have aspects
     let aspects = response["aspects"].as_array().unwrap();
@@ -0,0 +1,135 @@
+use actix_web::{test, App};
+use astrolog_rs::api::server::config;
+use astrolog_rs::calc::swiss_ephemeris;
+use astrolog_rs::core::types::EphemerisSource;
+use serde_json::json;
+
+// Runs in its own process, so it can initialize against a directory with no .se1 files
+fn ensure_moshier_fallback_initialized() {
+    let empty_dir = std::env::temp_dir().join("astrolog-rs-empty-ephe");
+    std::fs::create_dir_all(&empty_dir).unwrap();
+    swiss_ephemeris::init_swiss_ephemeris_with(empty_dir.to_str().unwrap(), EphemerisSource::Auto)
+        .expect("missing ephemeris files must not be fatal in auto mode");
+}
+
+#[actix_web::test]
+async fn test_natal_chart_falls_back_to_moshier() {
+    ensure_moshier_fallback_initialized();
+    assert!(!swiss_ephemeris::ephemeris_files_available());
+    let app = test::init_service(App::new().configure(config)).await;
+
+    let request = json!({
+        "date": "1977-10-24T04:56:00Z",
+        "latitude": 14.6486,
+        "longitude": 121.0508,
+        "house_system": "placidus",
+        "ayanamsa": "tropical",
+        "ephemeris": "auto"
+    });
+
+    let resp = test::TestRequest::post()
+        .uri("/api/chart/natal")
+        .set_json(&request)
+        .send_request(&app)
+        .await;
+
+    assert!(resp.status().is_success());
+    let body = test::read_body(resp).await;
+    let response: serde_json::Value = serde_json::from_slice(&body).unwrap();
+
+    assert_eq!(response["ephemeris"], "moshier");
+    let planets = response["planets"].as_array().unwrap();
+    assert_eq!(planets.len(), 12);
+    // Moshier agrees with the file ephemeris to well under a degree
+    let sun = planets[0]["longitude"].as_f64().unwrap();
+    assert!((sun - 210.674).abs() < 0.01, "Sun at {}", sun);
+}
+
+#[actix_web::test]
+async fn test_swiss_only_request_is_unprocessable_without_files() {
+    ensure_moshier_fallback_initialized();
+    let app = test::init_service(App::new().configure(config)).await;
+
+    let request = json!({
+        "date": "1977-10-24T04:56:00Z",
+        "latitude": 14.6486,
+        "longitude": 121.0508,
+        "house_system": "placidus",
+        "ayanamsa": "tropical",
+        "ephemeris": "swiss"
+    });
+
+    let resp = test::TestRequest::post()
+        .uri("/api/chart/natal")
+        .set_json(&request)
+        .send_request(&app)
+        .await;
+    assert_eq!(resp.status(), 422);
+    let body = test::read_body(resp).await;
+    assert!(String::from_utf8_lossy(&body).contains("ephemeris 'swiss'"), "{:?}", body);
+
+    // Or falls back to Moshier when the request allows it
+    let mut request = request;
+    request["allow_degraded"] = json!(true);
+    let resp = test::TestRequest::post()
+        .uri("/api/chart/natal")
+        .set_json(&request)
+        .send_request(&app)
+        .await;
+    assert!(resp.status().is_success());
+    let response: serde_json::Value = serde_json::from_slice(&test::read_body(resp).await).unwrap();
+    assert_eq!(response["ephemeris"], "moshier");
+    assert_eq!(response["degraded_options"][0]["option"], "ephemeris");
+    assert_eq!(response["degraded_options"][0]["requested"], "swiss");
+}
+
+#[actix_web::test]
+async fn test_invalid_ephemeris_source_is_rejected() {
+    ensure_moshier_fallback_initialized();
+    let app = test::init_service(App::new().configure(config)).await;
+
+    let request = json!({
+        "date": "1977-10-24T04:56:00Z",
+        "latitude": 14.6486,
+        "longitude": 121.0508,
+        "house_system": "placidus",
+        "ayanamsa": "tropical",
+        "ephemeris": "jpl"
+    });
+
+    let resp = test::TestRequest::post()
+        .uri("/api/chart/natal")
+        .set_json(&request)
+        .send_request(&app)
+        .await;
+
+    assert_eq!(resp.status(), 400);
+}
+
+#[actix_web::test]
+async fn test_chiron_is_a_per_body_error_under_moshier() {
+    ensure_moshier_fallback_initialized();
+
+    let err = swiss_ephemeris::calculate_planet_position_with_source(
+        swisseph::Planet::Chiron,
+        1977,
+        10,
+        24,
+        4.9,
+        EphemerisSource::Auto,
+    )
+    .unwrap_err();
+    assert!(err.to_string().contains("Chiron"), "{}", err);
+
+    // Other bodies in the same mode are unaffected
+    let (_, source) = swiss_ephemeris::calculate_planet_position_with_source(
+        swisseph::Planet::Mars,
+        1977,
+        10,
+        24,
+        4.9,
+        EphemerisSource::Auto,
+    )
+    .unwrap();
+    assert_eq!(source, EphemerisSource::Moshier);
+}
@@ -0,0 +1,85 @@
+//! `/health` latency while chart requests are in flight.
+//!
+//! Charts are calculated on the calculation pool, so the HTTP worker keeps
+//! answering cheap requests instead of queueing them behind every chart. Run with
+//! `--nocapture` to see the measured latencies.
+
+use actix_web::{test, App};
+use astrolog_rs::api::server::config;
+use astrolog_rs::calc::swiss_ephemeris;
+use serde_json::json;
+use std::cell::Cell;
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+
+const CHART_REQUESTS: usize = 200;
+
+/// 99th percentile of the samples
+fn p99(samples: &mut [Duration]) -> Duration {
+    samples.sort();
+    samples[(samples.len() * 99 / 100).min(samples.len() - 1)]
+}
+
+#[actix_web::test]
+async fn test_health_stays_fast_while_charts_are_calculated() {
+    swiss_ephemeris::init_moshier_ephemeris().expect("Swiss Ephemeris must initialize");
+    let app = Rc::new(test::init_service(App::new().configure(config)).await);
+
+    let probe = || async {
+        let start = Instant::now();
+        let resp = test::call_service(&*app, test::TestRequest::get().uri("/health").to_request()).await;
+        assert!(resp.status().is_success());
+        start.elapsed()
+    };
+
+    let mut idle: Vec<Duration> = Vec::new();
+    for _ in 0..50 {
+        idle.push(probe().await);
+    }
+
+    let started = Rc::new(Cell::new(0));
+    let completed = Rc::new(Cell::new(0));
+    for i in 0..CHART_REQUESTS {
+        let (app, started, completed) = (Rc::clone(&app), Rc::clone(&started), Rc::clone(&completed));
+        actix_rt::spawn(async move {
+            let request = json!({
+                "date": format!("19{:02}-06-15T12:00:00Z", 10 + i % 90),
+                "latitude": 51.5074,
+                "longitude": -0.1278,
+                "house_system": "placidus",
+                "ayanamsa": "tropical"
+            });
+            started.set(started.get() + 1);
+            let resp = test::call_service(
+                &*app,
+                test::TestRequest::post().uri("/api/chart").set_json(&request).to_request(),
+            )
+            .await;
+            assert!(resp.status().is_success());
+            completed.set(completed.get() + 1);
+        });
+    }
+    // Each chart request is handed to the pool the first time it's polled
+    while started.get() < CHART_REQUESTS {
+        actix_rt::task::yield_now().await;
+    }
+
+    let mut loaded: Vec<Duration> = Vec::new();
+    while completed.get() < CHART_REQUESTS {
+        loaded.push(probe().await);
+        actix_rt::task::yield_now().await;
+    }
+
+    let samples = loaded.len();
+    let (idle_p99, loaded_p99) = (p99(&mut idle), p99(&mut loaded));
+    println!(
+        "/health p99: {:?} idle, {:?} with {} chart requests in flight ({} samples)",
+        idle_p99, loaded_p99, CHART_REQUESTS, samples
+    );
+    assert!(samples > 0);
+    assert!(
+        loaded_p99 < Duration::from_millis(50),
+        "/health p99 rose to {:?} under load",
+        loaded_p99
+    );
+}
@@ -0,0 +1,205 @@
+//! Random chart requests thrown at the request parsers, validation and Julian
+//! date conversion. Whatever the input, the server must answer with a success or
+//! an error message, never a panic or a position that isn't a number.
+//!
+//! The runs are seeded so CI sees the same requests every time; set
+//! `PROPTEST_CASES` to try more of them locally.
+
+use actix_web::test::{init_service, read_body, TestRequest};
+use actix_web::App;
+use astrolog_rs::api::server::config;
+use astrolog_rs::calc::swiss_ephemeris;
+use astrolog_rs::calc::utils::{date_to_julian, julian_to_calendar, julian_to_date};
+use chrono::DateTime;
+use proptest::prelude::*;
+use proptest::test_runner::{Config, RngSeed};
+use serde_json::{json, Value};
+
+/// Seed of every run, so a failure reproduces
+const SEED: u64 = 0x6173_7472_6f6c_6f67;
+
+fn runner_config(cases: u32) -> Config {
+    Config {
+        cases: std::env::var("PROPTEST_CASES").ok().and_then(|n| n.parse().ok()).unwrap_or(cases),
+        rng_seed: RngSeed::Fixed(SEED),
+        failure_persistence: None,
+        ..Config::default()
+    }
+}
+
+/// Coordinates from the edges of what JSON and f64 can hold
+fn coordinate() -> impl Strategy<Value = f64> {
+    prop_oneof![
+        12 => -200.0..200.0f64,
+        1 => Just(0.0),
+        1 => Just(-0.0),
+        1 => Just(90.0),
+        1 => Just(-90.0),
+        1 => Just(89.95),
+        1 => Just(f64::MAX),
+        1 => Just(f64::MIN),
+        1 => Just(f64::MIN_POSITIVE),
+        1 => Just(5e-324),
+        1 => Just(f64::EPSILON),
+        1 => Just(1e300),
+    ]
+}
+
+/// Dates well formed or not, from any year chrono can hold and beyond
+fn date() -> impl Strategy<Value = String> {
+    prop_oneof![
+        12 => (1800i32..2200, 1u32..13, 0u32..86400).prop_map(|(year, month, second)| {
+            format!("{}-{:02}-15T{:02}:{:02}:{:02}Z", year, month, second / 3600, second / 60 % 60, second % 60)
+        }),
+        4 => (-300_000i64..300_000).prop_map(|year| format!("{:+07}-06-15T12:00:00Z", year)),
+        1 => any::<i64>().prop_map(|seconds| format!("{}", seconds)),
+        1 => Just("2024-02-30T00:00:00Z".to_string()),
+        1 => Just("2024-01-01T24:00:00+25:00".to_string()),
+        1 => Just(String::new()),
+        1 => "\\PC{0,24}",
+    ]
+}
+
+/// Names a parser might be handed: real ones, near misses and junk
+fn name(real: &'static [&'static str]) -> impl Strategy<Value = String> {
+    prop_oneof![
+        24 => proptest::sample::select(real).prop_map(str::to_string),
+        2 => proptest::sample::select(real).prop_map(|name| name.to_uppercase()),
+        1 => Just("\0".to_string()),
+        1 => Just("a\0b".to_string()),
+        1 => "\\PC{0,12}",
+        1 => "[a-z_ -]{0,20}",
+    ]
+}
+
+const HOUSE_SYSTEMS: &[&str] = &["placidus", "koch", "equal", "whole_sign", "P", "W", "gauquelin", "null", "porphyry"];
+const EXTRA_BODIES: &[&str] = &["chiron", "true_node", "mean_node"];
+const NODE_TYPES: &[&str] = &["true", "mean"];
+const SVG_DETAILS: &[&str] = &["full", "compact"];
+const LANGS: &[&str] = &["en", "es", "de", "fr", "pt"];
+const EPHEMERIDES: &[&str] = &["auto", "swiss", "moshier", "analytic"];
+
+fn chart_request() -> impl Strategy<Value = Value> {
+    (
+        (date(), coordinate(), coordinate(), name(HOUSE_SYSTEMS), name(&["tropical", "lahiri"])),
+        (
+            proptest::option::weighted(0.2, name(EPHEMERIDES)),
+            proptest::option::weighted(0.2, name(NODE_TYPES)),
+            proptest::option::weighted(0.2, name(SVG_DETAILS)),
+            proptest::option::weighted(0.2, name(LANGS)),
+            proptest::option::weighted(0.2, name(&["text", "paths"])),
+        ),
+        (
+            proptest::collection::vec(name(EXTRA_BODIES), 0..3),
+            proptest::collection::vec(name(&["sun", "moon"]), 0..2),
+            proptest::collection::vec(name(&["vertex", "anti_vertex", "east_point"]), 0..3),
+            proptest::collection::vec((name(&["Decile"]), 1.0..180.0f64, 0.5..10.0f64), 0..2),
+            proptest::option::weighted(0.2, name(&["arabic", "nakshatra"])),
+        ),
+        (proptest::option::weighted(0.3, (date(), coordinate(), coordinate())), any::<bool>(), any::<bool>()),
+    )
+        .prop_map(|(moment, options, lists, extras)| {
+            let (date, latitude, longitude, house_system, ayanamsa) = moment;
+            let (ephemeris, node_type, svg_detail, lang, glyph_mode) = options;
+            let (extra_bodies, required_bodies, aspect_points, custom_aspects, mansion_system) = lists;
+            let (transit, include_exact_times, include_decans) = extras;
+            json!({
+                "date": date,
+                "latitude": latitude,
+                "longitude": longitude,
+                "house_system": house_system,
+                "ayanamsa": ayanamsa,
+                "ephemeris": ephemeris,
+                "node_type": node_type,
+                "svg_detail": svg_detail,
+                "lang": lang,
+                "glyph_mode": glyph_mode,
+                "extra_bodies": extra_bodies,
+                "required_bodies": required_bodies,
+                "aspect_points": aspect_points,
+                "custom_aspects": custom_aspects
+                    .into_iter()
+                    .map(|(name, angle, orb)| json!({ "name": name, "angle": angle, "orb": orb }))
+                    .collect::<Vec<_>>(),
+                "mansion_system": mansion_system,
+                "transit": transit.map(|(date, latitude, longitude)| json!({
+                    "date": date,
+                    "latitude": latitude,
+                    "longitude": longitude,
+                })),
+                "include_exact_times": include_exact_times,
+                "include_decans": include_decans,
+            })
+        })
+}
+
+/// Sends a body and checks the answer is a success with numeric positions, or
+/// an error that says what went wrong
+fn check_answer(uri: &str, body: String) -> Result<(), TestCaseError> {
+    let (status, text) = actix_web::rt::System::new().block_on(async {
+        let app = init_service(App::new().configure(config)).await;
+        let resp = TestRequest::post()
+            .uri(uri)
+            .insert_header(("content-type", "application/json"))
+            .set_payload(body.clone())
+            .send_request(&app)
+            .await;
+        let status = resp.status();
+        (status, String::from_utf8_lossy(&read_body(resp).await).to_string())
+    });
+    prop_assert!(!text.contains("panicked"), "{} panicked on {}", uri, body);
+    if status.is_success() {
+        let response: Value = serde_json::from_str(&text).map_err(|e| TestCaseError::fail(e.to_string()))?;
+        for planet in response["planets"].as_array().into_iter().flatten() {
+            prop_assert!(planet["longitude"].is_f64(), "{} gave {} for {}", uri, planet, body);
+            prop_assert!(planet["speed"].is_f64(), "{} gave {} for {}", uri, planet, body);
+        }
+    } else {
+        prop_assert!(status.is_client_error() || status.is_server_error(), "{} for {}", status, body);
+        prop_assert!(!text.trim().is_empty(), "{} with no message for {}", status, body);
+    }
+    Ok(())
+}
+
+proptest! {
+    #![proptest_config(runner_config(48))]
+
+    #[test]
+    fn chart_requests_never_panic(request in chart_request()) {
+        swiss_ephemeris::init_moshier_ephemeris().unwrap();
+        check_answer("/api/chart", request.to_string())?;
+        check_answer("/api/chart/natal", request.to_string())?;
+    }
+
+    #[test]
+    fn raw_bodies_are_refused_cleanly(latitude in prop_oneof![
+        Just("NaN"), Just("Infinity"), Just("-inf"), Just("1e400"), Just("-1e400"), Just("0x10"), Just("\"12\""),
+    ]) {
+        let body = format!(
+            r#"{{"date":"2000-01-01T00:00:00Z","latitude":{},"longitude":0,"house_system":"equal","ayanamsa":"tropical"}}"#,
+            latitude
+        );
+        check_answer("/api/chart/natal", body)?;
+    }
+}
+
+proptest! {
+    #![proptest_config(runner_config(2048))]
+
+    #[test]
+    fn julian_dates_are_finite(seconds in DateTime::<chrono::Utc>::MIN_UTC.timestamp()..=DateTime::<chrono::Utc>::MAX_UTC.timestamp()) {
+        let datetime = DateTime::from_timestamp(seconds, 0).unwrap();
+        let jd = date_to_julian(datetime);
+        prop_assert!(jd.is_finite());
+        prop_assert_eq!(julian_to_date(jd), Some(datetime));
+        let (_, month, day, hour) = julian_to_calendar(jd);
+        prop_assert!((1..=12).contains(&month) && (1..=31).contains(&day) && (0.0..24.0).contains(&hour));
+    }
+
+    #[test]
+    fn julian_dates_outside_chrono_have_no_date(jd in prop_oneof![
+        Just(f64::NAN), Just(f64::INFINITY), Just(f64::NEG_INFINITY), Just(f64::MAX), Just(f64::MIN), 1e12..1e300f64,
+    ]) {
+        prop_assert_eq!(julian_to_date(jd), None);
+    }
+}
@@ -0,0 +1,65 @@
+use astrolog_rs::api::types::{ChartResponse, SynastryResponse};
+use astrolog_rs::charts;
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+// Counts every allocation in this process; keep this file to a single test so
+// nothing else allocates while it measures
+struct CountingAllocator;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+/// Allocations for these charts when they were built as `svg::Document` trees
+/// and then printed
+const NATAL_ALLOCATIONS_WITH_DOCUMENT: usize = 4359;
+const SYNASTRY_ALLOCATIONS_WITH_DOCUMENT: usize = 4923;
+
+fn count<T>(render: impl Fn() -> T) -> usize {
+    // Warm up so the cached wheel isn't counted
+    std::hint::black_box(render());
+    let before = ALLOCATIONS.load(Ordering::Relaxed);
+    std::hint::black_box(render());
+    ALLOCATIONS.load(Ordering::Relaxed) - before
+}
+
+#[test]
+fn test_svg_rendering_allocates_less() {
+    charts::init_styles().unwrap();
+    let natal: ChartResponse = serde_json::from_str(include_str!("golden/svg/natal_transit.json")).unwrap();
+    let synastry: SynastryResponse = serde_json::from_str(include_str!("golden/svg/synastry.json")).unwrap();
+
+    let natal_allocations = count(|| charts::generate_natal_svg(&natal).unwrap());
+    let synastry_allocations = count(|| charts::generate_synastry_svg(&synastry).unwrap());
+    println!("natal chart with transits: {} allocations", natal_allocations);
+    println!("synastry chart: {} allocations", synastry_allocations);
+    for (allocations, before) in [
+        (natal_allocations, NATAL_ALLOCATIONS_WITH_DOCUMENT),
+        (synastry_allocations, SYNASTRY_ALLOCATIONS_WITH_DOCUMENT),
+    ] {
+        assert!(
+            allocations * 5 <= before,
+            "expected at most a fifth of {} allocations, got {}",
+            before,
+            allocations
+        );
+    }
+}
@@ -0,0 +1,61 @@
+//! Charts drawn from fixed chart data, compared byte for byte with the SVGs in
+//! `tests/golden/svg`. Those were first written by the renderer that built an
+//! `svg::Document` tree, so the string writer prints exactly what it did.
+//!
+//! When a change to the drawing is meant to change the charts, run the test
+//! with `UPDATE_GOLDEN=1` to rewrite the SVGs, then review their diff.
+
+use astrolog_rs::api::types::{ChartResponse, SynastryResponse, TransitResponse};
+use astrolog_rs::charts::{self, GlyphMode, Lang, SvgDetail, SvgOptions, WheelStyle};
+
+fn fixture<T: serde::de::DeserializeOwned>(json: &str) -> T {
+    serde_json::from_str(json).expect("fixture must parse")
+}
+
+fn compact() -> SvgOptions {
+    SvgOptions {
+        glyph_mode: GlyphMode::Paths,
+        lang: Lang::De,
+        detail: SvgDetail::Compact,
+        show_second_houses: true,
+        ..SvgOptions::default()
+    }
+}
+
+#[test]
+fn test_charts_match_golden_svgs() {
+    charts::init_styles().unwrap();
+    let natal: ChartResponse = fixture(include_str!("golden/svg/natal.json"));
+    let natal_transit: ChartResponse = fixture(include_str!("golden/svg/natal_transit.json"));
+    let transit: TransitResponse = fixture(include_str!("golden/svg/transit.json"));
+    let synastry: SynastryResponse = fixture(include_str!("golden/svg/synastry.json"));
+
+    // Each twice, so both a freshly drawn and a cached wheel are checked
+    for _ in 0..2 {
+        for (name, svg, golden) in [
+            ("natal", charts::generate_natal_svg(&natal), include_str!("golden/svg/natal.svg")),
+            ("natal_compact", charts::generate_natal_svg_with_options(&natal, compact()), include_str!("golden/svg/natal_compact.svg")),
+            (
+                "natal_whole_sign",
+                charts::generate_natal_svg_with_options(&natal, SvgOptions { wheel_style: WheelStyle::WholeSign, ..SvgOptions::default() }),
+                include_str!("golden/svg/natal_whole_sign.svg"),
+            ),
+            ("natal_transit", charts::generate_natal_svg(&natal_transit), include_str!("golden/svg/natal_transit.svg")),
+            ("transit", charts::generate_transit_svg(&transit), include_str!("golden/svg/transit.svg")),
+            ("synastry", charts::generate_synastry_svg(&synastry), include_str!("golden/svg/synastry.svg")),
+            (
+                "synastry_compact",
+                charts::generate_synastry_svg_with_options(&synastry, compact()),
+                include_str!("golden/svg/synastry_compact.svg"),
+            ),
+            ("thumbnail", charts::generate_thumbnail_svg(&natal, charts::THUMBNAIL_SIZE), include_str!("golden/svg/thumbnail.svg")),
+        ] {
+            if std::env::var_os("UPDATE_GOLDEN").is_some() {
+                let path = format!("{}/tests/golden/svg/{}.svg", env!("CARGO_MANIFEST_DIR"), name);
+                std::fs::write(path, svg.unwrap()).unwrap();
+                continue;
+            }
+            assert!(svg.as_deref() == Ok(golden), "{} differs from tests/golden/svg/{}.svg", name, name);
+        }
+    }
+}
@@ -0,0 +1,57 @@
+use actix_web::{test, App};
+use astrolog_rs::api::server::config;
+use astrolog_rs::calc::swiss_ephemeris;
+use astrolog_rs::core::types::EphemerisSource;
+use serde_json::json;
+
+// Runs in its own process, so it can initialize against the ephemeris files
+fn ensure_swiss_files_initialized() -> bool {
+    let ephe = concat!(env!("CARGO_MANIFEST_DIR"), "/ephe");
+    swiss_ephemeris::init_swiss_ephemeris_with(ephe, EphemerisSource::Auto).expect("Swiss Ephemeris must initialize");
+    if !swiss_ephemeris::ephemeris_files_available() {
+        eprintln!("No Swiss Ephemeris files in {}, skipping", ephe);
+        return false;
+    }
+    true
+}
+
+#[actix_web::test]
+async fn test_asteroids_from_the_swiss_files() {
+    if !ensure_swiss_files_initialized() {
+        return;
+    }
+    let app = test::init_service(App::new().configure(config)).await;
+
+    let request = json!({
+        "date": "2000-01-01T12:00:00Z",
+        "latitude": 51.4769,
+        "longitude": -0.0005,
+        "house_system": "placidus",
+        "ayanamsa": "tropical",
+        "ephemeris": "swiss",
+        "include_asteroids": true
+    });
+    let resp = test::TestRequest::post().uri("/api/chart").set_json(&request).send_request(&app).await;
+    assert_eq!(resp.status(), 200);
+    let response: serde_json::Value = serde_json::from_slice(&test::read_body(resp).await).unwrap();
+    assert_eq!(response["ephemeris"], "swiss");
+    assert!(response["warnings"].as_array().is_none_or(|warnings| warnings.is_empty()));
+
+    let longitude = |name: &str| {
+        response["planets"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|planet| planet["name"] == name)
+            .and_then(|planet| planet["longitude"].as_f64())
+            .unwrap_or_else(|| panic!("no {} in the chart", name))
+    };
+    // At J2000, from seas_18.se1: Ceres early in Libra, Vesta in Sagittarius
+    let ceres = longitude("Ceres");
+    assert!((ceres - 184.453).abs() < 0.001, "Ceres at {}", ceres);
+    let vesta = longitude("Vesta");
+    assert!((vesta - 245.972).abs() < 0.001, "Vesta at {}", vesta);
+    // And the planets from sepl_18.se1
+    let sun = longitude("Sun");
+    assert!((sun - 280.369).abs() < 0.001, "Sun at {}", sun);
+}
@@ -0,0 +1,54 @@
+//! Runs in a JavaScript engine rather than natively:
+//! `wasm-pack test --node -- --no-default-features --features wasm --test wasm_tests`
+#![cfg(target_arch = "wasm32")]
+
+use astrolog_rs::wasm::{compute_natal_chart, render_svg};
+use wasm_bindgen_test::*;
+
+/// The analytic theory keeps the Sun within half a degree of the Swiss Ephemeris
+const SUN_TOLERANCE: f64 = 0.5;
+
+#[wasm_bindgen_test]
+fn test_natal_chart_1977() {
+    let request = r#"{
+        "date": "1977-10-24T04:56:00Z",
+        "latitude": 14.6486,
+        "longitude": 121.0508,
+        "house_system": "wholesign",
+        "ayanamsa": "tropical"
+    }"#;
+    let Ok(json) = compute_natal_chart(request) else {
+        panic!("compute_natal_chart failed");
+    };
+    let chart: serde_json::Value = serde_json::from_str(&json).unwrap();
+
+    let sun = &chart["planets"][0];
+    assert_eq!(sun["name"], "Sun");
+    assert!((sun["longitude"].as_f64().unwrap() - 210.674).abs() < SUN_TOLERANCE);
+    assert_eq!(chart["houses"].as_array().unwrap().len(), 12);
+    assert_eq!(chart["capabilities"]["swiss_ephemeris"], false);
+    assert_eq!(chart["capabilities"]["ephemeris"], "analytic");
+
+    let Ok(svg) = render_svg(&json) else {
+        panic!("render_svg failed");
+    };
+    assert!(svg.contains("<svg"));
+}
+
+#[wasm_bindgen_test]
+fn test_pluto_warning_outside_its_theory() {
+    let chart = |date: &str| -> serde_json::Value {
+        let request = format!(
+            r#"{{"date": "{}", "latitude": 51.5, "longitude": 0.0, "house_system": "equal", "ayanamsa": "tropical"}}"#,
+            date
+        );
+        let Ok(json) = compute_natal_chart(&request) else {
+            panic!("compute_natal_chart failed");
+        };
+        serde_json::from_str(&json).unwrap()
+    };
+
+    assert!(chart("1977-10-24T04:56:00Z")["meta"].get("warnings").is_none());
+    let warnings = chart("1850-06-01T12:00:00Z")["meta"]["warnings"].clone();
+    assert!(warnings[0].as_str().unwrap().contains("Pluto"));
+}
@@ -0,0 +1,156 @@
+//! Points either side of 0° Aries, at 29°30' Pisces (359.5°) and 0°30' Aries
+//! (0.5°), through the aspect, house, midpoint and SVG calculations. Each must
+//! take them to be 1° apart, not 359°.
+
+use astrolog_rs::api::types::ChartResponse;
+use astrolog_rs::calc::angles::{midpoint_shortest, proximity_groups};
+use astrolog_rs::calc::aspects::{
+    aspect_defs, calculate_aspect, calculate_aspect_time, calculate_all_aspects, calculate_point_aspects_with_defs,
+    synastry_aspects_between, AspectType, ChartPoint, OrbConfig,
+};
+use astrolog_rs::calc::comparison::{composite_cusps, composite_midpoints, house_overlays, CompositeHouses};
+use astrolog_rs::calc::houses::{calculate_house_placements, house_of, porphyry_from_angles};
+use astrolog_rs::calc::planets::PlanetPosition;
+use astrolog_rs::charts;
+
+const PISCES: f64 = 359.5;
+const ARIES: f64 = 0.5;
+
+#[test]
+fn test_conjunction_across_zero_aries() {
+    for (pos1, pos2) in [(PISCES, ARIES), (ARIES, PISCES)] {
+        let config =
+            calculate_aspect(pos1, 0.0, pos2, 0.0, AspectType::Conjunction, 1.0).expect("1° apart is within a 1° orb");
+        assert!((config.orb - 1.0).abs() < 1e-9, "{}", config.orb);
+        assert!(calculate_aspect(pos1, 0.0, pos2, 0.0, AspectType::Opposition, 10.0).is_none());
+    }
+    // Applying the same way as the same pair clear of the wrap
+    for (vel1, vel2) in [(1.0, 0.0), (0.0, 1.0)] {
+        assert_eq!(
+            calculate_aspect(ARIES, vel1, PISCES, vel2, AspectType::Conjunction, 1.0).unwrap().applying,
+            calculate_aspect(10.5, vel1, 9.5, vel2, AspectType::Conjunction, 1.0).unwrap().applying
+        );
+    }
+    assert!(calculate_aspect(PISCES, 1.0, ARIES, 0.0, AspectType::Conjunction, 1.0).unwrap().applying);
+    assert!(!calculate_aspect(ARIES, 1.0, PISCES, 0.0, AspectType::Conjunction, 1.0).unwrap().applying);
+
+    let positions = [PISCES, 90.0, ARIES].map(|longitude| PlanetPosition::new(longitude, 0.0, 0.0, false));
+    let aspects = calculate_all_aspects(&positions, &OrbConfig::uniform(1.0), &[AspectType::Conjunction]);
+    assert_eq!(aspects.len(), 1);
+    assert_eq!((aspects[0].index1, aspects[0].index2), (0, 2));
+    assert!((aspects[0].angle - 1.0).abs() < 1e-9);
+
+    let points = [ChartPoint::new("Moon", PISCES, 1.0), ChartPoint::new("Mars", ARIES, 1.0)];
+    let aspects = calculate_point_aspects_with_defs(&points[..1], &points[1..], &aspect_defs(false, false, &[]));
+    assert_eq!(aspects.len(), 1);
+    assert_eq!(aspects[0].aspect_type, Some(AspectType::Conjunction));
+    assert!((aspects[0].orb - 1.0).abs() < 1e-9);
+
+    // A day apart at 1° a day, whichever side is moving
+    let time = calculate_aspect_time(PISCES, 1.0, ARIES, 0.0, AspectType::Conjunction).unwrap();
+    assert!((time - 1.0).abs() < 1e-9, "{}", time);
+    let time = calculate_aspect_time(ARIES, 0.0, PISCES, 1.0, AspectType::Conjunction).unwrap();
+    assert!((time - 1.0).abs() < 1e-9, "{}", time);
+    assert!(calculate_aspect_time(ARIES, 1.0, PISCES, 0.0, AspectType::Conjunction).is_none());
+    // A square perfected after crossing 0°
+    let time = calculate_aspect_time(355.0, 1.0, 275.0, 0.0, AspectType::Square).unwrap();
+    assert!((time - 10.0).abs() < 1e-9, "{}", time);
+}
+
+#[test]
+fn test_synastry_across_zero_aries() {
+    let person1 = [ChartPoint::new("Sun", PISCES, 1.0), ChartPoint::new("Venus", 200.0, 1.0)];
+    let person2 = [ChartPoint::new("Moon", ARIES, 1.0), ChartPoint::new("Mars", 180.5, 1.0)];
+    let aspects = synastry_aspects_between(&person1, &person2, false);
+    let found: Vec<(&str, &str, Option<AspectType>)> = aspects
+        .iter()
+        .map(|aspect| (aspect.person1_planet.as_str(), aspect.person2_planet.as_str(), aspect.aspect_type))
+        .collect();
+    assert!(found.contains(&("Sun", "Moon", Some(AspectType::Conjunction))), "{:?}", found);
+    // 359.5° and 180.5° are opposite with 1° to spare
+    assert!(found.contains(&("Sun", "Mars", Some(AspectType::Opposition))), "{:?}", found);
+    for aspect in aspects.iter().filter(|aspect| aspect.person1_planet == "Sun") {
+        assert!((aspect.orb - 1.0).abs() < 1e-9, "{:?}", aspect);
+    }
+}
+
+#[test]
+fn test_houses_straddling_zero_aries() {
+    // The first house runs from 350° to 20°
+    let cusps = [350.0, 20.0, 50.0, 80.0, 110.0, 140.0, 170.0, 200.0, 230.0, 260.0, 290.0, 320.0];
+    let pisces = house_of(PISCES, &cusps);
+    let aries = house_of(ARIES, &cusps);
+    assert_eq!((pisces.house, aries.house), (1, 1));
+    assert!((aries.fraction - pisces.fraction - 1.0 / 30.0).abs() < 1e-9);
+    assert_eq!(house_of(-0.5, &cusps), pisces);
+    assert_eq!(house_of(360.5, &cusps), aries);
+
+    // A cusp on 0° Aries itself: the point before it is still in the twelfth
+    let cusps: [f64; 12] = std::array::from_fn(|i| i as f64 * 30.0);
+    assert_eq!(calculate_house_placements(&[PISCES, ARIES, 0.0], &cusps).unwrap(), vec![12, 1, 1]);
+
+    // Quadrant houses with the Ascendant in late Pisces and the twelfth house
+    // before it
+    let cusps = porphyry_from_angles(359.0, 270.0);
+    let points = [
+        ChartPoint::new("Moon", PISCES, 1.0),
+        ChartPoint::new("Mars", ARIES, 1.0),
+        ChartPoint::new("Sun", 358.5, 1.0),
+    ];
+    let overlays = house_overlays(&points, &cusps);
+    let houses: Vec<u8> = overlays.iter().map(|overlay| overlay.placement.house).collect();
+    assert_eq!(houses, [1, 1, 12]);
+}
+
+#[test]
+fn test_midpoints_across_zero_aries() {
+    assert_eq!(midpoint_shortest(PISCES, ARIES), 0.0);
+    assert_eq!(midpoint_shortest(ARIES, PISCES), 0.0);
+    assert!((midpoint_shortest(358.0, 1.0) - 359.5).abs() < 1e-9);
+
+    let chart1 = [ChartPoint::new("Sun", PISCES, 1.0), ChartPoint::new("Moon", 10.0, 1.0)];
+    let chart2 = [ChartPoint::new("Sun", ARIES, 1.0), ChartPoint::new("Moon", 340.0, 1.0)];
+    let composite = composite_midpoints(&chart1, &chart2);
+    assert_eq!(composite[0].longitude, 0.0);
+    assert!((composite[1].longitude - 355.0).abs() < 1e-9);
+
+    let cusps1: [f64; 12] = std::array::from_fn(|i| (350.0 + i as f64 * 30.0) % 360.0);
+    let cusps2: [f64; 12] = std::array::from_fn(|i| (20.0 + i as f64 * 30.0) % 360.0);
+    let composite = composite_cusps(&cusps1, &cusps2, CompositeHouses::DerivePlacidus);
+    assert!((composite[0] - 5.0).abs() < 1e-9, "{:?}", composite);
+    assert!((composite[11] - 335.0).abs() < 1e-9, "{:?}", composite);
+}
+
+/// Where the planet glyph with the given id was drawn
+fn glyph_at(svg: &str, body: &str) -> (f64, f64) {
+    let start = svg.find(&format!("id=\"planet-natal-{}\"", body)).expect("planet glyph must be drawn");
+    let translate = &svg[start..][svg[start..].find("translate(").unwrap() + "translate(".len()..];
+    let mut coordinates = translate[..translate.find(')').unwrap()].split(' ').map(|c| c.parse::<f64>().unwrap());
+    (coordinates.next().unwrap(), coordinates.next().unwrap())
+}
+
+#[test]
+fn test_svg_clusters_glyphs_across_zero_aries() {
+    charts::init_styles().unwrap();
+    assert_eq!(proximity_groups(&[PISCES, 120.0, ARIES], 8.0), vec![vec![0, 2], vec![1]]);
+
+    let mut natal: ChartResponse = serde_json::from_str(include_str!("golden/svg/natal.json")).unwrap();
+    let set = |natal: &mut ChartResponse, name: &str, longitude: f64| {
+        natal.planets.iter_mut().find(|planet| planet.name == name).unwrap().longitude = longitude;
+    };
+    // The Moon alone on 0° Aries shows where the cluster should be drawn
+    set(&mut natal, "Moon", 0.0);
+    let alone = glyph_at(&charts::generate_natal_svg(&natal).unwrap(), "moon");
+
+    set(&mut natal, "Moon", PISCES);
+    set(&mut natal, "Mars", ARIES);
+    let svg = charts::generate_natal_svg(&natal).unwrap();
+    let distance = |(x1, y1): (f64, f64), (x2, y2): (f64, f64)| (x1 - x2).hypot(y1 - y2);
+    for body in ["moon", "mars"] {
+        let at = glyph_at(&svg, body);
+        assert!(distance(at, alone) < 25.0, "{} drawn at {:?}, 0° Aries at {:?}", body, at, alone);
+    }
+    // One group, stepped apart, rather than two glyphs drawn over each other
+    let apart = distance(glyph_at(&svg, "moon"), glyph_at(&svg, "mars"));
+    assert!((10.0..25.0).contains(&apart), "{}", apart);
+}